@@ -16,6 +16,12 @@ use spin::Once;
 use super::{id::Sid, BlockDevice};
 use crate::{prelude::*, BLOCK_SIZE, SECTOR_SIZE};
 
+/// The function invoked with the type and the number of sectors of a `Bio`
+/// when it is submitted.
+///
+/// The kernel registers it to account the block I/O to the submitting thread.
+pub static BIO_SUBMIT_ACCOUNT_FN: Once<Arc<dyn Fn(BioType, usize) + Sync + Send>> = Once::new();
+
 /// The unit for block I/O.
 ///
 /// Each `Bio` packs the following information:
@@ -104,6 +110,12 @@ impl Bio {
             return Err(e);
         }
 
+        if let Some(account_fn) = BIO_SUBMIT_ACCOUNT_FN.get() {
+            let sid_range = self.sid_range();
+            let nsectors = sid_range.end.to_raw() - sid_range.start.to_raw();
+            account_fn(self.type_(), nsectors as usize);
+        }
+
         Ok(BioWaiter {
             bios: vec![self.0.clone()],
         })
@@ -68,6 +68,10 @@ pub struct BlockDeviceMeta {
     pub max_nr_segments_per_bio: usize,
     /// The total number of sectors of the block device.
     pub nr_sectors: usize,
+    /// Whether the block device is read-only.
+    pub is_read_only: bool,
+    /// Whether the block device has removable media.
+    pub is_removable: bool,
     // Additional useful metadata can be added here in the future.
 }
 
@@ -161,6 +161,8 @@ impl<D: BlockSet + 'static> aster_block::BlockDevice for SwornDisk<D> {
         BlockDeviceMeta {
             max_nr_segments_per_bio: usize::MAX,
             nr_sectors: (BLOCK_SIZE / SECTOR_SIZE) * self.total_blocks(),
+            is_read_only: false,
+            is_removable: false,
         }
     }
 }
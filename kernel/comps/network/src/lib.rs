@@ -42,6 +42,8 @@ pub trait AnyNetworkDevice: Send + Sync + Any + Debug {
 
     fn mac_addr(&self) -> EthernetAddr;
     fn capabilities(&self) -> DeviceCapabilities;
+    /// Returns whether the link of the device is up.
+    fn is_link_up(&self) -> bool;
 
     // ================Device Operation===================
 
@@ -72,6 +72,11 @@ pub fn read() -> SystemTime {
     *READ_TIME.lock()
 }
 
+/// Writes the real time back to the RTC.
+pub fn write_real_time(time: SystemTime) {
+    RTC_DRIVER.get().unwrap().write_rtc(time);
+}
+
 fn update_time() {
     let mut lock = READ_TIME.lock();
     *lock = RTC_DRIVER.get().unwrap().read_rtc();
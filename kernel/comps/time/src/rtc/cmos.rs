@@ -14,6 +14,11 @@ fn get_cmos(reg: u8) -> u8 {
     CMOS_DATA.read()
 }
 
+fn set_cmos(reg: u8, value: u8) {
+    CMOS_ADDRESS.write(reg);
+    CMOS_DATA.write(value);
+}
+
 fn is_updating() -> bool {
     CMOS_ADDRESS.write(0x0A);
     CMOS_DATA.read() & 0x80 != 0
@@ -93,6 +98,58 @@ impl CmosData {
         self.year += self.century as u16 * 100;
     }
 
+    /// Converts binary values to BCD, which is the reverse of
+    /// [`Self::convert_bcd_to_binary`].
+    fn convert_binary_to_bcd(&mut self, register_b: u8) {
+        if register_b & 0x04 == 0 {
+            let to_bcd = |value: u8| ((value / 10) << 4) | (value % 10);
+            self.second = to_bcd(self.second);
+            self.minute = to_bcd(self.minute);
+            self.hour = to_bcd(self.hour & 0x7F) | (self.hour & 0x80);
+            self.day = to_bcd(self.day);
+            self.month = to_bcd(self.month);
+            self.year = to_bcd(self.year as u8) as u16;
+            self.century = to_bcd(self.century);
+        }
+    }
+
+    /// Converts 24 hour clock to 12 hour clock if the RTC uses the latter,
+    /// which is the reverse of [`Self::convert_12_hour_to_24_hour`].
+    fn convert_24_hour_to_12_hour(&mut self, register_b: u8) {
+        if (register_b & 0x02) == 0 {
+            let is_pm = self.hour >= 12;
+            self.hour %= 12;
+            if self.hour == 0 {
+                self.hour = 12;
+            }
+            if is_pm {
+                self.hour |= 0x80;
+            }
+        }
+    }
+
+    pub fn write_rtc(mut self, century_register: u8) {
+        let register_b: u8 = get_cmos(0x0B);
+
+        self.century = (self.year / 100) as u8;
+        self.year %= 100;
+        self.convert_24_hour_to_12_hour(register_b);
+        self.convert_binary_to_bcd(register_b);
+
+        // Inhibit the updates of the RTC while writing the registers.
+        set_cmos(0x0B, register_b | 0x80);
+        set_cmos(0x00, self.second);
+        set_cmos(0x02, self.minute);
+        set_cmos(0x04, self.hour);
+        set_cmos(0x07, self.day);
+        set_cmos(0x08, self.month);
+        set_cmos(0x09, self.year as u8);
+        if century_register != 0 {
+            set_cmos(century_register, self.century);
+        }
+        set_cmos(0x0B, register_b);
+    }
+
     pub fn read_rtc(century_register: u8) -> Self {
         let mut now = Self::from_rtc_raw(century_register);
         while let new = Self::from_rtc_raw(century_register) && now != new {
@@ -109,6 +166,20 @@ impl CmosData {
     }
 }
 
+impl From<SystemTime> for CmosData {
+    fn from(time: SystemTime) -> CmosData {
+        CmosData {
+            century: 0,
+            year: time.year,
+            month: time.month,
+            day: time.day,
+            hour: time.hour,
+            minute: time.minute,
+            second: time.second,
+        }
+    }
+}
+
 impl From<CmosData> for SystemTime {
     fn from(cmos: CmosData) -> SystemTime {
         SystemTime {
@@ -137,4 +208,8 @@ impl Driver for RtcCmos {
     fn read_rtc(&self) -> SystemTime {
         CmosData::read_rtc(self.century_register).into()
     }
+
+    fn write_rtc(&self, time: SystemTime) {
+        CmosData::from(time).write_rtc(self.century_register);
+    }
 }
@@ -1,12 +1,15 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use ostd::{arch::riscv::timer::GOLDFISH_IO_MEM, mm::VmIoOnce};
-use chrono::{DateTime, Datelike, Timelike};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike};
 
 use crate::{SystemTime, rtc::Driver};
 
 pub struct RtcGoldfish;
 
+const TIME_LOW: usize = 0;
+const TIME_HIGH: usize = 4;
+
 impl Driver for RtcGoldfish {
     fn try_new() -> Option<RtcGoldfish> {
         GOLDFISH_IO_MEM.get()?;
@@ -14,9 +17,6 @@ impl Driver for RtcGoldfish {
     }
 
     fn read_rtc(&self) -> SystemTime {
-        const TIME_LOW: usize = 0;
-        const TIME_HIGH: usize = 4;
-
         let io_mem = GOLDFISH_IO_MEM.get().unwrap();
 
         let mut last_time_high = io_mem.read_once(TIME_HIGH).unwrap();
@@ -43,4 +43,28 @@ impl Driver for RtcGoldfish {
             nanos: time.nanosecond() as u64,
         }
     }
+
+    fn write_rtc(&self, time: SystemTime) {
+        let Some(timestamp) =
+            NaiveDate::from_ymd_opt(time.year as i32, time.month as u32, time.day as u32)
+                .and_then(|date| {
+                    date.and_hms_nano_opt(
+                        time.hour as u32,
+                        time.minute as u32,
+                        time.second as u32,
+                        time.nanos as u32,
+                    )
+                })
+                .and_then(|time| time.and_utc().timestamp_nanos_opt())
+        else {
+            return;
+        };
+
+        // The time is latched when the low half is written.
+        let io_mem = GOLDFISH_IO_MEM.get().unwrap();
+        io_mem
+            .write_once(TIME_HIGH, &((timestamp as u64 >> 32) as u32))
+            .unwrap();
+        io_mem.write_once(TIME_LOW, &(timestamp as u32)).unwrap();
+    }
 }
@@ -14,6 +14,9 @@ pub trait Driver {
 
     /// Reads RTC.
     fn read_rtc(&self) -> SystemTime;
+
+    /// Writes RTC.
+    fn write_rtc(&self, time: SystemTime);
 }
 
 macro_rules! declare_rtc_drivers {
@@ -98,6 +98,9 @@ impl aster_block::BlockDevice for BlockDevice {
         BlockDeviceMeta {
             max_nr_segments_per_bio: self.queue.max_nr_segments_per_bio(),
             nr_sectors: self.device.config_manager.capacity_sectors(),
+            is_read_only: self.device.features.is_read_only,
+            // VirtIO block devices do not have removable media.
+            is_removable: false,
         }
     }
 }
@@ -119,6 +119,7 @@ pub struct VirtioBlockTopology {
 #[repr(C)]
 pub struct VirtioBlockFeature {
     support_flush: bool,
+    is_read_only: bool,
 }
 
 impl VirtioBlockConfig {
@@ -199,7 +200,12 @@ impl ConfigManager<VirtioBlockConfig> {
 
 impl VirtioBlockFeature {
     pub(self) fn new(transport: &dyn VirtioTransport) -> Self {
-        let support_flush = transport.read_device_features() & BlockFeatures::FLUSH.bits() == 1;
-        VirtioBlockFeature { support_flush }
+        let device_features = transport.read_device_features();
+        let support_flush = device_features & BlockFeatures::FLUSH.bits() == 1;
+        let is_read_only = device_features & BlockFeatures::RO.bits() != 0;
+        VirtioBlockFeature {
+            support_flush,
+            is_read_only,
+        }
     }
 }
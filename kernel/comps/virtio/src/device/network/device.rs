@@ -17,7 +17,10 @@ use ostd::{
     trap::TrapFrame,
 };
 
-use super::{config::VirtioNetConfig, header::VirtioNetHdr};
+use super::{
+    config::{Status, VirtioNetConfig},
+    header::VirtioNetHdr,
+};
 use crate::{
     device::{network::config::NetworkFeatures, VirtioDeviceError},
     queue::{QueueError, VirtQueue},
@@ -26,6 +29,7 @@ use crate::{
 
 pub struct NetworkDevice {
     config_manager: ConfigManager<VirtioNetConfig>,
+    features: NetworkFeatures,
     // For smoltcp use
     caps: DeviceCapabilities,
     mac_addr: EthernetAddr,
@@ -109,6 +113,7 @@ impl NetworkDevice {
 
         let mut device = Self {
             config_manager,
+            features,
             caps,
             mac_addr,
             send_queue,
@@ -323,6 +328,18 @@ impl AnyNetworkDevice for NetworkDevice {
         self.caps.clone()
     }
 
+    fn is_link_up(&self) -> bool {
+        // Without `VIRTIO_NET_F_STATUS`, the link is assumed to be always up.
+        if !self.features.contains(NetworkFeatures::VIRTIO_NET_F_STATUS) {
+            return true;
+        }
+
+        self.config_manager
+            .read_config()
+            .status
+            .contains(Status::VIRTIO_NET_S_LINK_UP)
+    }
+
     fn can_receive(&self) -> bool {
         self.recv_queue.can_pop()
     }
@@ -2,6 +2,7 @@
 
 use ostd::{
     cpu::{CpuExceptionInfo, RawGeneralRegs, UserContext},
+    user::UserContextApi,
     Pod,
 };
 
@@ -125,6 +126,86 @@ impl GpRegs {
     }
 }
 
+/// General-purpose registers in the layout of `struct user_regs_struct` in Linux.
+///
+/// This is the layout of the registers saved in the `NT_PRSTATUS` notes of core dumps.
+#[derive(Debug, Clone, Copy, Pod, Default)]
+#[repr(C)]
+pub struct UserRegs {
+    pub pc: usize,
+    pub ra: usize,
+    pub sp: usize,
+    pub gp: usize,
+    pub tp: usize,
+    pub t0: usize,
+    pub t1: usize,
+    pub t2: usize,
+    pub s0: usize,
+    pub s1: usize,
+    pub a0: usize,
+    pub a1: usize,
+    pub a2: usize,
+    pub a3: usize,
+    pub a4: usize,
+    pub a5: usize,
+    pub a6: usize,
+    pub a7: usize,
+    pub s2: usize,
+    pub s3: usize,
+    pub s4: usize,
+    pub s5: usize,
+    pub s6: usize,
+    pub s7: usize,
+    pub s8: usize,
+    pub s9: usize,
+    pub s10: usize,
+    pub s11: usize,
+    pub t3: usize,
+    pub t4: usize,
+    pub t5: usize,
+    pub t6: usize,
+}
+
+impl From<&UserContext> for UserRegs {
+    fn from(user_ctx: &UserContext) -> Self {
+        let regs = user_ctx.general_regs();
+        Self {
+            pc: user_ctx.instruction_pointer(),
+            ra: regs.ra,
+            sp: regs.sp,
+            gp: regs.gp,
+            tp: regs.tp,
+            t0: regs.t0,
+            t1: regs.t1,
+            t2: regs.t2,
+            s0: regs.s0,
+            s1: regs.s1,
+            a0: regs.a0,
+            a1: regs.a1,
+            a2: regs.a2,
+            a3: regs.a3,
+            a4: regs.a4,
+            a5: regs.a5,
+            a6: regs.a6,
+            a7: regs.a7,
+            s2: regs.s2,
+            s3: regs.s3,
+            s4: regs.s4,
+            s5: regs.s5,
+            s6: regs.s6,
+            s7: regs.s7,
+            s8: regs.s8,
+            s9: regs.s9,
+            s10: regs.s10,
+            s11: regs.s11,
+            t3: regs.t3,
+            t4: regs.t4,
+            t5: regs.t5,
+            t6: regs.t6,
+        }
+    }
+}
+
 impl TryFrom<&CpuExceptionInfo> for PageFaultInfo {
     // [`Err`] indicates that the [`CpuExceptionInfo`] is not a page fault,
     // with no additional error information.
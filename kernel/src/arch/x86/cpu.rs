@@ -111,6 +111,82 @@ impl GpRegs {
     }
 }
 
+/// General-purpose registers in the layout of `struct user_regs_struct` in Linux.
+///
+/// This is the layout of the registers saved in the `NT_PRSTATUS` notes of core dumps.
+#[derive(Debug, Clone, Copy, Pod, Default)]
+#[repr(C)]
+pub struct UserRegs {
+    pub r15: usize,
+    pub r14: usize,
+    pub r13: usize,
+    pub r12: usize,
+    pub rbp: usize,
+    pub rbx: usize,
+    pub r11: usize,
+    pub r10: usize,
+    pub r9: usize,
+    pub r8: usize,
+    pub rax: usize,
+    pub rcx: usize,
+    pub rdx: usize,
+    pub rsi: usize,
+    pub rdi: usize,
+    pub orig_rax: usize,
+    pub rip: usize,
+    pub cs: usize,
+    pub rflags: usize,
+    pub rsp: usize,
+    pub ss: usize,
+    pub fsbase: usize,
+    pub gsbase: usize,
+    pub ds: usize,
+    pub es: usize,
+    pub fs: usize,
+    pub gs: usize,
+}
+
+impl From<&UserContext> for UserRegs {
+    fn from(user_ctx: &UserContext) -> Self {
+        /// The selectors of the 64-bit user code and data segments in Linux.
+        const USER_CS: usize = 0x33;
+        const USER_DS: usize = 0x2b;
+
+        let regs = user_ctx.general_regs();
+        Self {
+            r15: regs.r15,
+            r14: regs.r14,
+            r13: regs.r13,
+            r12: regs.r12,
+            rbp: regs.rbp,
+            rbx: regs.rbx,
+            r11: regs.r11,
+            r10: regs.r10,
+            r9: regs.r9,
+            r8: regs.r8,
+            rax: regs.rax,
+            rcx: regs.rcx,
+            rdx: regs.rdx,
+            rsi: regs.rsi,
+            rdi: regs.rdi,
+            // We do not keep the original syscall number, so the registers are
+            // reported as if they were not saved in a syscall.
+            orig_rax: usize::MAX,
+            rip: regs.rip,
+            cs: USER_CS,
+            rflags: regs.rflags,
+            rsp: regs.rsp,
+            ss: USER_DS,
+            fsbase: regs.fsbase,
+            gsbase: regs.gsbase,
+            ds: 0,
+            es: 0,
+            fs: 0,
+            gs: 0,
+        }
+    }
+}
+
 impl TryFrom<&CpuExceptionInfo> for PageFaultInfo {
     // [`Err`] indicates that the [`CpuExceptionInfo`] is not a page fault,
     // with no additional error information.
@@ -4,7 +4,7 @@ use crate::{
     fs::{
         devpts::DevPts,
         fs_resolver::{FsPath, FsResolver},
-        path::Dentry,
+        path::{Dentry, PerMountFlags},
         utils::{Inode, InodeMode, InodeType},
     },
     prelude::*,
@@ -25,7 +25,7 @@ pub fn init() -> Result<()> {
     // Create the "pts" directory and mount devpts on it.
     let devpts_dentry =
         dev.new_fs_child("pts", InodeType::Dir, InodeMode::from_bits_truncate(0o755))?;
-    let devpts_mount_node = devpts_dentry.mount(DevPts::new())?;
+    let devpts_mount_node = devpts_dentry.mount(DevPts::new(), PerMountFlags::empty())?;
     let devpts = Dentry::new_fs_root(devpts_mount_node.clone());
 
     DEV_PTS.call_once(|| devpts);
@@ -220,7 +220,7 @@ impl FileIo for PtyMaster {
                     let file_table = thread_local.file_table().borrow();
                     let mut file_table_locked = file_table.write();
                     // TODO: deal with the O_CLOEXEC flag
-                    file_table_locked.insert(slave, FdFlags::empty())
+                    file_table_locked.insert(slave, FdFlags::empty())?
                 };
                 Ok(fd)
             }
@@ -3,7 +3,8 @@
 use crate::{
     fs::{
         fs_resolver::{FsPath, FsResolver},
-        ramfs::RamFS,
+        path::PerMountFlags,
+        ramfs::{RamFS, TmpfsMountOptions},
         utils::{InodeMode, InodeType},
     },
     prelude::*,
@@ -16,10 +17,13 @@ pub fn init() -> Result<()> {
         fs.lookup(&FsPath::try_from("/dev")?)?
     };
 
-    // Create the "shm" directory under "/dev" and mount a ramfs on it.
+    // Create the "shm" directory under "/dev" and mount a tmpfs on it.
     let shm_dentry =
         dev_dentry.new_fs_child("shm", InodeType::Dir, InodeMode::from_bits_truncate(0o1777))?;
-    shm_dentry.mount(RamFS::new())?;
-    log::debug!("Mount RamFS at \"/dev/shm\"");
+    shm_dentry.mount(
+        RamFS::new_tmpfs(&TmpfsMountOptions::default()),
+        PerMountFlags::empty(),
+    )?;
+    log::debug!("Mount tmpfs at \"/dev/shm\"");
     Ok(())
 }
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use super::{read_written_str, CgroupDirOps};
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcDir, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::{
+        cgroup::{Cgroup, Controllers},
+        process_table, Pid,
+    },
+};
+
+/// Represents the inode at `cgroup.controllers`.
+pub struct ControllersFileOps(Arc<Cgroup>);
+
+impl ControllersFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for ControllersFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.controllers().names()).into_bytes())
+    }
+}
+
+/// Represents the inode at `cgroup.subtree_control`.
+///
+/// Writing `+<controller>` enables a controller for the children, and writing
+/// `-<controller>` disables it. Multiple controllers can be written at once,
+/// separated by spaces.
+pub struct SubtreeControlFileOps {
+    cgroup: Arc<Cgroup>,
+    dir: Weak<dyn Inode>,
+}
+
+impl SubtreeControlFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self {
+            cgroup,
+            dir: parent.clone(),
+        })
+        .parent(parent)
+        .mode(InodeMode::from_bits_truncate(0o644))
+        .build()
+        .unwrap()
+    }
+}
+
+impl FileOps for SubtreeControlFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.cgroup.subtree_control().names()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (data, len) = read_written_str(reader)?;
+
+        let mut enable = Controllers::empty();
+        let mut disable = Controllers::empty();
+        for token in data.split_whitespace() {
+            let (controllers, name) = if let Some(name) = token.strip_prefix('+') {
+                (&mut enable, name)
+            } else if let Some(name) = token.strip_prefix('-') {
+                (&mut disable, name)
+            } else {
+                return_errno_with_message!(Errno::EINVAL, "the controller must start with + or -");
+            };
+            let Some(controller) = Controllers::from_name(name) else {
+                return_errno_with_message!(Errno::ENOENT, "the controller does not exist");
+            };
+            *controllers |= controller;
+        }
+        if enable.intersects(disable) {
+            return_errno_with_message!(Errno::EINVAL, "the controller is enabled and disabled");
+        }
+
+        self.cgroup.update_subtree_control(enable, disable)?;
+
+        // The interface files of the disabled controllers are gone in the
+        // children, but they may be cached in the directories.
+        if let Some(dir) = self.dir.upgrade() {
+            if let Some(dir) = dir.downcast_ref::<ProcDir<CgroupDirOps>>() {
+                dir.remove_children_files(disable);
+            }
+        }
+
+        Ok(len)
+    }
+}
+
+/// Represents the inode at `cgroup.procs`.
+///
+/// Reading it lists the PIDs of the processes in the cgroup. Writing a PID
+/// moves the process to the cgroup, where `0` means the current process.
+pub struct ProcsFileOps(Arc<Cgroup>);
+
+impl ProcsFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for ProcsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut data = String::new();
+        for pid in self.0.procs() {
+            data.push_str(&format!("{}\n", pid));
+        }
+        Ok(data.into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (data, len) = read_written_str(reader)?;
+
+        let pid = data
+            .trim()
+            .parse::<Pid>()
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the PID is invalid"))?;
+        let process = if pid == 0 {
+            current!()
+        } else {
+            process_table::get_process(pid)
+                .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?
+        };
+        if process.status().is_zombie() {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        }
+
+        self.0.attach(&process)?;
+
+        Ok(len)
+    }
+}
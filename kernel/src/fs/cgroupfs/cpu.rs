@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::time::Duration;
+
+use super::read_written_str;
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::cgroup::Cgroup,
+    sched::FairGroup,
+};
+
+/// The minimum quota in `cpu.max`.
+const MIN_QUOTA: Duration = Duration::from_millis(1);
+/// The minimum period in `cpu.max`.
+const MIN_PERIOD: Duration = Duration::from_millis(1);
+/// The maximum period in `cpu.max`.
+const MAX_PERIOD: Duration = Duration::from_secs(1);
+
+fn fair_group(cgroup: &Cgroup) -> &Arc<FairGroup> {
+    // The interface files of the `cpu` controller do not exist in the root
+    // cgroup, which is the only cgroup without a group in the FAIR class.
+    cgroup.fair_group().unwrap()
+}
+
+/// Represents the inode at `cpu.max`.
+///
+/// The content is the quota and the period in microseconds, where the quota
+/// is `max` if the cgroup is not limited.
+pub struct CpuMaxFileOps(Arc<Cgroup>);
+
+impl CpuMaxFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CpuMaxFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let (quota, period) = fair_group(&self.0).max();
+        let quota = match quota {
+            Some(quota) => quota.as_micros().to_string(),
+            None => String::from("max"),
+        };
+        Ok(format!("{} {}\n", quota, period.as_micros()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (data, len) = read_written_str(reader)?;
+        let parse_micros = |value: &str| {
+            value
+                .parse::<u64>()
+                .map(Duration::from_micros)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the time is invalid"))
+        };
+
+        let mut values = data.split_whitespace();
+        let quota = match values.next() {
+            Some("max") => None,
+            Some(quota) => Some(parse_micros(quota)?),
+            None => return_errno_with_message!(Errno::EINVAL, "the quota is missing"),
+        };
+        let period = match values.next() {
+            Some(period) => parse_micros(period)?,
+            None => fair_group(&self.0).max().1,
+        };
+        if values.next().is_some() {
+            return_errno_with_message!(Errno::EINVAL, "too many values");
+        }
+
+        if quota.is_some_and(|quota| quota < MIN_QUOTA) {
+            return_errno_with_message!(Errno::EINVAL, "the quota is too small");
+        }
+        if !(MIN_PERIOD..=MAX_PERIOD).contains(&period) {
+            return_errno_with_message!(Errno::EINVAL, "the period is out of range");
+        }
+        fair_group(&self.0).set_max(quota, period);
+
+        Ok(len)
+    }
+}
+
+/// Represents the inode at `cpu.weight`.
+pub struct CpuWeightFileOps(Arc<Cgroup>);
+
+impl CpuWeightFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CpuWeightFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", fair_group(&self.0).weight()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (data, len) = read_written_str(reader)?;
+
+        let weight = data
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .filter(|weight| (FairGroup::MIN_WEIGHT..=FairGroup::MAX_WEIGHT).contains(weight))
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the weight is invalid"))?;
+        fair_group(&self.0).set_weight(weight);
+
+        Ok(len)
+    }
+}
+
+/// Represents the inode at `cpu.stat`.
+pub struct CpuStatFileOps(Arc<Cgroup>);
+
+impl CpuStatFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CpuStatFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let fair_group = fair_group(&self.0);
+        Ok(format!(
+            "usage_usec {}\nnr_throttled {}\n",
+            fair_group.usage().as_micros(),
+            fair_group.nr_throttled()
+        )
+        .into_bytes())
+    }
+}
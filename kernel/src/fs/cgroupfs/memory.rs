@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use super::{format_limit, read_written_str};
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::cgroup::Cgroup,
+};
+
+/// Represents the inode at `memory.current`.
+pub struct MemoryCurrentFileOps(Arc<Cgroup>);
+
+impl MemoryCurrentFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for MemoryCurrentFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.memory_current()).into_bytes())
+    }
+}
+
+/// Represents the inode at `memory.max`.
+///
+/// The limit is in bytes, which can be suffixed with `K`, `M` or `G`, and is
+/// rounded down to the page size.
+pub struct MemoryMaxFileOps(Arc<Cgroup>);
+
+impl MemoryMaxFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for MemoryMaxFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", format_limit(self.0.memory_max())).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (data, len) = read_written_str(reader)?;
+
+        let max = parse_bytes(data.trim())
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the memory limit is invalid"))?;
+        self.0
+            .set_memory_max(max.map(|max| max / PAGE_SIZE as u64 * PAGE_SIZE as u64));
+
+        Ok(len)
+    }
+}
+
+/// Parses a number of bytes, which is either `max` or a number with an
+/// optional `K`, `M` or `G` suffix.
+fn parse_bytes(value: &str) -> Option<Option<u64>> {
+    if value == "max" {
+        return Some(None);
+    }
+
+    let (number, shift) = match value.as_bytes().last()? {
+        b'k' | b'K' => (&value[..value.len() - 1], 10),
+        b'm' | b'M' => (&value[..value.len() - 1], 20),
+        b'g' | b'G' => (&value[..value.len() - 1], 30),
+        _ => (value, 0),
+    };
+    let bytes = number.parse::<u64>().ok()?.checked_mul(1 << shift)?;
+
+    Some(Some(bytes))
+}
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The cgroup v2 file system.
+//!
+//! Each directory represents a cgroup in the hierarchy of
+//! [`Cgroup`]s. Creating or removing a directory creates or removes a
+//! cgroup. The interface files in a directory expose the membership of the
+//! processes and the limits of the controllers that are available in the
+//! cgroup.
+//!
+//! There is only one hierarchy, so the file system is a singleton that is
+//! shared by all the mounts.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/cgroup-v2.html>
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Once;
+
+use self::{
+    core_files::{ControllersFileOps, ProcsFileOps, SubtreeControlFileOps},
+    cpu::{CpuMaxFileOps, CpuStatFileOps, CpuWeightFileOps},
+    memory::{MemoryCurrentFileOps, MemoryMaxFileOps},
+    pids::{PidsCurrentFileOps, PidsMaxFileOps},
+};
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        utils::{DirEntryVecExt, FileSystem, FsFlags, Inode, InodeMode, SuperBlock, NAME_MAX},
+    },
+    prelude::*,
+    process::cgroup::{Cgroup, Controllers},
+};
+
+mod core_files;
+mod cpu;
+mod memory;
+mod pids;
+
+/// Magic number.
+const CGROUP2_SUPER_MAGIC: u64 = 0x63677270;
+/// Root Inode ID.
+const CGROUP_ROOT_INO: u64 = 1;
+/// Block size.
+const BLOCK_SIZE: usize = 4096;
+
+pub struct CgroupFS {
+    sb: SuperBlock,
+    root: Arc<dyn Inode>,
+    inode_allocator: AtomicU64,
+}
+
+impl CgroupFS {
+    /// Returns the cgroup file system.
+    pub fn singleton() -> &'static Arc<CgroupFS> {
+        static SINGLETON: Once<Arc<CgroupFS>> = Once::new();
+        SINGLETON.call_once(|| {
+            Arc::new_cyclic(|weak_fs| Self {
+                sb: SuperBlock::new(CGROUP2_SUPER_MAGIC, BLOCK_SIZE, NAME_MAX),
+                root: CgroupDirOps::new_root_inode(weak_fs.clone()),
+                inode_allocator: AtomicU64::new(CGROUP_ROOT_INO + 1),
+            })
+        })
+    }
+
+    pub(in crate::fs) fn alloc_id(&self) -> u64 {
+        self.inode_allocator.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+impl FileSystem for CgroupFS {
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn flags(&self) -> FsFlags {
+        FsFlags::empty()
+    }
+
+    fn name(&self) -> &'static str {
+        "cgroup2"
+    }
+}
+
+type NewInodeFn = fn(Arc<Cgroup>, Weak<dyn Inode>) -> Arc<dyn Inode>;
+
+/// The interface files, and the controllers that they belong to.
+///
+/// The files of the core (whose controller is `None`) exist in every cgroup,
+/// while the files of a controller only exist in the non-root cgroups where
+/// the controller is available.
+const INTERFACE_FILES: &[(&str, Option<Controllers>, NewInodeFn)] = &[
+    ("cgroup.controllers", None, ControllersFileOps::new_inode),
+    ("cgroup.procs", None, ProcsFileOps::new_inode),
+    (
+        "cgroup.subtree_control",
+        None,
+        SubtreeControlFileOps::new_inode,
+    ),
+    ("cpu.max", Some(Controllers::CPU), CpuMaxFileOps::new_inode),
+    (
+        "cpu.stat",
+        Some(Controllers::CPU),
+        CpuStatFileOps::new_inode,
+    ),
+    (
+        "cpu.weight",
+        Some(Controllers::CPU),
+        CpuWeightFileOps::new_inode,
+    ),
+    (
+        "memory.current",
+        Some(Controllers::MEMORY),
+        MemoryCurrentFileOps::new_inode,
+    ),
+    (
+        "memory.max",
+        Some(Controllers::MEMORY),
+        MemoryMaxFileOps::new_inode,
+    ),
+    (
+        "pids.current",
+        Some(Controllers::PIDS),
+        PidsCurrentFileOps::new_inode,
+    ),
+    (
+        "pids.max",
+        Some(Controllers::PIDS),
+        PidsMaxFileOps::new_inode,
+    ),
+];
+
+/// Represents the directory of a cgroup.
+pub struct CgroupDirOps(Arc<Cgroup>);
+
+impl CgroupDirOps {
+    fn new_root_inode(fs: Weak<CgroupFS>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(Cgroup::root().clone()))
+            .fs(fs)
+            .ino(CGROUP_ROOT_INO)
+            // The interface files come and go with the controllers.
+            .volatile()
+            .mode(InodeMode::from_bits_truncate(0o755))
+            .build()
+            .unwrap()
+    }
+
+    fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(cgroup))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o755))
+            .build()
+            .unwrap()
+    }
+
+    fn has_file(&self, controller: Option<Controllers>) -> bool {
+        controller
+            .is_none_or(|controller| !self.0.is_root() && self.0.controllers().contains(controller))
+    }
+}
+
+impl ProcDir<CgroupDirOps> {
+    /// Removes the cached interface files of the controllers from the
+    /// directories of the child cgroups.
+    fn remove_children_files(&self, controllers: Controllers) {
+        let cached_children = self.cached_children().read();
+        for (_, child) in cached_children.iter() {
+            let Some(child) = child.downcast_ref::<ProcDir<CgroupDirOps>>() else {
+                continue;
+            };
+            let mut child_children = child.cached_children().write();
+            for (name, controller, _) in INTERFACE_FILES {
+                if controller.is_some_and(|controller| controllers.contains(controller)) {
+                    child_children.remove_entry_by_name(name);
+                }
+            }
+        }
+    }
+}
+
+impl DirOps for CgroupDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        if let Some((_, controller, new_inode)) = INTERFACE_FILES
+            .iter()
+            .find(|(file_name, _, _)| *file_name == name)
+        {
+            if !self.has_file(*controller) {
+                return_errno!(Errno::ENOENT);
+            }
+            return Ok(new_inode(self.0.clone(), this_ptr));
+        }
+
+        let Some(child) = self.0.child(name) else {
+            return_errno!(Errno::ENOENT);
+        };
+        Ok(CgroupDirOps::new_inode(child, this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<CgroupDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+
+        for (name, controller, new_inode) in INTERFACE_FILES {
+            if self.has_file(*controller) {
+                cached_children
+                    .put_entry_if_not_found(name, || new_inode(self.0.clone(), this_ptr.clone()));
+            }
+        }
+        for child in self.0.children() {
+            cached_children.put_entry_if_not_found(child.name(), || {
+                CgroupDirOps::new_inode(child.clone(), this_ptr.clone())
+            });
+        }
+    }
+
+    fn create_dir(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let child = self.0.create_child(name)?;
+        Ok(CgroupDirOps::new_inode(child, this_ptr))
+    }
+
+    fn remove_dir(&self, name: &str) -> Result<()> {
+        self.0.remove_child(name)
+    }
+}
+
+/// Reads the string written to an interface file.
+fn read_written_str(reader: &mut VmReader) -> Result<(String, usize)> {
+    /// The maximum length of the written string.
+    const MAX_LEN: usize = 256;
+
+    let len = reader.remain();
+    if len > MAX_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the written string is too long");
+    }
+    let data = reader.collect()?;
+    let string = String::from_utf8(data)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the written string is not UTF-8"))?;
+
+    Ok((string, len))
+}
+
+/// Parses a limit, which is either `max` or a number.
+fn parse_limit(value: &str) -> Result<Option<u64>> {
+    if value == "max" {
+        return Ok(None);
+    }
+    value
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the limit is invalid"))
+}
+
+/// Formats a limit, which is either `max` or a number.
+fn format_limit(limit: Option<u64>) -> String {
+    match limit {
+        Some(limit) => limit.to_string(),
+        None => String::from("max"),
+    }
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use super::{format_limit, parse_limit, read_written_str};
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::cgroup::Cgroup,
+};
+
+/// Represents the inode at `pids.current`.
+pub struct PidsCurrentFileOps(Arc<Cgroup>);
+
+impl PidsCurrentFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for PidsCurrentFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.pids_current()).into_bytes())
+    }
+}
+
+/// Represents the inode at `pids.max`.
+pub struct PidsMaxFileOps(Arc<Cgroup>);
+
+impl PidsMaxFileOps {
+    pub fn new_inode(cgroup: Arc<Cgroup>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(cgroup))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for PidsMaxFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", format_limit(self.0.pids_max())).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (data, len) = read_written_str(reader)?;
+
+        let max = parse_limit(data.trim())?;
+        self.0.set_pids_max(max);
+
+        Ok(len)
+    }
+}
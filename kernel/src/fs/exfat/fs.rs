@@ -25,7 +25,10 @@ use super::{
 use crate::{
     fs::{
         exfat::{constants::*, inode::Ino},
-        utils::{CachePage, FileSystem, FsFlags, Inode, PageCache, PageCacheBackend, SuperBlock},
+        utils::{
+            CachePage, FileSystem, FsFlags, Inode, MountOption, PageCache, PageCacheBackend,
+            SuperBlock,
+        },
     },
     prelude::*,
 };
@@ -421,6 +424,10 @@ impl FileSystem for ExfatFS {
     fn flags(&self) -> FsFlags {
         FsFlags::DENTRY_UNEVICTABLE
     }
+
+    fn name(&self) -> &'static str {
+        "exfat"
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -449,3 +456,76 @@ pub struct ExfatMountOptions {
     pub(super) time_offset: i32,
     pub(super) zero_size_dir: bool,
 }
+
+impl ExfatMountOptions {
+    /// Parses the mount options from the `data` argument of `mount()`.
+    ///
+    /// The supported options are the same as the ones of Linux, e.g.,
+    /// `uid=1000,gid=1000,umask=022,errors=remount-ro`.
+    ///
+    /// See <https://docs.kernel.org/filesystems/exfat.html>.
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for option in MountOption::parse_all(data) {
+            match option.key() {
+                "uid" => options.fs_uid = option.parse_value()?,
+                "gid" => options.fs_gid = option.parse_value()?,
+                "umask" => {
+                    let umask = parse_mask(&option)?;
+                    options.fs_fmask = umask;
+                    options.fs_dmask = umask;
+                }
+                "fmask" => options.fs_fmask = parse_mask(&option)?,
+                "dmask" => options.fs_dmask = parse_mask(&option)?,
+                "allow_utime" => options.allow_utime = parse_mask(&option)?,
+                "iocharset" => options.iocharset = String::from(option.value()?),
+                "errors" => {
+                    options.errors = match option.value()? {
+                        "continue" => ExfatErrorMode::Continue,
+                        "panic" => ExfatErrorMode::Panic,
+                        "remount-ro" => ExfatErrorMode::ReadOnly,
+                        _ => return_errno_with_message!(Errno::EINVAL, "invalid exfat error mode"),
+                    }
+                }
+                "utf8" => {
+                    option.check_flag()?;
+                    options.utf8 = true;
+                }
+                "discard" => {
+                    option.check_flag()?;
+                    options.discard = true;
+                }
+                "keep_last_dots" => {
+                    option.check_flag()?;
+                    options.keep_last_dots = true;
+                }
+                "sys_tz" => {
+                    option.check_flag()?;
+                    options.sys_tz = true;
+                }
+                "zero_size_dir" => {
+                    option.check_flag()?;
+                    options.zero_size_dir = true;
+                }
+                "time_offset" => {
+                    let time_offset: i32 = option.parse_value()?;
+                    // The offset is in minutes and must be within a day.
+                    if !(-24 * 60..=24 * 60).contains(&time_offset) {
+                        return_errno_with_message!(Errno::EINVAL, "invalid exfat time offset");
+                    }
+                    options.time_offset = time_offset;
+                }
+                _ => return_errno_with_message!(Errno::EINVAL, "unknown exfat mount option"),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_mask(option: &MountOption) -> Result<u16> {
+    let mask = option.parse_octal_value()?;
+    if mask > 0o777 {
+        return_errno_with_message!(Errno::EINVAL, "invalid mask");
+    }
+    Ok(mask as u16)
+}
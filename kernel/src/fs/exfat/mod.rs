@@ -104,6 +104,8 @@ mod test {
             BlockDeviceMeta {
                 max_nr_segments_per_bio: usize::MAX,
                 nr_sectors: self.sectors_count(),
+                is_read_only: false,
+                is_removable: false,
             }
         }
     }
@@ -19,7 +19,7 @@ use crate::{
     prelude::*,
     process::{
         signal::{constants::SIGIO, signals::kernel::KernelSignal, PollAdaptor},
-        Pid, Process,
+        Pid, Process, ResourceType,
     },
 };
 
@@ -97,14 +97,22 @@ impl FileTable {
         };
 
         let min_free_fd = get_min_free_fd();
+        if min_free_fd >= max_fd_count() {
+            return_errno_with_message!(Errno::EMFILE, "the file descriptor exceeds RLIMIT_NOFILE");
+        }
         let entry = FileTableEntry::new(file, flags);
         self.table.put_at(min_free_fd, entry);
         Ok(min_free_fd as FileDesc)
     }
 
-    pub fn insert(&mut self, item: Arc<dyn FileLike>, flags: FdFlags) -> FileDesc {
+    pub fn insert(&mut self, item: Arc<dyn FileLike>, flags: FdFlags) -> Result<FileDesc> {
         let entry = FileTableEntry::new(item, flags);
-        self.table.put(entry) as FileDesc
+        let fd = self.table.put(entry);
+        if fd >= max_fd_count() {
+            self.table.remove(fd);
+            return_errno_with_message!(Errno::EMFILE, "the file descriptor exceeds RLIMIT_NOFILE");
+        }
+        Ok(fd as FileDesc)
     }
 
     pub fn insert_at(
@@ -238,6 +246,20 @@ impl Drop for FileTable {
     }
 }
 
+/// Returns the maximum number of file descriptors that the current process can
+/// open, i.e., the soft limit of `RLIMIT_NOFILE`.
+fn max_fd_count() -> usize {
+    let Some(process) = Process::current() else {
+        return usize::MAX;
+    };
+    let max_fd_count = process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_NOFILE)
+        .get_cur();
+    max_fd_count.try_into().unwrap_or(usize::MAX)
+}
+
 /// A helper trait that provides methods to operate the file table.
 pub trait WithFileTable {
     /// Calls `f` with the file table.
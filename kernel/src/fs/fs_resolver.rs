@@ -7,7 +7,7 @@ use ostd::task::Task;
 use super::{
     file_table::{get_file_fast, FileDesc},
     inode_handle::InodeHandle,
-    path::Dentry,
+    path::{Dentry, PerMountFlags},
     rootfs::root_mount,
     utils::{AccessMode, CreationFlags, InodeMode, InodeType, StatusFlags, PATH_MAX, SYMLINKS_MAX},
 };
@@ -112,6 +112,21 @@ impl FsResolver {
             );
         }
 
+        let flags = target_dentry.mount_node().flags();
+        if inode_type.is_device() && flags.contains(PerMountFlags::NODEV) {
+            return_errno_with_message!(Errno::EACCES, "the mount disallows device files");
+        }
+        // Device files, pipes and sockets can still be written on a read-only mount,
+        // since writing them does not modify the file system.
+        if (inode_type.is_regular_file()
+            || inode_type.is_directory()
+            || inode_type == InodeType::SymLink)
+            && (open_args.access_mode.is_writable()
+                || creation_flags.contains(CreationFlags::O_TRUNC))
+        {
+            target_dentry.check_writable_mount()?;
+        }
+
         if creation_flags.contains(CreationFlags::O_TRUNC) {
             target_dentry.resize(0)?;
         }
@@ -253,6 +268,16 @@ impl FsResolver {
                 if follows >= SYMLINKS_MAX {
                     return_errno_with_message!(Errno::ELOOP, "too many symlinks");
                 }
+                if next_dentry
+                    .mount_node()
+                    .flags()
+                    .contains(PerMountFlags::NOSYMFOLLOW)
+                {
+                    return_errno_with_message!(
+                        Errno::ELOOP,
+                        "the mount disallows following symlinks"
+                    );
+                }
                 let link_path_remain = {
                     let mut tmp_link_path = next_dentry.inode().read_link()?;
                     if tmp_link_path.is_empty() {
@@ -25,6 +25,7 @@ use crate::{
     },
     prelude::*,
     process::{
+        rlimit::{max_file_size, send_sigxfsz},
         signal::{PollHandle, Pollable},
         Gid, Uid,
     },
@@ -106,6 +107,22 @@ impl InodeHandle_ {
             offset = self.dentry.size();
         }
 
+        // Writes to regular files are restricted by `RLIMIT_FSIZE`. If only a
+        // part of the data can be written, the write is truncated to the limit.
+        if self.dentry.type_() == InodeType::File && reader.has_remain() {
+            let max_file_size = max_file_size();
+            if offset as u64 >= max_file_size {
+                send_sigxfsz();
+                return_errno_with_message!(Errno::EFBIG, "the file size exceeds RLIMIT_FSIZE");
+            }
+
+            let max_len = usize::try_from(max_file_size - offset as u64).unwrap_or(usize::MAX);
+            if max_len < reader.remain() {
+                let empty_reader = VmReader::from(&[] as &[u8]).to_fallible();
+                *reader = core::mem::replace(reader, empty_reader).limit(max_len);
+            }
+        }
+
         if status_flags.contains(StatusFlags::O_DIRECT) {
             self.dentry.inode().write_direct_at(offset, reader)
         } else {
@@ -1,4 +1,5 @@
 // SPDX-License-Identifier: MPL-2.0
+pub mod cgroupfs;
 pub mod device;
 pub mod devpts;
 pub mod epoll;
@@ -14,8 +15,10 @@ pub mod pipe;
 pub mod procfs;
 pub mod ramfs;
 pub mod rootfs;
+pub mod sysfs;
 pub mod thread_info;
 pub mod utils;
+pub mod vfat;
 
 use aster_block::BlockDevice;
 use aster_virtio::device::block::device::BlockDevice as VirtIoBlockDevice;
@@ -47,6 +50,8 @@ fn start_block_device(device_name: &str) -> Result<Arc<dyn BlockDevice>> {
 }
 
 pub fn lazy_init() {
+    self::utils::spawn_reclaimer_thread();
+
     //The device name is specified in qemu args as --serial={device_name}
     let ext2_device_name = "vext2";
     let exfat_device_name = "vexfat";
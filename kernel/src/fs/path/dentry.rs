@@ -14,7 +14,7 @@ use ostd::sync::RwMutexWriteGuard;
 
 use crate::{
     fs::{
-        path::mount::MountNode,
+        path::mount::{MountNode, PerMountFlags},
         utils::{
             FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType, Permission, NAME_MAX,
         },
@@ -463,6 +463,7 @@ impl Dentry {
 
     /// Creates a new `Dentry` to represent the child directory of a file system.
     pub fn new_fs_child(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Self> {
+        self.check_writable_mount()?;
         if self
             .inode()
             .check_permission(Permission::MAY_WRITE)
@@ -607,8 +608,11 @@ impl Dentry {
     /// its mounted child mount will be updated.
     /// The root Dentry cannot be mounted.
     ///
+    /// The child mount has the per-mount `flags`, which are also copied to
+    /// the mounts propagated to the peers and slaves.
+    ///
     /// Returns the mounted child mount.
-    pub fn mount(&self, fs: Arc<dyn FileSystem>) -> Result<Arc<MountNode>> {
+    pub fn mount(&self, fs: Arc<dyn FileSystem>, flags: PerMountFlags) -> Result<Arc<MountNode>> {
         if self.type_() != InodeType::Dir {
             return_errno!(Errno::ENOTDIR);
         }
@@ -616,8 +620,9 @@ impl Dentry {
             return_errno_with_message!(Errno::EINVAL, "can not mount on root");
         }
 
-        let child_mount = self.mount_node.mount(fs, &self.this())?;
+        let child_mount = self.mount_node.mount(fs, &self.this(), flags)?;
         self.set_mountpoint(child_mount.clone());
+        self.mount_node.propagate_mount(&child_mount);
         Ok(child_mount)
     }
 
@@ -643,6 +648,7 @@ impl Dentry {
 
     /// Creates a `Dentry` by making an inode of the `type_` with the `mode`.
     pub fn mknod(&self, name: &str, mode: InodeMode, type_: MknodType) -> Result<Self> {
+        self.check_writable_mount()?;
         let inner = self.inner.mknod(name, mode, type_)?;
         Ok(Self::new(self.mount_node.clone(), inner))
     }
@@ -652,16 +658,19 @@ impl Dentry {
         if !Arc::ptr_eq(&old.mount_node, &self.mount_node) {
             return_errno_with_message!(Errno::EXDEV, "cannot cross mount");
         }
+        self.check_writable_mount()?;
         self.inner.link(&old.inner, name)
     }
 
     /// Deletes a `Dentry`.
     pub fn unlink(&self, name: &str) -> Result<()> {
+        self.check_writable_mount()?;
         self.inner.unlink(name)
     }
 
     /// Deletes a directory `Dentry`.
     pub fn rmdir(&self, name: &str) -> Result<()> {
+        self.check_writable_mount()?;
         self.inner.rmdir(name)
     }
 
@@ -670,6 +679,7 @@ impl Dentry {
         if !Arc::ptr_eq(&self.mount_node, &new_dir.mount_node) {
             return_errno_with_message!(Errno::EXDEV, "cannot cross mount");
         }
+        self.check_writable_mount()?;
         self.inner.rename(old_name, &new_dir.inner, new_name)
     }
 
@@ -679,6 +689,9 @@ impl Dentry {
     /// to the destination `Dentry`. Otherwise, it will only bind mount
     /// the root mount node.
     pub fn bind_mount_to(&self, dst_dentry: &Self, recursive: bool) -> Result<()> {
+        if self.mount_node.is_unbindable() {
+            return_errno_with_message!(Errno::EINVAL, "the mount is unbindable");
+        }
         let src_mount = self
             .mount_node
             .clone_mount_node_tree(&self.inner, recursive);
@@ -694,6 +707,48 @@ impl Dentry {
     pub fn mount_node(&self) -> &Arc<MountNode> {
         &self.mount_node
     }
+
+    /// Returns whether the `Dentry` is the root of its mount node.
+    ///
+    /// Unlike [`Self::is_root_of_mount`], this also holds for the root of
+    /// a bind mount of a subdirectory.
+    pub fn is_mount_root(&self) -> bool {
+        Arc::ptr_eq(&self.inner, self.mount_node.root_dentry())
+    }
+
+    /// Checks whether the mount of the `Dentry` allows modifications.
+    ///
+    /// Returns `EROFS` if the mount is read-only.
+    pub fn check_writable_mount(&self) -> Result<()> {
+        if self.mount_node.flags().contains(PerMountFlags::RDONLY) {
+            return_errno_with_message!(Errno::EROFS, "the mount is read-only");
+        }
+        Ok(())
+    }
+
+    /// Sets the mode of the inode.
+    pub fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.check_writable_mount()?;
+        self.inner.set_mode(mode)
+    }
+
+    /// Resizes the inode.
+    pub fn resize(&self, size: usize) -> Result<()> {
+        self.check_writable_mount()?;
+        self.inner.resize(size)
+    }
+
+    /// Sets the owner of the inode.
+    pub fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.check_writable_mount()?;
+        self.inner.set_owner(uid)
+    }
+
+    /// Sets the group of the inode.
+    pub fn set_group(&self, gid: Gid) -> Result<()> {
+        self.check_writable_mount()?;
+        self.inner.set_group(gid)
+    }
 }
 
 #[inherit_methods(from = "self.inner")]
@@ -704,13 +759,9 @@ impl Dentry {
     pub fn metadata(&self) -> Metadata;
     pub fn type_(&self) -> InodeType;
     pub fn mode(&self) -> Result<InodeMode>;
-    pub fn set_mode(&self, mode: InodeMode) -> Result<()>;
     pub fn size(&self) -> usize;
-    pub fn resize(&self, size: usize) -> Result<()>;
     pub fn owner(&self) -> Result<Uid>;
-    pub fn set_owner(&self, uid: Uid) -> Result<()>;
     pub fn group(&self) -> Result<Gid>;
-    pub fn set_group(&self, gid: Gid) -> Result<()>;
     pub fn atime(&self) -> Duration;
     pub fn set_atime(&self, time: Duration);
     pub fn mtime(&self) -> Duration;
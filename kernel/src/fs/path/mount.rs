@@ -1,5 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use alloc::collections::BTreeSet;
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
 use hashbrown::HashMap;
 
 use crate::{
@@ -12,6 +15,8 @@ use crate::{
 
 /// The `MountNode` is used to form a mount tree to maintain the mount information.
 pub struct MountNode {
+    /// The unique ID of the mount node.
+    id: usize,
     /// Root dentry.
     root_dentry: Arc<Dentry_>,
     /// Mountpoint dentry. A mount node can be mounted on one dentry of another mount node,
@@ -23,10 +28,139 @@ pub struct MountNode {
     parent: RwLock<Option<Weak<MountNode>>>,
     /// Child mount nodes which are mounted on one dentry of self.
     children: RwLock<HashMap<DentryKey, Arc<Self>>>,
+    /// The per-mount flags.
+    flags: AtomicU32,
+    /// The propagation state, which decides how mount events propagate
+    /// between this mount node and others.
+    propagation: RwLock<PropagationState>,
     /// Reference to self.
     this: Weak<Self>,
 }
 
+/// The ID allocator of mount nodes.
+static MOUNT_ID_ALLOCATOR: AtomicUsize = AtomicUsize::new(1);
+
+/// The ID allocator of peer groups.
+static PEER_GROUP_ID_ALLOCATOR: AtomicUsize = AtomicUsize::new(1);
+
+bitflags! {
+    /// The flags that apply to a single mount rather than the whole file system.
+    ///
+    /// The values are the same as the corresponding `MS_*` flags of `mount()`.
+    pub struct PerMountFlags: u32 {
+        /// Mount read-only.
+        const RDONLY        = 1 << 0;
+        /// Ignore the set-user-ID and set-group-ID bits on execution.
+        const NOSUID        = 1 << 1;
+        /// Disallow access to device special files.
+        const NODEV         = 1 << 2;
+        /// Disallow program execution.
+        const NOEXEC        = 1 << 3;
+        /// Do not follow symbolic links.
+        const NOSYMFOLLOW   = 1 << 8;
+        /// Do not update access times.
+        const NOATIME       = 1 << 10;
+        /// Do not update directory access times.
+        const NODIRATIME    = 1 << 11;
+        /// Update access times relative to modification or change times.
+        const RELATIME      = 1 << 21;
+    }
+}
+
+impl PerMountFlags {
+    /// Returns the comma-separated options of the flags, e.g., `ro,nosuid`,
+    /// in the form shown in `/proc/[pid]/mountinfo`.
+    pub fn to_options_string(&self) -> String {
+        let access = if self.contains(Self::RDONLY) {
+            "ro"
+        } else {
+            "rw"
+        };
+        let mut options = vec![access];
+        let named_flags = [
+            (Self::NOSUID, "nosuid"),
+            (Self::NODEV, "nodev"),
+            (Self::NOEXEC, "noexec"),
+            (Self::NOSYMFOLLOW, "nosymfollow"),
+            (Self::NOATIME, "noatime"),
+            (Self::NODIRATIME, "nodiratime"),
+            (Self::RELATIME, "relatime"),
+        ];
+        for (flag, name) in named_flags {
+            if self.contains(flag) {
+                options.push(name);
+            }
+        }
+        options.join(",")
+    }
+}
+
+/// The propagation type of a mount.
+///
+/// See <https://docs.kernel.org/filesystems/sharedsubtree.html>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MountPropagation {
+    /// Mount events propagate to and from the peers of the mount.
+    Shared,
+    /// Mount events do not propagate to or from the mount.
+    Private,
+    /// Mount events propagate from the master of the mount, but not in the
+    /// reverse direction.
+    Slave,
+    /// The mount is private and cannot be bind mounted.
+    Unbindable,
+}
+
+/// A group of mount nodes that propagate mount events to each other.
+struct PeerGroup {
+    id: usize,
+    members: SpinLock<Vec<Weak<MountNode>>>,
+    /// The mount nodes that receive the mount events of this group.
+    slaves: SpinLock<Vec<Weak<MountNode>>>,
+}
+
+impl PeerGroup {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            id: PEER_GROUP_ID_ALLOCATOR.fetch_add(1, Ordering::Relaxed),
+            members: SpinLock::new(Vec::new()),
+            slaves: SpinLock::new(Vec::new()),
+        })
+    }
+
+    fn members(&self) -> Vec<Arc<MountNode>> {
+        self.members
+            .lock()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    fn slaves(&self) -> Vec<Arc<MountNode>> {
+        self.slaves
+            .lock()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+}
+
+/// Removes the mount node from a list of the peer group.
+fn remove_from(list: &SpinLock<Vec<Weak<MountNode>>>, mount_node: &MountNode) {
+    list.lock()
+        .retain(|weak| !core::ptr::eq(weak.as_ptr(), mount_node));
+}
+
+#[derive(Clone, Default)]
+struct PropagationState {
+    /// The peer group if the mount node is shared.
+    peer_group: Option<Arc<PeerGroup>>,
+    /// The peer group that the mount node receives mount events from if the
+    /// mount node is a slave.
+    master: Option<Arc<PeerGroup>>,
+    is_unbindable: bool,
+}
+
 impl MountNode {
     /// Creates a root mount node with an associated FS.
     ///
@@ -49,11 +183,14 @@ impl MountNode {
     /// mount nodes must be explicitly assigned a mountpoint to maintain structural integrity.
     fn new(fs: Arc<dyn FileSystem>, parent_mount: Option<Weak<MountNode>>) -> Arc<Self> {
         Arc::new_cyclic(|weak_self| Self {
+            id: MOUNT_ID_ALLOCATOR.fetch_add(1, Ordering::Relaxed),
             root_dentry: Dentry_::new_root(fs.root_inode()),
             mountpoint_dentry: RwLock::new(None),
             parent: RwLock::new(parent_mount),
             children: RwLock::new(HashMap::new()),
             fs,
+            flags: AtomicU32::new(PerMountFlags::empty().bits()),
+            propagation: RwLock::new(PropagationState::default()),
             this: weak_self.clone(),
         })
     }
@@ -68,8 +205,15 @@ impl MountNode {
     /// It is allowed to mount a fs even if the fs has been provided to another
     /// mountpoint. It is the fs's responsibility to ensure the data consistency.
     ///
+    /// The child mount node has the per-mount `flags`.
+    ///
     /// Return the mounted child mount.
-    pub fn mount(&self, fs: Arc<dyn FileSystem>, mountpoint: &Dentry) -> Result<Arc<Self>> {
+    pub fn mount(
+        &self,
+        fs: Arc<dyn FileSystem>,
+        mountpoint: &Dentry,
+        flags: PerMountFlags,
+    ) -> Result<Arc<Self>> {
         if !Arc::ptr_eq(mountpoint.mount_node(), &self.this()) {
             return_errno_with_message!(Errno::EINVAL, "mountpoint not belongs to this");
         }
@@ -79,6 +223,12 @@ impl MountNode {
 
         let key = mountpoint.key();
         let child_mount = Self::new(fs, Some(Arc::downgrade(mountpoint.mount_node())));
+        child_mount.set_flags(flags);
+        // A new mount under a shared mount is shared as well, so that it can
+        // be propagated to the peers of its parent.
+        if self.is_shared() {
+            child_mount.set_propagation(MountPropagation::Shared, false);
+        }
         self.children.write().insert(key, child_mount.clone());
         Ok(child_mount)
     }
@@ -96,22 +246,36 @@ impl MountNode {
             .write()
             .remove(&mountpoint.key())
             .ok_or_else(|| Error::with_message(Errno::ENOENT, "can not find child mount"))?;
+        self.propagate_unmount(&child_mount);
+        child_mount.set_propagation(MountPropagation::Private, true);
         Ok(child_mount)
     }
 
     /// Clones a mount node with the an root `Dentry_`.
     ///
-    /// The new mount node will have the same fs as the original one and
-    /// have no parent and children. We should set the parent and children manually.
+    /// The new mount node will have the same fs, flags and propagation as the
+    /// original one, i.e., a clone of a shared mount node is its peer and a clone
+    /// of a slave mount node is a slave of the same master.
+    ///
+    /// The new mount node has no parent and children. We should set the parent
+    /// and children manually.
     fn clone_mount_node(&self, root_dentry: &Arc<Dentry_>) -> Arc<Self> {
-        Arc::new_cyclic(|weak_self| Self {
+        let new_mount = Arc::new_cyclic(|weak_self| Self {
+            id: MOUNT_ID_ALLOCATOR.fetch_add(1, Ordering::Relaxed),
             root_dentry: root_dentry.clone(),
             mountpoint_dentry: RwLock::new(None),
             parent: RwLock::new(None),
             children: RwLock::new(HashMap::new()),
             fs: self.fs.clone(),
+            flags: AtomicU32::new(self.flags.load(Ordering::Relaxed)),
+            propagation: RwLock::new(PropagationState::default()),
             this: weak_self.clone(),
-        })
+        });
+
+        let mut propagation = self.propagation.read().clone();
+        propagation.is_unbindable = false;
+        new_mount.set_propagation_state(propagation);
+        new_mount
     }
 
     /// Clones a mount tree starting from the specified root `Dentry_`.
@@ -142,6 +306,9 @@ impl MountNode {
                 if !mountpoint_dentry.is_descendant_of(old_mount.root_dentry()) {
                     continue;
                 }
+                if old_child_mount.is_unbindable() {
+                    continue;
+                }
                 let new_child_mount =
                     old_child_mount.clone_mount_node(old_child_mount.root_dentry());
                 let key = mountpoint_dentry.key();
@@ -184,15 +351,228 @@ impl MountNode {
     }
 
     /// Grafts the mount node tree to the mountpoint.
+    ///
+    /// If the mountpoint belongs to a shared mount node, the grafted tree will
+    /// be shared and propagated to the peers and slaves of that mount node.
     pub fn graft_mount_node_tree(&self, mountpoint: &Dentry) -> Result<()> {
         if mountpoint.type_() != InodeType::Dir {
             return_errno!(Errno::ENOTDIR);
         }
+        if let Some(parent) = self.parent().and_then(|parent| parent.upgrade()) {
+            if parent.is_shared() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "cannot move a mount whose parent mount is shared"
+                );
+            }
+        }
         self.detach_mount_node();
         self.attach_mount_node(mountpoint);
+
+        let new_parent = mountpoint.mount_node();
+        if new_parent.is_shared() {
+            if !self.is_shared() {
+                self.set_propagation(MountPropagation::Shared, false);
+            }
+            new_parent.propagate_mount(&self.this());
+        }
         Ok(())
     }
 
+    /// Gets the per-mount flags.
+    pub fn flags(&self) -> PerMountFlags {
+        PerMountFlags::from_bits_truncate(self.flags.load(Ordering::Relaxed))
+    }
+
+    /// Sets the per-mount flags.
+    pub fn set_flags(&self, flags: PerMountFlags) {
+        self.flags.store(flags.bits(), Ordering::Relaxed);
+    }
+
+    /// Returns whether the mount node is shared.
+    pub fn is_shared(&self) -> bool {
+        self.propagation.read().peer_group.is_some()
+    }
+
+    /// Returns whether the mount node cannot be bind mounted.
+    pub fn is_unbindable(&self) -> bool {
+        self.propagation.read().is_unbindable
+    }
+
+    /// Gets the ID of the peer group if the mount node is shared.
+    pub fn peer_group_id(&self) -> Option<usize> {
+        self.propagation
+            .read()
+            .peer_group
+            .as_ref()
+            .map(|group| group.id)
+    }
+
+    /// Gets the ID of the master peer group if the mount node is a slave.
+    pub fn master_id(&self) -> Option<usize> {
+        self.propagation
+            .read()
+            .master
+            .as_ref()
+            .map(|group| group.id)
+    }
+
+    /// Changes the propagation type of the mount node.
+    ///
+    /// If `recursive` is true, the propagation types of all the mount nodes in
+    /// the mount tree rooted at this mount node are changed.
+    pub fn set_propagation(&self, propagation: MountPropagation, recursive: bool) {
+        let old_state = self.propagation.read().clone();
+        let new_state = match propagation {
+            MountPropagation::Shared => PropagationState {
+                peer_group: Some(old_state.peer_group.unwrap_or_else(PeerGroup::new)),
+                master: old_state.master,
+                is_unbindable: false,
+            },
+            MountPropagation::Private => PropagationState::default(),
+            MountPropagation::Slave => {
+                // A shared mount becomes a slave of its former peers. If it has
+                // no peers, it keeps its current master, if any.
+                let master = old_state
+                    .peer_group
+                    .filter(|group| {
+                        group
+                            .members()
+                            .iter()
+                            .any(|member| !core::ptr::eq(Arc::as_ptr(member), self))
+                    })
+                    .or(old_state.master);
+                PropagationState {
+                    peer_group: None,
+                    master,
+                    is_unbindable: false,
+                }
+            }
+            MountPropagation::Unbindable => PropagationState {
+                peer_group: None,
+                master: None,
+                is_unbindable: true,
+            },
+        };
+        self.set_propagation_state(new_state);
+
+        if recursive {
+            for child in self.children() {
+                child.set_propagation(propagation, true);
+            }
+        }
+    }
+
+    fn set_propagation_state(&self, new_state: PropagationState) {
+        let mut state = self.propagation.write();
+        if let Some(peer_group) = state.peer_group.take() {
+            remove_from(&peer_group.members, self);
+        }
+        if let Some(master) = state.master.take() {
+            remove_from(&master.slaves, self);
+        }
+        if let Some(peer_group) = &new_state.peer_group {
+            peer_group.members.lock().push(self.this.clone());
+        }
+        if let Some(master) = &new_state.master {
+            master.slaves.lock().push(self.this.clone());
+        }
+        *state = new_state;
+    }
+
+    /// Collects the mount nodes that receive the mount events of this mount
+    /// node, i.e., the other peers and (recursively) the slaves.
+    ///
+    /// Each mount node is paired with whether it receives the events as a slave.
+    fn propagation_targets(&self) -> Vec<(Arc<Self>, bool)> {
+        let Some(peer_group) = self.propagation.read().peer_group.clone() else {
+            return Vec::new();
+        };
+
+        let mut visited = BTreeSet::from([self.id]);
+        let mut targets = Vec::new();
+        for peer in peer_group.members() {
+            if visited.insert(peer.id) {
+                targets.push((peer, false));
+            }
+        }
+
+        let mut pending_slaves = peer_group.slaves();
+        while let Some(slave) = pending_slaves.pop() {
+            if !visited.insert(slave.id) {
+                continue;
+            }
+            // The slave propagates the events further if it is shared.
+            let slave_group = slave.propagation.read().peer_group.clone();
+            if let Some(slave_group) = slave_group {
+                pending_slaves.extend(slave_group.members());
+                pending_slaves.extend(slave_group.slaves());
+            }
+            targets.push((slave, true));
+        }
+        targets
+    }
+
+    /// Propagates a new child mount node to the mount nodes that receive
+    /// the mount events of this mount node.
+    ///
+    /// A copy of the child mount tree is mounted on the same mountpoint in
+    /// each of them, if the mountpoint is visible there.
+    pub(super) fn propagate_mount(&self, child_mount: &Arc<Self>) {
+        let mountpoint_dentry = child_mount.mountpoint_dentry().unwrap();
+        let key = mountpoint_dentry.key();
+
+        for (target, is_slave) in self.propagation_targets() {
+            if !target.contains_dentry(&mountpoint_dentry)
+                || target.children.read().contains_key(&key)
+            {
+                continue;
+            }
+
+            let new_mount = child_mount.clone_mount_node_tree(child_mount.root_dentry(), true);
+            if is_slave {
+                let master = child_mount.propagation.read().peer_group.clone();
+                new_mount.set_propagation_state(PropagationState {
+                    peer_group: None,
+                    master,
+                    is_unbindable: false,
+                });
+            }
+            new_mount.set_parent(&target);
+            new_mount.set_mountpoint_dentry(&mountpoint_dentry);
+            target.children.write().insert(key, new_mount);
+        }
+    }
+
+    /// Propagates the unmount of a child mount node to the mount nodes that
+    /// receive the mount events of this mount node.
+    ///
+    /// Only the copies of the child mount node that are not mounted over are
+    /// unmounted.
+    fn propagate_unmount(&self, child_mount: &Arc<Self>) {
+        let mountpoint_dentry = child_mount.mountpoint_dentry().unwrap();
+        let key = mountpoint_dentry.key();
+
+        for (target, _) in self.propagation_targets() {
+            let removed_mount = {
+                let mut children = target.children.write();
+                let is_copy = children.get(&key).is_some_and(|mount| {
+                    Arc::ptr_eq(&mount.fs, &child_mount.fs) && mount.children.read().is_empty()
+                });
+                if !is_copy {
+                    continue;
+                }
+                children.remove(&key).unwrap()
+            };
+            removed_mount.set_propagation(MountPropagation::Private, false);
+        }
+    }
+
+    /// Returns whether the `Dentry_` is in the mount node.
+    fn contains_dentry(&self, dentry: &Arc<Dentry_>) -> bool {
+        Arc::ptr_eq(dentry, &self.root_dentry) || dentry.is_descendant_of(&self.root_dentry)
+    }
+
     /// Gets a child mount node from the mountpoint if any.
     pub fn get(&self, mountpoint: &Dentry) -> Option<Arc<Self>> {
         if !Arc::ptr_eq(mountpoint.mount_node(), &self.this()) {
@@ -201,11 +581,36 @@ impl MountNode {
         self.children.read().get(&mountpoint.key()).cloned()
     }
 
+    /// Gets the child mount nodes which are mounted on the dentries of self.
+    pub fn children(&self) -> Vec<Arc<Self>> {
+        self.children.read().values().cloned().collect()
+    }
+
+    /// Gets the unique ID of this mount node.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
     /// Gets the root `Dentry_` of this mount node.
     pub fn root_dentry(&self) -> &Arc<Dentry_> {
         &self.root_dentry
     }
 
+    /// Gets the path of the root `Dentry_` relative to the root of the fs.
+    ///
+    /// The path is not "/" only if the mount node is created by a bind mount
+    /// of a subdirectory.
+    pub fn root_path(&self) -> String {
+        let mut names = Vec::new();
+        let mut dentry = self.root_dentry.clone();
+        while let Some(parent) = dentry.parent() {
+            names.push(dentry.name());
+            dentry = parent;
+        }
+        names.reverse();
+        String::from("/") + &names.join("/")
+    }
+
     /// Gets the mountpoint `Dentry_` of this mount node if any.
     pub fn mountpoint_dentry(&self) -> Option<Arc<Dentry_>> {
         self.mountpoint_dentry.read().clone()
@@ -264,6 +669,13 @@ impl Debug for MountNode {
             .field("root", &self.root_dentry)
             .field("mountpoint", &self.mountpoint_dentry)
             .field("fs", &self.fs)
+            .field("flags", &self.flags())
             .finish()
     }
 }
+
+impl Drop for MountNode {
+    fn drop(&mut self) {
+        self.set_propagation_state(PropagationState::default());
+    }
+}
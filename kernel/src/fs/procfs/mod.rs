@@ -8,11 +8,14 @@ use self::{
     cpuinfo::CpuInfoFileOps,
     loadavg::LoadAvgFileOps,
     meminfo::MemInfoFileOps,
+    mounts::MountsSymOps,
     pid::PidDirOps,
     self_::SelfSymOps,
+    stat::StatFileOps,
     sys::SysDirOps,
     template::{DirOps, ProcDir, ProcDirBuilder, ProcSymBuilder, SymOps},
     thread_self::ThreadSelfSymOps,
+    uptime::UptimeFileOps,
 };
 use crate::{
     events::Observer,
@@ -31,20 +34,27 @@ mod cpuinfo;
 mod filesystems;
 mod loadavg;
 mod meminfo;
+mod mounts;
 mod pid;
 mod self_;
+mod stat;
 mod sys;
-mod template;
+pub(in crate::fs) mod template;
 mod thread_self;
+mod uptime;
 
 pub(super) fn init() {
     FILESYSTEM_TYPES.call_once(|| {
         vec![
             FileSystemType::new("proc", true),
+            FileSystemType::new("sysfs", true),
             FileSystemType::new("ramfs", true),
+            FileSystemType::new("tmpfs", true),
             FileSystemType::new("devpts", true),
+            FileSystemType::new("cgroup2", true),
             FileSystemType::new("ext2", false),
             FileSystemType::new("exfat", false),
+            FileSystemType::new("vfat", false),
         ]
     });
 }
@@ -92,6 +102,10 @@ impl FileSystem for ProcFS {
     fn flags(&self) -> FsFlags {
         FsFlags::empty()
     }
+
+    fn name(&self) -> &'static str {
+        "proc"
+    }
 }
 
 /// Represents the inode at `/proc`.
@@ -134,6 +148,12 @@ impl DirOps for RootDirOps {
             LoadAvgFileOps::new_inode(this_ptr.clone())
         } else if name == "cpuinfo" {
             CpuInfoFileOps::new_inode(this_ptr.clone())
+        } else if name == "stat" {
+            StatFileOps::new_inode(this_ptr.clone())
+        } else if name == "uptime" {
+            UptimeFileOps::new_inode(this_ptr.clone())
+        } else if name == "mounts" {
+            MountsSymOps::new_inode(this_ptr.clone())
         } else if let Ok(pid) = name.parse::<Pid>() {
             let process_ref =
                 process_table::get_process(pid).ok_or_else(|| Error::new(Errno::ENOENT))?;
@@ -164,6 +184,11 @@ impl DirOps for RootDirOps {
             .put_entry_if_not_found("loadavg", || LoadAvgFileOps::new_inode(this_ptr.clone()));
         cached_children
             .put_entry_if_not_found("cpuinfo", || CpuInfoFileOps::new_inode(this_ptr.clone()));
+        cached_children.put_entry_if_not_found("stat", || StatFileOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("uptime", || UptimeFileOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("mounts", || MountsSymOps::new_inode(this_ptr.clone()));
         for process in process_table::process_table_mut().iter() {
             let pid = process.pid().to_string();
             cached_children.put_entry_if_not_found(&pid, || {
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/cgroup`.
+pub struct CgroupFileOps(Arc<Process>);
+
+impl CgroupFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CgroupFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        // There is only the unified hierarchy of cgroup v2, whose ID is zero.
+        Ok(format!("0::{}\n", self.0.cgroup().path()).into_bytes())
+    }
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    process::Process,
+    vm::vmar::vm_mapping::VmMappingFlags,
+};
+
+/// Represents the inode at `/proc/[pid]/ksm_merging_pages`.
+pub struct KsmMergingPagesFileOps(Arc<Process>);
+
+impl KsmMergingPagesFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for KsmMergingPagesFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.root_vmar().nr_ksm_pages()).into_bytes())
+    }
+}
+
+/// Represents the inode at `/proc/[pid]/ksm_stat`.
+pub struct KsmStatFileOps(Arc<Process>);
+
+impl KsmStatFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for KsmStatFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let root_vmar = self.0.root_vmar();
+        let is_mergeable = root_vmar
+            .mappings_info()
+            .iter()
+            .any(|info| info.flags.contains(VmMappingFlags::MERGEABLE));
+        let output = format!(
+            "ksm_merging_pages {}\nksm_mergeable {}\n",
+            root_vmar.nr_ksm_pages(),
+            if is_mergeable { "yes" } else { "no" }
+        );
+        Ok(output.into_bytes())
+    }
+}
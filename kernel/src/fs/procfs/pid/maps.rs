@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::{fmt::Write, ops::Range};
+
+use crate::{
+    fs::{
+        device::DeviceId,
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    vm::{
+        perms::VmPerms,
+        vmar::{
+            is_intersected,
+            vm_mapping::{VmMappingFlags, VmMappingInfo},
+        },
+    },
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/maps`.
+///
+/// Each line describes one mapping in the address space of the process:
+/// ```text
+/// address           perms offset  dev   inode      pathname
+/// 00400000-00452000 r-xp 00000000 08:02 173521     /usr/bin/dbus-daemon
+/// ```
+///
+/// See <https://man7.org/linux/man-pages/man5/proc_pid_maps.5.html>.
+pub struct MapsFileOps(Arc<Process>);
+
+impl MapsFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for MapsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut maps_output = String::new();
+        for_each_mapping(&self.0, |info, name| {
+            write_mapping_line(&mut maps_output, info, name);
+        });
+        Ok(maps_output.into_bytes())
+    }
+}
+
+/// Represents the inode at `/proc/[pid]/smaps`.
+///
+/// It shows the same mappings as `/proc/[pid]/maps`, with the memory
+/// consumption of each mapping following its header line.
+///
+/// FIXME: The proportional set size (`Pss`) is reported as the same as `Rss`,
+/// since we do not track how many processes share a page.
+pub struct SmapsFileOps(Arc<Process>);
+
+impl SmapsFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for SmapsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut smaps_output = String::new();
+        for_each_mapping(&self.0, |info, name| {
+            write_mapping_line(&mut smaps_output, info, name);
+
+            let clean = info.rss - info.dirty;
+            let (shared_clean, shared_dirty, private_clean, private_dirty) = if info.is_shared {
+                (clean, info.dirty, 0, 0)
+            } else {
+                (0, 0, clean, info.dirty)
+            };
+            let locked = if info.flags.contains(VmMappingFlags::LOCKED) {
+                info.rss
+            } else {
+                0
+            };
+            let fields = [
+                ("Size", info.range.len()),
+                ("KernelPageSize", PAGE_SIZE),
+                ("MMUPageSize", PAGE_SIZE),
+                ("Rss", info.rss),
+                ("Pss", info.rss),
+                ("Shared_Clean", shared_clean),
+                ("Shared_Dirty", shared_dirty),
+                ("Private_Clean", private_clean),
+                ("Private_Dirty", private_dirty),
+                ("Referenced", info.referenced),
+                ("Anonymous", info.anonymous),
+                ("Swap", info.swap),
+                ("Locked", locked),
+            ];
+            for (field, size) in fields {
+                writeln!(
+                    smaps_output,
+                    "{:<16}{:>8} kB",
+                    format!("{}:", field),
+                    size / 1024
+                )
+                .unwrap();
+            }
+            writeln!(smaps_output, "VmFlags: {}", vm_flags(info)).unwrap();
+        });
+        Ok(smaps_output.into_bytes())
+    }
+}
+
+/// Invokes `f` on each mapping of the process, together with the name that
+/// describes the mapping (a file path, `[heap]`, `[stack]` or nothing).
+fn for_each_mapping(process: &Process, mut f: impl FnMut(&VmMappingInfo, Option<String>)) {
+    let process_vm = process.vm();
+    let heap_range = process_vm.heap_range();
+    let stack_range = process_vm.stack_range();
+
+    for info in process_vm.root_vmar().mappings_info() {
+        let name = if let Some(dentry) = &info.mapped_file {
+            Some(dentry.abs_path())
+        } else if is_labeled(&info, &heap_range) {
+            Some(String::from("[heap]"))
+        } else if is_labeled(&info, &stack_range) {
+            Some(String::from("[stack]"))
+        } else {
+            None
+        };
+        f(&info, name);
+    }
+}
+
+/// Returns whether the mapping belongs to the labeled region.
+///
+/// The inaccessible mappings that only reserve the address space (e.g., the
+/// space reserved for the heap to grow) are not labeled.
+fn is_labeled(info: &VmMappingInfo, region: &Range<Vaddr>) -> bool {
+    !info.perms.is_empty() && is_intersected(&info.range, region)
+}
+
+fn write_mapping_line(output: &mut String, info: &VmMappingInfo, name: Option<String>) {
+    let (dev, ino, offset) = match &info.mapped_file {
+        Some(dentry) => {
+            let metadata = dentry.metadata();
+            (DeviceId::from(metadata.dev), metadata.ino, info.vmo_offset)
+        }
+        None => (DeviceId::from(0), 0, 0),
+    };
+
+    let mut line = String::new();
+    write!(
+        line,
+        "{:08x}-{:08x} {}{}{}{} {:08x} {:02x}:{:02x} {}",
+        info.range.start,
+        info.range.end,
+        perm_char(info.perms, VmPerms::READ, 'r'),
+        perm_char(info.perms, VmPerms::WRITE, 'w'),
+        perm_char(info.perms, VmPerms::EXEC, 'x'),
+        if info.is_shared { 's' } else { 'p' },
+        offset,
+        dev.major(),
+        dev.minor(),
+        ino,
+    )
+    .unwrap();
+
+    if let Some(name) = name {
+        // Linux pads the path name to the 74th column on 64-bit platforms.
+        const NAME_COLUMN: usize = 73;
+        let padding = NAME_COLUMN.saturating_sub(line.len()).max(1);
+        line.extend(core::iter::repeat(' ').take(padding));
+        line.push_str(&name);
+    }
+
+    writeln!(output, "{}", line).unwrap();
+}
+
+fn perm_char(perms: VmPerms, perm: VmPerms, c: char) -> char {
+    if perms.contains(perm) {
+        c
+    } else {
+        '-'
+    }
+}
+
+/// Returns the two-letter flags of the mapping shown in `/proc/[pid]/smaps`.
+fn vm_flags(info: &VmMappingInfo) -> String {
+    let mut flags = Vec::new();
+    if info.perms.contains(VmPerms::READ) {
+        flags.push("rd");
+    }
+    if info.perms.contains(VmPerms::WRITE) {
+        flags.push("wr");
+    }
+    if info.perms.contains(VmPerms::EXEC) {
+        flags.push("ex");
+    }
+    if info.is_shared {
+        flags.push("sh");
+    }
+    flags.push("mr");
+    flags.push("mw");
+    flags.push("me");
+    let mapping_flags = [
+        (VmMappingFlags::LOCKED, "lo"),
+        (VmMappingFlags::SEQ_READ, "sr"),
+        (VmMappingFlags::RAND_READ, "rr"),
+        (VmMappingFlags::DONT_FORK, "dc"),
+        (VmMappingFlags::DONT_DUMP, "dd"),
+        (VmMappingFlags::HUGEPAGE, "hg"),
+        (VmMappingFlags::NO_HUGEPAGE, "nh"),
+        (VmMappingFlags::MERGEABLE, "mg"),
+        (VmMappingFlags::WIPE_ON_FORK, "wf"),
+    ];
+    for (flag, name) in mapping_flags {
+        if info.flags.contains(flag) {
+            flags.push(name);
+        }
+    }
+    flags.join(" ")
+}
@@ -1,7 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use self::{
-    cmdline::CmdlineFileOps, comm::CommFileOps, exe::ExeSymOps, fd::FdDirOps, task::TaskDirOps,
+    cgroup::CgroupFileOps,
+    cmdline::CmdlineFileOps,
+    comm::CommFileOps,
+    exe::ExeSymOps,
+    fd::FdDirOps,
+    ksm::{KsmMergingPagesFileOps, KsmStatFileOps},
+    maps::{MapsFileOps, SmapsFileOps},
+    mounts::{MountInfoFileOps, MountsFileOps},
+    oom::{OomScoreAdjFileOps, OomScoreFileOps},
+    task::TaskDirOps,
 };
 use super::template::{DirOps, ProcDir, ProcDirBuilder};
 use crate::{
@@ -14,10 +23,15 @@ use crate::{
     process::{posix_thread::AsPosixThread, Process},
 };
 
+mod cgroup;
 mod cmdline;
 mod comm;
 mod exe;
 mod fd;
+mod ksm;
+mod maps;
+mod mounts;
+mod oom;
 mod stat;
 mod status;
 mod task;
@@ -56,10 +70,21 @@ impl DirOps for PidDirOps {
             "exe" => ExeSymOps::new_inode(self.0.clone(), this_ptr.clone()),
             "comm" => CommFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "fd" => FdDirOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "cgroup" => CgroupFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "cmdline" => CmdlineFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "status" => status::StatusFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "stat" => stat::StatFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             "task" => TaskDirOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "maps" => MapsFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "smaps" => SmapsFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "mountinfo" => MountInfoFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "mounts" => MountsFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "oom_score" => OomScoreFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "oom_score_adj" => OomScoreAdjFileOps::new_inode(self.0.clone(), this_ptr.clone()),
+            "ksm_merging_pages" => {
+                KsmMergingPagesFileOps::new_inode(self.0.clone(), this_ptr.clone())
+            }
+            "ksm_stat" => KsmStatFileOps::new_inode(self.0.clone(), this_ptr.clone()),
             _ => return_errno!(Errno::ENOENT),
         };
         Ok(inode)
@@ -80,6 +105,9 @@ impl DirOps for PidDirOps {
         cached_children.put_entry_if_not_found("fd", || {
             FdDirOps::new_inode(self.0.clone(), this_ptr.clone())
         });
+        cached_children.put_entry_if_not_found("cgroup", || {
+            CgroupFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
         cached_children.put_entry_if_not_found("cmdline", || {
             CmdlineFileOps::new_inode(self.0.clone(), this_ptr.clone())
         });
@@ -92,5 +120,29 @@ impl DirOps for PidDirOps {
         cached_children.put_entry_if_not_found("task", || {
             TaskDirOps::new_inode(self.0.clone(), this_ptr.clone())
         });
+        cached_children.put_entry_if_not_found("maps", || {
+            MapsFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("smaps", || {
+            SmapsFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("mountinfo", || {
+            MountInfoFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("mounts", || {
+            MountsFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("oom_score", || {
+            OomScoreFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("oom_score_adj", || {
+            OomScoreAdjFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("ksm_merging_pages", || {
+            KsmMergingPagesFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("ksm_stat", || {
+            KsmStatFileOps::new_inode(self.0.clone(), this_ptr.clone())
+        });
     }
 }
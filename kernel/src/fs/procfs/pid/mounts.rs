@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Write;
+
+use crate::{
+    fs::{
+        path::{Dentry, MountNode, PerMountFlags},
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/mountinfo`.
+///
+/// Each line describes one mount visible to the process:
+/// ```text
+/// 36 35 0:0 / /mnt rw,nosuid shared:1 - ext2 ext2 rw
+/// ```
+///
+/// See <https://man7.org/linux/man-pages/man5/proc_pid_mountinfo.5.html>.
+pub struct MountInfoFileOps(Arc<Process>);
+
+impl MountInfoFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for MountInfoFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut mountinfo_output = String::new();
+        for_each_mount(&self.0, |mount_node, mountpoint| {
+            let parent_id = mount_node
+                .parent()
+                .and_then(|parent| parent.upgrade())
+                .map_or(mount_node.id(), |parent| parent.id());
+            let fs = mount_node.fs();
+            let mut optional_fields = String::new();
+            if let Some(peer_group_id) = mount_node.peer_group_id() {
+                write!(optional_fields, " shared:{}", peer_group_id).unwrap();
+            }
+            if let Some(master_id) = mount_node.master_id() {
+                write!(optional_fields, " master:{}", master_id).unwrap();
+            }
+            if mount_node.is_unbindable() {
+                optional_fields.push_str(" unbindable");
+            }
+            // TODO: Report the device numbers and the filesystem-specific options.
+            writeln!(
+                mountinfo_output,
+                "{} {} 0:0 {} {} {}{} - {} {} {}",
+                mount_node.id(),
+                parent_id,
+                mount_node.root_path(),
+                mountpoint,
+                mount_node.flags().to_options_string(),
+                optional_fields,
+                fs.name(),
+                fs.name(),
+                if mount_node.flags().contains(PerMountFlags::RDONLY) {
+                    "ro"
+                } else {
+                    "rw"
+                },
+            )
+            .unwrap();
+        });
+        Ok(mountinfo_output.into_bytes())
+    }
+}
+
+/// Represents the inode at `/proc/[pid]/mounts`.
+///
+/// The format is the same as the one of `/etc/fstab`.
+///
+/// See <https://man7.org/linux/man-pages/man5/proc_mounts.5.html>.
+pub struct MountsFileOps(Arc<Process>);
+
+impl MountsFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for MountsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut mounts_output = String::new();
+        for_each_mount(&self.0, |mount_node, mountpoint| {
+            let fs_name = mount_node.fs().name();
+            writeln!(
+                mounts_output,
+                "{} {} {} {} 0 0",
+                fs_name,
+                mountpoint,
+                fs_name,
+                mount_node.flags().to_options_string()
+            )
+            .unwrap();
+        });
+        Ok(mounts_output.into_bytes())
+    }
+}
+
+/// Invokes `f` on each mount under the root directory of the process, in the
+/// preorder of the mount tree, together with the path of its mountpoint.
+fn for_each_mount(process: &Process, mut f: impl FnMut(&Arc<MountNode>, &str)) {
+    let root_mount = {
+        let main_thread = process.main_thread();
+        let posix_thread = main_thread.as_posix_thread().unwrap();
+        let fs_resolver = posix_thread.fs().resolver().read();
+        fs_resolver.root().mount_node().clone()
+    };
+
+    let mut stack = vec![root_mount];
+    while let Some(mount_node) = stack.pop() {
+        let mountpoint = Dentry::new_fs_root(mount_node.clone()).abs_path();
+        f(&mount_node, &mountpoint);
+
+        let mut children = mount_node.children();
+        // Visit the children in the order that they are mounted.
+        children.sort_by_key(|child| child.id());
+        stack.extend(children.into_iter().rev());
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::{
+        oom::{oom_score, OOM_SCORE_ADJ_MAX, OOM_SCORE_ADJ_MIN},
+        Process,
+    },
+};
+
+/// Represents the inode at `/proc/[pid]/oom_score`.
+pub struct OomScoreFileOps(Arc<Process>);
+
+impl OomScoreFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for OomScoreFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", oom_score(&self.0)).into_bytes())
+    }
+}
+
+/// Represents the inode at `/proc/[pid]/oom_score_adj`.
+pub struct OomScoreAdjFileOps(Arc<Process>);
+
+impl OomScoreAdjFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for OomScoreAdjFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.oom_score_adj()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        /// The maximum length of the written number, including the sign and
+        /// the trailing newline.
+        const MAX_LEN: usize = 24;
+
+        let len = reader.remain();
+        if len > MAX_LEN {
+            return_errno_with_message!(Errno::EINVAL, "the OOM score adjustment is too long");
+        }
+        let data = reader.collect()?;
+
+        let oom_score_adj = core::str::from_utf8(&data)
+            .ok()
+            .and_then(|data| data.trim().parse::<i16>().ok())
+            .filter(|adj| (OOM_SCORE_ADJ_MIN..=OOM_SCORE_ADJ_MAX).contains(adj))
+            .ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the OOM score adjustment is invalid")
+            })?;
+        self.0.set_oom_score_adj(oom_score_adj);
+
+        Ok(len)
+    }
+}
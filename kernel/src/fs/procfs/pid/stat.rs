@@ -1,20 +1,24 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::fmt::Write;
+use core::{fmt::Write, sync::atomic::Ordering, time::Duration};
 
+use super::status::{ProcessState, VmUsage};
 use crate::{
     fs::{
         procfs::template::{FileOps, ProcFileBuilder},
         utils::Inode,
     },
     prelude::*,
+    process::{posix_thread::AsPosixThread, rlimit::ResourceType},
+    time::USER_HZ,
     Process,
 };
 
 /// Represents the inode at `/proc/[pid]/stat`.
 /// The fields are the same as the ones in `/proc/[pid]/status`. But the format is different.
 /// See https://github.com/torvalds/linux/blob/ce1c54fdff7c4556b08f5b875a331d8952e8b6b7/fs/proc/array.c#L467
-/// FIXME: Some fields are not implemented yet.
+/// FIXME: The fields about the terminal and most addresses of the process
+/// image are not implemented yet and are always zero.
 ///
 /// Fields:
 /// - pid              : Process ID.
@@ -82,28 +86,117 @@ impl StatFileOps {
 impl FileOps for StatFileOps {
     fn data(&self) -> Result<Vec<u8>> {
         let process = &self.0;
+        let main_thread = process.main_thread();
+        let posix_thread = main_thread.as_posix_thread().unwrap();
 
         let pid = process.pid();
-        let comm = process.executable_path();
-        let ppid = process.parent().pid();
-        let state = if process.status().is_zombie() {
-            'Z'
-        } else {
-            'R'
+        let comm = {
+            let exe_path = process.executable_path();
+            exe_path.rsplit('/').next().unwrap_or(&exe_path).to_string()
         };
+        let state = ProcessState::of(process).as_char();
+        let ppid = process.parent().pid();
         let pgrp = if let Some(pgrp) = process.process_group() {
             pgrp.pgid()
         } else {
             0
         };
+        let session = if let Some(session) = process.session() {
+            session.sid()
+        } else {
+            0
+        };
+
+        let usage = process.resource_usage();
+        let children_usage = process.children_resource_usage();
+        let utime = duration_to_user_hz(usage.utime);
+        let stime = duration_to_user_hz(usage.stime);
+        let cutime = duration_to_user_hz(children_usage.utime);
+        let cstime = duration_to_user_hz(children_usage.stime);
+
+        let nice = i8::from(process.nice().load(Ordering::Relaxed));
+        // The priority of normal processes is reported as `nice + 20`.
+        let priority = nice as i32 + 20;
+        let num_threads = process.tasks().lock().as_slice().len();
+        let starttime = duration_to_user_hz(process.start_time());
+
+        let vm_usage = VmUsage::of(process);
+        let rsslim = process
+            .resource_limits()
+            .lock()
+            .get_rlimit(ResourceType::RLIMIT_RSS)
+            .get_cur();
+        let startstack = process.vm().stack_range().end;
+        let start_brk = process.vm().heap_range().start;
+
+        let signal = u64::from(posix_thread.sig_pending());
+        let blocked = u64::from(posix_thread.sig_mask().load(Ordering::Relaxed));
+        let (sigignore, sigcatch) = {
+            let sig_dispositions = process.sig_dispositions().lock();
+            (
+                u64::from(sig_dispositions.ignored_signals()),
+                u64::from(sig_dispositions.caught_signals()),
+            )
+        };
+        let exit_signal = process.exit_signal().map_or(0, |signum| signum.as_u8());
+        let exit_code = if process.status().is_zombie() {
+            process.status().exit_code()
+        } else {
+            0
+        };
 
         let mut stat_output = String::new();
-        writeln!(
+        // pid (comm) state ppid pgrp session tty_nr tpgid flags
+        write!(
+            stat_output,
+            "{} ({}) {} {} {} {} 0 -1 0 ",
+            pid, comm, state, ppid, pgrp, session
+        )
+        .unwrap();
+        // minflt cminflt majflt cmajflt utime stime cutime cstime
+        write!(
+            stat_output,
+            "{} {} {} {} {} {} {} {} ",
+            usage.minflt,
+            children_usage.minflt,
+            usage.majflt,
+            children_usage.majflt,
+            utime,
+            stime,
+            cutime,
+            cstime
+        )
+        .unwrap();
+        // priority nice num_threads itrealvalue starttime vsize rss rsslim
+        write!(
+            stat_output,
+            "{} {} {} 0 {} {} {} {} ",
+            priority,
+            nice,
+            num_threads,
+            starttime,
+            vm_usage.size,
+            vm_usage.rss / PAGE_SIZE,
+            rsslim
+        )
+        .unwrap();
+        // startcode endcode startstack kstkesp kstkeip
+        write!(stat_output, "0 0 {} 0 0 ", startstack).unwrap();
+        // signal blocked sigignore sigcatch wchan nswap cnswap exit_signal
+        write!(
             stat_output,
-            "{} ({}) {} {} {}",
-            pid, comm, state, ppid, pgrp
+            "{} {} {} {} 0 0 0 {} ",
+            signal, blocked, sigignore, sigcatch, exit_signal
         )
         .unwrap();
+        // processor rt_priority policy delayacct_blkio_ticks guest_time cguest_time
+        write!(stat_output, "0 0 0 0 0 0 ").unwrap();
+        // start_data end_data start_brk arg_start arg_end env_start env_end exit_code
+        writeln!(stat_output, "0 0 {} 0 0 0 0 {}", start_brk, exit_code).unwrap();
         Ok(stat_output.into_bytes())
     }
 }
+
+fn duration_to_user_hz(duration: Duration) -> u64 {
+    duration.as_millis() as u64 * USER_HZ / 1000
+}
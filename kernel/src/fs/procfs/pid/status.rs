@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::fmt::Write;
+use core::{fmt::Write, sync::atomic::Ordering};
 
 use crate::{
     fs::{
@@ -9,12 +9,16 @@ use crate::{
     },
     prelude::*,
     process::posix_thread::AsPosixThread,
+    vm::{
+        perms::VmPerms,
+        vmar::{is_intersected, vm_mapping::VmMappingFlags},
+    },
     Process,
 };
 
 /// Represents the inode at `/proc/[pid]/status`.
 /// See https://github.com/torvalds/linux/blob/ce1c54fdff7c4556b08f5b875a331d8952e8b6b7/fs/proc/array.c#L148
-/// FIXME: Some fields are not implemented yet.
+/// FIXME: The fields about capabilities, seccomp and NUMA are not implemented yet.
 ///
 /// Fields:
 /// - Name:   The name of the process.
@@ -73,21 +77,188 @@ impl FileOps for StatusFileOps {
     fn data(&self) -> Result<Vec<u8>> {
         let process = &self.0;
         let main_thread = process.main_thread();
-        let file_table = main_thread.as_posix_thread().unwrap().file_table();
+        let posix_thread = main_thread.as_posix_thread().unwrap();
+        let file_table = posix_thread.file_table();
+        let credentials = posix_thread.credentials();
+
+        let exe_path = process.executable_path();
+        let name = exe_path.rsplit('/').next().unwrap_or(&exe_path);
 
         let mut status_output = String::new();
-        writeln!(status_output, "Name:\t{}", process.executable_path()).unwrap();
+        writeln!(status_output, "Name:\t{}", name).unwrap();
+        writeln!(
+            status_output,
+            "State:\t{}",
+            ProcessState::of(process).as_status_str()
+        )
+        .unwrap();
         writeln!(status_output, "Tgid:\t{}", process.pid()).unwrap();
         writeln!(status_output, "Pid:\t{}", process.pid()).unwrap();
         writeln!(status_output, "PPid:\t{}", process.parent().pid()).unwrap();
-        writeln!(status_output, "TracerPid:\t{}", process.parent().pid()).unwrap(); // Assuming TracerPid is the same as PPid
+        // Process tracing is not supported.
+        writeln!(status_output, "TracerPid:\t0").unwrap();
+        writeln!(
+            status_output,
+            "Uid:\t{}\t{}\t{}\t{}",
+            u32::from(credentials.ruid()),
+            u32::from(credentials.euid()),
+            u32::from(credentials.suid()),
+            u32::from(credentials.fsuid()),
+        )
+        .unwrap();
+        writeln!(
+            status_output,
+            "Gid:\t{}\t{}\t{}\t{}",
+            u32::from(credentials.rgid()),
+            u32::from(credentials.egid()),
+            u32::from(credentials.sgid()),
+            u32::from(credentials.fsgid()),
+        )
+        .unwrap();
         writeln!(status_output, "FDSize:\t{}", file_table.read().len()).unwrap();
+        let groups = credentials
+            .groups()
+            .iter()
+            .map(|gid| u32::from(*gid).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(status_output, "Groups:\t{}", groups).unwrap();
+
+        let vm_usage = VmUsage::of(process);
+        let vm_fields = [
+            ("VmSize", vm_usage.size),
+            ("VmLck", vm_usage.locked),
+            ("VmHWM", process.vm().rss_peak()),
+            ("VmRSS", vm_usage.rss),
+            ("VmData", vm_usage.data),
+            ("VmStk", vm_usage.stack),
+            ("VmExe", vm_usage.exe),
+            ("VmSwap", vm_usage.swap),
+        ];
+        for (field, size) in vm_fields {
+            writeln!(status_output, "{}:\t{:>8} kB", field, size / 1024).unwrap();
+        }
+
         writeln!(
             status_output,
             "Threads:\t{}",
             process.tasks().lock().as_slice().len()
         )
         .unwrap();
+
+        let sig_dispositions = *process.sig_dispositions().lock();
+        let sig_fields = [
+            ("SigPnd", u64::from(posix_thread.sig_pending())),
+            // There are no process-directed signal queues. Process-directed
+            // signals are enqueued to one of the threads.
+            ("ShdPnd", 0),
+            (
+                "SigBlk",
+                u64::from(posix_thread.sig_mask().load(Ordering::Relaxed)),
+            ),
+            ("SigIgn", u64::from(sig_dispositions.ignored_signals())),
+            ("SigCgt", u64::from(sig_dispositions.caught_signals())),
+        ];
+        for (field, set) in sig_fields {
+            writeln!(status_output, "{}:\t{:016x}", field, set).unwrap();
+        }
+
+        // As in Linux, the context switches are those of the main thread.
+        let usage = posix_thread.rusage().snapshot();
+        writeln!(status_output, "voluntary_ctxt_switches:\t{}", usage.nvcsw).unwrap();
+        writeln!(
+            status_output,
+            "nonvoluntary_ctxt_switches:\t{}",
+            usage.nivcsw
+        )
+        .unwrap();
+
         Ok(status_output.into_bytes())
     }
 }
+
+/// The state of a process, as shown in `/proc/[pid]/stat` and `/proc/[pid]/status`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ProcessState {
+    Running,
+    Stopped,
+    Zombie,
+}
+
+impl ProcessState {
+    pub(super) fn of(process: &Process) -> Self {
+        if process.status().is_zombie() {
+            Self::Zombie
+        } else if process.main_thread().is_stopped() {
+            Self::Stopped
+        } else {
+            Self::Running
+        }
+    }
+
+    pub(super) fn as_char(&self) -> char {
+        match self {
+            Self::Running => 'R',
+            Self::Stopped => 'T',
+            Self::Zombie => 'Z',
+        }
+    }
+
+    fn as_status_str(&self) -> &'static str {
+        match self {
+            Self::Running => "R (running)",
+            Self::Stopped => "T (stopped)",
+            Self::Zombie => "Z (zombie)",
+        }
+    }
+}
+
+/// The memory usage of a process in bytes.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct VmUsage {
+    /// The total size of the mappings.
+    pub(super) size: usize,
+    /// The size of the locked mappings.
+    pub(super) locked: usize,
+    /// The size of the resident pages.
+    pub(super) rss: usize,
+    /// The size of the private writable mappings, excluding the stack.
+    pub(super) data: usize,
+    /// The size of the stack mappings.
+    pub(super) stack: usize,
+    /// The size of the executable file-backed mappings.
+    pub(super) exe: usize,
+    /// The size of the pages that are swapped out.
+    pub(super) swap: usize,
+}
+
+impl VmUsage {
+    pub(super) fn of(process: &Process) -> Self {
+        let process_vm = process.vm();
+        let stack_range = process_vm.stack_range();
+
+        let mut usage = Self::default();
+        for info in process_vm.root_vmar().mappings_info() {
+            // Skip the mappings that only reserve the address space.
+            if info.perms.is_empty() {
+                continue;
+            }
+
+            let size = info.range.len();
+            usage.size += size;
+            if info.flags.contains(VmMappingFlags::LOCKED) {
+                usage.locked += size;
+            }
+            usage.rss += info.rss;
+            usage.swap += info.swap;
+            if is_intersected(&info.range, &stack_range) {
+                usage.stack += size;
+            } else if !info.is_shared && info.perms.contains(VmPerms::WRITE) {
+                usage.data += size;
+            } else if info.mapped_file.is_some() && info.perms.contains(VmPerms::EXEC) {
+                usage.exe += size;
+            }
+        }
+        usage
+    }
+}
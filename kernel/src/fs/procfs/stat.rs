@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/stat` file support, which provides kernel and
+//! system statistics, most notably the time that each CPU spends in
+//! different kinds of work.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_stat.5.html>
+
+use alloc::format;
+use core::fmt::Write;
+
+use ostd::{arch::timer::TIMER_FREQ, cpu::all_cpus};
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    process::posix_thread,
+    sched::{
+        self,
+        cpu_time::{cpu_time, total_cpu_time, CpuTime},
+    },
+    time::{SystemTime, START_TIME, USER_HZ},
+};
+
+/// Represents the inode at `/proc/stat`.
+pub struct StatFileOps;
+
+impl StatFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for StatFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut stat_output = String::new();
+
+        write_cpu_line(&mut stat_output, "cpu", &total_cpu_time());
+        for cpu in all_cpus() {
+            let name = format!("cpu{}", cpu.as_usize());
+            write_cpu_line(&mut stat_output, &name, &cpu_time(cpu));
+        }
+
+        let boot_time = START_TIME
+            .get()
+            .unwrap()
+            .duration_since(&SystemTime::UNIX_EPOCH)
+            .unwrap();
+        writeln!(stat_output, "btime {}", boot_time.as_secs()).unwrap();
+
+        // The TIDs are allocated in an increasing order, so the last TID is
+        // the number of threads and processes created since boot.
+        writeln!(stat_output, "processes {}", posix_thread::last_tid()).unwrap();
+
+        let (_, nr_running) = sched::nr_queued_and_running();
+        writeln!(stat_output, "procs_running {}", nr_running).unwrap();
+        // TODO: Count the tasks blocked on I/O.
+        writeln!(stat_output, "procs_blocked 0").unwrap();
+
+        Ok(stat_output.into_bytes())
+    }
+}
+
+/// Writes a line of CPU time in the unit of `USER_HZ`.
+///
+/// The fields are user, nice, system, idle, iowait, irq, softirq, steal,
+/// guest and guest_nice. The ones that are not accounted are always zero.
+fn write_cpu_line(output: &mut String, name: &str, cpu_time: &CpuTime) {
+    writeln!(
+        output,
+        "{} {} {} {} {} 0 0 0 0 0 0",
+        name,
+        ticks_to_user_hz(cpu_time.user),
+        ticks_to_user_hz(cpu_time.nice),
+        ticks_to_user_hz(cpu_time.system),
+        ticks_to_user_hz(cpu_time.idle),
+    )
+    .unwrap();
+}
+
+fn ticks_to_user_hz(ticks: u64) -> u64 {
+    ticks * USER_HZ / TIMER_FREQ
+}
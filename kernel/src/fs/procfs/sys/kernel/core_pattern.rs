@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::{Inode, InodeMode},
+    },
+    prelude::*,
+    process::coredump::{core_pattern, set_core_pattern},
+};
+
+/// Represents the inode at `/proc/sys/kernel/core_pattern`.
+pub struct CorePatternFileOps;
+
+impl CorePatternFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self)
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(0o644))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for CorePatternFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", core_pattern()).into_bytes())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let len = reader.remain();
+        if len > PAGE_SIZE {
+            return_errno_with_message!(Errno::EINVAL, "the core pattern is too long");
+        }
+        let data = reader.collect()?;
+
+        // The pattern ends at the first newline.
+        let data = data.split(|byte| *byte == b'\n').next().unwrap();
+        let pattern = core::str::from_utf8(data)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the core pattern is invalid"))?;
+        set_core_pattern(pattern);
+
+        Ok(len)
+    }
+}
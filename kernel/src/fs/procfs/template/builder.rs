@@ -8,7 +8,7 @@ use super::{
     sym::{ProcSym, SymOps},
 };
 use crate::{
-    fs::utils::{FileSystem, Inode},
+    fs::utils::{FileSystem, Inode, InodeMode},
     prelude::*,
 };
 
@@ -16,6 +16,7 @@ pub struct ProcDirBuilder<O: DirOps> {
     // Mandatory field
     dir: O,
     // Optional fields
+    mode: InodeMode,
     optional_builder: Option<OptionalBuilder>,
 }
 
@@ -24,6 +25,7 @@ impl<O: DirOps> ProcDirBuilder<O> {
         let optional_builder: OptionalBuilder = Default::default();
         Self {
             dir,
+            mode: InodeMode::from_bits_truncate(0o555),
             optional_builder: Some(optional_builder),
         }
     }
@@ -44,9 +46,21 @@ impl<O: DirOps> ProcDirBuilder<O> {
         self.optional_builder(|ob| ob.ino(ino))
     }
 
+    pub fn mode(mut self, mode: InodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     pub fn build(mut self) -> Result<Arc<ProcDir<O>>> {
         let (fs, parent, ino, is_volatile) = self.optional_builder.take().unwrap().build()?;
-        Ok(ProcDir::new(self.dir, fs, parent, ino, is_volatile))
+        Ok(ProcDir::new(
+            self.dir,
+            fs,
+            parent,
+            ino,
+            is_volatile,
+            self.mode,
+        ))
     }
 
     fn optional_builder<F>(mut self, f: F) -> Self
@@ -63,6 +77,7 @@ pub struct ProcFileBuilder<O: FileOps> {
     // Mandatory field
     file: O,
     // Optional fields
+    mode: InodeMode,
     optional_builder: Option<OptionalBuilder>,
 }
 
@@ -71,6 +86,7 @@ impl<O: FileOps> ProcFileBuilder<O> {
         let optional_builder: OptionalBuilder = Default::default();
         Self {
             file,
+            mode: InodeMode::from_bits_truncate(0o444),
             optional_builder: Some(optional_builder),
         }
     }
@@ -83,9 +99,14 @@ impl<O: FileOps> ProcFileBuilder<O> {
         self.optional_builder(|ob| ob.volatile())
     }
 
+    pub fn mode(mut self, mode: InodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     pub fn build(mut self) -> Result<Arc<ProcFile<O>>> {
         let (fs, _, _, is_volatile) = self.optional_builder.take().unwrap().build()?;
-        Ok(ProcFile::new(self.file, fs, is_volatile))
+        Ok(ProcFile::new(self.file, fs, is_volatile, self.mode))
     }
 
     fn optional_builder<F>(mut self, f: F) -> Self
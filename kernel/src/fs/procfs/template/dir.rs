@@ -7,9 +7,11 @@ use core::time::Duration;
 use aster_util::slot_vec::SlotVec;
 use inherit_methods_macro::inherit_methods;
 
-use super::{Common, ProcFS};
+use super::{alloc_ino, Common};
 use crate::{
-    fs::utils::{DirentVisitor, FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType},
+    fs::utils::{
+        DirEntryVecExt, DirentVisitor, FileSystem, Inode, InodeMode, InodeType, Metadata, MknodType,
+    },
     prelude::*,
     process::{Gid, Uid},
 };
@@ -29,16 +31,12 @@ impl<D: DirOps> ProcDir<D> {
         parent: Option<Weak<dyn Inode>>,
         ino: Option<u64>,
         is_volatile: bool,
+        mode: InodeMode,
     ) -> Arc<Self> {
         let common = {
-            let ino = ino.unwrap_or_else(|| {
-                let arc_fs = fs.upgrade().unwrap();
-                let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
-                procfs.alloc_id()
-            });
-
-            let metadata =
-                Metadata::new_dir(ino, InodeMode::from_bits_truncate(0o555), super::BLOCK_SIZE);
+            let ino = ino.unwrap_or_else(|| alloc_ino(&fs));
+
+            let metadata = Metadata::new_dir(ino, mode, super::BLOCK_SIZE);
             Common::new(metadata, fs, is_volatile)
         };
         Arc::new_cyclic(|weak_self| Self {
@@ -90,8 +88,19 @@ impl<D: DirOps + 'static> Inode for ProcDir<D> {
         InodeType::Dir
     }
 
-    fn create(&self, _name: &str, _type_: InodeType, _mode: InodeMode) -> Result<Arc<dyn Inode>> {
-        Err(Error::new(Errno::EPERM))
+    fn create(&self, name: &str, type_: InodeType, _mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        if type_ != InodeType::Dir {
+            return_errno!(Errno::EPERM);
+        }
+        if self.lookup(name).is_ok() {
+            return_errno!(Errno::EEXIST);
+        }
+
+        let inode = self.inner.create_dir(self.this.clone(), name)?;
+        self.cached_children
+            .write()
+            .put((String::from(name), inode.clone()));
+        Ok(inode)
     }
 
     fn mknod(&self, _name: &str, _mode: InodeMode, type_: MknodType) -> Result<Arc<dyn Inode>> {
@@ -147,8 +156,10 @@ impl<D: DirOps + 'static> Inode for ProcDir<D> {
         Err(Error::new(Errno::EPERM))
     }
 
-    fn rmdir(&self, _name: &str) -> Result<()> {
-        Err(Error::new(Errno::EPERM))
+    fn rmdir(&self, name: &str) -> Result<()> {
+        self.inner.remove_dir(name)?;
+        self.cached_children.write().remove_entry_by_name(name);
+        Ok(())
     }
 
     fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
@@ -186,4 +197,16 @@ pub trait DirOps: Sync + Send {
     }
 
     fn populate_children(&self, this_ptr: Weak<dyn Inode>) {}
+
+    /// Creates a child directory.
+    ///
+    /// The directory is cached by the parent once it is created.
+    fn create_dir(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        Err(Error::new(Errno::EPERM))
+    }
+
+    /// Removes a child directory.
+    fn remove_dir(&self, name: &str) -> Result<()> {
+        Err(Error::new(Errno::EPERM))
+    }
 }
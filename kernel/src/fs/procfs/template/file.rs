@@ -4,7 +4,7 @@ use core::time::Duration;
 
 use inherit_methods_macro::inherit_methods;
 
-use super::{Common, ProcFS};
+use super::{alloc_ino, Common};
 use crate::{
     fs::utils::{FileSystem, Inode, InodeMode, InodeType, IoctlCmd, Metadata},
     prelude::*,
@@ -17,15 +17,9 @@ pub struct ProcFile<F: FileOps> {
 }
 
 impl<F: FileOps> ProcFile<F> {
-    pub fn new(file: F, fs: Weak<dyn FileSystem>, is_volatile: bool) -> Arc<Self> {
+    pub fn new(file: F, fs: Weak<dyn FileSystem>, is_volatile: bool, mode: InodeMode) -> Arc<Self> {
         let common = {
-            let arc_fs = fs.upgrade().unwrap();
-            let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
-            let metadata = Metadata::new_file(
-                procfs.alloc_id(),
-                InodeMode::from_bits_truncate(0o444),
-                super::BLOCK_SIZE,
-            );
+            let metadata = Metadata::new_file(alloc_ino(&fs), mode, super::BLOCK_SIZE);
             Common::new(metadata, fs, is_volatile)
         };
         Arc::new(Self {
@@ -55,6 +49,11 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
     fn fs(&self) -> Arc<dyn FileSystem>;
 
     fn resize(&self, _new_size: usize) -> Result<()> {
+        // Truncating a writable file is a no-op, so that it can be opened
+        // with `O_TRUNC`.
+        if self.common.metadata().mode.is_owner_writable() {
+            return Ok(());
+        }
         Err(Error::new(Errno::EPERM))
     }
 
@@ -75,12 +74,12 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
         self.read_at(offset, writer)
     }
 
-    fn write_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
-        Err(Error::new(Errno::EPERM))
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        self.inner.write_at(offset, reader)
     }
 
-    fn write_direct_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
-        Err(Error::new(Errno::EPERM))
+    fn write_direct_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        self.write_at(offset, reader)
     }
 
     fn read_link(&self) -> Result<String> {
@@ -102,4 +101,11 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
 
 pub trait FileOps: Sync + Send {
     fn data(&self) -> Result<Vec<u8>>;
+
+    /// Writes the data to the file.
+    ///
+    /// The file should be built with a writable mode if this is implemented.
+    fn write_at(&self, _offset: usize, _reader: &mut VmReader) -> Result<usize> {
+        Err(Error::new(Errno::EPERM))
+    }
 }
@@ -10,7 +10,11 @@ pub use self::{
 };
 use super::{ProcFS, BLOCK_SIZE};
 use crate::{
-    fs::utils::{FileSystem, InodeMode, InodeType, Metadata},
+    fs::{
+        cgroupfs::CgroupFS,
+        sysfs::SysFS,
+        utils::{FileSystem, InodeMode, InodeType, Metadata},
+    },
     prelude::*,
     process::{Gid, Uid},
 };
@@ -20,6 +24,23 @@ mod dir;
 mod file;
 mod sym;
 
+/// Allocates a new inode number from the pseudo file system that the template inodes live in.
+///
+/// The template machinery is shared by procfs, sysfs and cgroupfs, all of which hand
+/// out inode numbers from a monotonically increasing counter.
+fn alloc_ino(fs: &Weak<dyn FileSystem>) -> u64 {
+    let arc_fs = fs.upgrade().unwrap();
+    if let Some(procfs) = arc_fs.downcast_ref::<ProcFS>() {
+        procfs.alloc_id()
+    } else if let Some(sysfs) = arc_fs.downcast_ref::<SysFS>() {
+        sysfs.alloc_id()
+    } else if let Some(cgroupfs) = arc_fs.downcast_ref::<CgroupFS>() {
+        cgroupfs.alloc_id()
+    } else {
+        unreachable!("template inodes must belong to procfs, sysfs or cgroupfs")
+    }
+}
+
 struct Common {
     metadata: RwLock<Metadata>,
     fs: Weak<dyn FileSystem>,
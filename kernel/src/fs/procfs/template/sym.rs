@@ -4,7 +4,7 @@ use core::time::Duration;
 
 use inherit_methods_macro::inherit_methods;
 
-use super::{Common, ProcFS};
+use super::{alloc_ino, Common};
 use crate::{
     fs::utils::{FileSystem, Inode, InodeMode, InodeType, IoctlCmd, Metadata},
     prelude::*,
@@ -19,10 +19,8 @@ pub struct ProcSym<S: SymOps> {
 impl<S: SymOps> ProcSym<S> {
     pub fn new(sym: S, fs: Weak<dyn FileSystem>, is_volatile: bool) -> Arc<Self> {
         let common = {
-            let arc_fs = fs.upgrade().unwrap();
-            let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
             let metadata = Metadata::new_symlink(
-                procfs.alloc_id(),
+                alloc_ino(&fs),
                 InodeMode::from_bits_truncate(0o777),
                 super::BLOCK_SIZE,
             );
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module offers `/proc/uptime` file support, which tells the user space
+//! how long the system has been running and how much of that time the CPUs
+//! have spent idle.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/proc_uptime.5.html>
+
+use alloc::format;
+
+use ostd::arch::timer::TIMER_FREQ;
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    sched::cpu_time::total_cpu_time,
+    time::{clocks::BootTimeClock, Clock},
+};
+
+/// Represents the inode at `/proc/uptime`.
+pub struct UptimeFileOps;
+
+impl UptimeFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for UptimeFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let uptime = BootTimeClock::get().read_time().as_secs_f64();
+        // The idle time is summed over all CPUs, so it can exceed the uptime
+        // on multiprocessor systems.
+        let idle_time = total_cpu_time().idle as f64 / TIMER_FREQ as f64;
+
+        let output = format!("{:.2} {:.2}\n", uptime, idle_time);
+        Ok(output.into_bytes())
+    }
+}
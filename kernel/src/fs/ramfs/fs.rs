@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use core::{
-    sync::atomic::{AtomicU64, Ordering},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
     time::Duration,
 };
 
@@ -15,7 +15,7 @@ use ostd::{
     sync::{PreemptDisabled, RwLockWriteGuard},
 };
 
-use super::*;
+use super::{tmpfs::non_zero, *};
 use crate::{
     events::IoEvents,
     fs::{
@@ -24,8 +24,8 @@ use crate::{
         named_pipe::NamedPipe,
         utils::{
             CStr256, CachePage, DirentVisitor, Extension, FallocMode, FileSystem, FsFlags, Inode,
-            InodeMode, InodeType, IoctlCmd, Metadata, MknodType, PageCache, PageCacheBackend,
-            SuperBlock,
+            InodeMode, InodeType, IoctlCmd, Metadata, MknodType, MountOption, PageCache,
+            PageCacheBackend, SuperBlock,
         },
     },
     prelude::*,
@@ -42,19 +42,55 @@ pub struct RamFS {
     root: Arc<RamInode>,
     /// An inode allocator
     inode_allocator: AtomicU64,
+    /// Whether the file system is a tmpfs, which has limits on its usage
+    is_tmpfs: bool,
+    /// The maximum number of blocks for the file data, or zero if unlimited
+    max_blocks: AtomicUsize,
+    /// The maximum number of inodes, or zero if unlimited
+    max_inodes: AtomicUsize,
+    /// The number of used blocks for the file data
+    used_blocks: AtomicUsize,
+    /// The number of used inodes
+    used_inodes: AtomicUsize,
 }
 
 impl RamFS {
     pub fn new() -> Arc<Self> {
+        Self::new_with(
+            false,
+            None,
+            None,
+            InodeMode::from_bits_truncate(0o755),
+            Uid::new_root(),
+            Gid::new_root(),
+        )
+    }
+
+    /// Creates a tmpfs, which is a `RamFS` with the usage limits in the options.
+    pub fn new_tmpfs(options: &TmpfsMountOptions) -> Arc<Self> {
+        Self::new_with(
+            true,
+            options.max_blocks,
+            options.max_inodes,
+            options.root_mode,
+            options.root_uid,
+            options.root_gid,
+        )
+    }
+
+    fn new_with(
+        is_tmpfs: bool,
+        max_blocks: Option<usize>,
+        max_inodes: Option<usize>,
+        root_mode: InodeMode,
+        root_uid: Uid,
+        root_gid: Gid,
+    ) -> Arc<Self> {
         Arc::new_cyclic(|weak_fs| Self {
             sb: SuperBlock::new(RAMFS_MAGIC, BLOCK_SIZE, NAME_MAX),
             root: Arc::new_cyclic(|weak_root| RamInode {
                 inner: Inner::new_dir(weak_root.clone(), weak_root.clone()),
-                metadata: SpinLock::new(InodeMeta::new_dir(
-                    InodeMode::from_bits_truncate(0o755),
-                    Uid::new_root(),
-                    Gid::new_root(),
-                )),
+                metadata: SpinLock::new(InodeMeta::new_dir(root_mode, root_uid, root_gid)),
                 ino: ROOT_INO,
                 typ: InodeType::Dir,
                 this: weak_root.clone(),
@@ -62,12 +98,72 @@ impl RamFS {
                 extension: Extension::new(),
             }),
             inode_allocator: AtomicU64::new(ROOT_INO + 1),
+            is_tmpfs,
+            max_blocks: AtomicUsize::new(max_blocks.unwrap_or(0)),
+            max_inodes: AtomicUsize::new(max_inodes.unwrap_or(0)),
+            used_blocks: AtomicUsize::new(0),
+            // The root inode is in use.
+            used_inodes: AtomicUsize::new(1),
         })
     }
 
+    fn max_blocks(&self) -> Option<usize> {
+        non_zero(self.max_blocks.load(Ordering::Relaxed))
+    }
+
+    fn max_inodes(&self) -> Option<usize> {
+        non_zero(self.max_inodes.load(Ordering::Relaxed))
+    }
+
     fn alloc_id(&self) -> u64 {
         self.inode_allocator.fetch_add(1, Ordering::SeqCst)
     }
+
+    /// Reserves an inode for a new file, which fails with `ENOSPC`
+    /// if the number of inodes exceeds the limit.
+    fn reserve_inode(&self) -> Result<()> {
+        if !try_reserve(&self.used_inodes, 1, self.max_inodes()) {
+            return_errno_with_message!(Errno::ENOSPC, "no inodes left on the ramfs");
+        }
+        Ok(())
+    }
+
+    fn release_inode(&self) {
+        self.used_inodes.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Changes the number of blocks used by a file from `old_blocks` to
+    /// `new_blocks`, which fails with `ENOSPC` if the number of blocks
+    /// exceeds the limit.
+    fn recharge_blocks(&self, old_blocks: usize, new_blocks: usize) -> Result<()> {
+        if new_blocks <= old_blocks {
+            self.used_blocks
+                .fetch_sub(old_blocks - new_blocks, Ordering::Relaxed);
+            return Ok(());
+        }
+        if !try_reserve(
+            &self.used_blocks,
+            new_blocks - old_blocks,
+            self.max_blocks(),
+        ) {
+            return_errno_with_message!(Errno::ENOSPC, "no space left on the ramfs");
+        }
+        Ok(())
+    }
+}
+
+/// Adds `count` to the `used` counter if the result does not exceed the `limit`.
+///
+/// Returns whether the counter is updated.
+fn try_reserve(used: &AtomicUsize, count: usize, limit: Option<usize>) -> bool {
+    used.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+        let new_used = used.checked_add(count)?;
+        if limit.is_some_and(|limit| new_used > limit) {
+            return None;
+        }
+        Some(new_used)
+    })
+    .is_ok()
 }
 
 impl FileSystem for RamFS {
@@ -81,12 +177,68 @@ impl FileSystem for RamFS {
     }
 
     fn sb(&self) -> SuperBlock {
-        self.sb.clone()
+        let mut sb = self.sb.clone();
+        // Like Linux, the usage is only reported if it is limited.
+        if let Some(max_blocks) = self.max_blocks() {
+            sb.blocks = max_blocks;
+            sb.bfree = max_blocks.saturating_sub(self.used_blocks.load(Ordering::Relaxed));
+            sb.bavail = sb.bfree;
+        }
+        if let Some(max_inodes) = self.max_inodes() {
+            sb.files = max_inodes;
+            sb.ffree = max_inodes.saturating_sub(self.used_inodes.load(Ordering::Relaxed));
+        }
+        sb
     }
 
     fn flags(&self) -> FsFlags {
         FsFlags::DENTRY_UNEVICTABLE
     }
+
+    fn name(&self) -> &'static str {
+        if self.is_tmpfs {
+            "tmpfs"
+        } else {
+            "ramfs"
+        }
+    }
+
+    fn reconfigure(&self, data: &str) -> Result<()> {
+        if !self.is_tmpfs {
+            return_errno_with_message!(Errno::EINVAL, "the ramfs has no options to change");
+        }
+
+        let mut options = TmpfsMountOptions::default();
+        options.max_blocks = self.max_blocks();
+        options.max_inodes = self.max_inodes();
+        for option in MountOption::parse_all(data) {
+            // Like Linux, the owner and the mode of the root directory are only set at mount time.
+            if matches!(option.key(), "mode" | "uid" | "gid") {
+                continue;
+            }
+            options.apply(&option)?;
+        }
+
+        // Like Linux, the limits cannot be lowered below the current usage.
+        if options
+            .max_blocks
+            .is_some_and(|max_blocks| max_blocks < self.used_blocks.load(Ordering::Relaxed))
+        {
+            return_errno_with_message!(Errno::EINVAL, "the tmpfs size is less than the usage");
+        }
+        if options
+            .max_inodes
+            .is_some_and(|max_inodes| max_inodes < self.used_inodes.load(Ordering::Relaxed))
+        {
+            return_errno_with_message!(Errno::EINVAL, "the tmpfs inodes are less than the usage");
+        }
+
+        self.max_blocks
+            .store(options.max_blocks.unwrap_or(0), Ordering::Relaxed);
+        self.max_inodes
+            .store(options.max_inodes.unwrap_or(0), Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 /// An inode of `RamFs`.
@@ -124,7 +276,7 @@ impl Inner {
     }
 
     pub fn new_file(this: Weak<RamInode>) -> Self {
-        Self::File(PageCache::new(this).unwrap())
+        Self::File(PageCache::new_unreclaimable(this).unwrap())
     }
 
     pub fn new_symlink() -> Self {
@@ -468,6 +620,27 @@ impl RamInode {
         })
     }
 
+    /// Charges the file system for the new number of blocks of the file.
+    ///
+    /// Returns the old number of blocks, so that the charge can be rolled back.
+    fn charge_blocks(&self, new_blocks: usize) -> Result<usize> {
+        let fs = self.fs.upgrade().unwrap();
+        let mut inode_meta = self.metadata.lock();
+        let old_blocks = inode_meta.blocks;
+        fs.recharge_blocks(old_blocks, new_blocks)?;
+        inode_meta.blocks = new_blocks;
+        Ok(old_blocks)
+    }
+
+    /// Rolls back a charge made by [`Self::charge_blocks`].
+    fn uncharge_blocks(&self, old_blocks: usize) {
+        let fs = self.fs.upgrade().unwrap();
+        let mut inode_meta = self.metadata.lock();
+        // Decreasing the number of blocks never fails.
+        let _ = fs.recharge_blocks(inode_meta.blocks, old_blocks);
+        inode_meta.blocks = old_blocks;
+    }
+
     fn find(&self, name: &str) -> Result<Arc<Self>> {
         if self.typ != InodeType::Dir {
             return_errno_with_message!(Errno::ENOTDIR, "self is not dir");
@@ -484,6 +657,21 @@ impl RamInode {
     }
 }
 
+impl Drop for RamInode {
+    fn drop(&mut self) {
+        // The root inode is dropped with the file system.
+        let Some(fs) = self.fs.upgrade() else {
+            return;
+        };
+
+        if self.typ == InodeType::File {
+            let blocks = self.metadata.lock().blocks;
+            let _ = fs.recharge_blocks(blocks, 0);
+        }
+        fs.release_inode();
+    }
+}
+
 impl PageCacheBackend for RamInode {
     fn read_page_async(&self, _idx: usize, frame: &CachePage) -> Result<BioWaiter> {
         // Initially, any block/page in a RamFs inode contains all zeros
@@ -555,10 +743,25 @@ impl Inode for RamInode {
                 let new_size = offset + write_len;
                 let should_expand_size = new_size > file_size;
                 let new_size_aligned = new_size.align_up(BLOCK_SIZE);
-                if should_expand_size {
-                    page_cache.resize(new_size_aligned)?;
+                let old_cache_size = page_cache.pages().size();
+                let old_blocks = if should_expand_size {
+                    let old_blocks = self.charge_blocks(new_size_aligned / BLOCK_SIZE)?;
+                    if let Err(err) = page_cache.resize(new_size_aligned) {
+                        self.uncharge_blocks(old_blocks);
+                        return Err(err);
+                    }
+                    Some(old_blocks)
+                } else {
+                    None
+                };
+                if let Err(err) = page_cache.pages().write(offset, reader) {
+                    if let Some(old_blocks) = old_blocks {
+                        // Shrinking back to the old size should not fail.
+                        let _ = page_cache.resize(old_cache_size);
+                        self.uncharge_blocks(old_blocks);
+                    }
+                    return Err(err.into());
                 }
-                page_cache.pages().write(offset, reader)?;
 
                 let now = now();
                 let mut inode_meta = self.metadata.lock();
@@ -604,7 +807,18 @@ impl Inode for RamInode {
         }
 
         let page_cache = self.inner.as_file().unwrap();
-        page_cache.resize(new_size)?;
+        let new_blocks = new_size.align_up(BLOCK_SIZE) / BLOCK_SIZE;
+        if new_size > file_size {
+            let old_blocks = self.charge_blocks(new_blocks)?;
+            if let Err(err) = page_cache.resize(new_size) {
+                self.uncharge_blocks(old_blocks);
+                return Err(err);
+            }
+        } else {
+            // Shrinking the page cache first, so that the blocks are still charged on failure.
+            page_cache.resize(new_size)?;
+            self.charge_blocks(new_blocks)?;
+        }
 
         let now = now();
         let mut inode_meta = self.metadata.lock();
@@ -692,6 +906,7 @@ impl Inode for RamInode {
             return_errno_with_message!(Errno::EEXIST, "entry exists");
         }
 
+        self.fs.upgrade().unwrap().reserve_inode()?;
         let new_inode = match type_ {
             MknodType::CharDeviceNode(device) | MknodType::BlockDeviceNode(device) => {
                 RamInode::new_device(
@@ -739,6 +954,7 @@ impl Inode for RamInode {
         }
 
         let fs = self.fs.upgrade().unwrap();
+        fs.reserve_inode()?;
         let new_inode = match type_ {
             InodeType::File => RamInode::new_file(&fs, mode, Uid::new_root(), Gid::new_root()),
             InodeType::SymLink => {
@@ -1,10 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
-//! Ramfs based on PageCache
+//! Ramfs based on PageCache, which also serves as tmpfs with usage limits
 
 pub use fs::RamFS;
+pub use tmpfs::TmpfsMountOptions;
 
 mod fs;
+mod tmpfs;
 
 const RAMFS_MAGIC: u64 = 0x0102_1994;
 const BLOCK_SIZE: usize = 4096;
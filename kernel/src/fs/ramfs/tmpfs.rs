@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::BLOCK_SIZE;
+use crate::{
+    fs::utils::{InodeMode, MountOption},
+    prelude::*,
+    process::{Gid, Uid},
+};
+
+/// The mount options of a tmpfs.
+///
+/// A tmpfs is a `RamFS` with limits on its size and its number of inodes,
+/// so that writing to it cannot exhaust the memory of the system.
+///
+/// See <https://docs.kernel.org/filesystems/tmpfs.html>.
+#[derive(Clone, Debug)]
+pub struct TmpfsMountOptions {
+    /// The maximum number of blocks, or `None` if the size is unlimited.
+    pub(super) max_blocks: Option<usize>,
+    /// The maximum number of inodes, or `None` if the number is unlimited.
+    pub(super) max_inodes: Option<usize>,
+    /// The mode of the root directory.
+    pub(super) root_mode: InodeMode,
+    /// The owner of the root directory.
+    pub(super) root_uid: Uid,
+    /// The group of the root directory.
+    pub(super) root_gid: Gid,
+}
+
+impl Default for TmpfsMountOptions {
+    /// Returns the default options, which limit both the size and the number
+    /// of inodes to half of the memory pages, like Linux.
+    fn default() -> Self {
+        let half_pages = ostd::mm::stat::mem_total() / PAGE_SIZE / 2;
+        Self {
+            max_blocks: Some(half_pages),
+            max_inodes: Some(half_pages),
+            root_mode: InodeMode::from_bits_truncate(0o1777),
+            root_uid: Uid::new_root(),
+            root_gid: Gid::new_root(),
+        }
+    }
+}
+
+impl TmpfsMountOptions {
+    /// Parses the mount options from the `data` argument of `mount()`,
+    /// e.g., `size=64m,nr_inodes=1k,mode=755`.
+    ///
+    /// A size or a number of inodes of zero means no limit.
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for option in MountOption::parse_all(data) {
+            options.apply(&option)?;
+        }
+        Ok(options)
+    }
+
+    /// Applies a single mount option to the options.
+    pub(super) fn apply(&mut self, option: &MountOption) -> Result<()> {
+        match option.key() {
+            "size" => {
+                let value = option.value()?;
+                let size = match value.strip_suffix('%') {
+                    Some(percent) => {
+                        let percent: usize = percent.parse().map_err(|_| {
+                            Error::with_message(Errno::EINVAL, "invalid tmpfs size")
+                        })?;
+                        ostd::mm::stat::mem_total() / 100 * percent
+                    }
+                    None => parse_memparse(value)?,
+                };
+                self.max_blocks = non_zero(size.div_ceil(BLOCK_SIZE));
+            }
+            "nr_blocks" => self.max_blocks = non_zero(parse_memparse(option.value()?)?),
+            "nr_inodes" => self.max_inodes = non_zero(parse_memparse(option.value()?)?),
+            "mode" => {
+                let mode = option.parse_octal_value()?;
+                self.root_mode = InodeMode::from_bits_truncate(mode as u16 & 0o7777);
+            }
+            "uid" => self.root_uid = Uid::new(option.parse_value()?),
+            "gid" => self.root_gid = Gid::new(option.parse_value()?),
+            _ => return_errno_with_message!(Errno::EINVAL, "unknown tmpfs mount option"),
+        }
+        Ok(())
+    }
+}
+
+/// Parses a number with an optional binary suffix of `k`, `m`, `g` or `t`.
+fn parse_memparse(value: &str) -> Result<usize> {
+    let (number, shift) = match value.chars().last() {
+        Some('k' | 'K') => (&value[..value.len() - 1], 10),
+        Some('m' | 'M') => (&value[..value.len() - 1], 20),
+        Some('g' | 'G') => (&value[..value.len() - 1], 30),
+        Some('t' | 'T') => (&value[..value.len() - 1], 40),
+        _ => (value, 0),
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(1 << shift))
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid tmpfs mount option value"))
+}
+
+pub(super) fn non_zero(limit: usize) -> Option<usize> {
+    if limit == 0 {
+        None
+    } else {
+        Some(limit)
+    }
+}
@@ -7,9 +7,10 @@ use spin::Once;
 
 use super::{
     fs_resolver::{FsPath, FsResolver},
-    path::MountNode,
+    path::{MountNode, PerMountFlags},
     procfs::{self, ProcFS},
     ramfs::RamFS,
+    sysfs::SysFS,
     utils::{FileSystem, InodeMode, InodeType},
 };
 use crate::prelude::*;
@@ -78,10 +79,13 @@ pub fn init(initramfs_buf: &[u8]) -> Result<()> {
     }
     // Mount ProcFS
     let proc_dentry = fs.lookup(&FsPath::try_from("/proc")?)?;
-    proc_dentry.mount(ProcFS::new())?;
+    proc_dentry.mount(ProcFS::new(), PerMountFlags::empty())?;
+    // Mount SysFS
+    let sys_dentry = fs.lookup(&FsPath::try_from("/sys")?)?;
+    sys_dentry.mount(SysFS::new(), PerMountFlags::empty())?;
     // Mount DevFS
     let dev_dentry = fs.lookup(&FsPath::try_from("/dev")?)?;
-    dev_dentry.mount(RamFS::new())?;
+    dev_dentry.mount(RamFS::new(), PerMountFlags::empty())?;
 
     println!("[kernel] rootfs is ready");
 
@@ -90,7 +94,7 @@ pub fn init(initramfs_buf: &[u8]) -> Result<()> {
 
 pub fn mount_fs_at(fs: Arc<dyn FileSystem>, fs_path: &FsPath) -> Result<()> {
     let target_dentry = FsResolver::new().lookup(fs_path)?;
-    target_dentry.mount(fs)?;
+    target_dentry.mount(fs, PerMountFlags::empty())?;
     Ok(())
 }
 
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder, ProcSymBuilder, SymOps},
+        utils::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents a read-only attribute file in sysfs.
+///
+/// Each sysfs attribute holds exactly one value, which is formatted by the
+/// `show` closure every time the file is read.
+pub(super) struct AttrFileOps<F: Fn() -> String + Send + Sync> {
+    show: F,
+}
+
+impl<F: Fn() -> String + Send + Sync + 'static> AttrFileOps<F> {
+    pub fn new_inode(show: F, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self { show })
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl<F: Fn() -> String + Send + Sync> FileOps for AttrFileOps<F> {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut output = (self.show)();
+        output.push('\n');
+        Ok(output.into_bytes())
+    }
+}
+
+/// Represents a symbolic link in sysfs that points to another sysfs entry.
+pub(super) struct LinkSymOps(String);
+
+impl LinkSymOps {
+    pub fn new_inode(target: String, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcSymBuilder::new(Self(target))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl SymOps for LinkSymOps {
+    fn read_link(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_block::BlockDevice;
+
+use super::attr::AttrFileOps;
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/sys/block`.
+pub struct BlockDirOps;
+
+impl BlockDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self)
+            .parent(parent)
+            // Block devices may be registered at any time.
+            .volatile()
+            .build()
+            .unwrap()
+    }
+}
+
+impl DirOps for BlockDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let device = aster_block::get_device(name).ok_or_else(|| Error::new(Errno::ENOENT))?;
+        Ok(BlockDeviceDirOps::new_inode(device, this_ptr.clone()))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<BlockDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for (name, device) in aster_block::all_devices() {
+            cached_children.put_entry_if_not_found(&name, || {
+                BlockDeviceDirOps::new_inode(device.clone(), this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// Represents the inode at `/sys/block/[name]`.
+struct BlockDeviceDirOps(Arc<dyn BlockDevice>);
+
+impl BlockDeviceDirOps {
+    pub fn new_inode(device: Arc<dyn BlockDevice>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(device))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+
+    fn new_attr(&self, name: &str, this_ptr: Weak<dyn Inode>) -> Option<Arc<dyn Inode>> {
+        let inode = match name {
+            // The size is always reported in 512-byte sectors, regardless of the
+            // logical block size of the device.
+            "size" => {
+                let device = self.0.clone();
+                AttrFileOps::new_inode(move || device.metadata().nr_sectors.to_string(), this_ptr)
+            }
+            "ro" => {
+                let device = self.0.clone();
+                AttrFileOps::new_inode(
+                    move || (device.metadata().is_read_only as u8).to_string(),
+                    this_ptr,
+                )
+            }
+            "removable" => {
+                let device = self.0.clone();
+                AttrFileOps::new_inode(
+                    move || (device.metadata().is_removable as u8).to_string(),
+                    this_ptr,
+                )
+            }
+            _ => return None,
+        };
+        Some(inode)
+    }
+}
+
+const BLOCK_DEVICE_ATTRS: &[&str] = &["size", "ro", "removable"];
+
+impl DirOps for BlockDeviceDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        self.new_attr(name, this_ptr)
+            .ok_or_else(|| Error::new(Errno::ENOENT))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<BlockDeviceDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for name in BLOCK_DEVICE_ATTRS {
+            cached_children
+                .put_entry_if_not_found(name, || self.new_attr(name, this_ptr.clone()).unwrap());
+        }
+    }
+}
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use aster_network::AnyNetworkDevice;
+use ostd::sync::LocalIrqDisabled;
+
+use super::attr::{AttrFileOps, LinkSymOps};
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/sys/class`.
+pub struct ClassDirOps;
+
+impl ClassDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for ClassDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "block" => BlockClassDirOps::new_inode(this_ptr.clone()),
+            "input" => InputClassDirOps::new_inode(this_ptr.clone()),
+            "net" => NetClassDirOps::new_inode(this_ptr.clone()),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<ClassDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children
+            .put_entry_if_not_found("block", || BlockClassDirOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("input", || InputClassDirOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("net", || NetClassDirOps::new_inode(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/sys/class/block`.
+///
+/// Each entry is a symbolic link to the device directory in `/sys/block`.
+struct BlockClassDirOps;
+
+impl BlockClassDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self)
+            .parent(parent)
+            .volatile()
+            .build()
+            .unwrap()
+    }
+
+    fn new_link(name: &str, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        LinkSymOps::new_inode(format!("../../block/{}", name), parent)
+    }
+}
+
+impl DirOps for BlockClassDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        if aster_block::get_device(name).is_none() {
+            return_errno!(Errno::ENOENT);
+        }
+        Ok(Self::new_link(name, this_ptr.clone()))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<BlockClassDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for (name, _) in aster_block::all_devices() {
+            cached_children
+                .put_entry_if_not_found(&name, || Self::new_link(&name, this_ptr.clone()));
+        }
+    }
+}
+
+/// Represents the inode at `/sys/class/input`.
+struct InputClassDirOps;
+
+impl InputClassDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self)
+            .parent(parent)
+            .volatile()
+            .build()
+            .unwrap()
+    }
+}
+
+impl DirOps for InputClassDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        if aster_input::get_device(name).is_none() {
+            return_errno!(Errno::ENOENT);
+        }
+        Ok(InputDeviceDirOps::new_inode(
+            String::from(name),
+            this_ptr.clone(),
+        ))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<InputClassDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for (name, _) in aster_input::all_devices() {
+            cached_children.put_entry_if_not_found(&name, || {
+                InputDeviceDirOps::new_inode(name.clone(), this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// Represents the inode at `/sys/class/input/[name]`.
+struct InputDeviceDirOps(String);
+
+impl InputDeviceDirOps {
+    pub fn new_inode(name: String, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(name))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+
+    fn new_name_attr(&self, this_ptr: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let name = self.0.clone();
+        AttrFileOps::new_inode(move || name.clone(), this_ptr)
+    }
+}
+
+impl DirOps for InputDeviceDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "name" => self.new_name_attr(this_ptr),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<InputDeviceDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("name", || self.new_name_attr(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/sys/class/net`.
+struct NetClassDirOps;
+
+impl NetClassDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self)
+            .parent(parent)
+            .volatile()
+            .build()
+            .unwrap()
+    }
+}
+
+impl DirOps for NetClassDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let device = aster_network::get_device(name).ok_or_else(|| Error::new(Errno::ENOENT))?;
+        Ok(NetDeviceDirOps::new_inode(device, this_ptr.clone()))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<NetClassDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for (name, device) in aster_network::all_devices() {
+            cached_children.put_entry_if_not_found(&name, || {
+                NetDeviceDirOps::new_inode(device.clone(), this_ptr.clone())
+            });
+        }
+    }
+}
+
+type NetworkDeviceRef = Arc<SpinLock<dyn AnyNetworkDevice, LocalIrqDisabled>>;
+
+/// The hardware type of Ethernet devices (`ARPHRD_ETHER`).
+const ARPHRD_ETHER: u16 = 1;
+
+/// Represents the inode at `/sys/class/net/[name]`.
+struct NetDeviceDirOps(NetworkDeviceRef);
+
+impl NetDeviceDirOps {
+    pub fn new_inode(device: NetworkDeviceRef, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self(device))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+
+    fn new_attr(&self, name: &str, this_ptr: Weak<dyn Inode>) -> Option<Arc<dyn Inode>> {
+        let device = self.0.clone();
+        let inode = match name {
+            "address" => AttrFileOps::new_inode(
+                move || {
+                    let mac_addr = device.lock().mac_addr();
+                    mac_addr
+                        .0
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<Vec<_>>()
+                        .join(":")
+                },
+                this_ptr,
+            ),
+            "mtu" => AttrFileOps::new_inode(
+                move || {
+                    let capabilities = device.lock().capabilities();
+                    capabilities.max_transmission_unit.to_string()
+                },
+                this_ptr,
+            ),
+            "type" => AttrFileOps::new_inode(|| ARPHRD_ETHER.to_string(), this_ptr),
+            "operstate" => AttrFileOps::new_inode(
+                move || {
+                    let state = if device.lock().is_link_up() {
+                        "up"
+                    } else {
+                        "down"
+                    };
+                    String::from(state)
+                },
+                this_ptr,
+            ),
+            _ => return None,
+        };
+        Some(inode)
+    }
+}
+
+const NET_DEVICE_ATTRS: &[&str] = &["address", "mtu", "type", "operstate"];
+
+impl DirOps for NetDeviceDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        self.new_attr(name, this_ptr)
+            .ok_or_else(|| Error::new(Errno::ENOENT))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<NetDeviceDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for name in NET_DEVICE_ATTRS {
+            cached_children
+                .put_entry_if_not_found(name, || self.new_attr(name, this_ptr.clone()).unwrap());
+        }
+    }
+}
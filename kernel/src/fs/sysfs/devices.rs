@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+
+use ostd::cpu::num_cpus;
+
+use super::attr::AttrFileOps;
+use crate::{
+    fs::{
+        procfs::template::{DirOps, ProcDir, ProcDirBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/sys/devices`.
+pub struct DevicesDirOps;
+
+impl DevicesDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for DevicesDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "system" => SystemDirOps::new_inode(this_ptr.clone()),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<DevicesDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children
+            .put_entry_if_not_found("system", || SystemDirOps::new_inode(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/sys/devices/system`.
+struct SystemDirOps;
+
+impl SystemDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for SystemDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "cpu" => CpuDirOps::new_inode(this_ptr.clone()),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<SystemDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("cpu", || CpuDirOps::new_inode(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/sys/devices/system/cpu`.
+struct CpuDirOps;
+
+impl CpuDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+
+    /// Formats the CPU list in the range format used by the kernel, e.g., "0-7".
+    fn cpu_list() -> String {
+        match num_cpus() {
+            1 => String::from("0"),
+            n => format!("0-{}", n - 1),
+        }
+    }
+
+    fn new_attr(name: &str, this_ptr: Weak<dyn Inode>) -> Option<Arc<dyn Inode>> {
+        let inode = match name {
+            // All CPUs are always present and online, since CPU hotplug is not supported.
+            "online" | "possible" | "present" => AttrFileOps::new_inode(Self::cpu_list, this_ptr),
+            "kernel_max" => AttrFileOps::new_inode(|| (num_cpus() - 1).to_string(), this_ptr),
+            _ => return None,
+        };
+        Some(inode)
+    }
+}
+
+const CPU_ATTRS: &[&str] = &["online", "possible", "present", "kernel_max"];
+
+impl DirOps for CpuDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        if let Some(inode) = Self::new_attr(name, this_ptr.clone()) {
+            return Ok(inode);
+        }
+        let is_valid_cpu = name
+            .strip_prefix("cpu")
+            .and_then(|id| id.parse::<usize>().ok())
+            .is_some_and(|id| id < num_cpus());
+        if !is_valid_cpu {
+            return_errno!(Errno::ENOENT);
+        }
+        Ok(CpuIdDirOps::new_inode(this_ptr.clone()))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<CpuDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for name in CPU_ATTRS {
+            cached_children
+                .put_entry_if_not_found(name, || Self::new_attr(name, this_ptr.clone()).unwrap());
+        }
+        for cpu_id in 0..num_cpus() {
+            cached_children.put_entry_if_not_found(&format!("cpu{}", cpu_id), || {
+                CpuIdDirOps::new_inode(this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// Represents the inode at `/sys/devices/system/cpu/cpu[id]`.
+struct CpuIdDirOps;
+
+impl CpuIdDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for CpuIdDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "online" => AttrFileOps::new_inode(|| String::from("1"), this_ptr),
+            _ => return_errno!(Errno::ENOENT),
+        };
+        Ok(inode)
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<CpuIdDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("online", || {
+            AttrFileOps::new_inode(|| String::from("1"), this_ptr.clone())
+        });
+    }
+}
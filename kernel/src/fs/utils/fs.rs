@@ -51,6 +51,20 @@ pub trait FileSystem: Any + Sync + Send {
     fn sb(&self) -> SuperBlock;
 
     fn flags(&self) -> FsFlags;
+
+    /// Returns the name of the file system type, e.g., `ext2`.
+    fn name(&self) -> &'static str;
+
+    /// Changes the file system specific options when the file system is remounted,
+    /// e.g., `size=64m` for tmpfs.
+    ///
+    /// Returns `EINVAL` if the file system cannot change the options.
+    fn reconfigure(&self, _data: &str) -> Result<()> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the file system options cannot be changed by remount"
+        );
+    }
 }
 
 impl dyn FileSystem {
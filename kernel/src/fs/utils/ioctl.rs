@@ -41,4 +41,16 @@ pub enum IoctlCmd {
     TIOCGPTPEER = 0x40045441,
     /// Get tdx report using TDCALL
     TDXGETREPORT = 0xc4405401,
+    /// Enable the userfaultfd API
+    UFFDIO_API = 0xc018aa3f,
+    /// Register a memory range with the userfaultfd
+    UFFDIO_REGISTER = 0xc020aa00,
+    /// Unregister a memory range from the userfaultfd
+    UFFDIO_UNREGISTER = 0x8010aa01,
+    /// Wake up the threads that fault on a memory range
+    UFFDIO_WAKE = 0x8010aa02,
+    /// Resolve page faults by copying pages
+    UFFDIO_COPY = 0xc028aa03,
+    /// Resolve page faults by mapping zero-filled pages
+    UFFDIO_ZEROPAGE = 0xc020aa04,
 }
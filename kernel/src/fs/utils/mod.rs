@@ -13,7 +13,8 @@ pub use flock::{FlockItem, FlockList, FlockType};
 pub use fs::{FileSystem, FsFlags, SuperBlock};
 pub use inode::{Extension, Inode, InodeMode, InodeType, Metadata, MknodType, Permission};
 pub use ioctl::IoctlCmd;
-pub use page_cache::{CachePage, PageCache, PageCacheBackend};
+pub use mount_options::MountOption;
+pub use page_cache::{spawn_reclaimer_thread, CachePage, PageCache, PageCacheBackend};
 pub use random_test::{generate_random_operation, new_fs_in_memory};
 pub use range_lock::{
     FileRange, RangeLockItem, RangeLockItemBuilder, RangeLockList, RangeLockType, OFFSET_MAX,
@@ -31,6 +32,7 @@ mod flock;
 mod fs;
 mod inode;
 mod ioctl;
+mod mount_options;
 mod page_cache;
 mod random_test;
 mod range_lock;
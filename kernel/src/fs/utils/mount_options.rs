@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::str::FromStr;
+
+use crate::prelude::*;
+
+/// One option in the `data` argument of `mount()`.
+///
+/// The `data` argument is a string of comma-separated options understood by
+/// the file system, where each option is either a flag (e.g., `discard`)
+/// or a key-value pair (e.g., `uid=1000`).
+#[derive(Clone, Debug)]
+pub struct MountOption<'a> {
+    key: &'a str,
+    value: Option<&'a str>,
+}
+
+impl<'a> MountOption<'a> {
+    /// Splits the `data` argument of `mount()` into options.
+    ///
+    /// Empty options (e.g., the one between two adjacent commas) are skipped.
+    pub fn parse_all(data: &'a str) -> impl Iterator<Item = MountOption<'a>> {
+        data.split(',')
+            .filter(|option| !option.is_empty())
+            .map(|option| match option.split_once('=') {
+                Some((key, value)) => Self {
+                    key,
+                    value: Some(value),
+                },
+                None => Self {
+                    key: option,
+                    value: None,
+                },
+            })
+    }
+
+    /// Gets the key of the option.
+    pub fn key(&self) -> &'a str {
+        self.key
+    }
+
+    /// Gets the value of the option.
+    ///
+    /// Returns `EINVAL` if the option has no value.
+    pub fn value(&self) -> Result<&'a str> {
+        self.value
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the mount option needs a value"))
+    }
+
+    /// Parses the value of the option.
+    pub fn parse_value<T: FromStr>(&self) -> Result<T> {
+        self.value()?
+            .parse()
+            .map_err(|_| Error::with_message(Errno::EINVAL, "invalid mount option value"))
+    }
+
+    /// Parses the value of the option as an octal number, e.g., a `umask`.
+    pub fn parse_octal_value(&self) -> Result<u32> {
+        u32::from_str_radix(self.value()?, 8)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "invalid mount option value"))
+    }
+
+    /// Checks that the option has no value, which is expected for a flag.
+    pub fn check_flag(&self) -> Result<()> {
+        if self.value.is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the mount option takes no value");
+        }
+        Ok(())
+    }
+}
@@ -5,7 +5,8 @@
 use core::{
     iter,
     ops::Range,
-    sync::atomic::{AtomicU8, Ordering},
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+    time::Duration,
 };
 
 use align_ext::AlignExt;
@@ -14,12 +15,22 @@ use aster_rights::Full;
 use lru::LruCache;
 use ostd::{
     impl_untyped_frame_meta_for,
-    mm::{Frame, FrameAllocOptions, UFrame, UntypedMem, VmIo},
+    mm::{
+        stat::{mem_available, mem_total},
+        Frame, FrameAllocOptions, UFrame, UntypedMem, VmIo,
+    },
+    sync::WaitQueue,
 };
+use spin::Once;
 
 use crate::{
     prelude::*,
-    vm::vmo::{get_page_idx_range, Pager, Vmo, VmoFlags, VmoOptions},
+    process::oom::alloc_or_oom,
+    thread::kernel_thread::ThreadOptions,
+    vm::{
+        swap,
+        vmo::{get_page_idx_range, Pager, Vmo, VmoFlags, VmoOptions, WeakVmo},
+    },
 };
 
 pub struct PageCache {
@@ -30,12 +41,7 @@ pub struct PageCache {
 impl PageCache {
     /// Creates an empty size page cache associated with a new backend.
     pub fn new(backend: Weak<dyn PageCacheBackend>) -> Result<Self> {
-        let manager = Arc::new(PageCacheManager::new(backend));
-        let pages = VmoOptions::<Full>::new(0)
-            .flags(VmoFlags::RESIZABLE)
-            .pager(manager.clone())
-            .alloc()?;
-        Ok(Self { pages, manager })
+        Self::with_capacity(0, backend)
     }
 
     /// Creates a page cache associated with an existing backend.
@@ -43,6 +49,23 @@ impl PageCache {
     /// The `capacity` is the initial cache size required by the backend.
     /// This size usually corresponds to the size of the backend.
     pub fn with_capacity(capacity: usize, backend: Weak<dyn PageCacheBackend>) -> Result<Self> {
+        let page_cache = Self::new_inner(capacity, backend)?;
+        RECLAIMABLE_CACHES.lock().push(ReclaimableCache {
+            pages: page_cache.pages.downgrade(),
+            manager: Arc::downgrade(&page_cache.manager),
+        });
+        Ok(page_cache)
+    }
+
+    /// Creates an empty size page cache whose pages are never reclaimed.
+    ///
+    /// This is for the backends that do not persist the pages (e.g. RamFS),
+    /// since the page cache is the only place to store their data.
+    pub fn new_unreclaimable(backend: Weak<dyn PageCacheBackend>) -> Result<Self> {
+        Self::new_inner(0, backend)
+    }
+
+    fn new_inner(capacity: usize, backend: Weak<dyn PageCacheBackend>) -> Result<Self> {
         let manager = Arc::new(PageCacheManager::new(backend));
         let pages = VmoOptions::<Full>::new(capacity)
             .flags(VmoFlags::RESIZABLE)
@@ -128,6 +151,15 @@ impl Drop for PageCache {
         // In contrast, resizing the `VMO` to zero greatly accelerates the process.
         // We need to find out the underlying cause of this discrepancy.
         let _ = self.pages.resize(0);
+
+        let manager = Arc::downgrade(&self.manager);
+        let mut caches = RECLAIMABLE_CACHES.lock();
+        if let Some(pos) = caches
+            .iter()
+            .position(|cache| Weak::ptr_eq(&cache.manager, &manager))
+        {
+            caches.swap_remove(pos);
+        }
     }
 }
 
@@ -410,6 +442,7 @@ impl PageCacheManager {
                 pages.get(&idx).unwrap().clone()
             } else {
                 // Cond 1.
+                page.set_active(true);
                 page.clone()
             }
         } else {
@@ -434,6 +467,122 @@ impl PageCacheManager {
         ra_state.set_prev_page(idx);
         Ok(frame.into())
     }
+
+    /// Scans at most `nr_to_scan` least recently used pages and reclaims the
+    /// inactive ones among them.
+    ///
+    /// A page is reclaimed in three steps, which approximates an active list
+    /// and an inactive list of the pages:
+    ///  1. An active page is deactivated.
+    ///  2. An inactive page is decommitted from the VMO if it is not mapped or
+    ///     being accessed. Any later access to it commits the page through
+    ///     the pager again, which activates the page.
+    ///  3. An inactive page that is no longer committed is evicted from the
+    ///     page cache. It is written back to the backend first if it is dirty.
+    ///
+    /// Returns the number of scanned pages that are deactivated, decommitted
+    /// or evicted.
+    fn reclaim(&self, vmo: &WeakVmo, nr_to_scan: usize) -> usize {
+        let Some(backend) = self.backend.upgrade() else {
+            return 0;
+        };
+
+        let mut nr_reclaimed = 0;
+        let mut to_decommit = Vec::new();
+        let mut to_write_back = Vec::new();
+        let mut pages = self.pages.lock();
+        let backend_npages = backend.npages();
+
+        let mut to_evict = Vec::new();
+        for (&idx, page) in pages.iter_mut().rev().take(nr_to_scan) {
+            match page.load_state() {
+                // The page is being read by the readahead.
+                PageState::Uninit => continue,
+                _ if page.is_active() => {
+                    page.set_active(false);
+                    nr_reclaimed += 1;
+                }
+                // The page cache is not the only holder of the page.
+                _ if page.reference_count() > 1 => to_decommit.push(idx),
+                PageState::Dirty if idx < backend_npages => {
+                    // The page is marked as clean before it is written back,
+                    // so any later writes to it will mark it as dirty again.
+                    page.store_state(PageState::UpToDate);
+                    to_write_back.push((idx, page.clone()));
+                }
+                PageState::UpToDate | PageState::Dirty => to_evict.push(idx),
+            }
+        }
+        for idx in to_evict {
+            // The lock is held, so nobody can get the page from the page
+            // cache now.
+            pages.pop(&idx);
+            nr_reclaimed += 1;
+        }
+        drop(pages);
+
+        // Write back the dirty pages without holding the lock, so that the
+        // page cache can still be accessed during the I/O.
+        let mut bio_waiter = BioWaiter::new();
+        let mut is_written_back = true;
+        for (idx, page) in to_write_back.iter() {
+            match backend.write_page_async(*idx, page) {
+                Ok(waiter) => bio_waiter.concat(waiter),
+                Err(err) => {
+                    warn!("failed to write back page {}: {:?}", idx, err);
+                    is_written_back = false;
+                    break;
+                }
+            }
+        }
+        if bio_waiter.nreqs() > 0 && !matches!(bio_waiter.wait(), Some(BioStatus::Complete)) {
+            is_written_back = false;
+        }
+
+        if !to_write_back.is_empty() {
+            let mut pages = self.pages.lock();
+            for (idx, mut page) in to_write_back {
+                if !is_written_back {
+                    // Keep the dirty pages if any of them fails to be
+                    // written back.
+                    page.store_state(PageState::Dirty);
+                    continue;
+                }
+                let paddr = page.start_paddr();
+                drop(page);
+                // The page is evicted only if it is not replaced, accessed or
+                // written during the write-back.
+                if pages.peek(&idx).is_some_and(|page| {
+                    page.start_paddr() == paddr
+                        && page.load_state() == PageState::UpToDate
+                        && !page.is_active()
+                        && page.reference_count() == 1
+                }) {
+                    pages.pop(&idx);
+                    nr_reclaimed += 1;
+                }
+            }
+        }
+
+        let mut in_use = Vec::new();
+        for idx in to_decommit {
+            if vmo.try_decommit_unused_page(idx) {
+                nr_reclaimed += 1;
+            } else {
+                in_use.push(idx);
+            }
+        }
+        if !in_use.is_empty() {
+            // Move the pages in use to the front so that they will not block
+            // the scanning of other pages.
+            let mut pages = self.pages.lock();
+            for idx in in_use {
+                pages.promote(&idx);
+            }
+        }
+
+        nr_reclaimed
+    }
 }
 
 impl Debug for PageCacheManager {
@@ -478,6 +627,7 @@ impl Pager for PageCacheManager {
 
     fn commit_overwrite(&self, idx: usize) -> Result<UFrame> {
         if let Some(page) = self.pages.lock().get(&idx) {
+            page.set_active(true);
             return Ok(page.clone().into());
         }
 
@@ -493,6 +643,11 @@ pub type CachePage = Frame<CachePageMeta>;
 #[derive(Debug)]
 pub struct CachePageMeta {
     pub state: AtomicPageState,
+    /// Whether the page is accessed recently.
+    ///
+    /// An active page will not be reclaimed until it is deactivated by the
+    /// reclaimer and not accessed again.
+    pub active: AtomicBool,
     // TODO: Add a reverse mapping from the page to VMO for eviction.
 }
 
@@ -502,15 +657,19 @@ pub trait CachePageExt {
     fn metadata(&self) -> &CachePageMeta;
 
     fn alloc() -> Result<CachePage> {
-        let meta = CachePageMeta {
+        let new_meta = || CachePageMeta {
             state: AtomicPageState {
                 state: AtomicU8::new(PageState::Uninit as u8),
             },
+            active: AtomicBool::new(true),
         };
-        let page = FrameAllocOptions::new()
-            .zeroed(false)
-            .alloc_frame_with(meta)?;
-        Ok(page)
+        let page = alloc_or_oom(|| {
+            FrameAllocOptions::new()
+                .zeroed(false)
+                .alloc_frame_with(new_meta())
+        });
+        wake_reclaimer_if_needed();
+        page
     }
 
     fn alloc_zero() -> Result<CachePage> {
@@ -526,6 +685,14 @@ pub trait CachePageExt {
     fn store_state(&mut self, new_state: PageState) {
         self.metadata().state.store(new_state, Ordering::Relaxed);
     }
+
+    fn is_active(&self) -> bool {
+        self.metadata().active.load(Ordering::Relaxed)
+    }
+
+    fn set_active(&self, active: bool) {
+        self.metadata().active.store(active, Ordering::Relaxed);
+    }
 }
 
 impl CachePageExt for CachePage {
@@ -598,3 +765,90 @@ impl dyn PageCacheBackend {
         }
     }
 }
+
+/// A page cache that the reclaimer can reclaim pages from.
+struct ReclaimableCache {
+    pages: WeakVmo,
+    manager: Weak<PageCacheManager>,
+}
+
+static RECLAIMABLE_CACHES: Mutex<Vec<ReclaimableCache>> = Mutex::new(Vec::new());
+
+/// The watermarks of the available memory (in bytes).
+///
+/// The reclaimer starts to reclaim pages if the available memory is below
+/// the low watermark, and stops once it is above the high watermark.
+struct Watermarks {
+    low: usize,
+    high: usize,
+}
+
+static WATERMARKS: Once<Watermarks> = Once::new();
+
+static RECLAIMER_WAIT_QUEUE: WaitQueue = WaitQueue::new();
+
+static RECLAIM_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Spawns the kernel thread that reclaims pages from the page caches, and
+/// swaps out anonymous pages if swap areas are enabled.
+pub fn spawn_reclaimer_thread() {
+    let mem_total = mem_total();
+    WATERMARKS.call_once(|| Watermarks {
+        low: mem_total / 32,
+        high: mem_total / 16,
+    });
+
+    ThreadOptions::new(reclaimer_loop).spawn();
+}
+
+fn wake_reclaimer_if_needed() {
+    let Some(watermarks) = WATERMARKS.get() else {
+        return;
+    };
+    if mem_available() < watermarks.low && !RECLAIM_REQUESTED.swap(true, Ordering::Relaxed) {
+        RECLAIMER_WAIT_QUEUE.wake_all();
+    }
+}
+
+fn reclaimer_loop() {
+    /// The interval to check the available memory if not woken up.
+    const RECLAIM_INTERVAL: Duration = Duration::from_secs(1);
+    /// The maximum number of pages to scan in each page cache at a time.
+    const NR_PAGES_TO_SCAN: usize = 32;
+
+    let watermarks = WATERMARKS.get().unwrap();
+
+    loop {
+        let _ = RECLAIMER_WAIT_QUEUE.wait_until_or_timeout(
+            || RECLAIM_REQUESTED.load(Ordering::Relaxed).then_some(()),
+            &RECLAIM_INTERVAL,
+        );
+        RECLAIM_REQUESTED.store(false, Ordering::Relaxed);
+
+        if mem_available() >= watermarks.low {
+            continue;
+        }
+
+        while mem_available() < watermarks.high {
+            let caches = RECLAIMABLE_CACHES
+                .lock()
+                .iter()
+                .map(|cache| (cache.pages.clone(), cache.manager.clone()))
+                .collect::<Vec<_>>();
+
+            let mut nr_reclaimed = 0;
+            for (pages, manager) in caches {
+                let Some(manager) = manager.upgrade() else {
+                    continue;
+                };
+                nr_reclaimed += manager.reclaim(&pages, NR_PAGES_TO_SCAN);
+            }
+            nr_reclaimed += swap::swap_out_pages(NR_PAGES_TO_SCAN);
+
+            if nr_reclaimed == 0 {
+                // Nothing can be reclaimed now. Wait for the next round.
+                break;
+            }
+        }
+    }
+}
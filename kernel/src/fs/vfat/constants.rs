@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MPL-2.0
+
+pub(super) const VFAT_ROOT_INO: u64 = 1;
+
+pub(super) const BOOT_SIGNATURE: u16 = 0xAA55;
+pub(super) const BOOT_SECTOR_SIZE: usize = 512;
+
+pub(super) const MSDOS_SUPER_MAGIC: u64 = 0x4d44;
+
+// Cluster 0, 1 are reserved, the first data cluster is 2.
+pub(super) const FAT_RESERVED_CLUSTERS: u32 = 2;
+
+// The cluster counts that decide the FAT type, as defined by the specification.
+pub(super) const MAX_FAT12_CLUSTERS: u32 = 4084;
+pub(super) const MAX_FAT16_CLUSTERS: u32 = 65524;
+
+pub(super) const MIN_SECTOR_SIZE: u16 = 512;
+pub(super) const MAX_SECTOR_SIZE: u16 = 4096;
+pub(super) const MAX_CLUSTER_SIZE: usize = 64 * 1024;
+
+// The FSInfo structure of FAT32.
+pub(super) const FSINFO_LEAD_SIGNATURE: u32 = 0x41615252;
+pub(super) const FSINFO_STRUCT_SIGNATURE: u32 = 0x61417272;
+pub(super) const FSINFO_STRUCT_OFFSET: usize = 484;
+pub(super) const FSINFO_FREE_COUNT_OFFSET: usize = 488;
+pub(super) const FSINFO_NEXT_FREE_OFFSET: usize = 492;
+pub(super) const FSINFO_UNKNOWN: u32 = 0xFFFF_FFFF;
+
+pub(super) const DENTRY_SIZE: usize = 32;
+pub(super) const SHORT_NAME_LEN: usize = 11;
+pub(super) const SHORT_BASE_LEN: usize = 8;
+pub(super) const SHORT_EXT_LEN: usize = 3;
+/// The number of UTF-16 code units in a long name dentry.
+pub(super) const LONG_NAME_CHARS_PER_DENTRY: usize = 13;
+pub(super) const MAX_LONG_NAME_LEN: usize = 255;
+pub(super) const MAX_LONG_NAME_DENTRIES: usize = 20;
+
+// The special values of the first byte of a dentry.
+pub(super) const DENTRY_END: u8 = 0x00;
+pub(super) const DENTRY_DELETED: u8 = 0xE5;
+/// The first byte of a name that starts with 0xE5, which is a valid KANJI lead byte.
+pub(super) const DENTRY_KANJI_E5: u8 = 0x05;
+
+pub(super) const LONG_NAME_LAST_ORDER: u8 = 0x40;
+pub(super) const LONG_NAME_ORDER_MASK: u8 = 0x1F;
+
+// The case flags in the `nt_res` field of a short dentry, used by Windows NT
+// to store lowercase 8.3 names without long name dentries.
+pub(super) const CASE_LOWER_BASE: u8 = 0x08;
+pub(super) const CASE_LOWER_EXT: u8 = 0x10;
+
+// The FAT timestamps start from 1980-01-01 and end at 2107-12-31.
+pub(super) const FAT_MIN_TIMESTAMP_SECS: u64 = 315532800;
+pub(super) const FAT_MAX_TIMESTAMP_SECS: u64 = 4354819199;
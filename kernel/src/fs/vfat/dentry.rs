@@ -0,0 +1,594 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::Pod;
+
+use super::{
+    constants::*,
+    fat::ClusterID,
+    fs::{VfatCharset, VfatMountOptions, VfatShortName},
+    utils::{char_to_cp437, cp437_to_char, DosTimestamp},
+};
+use crate::prelude::*;
+
+bitflags! {
+    pub(super) struct FatAttr: u8 {
+        const READONLY  = 0x01;
+        const HIDDEN    = 0x02;
+        const SYSTEM    = 0x04;
+        const VOLUME_ID = 0x08;
+        const DIRECTORY = 0x10;
+        const ARCHIVE   = 0x20;
+        /// The attribute of a long name dentry, which is the combination of `READONLY`, `HIDDEN`, `SYSTEM` and `VOLUME_ID`.
+        const LONG_NAME = 0x0F;
+    }
+}
+
+/// A short (8.3) dentry, which holds the metadata of a file or a directory.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub(super) struct ShortDentry {
+    pub name: [u8; SHORT_NAME_LEN],
+    pub attr: u8,
+    /// The case flags of the name, see `CASE_LOWER_BASE` and `CASE_LOWER_EXT`.
+    pub nt_res: u8,
+    pub ctime_cs: u8,
+    pub ctime: u16,
+    pub cdate: u16,
+    pub adate: u16,
+    pub cluster_hi: u16,
+    pub mtime: u16,
+    pub mdate: u16,
+    pub cluster_lo: u16,
+    pub size: u32,
+}
+
+/// A long name dentry, which holds 13 UTF-16 code units of a long name.
+///
+/// The long name dentries of a file precede its short dentry in the reverse order.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub(super) struct LongDentry {
+    pub order: u8,
+    pub name1: [u8; 10],
+    pub attr: u8,
+    pub type_: u8,
+    pub checksum: u8,
+    pub name2: [u8; 12],
+    pub cluster_lo: u16,
+    pub name3: [u8; 4],
+}
+
+impl ShortDentry {
+    pub fn new(name: [u8; SHORT_NAME_LEN], nt_res: u8, attr: FatAttr, now: DosTimestamp) -> Self {
+        Self {
+            name,
+            attr: attr.bits(),
+            nt_res,
+            ctime_cs: now.time_cs,
+            ctime: now.time,
+            cdate: now.date,
+            adate: now.date,
+            mtime: now.time,
+            mdate: now.date,
+            ..Default::default()
+        }
+    }
+
+    /// Creates the "." or ".." dentry of a new directory.
+    pub fn new_dot(name: &[u8; SHORT_NAME_LEN], cluster: ClusterID, now: DosTimestamp) -> Self {
+        let mut dentry = Self::new(*name, 0, FatAttr::DIRECTORY, now);
+        dentry.set_first_cluster(cluster);
+        dentry
+    }
+
+    pub fn attr(&self) -> FatAttr {
+        FatAttr::from_bits_truncate(self.attr)
+    }
+
+    pub fn first_cluster(&self) -> ClusterID {
+        ((self.cluster_hi as u32) << 16) | self.cluster_lo as u32
+    }
+
+    pub fn set_first_cluster(&mut self, cluster: ClusterID) {
+        self.cluster_hi = (cluster >> 16) as u16;
+        self.cluster_lo = cluster as u16;
+    }
+
+    /// Returns the creation time, which is used as the change time.
+    pub fn ctime(&self) -> DosTimestamp {
+        DosTimestamp {
+            time: self.ctime,
+            date: self.cdate,
+            time_cs: self.ctime_cs,
+        }
+    }
+
+    pub fn mtime(&self) -> DosTimestamp {
+        DosTimestamp {
+            time: self.mtime,
+            date: self.mdate,
+            time_cs: 0,
+        }
+    }
+
+    /// Returns the access time, which only has the date.
+    pub fn atime(&self) -> DosTimestamp {
+        DosTimestamp {
+            time: 0,
+            date: self.adate,
+            time_cs: 0,
+        }
+    }
+
+    pub fn set_ctime(&mut self, time: DosTimestamp) {
+        self.ctime = time.time;
+        self.cdate = time.date;
+        self.ctime_cs = time.time_cs;
+    }
+
+    pub fn set_mtime(&mut self, time: DosTimestamp) {
+        self.mtime = time.time;
+        self.mdate = time.date;
+    }
+
+    pub fn set_atime(&mut self, time: DosTimestamp) {
+        self.adate = time.date;
+    }
+
+    fn is_dot_or_dotdot(&self) -> bool {
+        self.name == *DOT_NAME || self.name == *DOTDOT_NAME
+    }
+
+    /// Returns whether the extension is an executable one of DOS.
+    pub fn has_exec_ext(&self) -> bool {
+        matches!(&self.name[SHORT_BASE_LEN..], b"EXE" | b"COM" | b"BAT")
+    }
+}
+
+pub(super) const DOT_NAME: &[u8; SHORT_NAME_LEN] = b".          ";
+pub(super) const DOTDOT_NAME: &[u8; SHORT_NAME_LEN] = b"..         ";
+
+/// A file or a directory found in a directory.
+#[derive(Debug)]
+pub(super) struct VfatDirEntry {
+    pub name: String,
+    pub dentry: ShortDentry,
+    /// The offset of the short dentry in the directory.
+    pub offset: usize,
+    /// The number of dentries, including the long name dentries and the short dentry.
+    pub num_dentries: usize,
+}
+
+impl VfatDirEntry {
+    /// Returns the offset of the first dentry in the directory.
+    pub fn start_offset(&self) -> usize {
+        self.offset - (self.num_dentries - 1) * DENTRY_SIZE
+    }
+}
+
+/// An iterator over the files and directories in the raw data of a directory,
+/// which skips the deleted dentries, the volume label, "." and "..".
+pub(super) struct VfatDirIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+    options: &'a VfatMountOptions,
+}
+
+impl<'a> VfatDirIter<'a> {
+    pub fn new(data: &'a [u8], offset: usize, options: &'a VfatMountOptions) -> Self {
+        Self {
+            data,
+            offset,
+            options,
+        }
+    }
+
+    /// Returns the offset from which the next dentry will be read.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Iterator for VfatDirIter<'_> {
+    type Item = VfatDirEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut long_name = LongNameBuilder::default();
+        while self.offset + DENTRY_SIZE <= self.data.len() {
+            let offset = self.offset;
+            let raw = &self.data[offset..offset + DENTRY_SIZE];
+            match raw[0] {
+                DENTRY_END => {
+                    // No dentries are in use after the end marker.
+                    self.offset = self.data.len();
+                    return None;
+                }
+                DENTRY_DELETED => {
+                    self.offset += DENTRY_SIZE;
+                    long_name.reset();
+                    continue;
+                }
+                _ => {}
+            }
+            self.offset += DENTRY_SIZE;
+
+            if raw[11] & 0x3F == FatAttr::LONG_NAME.bits() {
+                long_name.push(&LongDentry::from_bytes(raw), offset);
+                continue;
+            }
+
+            let dentry = ShortDentry::from_bytes(raw);
+            if dentry.attr().contains(FatAttr::VOLUME_ID) || dentry.is_dot_or_dotdot() {
+                long_name.reset();
+                continue;
+            }
+
+            let (name, num_dentries) = match long_name.finish(&dentry.name, offset) {
+                Some((name, num_long_dentries)) => {
+                    (decode_long_name(&name, self.options), num_long_dentries + 1)
+                }
+                None => (decode_short_name(&dentry, self.options), 1),
+            };
+            return Some(VfatDirEntry {
+                name,
+                dentry,
+                offset,
+                num_dentries,
+            });
+        }
+        None
+    }
+}
+
+/// Collects the long name dentries preceding a short dentry.
+#[derive(Default)]
+struct LongNameBuilder {
+    units: Vec<u16>,
+    /// The order of the last collected dentry, which counts down to 1.
+    order: u8,
+    checksum: u8,
+    /// The offset of the next expected dentry.
+    next_offset: usize,
+}
+
+impl LongNameBuilder {
+    fn reset(&mut self) {
+        self.units.clear();
+        self.order = 0;
+    }
+
+    fn push(&mut self, dentry: &LongDentry, offset: usize) {
+        let order = dentry.order & LONG_NAME_ORDER_MASK;
+        if dentry.order & LONG_NAME_LAST_ORDER != 0 {
+            if order == 0 || order as usize > MAX_LONG_NAME_DENTRIES {
+                self.reset();
+                return;
+            }
+            self.units = vec![0xFFFF; order as usize * LONG_NAME_CHARS_PER_DENTRY];
+            self.checksum = dentry.checksum;
+        } else if self.order == 0
+            || order + 1 != self.order
+            || dentry.checksum != self.checksum
+            || offset != self.next_offset
+        {
+            // An orphaned long name dentry.
+            self.reset();
+            return;
+        }
+        self.order = order;
+        self.next_offset = offset + DENTRY_SIZE;
+
+        let start = (order as usize - 1) * LONG_NAME_CHARS_PER_DENTRY;
+        let bytes = dentry
+            .name1
+            .iter()
+            .chain(dentry.name2.iter())
+            .chain(dentry.name3.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        for (idx, unit) in bytes.chunks_exact(2).enumerate() {
+            self.units[start + idx] = u16::from_le_bytes([unit[0], unit[1]]);
+        }
+    }
+
+    /// Returns the long name and the number of its dentries if it belongs to the short dentry.
+    fn finish(
+        &mut self,
+        short_name: &[u8; SHORT_NAME_LEN],
+        offset: usize,
+    ) -> Option<(Vec<u16>, usize)> {
+        if self.order != 1 || offset != self.next_offset || self.checksum != checksum(short_name) {
+            return None;
+        }
+        let num_dentries = self.units.len() / LONG_NAME_CHARS_PER_DENTRY;
+        let len = self
+            .units
+            .iter()
+            .position(|&unit| unit == 0x0000)
+            .unwrap_or(self.units.len());
+        self.units.truncate(len);
+        self.order = 0;
+        (!self.units.is_empty()).then(|| (core::mem::take(&mut self.units), num_dentries))
+    }
+}
+
+/// Calculates the checksum of a short name, which is stored in its long name dentries.
+pub(super) fn checksum(short_name: &[u8; SHORT_NAME_LEN]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, &byte| sum.rotate_right(1).wrapping_add(byte))
+}
+
+fn decode_long_name(units: &[u16], options: &VfatMountOptions) -> String {
+    char::decode_utf16(units.iter().copied())
+        .map(|c| c.map_or('?', |c| options.charset.display(c)))
+        .collect()
+}
+
+fn decode_short_name(dentry: &ShortDentry, options: &VfatMountOptions) -> String {
+    let (lower_base, lower_ext) = match options.shortname {
+        VfatShortName::Lower => (true, true),
+        VfatShortName::Win95 => (false, false),
+        VfatShortName::WinNT | VfatShortName::Mixed => (
+            dentry.nt_res & CASE_LOWER_BASE != 0,
+            dentry.nt_res & CASE_LOWER_EXT != 0,
+        ),
+    };
+    let decode_part = |bytes: &[u8], lower: bool| -> String {
+        let len = bytes
+            .iter()
+            .rposition(|&byte| byte != b' ')
+            .map_or(0, |pos| pos + 1);
+        bytes[..len]
+            .iter()
+            .enumerate()
+            .map(|(idx, &byte)| {
+                let byte = if idx == 0 && byte == DENTRY_KANJI_E5 {
+                    DENTRY_DELETED
+                } else {
+                    byte
+                };
+                let c = cp437_to_char(byte);
+                let c = if lower && c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c
+                };
+                options.charset.display(c)
+            })
+            .collect()
+    };
+
+    let mut name = decode_part(&dentry.name[..SHORT_BASE_LEN], lower_base);
+    let ext = decode_part(&dentry.name[SHORT_BASE_LEN..], lower_ext);
+    if !ext.is_empty() {
+        name.push('.');
+        name.push_str(&ext);
+    }
+    name
+}
+
+/// The dentries to be written for a new name.
+pub(super) struct NameDentries {
+    pub short_name: [u8; SHORT_NAME_LEN],
+    pub nt_res: u8,
+    pub long_dentries: Vec<LongDentry>,
+}
+
+impl NameDentries {
+    /// Creates the dentries for a name, where `is_taken` tells whether a short name
+    /// is used in the directory.
+    ///
+    /// A long name is stored only if the name cannot be represented by a short name.
+    pub fn new(
+        name: &str,
+        options: &VfatMountOptions,
+        is_taken: impl Fn(&[u8; SHORT_NAME_LEN]) -> bool,
+    ) -> Result<Self> {
+        let name = check_long_name(name, options)?;
+
+        if let Some((short_name, nt_res)) = exact_short_name(name, options)
+            && !is_taken(&short_name)
+        {
+            return Ok(Self {
+                short_name,
+                nt_res,
+                long_dentries: Vec::new(),
+            });
+        }
+
+        let short_name = alias_short_name(name, is_taken)?;
+        let units = name.encode_utf16().collect::<Vec<_>>();
+        Ok(Self {
+            short_name,
+            nt_res: 0,
+            long_dentries: make_long_dentries(&units, checksum(&short_name)),
+        })
+    }
+
+    pub fn num_dentries(&self) -> usize {
+        self.long_dentries.len() + 1
+    }
+}
+
+/// Checks that the name is a valid long name and strips its trailing dots,
+/// which are ignored by FAT.
+pub(super) fn check_long_name<'a>(name: &'a str, options: &VfatMountOptions) -> Result<&'a str> {
+    let name = name.trim_end_matches('.');
+    if name.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "invalid vfat name");
+    }
+    if name.encode_utf16().count() > MAX_LONG_NAME_LEN {
+        return_errno!(Errno::ENAMETOOLONG);
+    }
+    if name
+        .chars()
+        .any(|c| (c as u32) < 0x20 || "\"*/:<>?\\|".contains(c) || !options.charset.contains(c))
+    {
+        return_errno_with_message!(Errno::EINVAL, "invalid character in vfat name");
+    }
+    Ok(name)
+}
+
+/// Compares two names as FAT does, which ignores the case.
+pub(super) fn name_eq(lhs: &str, rhs: &str) -> bool {
+    lhs.chars()
+        .flat_map(char::to_lowercase)
+        .eq(rhs.chars().flat_map(char::to_lowercase))
+}
+
+fn is_valid_short_char(byte: u8) -> bool {
+    byte > b' ' && !b"\"*+,./:;<=>?[\\]|".contains(&byte) && !byte.is_ascii_lowercase()
+}
+
+/// Returns the short name and its case flags if the name is a valid 8.3 name
+/// that needs no long name under the `shortname` option.
+fn exact_short_name(name: &str, options: &VfatMountOptions) -> Option<([u8; SHORT_NAME_LEN], u8)> {
+    let (base, ext) = match name.rsplit_once('.') {
+        Some((base, ext)) => (base, ext),
+        None => (name, ""),
+    };
+    if base.is_empty() || base.len() > SHORT_BASE_LEN || ext.len() > SHORT_EXT_LEN {
+        return None;
+    }
+
+    // Returns whether the part is in lowercase, or `None` if it is in mixed case.
+    let part_case = |part: &str| -> Option<bool> {
+        let has_lower = part.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = part.chars().any(|c| c.is_ascii_uppercase());
+        match (has_lower, has_upper) {
+            (true, true) => None,
+            (has_lower, _) => Some(has_lower),
+        }
+    };
+    let (lower_base, lower_ext) = (part_case(base)?, part_case(ext)?);
+    let nt_res = match options.shortname {
+        VfatShortName::WinNT => {
+            (if lower_base { CASE_LOWER_BASE } else { 0 })
+                | (if lower_ext { CASE_LOWER_EXT } else { 0 })
+        }
+        _ if lower_base || lower_ext => return None,
+        _ => 0,
+    };
+
+    let mut short_name = [b' '; SHORT_NAME_LEN];
+    let (base_bytes, ext_bytes) = short_name.split_at_mut(SHORT_BASE_LEN);
+    for (dst, c) in base_bytes
+        .iter_mut()
+        .zip(base.chars())
+        .chain(ext_bytes.iter_mut().zip(ext.chars()))
+    {
+        if !c.is_ascii() {
+            return None;
+        }
+        let byte = c.to_ascii_uppercase() as u8;
+        if !is_valid_short_char(byte) {
+            return None;
+        }
+        *dst = byte;
+    }
+    if short_name[0] == DENTRY_DELETED {
+        short_name[0] = DENTRY_KANJI_E5;
+    }
+    Some((short_name, nt_res))
+}
+
+/// Generates a unique short alias for a long name, e.g., "LONGFI~1.TXT".
+fn alias_short_name(
+    name: &str,
+    is_taken: impl Fn(&[u8; SHORT_NAME_LEN]) -> bool,
+) -> Result<[u8; SHORT_NAME_LEN]> {
+    let to_short_chars = |part: &str, max_len: usize| -> Vec<u8> {
+        part.chars()
+            .filter(|&c| c != ' ' && c != '.')
+            .map(|c| {
+                let c = c.to_uppercase().next().unwrap_or(c);
+                match char_to_cp437(c) {
+                    Some(byte) if byte >= 0x80 || is_valid_short_char(byte) => byte,
+                    _ => b'_',
+                }
+            })
+            .take(max_len)
+            .collect()
+    };
+
+    let name = name.trim_start_matches('.');
+    let (base, ext) = match name.rsplit_once('.') {
+        Some((base, ext)) if !base.is_empty() => (base, ext),
+        _ => (name, ""),
+    };
+    let base = to_short_chars(base, SHORT_BASE_LEN);
+    let ext = to_short_chars(ext, SHORT_EXT_LEN);
+
+    let mut short_name = [b' '; SHORT_NAME_LEN];
+    short_name[SHORT_BASE_LEN..SHORT_BASE_LEN + ext.len()].copy_from_slice(&ext);
+    for seq in 1..1_000_000u32 {
+        let tail = format!("~{}", seq);
+        let base_len = base.len().min(SHORT_BASE_LEN - tail.len());
+        short_name[..SHORT_BASE_LEN].fill(b' ');
+        short_name[..base_len].copy_from_slice(&base[..base_len]);
+        short_name[base_len..base_len + tail.len()].copy_from_slice(tail.as_bytes());
+        if short_name[0] == DENTRY_DELETED {
+            short_name[0] = DENTRY_KANJI_E5;
+        }
+        if !is_taken(&short_name) {
+            return Ok(short_name);
+        }
+    }
+    return_errno_with_message!(Errno::EEXIST, "no available short name")
+}
+
+/// Makes the long name dentries in the order in which they are stored on disk.
+fn make_long_dentries(units: &[u16], checksum: u8) -> Vec<LongDentry> {
+    let num_dentries = units.len().div_ceil(LONG_NAME_CHARS_PER_DENTRY);
+    // The name is terminated by 0x0000 and then padded with 0xFFFF.
+    let mut padded = units.to_vec();
+    if padded.len() % LONG_NAME_CHARS_PER_DENTRY != 0 {
+        padded.push(0x0000);
+    }
+    padded.resize(num_dentries * LONG_NAME_CHARS_PER_DENTRY, 0xFFFF);
+
+    (1..=num_dentries)
+        .rev()
+        .map(|order| {
+            let chunk =
+                &padded[(order - 1) * LONG_NAME_CHARS_PER_DENTRY..][..LONG_NAME_CHARS_PER_DENTRY];
+            let bytes = chunk
+                .iter()
+                .flat_map(|unit| unit.to_le_bytes())
+                .collect::<Vec<_>>();
+            let mut dentry = LongDentry {
+                order: order as u8,
+                attr: FatAttr::LONG_NAME.bits(),
+                checksum,
+                ..Default::default()
+            };
+            if order == num_dentries {
+                dentry.order |= LONG_NAME_LAST_ORDER;
+            }
+            dentry.name1.copy_from_slice(&bytes[..10]);
+            dentry.name2.copy_from_slice(&bytes[10..22]);
+            dentry.name3.copy_from_slice(&bytes[22..]);
+            dentry
+        })
+        .collect()
+}
+
+impl VfatCharset {
+    fn contains(&self, c: char) -> bool {
+        match self {
+            VfatCharset::Utf8 => true,
+            VfatCharset::Iso8859_1 => (c as u32) < 0x100,
+            VfatCharset::Ascii => c.is_ascii(),
+        }
+    }
+
+    /// Maps a character for display, where the ones out of the charset are shown as '?'.
+    fn display(&self, c: char) -> char {
+        if self.contains(c) {
+            c
+        } else {
+            '?'
+        }
+    }
+}
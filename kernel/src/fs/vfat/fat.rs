@@ -0,0 +1,325 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use align_ext::AlignExt;
+use aster_block::{BlockDevice, SECTOR_SIZE};
+use ostd::mm::VmIo;
+
+use super::{constants::*, super_block::VfatSuperBlock};
+use crate::prelude::*;
+
+pub(super) type ClusterID = u32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum FatType {
+    Fat12,
+    Fat16,
+    Fat32,
+}
+
+impl FatType {
+    /// Returns the number of bytes occupied by `num_entries` FAT entries.
+    pub fn fat_bytes(&self, num_entries: u32) -> usize {
+        let num_entries = num_entries as usize;
+        match self {
+            FatType::Fat12 => (num_entries * 3).div_ceil(2),
+            FatType::Fat16 => num_entries * 2,
+            FatType::Fat32 => num_entries * 4,
+        }
+    }
+
+    fn bad_cluster(&self) -> u32 {
+        match self {
+            FatType::Fat12 => 0xFF7,
+            FatType::Fat16 => 0xFFF7,
+            FatType::Fat32 => 0x0FFF_FFF7,
+        }
+    }
+
+    fn end_of_chain(&self) -> u32 {
+        match self {
+            FatType::Fat12 => 0xFFF,
+            FatType::Fat16 => 0xFFFF,
+            FatType::Fat32 => 0x0FFF_FFFF,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum FatValue {
+    Free,
+    Next(ClusterID),
+    Bad,
+    EndOfChain,
+}
+
+impl FatValue {
+    fn from_raw(raw: u32, fat_type: FatType) -> Self {
+        if raw == 0 {
+            FatValue::Free
+        } else if raw == fat_type.bad_cluster() {
+            FatValue::Bad
+        } else if raw > fat_type.bad_cluster() {
+            FatValue::EndOfChain
+        } else {
+            FatValue::Next(raw)
+        }
+    }
+
+    fn into_raw(self, fat_type: FatType) -> u32 {
+        match self {
+            FatValue::Free => 0,
+            FatValue::Next(cluster) => cluster,
+            FatValue::Bad => fat_type.bad_cluster(),
+            FatValue::EndOfChain => fat_type.end_of_chain(),
+        }
+    }
+}
+
+/// The file allocation table.
+///
+/// The whole table is kept in memory, since it is at most a few megabytes
+/// even for FAT32. The modified entries are written back to all the FAT copies
+/// on disk when a cluster operation completes.
+#[derive(Debug)]
+pub(super) struct FatTable {
+    sb: VfatSuperBlock,
+    raw: Vec<u8>,
+    num_free: u32,
+    /// The cluster from which to search for free clusters.
+    next_free: ClusterID,
+    /// The byte range of the table that is modified but not written back.
+    dirty: Option<Range<usize>>,
+}
+
+impl FatTable {
+    pub fn load(block_device: &dyn BlockDevice, sb: &VfatSuperBlock) -> Result<Self> {
+        let mut raw = vec![0u8; sb.fat_size];
+        block_device.read_bytes(sb.fat_start, &mut raw)?;
+
+        let mut table = Self {
+            sb: *sb,
+            raw,
+            num_free: 0,
+            next_free: FAT_RESERVED_CLUSTERS,
+            dirty: None,
+        };
+        // The free count in the FSInfo sector is only a hint that may be stale,
+        // so we count the free clusters by ourselves.
+        table.num_free = table
+            .clusters()
+            .filter(|&cluster| table.get_raw(cluster) == 0)
+            .count() as u32;
+        if let Some(next_free) = table.read_fs_info(block_device)?
+            && sb.is_valid_cluster(next_free)
+        {
+            table.next_free = next_free;
+        }
+        Ok(table)
+    }
+
+    pub fn num_free_clusters(&self) -> u32 {
+        self.num_free
+    }
+
+    pub fn get(&self, cluster: ClusterID) -> Result<FatValue> {
+        if !self.sb.is_valid_cluster(cluster) {
+            return_errno_with_message!(Errno::EIO, "invalid access to FAT");
+        }
+        Ok(FatValue::from_raw(self.get_raw(cluster), self.sb.fat_type))
+    }
+
+    /// Collects the clusters of the chain that starts from `start`.
+    pub fn chain(&self, start: ClusterID) -> Result<Vec<ClusterID>> {
+        let mut clusters = Vec::new();
+        let mut cluster = start;
+        loop {
+            // A chain longer than the volume must contain a loop.
+            if clusters.len() >= self.sb.num_clusters as usize {
+                return_errno_with_message!(Errno::EIO, "loop in the cluster chain");
+            }
+            clusters.push(cluster);
+            match self.get(cluster)? {
+                FatValue::Next(next) => cluster = next,
+                FatValue::EndOfChain => return Ok(clusters),
+                FatValue::Free | FatValue::Bad => {
+                    return_errno_with_message!(Errno::EIO, "corrupted cluster chain")
+                }
+            }
+        }
+    }
+
+    /// Allocates `count` clusters and appends them to the chain ending with `last`,
+    /// or makes them a new chain if `last` is `None`.
+    pub fn alloc(
+        &mut self,
+        block_device: &dyn BlockDevice,
+        count: usize,
+        last: Option<ClusterID>,
+    ) -> Result<Vec<ClusterID>> {
+        if count > self.num_free as usize {
+            return_errno_with_message!(Errno::ENOSPC, "no free clusters");
+        }
+
+        let mut new_clusters = Vec::with_capacity(count);
+        let mut prev = last;
+        let mut cluster = self.next_free;
+        while new_clusters.len() < count {
+            if !self.sb.is_valid_cluster(cluster) {
+                cluster = FAT_RESERVED_CLUSTERS;
+            }
+            if self.get_raw(cluster) == 0 {
+                self.set(cluster, FatValue::EndOfChain);
+                if let Some(prev) = prev {
+                    self.set(prev, FatValue::Next(cluster));
+                }
+                prev = Some(cluster);
+                new_clusters.push(cluster);
+            }
+            cluster += 1;
+        }
+        self.num_free -= count as u32;
+        self.next_free = cluster;
+
+        self.flush(block_device)?;
+        Ok(new_clusters)
+    }
+
+    /// Frees the clusters, and terminates the chain at `new_last` if it is not `None`.
+    pub fn free(
+        &mut self,
+        block_device: &dyn BlockDevice,
+        clusters: &[ClusterID],
+        new_last: Option<ClusterID>,
+    ) -> Result<()> {
+        if let Some(new_last) = new_last {
+            self.set(new_last, FatValue::EndOfChain);
+        }
+        for &cluster in clusters {
+            if self.get_raw(cluster) != 0 {
+                self.set(cluster, FatValue::Free);
+                self.num_free += 1;
+            }
+        }
+        if let Some(&first) = clusters.first() {
+            self.next_free = self.next_free.min(first);
+        }
+        self.flush(block_device)
+    }
+
+    /// Writes the free cluster count and the next free cluster to the FSInfo sector.
+    pub fn sync_fs_info(&self, block_device: &dyn BlockDevice) -> Result<()> {
+        let Some(fs_info_start) = self.sb.fs_info_start else {
+            return Ok(());
+        };
+        let mut sector = [0u8; BOOT_SECTOR_SIZE];
+        block_device.read_bytes(fs_info_start, &mut sector)?;
+        if !is_valid_fs_info(&sector) {
+            return Ok(());
+        }
+        sector[FSINFO_FREE_COUNT_OFFSET..FSINFO_FREE_COUNT_OFFSET + 4]
+            .copy_from_slice(&self.num_free.to_le_bytes());
+        sector[FSINFO_NEXT_FREE_OFFSET..FSINFO_NEXT_FREE_OFFSET + 4]
+            .copy_from_slice(&self.next_free.to_le_bytes());
+        block_device.write_bytes(fs_info_start, &sector)?;
+        Ok(())
+    }
+
+    fn read_fs_info(&self, block_device: &dyn BlockDevice) -> Result<Option<ClusterID>> {
+        let Some(fs_info_start) = self.sb.fs_info_start else {
+            return Ok(None);
+        };
+        let mut sector = [0u8; BOOT_SECTOR_SIZE];
+        block_device.read_bytes(fs_info_start, &mut sector)?;
+        if !is_valid_fs_info(&sector) {
+            warn!("invalid FSInfo sector of FAT32");
+            return Ok(None);
+        }
+        let next_free = read_u32(&sector, FSINFO_NEXT_FREE_OFFSET);
+        Ok((next_free != FSINFO_UNKNOWN).then_some(next_free))
+    }
+
+    fn clusters(&self) -> Range<ClusterID> {
+        FAT_RESERVED_CLUSTERS..self.sb.num_clusters + FAT_RESERVED_CLUSTERS
+    }
+
+    fn get_raw(&self, cluster: ClusterID) -> u32 {
+        let cluster = cluster as usize;
+        match self.sb.fat_type {
+            FatType::Fat12 => {
+                let offset = cluster * 3 / 2;
+                let value = u16::from_le_bytes([self.raw[offset], self.raw[offset + 1]]);
+                if cluster & 1 == 1 {
+                    (value >> 4) as u32
+                } else {
+                    (value & 0xFFF) as u32
+                }
+            }
+            FatType::Fat16 => {
+                let offset = cluster * 2;
+                u16::from_le_bytes([self.raw[offset], self.raw[offset + 1]]) as u32
+            }
+            FatType::Fat32 => read_u32(&self.raw, cluster * 4) & 0x0FFF_FFFF,
+        }
+    }
+
+    fn set(&mut self, cluster: ClusterID, value: FatValue) {
+        let raw_value = value.into_raw(self.sb.fat_type);
+        let cluster = cluster as usize;
+        let range = match self.sb.fat_type {
+            FatType::Fat12 => {
+                let offset = cluster * 3 / 2;
+                if cluster & 1 == 1 {
+                    self.raw[offset] = (self.raw[offset] & 0x0F) | ((raw_value << 4) as u8);
+                    self.raw[offset + 1] = (raw_value >> 4) as u8;
+                } else {
+                    self.raw[offset] = raw_value as u8;
+                    self.raw[offset + 1] =
+                        (self.raw[offset + 1] & 0xF0) | ((raw_value >> 8) as u8 & 0x0F);
+                }
+                offset..offset + 2
+            }
+            FatType::Fat16 => {
+                let offset = cluster * 2;
+                self.raw[offset..offset + 2].copy_from_slice(&(raw_value as u16).to_le_bytes());
+                offset..offset + 2
+            }
+            FatType::Fat32 => {
+                // The high 4 bits of a FAT32 entry are reserved and must be preserved.
+                let offset = cluster * 4;
+                let raw_value = (read_u32(&self.raw, offset) & 0xF000_0000) | raw_value;
+                self.raw[offset..offset + 4].copy_from_slice(&raw_value.to_le_bytes());
+                offset..offset + 4
+            }
+        };
+
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(range.start)..dirty.end.max(range.end),
+            None => range,
+        });
+    }
+
+    /// Writes the modified sectors back to all the FAT copies.
+    fn flush(&mut self, block_device: &dyn BlockDevice) -> Result<()> {
+        let Some(dirty) = self.dirty.take() else {
+            return Ok(());
+        };
+        let start = dirty.start.align_down(SECTOR_SIZE);
+        let end = dirty.end.align_up(SECTOR_SIZE).min(self.raw.len());
+        for fat_idx in 0..self.sb.num_fats {
+            let fat_start = self.sb.fat_start + fat_idx * self.sb.fat_size;
+            block_device.write_bytes(fat_start + start, &self.raw[start..end])?;
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_fs_info(sector: &[u8]) -> bool {
+    read_u32(sector, 0) == FSINFO_LEAD_SIGNATURE
+        && read_u32(sector, FSINFO_STRUCT_OFFSET) == FSINFO_STRUCT_SIGNATURE
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
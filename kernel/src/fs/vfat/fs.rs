@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use aster_block::BlockDevice;
+use hashbrown::HashMap;
+use ostd::mm::VmIo;
+
+use super::{
+    constants::*,
+    fat::{ClusterID, FatTable},
+    inode::{Ino, InodeData, VfatInode},
+    super_block::{FatBootSector, VfatSuperBlock},
+};
+use crate::{
+    fs::utils::{FileSystem, FsFlags, Inode, MountOption, SuperBlock},
+    prelude::*,
+};
+
+/// A FAT12/16/32 file system with long name support, a.k.a. vfat.
+#[derive(Debug)]
+pub struct VfatFS {
+    block_device: Arc<dyn BlockDevice>,
+    super_block: VfatSuperBlock,
+    fat: Mutex<FatTable>,
+    mount_options: VfatMountOptions,
+    /// Used for inode allocation.
+    next_ino: AtomicU64,
+    /// The opened inodes, indexed by the inode number of the parent directory
+    /// and the offset of the short dentry in it.
+    inodes: RwMutex<HashMap<(Ino, usize), Arc<VfatInode>>>,
+    root: Arc<VfatInode>,
+    /// A global lock, which serializes the operations that modify directories.
+    mutex: Mutex<()>,
+}
+
+impl VfatFS {
+    pub fn open(
+        block_device: Arc<dyn BlockDevice>,
+        mount_options: VfatMountOptions,
+    ) -> Result<Arc<Self>> {
+        let super_block = Self::read_super_block(block_device.as_ref())?;
+        let fat = FatTable::load(block_device.as_ref(), &super_block)?;
+        let root_clusters = if super_block.root_dir_size == 0 {
+            Some(fat.chain(super_block.root_cluster)?)
+        } else {
+            None
+        };
+
+        let root_data = InodeData::new_root(block_device.clone(), &super_block, root_clusters);
+        let root_page_cache = root_data.new_page_cache(root_data.allocated_size())?;
+
+        let vfat_fs = Arc::new_cyclic(|weak_fs| Self {
+            block_device,
+            super_block,
+            fat: Mutex::new(fat),
+            root: VfatInode::new_root(weak_fs.clone(), root_data, root_page_cache),
+            mount_options,
+            next_ino: AtomicU64::new(VFAT_ROOT_INO + 1),
+            inodes: RwMutex::new(HashMap::new()),
+            mutex: Mutex::new(()),
+        });
+        vfat_fs.root.init_root()?;
+        Ok(vfat_fs)
+    }
+
+    fn read_super_block(block_device: &dyn BlockDevice) -> Result<VfatSuperBlock> {
+        let mut sector = [0u8; BOOT_SECTOR_SIZE];
+        block_device.read_bytes(0, &mut sector)?;
+        let signature = u16::from_le_bytes([sector[510], sector[511]]);
+        if signature != BOOT_SIGNATURE {
+            return_errno_with_message!(Errno::EINVAL, "invalid boot sector signature");
+        }
+        // The jump instruction is either a short jump or a near jump.
+        if sector[0] != 0xEB && sector[0] != 0xE9 {
+            return_errno_with_message!(Errno::EINVAL, "invalid boot sector jump instruction");
+        }
+
+        let boot_sector =
+            FatBootSector::from_bytes(&sector[..core::mem::size_of::<FatBootSector>()]);
+        VfatSuperBlock::try_from(&boot_sector)
+    }
+
+    pub(super) fn alloc_ino(&self) -> Ino {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub(super) fn find_inode(&self, parent_ino: Ino, offset: usize) -> Option<Arc<VfatInode>> {
+        self.inodes.read().get(&(parent_ino, offset)).cloned()
+    }
+
+    pub(super) fn insert_inode(&self, parent_ino: Ino, offset: usize, inode: Arc<VfatInode>) {
+        self.inodes.write().insert((parent_ino, offset), inode);
+    }
+
+    pub(super) fn remove_inode(&self, parent_ino: Ino, offset: usize) -> Option<Arc<VfatInode>> {
+        self.inodes.write().remove(&(parent_ino, offset))
+    }
+
+    pub(super) fn block_device(&self) -> &dyn BlockDevice {
+        self.block_device.as_ref()
+    }
+
+    /// Creates the data of an inode in the clusters of the file system.
+    pub(super) fn new_inode_data(&self, clusters: Vec<ClusterID>) -> Arc<InodeData> {
+        InodeData::with_clusters(clusters, self.block_device.clone(), &self.super_block)
+    }
+
+    pub(super) fn super_block(&self) -> &VfatSuperBlock {
+        &self.super_block
+    }
+
+    pub(super) fn fat(&self) -> MutexGuard<FatTable> {
+        self.fat.lock()
+    }
+
+    pub(super) fn mount_options(&self) -> &VfatMountOptions {
+        &self.mount_options
+    }
+
+    pub(super) fn cluster_size(&self) -> usize {
+        self.super_block.cluster_size
+    }
+
+    pub(super) fn lock(&self) -> MutexGuard<()> {
+        self.mutex.lock()
+    }
+}
+
+impl FileSystem for VfatFS {
+    fn sync(&self) -> Result<()> {
+        let inodes = self.inodes.read().values().cloned().collect::<Vec<_>>();
+        for inode in inodes.iter().chain(core::iter::once(&self.root)) {
+            inode.sync_page_cache()?;
+        }
+        self.fat().sync_fs_info(self.block_device())?;
+        self.block_device.sync()?;
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        let mut sb = SuperBlock::new(
+            MSDOS_SUPER_MAGIC,
+            self.super_block.cluster_size,
+            MAX_LONG_NAME_LEN,
+        );
+        sb.blocks = self.super_block.num_clusters as usize;
+        sb.bfree = self.fat().num_free_clusters() as usize;
+        sb.bavail = sb.bfree;
+        sb.fsid = self.super_block.volume_id as u64;
+        sb
+    }
+
+    fn flags(&self) -> FsFlags {
+        FsFlags::DENTRY_UNEVICTABLE
+    }
+
+    fn name(&self) -> &'static str {
+        "vfat"
+    }
+}
+
+/// How short names are displayed and when long names are stored.
+///
+/// See the `shortname` option in <https://docs.kernel.org/filesystems/vfat.html>.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VfatShortName {
+    /// Displays short names in lowercase.
+    Lower,
+    /// Displays short names in uppercase.
+    Win95,
+    /// Displays short names as is, and stores names that are all in lowercase
+    /// or all in uppercase without long names.
+    WinNT,
+    /// Displays short names as is, and stores names that are all in uppercase
+    /// without long names.
+    #[default]
+    Mixed,
+}
+
+/// The charset of the names seen by the users.
+///
+/// Names are always passed as UTF-8 strings, so a charset other than UTF-8
+/// only restricts the characters that can be used in names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VfatCharset {
+    #[default]
+    Utf8,
+    Iso8859_1,
+    Ascii,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VfatErrorMode {
+    #[default]
+    Continue,
+    Panic,
+    ReadOnly,
+}
+
+/// The mount options of a vfat file system.
+#[derive(Clone, Debug)]
+pub struct VfatMountOptions {
+    pub(super) fs_uid: u32,
+    pub(super) fs_gid: u32,
+    pub(super) fs_fmask: u16,
+    pub(super) fs_dmask: u16,
+    pub(super) codepage: u16,
+    pub(super) charset: VfatCharset,
+    pub(super) shortname: VfatShortName,
+    pub(super) showexec: bool,
+    pub(super) errors: VfatErrorMode,
+    /// The offset of the local time, in which the timestamps are stored, from UTC in minutes.
+    pub(super) time_offset: i32,
+}
+
+impl Default for VfatMountOptions {
+    fn default() -> Self {
+        Self {
+            fs_uid: 0,
+            fs_gid: 0,
+            fs_fmask: 0o022,
+            fs_dmask: 0o022,
+            codepage: 437,
+            charset: VfatCharset::default(),
+            shortname: VfatShortName::default(),
+            showexec: false,
+            errors: VfatErrorMode::default(),
+            time_offset: 0,
+        }
+    }
+}
+
+impl VfatMountOptions {
+    /// Parses the mount options from the `data` argument of `mount()`,
+    /// e.g., `uid=1000,umask=022,shortname=winnt,utf8`.
+    ///
+    /// See <https://docs.kernel.org/filesystems/vfat.html>.
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for option in MountOption::parse_all(data) {
+            match option.key() {
+                "uid" => options.fs_uid = option.parse_value()?,
+                "gid" => options.fs_gid = option.parse_value()?,
+                "umask" => {
+                    let umask = parse_mask(&option)?;
+                    options.fs_fmask = umask;
+                    options.fs_dmask = umask;
+                }
+                "fmask" => options.fs_fmask = parse_mask(&option)?,
+                "dmask" => options.fs_dmask = parse_mask(&option)?,
+                "codepage" => {
+                    // Only the default code page is built in.
+                    let codepage: u16 = option.parse_value()?;
+                    if codepage != 437 {
+                        return_errno_with_message!(Errno::EINVAL, "unsupported vfat codepage");
+                    }
+                    options.codepage = codepage;
+                }
+                "iocharset" => {
+                    options.charset = match option.value()? {
+                        "utf8" | "utf-8" => VfatCharset::Utf8,
+                        "iso8859-1" => VfatCharset::Iso8859_1,
+                        "ascii" => VfatCharset::Ascii,
+                        _ => {
+                            return_errno_with_message!(Errno::EINVAL, "unsupported vfat iocharset")
+                        }
+                    }
+                }
+                "utf8" => {
+                    option.check_flag()?;
+                    options.charset = VfatCharset::Utf8;
+                }
+                "shortname" => {
+                    options.shortname = match option.value()? {
+                        "lower" => VfatShortName::Lower,
+                        "win95" => VfatShortName::Win95,
+                        "winnt" => VfatShortName::WinNT,
+                        "mixed" => VfatShortName::Mixed,
+                        _ => return_errno_with_message!(Errno::EINVAL, "invalid vfat shortname"),
+                    }
+                }
+                "showexec" => {
+                    option.check_flag()?;
+                    options.showexec = true;
+                }
+                "errors" => {
+                    options.errors = match option.value()? {
+                        "continue" => VfatErrorMode::Continue,
+                        "panic" => VfatErrorMode::Panic,
+                        "remount-ro" => VfatErrorMode::ReadOnly,
+                        _ => return_errno_with_message!(Errno::EINVAL, "invalid vfat error mode"),
+                    }
+                }
+                "tz" => {
+                    if option.value()? != "UTC" {
+                        return_errno_with_message!(Errno::EINVAL, "invalid vfat time zone");
+                    }
+                    options.time_offset = 0;
+                }
+                "time_offset" => {
+                    let time_offset: i32 = option.parse_value()?;
+                    // The offset is in minutes and must be within a day.
+                    if !(-24 * 60..=24 * 60).contains(&time_offset) {
+                        return_errno_with_message!(Errno::EINVAL, "invalid vfat time offset");
+                    }
+                    options.time_offset = time_offset;
+                }
+                // These options do not change the behavior of our implementation.
+                "allow_utime" | "check" | "quiet" | "flush" | "discard" | "rodir" => {}
+                _ => return_errno_with_message!(Errno::EINVAL, "unknown vfat mount option"),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_mask(option: &MountOption) -> Result<u16> {
+    let mask = option.parse_octal_value()?;
+    if mask > 0o777 {
+        return_errno_with_message!(Errno::EINVAL, "invalid mask");
+    }
+    Ok(mask as u16)
+}
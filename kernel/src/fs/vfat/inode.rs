@@ -0,0 +1,1168 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{ops::Range, time::Duration};
+
+use aster_block::{bio::BioWaiter, BlockDevice};
+use aster_rights::Full;
+use ostd::mm::{UntypedMem, VmIo};
+
+use super::{
+    constants::*,
+    dentry::{
+        check_long_name, name_eq, FatAttr, NameDentries, ShortDentry, VfatDirEntry, VfatDirIter,
+        DOTDOT_NAME, DOT_NAME,
+    },
+    fat::ClusterID,
+    fs::VfatFS,
+    super_block::VfatSuperBlock,
+    utils::DosTimestamp,
+};
+use crate::{
+    events::IoEvents,
+    fs::utils::{
+        CachePage, DirentVisitor, Extension, FileSystem, Inode, InodeMode, InodeType, IoctlCmd,
+        Metadata, MknodType, PageCache, PageCacheBackend,
+    },
+    prelude::*,
+    process::{signal::PollHandle, Gid, Uid},
+    vm::vmo::Vmo,
+};
+
+/// Inode number.
+pub type Ino = u64;
+
+/// The maximum size of a file, which is limited by the 32-bit size field.
+const MAX_FILE_SIZE: usize = u32::MAX as usize;
+
+#[derive(Debug)]
+pub struct VfatInode {
+    ino: Ino,
+    type_: InodeType,
+    /// Where the data of the inode are on the device, which is the backend
+    /// of the page cache.
+    data: Arc<InodeData>,
+    inner: RwMutex<VfatInodeInner>,
+    /// The page cache of the file data or the directory dentries.
+    page_cache: PageCache,
+    this: Weak<VfatInode>,
+    fs: Weak<VfatFS>,
+    extension: Extension,
+}
+
+#[derive(Debug)]
+struct VfatInodeInner {
+    /// The parent directory and the offset of the short dentry in it,
+    /// which is `None` for the root directory.
+    position: Option<(Arc<VfatInode>, usize)>,
+    /// The short dentry, which holds the metadata of the inode.
+    ///
+    /// The changes are written through to the page cache of the parent directory.
+    dentry: ShortDentry,
+    /// The size of a file, or the allocated size of a directory.
+    size: usize,
+    /// The number of sub-directories of a directory.
+    num_subdirs: usize,
+    is_deleted: bool,
+}
+
+/// Where the data of an inode are on the device.
+///
+/// It is separated from the inode, so that the page cache can be created
+/// before the inode, and from `inner`, since it is needed by the page cache,
+/// which may be accessed with `inner` locked.
+#[derive(Debug)]
+pub(super) struct InodeData {
+    layout: RwMutex<Layout>,
+    block_device: Arc<dyn BlockDevice>,
+    sb: VfatSuperBlock,
+}
+
+#[derive(Debug)]
+enum Layout {
+    /// The clusters of a file or a directory, which is empty for an empty file.
+    Clusters(Vec<ClusterID>),
+    /// The fixed root directory region of FAT12/16.
+    FixedRoot { start: usize, size: usize },
+}
+
+impl Layout {
+    fn allocated_size(&self, cluster_size: usize) -> usize {
+        match self {
+            Layout::Clusters(clusters) => clusters.len() * cluster_size,
+            Layout::FixedRoot { size, .. } => *size,
+        }
+    }
+
+    fn first_cluster(&self) -> ClusterID {
+        match self {
+            Layout::Clusters(clusters) => clusters.first().copied().unwrap_or(0),
+            Layout::FixedRoot { .. } => 0,
+        }
+    }
+
+    /// Maps a byte range of the inode to the contiguous runs on the device,
+    /// each of which is a device offset and the corresponding range of the inode.
+    ///
+    /// The part of the range beyond the allocated size is not mapped.
+    fn device_runs(&self, range: Range<usize>, sb: &VfatSuperBlock) -> Vec<(usize, Range<usize>)> {
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        match self {
+            Layout::Clusters(clusters) => {
+                let cluster_size = sb.cluster_size;
+                let end = range.end.min(clusters.len() * cluster_size);
+                let mut pos = range.start;
+                while pos < end {
+                    let offset_in_cluster = pos % cluster_size;
+                    let len = (cluster_size - offset_in_cluster).min(end - pos);
+                    let device_offset =
+                        sb.cluster_offset(clusters[pos / cluster_size]) + offset_in_cluster;
+                    match runs.last_mut() {
+                        Some((run_offset, run_range))
+                            if *run_offset + run_range.len() == device_offset =>
+                        {
+                            run_range.end += len;
+                        }
+                        _ => runs.push((device_offset, pos..pos + len)),
+                    }
+                    pos += len;
+                }
+            }
+            Layout::FixedRoot { start, size } => {
+                let end = range.end.min(*size);
+                if range.start < end {
+                    runs.push((start + range.start, range.start..end));
+                }
+            }
+        }
+        runs
+    }
+}
+
+impl InodeData {
+    fn new(layout: Layout, block_device: Arc<dyn BlockDevice>, sb: VfatSuperBlock) -> Arc<Self> {
+        Arc::new(Self {
+            layout: RwMutex::new(layout),
+            block_device,
+            sb,
+        })
+    }
+
+    /// Creates the data of a file or a directory in the clusters.
+    pub(super) fn with_clusters(
+        clusters: Vec<ClusterID>,
+        block_device: Arc<dyn BlockDevice>,
+        sb: &VfatSuperBlock,
+    ) -> Arc<Self> {
+        Self::new(Layout::Clusters(clusters), block_device, *sb)
+    }
+
+    /// Creates the data of the root directory, which is either the root
+    /// cluster chain of FAT32 or the fixed root directory region of FAT12/16.
+    pub(super) fn new_root(
+        block_device: Arc<dyn BlockDevice>,
+        sb: &VfatSuperBlock,
+        clusters: Option<Vec<ClusterID>>,
+    ) -> Arc<Self> {
+        let layout = match clusters {
+            Some(clusters) => Layout::Clusters(clusters),
+            None => Layout::FixedRoot {
+                start: sb.root_dir_start,
+                size: sb.root_dir_size,
+            },
+        };
+        Self::new(layout, block_device, *sb)
+    }
+
+    /// Creates a page cache of `size` bytes backed by the data.
+    pub(super) fn new_page_cache(self: &Arc<Self>, size: usize) -> Result<PageCache> {
+        PageCache::with_capacity(size, Arc::downgrade(self) as _)
+    }
+
+    pub(super) fn allocated_size(&self) -> usize {
+        self.layout.read().allocated_size(self.sb.cluster_size)
+    }
+}
+
+impl PageCacheBackend for InodeData {
+    fn read_page_async(&self, idx: usize, frame: &CachePage) -> Result<BioWaiter> {
+        let page_start = idx * PAGE_SIZE;
+        // The layout is locked during the I/O, so the clusters cannot be freed
+        // and reused by others in the meantime.
+        let layout = self.layout.read();
+        let runs = layout.device_runs(page_start..page_start + PAGE_SIZE, &self.sb);
+
+        let mut read_len = 0;
+        for (device_offset, range) in runs {
+            let offset_in_page = range.start - page_start;
+            let mut writer = frame
+                .writer()
+                .skip(offset_in_page)
+                .limit(range.len())
+                .to_fallible();
+            self.block_device.read(device_offset, &mut writer)?;
+            read_len = offset_in_page + range.len();
+        }
+        // The part beyond the allocated clusters is read as zeros.
+        frame.writer().skip(read_len).fill(0u8);
+
+        Ok(BioWaiter::new())
+    }
+
+    fn write_page_async(&self, idx: usize, frame: &CachePage) -> Result<BioWaiter> {
+        let page_start = idx * PAGE_SIZE;
+        let layout = self.layout.read();
+        let runs = layout.device_runs(page_start..page_start + PAGE_SIZE, &self.sb);
+
+        // The part beyond the allocated clusters is dropped.
+        for (device_offset, range) in runs {
+            let mut reader = frame
+                .reader()
+                .skip(range.start - page_start)
+                .limit(range.len())
+                .to_fallible();
+            self.block_device.write(device_offset, &mut reader)?;
+        }
+
+        Ok(BioWaiter::new())
+    }
+
+    fn npages(&self) -> usize {
+        self.allocated_size().div_ceil(PAGE_SIZE)
+    }
+}
+
+impl VfatInodeInner {
+    /// Writes the short dentry to the parent directory.
+    fn write_dentry(&self) -> Result<()> {
+        if self.is_deleted {
+            return Ok(());
+        }
+        let Some((parent, offset)) = &self.position else {
+            return Ok(());
+        };
+        parent.page_cache.pages().write_val(*offset, &self.dentry)?;
+        Ok(())
+    }
+
+    fn parent_ino(&self) -> Option<Ino> {
+        self.position.as_ref().map(|(parent, _)| parent.ino)
+    }
+}
+
+impl VfatInode {
+    /// Creates the root inode from its data and page cache, which are created
+    /// before the file system, so that this can be done along with the file system.
+    pub(super) fn new_root(
+        fs: Weak<VfatFS>,
+        data: Arc<InodeData>,
+        page_cache: PageCache,
+    ) -> Arc<Self> {
+        let size = data.allocated_size();
+        let mut dentry = ShortDentry::new(
+            [b' '; SHORT_NAME_LEN],
+            0,
+            FatAttr::DIRECTORY,
+            DosTimestamp::default(),
+        );
+        dentry.set_first_cluster(data.layout.read().first_cluster());
+
+        Self::with_page_cache(
+            fs,
+            VFAT_ROOT_INO,
+            InodeType::Dir,
+            data,
+            page_cache,
+            VfatInodeInner {
+                position: None,
+                dentry,
+                size,
+                num_subdirs: 0,
+                is_deleted: false,
+            },
+        )
+    }
+
+    /// Counts the sub-directories of the root, which can only be done
+    /// after the file system is built.
+    pub(super) fn init_root(&self) -> Result<()> {
+        let num_subdirs = self.count_subdirs()?;
+        self.inner.write().num_subdirs = num_subdirs;
+        Ok(())
+    }
+
+    fn new(
+        fs: Weak<VfatFS>,
+        ino: Ino,
+        type_: InodeType,
+        data: Arc<InodeData>,
+        inner: VfatInodeInner,
+    ) -> Result<Arc<Self>> {
+        let page_cache = data.new_page_cache(inner.size)?;
+        Ok(Self::with_page_cache(
+            fs, ino, type_, data, page_cache, inner,
+        ))
+    }
+
+    fn with_page_cache(
+        fs: Weak<VfatFS>,
+        ino: Ino,
+        type_: InodeType,
+        data: Arc<InodeData>,
+        page_cache: PageCache,
+        inner: VfatInodeInner,
+    ) -> Arc<Self> {
+        Arc::new_cyclic(|weak_self| Self {
+            ino,
+            type_,
+            data,
+            inner: RwMutex::new(inner),
+            page_cache,
+            this: weak_self.clone(),
+            fs,
+            extension: Extension::new(),
+        })
+    }
+
+    fn fs(&self) -> Arc<VfatFS> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn this(&self) -> Arc<VfatInode> {
+        self.this.upgrade().unwrap()
+    }
+
+    fn is_root(&self) -> bool {
+        self.ino == VFAT_ROOT_INO
+    }
+
+    /// Writes back the dirty pages of the page cache.
+    pub(super) fn sync_page_cache(&self) -> Result<()> {
+        let size = self.inner.read().size;
+        self.page_cache.evict_range(0..size)
+    }
+
+    fn make_mode(&self, inner: &VfatInodeInner) -> InodeMode {
+        let options = self.fs().mount_options().clone();
+        let attr = inner.dentry.attr();
+        let mode = if self.type_ == InodeType::Dir {
+            0o777 & !options.fs_dmask
+        } else {
+            let mut mode = 0o777 & !options.fs_fmask;
+            if attr.contains(FatAttr::READONLY) {
+                mode &= !0o222;
+            }
+            if options.showexec && !inner.dentry.has_exec_ext() {
+                mode &= !0o111;
+            }
+            mode
+        };
+        InodeMode::from_bits_truncate(mode)
+    }
+
+    /// Returns the cluster of the directory to be stored in the ".." dentry of its children.
+    fn dotdot_cluster(&self) -> ClusterID {
+        if self.is_root() {
+            0
+        } else {
+            self.data.layout.read().first_cluster()
+        }
+    }
+
+    fn num_clusters(&self) -> usize {
+        match &*self.data.layout.read() {
+            Layout::Clusters(clusters) => clusters.len(),
+            Layout::FixedRoot { .. } => 0,
+        }
+    }
+
+    /// Allocates clusters at the end of the inode.
+    fn alloc_clusters(&self, inner: &mut VfatInodeInner, num_clusters: usize) -> Result<()> {
+        let fs = self.fs();
+        let mut layout = self.data.layout.write();
+        let Layout::Clusters(clusters) = &mut *layout else {
+            return_errno_with_message!(Errno::ENOSPC, "the root directory is full");
+        };
+        let new_clusters =
+            fs.fat()
+                .alloc(fs.block_device(), num_clusters, clusters.last().copied())?;
+        if clusters.is_empty() {
+            inner.dentry.set_first_cluster(new_clusters[0]);
+        }
+        clusters.extend(new_clusters);
+        Ok(())
+    }
+
+    /// Frees the clusters of the inode except the first `num_kept` ones.
+    fn free_clusters(&self, inner: &mut VfatInodeInner, num_kept: usize) -> Result<()> {
+        let fs = self.fs();
+        let mut layout = self.data.layout.write();
+        let Layout::Clusters(clusters) = &mut *layout else {
+            return Ok(());
+        };
+        if num_kept >= clusters.len() {
+            return Ok(());
+        }
+        let freed = clusters.split_off(num_kept);
+        fs.fat()
+            .free(fs.block_device(), &freed, clusters.last().copied())?;
+        if clusters.is_empty() {
+            inner.dentry.set_first_cluster(0);
+        }
+        Ok(())
+    }
+
+    fn resize_file(&self, inner: &mut VfatInodeInner, new_size: usize) -> Result<()> {
+        if new_size > MAX_FILE_SIZE {
+            return_errno_with_message!(Errno::EFBIG, "the file is too large for vfat");
+        }
+
+        let old_size = inner.size;
+        let num_clusters = new_size.div_ceil(self.fs().cluster_size());
+        if new_size > old_size {
+            let num_allocated = self.num_clusters();
+            if num_clusters > num_allocated {
+                self.alloc_clusters(inner, num_clusters - num_allocated)?;
+            }
+            // The newly allocated clusters contain stale data on disk.
+            self.page_cache.resize(new_size)?;
+            self.page_cache.fill_zeros(old_size..new_size)?;
+        } else if new_size < old_size {
+            // Shrink the page cache before freeing the clusters,
+            // so that the dirty pages will not be written to the freed clusters.
+            self.page_cache.resize(new_size)?;
+            self.free_clusters(inner, num_clusters)?;
+        }
+
+        inner.size = new_size;
+        inner.dentry.size = new_size as u32;
+        Ok(())
+    }
+
+    /// Expands the directory to hold at least `min_size` bytes of dentries.
+    fn expand_dir(&self, min_size: usize) -> Result<()> {
+        let cluster_size = self.fs().cluster_size();
+        let mut inner = self.inner.write();
+        let old_size = inner.size;
+        if min_size <= old_size {
+            return Ok(());
+        }
+        let num_new_clusters = (min_size - old_size).div_ceil(cluster_size);
+        self.alloc_clusters(&mut inner, num_new_clusters)?;
+
+        let new_size = old_size + num_new_clusters * cluster_size;
+        self.page_cache.resize(new_size)?;
+        self.page_cache.fill_zeros(old_size..new_size)?;
+        inner.size = new_size;
+        Ok(())
+    }
+
+    fn read_dir_data(&self) -> Result<Vec<u8>> {
+        let size = self.inner.read().size;
+        let mut data = vec![0u8; size];
+        self.page_cache.pages().read_bytes(0, &mut data)?;
+        Ok(data)
+    }
+
+    fn find_entry(&self, data: &[u8], name: &str) -> Option<VfatDirEntry> {
+        let fs = self.fs();
+        let name = name.trim_end_matches('.');
+        VfatDirIter::new(data, 0, fs.mount_options()).find(|entry| name_eq(&entry.name, name))
+    }
+
+    /// Gets the inode of a directory entry, which is loaded if it is not opened yet.
+    fn get_child(&self, entry: &VfatDirEntry) -> Result<Arc<VfatInode>> {
+        let fs = self.fs();
+        if let Some(inode) = fs.find_inode(self.ino, entry.offset) {
+            return Ok(inode);
+        }
+
+        let dentry = entry.dentry;
+        let is_dir = dentry.attr().contains(FatAttr::DIRECTORY);
+        let first_cluster = dentry.first_cluster();
+        let clusters = if first_cluster == 0 {
+            Vec::new()
+        } else if fs.super_block().is_valid_cluster(first_cluster) {
+            fs.fat().chain(first_cluster)?
+        } else {
+            return_errno_with_message!(Errno::EIO, "invalid first cluster");
+        };
+
+        let allocated_size = clusters.len() * fs.cluster_size();
+        let size = if is_dir {
+            if clusters.is_empty() {
+                return_errno_with_message!(Errno::EIO, "directory without clusters");
+            }
+            allocated_size
+        } else {
+            if dentry.size as usize > allocated_size {
+                warn!("the size of a vfat file is larger than its clusters");
+            }
+            (dentry.size as usize).min(allocated_size)
+        };
+
+        let inode = Self::new(
+            self.fs.clone(),
+            fs.alloc_ino(),
+            if is_dir {
+                InodeType::Dir
+            } else {
+                InodeType::File
+            },
+            fs.new_inode_data(clusters),
+            VfatInodeInner {
+                position: Some((self.this(), entry.offset)),
+                dentry,
+                size,
+                num_subdirs: 0,
+                is_deleted: false,
+            },
+        )?;
+        if is_dir {
+            inode.inner.write().num_subdirs = inode.count_subdirs()?;
+        }
+        fs.insert_inode(self.ino, entry.offset, inode.clone());
+        Ok(inode)
+    }
+
+    fn count_subdirs(&self) -> Result<usize> {
+        let fs = self.fs();
+        let data = self.read_dir_data()?;
+        Ok(VfatDirIter::new(&data, 0, fs.mount_options())
+            .filter(|entry| entry.dentry.attr().contains(FatAttr::DIRECTORY))
+            .count())
+    }
+
+    /// Writes the dentries of a new name, and returns the offset of the short dentry.
+    fn add_dentries(&self, names: &NameDentries, dentry: &mut ShortDentry) -> Result<usize> {
+        let num_dentries = names.num_dentries();
+        let data = self.read_dir_data()?;
+        let start = match find_free_dentries(&data, num_dentries) {
+            Ok(start) => start,
+            Err(tail_start) => {
+                self.expand_dir(tail_start + num_dentries * DENTRY_SIZE)?;
+                tail_start
+            }
+        };
+        let end = start + num_dentries * DENTRY_SIZE;
+
+        let pages = self.page_cache.pages();
+        for (idx, long_dentry) in names.long_dentries.iter().enumerate() {
+            pages.write_val(start + idx * DENTRY_SIZE, long_dentry)?;
+        }
+        dentry.name = names.short_name;
+        dentry.nt_res = names.nt_res;
+        pages.write_val(end - DENTRY_SIZE, dentry)?;
+
+        // The dentries after an end marker may contain garbage,
+        // so the end marker is moved after the new dentries.
+        let data_end = end.min(data.len());
+        let used_end_marker = data[start..data_end]
+            .chunks_exact(DENTRY_SIZE)
+            .any(|raw| raw[0] == DENTRY_END);
+        if used_end_marker && end < data.len() {
+            pages.write_val(end, &DENTRY_END)?;
+        }
+
+        Ok(end - DENTRY_SIZE)
+    }
+
+    /// Removes the dentries of a directory entry, and returns its inode.
+    fn remove_dentries(&self, entry: &VfatDirEntry) -> Result<Arc<VfatInode>> {
+        let inode = self.get_child(entry)?;
+        let pages = self.page_cache.pages();
+        for idx in 0..entry.num_dentries {
+            pages.write_val(entry.start_offset() + idx * DENTRY_SIZE, &DENTRY_DELETED)?;
+        }
+        self.fs().remove_inode(self.ino, entry.offset);
+        Ok(inode)
+    }
+
+    /// Initializes the first cluster of a new directory with "." and "..".
+    fn init_dir_cluster(&self, cluster: ClusterID, now: DosTimestamp) -> Result<()> {
+        let fs = self.fs();
+        let mut data = vec![0u8; fs.cluster_size()];
+        let dot = ShortDentry::new_dot(DOT_NAME, cluster, now);
+        let dotdot = ShortDentry::new_dot(DOTDOT_NAME, self.dotdot_cluster(), now);
+        data[..DENTRY_SIZE].copy_from_slice(dot.as_bytes());
+        data[DENTRY_SIZE..2 * DENTRY_SIZE].copy_from_slice(dotdot.as_bytes());
+        fs.block_device()
+            .write_bytes(fs.super_block().cluster_offset(cluster), &data)?;
+        Ok(())
+    }
+
+    fn update_mtime(&self, inner: &mut VfatInodeInner) -> Result<()> {
+        let now = DosTimestamp::now(self.fs().mount_options().time_offset);
+        inner.dentry.set_mtime(now);
+        inner.dentry.set_atime(now);
+        inner.write_dentry()
+    }
+
+    fn check_dir(&self) -> Result<()> {
+        if self.type_ != InodeType::Dir {
+            return_errno!(Errno::ENOTDIR);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the inode is the directory itself or one of its ancestors.
+    fn is_ancestor_of(&self, dir: &VfatInode) -> bool {
+        let mut current = dir.this();
+        loop {
+            if current.ino == self.ino {
+                return true;
+            }
+            let parent = match &current.inner.read().position {
+                Some((parent, _)) => parent.clone(),
+                None => return false,
+            };
+            current = parent;
+        }
+    }
+
+    fn is_empty_dir(&self) -> Result<bool> {
+        let fs = self.fs();
+        let data = self.read_dir_data()?;
+        Ok(VfatDirIter::new(&data, 0, fs.mount_options())
+            .next()
+            .is_none())
+    }
+}
+
+impl Drop for VfatInode {
+    fn drop(&mut self) {
+        if !self.inner.get_mut().is_deleted {
+            return;
+        }
+        let Some(fs) = self.fs.upgrade() else {
+            return;
+        };
+        // The clusters are taken out of the layout before being freed, so the
+        // page cache, whose backend may still be in use, cannot write back to them.
+        let clusters = match &mut *self.data.layout.write() {
+            Layout::Clusters(clusters) => core::mem::take(clusters),
+            Layout::FixedRoot { .. } => return,
+        };
+        if !clusters.is_empty() {
+            let _ = fs.fat().free(fs.block_device(), &clusters, None);
+        }
+    }
+}
+
+/// Finds `num_dentries` contiguous free dentries in the directory.
+///
+/// If there are not enough ones, returns the offset of the free dentries at the end.
+fn find_free_dentries(data: &[u8], num_dentries: usize) -> core::result::Result<usize, usize> {
+    let mut free_start = None;
+    for offset in (0..data.len()).step_by(DENTRY_SIZE) {
+        match data[offset] {
+            DENTRY_END => {
+                // All the dentries after the end marker are free.
+                let start = *free_start.get_or_insert(offset);
+                return if data.len() - start >= num_dentries * DENTRY_SIZE {
+                    Ok(start)
+                } else {
+                    Err(start)
+                };
+            }
+            DENTRY_DELETED => {
+                let start = *free_start.get_or_insert(offset);
+                if offset + DENTRY_SIZE - start >= num_dentries * DENTRY_SIZE {
+                    return Ok(start);
+                }
+            }
+            _ => free_start = None,
+        }
+    }
+    Err(free_start.unwrap_or(data.len()))
+}
+
+/// Returns whether the short name is used by a dentry in the directory.
+fn is_short_name_taken(data: &[u8], short_name: &[u8; SHORT_NAME_LEN]) -> bool {
+    for raw in data.chunks_exact(DENTRY_SIZE) {
+        match raw[0] {
+            DENTRY_END => return false,
+            DENTRY_DELETED => continue,
+            _ => {}
+        }
+        if raw[11] & 0x3F != FatAttr::LONG_NAME.bits() && raw[..SHORT_NAME_LEN] == short_name[..] {
+            return true;
+        }
+    }
+    false
+}
+
+impl Inode for VfatInode {
+    fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    fn size(&self) -> usize {
+        self.inner.read().size
+    }
+
+    fn resize(&self, new_size: usize) -> Result<()> {
+        if self.type_ == InodeType::Dir {
+            return_errno!(Errno::EISDIR);
+        }
+        let mut inner = self.inner.write();
+        self.resize_file(&mut inner, new_size)?;
+        self.update_mtime(&mut inner)
+    }
+
+    fn metadata(&self) -> Metadata {
+        let inner = self.inner.read();
+        let fs = self.fs();
+        let options = fs.mount_options();
+        let cluster_size = fs.cluster_size();
+        let time_offset = options.time_offset;
+
+        Metadata {
+            dev: 0,
+            ino: self.ino,
+            size: inner.size,
+            blk_size: cluster_size,
+            blocks: inner.size.div_ceil(cluster_size),
+            atime: inner.dentry.atime().as_duration(time_offset),
+            mtime: inner.dentry.mtime().as_duration(time_offset),
+            ctime: inner.dentry.ctime().as_duration(time_offset),
+            type_: self.type_,
+            mode: self.make_mode(&inner),
+            nlinks: if self.type_ == InodeType::Dir {
+                inner.num_subdirs + 2
+            } else {
+                1
+            },
+            uid: Uid::new(options.fs_uid),
+            gid: Gid::new(options.fs_gid),
+            rdev: 0,
+        }
+    }
+
+    fn type_(&self) -> InodeType {
+        self.type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.make_mode(&self.inner.read()))
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        // Only the write permission can be stored, as the read-only attribute.
+        if self.type_ == InodeType::Dir {
+            return Ok(());
+        }
+        let mut inner = self.inner.write();
+        let mut attr = inner.dentry.attr();
+        attr.set(
+            FatAttr::READONLY,
+            !mode.intersects(InodeMode::S_IWUSR | InodeMode::S_IWGRP | InodeMode::S_IWOTH),
+        );
+        inner.dentry.attr = attr.bits();
+        inner.write_dentry()
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(Uid::new(self.fs().mount_options().fs_uid))
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        if uid != self.owner()? {
+            return_errno_with_message!(Errno::EPERM, "vfat does not support changing the owner");
+        }
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(Gid::new(self.fs().mount_options().fs_gid))
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        if gid != self.group()? {
+            return_errno_with_message!(Errno::EPERM, "vfat does not support changing the group");
+        }
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        let time_offset = self.fs().mount_options().time_offset;
+        self.inner.read().dentry.atime().as_duration(time_offset)
+    }
+
+    fn set_atime(&self, time: Duration) {
+        let time_offset = self.fs().mount_options().time_offset;
+        let mut inner = self.inner.write();
+        inner
+            .dentry
+            .set_atime(DosTimestamp::from_duration(time, time_offset));
+        let _ = inner.write_dentry();
+    }
+
+    fn mtime(&self) -> Duration {
+        let time_offset = self.fs().mount_options().time_offset;
+        self.inner.read().dentry.mtime().as_duration(time_offset)
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        let time_offset = self.fs().mount_options().time_offset;
+        let mut inner = self.inner.write();
+        inner
+            .dentry
+            .set_mtime(DosTimestamp::from_duration(time, time_offset));
+        let _ = inner.write_dentry();
+    }
+
+    fn ctime(&self) -> Duration {
+        let time_offset = self.fs().mount_options().time_offset;
+        self.inner.read().dentry.ctime().as_duration(time_offset)
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        let time_offset = self.fs().mount_options().time_offset;
+        let mut inner = self.inner.write();
+        inner
+            .dentry
+            .set_ctime(DosTimestamp::from_duration(time, time_offset));
+        let _ = inner.write_dentry();
+    }
+
+    fn page_cache(&self) -> Option<Vmo<Full>> {
+        (self.type_ == InodeType::File).then(|| self.page_cache.pages().dup())
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        if self.type_ == InodeType::Dir {
+            return_errno!(Errno::EISDIR);
+        }
+        let (read_off, read_len) = {
+            let file_size = self.inner.read().size;
+            let start = file_size.min(offset);
+            let end = file_size.min(offset + writer.avail());
+            (start, end - start)
+        };
+        self.page_cache.pages().read(read_off, writer)?;
+        Ok(read_len)
+    }
+
+    fn read_direct_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        // The direct I/O is served by the page cache, which is coherent with the device.
+        self.read_at(offset, writer)
+    }
+
+    fn write_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        if self.type_ == InodeType::Dir {
+            return_errno!(Errno::EISDIR);
+        }
+        let write_len = reader.remain();
+        let new_size = offset + write_len;
+        {
+            let mut inner = self.inner.write();
+            if new_size > inner.size {
+                self.resize_file(&mut inner, new_size)?;
+            }
+        }
+
+        self.page_cache.pages().write(offset, reader)?;
+
+        let mut inner = self.inner.write();
+        inner.dentry.attr |= FatAttr::ARCHIVE.bits();
+        self.update_mtime(&mut inner)?;
+        Ok(write_len)
+    }
+
+    fn write_direct_at(&self, offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let write_len = self.write_at(offset, reader)?;
+        self.page_cache.evict_range(offset..offset + write_len)?;
+        Ok(write_len)
+    }
+
+    fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        self.check_dir()?;
+        let mut attr = match type_ {
+            InodeType::File => FatAttr::ARCHIVE,
+            InodeType::Dir => FatAttr::DIRECTORY,
+            _ => {
+                return_errno_with_message!(Errno::EPERM, "vfat only supports files and directories")
+            }
+        };
+        if type_ == InodeType::File
+            && !mode.intersects(InodeMode::S_IWUSR | InodeMode::S_IWGRP | InodeMode::S_IWOTH)
+        {
+            attr |= FatAttr::READONLY;
+        }
+
+        let fs = self.fs();
+        let _guard = fs.lock();
+        let options = fs.mount_options();
+
+        let data = self.read_dir_data()?;
+        if self.find_entry(&data, name).is_some() {
+            return_errno!(Errno::EEXIST);
+        }
+        let names = NameDentries::new(name, options, |short_name| {
+            is_short_name_taken(&data, short_name)
+        })?;
+
+        let now = DosTimestamp::now(options.time_offset);
+        let mut dentry = ShortDentry::new(names.short_name, names.nt_res, attr, now);
+        if type_ == InodeType::Dir {
+            let cluster = fs.fat().alloc(fs.block_device(), 1, None)?[0];
+            dentry.set_first_cluster(cluster);
+            if let Err(err) = self.init_dir_cluster(cluster, now) {
+                let _ = fs.fat().free(fs.block_device(), &[cluster], None);
+                return Err(err);
+            }
+        }
+
+        let offset = match self.add_dentries(&names, &mut dentry) {
+            Ok(offset) => offset,
+            Err(err) => {
+                if type_ == InodeType::Dir {
+                    let _ = fs
+                        .fat()
+                        .free(fs.block_device(), &[dentry.first_cluster()], None);
+                }
+                return Err(err);
+            }
+        };
+        let inode = self.get_child(&VfatDirEntry {
+            name: String::from(name),
+            dentry,
+            offset,
+            num_dentries: names.num_dentries(),
+        })?;
+
+        let mut inner = self.inner.write();
+        if type_ == InodeType::Dir {
+            inner.num_subdirs += 1;
+        }
+        self.update_mtime(&mut inner)?;
+
+        Ok(inode)
+    }
+
+    fn mknod(&self, _name: &str, _mode: InodeMode, _type_: MknodType) -> Result<Arc<dyn Inode>> {
+        return_errno_with_message!(Errno::EPERM, "vfat does not support special files")
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        self.check_dir()?;
+        let fs = self.fs();
+        let _guard = fs.lock();
+        let data = self.read_dir_data()?;
+
+        let try_visit = |idx: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the two special entries("." and "..").
+            if *idx == 0 {
+                visitor.visit(".", self.ino, InodeType::Dir, *idx)?;
+                *idx += 1;
+            }
+            if *idx == 1 {
+                let parent_ino = self.inner.read().parent_ino().unwrap_or(self.ino);
+                visitor.visit("..", parent_ino, InodeType::Dir, *idx)?;
+                *idx += 1;
+            }
+            // Read the normal entries, whose indexes are the offsets of dentries.
+            let mut iter = VfatDirIter::new(&data, (*idx - 2) * DENTRY_SIZE, fs.mount_options());
+            while let Some(entry) = iter.next() {
+                // Like Linux, an unique inode number is used if the inode is not opened.
+                let ino = match fs.find_inode(self.ino, entry.offset) {
+                    Some(inode) => inode.ino,
+                    None => fs.alloc_ino(),
+                };
+                let type_ = if entry.dentry.attr().contains(FatAttr::DIRECTORY) {
+                    InodeType::Dir
+                } else {
+                    InodeType::File
+                };
+                visitor.visit(&entry.name, ino, type_, *idx)?;
+                *idx = iter.offset() / DENTRY_SIZE + 2;
+            }
+            Ok(())
+        };
+
+        let mut iterate_idx = offset;
+        match try_visit(&mut iterate_idx, visitor) {
+            Err(e) if offset == iterate_idx => Err(e),
+            _ => Ok(iterate_idx - offset),
+        }
+    }
+
+    fn link(&self, _old: &Arc<dyn Inode>, _name: &str) -> Result<()> {
+        return_errno_with_message!(Errno::EPERM, "vfat does not support hard links")
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        self.check_dir()?;
+        let fs = self.fs();
+        let _guard = fs.lock();
+
+        let data = self.read_dir_data()?;
+        let entry = self
+            .find_entry(&data, name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        if entry.dentry.attr().contains(FatAttr::DIRECTORY) {
+            return_errno!(Errno::EISDIR);
+        }
+        let inode = self.remove_dentries(&entry)?;
+        inode.inner.write().is_deleted = true;
+
+        self.update_mtime(&mut self.inner.write())
+    }
+
+    fn rmdir(&self, name: &str) -> Result<()> {
+        self.check_dir()?;
+        let fs = self.fs();
+        let _guard = fs.lock();
+
+        let data = self.read_dir_data()?;
+        let entry = self
+            .find_entry(&data, name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        if !entry.dentry.attr().contains(FatAttr::DIRECTORY) {
+            return_errno!(Errno::ENOTDIR);
+        }
+        if !self.get_child(&entry)?.is_empty_dir()? {
+            return_errno!(Errno::ENOTEMPTY);
+        }
+        let inode = self.remove_dentries(&entry)?;
+        inode.inner.write().is_deleted = true;
+
+        let mut inner = self.inner.write();
+        inner.num_subdirs -= 1;
+        self.update_mtime(&mut inner)
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        self.check_dir()?;
+        let fs = self.fs();
+        let _guard = fs.lock();
+
+        check_long_name(name, fs.mount_options())?;
+        let data = self.read_dir_data()?;
+        let entry = self
+            .find_entry(&data, name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        Ok(self.get_child(&entry)?)
+    }
+
+    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+        self.check_dir()?;
+        let target = target
+            .downcast_ref::<VfatInode>()
+            .ok_or(Error::new(Errno::EXDEV))?;
+        if !Arc::ptr_eq(&self.fs(), &target.fs()) {
+            return_errno_with_message!(Errno::EXDEV, "not the same file system");
+        }
+        target.check_dir()?;
+
+        let fs = self.fs();
+        let _guard = fs.lock();
+        let options = fs.mount_options();
+
+        let data = self.read_dir_data()?;
+        let old_entry = self
+            .find_entry(&data, old_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        let is_dir = old_entry.dentry.attr().contains(FatAttr::DIRECTORY);
+        let old_inode = self.get_child(&old_entry)?;
+        if is_dir && old_inode.is_ancestor_of(target) {
+            return_errno_with_message!(Errno::EINVAL, "cannot move a directory into itself");
+        }
+
+        let target_data = target.read_dir_data()?;
+        // Renaming a file to a name that only differs in case is allowed.
+        let exist_entry = target
+            .find_entry(&target_data, new_name)
+            .filter(|entry| !(self.ino == target.ino && entry.offset == old_entry.offset));
+        if let Some(exist_entry) = &exist_entry {
+            let exist_is_dir = exist_entry.dentry.attr().contains(FatAttr::DIRECTORY);
+            match (is_dir, exist_is_dir) {
+                (true, false) => return_errno!(Errno::ENOTDIR),
+                (false, true) => return_errno!(Errno::EISDIR),
+                (true, true) if !target.get_child(exist_entry)?.is_empty_dir()? => {
+                    return_errno!(Errno::ENOTEMPTY)
+                }
+                _ => {}
+            }
+        } else if self.ino == target.ino && old_entry.name == new_name {
+            return Ok(());
+        }
+        check_long_name(new_name, options)?;
+
+        // Remove the old dentries first, so that they can be reused.
+        if let Some(exist_entry) = &exist_entry {
+            let exist_inode = target.remove_dentries(exist_entry)?;
+            exist_inode.inner.write().is_deleted = true;
+            if is_dir {
+                target.inner.write().num_subdirs -= 1;
+            }
+        }
+        self.remove_dentries(&old_entry)?;
+
+        let target_data = target.read_dir_data()?;
+        let names = NameDentries::new(new_name, options, |short_name| {
+            is_short_name_taken(&target_data, short_name)
+        })?;
+        let mut old_inner = old_inode.inner.write();
+        let new_offset = target.add_dentries(&names, &mut old_inner.dentry)?;
+        old_inner.position = Some((target.this(), new_offset));
+        drop(old_inner);
+        fs.insert_inode(target.ino, new_offset, old_inode.clone());
+
+        if is_dir && self.ino != target.ino {
+            // Update the ".." dentry of the moved directory.
+            let pages = old_inode.page_cache.pages();
+            let mut dotdot = pages.read_val::<ShortDentry>(DENTRY_SIZE)?;
+            if dotdot.name == *DOTDOT_NAME {
+                dotdot.set_first_cluster(target.dotdot_cluster());
+                pages.write_val(DENTRY_SIZE, &dotdot)?;
+            }
+            self.inner.write().num_subdirs -= 1;
+            target.inner.write().num_subdirs += 1;
+        }
+
+        self.update_mtime(&mut self.inner.write())?;
+        if self.ino != target.ino {
+            target.update_mtime(&mut target.inner.write())?;
+        }
+        Ok(())
+    }
+
+    fn ioctl(&self, _cmd: IoctlCmd, _arg: usize) -> Result<i32> {
+        return_errno_with_message!(Errno::EINVAL, "unsupported operation")
+    }
+
+    fn sync_all(&self) -> Result<()> {
+        self.sync_data()?;
+        let inner = self.inner.read();
+        if let Some((parent, offset)) = &inner.position {
+            parent
+                .page_cache
+                .evict_range(*offset..*offset + DENTRY_SIZE)?;
+        }
+        Ok(())
+    }
+
+    fn sync_data(&self) -> Result<()> {
+        self.sync_page_cache()?;
+        self.fs().block_device().sync()?;
+        Ok(())
+    }
+
+    fn poll(&self, mask: IoEvents, _poller: Option<&mut PollHandle>) -> IoEvents {
+        let events = IoEvents::IN | IoEvents::OUT;
+        events & mask
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs()
+    }
+
+    fn is_dentry_cacheable(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> Option<&Extension> {
+        Some(&self.extension)
+    }
+}
@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: MPL-2.0
+
+mod constants;
+mod dentry;
+mod fat;
+mod fs;
+mod inode;
+mod super_block;
+mod utils;
+
+pub use fs::{VfatFS, VfatMountOptions};
+pub use inode::VfatInode;
+
+#[cfg(ktest)]
+mod test {
+    use aster_block::{
+        bio::{BioEnqueueError, BioStatus, BioType, SubmittedBio},
+        BlockDevice, BlockDeviceMeta,
+    };
+    use ostd::{
+        mm::{FrameAllocOptions, Segment, VmIo, PAGE_SIZE},
+        prelude::*,
+        Pod,
+    };
+
+    use super::{constants::BOOT_SIGNATURE, super_block::FatBootSector, VfatFS, VfatMountOptions};
+    use crate::{
+        fs::utils::{FileSystem, Inode, InodeMode, InodeType},
+        prelude::*,
+    };
+
+    const SECTOR_SIZE: usize = 512;
+
+    /// A block device in memory.
+    #[derive(Debug)]
+    struct VfatMemoryDisk(Segment<()>);
+
+    impl BlockDevice for VfatMemoryDisk {
+        fn enqueue(&self, bio: SubmittedBio) -> core::result::Result<(), BioEnqueueError> {
+            let mut device_offset = bio.sid_range().start.to_raw() as usize * SECTOR_SIZE;
+            for seg in bio.segments() {
+                let size = match bio.type_() {
+                    BioType::Read => seg
+                        .inner_segment()
+                        .writer()
+                        .write(&mut self.0.reader().skip(device_offset)),
+                    BioType::Write => self
+                        .0
+                        .writer()
+                        .skip(device_offset)
+                        .write(&mut seg.inner_segment().reader()),
+                    _ => 0,
+                };
+                device_offset += size;
+            }
+            bio.complete(BioStatus::Complete);
+            Ok(())
+        }
+
+        fn metadata(&self) -> BlockDeviceMeta {
+            BlockDeviceMeta {
+                max_nr_segments_per_bio: usize::MAX,
+                nr_sectors: self.0.size() / SECTOR_SIZE,
+                is_read_only: false,
+                is_removable: false,
+            }
+        }
+    }
+
+    // The layout of the generated FAT16 image, which has 8143 clusters of 1 KiB.
+    const IMAGE_SIZE: usize = 8 * 1024 * 1024;
+    const SECTORS_PER_CLUSTER: u8 = 2;
+    const RESERVED_SECTORS: u16 = 1;
+    const NUM_FATS: u8 = 2;
+    const ROOT_ENTRIES: u16 = 512;
+    const FAT_SECTORS: u16 = 32;
+
+    /// Generates an empty FAT16 image, as `mkfs.vfat -F 16` does.
+    fn new_vfat_disk() -> Arc<VfatMemoryDisk> {
+        let segment = FrameAllocOptions::new()
+            .alloc_segment(IMAGE_SIZE / PAGE_SIZE)
+            .unwrap();
+
+        let mut boot_sector = FatBootSector::new_zeroed();
+        boot_sector.jump_boot = [0xEB, 0x3C, 0x90];
+        boot_sector.oem_name = *b"MSWIN4.1";
+        boot_sector.bytes_per_sector = SECTOR_SIZE as u16;
+        boot_sector.sectors_per_cluster = SECTORS_PER_CLUSTER;
+        boot_sector.reserved_sectors = RESERVED_SECTORS;
+        boot_sector.num_fats = NUM_FATS;
+        boot_sector.root_entries = ROOT_ENTRIES;
+        boot_sector.total_sectors_16 = (IMAGE_SIZE / SECTOR_SIZE) as u16;
+        boot_sector.media = 0xF8;
+        boot_sector.fat_size_16 = FAT_SECTORS;
+        segment.write_val(0, &boot_sector).unwrap();
+        segment.write_val(510, &BOOT_SIGNATURE).unwrap();
+
+        // The first two entries of each FAT hold the media type and the end of chain mark.
+        for i in 0..NUM_FATS as usize {
+            let fat_start = (RESERVED_SECTORS as usize + i * FAT_SECTORS as usize) * SECTOR_SIZE;
+            segment
+                .write_bytes(fat_start, &[0xF8, 0xFF, 0xFF, 0xFF])
+                .unwrap();
+        }
+
+        Arc::new(VfatMemoryDisk(segment))
+    }
+
+    fn open_vfat(disk: &Arc<VfatMemoryDisk>) -> Arc<VfatFS> {
+        let fs = VfatFS::open(disk.clone(), VfatMountOptions::default());
+        assert!(fs.is_ok(), "Fs failed to init: {:?}", fs.unwrap_err());
+        fs.unwrap()
+    }
+
+    fn load_vfat() -> Arc<VfatFS> {
+        open_vfat(&new_vfat_disk())
+    }
+
+    fn create_file(parent: &Arc<dyn Inode>, name: &str) -> Arc<dyn Inode> {
+        let result = parent.create(name, InodeType::File, InodeMode::all());
+        assert!(
+            result.is_ok(),
+            "Fs failed to create: {:?}",
+            result.unwrap_err()
+        );
+        result.unwrap()
+    }
+
+    fn create_dir(parent: &Arc<dyn Inode>, name: &str) -> Arc<dyn Inode> {
+        let result = parent.create(name, InodeType::Dir, InodeMode::all());
+        assert!(
+            result.is_ok(),
+            "Fs failed to create: {:?}",
+            result.unwrap_err()
+        );
+        result.unwrap()
+    }
+
+    fn list_dir(dir: &Arc<dyn Inode>) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        dir.readdir_at(0, &mut names).unwrap();
+        names
+    }
+
+    fn pattern(len: usize) -> Vec<u8> {
+        // Use a prime number to make each sector different.
+        (0..len).map(|i| (i % 107) as u8).collect()
+    }
+
+    #[ktest]
+    fn new_vfat() {
+        let fs = load_vfat();
+        let root = fs.root_inode();
+        assert_eq!(list_dir(&root), vec![".", ".."]);
+        assert_eq!(fs.sb().blocks, 8143);
+        assert_eq!(fs.sb().bfree, 8143);
+    }
+
+    #[ktest]
+    fn create() {
+        let fs = load_vfat();
+        let root = fs.root_inode();
+
+        create_file(&root, "A.TXT");
+        let dir = create_dir(&root, "dir");
+        create_file(&dir, "b.txt");
+
+        // Names are compared case-insensitively.
+        assert!(root
+            .create("a.txt", InodeType::File, InodeMode::all())
+            .is_err());
+        assert!(root
+            .create("DIR", InodeType::Dir, InodeMode::all())
+            .is_err());
+
+        assert_eq!(list_dir(&root), vec![".", "..", "A.TXT", "dir"]);
+        assert_eq!(list_dir(&dir), vec![".", "..", "b.txt"]);
+        assert_eq!(root.lookup("a.txt").unwrap().type_(), InodeType::File);
+        assert_eq!(root.lookup("Dir").unwrap().type_(), InodeType::Dir);
+    }
+
+    #[ktest]
+    fn write_and_read_file() {
+        let fs = load_vfat();
+        let root = fs.root_inode();
+        let file = create_file(&root, "test");
+
+        const BUF_SIZE: usize = PAGE_SIZE * 11 + 2023;
+        let buf = pattern(BUF_SIZE);
+        assert_eq!(file.write_bytes_at(0, &buf).unwrap(), BUF_SIZE);
+        assert_eq!(file.size(), BUF_SIZE);
+
+        let mut read = vec![0u8; BUF_SIZE];
+        assert_eq!(file.read_bytes_at(0, &mut read).unwrap(), BUF_SIZE);
+        assert!(buf.eq(&read), "File mismatch");
+
+        // Writing beyond the end leaves a hole of zeros.
+        let offset = BUF_SIZE + 3 * PAGE_SIZE + 5;
+        file.write_bytes_at(offset, &[0xAA; 10]).unwrap();
+        let mut read = vec![0xFFu8; offset - BUF_SIZE + 10];
+        file.read_bytes_at(BUF_SIZE, &mut read).unwrap();
+        assert!(read[..offset - BUF_SIZE].iter().all(|&byte| byte == 0));
+        assert!(read[offset - BUF_SIZE..].iter().all(|&byte| byte == 0xAA));
+    }
+
+    #[ktest]
+    fn data_persist_after_remount() {
+        let disk = new_vfat_disk();
+        const BUF_SIZE: usize = PAGE_SIZE * 3 + 100;
+        let buf = pattern(BUF_SIZE);
+        {
+            let fs = open_vfat(&disk);
+            let root = fs.root_inode();
+            let dir = create_dir(&root, "Sub Directory");
+            let file = create_file(&dir, "data.bin");
+            file.write_bytes_at(0, &buf).unwrap();
+            fs.sync().unwrap();
+        }
+
+        let fs = open_vfat(&disk);
+        let root = fs.root_inode();
+        let dir = root.lookup("Sub Directory").unwrap();
+        let file = dir.lookup("data.bin").unwrap();
+        assert_eq!(file.size(), BUF_SIZE);
+        let mut read = vec![0u8; BUF_SIZE];
+        file.read_bytes_at(0, &mut read).unwrap();
+        assert!(buf.eq(&read), "File mismatch after remount");
+    }
+
+    #[ktest]
+    fn long_names() {
+        let disk = new_vfat_disk();
+        let names = [
+            "A long file name.txt".to_string(),
+            "MixedCase.Txt".to_string(),
+            "many.dots.in.name".to_string(),
+            "x".repeat(255),
+        ];
+        {
+            let fs = open_vfat(&disk);
+            let root = fs.root_inode();
+            for name in names.iter() {
+                create_file(&root, name);
+            }
+            assert!(root
+                .create(&"y".repeat(256), InodeType::File, InodeMode::all())
+                .is_err());
+            fs.sync().unwrap();
+        }
+
+        // The long names are read back from the long name dentries on the disk.
+        let fs = open_vfat(&disk);
+        let root = fs.root_inode();
+        let mut expected = vec![".".to_string(), "..".to_string()];
+        expected.extend(names.iter().cloned());
+        assert_eq!(list_dir(&root), expected);
+        for name in names.iter() {
+            assert!(root.lookup(name).is_ok(), "Fail to look up {}", name);
+        }
+    }
+
+    #[ktest]
+    fn rename() {
+        let fs = load_vfat();
+        let root = fs.root_inode();
+        let dir = create_dir(&root, "dir");
+        let file = create_file(&root, "old.txt");
+        let buf = pattern(PAGE_SIZE * 2 + 7);
+        file.write_bytes_at(0, &buf).unwrap();
+        drop(file);
+
+        // Rename in the same directory.
+        root.rename("old.txt", &root, "a new long name.txt")
+            .unwrap();
+        assert!(root.lookup("old.txt").is_err());
+
+        // Move to another directory.
+        root.rename("a new long name.txt", &dir, "moved.txt")
+            .unwrap();
+        assert_eq!(list_dir(&root), vec![".", "..", "dir"]);
+        assert_eq!(list_dir(&dir), vec![".", "..", "moved.txt"]);
+
+        let file = dir.lookup("moved.txt").unwrap();
+        let mut read = vec![0u8; buf.len()];
+        file.read_bytes_at(0, &mut read).unwrap();
+        assert!(buf.eq(&read), "File mismatch after rename");
+
+        // Replace an existing file.
+        let other = create_file(&root, "other.txt");
+        other.write_bytes_at(0, b"other").unwrap();
+        drop(other);
+        root.rename("other.txt", &dir, "moved.txt").unwrap();
+        let file = dir.lookup("moved.txt").unwrap();
+        assert_eq!(file.size(), 5);
+
+        // A directory cannot be moved into itself.
+        let sub = create_dir(&dir, "sub");
+        assert!(root.rename("dir", &sub, "dir").is_err());
+    }
+
+    #[ktest]
+    fn unlink() {
+        let fs = load_vfat();
+        let root = fs.root_inode();
+        let free_clusters = fs.sb().bfree;
+
+        let file = create_file(&root, "file");
+        file.write_bytes_at(0, &pattern(PAGE_SIZE * 4)).unwrap();
+        drop(file);
+        assert!(fs.sb().bfree < free_clusters);
+
+        let dir = create_dir(&root, "dir");
+        create_file(&dir, "child");
+        assert!(root.unlink("dir").is_err());
+        assert!(root.rmdir("dir").is_err());
+        dir.unlink("child").unwrap();
+        drop(dir);
+        root.rmdir("dir").unwrap();
+
+        assert!(root.rmdir("file").is_err());
+        root.unlink("file").unwrap();
+        assert!(root.lookup("file").is_err());
+        assert!(root.unlink("file").is_err());
+        assert_eq!(list_dir(&root), vec![".", ".."]);
+        assert_eq!(fs.sb().bfree, free_clusters);
+    }
+
+    #[ktest]
+    fn truncate() {
+        let fs = load_vfat();
+        let root = fs.root_inode();
+        let free_clusters = fs.sb().bfree;
+        let cluster_size = fs.sb().bsize;
+
+        let file = create_file(&root, "file");
+        let buf = pattern(cluster_size * 10);
+        file.write_bytes_at(0, &buf).unwrap();
+        assert_eq!(fs.sb().bfree, free_clusters - 10);
+
+        // Shrink the file and free the clusters beyond the new size.
+        file.resize(cluster_size * 3 + 1).unwrap();
+        assert_eq!(file.size(), cluster_size * 3 + 1);
+        assert_eq!(fs.sb().bfree, free_clusters - 4);
+
+        // Grow the file with zeros.
+        file.resize(cluster_size * 6).unwrap();
+        assert_eq!(fs.sb().bfree, free_clusters - 6);
+        let mut read = vec![0xFFu8; cluster_size * 6];
+        file.read_bytes_at(0, &mut read).unwrap();
+        assert!(read[..cluster_size * 3 + 1].eq(&buf[..cluster_size * 3 + 1]));
+        assert!(read[cluster_size * 3 + 1..].iter().all(|&byte| byte == 0));
+
+        file.resize(0).unwrap();
+        assert_eq!(file.size(), 0);
+        assert_eq!(fs.sb().bfree, free_clusters);
+    }
+}
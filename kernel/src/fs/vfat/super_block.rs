@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::Pod;
+
+use super::{
+    constants::*,
+    fat::{ClusterID, FatType},
+};
+use crate::prelude::*;
+
+/// The BIOS parameter block (BPB) in the boot sector of a FAT volume.
+///
+/// The fields after `total_sectors_32` are only valid for FAT32, since the
+/// extended BPB of FAT12/16 has a different layout, which we do not use.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub(super) struct FatBootSector {
+    pub jump_boot: [u8; 3],
+    pub oem_name: [u8; 8],
+    pub bytes_per_sector: u16,
+    pub sectors_per_cluster: u8,
+    pub reserved_sectors: u16,
+    pub num_fats: u8,
+    /// The number of dentries in the root directory of FAT12/16.
+    pub root_entries: u16,
+    pub total_sectors_16: u16,
+    pub media: u8,
+    pub fat_size_16: u16,
+    pub sectors_per_track: u16,
+    pub num_heads: u16,
+    pub hidden_sectors: u32,
+    pub total_sectors_32: u32,
+    // The extended BPB of FAT32.
+    pub fat_size_32: u32,
+    pub ext_flags: u16,
+    pub fs_version: u16,
+    pub root_cluster: u32,
+    pub fs_info_sector: u16,
+    pub backup_boot_sector: u16,
+    pub reserved: [u8; 12],
+    pub drive_number: u8,
+    pub reserved1: u8,
+    pub boot_signature: u8,
+    pub volume_id: u32,
+    pub volume_label: [u8; 11],
+    pub fs_type: [u8; 8],
+}
+
+/// The in-memory superblock info, with all the positions in bytes.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct VfatSuperBlock {
+    pub fat_type: FatType,
+    pub sector_size: usize,
+    pub cluster_size: usize,
+    /// The number of data clusters, which are numbered from 2.
+    pub num_clusters: u32,
+    /// The offset of the first FAT.
+    pub fat_start: usize,
+    /// The size of each FAT.
+    pub fat_size: usize,
+    pub num_fats: usize,
+    /// The offset of the fixed root directory region of FAT12/16.
+    pub root_dir_start: usize,
+    /// The size of the fixed root directory region of FAT12/16,
+    /// which is zero for FAT32.
+    pub root_dir_size: usize,
+    /// The first cluster of the root directory of FAT32.
+    pub root_cluster: ClusterID,
+    /// The offset of the data region.
+    pub data_start: usize,
+    /// The offset of the FSInfo sector of FAT32, if any.
+    pub fs_info_start: Option<usize>,
+    pub volume_id: u32,
+}
+
+impl TryFrom<&FatBootSector> for VfatSuperBlock {
+    type Error = crate::error::Error;
+
+    fn try_from(boot_sector: &FatBootSector) -> Result<Self> {
+        let sector_size = boot_sector.bytes_per_sector;
+        if !sector_size.is_power_of_two()
+            || !(MIN_SECTOR_SIZE..=MAX_SECTOR_SIZE).contains(&sector_size)
+        {
+            return_errno_with_message!(Errno::EINVAL, "bogus sector size");
+        }
+        let sector_size = sector_size as usize;
+
+        let sectors_per_cluster = boot_sector.sectors_per_cluster as usize;
+        if !sectors_per_cluster.is_power_of_two()
+            || sectors_per_cluster * sector_size > MAX_CLUSTER_SIZE
+        {
+            return_errno_with_message!(Errno::EINVAL, "bogus sectors per cluster");
+        }
+        let cluster_size = sectors_per_cluster * sector_size;
+
+        if boot_sector.reserved_sectors == 0 {
+            return_errno_with_message!(Errno::EINVAL, "bogus number of reserved sectors");
+        }
+        if boot_sector.num_fats == 0 {
+            return_errno_with_message!(Errno::EINVAL, "bogus number of FATs");
+        }
+
+        let fat_sectors = if boot_sector.fat_size_16 != 0 {
+            boot_sector.fat_size_16 as usize
+        } else {
+            boot_sector.fat_size_32 as usize
+        };
+        let total_sectors = if boot_sector.total_sectors_16 != 0 {
+            boot_sector.total_sectors_16 as usize
+        } else {
+            boot_sector.total_sectors_32 as usize
+        };
+        if fat_sectors == 0 || total_sectors == 0 {
+            return_errno_with_message!(Errno::EINVAL, "bogus FAT size or volume size");
+        }
+
+        let root_dir_sectors =
+            (boot_sector.root_entries as usize * DENTRY_SIZE).div_ceil(sector_size);
+        let fat_start_sector = boot_sector.reserved_sectors as usize;
+        let root_dir_start_sector = fat_start_sector + boot_sector.num_fats as usize * fat_sectors;
+        let data_start_sector = root_dir_start_sector + root_dir_sectors;
+        if data_start_sector >= total_sectors {
+            return_errno_with_message!(Errno::EINVAL, "bogus data region");
+        }
+
+        let num_clusters = ((total_sectors - data_start_sector) / sectors_per_cluster) as u32;
+        let fat_type = if num_clusters <= MAX_FAT12_CLUSTERS {
+            FatType::Fat12
+        } else if num_clusters <= MAX_FAT16_CLUSTERS {
+            FatType::Fat16
+        } else {
+            FatType::Fat32
+        };
+
+        // The FAT should be large enough to hold the entries of all clusters.
+        let fat_size = fat_sectors * sector_size;
+        if fat_type.fat_bytes(num_clusters + FAT_RESERVED_CLUSTERS) > fat_size {
+            return_errno_with_message!(Errno::EINVAL, "bogus FAT size");
+        }
+
+        let (root_cluster, fs_info_start, volume_id) = if fat_type == FatType::Fat32 {
+            if root_dir_sectors != 0 {
+                return_errno_with_message!(Errno::EINVAL, "bogus root directory of FAT32");
+            }
+            let root_cluster = boot_sector.root_cluster;
+            if root_cluster < FAT_RESERVED_CLUSTERS
+                || root_cluster >= num_clusters + FAT_RESERVED_CLUSTERS
+            {
+                return_errno_with_message!(Errno::EINVAL, "bogus root cluster");
+            }
+            let fs_info_sector = boot_sector.fs_info_sector as usize;
+            let fs_info_start = (fs_info_sector != 0 && fs_info_sector < fat_start_sector)
+                .then_some(fs_info_sector * sector_size);
+            (root_cluster, fs_info_start, boot_sector.volume_id)
+        } else {
+            if root_dir_sectors == 0 {
+                return_errno_with_message!(Errno::EINVAL, "bogus root directory of FAT12/16");
+            }
+            (0, None, 0)
+        };
+
+        Ok(Self {
+            fat_type,
+            sector_size,
+            cluster_size,
+            num_clusters,
+            fat_start: fat_start_sector * sector_size,
+            fat_size,
+            num_fats: boot_sector.num_fats as usize,
+            root_dir_start: root_dir_start_sector * sector_size,
+            root_dir_size: root_dir_sectors * sector_size,
+            root_cluster,
+            data_start: data_start_sector * sector_size,
+            fs_info_start,
+            volume_id,
+        })
+    }
+}
+
+impl VfatSuperBlock {
+    /// Returns the offset of the cluster on the device.
+    pub fn cluster_offset(&self, cluster: ClusterID) -> usize {
+        debug_assert!(self.is_valid_cluster(cluster));
+        self.data_start + (cluster - FAT_RESERVED_CLUSTERS) as usize * self.cluster_size
+    }
+
+    pub fn is_valid_cluster(&self, cluster: ClusterID) -> bool {
+        cluster >= FAT_RESERVED_CLUSTERS && cluster < self.num_clusters + FAT_RESERVED_CLUSTERS
+    }
+}
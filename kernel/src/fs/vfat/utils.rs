@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+use super::constants::*;
+use crate::prelude::*;
+
+/// A FAT timestamp, which is in local time at the precision of two seconds,
+/// with an optional fine resolution of 10ms for the creation time.
+#[derive(Default, Debug, Clone, Copy)]
+pub(super) struct DosTimestamp {
+    pub time: u16,
+    pub date: u16,
+    /// The count of 10ms in the range of 0 to 199.
+    pub time_cs: u8,
+}
+
+impl DosTimestamp {
+    pub fn now(time_offset: i32) -> Self {
+        use crate::time::clocks::RealTimeClock;
+        Self::from_duration(RealTimeClock::get().read_time(), time_offset)
+    }
+
+    /// Converts the time since the UNIX epoch to a FAT timestamp,
+    /// where `time_offset` is the offset of the local time from UTC in minutes.
+    ///
+    /// The time out of the range of FAT timestamps is clamped.
+    pub fn from_duration(duration: Duration, time_offset: i32) -> Self {
+        let secs = (duration.as_secs() as i64 + time_offset as i64 * 60)
+            .clamp(FAT_MIN_TIMESTAMP_SECS as i64, FAT_MAX_TIMESTAMP_SECS as i64);
+        let Ok(date_time) = OffsetDateTime::from_unix_timestamp(secs) else {
+            return Self::default();
+        };
+
+        let time = ((date_time.hour() as u16) << 11)
+            | ((date_time.minute() as u16) << 5)
+            | ((date_time.second() as u16) >> 1);
+        let date = (((date_time.year() - 1980) as u16) << 9)
+            | ((date_time.month() as u16) << 5)
+            | (date_time.day() as u16);
+        let time_cs = ((date_time.second() % 2) as u32 * 100 + duration.subsec_millis() / 10) as u8;
+
+        Self {
+            time,
+            date,
+            time_cs,
+        }
+    }
+
+    /// Converts the FAT timestamp to the time since the UNIX epoch,
+    /// where `time_offset` is the offset of the local time from UTC in minutes.
+    ///
+    /// An invalid timestamp is converted to the UNIX epoch.
+    pub fn as_duration(&self, time_offset: i32) -> Duration {
+        let date = Month::try_from(((self.date >> 5) & 0xF) as u8).and_then(|month| {
+            Date::from_calendar_date(
+                1980 + (self.date >> 9) as i32,
+                month,
+                (self.date & 0x1F) as u8,
+            )
+        });
+        let time = Time::from_hms(
+            (self.time >> 11) as u8,
+            ((self.time >> 5) & 0x3F) as u8,
+            ((self.time & 0x1F) * 2) as u8,
+        );
+        let (Ok(date), Ok(time)) = (date, time) else {
+            return Duration::ZERO;
+        };
+
+        let secs = PrimitiveDateTime::new(date, time)
+            .assume_utc()
+            .unix_timestamp()
+            - time_offset as i64 * 60
+            + (self.time_cs / 100) as i64;
+        if secs < 0 {
+            return Duration::ZERO;
+        }
+        Duration::new(secs as u64, (self.time_cs % 100) as u32 * 10_000_000)
+    }
+}
+
+/// The characters of code page 437 from 0x80 to 0xFF.
+///
+/// Code page 437 is the default OEM code page used by FAT to store short names.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// Decodes a byte of code page 437.
+pub(super) fn cp437_to_char(byte: u8) -> char {
+    if byte < 0x80 {
+        byte as char
+    } else {
+        CP437_HIGH[(byte - 0x80) as usize]
+    }
+}
+
+/// Encodes a character with code page 437, if possible.
+pub(super) fn char_to_cp437(c: char) -> Option<u8> {
+    if c.is_ascii() {
+        return Some(c as u8);
+    }
+    CP437_HIGH
+        .iter()
+        .position(|&high| high == c)
+        .map(|idx| idx as u8 + 0x80)
+}
@@ -98,6 +98,7 @@ pub fn init() {
     #[cfg(target_arch = "x86_64")]
     net::init();
     sched::init();
+    vm::init();
     fs::rootfs::init(boot_info().initramfs.expect("No initramfs found!")).unwrap();
     device::init().unwrap();
     syscall::init();
@@ -119,6 +120,8 @@ fn ap_init() {
     let cpu_id = preempt_guard.current_cpu();
     drop(preempt_guard);
 
+    sched::cpu_time::init_on_current_cpu();
+
     ThreadOptions::new(ap_idle_thread)
         .cpu_affinity(cpu_id.into())
         .priority(Priority::idle())
@@ -133,6 +136,7 @@ fn init_thread() {
     #[cfg(target_arch = "x86_64")]
     net::lazy_init();
     fs::lazy_init();
+    vm::lazy_init();
     ipc::init();
     // driver::pci::virtio::block::block_device_test();
     let thread = ThreadOptions::new(|| {
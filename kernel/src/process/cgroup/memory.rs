@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use ostd::{impl_untyped_frame_meta_for, task::Task};
+
+use super::Cgroup;
+use crate::{
+    prelude::*,
+    process::{posix_thread::AsThreadLocal, Process},
+};
+
+/// Charges `amount` bytes of memory to the cgroup and its ancestors.
+///
+/// If the `memory.max` of any of them is exceeded, `ENOMEM` is returned. The
+/// cgroup whose limit is exceeded is recorded in the current thread, so that
+/// the OOM killer only selects the victim in that cgroup, instead of killing
+/// the processes outside it.
+fn try_charge_memory(cgroup: &Arc<Cgroup>, amount: u64) -> Result<()> {
+    if cgroup.try_charge(|cgroup| &cgroup.memory, amount) {
+        return Ok(());
+    }
+
+    let exceeded = core::iter::successors(Some(cgroup.clone()), |cgroup| cgroup.parent.clone())
+        .find(|cgroup| {
+            cgroup
+                .memory
+                .max()
+                .is_some_and(|max| cgroup.memory.usage() + amount > max)
+        })
+        .unwrap_or_else(|| cgroup.clone());
+    if let Some(thread_local) = Task::current()
+        .as_ref()
+        .and_then(|task| task.as_thread_local())
+    {
+        *thread_local.memory_oom_cgroup().borrow_mut() = Some(exceeded);
+    }
+
+    return_errno_with_message!(Errno::ENOMEM, "the memory exceeds memory.max");
+}
+
+/// The memory charged to a cgroup by an object, counted in pages.
+///
+/// The charge is moved back when the object is dropped. Cloning the object
+/// charges the same amount of memory again, since the frames of the clone are
+/// accounted separately.
+#[derive(Debug)]
+pub struct MemoryCharge {
+    cgroup: Arc<Cgroup>,
+    nr_pages: AtomicUsize,
+}
+
+impl MemoryCharge {
+    pub fn new(cgroup: Arc<Cgroup>) -> Self {
+        Self {
+            cgroup,
+            nr_pages: AtomicUsize::new(0),
+        }
+    }
+
+    /// Charges a page to the cgroup.
+    ///
+    /// If the `memory.max` of the cgroup or any of its ancestors is exceeded,
+    /// this method returns `ENOMEM`.
+    pub fn try_charge_page(&self) -> Result<()> {
+        try_charge_memory(&self.cgroup, PAGE_SIZE as u64)?;
+        self.nr_pages.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Uncharges a page from the cgroup.
+    pub fn uncharge_page(&self) {
+        let old_nr_pages = self.nr_pages.fetch_sub(1, Ordering::Relaxed);
+        debug_assert!(old_nr_pages > 0);
+        self.cgroup
+            .uncharge(|cgroup| &cgroup.memory, PAGE_SIZE as u64);
+    }
+}
+
+impl Clone for MemoryCharge {
+    fn clone(&self) -> Self {
+        let nr_pages = self.nr_pages.load(Ordering::Relaxed);
+        self.cgroup
+            .charge(|cgroup| &cgroup.memory, (nr_pages * PAGE_SIZE) as u64);
+        Self {
+            cgroup: self.cgroup.clone(),
+            nr_pages: AtomicUsize::new(nr_pages),
+        }
+    }
+}
+
+impl Drop for MemoryCharge {
+    fn drop(&mut self) {
+        let nr_pages = *self.nr_pages.get_mut();
+        self.cgroup
+            .uncharge(|cgroup| &cgroup.memory, (nr_pages * PAGE_SIZE) as u64);
+    }
+}
+
+/// The metadata of a private frame, which is charged to the memory cgroup of
+/// the process that allocates it.
+///
+/// The frame is uncharged when it is freed, i.e., when it is unmapped from
+/// all the address spaces, including the ones that share it after `fork`.
+#[derive(Debug)]
+pub struct ChargedFrameMeta {
+    // The cgroup is `None` if the frame is not allocated by a process, e.g.,
+    // when a kernel thread handles the page fault.
+    cgroup: Option<Arc<Cgroup>>,
+}
+
+impl ChargedFrameMeta {
+    /// Charges a page to the memory cgroup of the current process.
+    ///
+    /// If the `memory.max` of the cgroup or any of its ancestors is exceeded,
+    /// this method returns `ENOMEM`.
+    pub fn try_new() -> Result<Self> {
+        let Some(process) = Process::current() else {
+            return Ok(Self { cgroup: None });
+        };
+
+        let cgroup = process.cgroup();
+        try_charge_memory(&cgroup, PAGE_SIZE as u64)?;
+        Ok(Self {
+            cgroup: Some(cgroup),
+        })
+    }
+}
+
+impl Drop for ChargedFrameMeta {
+    fn drop(&mut self) {
+        if let Some(cgroup) = &self.cgroup {
+            cgroup.uncharge(|cgroup| &cgroup.memory, PAGE_SIZE as u64);
+        }
+    }
+}
+
+impl_untyped_frame_meta_for!(ChargedFrameMeta);
@@ -0,0 +1,455 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Control groups (cgroups) of version 2.
+//!
+//! The cgroups form a single hierarchy rooted at [`Cgroup::root`]. Every
+//! process belongs to exactly one cgroup, and a new process belongs to the
+//! cgroup of its parent. The controllers limit the resources used by the
+//! processes in a cgroup and its descendants:
+//!
+//! - The `cpu` controller (`cpu.weight` and `cpu.max`), which is implemented
+//!   by [`FairGroup`] in the FAIR scheduling class.
+//! - The `memory` controller (`memory.max`), which limits the frames committed
+//!   to the anonymous VMOs of the user (see [`MemoryCharge`]) and the private
+//!   frames mapped by the user (see [`ChargedFrameMeta`]). If the limit is
+//!   exceeded on a page fault, the OOM killer kills a process in the cgroup.
+//! - The `pids` controller (`pids.max`), which limits the number of threads.
+//!
+//! A controller is available in a cgroup only if it is enabled in the
+//! `cgroup.subtree_control` of the parent. The root cgroup has all the
+//! controllers, but cannot be limited.
+//!
+//! Reference: <https://docs.kernel.org/admin-guide/cgroup-v2.html>
+
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use spin::Once;
+
+pub use self::memory::{ChargedFrameMeta, MemoryCharge};
+use super::{process_table, Pid, Process};
+use crate::{
+    prelude::*,
+    sched::FairGroup,
+    thread::{AsThread, Thread},
+};
+
+mod memory;
+
+bitflags! {
+    /// The controllers of cgroups.
+    pub struct Controllers: u8 {
+        const CPU    = 1 << 0;
+        const MEMORY = 1 << 1;
+        const PIDS   = 1 << 2;
+    }
+}
+
+impl Controllers {
+    const NAMES: [(Controllers, &'static str); 3] = [
+        (Controllers::CPU, "cpu"),
+        (Controllers::MEMORY, "memory"),
+        (Controllers::PIDS, "pids"),
+    ];
+
+    /// Parses the name of a controller.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(_, controller_name)| *controller_name == name)
+            .map(|(controller, _)| *controller)
+    }
+
+    /// Returns the names of the controllers, separated by spaces.
+    pub fn names(&self) -> String {
+        let names: Vec<&str> = Self::NAMES
+            .iter()
+            .filter(|(controller, _)| self.contains(*controller))
+            .map(|(_, name)| *name)
+            .collect();
+        names.join(" ")
+    }
+}
+
+/// A resource counter with a limit.
+#[derive(Debug)]
+struct Counter {
+    usage: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Counter {
+    /// The limit of a counter that is not limited.
+    const UNLIMITED: u64 = u64::MAX;
+
+    fn new() -> Self {
+        Self {
+            usage: AtomicU64::new(0),
+            max: AtomicU64::new(Self::UNLIMITED),
+        }
+    }
+
+    fn usage(&self) -> u64 {
+        self.usage.load(Ordering::Relaxed)
+    }
+
+    fn max(&self) -> Option<u64> {
+        match self.max.load(Ordering::Relaxed) {
+            Self::UNLIMITED => None,
+            max => Some(max),
+        }
+    }
+
+    fn set_max(&self, max: Option<u64>) {
+        self.max
+            .store(max.unwrap_or(Self::UNLIMITED), Ordering::Relaxed);
+    }
+
+    fn try_add(&self, amount: u64) -> bool {
+        let max = self.max.load(Ordering::Relaxed);
+        self.usage
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |usage| {
+                usage.checked_add(amount).filter(|usage| *usage <= max)
+            })
+            .is_ok()
+    }
+
+    fn add(&self, amount: u64) {
+        self.usage.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    fn sub(&self, amount: u64) {
+        let old_usage = self.usage.fetch_sub(amount, Ordering::Relaxed);
+        debug_assert!(old_usage >= amount);
+    }
+}
+
+/// A control group.
+pub struct Cgroup {
+    name: String,
+    parent: Option<Arc<Cgroup>>,
+    children: Mutex<BTreeMap<String, Arc<Cgroup>>>,
+    /// The controllers that are enabled for the children.
+    subtree_control: Mutex<Controllers>,
+    /// Whether the cgroup has been removed from the hierarchy.
+    is_removed: AtomicBool,
+    /// The group of the threads in the FAIR scheduling class.
+    ///
+    /// The root cgroup does not have one, since it cannot be limited.
+    fair_group: Option<Arc<FairGroup>>,
+    /// The memory charged by the processes, measured in bytes.
+    memory: Counter,
+    /// The number of threads.
+    pids: Counter,
+}
+
+impl Debug for Cgroup {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Cgroup")
+            .field("path", &self.path())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Cgroup {
+    /// Returns the root cgroup.
+    pub fn root() -> &'static Arc<Cgroup> {
+        static ROOT: Once<Arc<Cgroup>> = Once::new();
+        ROOT.call_once(|| Arc::new(Cgroup::new(String::new(), None)))
+    }
+
+    fn new(name: String, parent: Option<Arc<Cgroup>>) -> Self {
+        let fair_group = parent
+            .as_ref()
+            .map(|parent| Arc::new(FairGroup::new(parent.fair_group.clone())));
+
+        Self {
+            name,
+            parent,
+            children: Mutex::new(BTreeMap::new()),
+            subtree_control: Mutex::new(Controllers::empty()),
+            is_removed: AtomicBool::new(false),
+            fair_group,
+            memory: Counter::new(),
+            pids: Counter::new(),
+        }
+    }
+
+    /// Returns the name of the cgroup, which is empty for the root cgroup.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent.is_none()
+    }
+
+    /// Returns the path of the cgroup, relative to the root cgroup.
+    pub fn path(&self) -> String {
+        let Some(parent) = &self.parent else {
+            return String::from("/");
+        };
+
+        let mut path = parent.path();
+        if !parent.is_root() {
+            path.push('/');
+        }
+        path.push_str(&self.name);
+        path
+    }
+
+    fn ancestors(&self) -> impl Iterator<Item = &Cgroup> {
+        core::iter::successors(Some(self), |cgroup| cgroup.parent.as_deref())
+    }
+
+    /// Returns whether the cgroup is `ancestor` or one of its descendants.
+    pub fn is_descendant_of(&self, ancestor: &Cgroup) -> bool {
+        self.ancestors()
+            .any(|cgroup| core::ptr::eq(cgroup, ancestor))
+    }
+
+    // ******************* Hierarchy ********************
+
+    /// Returns the child cgroup with the name.
+    pub fn child(&self, name: &str) -> Option<Arc<Cgroup>> {
+        self.children.lock().get(name).cloned()
+    }
+
+    /// Returns all the child cgroups.
+    pub fn children(&self) -> Vec<Arc<Cgroup>> {
+        self.children.lock().values().cloned().collect()
+    }
+
+    /// Creates a child cgroup with the name.
+    pub fn create_child(self: &Arc<Self>, name: &str) -> Result<Arc<Cgroup>> {
+        if self.is_removed() {
+            return_errno_with_message!(Errno::ENOENT, "the cgroup has been removed");
+        }
+
+        let mut children = self.children.lock();
+        if children.contains_key(name) {
+            return_errno_with_message!(Errno::EEXIST, "the cgroup already exists");
+        }
+        let child = Arc::new(Cgroup::new(String::from(name), Some(self.clone())));
+        children.insert(String::from(name), child.clone());
+
+        Ok(child)
+    }
+
+    /// Removes the child cgroup with the name.
+    ///
+    /// A cgroup can be removed only if it has neither child cgroups nor
+    /// living threads.
+    pub fn remove_child(&self, name: &str) -> Result<()> {
+        let mut children = self.children.lock();
+        let Some(child) = children.get(name) else {
+            return_errno_with_message!(Errno::ENOENT, "the cgroup does not exist");
+        };
+        if !child.children.lock().is_empty() || child.pids.usage() != 0 {
+            return_errno_with_message!(Errno::EBUSY, "the cgroup is still in use");
+        }
+
+        child.is_removed.store(true, Ordering::Relaxed);
+        children.remove(name);
+
+        Ok(())
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.is_removed.load(Ordering::Relaxed)
+    }
+
+    // ******************* Controllers ********************
+
+    /// Returns the controllers that are available in the cgroup.
+    pub fn controllers(&self) -> Controllers {
+        match &self.parent {
+            Some(parent) => parent.subtree_control(),
+            None => Controllers::all(),
+        }
+    }
+
+    /// Returns the controllers that are enabled for the children.
+    pub fn subtree_control(&self) -> Controllers {
+        *self.subtree_control.lock()
+    }
+
+    /// Enables and disables the controllers for the children.
+    ///
+    /// Only the available controllers can be enabled. A controller cannot be
+    /// disabled if a child enables it for its own children. The limits of a
+    /// disabled controller are reset in the children.
+    pub fn update_subtree_control(&self, enable: Controllers, disable: Controllers) -> Result<()> {
+        if !self.controllers().contains(enable) {
+            return_errno_with_message!(Errno::ENOENT, "the controller is not available");
+        }
+
+        let mut subtree_control = self.subtree_control.lock();
+        let enable = enable - *subtree_control;
+        let disable = disable & *subtree_control;
+
+        // Processes can only live in the leaves if any controller is enabled
+        // (the "no internal process" rule), except for the root cgroup.
+        if !enable.is_empty() && !self.is_root() && !self.procs().is_empty() {
+            return_errno_with_message!(Errno::EBUSY, "the cgroup has processes");
+        }
+
+        let children = self.children.lock();
+        if children
+            .values()
+            .any(|child| child.subtree_control().intersects(disable))
+        {
+            return_errno_with_message!(Errno::EBUSY, "the controller is used by a child");
+        }
+        for child in children.values() {
+            child.reset_limits(disable);
+        }
+
+        *subtree_control = (*subtree_control | enable) - disable;
+        Ok(())
+    }
+
+    fn reset_limits(&self, controllers: Controllers) {
+        if controllers.contains(Controllers::CPU) {
+            let fair_group = self.fair_group.as_ref().unwrap();
+            fair_group.set_weight(FairGroup::DEFAULT_WEIGHT);
+            fair_group.set_max(None, FairGroup::DEFAULT_PERIOD);
+        }
+        if controllers.contains(Controllers::MEMORY) {
+            self.memory.set_max(None);
+        }
+        if controllers.contains(Controllers::PIDS) {
+            self.pids.set_max(None);
+        }
+    }
+
+    /// Returns the group of the threads in the FAIR scheduling class.
+    ///
+    /// This is `None` for the root cgroup.
+    pub fn fair_group(&self) -> Option<&Arc<FairGroup>> {
+        self.fair_group.as_ref()
+    }
+
+    /// Returns the memory charged to the cgroup and its descendants, in bytes.
+    pub fn memory_current(&self) -> u64 {
+        self.memory.usage()
+    }
+
+    pub fn memory_max(&self) -> Option<u64> {
+        self.memory.max()
+    }
+
+    pub fn set_memory_max(&self, max: Option<u64>) {
+        debug_assert!(!self.is_root());
+        self.memory.set_max(max);
+    }
+
+    /// Returns the number of threads in the cgroup and its descendants.
+    pub fn pids_current(&self) -> u64 {
+        self.pids.usage()
+    }
+
+    pub fn pids_max(&self) -> Option<u64> {
+        self.pids.max()
+    }
+
+    pub fn set_pids_max(&self, max: Option<u64>) {
+        debug_assert!(!self.is_root());
+        self.pids.set_max(max);
+    }
+
+    /// Charges to the cgroup and its ancestors.
+    ///
+    /// If any of their limits is exceeded, nothing is charged and `false` is
+    /// returned.
+    fn try_charge(&self, counter: fn(&Cgroup) -> &Counter, amount: u64) -> bool {
+        for (charged, cgroup) in self.ancestors().enumerate() {
+            if !counter(cgroup).try_add(amount) {
+                self.ancestors()
+                    .take(charged)
+                    .for_each(|cgroup| counter(cgroup).sub(amount));
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Charges to the cgroup and its ancestors regardless of their limits.
+    fn charge(&self, counter: fn(&Cgroup) -> &Counter, amount: u64) {
+        self.ancestors()
+            .for_each(|cgroup| counter(cgroup).add(amount));
+    }
+
+    fn uncharge(&self, counter: fn(&Cgroup) -> &Counter, amount: u64) {
+        self.ancestors()
+            .for_each(|cgroup| counter(cgroup).sub(amount));
+    }
+
+    // ******************* Processes ********************
+
+    /// Returns the PIDs of the living processes in the cgroup.
+    pub fn procs(&self) -> Vec<Pid> {
+        process_table::process_table_mut()
+            .iter()
+            .filter(|process| !process.status().is_zombie())
+            .filter(|process| core::ptr::eq(Arc::as_ptr(&process.cgroup()), self))
+            .map(|process| process.pid())
+            .collect()
+    }
+
+    /// Moves the process with all its threads to the cgroup.
+    ///
+    /// The threads are charged to the cgroup regardless of its limits.
+    pub fn attach(self: &Arc<Self>, process: &Process) -> Result<()> {
+        if self.is_removed() {
+            return_errno_with_message!(Errno::ENOENT, "the cgroup has been removed");
+        }
+        if !self.is_root() && !self.subtree_control().is_empty() {
+            return_errno_with_message!(
+                Errno::EBUSY,
+                "processes cannot be attached to a cgroup with controllers enabled"
+            );
+        }
+
+        // Lock order: tasks of process -> cgroup of process
+        let tasks = process.tasks().lock();
+        let mut cgroup = process.cgroup.lock();
+        if Arc::ptr_eq(&cgroup, self) {
+            return Ok(());
+        }
+
+        let threads: Vec<&Arc<Thread>> = tasks
+            .as_slice()
+            .iter()
+            .filter_map(|task| task.as_thread())
+            .filter(|thread| !thread.is_exited())
+            .collect();
+        let nr_threads = threads.len() as u64;
+        cgroup.uncharge(|cgroup| &cgroup.pids, nr_threads);
+        self.charge(|cgroup| &cgroup.pids, nr_threads);
+        for thread in threads {
+            thread.sched_attr().set_fair_group(self.fair_group.clone());
+        }
+
+        *cgroup = self.clone();
+        Ok(())
+    }
+
+    /// Charges a new thread to the cgroup.
+    ///
+    /// This method should be called with the tasks of the process locked, so
+    /// that the process cannot be moved to another cgroup concurrently.
+    pub(super) fn charge_thread(&self, thread: &Thread) -> Result<()> {
+        if !self.try_charge(|cgroup| &cgroup.pids, 1) {
+            return_errno_with_message!(Errno::EAGAIN, "the number of threads exceeds pids.max");
+        }
+        thread.sched_attr().set_fair_group(self.fair_group.clone());
+        Ok(())
+    }
+
+    /// Uncharges an exited thread from the cgroup.
+    ///
+    /// This method should be called with the tasks of the process locked.
+    pub(super) fn uncharge_thread(&self) {
+        self.uncharge(|cgroup| &cgroup.pids, 1);
+    }
+}
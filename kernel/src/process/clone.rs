@@ -10,18 +10,23 @@ use ostd::{
 };
 
 use super::{
-    posix_thread::{AsPosixThread, PosixThreadBuilder, ThreadName},
+    credentials::capabilities::CapSet,
+    posix_thread::{thread_table, AsPosixThread, PosixThreadBuilder, ThreadName},
     process_table,
     process_vm::ProcessVm,
     signal::{constants::SIGCHLD, sig_disposition::SigDispositions, sig_num::SigNum},
-    Credentials, Process, ProcessBuilder,
+    Credentials, PidFile, Process, ProcessBuilder, ResourceType,
 };
 use crate::{
     cpu::LinuxAbi,
     current_userspace,
-    fs::{file_table::FileTable, thread_info::ThreadFsInfo},
+    fs::{
+        file_table::{FdFlags, FileTable},
+        thread_info::ThreadFsInfo,
+    },
     prelude::*,
     process::posix_thread::allocate_posix_tid,
+    sched::SchedPolicy,
     thread::{AsThread, Tid},
 };
 
@@ -82,7 +87,7 @@ bitflags! {
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CloneArgs {
     pub flags: CloneFlags,
-    pub _pidfd: Option<u64>,
+    pub pidfd: Option<u64>,
     pub child_tid: Vaddr,
     pub parent_tid: Option<Vaddr>,
     pub exit_signal: Option<SigNum>,
@@ -126,7 +131,7 @@ impl CloneArgs {
 
         Ok(Self {
             flags,
-            _pidfd: pidfd,
+            pidfd,
             child_tid,
             parent_tid,
             exit_signal: (exit_signal != 0).then(|| SigNum::from_u8(exit_signal as u8)),
@@ -158,6 +163,7 @@ impl CloneFlags {
             | CloneFlags::CLONE_FS
             | CloneFlags::CLONE_FILES
             | CloneFlags::CLONE_SIGHAND
+            | CloneFlags::CLONE_PIDFD
             | CloneFlags::CLONE_THREAD
             | CloneFlags::CLONE_SYSVSEM
             | CloneFlags::CLONE_SETTLS
@@ -182,7 +188,19 @@ pub fn clone_child(
     clone_args: CloneArgs,
 ) -> Result<Tid> {
     clone_args.flags.check_unsupported_flags()?;
+    check_nproc_limit(ctx)?;
+    // Like Linux, the bandwidth of a deadline thread cannot be inherited.
+    if matches!(ctx.thread.sched_attr().policy(), SchedPolicy::Deadline(_)) {
+        return_errno_with_message!(Errno::EAGAIN, "a deadline thread cannot be cloned");
+    }
     if clone_args.flags.contains(CloneFlags::CLONE_THREAD) {
+        if clone_args.flags.contains(CloneFlags::CLONE_PIDFD) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "CLONE_PIDFD cannot be specified with CLONE_THREAD"
+            );
+        }
+
         let child_task = clone_child_task(ctx, parent_context, clone_args)?;
         let child_thread = child_task.as_thread().unwrap();
         child_thread.run();
@@ -198,6 +216,34 @@ pub fn clone_child(
     }
 }
 
+/// Checks whether the user of the current thread can create one more thread
+/// under the `RLIMIT_NPROC` limit.
+///
+/// Similar to Linux, the limit does not apply to the root user and the users
+/// with `CAP_SYS_RESOURCE` or `CAP_SYS_ADMIN`.
+fn check_nproc_limit(ctx: &Context) -> Result<()> {
+    let credentials = ctx.posix_thread.credentials();
+    let ruid = credentials.ruid();
+    if ruid.is_root()
+        || credentials
+            .effective_capset()
+            .intersects(CapSet::SYS_RESOURCE | CapSet::SYS_ADMIN)
+    {
+        return Ok(());
+    }
+
+    let nproc_limit = ctx
+        .process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_NPROC)
+        .get_cur();
+    if thread_table::count_threads_of_user(ruid) as u64 >= nproc_limit {
+        return_errno_with_message!(Errno::EAGAIN, "the number of threads exceeds RLIMIT_NPROC");
+    }
+    Ok(())
+}
+
 fn clone_child_task(
     ctx: &Context,
     parent_context: &UserContext,
@@ -253,11 +299,13 @@ fn clone_child_task(
             Credentials::new_from(&credentials)
         };
 
+        // Inherit the scheduling policy of the current thread.
         let mut thread_builder = PosixThreadBuilder::new(child_tid, child_user_space, credentials)
             .process(posix_thread.weak_process())
             .sig_mask(sig_mask)
             .file_table(child_file_table)
-            .fs(child_fs);
+            .fs(child_fs)
+            .sched_policy(ctx.thread.sched_attr().policy());
 
         // Deal with SETTID/CLEARTID flags
         clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
@@ -267,11 +315,20 @@ fn clone_child_task(
         thread_builder.build()
     };
 
-    process
-        .tasks()
-        .lock()
-        .insert(child_task.clone())
-        .map_err(|_| Error::with_message(Errno::EINTR, "the process has exited"))?;
+    let mut tasks = process.tasks().lock();
+    // Charge the child thread to the cgroup, which also enforces `pids.max`.
+    // The thread is removed from the thread table if it cannot be added.
+    let cgroup = process.cgroup();
+    if let Err(err) = cgroup.charge_thread(child_task.as_thread().unwrap()) {
+        thread_table::remove_thread(child_tid);
+        return Err(err);
+    }
+    if tasks.insert(child_task.clone()).is_err() {
+        cgroup.uncharge_thread();
+        thread_table::remove_thread(child_tid);
+        return_errno_with_message!(Errno::EINTR, "the process has exited");
+    }
+    drop(tasks);
 
     Ok(child_task)
 }
@@ -327,6 +384,9 @@ fn clone_child_process(
     // inherit parent's sig mask
     let child_sig_mask = posix_thread.sig_mask().load(Ordering::Relaxed).into();
 
+    // inherit parent's resource limits
+    let child_resource_limits = process.resource_limits().lock().clone();
+
     // inherit parent's nice value
     let child_nice = process.nice().load(Ordering::Relaxed);
 
@@ -342,11 +402,13 @@ fn clone_child_process(
                 Credentials::new_from(&credentials)
             };
 
+            // inherit the scheduling policy of the current thread
             PosixThreadBuilder::new(child_tid, child_user_space, credentials)
                 .thread_name(Some(child_thread_name))
                 .sig_mask(child_sig_mask)
                 .file_table(child_file_table)
                 .fs(child_fs)
+                .sched_policy(ctx.thread.sched_attr().policy())
         };
 
         // Deal with SETTID/CLEARTID flags
@@ -362,22 +424,69 @@ fn clone_child_process(
         process_builder
             .main_thread_builder(child_thread_builder)
             .process_vm(child_process_vm)
+            .cgroup(process.cgroup())
             .sig_dispositions(child_sig_dispositions)
+            .resource_limits(child_resource_limits)
             .nice(child_nice);
 
         process_builder.build()?
     };
 
+    // Deal with the CLONE_PIDFD flag. The child is discarded on failure,
+    // since it has not been published yet.
+    if let Err(err) = clone_pidfd(ctx, &child, clone_args.pidfd, clone_flags) {
+        discard_child_process(&child);
+        return Err(err);
+    }
+
     if let Some(sig) = clone_args.exit_signal {
         child.set_exit_signal(sig);
     };
 
+    // inherit parent's OOM score adjustment
+    child.set_oom_score_adj(process.oom_score_adj());
+
+    // inherit parent's dumpable attribute
+    child.set_dumpable(process.is_dumpable());
+
     // Sets parent process and group for child process.
     set_parent_and_group(process, &child);
 
     Ok(child)
 }
 
+/// Discards a child process that is built but not published yet, which
+/// undoes what [`ProcessBuilder::build`] does to its main thread.
+fn discard_child_process(child: &Process) {
+    let tasks = child.tasks().lock();
+    child.cgroup().uncharge_thread();
+    thread_table::remove_thread(child.pid());
+    drop(tasks);
+}
+
+fn clone_pidfd(
+    ctx: &Context,
+    child: &Arc<Process>,
+    pidfd_ptr: Option<u64>,
+    clone_flags: CloneFlags,
+) -> Result<()> {
+    let Some(addr) = pidfd_ptr.filter(|_| clone_flags.contains(CloneFlags::CLONE_PIDFD)) else {
+        return Ok(());
+    };
+
+    let pid_file = PidFile::new(child.clone(), false);
+    let file_table = ctx.thread_local.file_table().borrow();
+    // A pidfd is always close-on-exec.
+    let fd = file_table
+        .write()
+        .insert(Arc::new(pid_file), FdFlags::CLOEXEC)?;
+    if let Err(err) = current_userspace!().write_val(addr as Vaddr, &fd) {
+        file_table.write().close_file(fd);
+        return Err(err);
+    }
+    Ok(())
+}
+
 fn clone_child_cleartid(
     child_builder: PosixThreadBuilder,
     child_tidptr: Vaddr,
@@ -416,7 +525,8 @@ fn clone_parent_settid(
 }
 
 /// Clone child process vm. If CLONE_VM is set, both threads share the same root vmar.
-/// Otherwise, fork a new copy-on-write vmar.
+/// Otherwise, fork a new copy-on-write vmar, where the mappings advised with
+/// `MADV_DONTFORK` are left out and those advised with `MADV_WIPEONFORK` are zero-filled.
 fn clone_vm(parent_process_vm: &ProcessVm, clone_flags: CloneFlags) -> Result<ProcessVm> {
     if clone_flags.contains(CloneFlags::CLONE_VM) {
         Ok(parent_process_vm.clone())
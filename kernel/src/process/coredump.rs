@@ -0,0 +1,826 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Core dumps in the ELF format.
+//!
+//! When a process is killed by a signal whose default action is to dump the
+//! core, an ELF core file is written with
+//! 1. a `PT_NOTE` segment that contains the `NT_PRSTATUS` note of each thread,
+//!    and the `NT_PRPSINFO`, `NT_SIGINFO`, `NT_AUXV` and `NT_FILE` notes;
+//! 2. a `PT_LOAD` segment for each mapping in the address space.
+//!
+//! Before the dump, the other threads are killed and their registers are
+//! recorded when they exit. The pages that are not resident are skipped,
+//! which leaves holes in the core file.
+//!
+//! The name of the core file is determined by `/proc/sys/kernel/core_pattern`,
+//! which is relative to the working directory of the process by default.
+//!
+//! See <https://man7.org/linux/man-pages/man5/core.5.html>.
+
+use alloc::borrow::Cow;
+use core::{fmt::Write, mem::size_of, ops::Range, sync::atomic::Ordering};
+
+use align_ext::AlignExt;
+use ostd::{cpu::UserContext, sync::WaitQueue};
+
+use super::{
+    posix_thread::{do_exit_group, sigkill_other_threads},
+    rlimit::ResourceType,
+    signal::{c_types::siginfo_t, constants::SIGKILL},
+    Process, TermStatus, MAX_ENVP_NUMBER,
+};
+use crate::{
+    arch::cpu::UserRegs,
+    fs::{
+        file_handle::FileLike,
+        fs_resolver::{FsPath, AT_FDCWD},
+        utils::{AccessMode, CreationFlags, InodeType},
+    },
+    prelude::*,
+    thread::{AsThread, Tid},
+    time::{clocks::RealTimeClock, timeval_t},
+    vm::{
+        perms::VmPerms,
+        vmar::vm_mapping::{VmMappingFlags, VmMappingInfo},
+    },
+};
+
+/// The maximum length of the core pattern.
+pub const CORE_PATTERN_MAX_LEN: usize = 127;
+
+/// The pattern to name the core files.
+static CORE_PATTERN: Mutex<Cow<'static, str>> = Mutex::new(Cow::Borrowed("core"));
+
+/// Returns the pattern to name the core files.
+pub fn core_pattern() -> String {
+    CORE_PATTERN.lock().to_string()
+}
+
+/// Sets the pattern to name the core files.
+///
+/// The pattern is truncated to [`CORE_PATTERN_MAX_LEN`] bytes, as Linux does.
+pub fn set_core_pattern(pattern: &str) {
+    let mut len = pattern.len().min(CORE_PATTERN_MAX_LEN);
+    while !pattern.is_char_boundary(len) {
+        len -= 1;
+    }
+    *CORE_PATTERN.lock() = Cow::Owned(pattern[..len].to_string());
+}
+
+/// Dumps the core of the current process, which is killed by the signal
+/// described by `sig_info`.
+///
+/// `user_ctx` is the user context of the current thread when the signal is
+/// delivered.
+///
+/// Returns whether the core file has been written completely.
+pub(super) fn do_coredump(ctx: &Context, user_ctx: &UserContext, sig_info: &siginfo_t) -> bool {
+    match dump_core(ctx, user_ctx, sig_info) {
+        Ok(is_dumped) => is_dumped,
+        Err(err) => {
+            warn!("failed to dump the core: {:?}", err);
+            false
+        }
+    }
+}
+
+fn dump_core(ctx: &Context, user_ctx: &UserContext, sig_info: &siginfo_t) -> Result<bool> {
+    let process = ctx.process;
+    if !process.is_dumpable() {
+        return Ok(false);
+    }
+
+    let limit = process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .get_cur() as usize;
+    // Linux does not dump the core if it cannot hold even a page.
+    if limit < PAGE_SIZE {
+        return Ok(false);
+    }
+
+    let pattern = core_pattern();
+    if pattern.starts_with('|') {
+        warn!("piping the core dumps to a program is not supported");
+        return Ok(false);
+    }
+    let core_name = format_core_name(&pattern, ctx, sig_info.si_signo, limit);
+    if core_name.is_empty() {
+        return Ok(false);
+    }
+
+    // Like Linux, the other threads are killed before the dump, so that the
+    // memory contents do not change during the dump.
+    let Some(core_dump) = kill_other_threads(ctx, sig_info) else {
+        return Ok(false);
+    };
+
+    let file = create_core_file(ctx, &core_name)?;
+    let mut writer = CoreWriter::new(file, limit);
+    write_elf_core(ctx, user_ctx, &core_dump, &mut writer)?;
+    writer.finish()?;
+
+    Ok(true)
+}
+
+/// The state of a core dump, which is shared by the threads of the process.
+pub(super) struct CoreDumpState {
+    dumper_tid: Tid,
+    sig_info: siginfo_t,
+    /// The `NT_PRSTATUS` notes of the other threads, recorded before they exit.
+    thread_statuses: Mutex<Vec<ElfPrstatus>>,
+    wait_queue: WaitQueue,
+}
+
+impl CoreDumpState {
+    fn new(dumper_tid: Tid, sig_info: siginfo_t) -> Self {
+        Self {
+            dumper_tid,
+            sig_info,
+            thread_statuses: Mutex::new(Vec::new()),
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Returns whether the thread is the one that dumps the core.
+    pub(super) fn is_dumper(&self, tid: Tid) -> bool {
+        self.dumper_tid == tid
+    }
+
+    /// Wakes up the thread that dumps the core, which waits for the other
+    /// threads to exit.
+    pub(super) fn wake_dumper(&self) {
+        self.wait_queue.wake_all();
+    }
+}
+
+/// Kills the other threads of the current process and waits for them to exit.
+///
+/// Returns `None` if another thread has started to exit the process. Only the
+/// first one dumps the core.
+fn kill_other_threads(ctx: &Context, sig_info: &siginfo_t) -> Option<Arc<CoreDumpState>> {
+    let core_dump = Arc::new(CoreDumpState::new(ctx.posix_thread.tid(), *sig_info));
+
+    let other_tasks = {
+        let mut tasks = ctx.process.tasks().lock();
+        if tasks.has_exited_group() {
+            return None;
+        }
+        sigkill_other_threads(ctx.task, &tasks);
+        tasks.set_core_dump(core_dump.clone());
+
+        tasks
+            .as_slice()
+            .iter()
+            .filter(|task| !core::ptr::eq(task.as_ref(), ctx.task))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    core_dump.wait_queue.wait_until(|| {
+        other_tasks
+            .iter()
+            .all(|task| task.as_thread().unwrap().is_exited())
+            .then_some(())
+    });
+
+    Some(core_dump)
+}
+
+/// Exits the current thread if it is killed by another thread that dumps the
+/// core, after recording the registers in `user_ctx` for the core file.
+///
+/// Returns whether the current thread has exited.
+pub(super) fn exit_for_coredump(ctx: &Context, user_ctx: &UserContext) -> bool {
+    let Some(core_dump) = ctx.process.tasks().lock().core_dump().cloned() else {
+        return false;
+    };
+    if core_dump.is_dumper(ctx.posix_thread.tid()) {
+        return false;
+    }
+
+    let prstatus = new_prstatus(ctx, user_ctx, &core_dump.sig_info);
+    core_dump.thread_statuses.lock().push(prstatus);
+
+    do_exit_group(TermStatus::Killed(SIGKILL));
+    true
+}
+
+/// Expands the `%` specifiers in the core pattern.
+///
+/// The unsupported specifiers (e.g., `%h`) are dropped, as Linux does for
+/// unknown specifiers.
+fn format_core_name(pattern: &str, ctx: &Context, sig_num: i32, limit: usize) -> String {
+    let process = ctx.process;
+    let credentials = ctx.posix_thread.credentials();
+
+    let mut core_name = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            core_name.push(c);
+            continue;
+        }
+
+        let Some(specifier) = chars.next() else {
+            break;
+        };
+        match specifier {
+            '%' => core_name.push('%'),
+            'p' | 'P' => write!(core_name, "{}", process.pid()).unwrap(),
+            'i' | 'I' => write!(core_name, "{}", ctx.posix_thread.tid()).unwrap(),
+            'u' => write!(core_name, "{}", u32::from(credentials.ruid())).unwrap(),
+            'g' => write!(core_name, "{}", u32::from(credentials.rgid())).unwrap(),
+            's' => write!(core_name, "{}", sig_num).unwrap(),
+            't' => write!(core_name, "{}", RealTimeClock::get().read_time().as_secs()).unwrap(),
+            'c' => write!(core_name, "{}", limit).unwrap(),
+            'e' => core_name.push_str(&thread_comm(ctx).replace('/', "!")),
+            'E' => core_name.push_str(&process.executable_path().replace('/', "!")),
+            _ => {}
+        }
+    }
+
+    core_name
+}
+
+/// Creates the core file, or truncates the existing one.
+fn create_core_file(ctx: &Context, core_name: &str) -> Result<Arc<dyn FileLike>> {
+    let fs = ctx.posix_thread.fs();
+    let flags =
+        AccessMode::O_WRONLY as u32 | (CreationFlags::O_CREAT | CreationFlags::O_NOFOLLOW).bits();
+    let mode = 0o600 & !fs.umask().read().get();
+    let fs_path = FsPath::new(AT_FDCWD, core_name)?;
+    let file: Arc<dyn FileLike> = Arc::new(fs.resolver().read().open(&fs_path, flags, mode)?);
+
+    // Like Linux, refuse to overwrite the files that may be used by others.
+    let metadata = file.metadata();
+    if metadata.type_ != InodeType::File {
+        return_errno_with_message!(Errno::EISDIR, "the core file is not a regular file");
+    }
+    if metadata.nlinks != 1 {
+        return_errno_with_message!(Errno::EPERM, "the core file has multiple hard links");
+    }
+    if metadata.uid != ctx.posix_thread.credentials().fsuid() {
+        return_errno_with_message!(Errno::EPERM, "the core file is owned by another user");
+    }
+
+    file.resize(0)?;
+    Ok(file)
+}
+
+/// Writes the core of the current process in the ELF format.
+fn write_elf_core(
+    ctx: &Context,
+    user_ctx: &UserContext,
+    core_dump: &CoreDumpState,
+    writer: &mut CoreWriter,
+) -> Result<()> {
+    let process = ctx.process;
+    let sig_info = &core_dump.sig_info;
+    let mappings = process.root_vmar().mappings_info();
+
+    let notes = {
+        let mut notes = NoteBuilder::new();
+        notes.add(
+            NT_PRSTATUS,
+            new_prstatus(ctx, user_ctx, sig_info).as_bytes(),
+        );
+        notes.add(NT_PRPSINFO, new_prpsinfo(ctx).as_bytes());
+        notes.add(NT_SIGINFO, sig_info.as_bytes());
+        match read_auxv(ctx) {
+            Ok(auxv) => notes.add(NT_AUXV, &auxv),
+            Err(err) => debug!("coredump: cannot read the auxiliary vector: {:?}", err),
+        }
+        notes.add(NT_FILE, &new_file_note(&mappings));
+        // Like Linux, the notes of the other threads follow the ones of the
+        // current thread.
+        for prstatus in core_dump.thread_statuses.lock().iter() {
+            notes.add(NT_PRSTATUS, prstatus.as_bytes());
+        }
+        notes.finish()
+    };
+
+    let nr_segments = u16::try_from(mappings.len() + 1)
+        .map_err(|_| Error::with_message(Errno::EFBIG, "too many mappings to dump"))?;
+    let notes_offset = size_of::<Elf64Ehdr>() + usize::from(nr_segments) * size_of::<Elf64Phdr>();
+    let data_offset = (notes_offset + notes.len()).align_up(PAGE_SIZE);
+
+    let ehdr = Elf64Ehdr::new_core(nr_segments);
+    writer.write(ehdr.as_bytes())?;
+
+    let note_phdr = Elf64Phdr {
+        type_: PT_NOTE,
+        flags: 0,
+        offset: notes_offset as u64,
+        vaddr: 0,
+        paddr: 0,
+        file_size: notes.len() as u64,
+        mem_size: 0,
+        align: 4,
+    };
+    writer.write(note_phdr.as_bytes())?;
+
+    let dump_sizes: Vec<usize> = mappings.iter().map(|info| dump_size(ctx, info)).collect();
+    let mut offset = data_offset;
+    for (info, dump_size) in mappings.iter().zip(dump_sizes.iter()) {
+        let load_phdr = Elf64Phdr {
+            type_: PT_LOAD,
+            flags: segment_flags(info.perms),
+            offset: offset as u64,
+            vaddr: info.range.start as u64,
+            paddr: 0,
+            file_size: *dump_size as u64,
+            mem_size: info.range.len() as u64,
+            align: PAGE_SIZE as u64,
+        };
+        writer.write(load_phdr.as_bytes())?;
+        offset += dump_size;
+    }
+
+    writer.write(&notes)?;
+    writer.pad_to(data_offset)?;
+
+    for (info, dump_size) in mappings.iter().zip(dump_sizes) {
+        let range = info.range.start..info.range.start + dump_size;
+        write_memory(ctx, info, range, writer)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the size of the memory to dump from the beginning of the mapping.
+///
+/// This follows the default `coredump_filter` of Linux, which dumps the
+/// anonymous mappings, the private file mappings that can be written and the
+/// ELF headers of the mapped files.
+fn dump_size(ctx: &Context, info: &VmMappingInfo) -> usize {
+    if info.flags.contains(VmMappingFlags::DONT_DUMP) || !info.perms.contains(VmPerms::READ) {
+        return 0;
+    }
+
+    if info.mapped_file.is_none() || (!info.is_shared && info.perms.contains(VmPerms::WRITE)) {
+        return info.range.len();
+    }
+
+    // The ELF header helps the debuggers to identify the mapped file.
+    const ELF_MAGIC: [u8; 4] = *b"\x7fELF";
+    if info.vmo_offset == 0
+        && ctx
+            .user_space()
+            .read_val::<[u8; 4]>(info.range.start)
+            .is_ok_and(|magic| magic == ELF_MAGIC)
+    {
+        return PAGE_SIZE;
+    }
+
+    0
+}
+
+/// Writes the memory contents in `range` of the mapping.
+///
+/// Like Linux, the missing pages of the anonymous mappings are not faulted in
+/// but skipped, which also avoids blocking on the userfaultfds. The pages that
+/// cannot be read are skipped as well.
+fn write_memory(
+    ctx: &Context,
+    info: &VmMappingInfo,
+    range: Range<Vaddr>,
+    writer: &mut CoreWriter,
+) -> Result<()> {
+    /// The maximum number of pages whose residency is queried at once.
+    const MAX_BATCH_PAGES: usize = 512;
+
+    // The swapped-out pages are not resident, so such mappings are read as a whole.
+    let checks_residency = info.mapped_file.is_none() && info.swap == 0;
+
+    let user_space = ctx.user_space();
+    let mut page = vec![0u8; PAGE_SIZE];
+    let mut batch_start = range.start;
+    while batch_start < range.end {
+        let batch_end = range.end.min(batch_start + MAX_BATCH_PAGES * PAGE_SIZE);
+        let batch = batch_start..batch_end;
+        let residency = if checks_residency {
+            Some(ctx.process.root_vmar().page_residency(batch.clone())?)
+        } else {
+            None
+        };
+
+        for (index, addr) in batch.step_by(PAGE_SIZE).enumerate() {
+            let is_resident = residency.as_ref().is_none_or(|residency| residency[index]);
+            if is_resident
+                && user_space
+                    .read_bytes(addr, &mut VmWriter::from(page.as_mut_slice()))
+                    .is_ok()
+            {
+                writer.write(&page)?;
+            } else {
+                writer.skip(PAGE_SIZE)?;
+            }
+        }
+
+        batch_start = batch_end;
+    }
+
+    Ok(())
+}
+
+fn segment_flags(perms: VmPerms) -> u32 {
+    let mut flags = 0;
+    if perms.contains(VmPerms::READ) {
+        flags |= PF_R;
+    }
+    if perms.contains(VmPerms::WRITE) {
+        flags |= PF_W;
+    }
+    if perms.contains(VmPerms::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+/// Returns the command name of the current thread.
+fn thread_comm(ctx: &Context) -> String {
+    let thread_name = ctx.posix_thread.thread_name().lock();
+    thread_name
+        .as_ref()
+        .and_then(|thread_name| thread_name.name().ok().flatten())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn new_prstatus(ctx: &Context, user_ctx: &UserContext, sig_info: &siginfo_t) -> ElfPrstatus {
+    let process = ctx.process;
+    let posix_thread = ctx.posix_thread;
+    let (pgrp, sid) = process_group_and_session(process);
+    let usage = process.resource_usage();
+    let children_usage = process.children_resource_usage();
+
+    ElfPrstatus {
+        info: ElfSiginfo {
+            signo: sig_info.si_signo,
+            code: sig_info.si_code,
+            errno: sig_info.si_errno,
+        },
+        cursig: sig_info.si_signo as u16,
+        _pad0: 0,
+        sigpend: u64::from(posix_thread.sig_pending()),
+        sighold: u64::from(posix_thread.sig_mask().load(Ordering::Relaxed)),
+        pid: posix_thread.tid() as i32,
+        ppid: process.parent().pid() as i32,
+        pgrp,
+        sid,
+        utime: timeval_t::from(usage.utime),
+        stime: timeval_t::from(usage.stime),
+        cutime: timeval_t::from(children_usage.utime),
+        cstime: timeval_t::from(children_usage.stime),
+        regs: UserRegs::from(user_ctx),
+        fpvalid: 0,
+        _pad1: 0,
+    }
+}
+
+fn new_prpsinfo(ctx: &Context) -> ElfPrpsinfo {
+    let process = ctx.process;
+    let credentials = ctx.posix_thread.credentials();
+    let (pgrp, sid) = process_group_and_session(process);
+
+    let mut fname = [0u8; 16];
+    let comm = thread_comm(ctx);
+    let len = comm.len().min(fname.len() - 1);
+    fname[..len].copy_from_slice(&comm.as_bytes()[..len]);
+
+    // The arguments are separated by spaces and truncated, as Linux does.
+    let mut psargs = [0u8; 80];
+    if let Ok(argv) = process.init_stack_reader().argv() {
+        let args = argv
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let len = args.len().min(psargs.len() - 1);
+        psargs[..len].copy_from_slice(&args.as_bytes()[..len]);
+    }
+
+    ElfPrpsinfo {
+        state: 0,
+        sname: b'R',
+        zomb: 0,
+        nice: i8::from(process.nice().load(Ordering::Relaxed)),
+        _pad0: 0,
+        flag: 0,
+        uid: u32::from(credentials.ruid()),
+        gid: u32::from(credentials.rgid()),
+        pid: process.pid() as i32,
+        ppid: process.parent().pid() as i32,
+        pgrp,
+        sid,
+        fname,
+        psargs,
+    }
+}
+
+fn process_group_and_session(process: &Process) -> (i32, i32) {
+    let pgrp = process.process_group().map_or(0, |pgrp| pgrp.pgid());
+    let sid = process.session().map_or(0, |session| session.sid());
+    (pgrp as i32, sid as i32)
+}
+
+/// Reads the auxiliary vector from the initial stack, including the ending `AT_NULL` entry.
+fn read_auxv(ctx: &Context) -> Result<Vec<u8>> {
+    /// The maximum number of entries in the auxiliary vector.
+    const MAX_AUXV_ENTRIES: usize = 64;
+    const AT_NULL: u64 = 0;
+
+    let user_space = ctx.user_space();
+    let read_u64 = |addr: Vaddr| user_space.read_val::<u64>(addr);
+
+    // Skip `argc`, the argument pointers and the environment pointers.
+    let mut addr = ctx.process.init_stack_reader().init_stack_bottom();
+    let argc = read_u64(addr)? as usize;
+    addr += (argc + 2) * size_of::<u64>();
+    for _ in 0..MAX_ENVP_NUMBER {
+        let envp = read_u64(addr)?;
+        addr += size_of::<u64>();
+        if envp == 0 {
+            break;
+        }
+    }
+
+    let mut auxv = Vec::new();
+    for _ in 0..MAX_AUXV_ENTRIES {
+        let key = read_u64(addr)?;
+        let value = read_u64(addr + size_of::<u64>())?;
+        addr += 2 * size_of::<u64>();
+
+        auxv.extend_from_slice(&key.to_ne_bytes());
+        auxv.extend_from_slice(&value.to_ne_bytes());
+        if key == AT_NULL {
+            return Ok(auxv);
+        }
+    }
+
+    return_errno_with_message!(Errno::EINVAL, "the auxiliary vector is corrupted");
+}
+
+/// Returns the description of the `NT_FILE` note, which lists the mapped files.
+fn new_file_note(mappings: &[VmMappingInfo]) -> Vec<u8> {
+    let files: Vec<_> = mappings
+        .iter()
+        .filter_map(|info| Some((info, info.mapped_file.as_ref()?.abs_path())))
+        .collect();
+
+    let mut desc = Vec::new();
+    let push_u64 = |desc: &mut Vec<u8>, value: usize| {
+        desc.extend_from_slice(&(value as u64).to_ne_bytes());
+    };
+    push_u64(&mut desc, files.len());
+    push_u64(&mut desc, PAGE_SIZE);
+    for (info, _) in files.iter() {
+        push_u64(&mut desc, info.range.start);
+        push_u64(&mut desc, info.range.end);
+        push_u64(&mut desc, info.vmo_offset / PAGE_SIZE);
+    }
+    for (_, path) in files.iter() {
+        desc.extend_from_slice(path.as_bytes());
+        desc.push(0);
+    }
+
+    desc
+}
+
+/// A writer that writes the core file sequentially within the size limit.
+///
+/// The skipped bytes are left as holes in the file.
+struct CoreWriter {
+    file: Arc<dyn FileLike>,
+    offset: usize,
+    limit: usize,
+}
+
+impl CoreWriter {
+    fn new(file: Arc<dyn FileLike>, limit: usize) -> Self {
+        Self {
+            file,
+            offset: 0,
+            limit,
+        }
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) -> Result<()> {
+        self.check_limit(bytes.len())?;
+
+        while !bytes.is_empty() {
+            let written_len = self.file.write_bytes_at(self.offset, bytes)?;
+            if written_len == 0 {
+                return_errno_with_message!(Errno::EIO, "the core file cannot be written");
+            }
+            bytes = &bytes[written_len..];
+            self.offset += written_len;
+        }
+
+        Ok(())
+    }
+
+    /// Skips `len` bytes without writing them.
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.check_limit(len)?;
+        self.offset += len;
+        Ok(())
+    }
+
+    /// Pads the file with zeros up to `offset`.
+    fn pad_to(&mut self, offset: usize) -> Result<()> {
+        let len = offset.saturating_sub(self.offset);
+        self.write(&vec![0u8; len])
+    }
+
+    /// Extends the file to the end of the written bytes, in case that the
+    /// last bytes are skipped.
+    fn finish(self) -> Result<()> {
+        self.file.resize(self.offset)
+    }
+
+    fn check_limit(&self, len: usize) -> Result<()> {
+        if self.offset + len > self.limit {
+            return_errno_with_message!(Errno::EFBIG, "the core file exceeds RLIMIT_CORE");
+        }
+        Ok(())
+    }
+}
+
+/// A builder of the notes in the `PT_NOTE` segment.
+struct NoteBuilder(Vec<u8>);
+
+impl NoteBuilder {
+    const NAME: &'static [u8] = b"CORE\0";
+
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn add(&mut self, type_: u32, desc: &[u8]) {
+        let header = Elf64Nhdr {
+            name_size: Self::NAME.len() as u32,
+            desc_size: desc.len() as u32,
+            type_,
+        };
+        self.0.extend_from_slice(header.as_bytes());
+        self.0.extend_from_slice(Self::NAME);
+        self.pad();
+        self.0.extend_from_slice(desc);
+        self.pad();
+    }
+
+    /// Pads the notes to the 4-byte alignment.
+    fn pad(&mut self) {
+        let len = self.0.len().align_up(4);
+        self.0.resize(len, 0);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+const EM_CURRENT: u16 = 62; // EM_X86_64
+#[cfg(target_arch = "riscv64")]
+const EM_CURRENT: u16 = 243; // EM_RISCV
+
+const ET_CORE: u16 = 4;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+
+const PF_X: u32 = 1 << 0;
+const PF_W: u32 = 1 << 1;
+const PF_R: u32 = 1 << 2;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_SIGINFO: u32 = 0x53494749;
+const NT_FILE: u32 = 0x46494c45;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct Elf64Ehdr {
+    ident: [u8; 16],
+    type_: u16,
+    machine: u16,
+    version: u32,
+    entry: u64,
+    phoff: u64,
+    shoff: u64,
+    flags: u32,
+    ehsize: u16,
+    phentsize: u16,
+    phnum: u16,
+    shentsize: u16,
+    shnum: u16,
+    shstrndx: u16,
+}
+
+impl Elf64Ehdr {
+    fn new_core(phnum: u16) -> Self {
+        const ELFCLASS64: u8 = 2;
+        const ELFDATA2LSB: u8 = 1;
+        const EV_CURRENT: u8 = 1;
+
+        let mut ident = [0u8; 16];
+        ident[..4].copy_from_slice(b"\x7fELF");
+        ident[4] = ELFCLASS64;
+        ident[5] = ELFDATA2LSB;
+        ident[6] = EV_CURRENT;
+
+        Self {
+            ident,
+            type_: ET_CORE,
+            machine: EM_CURRENT,
+            version: EV_CURRENT as u32,
+            entry: 0,
+            phoff: size_of::<Elf64Ehdr>() as u64,
+            shoff: 0,
+            flags: 0,
+            ehsize: size_of::<Elf64Ehdr>() as u16,
+            phentsize: size_of::<Elf64Phdr>() as u16,
+            phnum,
+            shentsize: 0,
+            shnum: 0,
+            shstrndx: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct Elf64Phdr {
+    type_: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    paddr: u64,
+    file_size: u64,
+    mem_size: u64,
+    align: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct Elf64Nhdr {
+    name_size: u32,
+    desc_size: u32,
+    type_: u32,
+}
+
+/// The signal information in `struct elf_prstatus` of Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct ElfSiginfo {
+    signo: i32,
+    code: i32,
+    errno: i32,
+}
+
+/// The description of the `NT_PRSTATUS` note, i.e., `struct elf_prstatus` of Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct ElfPrstatus {
+    info: ElfSiginfo,
+    cursig: u16,
+    _pad0: u16,
+    sigpend: u64,
+    sighold: u64,
+    pid: i32,
+    ppid: i32,
+    pgrp: i32,
+    sid: i32,
+    utime: timeval_t,
+    stime: timeval_t,
+    cutime: timeval_t,
+    cstime: timeval_t,
+    regs: UserRegs,
+    fpvalid: i32,
+    _pad1: u32,
+}
+
+/// The description of the `NT_PRPSINFO` note, i.e., `struct elf_prpsinfo` of Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct ElfPrpsinfo {
+    state: u8,
+    sname: u8,
+    zomb: u8,
+    nice: i8,
+    _pad0: u32,
+    flag: u64,
+    uid: u32,
+    gid: u32,
+    pid: i32,
+    ppid: i32,
+    pgrp: i32,
+    sid: i32,
+    fname: [u8; 16],
+    psargs: [u8; 80],
+}
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::{posix_thread::ThreadLocal, process_table, Pid, Process};
-use crate::{prelude::*, process::signal::signals::kernel::KernelSignal};
+use crate::{events::IoEvents, prelude::*, process::signal::signals::kernel::KernelSignal};
 
 /// Exits the current POSIX process.
 ///
@@ -22,6 +22,8 @@ pub(super) fn exit_process(thread_local: &ThreadLocal, current_process: &Process
     move_children_to_init(current_process);
 
     send_child_death_signal(current_process);
+
+    current_process.pidfd_pollee().notify(IoEvents::IN);
 }
 
 /// Sends parent-death signals to the children.
@@ -6,7 +6,7 @@ use super::{
     signal::{
         constants::SIGCONT,
         sig_num::SigNum,
-        signals::{user::UserSignal, Signal},
+        signals::{kernel::KernelSignal, user::UserSignal, Signal},
     },
     Pgid, Pid, Process, Sid, Uid,
 };
@@ -42,6 +42,26 @@ pub fn kill(pid: Pid, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     kill_process(&process, signal, ctx)
 }
 
+/// Sends a signal to a process referred to by a pidfd, using the current
+/// process as the sender.
+///
+/// Unlike [`kill`], the target process is not looked up by its PID, so the
+/// signal will never be sent to another process that reuses the PID.
+///
+/// If `signal` is `None`, this method will only check permission without sending
+/// any signal.
+pub fn kill_pidfd(process: &Process, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
+    if process.status().is_reaped() {
+        return_errno_with_message!(Errno::ESRCH, "the target process has been reaped");
+    }
+
+    if core::ptr::eq(process, ctx.process) {
+        return kill(process.pid(), signal, ctx);
+    }
+
+    kill_process(process, signal, ctx)
+}
+
 /// Sends a signal to all processes in a group, using the current process
 /// as the sender.
 ///
@@ -116,6 +136,14 @@ pub fn kill_all(signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     Ok(())
 }
 
+/// Sends a signal to a process on behalf of the kernel.
+///
+/// No permission is checked, since the signal is not sent by any process.
+/// For example, the OOM killer uses this to send `SIGKILL` to the victim.
+pub(super) fn kill_by_kernel(process: &Process, signum: SigNum) {
+    process.enqueue_signal(KernelSignal::new(signum));
+}
+
 fn kill_process(process: &Process, signal: Option<UserSignal>, ctx: &Context) -> Result<()> {
     let tasks = process.tasks().lock();
 
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The out-of-memory (OOM) killer.
+//!
+//! When a page fault cannot be handled or a frame cannot be allocated because
+//! the memory is exhausted, the OOM killer selects the process with the highest
+//! badness score and kills it with `SIGKILL`, so that its memory can be freed.
+//!
+//! The badness score of a process is its resident set size in pages, adjusted
+//! by `/proc/[pid]/oom_score_adj`. The adjustment is in the unit of 1/1000 of
+//! the total memory (including the swap space). A process whose adjustment is
+//! [`OOM_SCORE_ADJ_MIN`] is never killed.
+//!
+//! If a memory cgroup exceeds its `memory.max`, only the processes in the
+//! cgroup are considered, since killing other processes does not help.
+
+use ostd::mm::stat;
+
+use super::{
+    cgroup::Cgroup, kill::kill_by_kernel, process_table, signal::constants::SIGKILL, Process,
+};
+use crate::{prelude::*, thread::Thread, vm::swap::swap_stat};
+
+/// The minimum OOM score adjustment, which disables the OOM killing.
+pub const OOM_SCORE_ADJ_MIN: i16 = -1000;
+/// The maximum OOM score adjustment.
+pub const OOM_SCORE_ADJ_MAX: i16 = 1000;
+
+/// The last process killed by the OOM killer.
+static LAST_VICTIM: Mutex<Weak<Process>> = Mutex::new(Weak::new());
+
+/// Returns the total number of pages of the memory and the swap space.
+fn total_pages() -> usize {
+    (stat::mem_total() + swap_stat().0) / PAGE_SIZE
+}
+
+/// Returns the badness score of the process.
+///
+/// Returns `None` if the process cannot be killed by the OOM killer.
+fn badness(process: &Process, total_pages: usize) -> Option<isize> {
+    let oom_score_adj = process.oom_score_adj();
+    if oom_score_adj == OOM_SCORE_ADJ_MIN
+        || process.is_init_process()
+        || process.status().is_zombie()
+    {
+        return None;
+    }
+
+    let points = (process.vm().rss() / PAGE_SIZE) as isize;
+    let adj = oom_score_adj as isize * (total_pages / 1000) as isize;
+    Some(points + adj)
+}
+
+/// Returns the OOM score of the process, which is reported in
+/// `/proc/[pid]/oom_score`.
+///
+/// Similar to Linux, the score is the badness score scaled to the range of
+/// `0..=1333`.
+pub fn oom_score(process: &Process) -> usize {
+    let total_pages = total_pages().max(1);
+    let Some(badness) = badness(process, total_pages) else {
+        return 0;
+    };
+    ((1000 + badness * 1000 / total_pages as isize) * 2 / 3).clamp(0, 1333) as usize
+}
+
+/// Kills the process with the highest badness score to free memory.
+///
+/// If the last victim has not exited yet, no more processes are killed, since
+/// its memory will be freed soon.
+///
+/// Returns whether the failed allocation is worth retrying, which is `false`
+/// if the current process is the victim or no process can be killed.
+pub fn out_of_memory() -> bool {
+    kill_victim(None)
+}
+
+/// Kills the process with the highest badness score in the memory cgroup
+/// (including its descendants) whose `memory.max` is exceeded.
+///
+/// The return value has the same meaning as that of [`out_of_memory`].
+pub fn memory_cgroup_out_of_memory(cgroup: &Cgroup) -> bool {
+    kill_victim(Some(cgroup))
+}
+
+fn kill_victim(cgroup: Option<&Cgroup>) -> bool {
+    let current = Process::current();
+    let is_current =
+        |victim: &Arc<Process>| current.as_ref().is_some_and(|cur| Arc::ptr_eq(victim, cur));
+    let is_in_cgroup =
+        |process: &Process| cgroup.is_none_or(|cgroup| process.cgroup().is_descendant_of(cgroup));
+
+    let mut last_victim = LAST_VICTIM.lock();
+    if let Some(victim) = last_victim.upgrade() {
+        if !victim.status().is_zombie() && is_in_cgroup(&victim) {
+            return !is_current(&victim);
+        }
+    }
+
+    let total_pages = total_pages();
+    let processes = process_table::process_table_mut()
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    let Some((victim, points)) = processes
+        .into_iter()
+        .filter(|process| is_in_cgroup(process))
+        .filter_map(|process| {
+            let points = badness(&process, total_pages)?;
+            Some((process, points))
+        })
+        .max_by_key(|(_, points)| *points)
+    else {
+        warn!("out of memory: no killable processes");
+        return false;
+    };
+
+    let domain = cgroup.map_or(String::new(), |cgroup| {
+        format!(" in cgroup {}", cgroup.path())
+    });
+    warn!(
+        "out of memory{}: killed process {} ({}), rss: {} kB, oom_score_adj: {}, badness: {}",
+        domain,
+        victim.pid(),
+        victim.executable_path(),
+        victim.vm().rss() / 1024,
+        victim.oom_score_adj(),
+        points
+    );
+    kill_by_kernel(&victim, SIGKILL);
+    *last_victim = Arc::downgrade(&victim);
+
+    !is_current(&victim)
+}
+
+/// Allocates frames with `alloc`, invoking the OOM killer and retrying if the
+/// memory is exhausted.
+///
+/// The retries are bounded, since the victim may not be able to exit while
+/// the caller holds some locks that it needs.
+pub fn alloc_or_oom<T>(mut alloc: impl FnMut() -> ostd::Result<T>) -> Result<T> {
+    /// The maximum number of retries after invoking the OOM killer.
+    const MAX_RETRIES: usize = 1000;
+
+    for _ in 0..MAX_RETRIES {
+        match alloc() {
+            Err(ostd::Error::NoMemory) if out_of_memory() => Thread::yield_now(),
+            result => return Ok(result?),
+        }
+    }
+    Ok(alloc()?)
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The file descriptors referring to processes (pidfds).
+//!
+//! A pidfd refers to a process stably, so it is not affected by the PID reuse.
+//! It becomes readable once the process exits, and hangs up once the process
+//! is reaped by its parent.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::{
+    signal::{PollHandle, Pollable},
+    Gid, Process, Uid,
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        utils::{InodeMode, InodeType, Metadata, StatusFlags},
+    },
+    prelude::*,
+    time::clocks::RealTimeClock,
+};
+
+/// A file referring to a process.
+pub struct PidFile {
+    process: Arc<Process>,
+    is_nonblocking: AtomicBool,
+}
+
+impl PidFile {
+    pub fn new(process: Arc<Process>, is_nonblocking: bool) -> Self {
+        Self {
+            process,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+        }
+    }
+
+    /// Returns the process that the file refers to.
+    pub fn process(&self) -> &Arc<Process> {
+        &self.process
+    }
+
+    pub fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let status = self.process.status();
+        if status.is_reaped() {
+            IoEvents::IN | IoEvents::HUP
+        } else if status.is_zombie() {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+}
+
+impl Pollable for PidFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.process
+            .pidfd_pollee()
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for PidFile {
+    fn read(&self, _writer: &mut VmWriter) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "a pidfd cannot be read");
+    }
+
+    fn write(&self, _reader: &mut VmReader) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "a pidfd cannot be written");
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking() {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `PidFile` to it.
+        let now = RealTimeClock::get().read_time();
+        Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: now,
+            mtime: now,
+            ctime: now,
+            type_: InodeType::NamedPipe,
+            mode: InodeMode::from_bits_truncate(0o600),
+            nlinks: 1,
+            uid: Uid::new_root(),
+            gid: Gid::new_root(),
+            rdev: 0,
+        }
+    }
+}
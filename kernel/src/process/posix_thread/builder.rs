@@ -11,9 +11,9 @@ use crate::{
     process::{
         posix_thread::name::ThreadName,
         signal::{sig_mask::AtomicSigMask, sig_queues::SigQueues},
-        Credentials, Process,
+        Credentials, Process, RusageCounters,
     },
-    sched::priority::Priority,
+    sched::{priority::Priority, SchedPolicy},
     thread::{task, Thread, Tid},
     time::{clocks::ProfClock, TimerManager},
 };
@@ -35,6 +35,7 @@ pub struct PosixThreadBuilder {
     sig_mask: AtomicSigMask,
     sig_queues: SigQueues,
     priority: Priority,
+    sched_policy: Option<SchedPolicy>,
 }
 
 impl PosixThreadBuilder {
@@ -52,6 +53,7 @@ impl PosixThreadBuilder {
             sig_mask: AtomicSigMask::new_empty(),
             sig_queues: SigQueues::new(),
             priority: Priority::default(),
+            sched_policy: None,
         }
     }
 
@@ -95,6 +97,15 @@ impl PosixThreadBuilder {
         self
     }
 
+    /// Sets the scheduling policy of the thread, which must not be
+    /// [`SchedPolicy::Deadline`].
+    ///
+    /// If it is not set, the policy is derived from the priority.
+    pub fn sched_policy(mut self, sched_policy: SchedPolicy) -> Self {
+        self.sched_policy = Some(sched_policy);
+        self
+    }
+
     pub fn build(self) -> Arc<Task> {
         let Self {
             tid,
@@ -109,12 +120,15 @@ impl PosixThreadBuilder {
             sig_mask,
             sig_queues,
             priority,
+            sched_policy,
         } = self;
 
         let file_table = file_table.unwrap_or_else(|| RwArc::new(FileTable::new_with_stdio()));
 
         let fs = fs.unwrap_or_else(|| Arc::new(ThreadFsInfo::default()));
 
+        let sched_policy = sched_policy.unwrap_or_else(|| priority.into());
+
         Arc::new_cyclic(|weak_task| {
             let posix_thread = {
                 let prof_clock = ProfClock::new();
@@ -134,6 +148,7 @@ impl PosixThreadBuilder {
                     prof_clock,
                     virtual_timer_manager,
                     prof_timer_manager,
+                    rusage: RusageCounters::new(),
                 }
             };
 
@@ -143,6 +158,7 @@ impl PosixThreadBuilder {
                 posix_thread,
                 priority,
                 cpu_affinity,
+                sched_policy,
             ));
 
             let thread_local = ThreadLocal::new(set_child_tid, clear_child_tid, file_table);
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use ostd::task::{CurrentTask, Task};
+use ostd::task::Task;
 
 use super::{
     futex::futex_wake, robust_list::wake_robust_futex, thread_table, AsPosixThread, AsThreadLocal,
@@ -53,9 +53,15 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
             tasks.set_exited_group();
         }
 
+        // The thread dumping the core has initiated the `exit_group` before the dump, but
+        // its exit code is determined after the dump.
+        let is_dumping_core = tasks
+            .core_dump()
+            .is_some_and(|core_dump| core_dump.is_dumper(posix_thread.tid()));
+
         // According to Linux's behavior, the last thread's exit code will become the process's
         // exit code, so here we should just overwrite the old value (if any).
-        if !has_exited_group {
+        if !has_exited_group || is_dumping_core {
             posix_process.status().set_exit_code(term_status.as_u32());
         }
 
@@ -65,6 +71,16 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
             return;
         }
         current_thread.exit();
+        if let Some(core_dump) = tasks.core_dump() {
+            core_dump.wake_dumper();
+        }
+        posix_process.cgroup().uncharge_thread();
+
+        // The main thread is kept in the task set, so its usage is still
+        // counted there.
+        if posix_thread.tid() != posix_process.pid() {
+            posix_process.add_exited_thread_rusage(posix_thread.rusage());
+        }
 
         tasks.remove_exited(&current_task)
     };
@@ -86,10 +102,11 @@ fn exit_internal(term_status: TermStatus, is_exiting_group: bool) {
 
 /// Sends `SIGKILL` to all other threads in the current process.
 ///
-/// This is only needed when initiating an `exit_group` for the first time.
-fn sigkill_other_threads(current_task: &CurrentTask, task_set: &TaskSet) {
+/// This is only needed when initiating an `exit_group` for the first time, or
+/// before dumping the core.
+pub(in crate::process) fn sigkill_other_threads(current_task: &Task, task_set: &TaskSet) {
     for task in task_set.as_slice() {
-        if core::ptr::eq(current_task.as_ref(), task.as_ref()) {
+        if core::ptr::eq(current_task, task.as_ref()) {
             continue;
         }
         task.as_posix_thread()
@@ -17,7 +17,7 @@ use super::{
         signals::Signal,
         SigEvents, SigEventsFilter,
     },
-    Credentials, Process,
+    Credentials, Process, RusageCounters,
 };
 use crate::{
     events::Observer,
@@ -25,7 +25,7 @@ use crate::{
     prelude::*,
     process::signal::constants::SIGCONT,
     thread::{Thread, Tid},
-    time::{clocks::ProfClock, Timer, TimerManager},
+    time::{clocks::ProfClock, TimerManager},
 };
 
 mod builder;
@@ -38,6 +38,7 @@ mod thread_local;
 pub mod thread_table;
 
 pub use builder::PosixThreadBuilder;
+pub(in crate::process) use exit::sigkill_other_threads;
 pub use exit::{do_exit, do_exit_group};
 pub use name::{ThreadName, MAX_THREAD_NAME_LEN};
 pub use posix_thread_ext::{create_posix_task_from_executable, AsPosixThread};
@@ -78,6 +79,9 @@ pub struct PosixThread {
 
     /// A manager that manages timers based on the profiling clock of the current thread.
     prof_timer_manager: Arc<TimerManager>,
+
+    /// The counters of the resource usage of the thread.
+    rusage: RusageCounters,
 }
 
 impl PosixThread {
@@ -218,26 +222,19 @@ impl PosixThread {
         &self.prof_clock
     }
 
-    /// Creates a timer based on the profiling CPU clock of the current thread.
-    pub fn create_prof_timer<F>(&self, func: F) -> Arc<Timer>
-    where
-        F: Fn() + Send + Sync + 'static,
-    {
-        self.prof_timer_manager.create_timer(func)
+    /// Returns the manager of the timers based on the profiling CPU clock of the thread.
+    pub fn prof_timer_manager(&self) -> &Arc<TimerManager> {
+        &self.prof_timer_manager
     }
 
-    /// Creates a timer based on the user CPU clock of the current thread.
-    pub fn create_virtual_timer<F>(&self, func: F) -> Arc<Timer>
-    where
-        F: Fn() + Send + Sync + 'static,
-    {
-        self.virtual_timer_manager.create_timer(func)
+    /// Returns the manager of the timers based on the user CPU clock of the thread.
+    pub fn virtual_timer_manager(&self) -> &Arc<TimerManager> {
+        &self.virtual_timer_manager
     }
 
-    /// Checks the `TimerCallback`s that are managed by the `prof_timer_manager`.
-    /// If any have timed out, call the corresponding callback functions.
-    pub fn process_expired_timers(&self) {
-        self.prof_timer_manager.process_expired_timers();
+    /// Returns the counters of the resource usage of the thread.
+    pub fn rusage(&self) -> &RusageCounters {
+        &self.rusage
     }
 
     pub fn dequeue_signal(&self, mask: &SigMask) -> Option<Box<dyn Signal>> {
@@ -5,7 +5,11 @@ use core::cell::{Cell, RefCell};
 use ostd::{mm::Vaddr, sync::RwArc, task::CurrentTask};
 
 use super::RobustListHead;
-use crate::{fs::file_table::FileTable, process::signal::SigStack};
+use crate::{
+    fs::file_table::FileTable,
+    prelude::*,
+    process::{cgroup::Cgroup, signal::SigStack},
+};
 
 /// Local data for a POSIX thread.
 pub struct ThreadLocal {
@@ -28,6 +32,11 @@ pub struct ThreadLocal {
     sig_context: Cell<Option<Vaddr>>,
     /// Stack address, size, and flags for the signal handler.
     sig_stack: RefCell<Option<SigStack>>,
+
+    // Memory.
+    /// The memory cgroup whose `memory.max` is exceeded by the last failed
+    /// charge, in which the OOM killer selects the victim.
+    memory_oom_cgroup: RefCell<Option<Arc<Cgroup>>>,
 }
 
 impl ThreadLocal {
@@ -43,6 +52,7 @@ impl ThreadLocal {
             file_table: RefCell::new(file_table),
             sig_context: Cell::new(None),
             sig_stack: RefCell::new(None),
+            memory_oom_cgroup: RefCell::new(None),
         }
     }
 
@@ -69,6 +79,10 @@ impl ThreadLocal {
     pub fn sig_stack(&self) -> &RefCell<Option<SigStack>> {
         &self.sig_stack
     }
+
+    pub fn memory_oom_cgroup(&self) -> &RefCell<Option<Arc<Cgroup>>> {
+        &self.memory_oom_cgroup
+    }
 }
 
 /// A trait to provide the `as_thread_local` method for tasks.
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::{Thread, Tid};
-use crate::{prelude::*, process::posix_thread::AsPosixThread};
+use crate::{
+    prelude::*,
+    process::{posix_thread::AsPosixThread, Uid},
+};
 
 static THREAD_TABLE: SpinLock<BTreeMap<Tid, Arc<Thread>>> = SpinLock::new(BTreeMap::new());
 
@@ -20,3 +23,12 @@ pub fn remove_thread(tid: Tid) {
 pub fn get_thread(tid: Tid) -> Option<Arc<Thread>> {
     THREAD_TABLE.lock().get(&tid).cloned()
 }
+
+/// Counts the posix threads whose real user ID is `uid` in the global thread table
+pub fn count_threads_of_user(uid: Uid) -> usize {
+    THREAD_TABLE
+        .lock()
+        .values()
+        .filter(|thread| thread.as_posix_thread().unwrap().credentials().ruid() == uid)
+        .count()
+}
@@ -6,13 +6,15 @@ use super::{Pid, Process};
 use crate::{
     prelude::*,
     process::{
-        posix_thread::{create_posix_task_from_executable, PosixThreadBuilder},
+        cgroup::Cgroup,
+        posix_thread::{create_posix_task_from_executable, thread_table, PosixThreadBuilder},
         process_vm::ProcessVm,
         rlimit::ResourceLimits,
         signal::sig_disposition::SigDispositions,
         Credentials,
     },
     sched::priority::Nice,
+    thread::AsThread,
 };
 
 pub struct ProcessBuilder<'a> {
@@ -26,6 +28,7 @@ pub struct ProcessBuilder<'a> {
     argv: Option<Vec<CString>>,
     envp: Option<Vec<CString>>,
     process_vm: Option<ProcessVm>,
+    cgroup: Option<Arc<Cgroup>>,
     resource_limits: Option<ResourceLimits>,
     sig_dispositions: Option<Arc<Mutex<SigDispositions>>>,
     credentials: Option<Credentials>,
@@ -42,6 +45,7 @@ impl<'a> ProcessBuilder<'a> {
             argv: None,
             envp: None,
             process_vm: None,
+            cgroup: None,
             resource_limits: None,
             sig_dispositions: None,
             credentials: None,
@@ -59,6 +63,11 @@ impl<'a> ProcessBuilder<'a> {
         self
     }
 
+    pub fn cgroup(&mut self, cgroup: Arc<Cgroup>) -> &mut Self {
+        self.cgroup = Some(cgroup);
+        self
+    }
+
     pub fn resource_limits(&mut self, resource_limits: ResourceLimits) -> &mut Self {
         self.resource_limits = Some(resource_limits);
         self
@@ -117,6 +126,7 @@ impl<'a> ProcessBuilder<'a> {
             argv,
             envp,
             process_vm,
+            cgroup,
             resource_limits,
             sig_dispositions,
             credentials,
@@ -125,6 +135,8 @@ impl<'a> ProcessBuilder<'a> {
 
         let process_vm = process_vm.or_else(|| Some(ProcessVm::alloc())).unwrap();
 
+        let cgroup = cgroup.unwrap_or_else(|| Cgroup::root().clone());
+
         let resource_limits = resource_limits
             .or_else(|| Some(ResourceLimits::default()))
             .unwrap();
@@ -140,6 +152,7 @@ impl<'a> ProcessBuilder<'a> {
             parent,
             executable_path.to_string(),
             process_vm,
+            cgroup.clone(),
             resource_limits,
             nice,
             sig_dispositions,
@@ -160,7 +173,15 @@ impl<'a> ProcessBuilder<'a> {
             )?
         };
 
-        process.tasks().lock().insert(task).unwrap();
+        let mut tasks = process.tasks().lock();
+        // The main thread is removed from the thread table if it cannot be
+        // charged to the cgroup.
+        if let Err(err) = cgroup.charge_thread(task.as_thread().unwrap()) {
+            thread_table::remove_thread(pid);
+            return Err(err);
+        }
+        tasks.insert(task).unwrap();
+        drop(tasks);
 
         Ok(process)
     }
@@ -1,17 +1,23 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::{
+    sync::atomic::{AtomicBool, AtomicI16, AtomicU32, Ordering},
+    time::Duration,
+};
 
 use self::timer_manager::PosixTimerManager;
 use super::{
+    cgroup::Cgroup,
     posix_thread::{allocate_posix_tid, AsPosixThread},
     process_table,
     process_vm::{Heap, InitStackReader, ProcessVm},
-    rlimit::ResourceLimits,
+    rlimit::{ResourceLimits, ResourceType},
+    rusage::{ResourceUsage, RusageCounters},
     signal::{
         sig_disposition::SigDispositions,
         sig_num::{AtomicSigNum, SigNum},
         signals::Signal,
+        Pollee,
     },
     status::ProcessStatus,
     task_set::TaskSet,
@@ -22,12 +28,16 @@ use crate::{
     prelude::*,
     sched::priority::{AtomicNice, Nice},
     thread::{AsThread, Thread},
-    time::clocks::ProfClock,
+    time::{
+        clocks::{BootTimeClock, ProfClock},
+        Clock,
+    },
     vm::vmar::Vmar,
 };
 
 mod builder;
 mod job_control;
+mod posix_timer;
 mod process_group;
 mod session;
 mod terminal;
@@ -38,6 +48,7 @@ use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 pub use builder::ProcessBuilder;
 pub use job_control::JobControl;
 use ostd::{sync::WaitQueue, task::Task};
+pub use posix_timer::{PosixTimer, TimerNotify};
 pub use process_group::ProcessGroup;
 pub use session::Session;
 pub use terminal::Terminal;
@@ -81,12 +92,18 @@ pub struct Process {
     children: Mutex<BTreeMap<Pid, Arc<Process>>>,
     /// Process group
     pub(super) process_group: Mutex<Weak<ProcessGroup>>,
+    /// The cgroup that the process belongs to
+    pub(super) cgroup: Mutex<Arc<Cgroup>>,
     /// resource limits
-    resource_limits: Mutex<ResourceLimits>,
+    resource_limits: SpinLock<ResourceLimits>,
     /// Scheduling priority nice value
     /// According to POSIX.1, the nice value is a per-process attribute,
     /// the threads in a process should share a nice value.
     nice: AtomicNice,
+    /// The adjustment to the badness score used by the OOM killer.
+    oom_score_adj: AtomicI16,
+    /// Whether the process can be dumped, which is set by `PR_SET_DUMPABLE`.
+    is_dumpable: AtomicBool,
 
     // Signal
     /// Sig dispositions
@@ -96,12 +113,22 @@ pub struct Process {
 
     /// The signal that should be sent to the parent when this process exits.
     exit_signal: AtomicSigNum,
+    /// The pollee of the pidfds referring to the process.
+    pidfd_pollee: Pollee,
 
     /// A profiling clock measures the user CPU time and kernel CPU time of the current process.
     prof_clock: Arc<ProfClock>,
 
     /// A manager that manages timer resources and utilities of the process.
     timer_manager: PosixTimerManager,
+
+    /// The resource usage left by the exited threads, except the main thread.
+    exited_threads_rusage: RusageCounters,
+    /// The resource usage of the reaped children and their reaped descendants.
+    children_rusage: SpinLock<ResourceUsage>,
+
+    /// The time when the process was created, measured since boot.
+    start_time: Duration,
 }
 
 /// Representing a parent process by holding a weak reference to it and its PID.
@@ -177,6 +204,7 @@ impl Process {
         parent: Weak<Process>,
         executable_path: String,
         process_vm: ProcessVm,
+        cgroup: Arc<Cgroup>,
 
         resource_limits: ResourceLimits,
         nice: Nice,
@@ -187,8 +215,9 @@ impl Process {
         let children_wait_queue = WaitQueue::new();
 
         let prof_clock = ProfClock::new();
+        let cpu_limit = *resource_limits.get_rlimit(ResourceType::RLIMIT_CPU);
 
-        Arc::new_cyclic(|process_ref: &Weak<Process>| Self {
+        let process = Arc::new_cyclic(|process_ref: &Weak<Process>| Self {
             pid,
             tasks: Mutex::new(TaskSet::new()),
             executable_path: RwLock::new(executable_path),
@@ -198,14 +227,23 @@ impl Process {
             parent: ParentProcess::new(parent),
             children: Mutex::new(BTreeMap::new()),
             process_group: Mutex::new(Weak::new()),
+            cgroup: Mutex::new(cgroup),
             sig_dispositions,
             parent_death_signal: AtomicSigNum::new_empty(),
             exit_signal: AtomicSigNum::new_empty(),
-            resource_limits: Mutex::new(resource_limits),
+            pidfd_pollee: Pollee::new(),
+            resource_limits: SpinLock::new(resource_limits),
             nice: AtomicNice::new(nice),
+            oom_score_adj: AtomicI16::new(0),
+            is_dumpable: AtomicBool::new(true),
             timer_manager: PosixTimerManager::new(&prof_clock, process_ref),
             prof_clock,
-        })
+            exited_threads_rusage: RusageCounters::new(),
+            children_rusage: SpinLock::new(ResourceUsage::default()),
+            start_time: BootTimeClock::get().read_time(),
+        });
+        process.timer_manager.set_cpu_limit(&cpu_limit);
+        process
     }
 
     /// init a user process and run the process
@@ -292,10 +330,62 @@ impl Process {
         &self.timer_manager
     }
 
+    /// Returns the resource usage of the process.
+    ///
+    /// The usage covers all the threads of the process, including the exited
+    /// ones.
+    pub fn resource_usage(&self) -> ResourceUsage {
+        let mut usage = {
+            let tasks = self.tasks.lock();
+            let mut usage = self.exited_threads_rusage.snapshot();
+            for task in tasks.as_slice() {
+                let posix_thread = task.as_posix_thread().unwrap();
+                usage.accumulate(&posix_thread.rusage().snapshot());
+            }
+            usage
+        };
+
+        usage.utime = self.prof_clock.user_clock().read_time();
+        usage.stime = self.prof_clock.kernel_clock().read_time();
+        usage.maxrss = self.process_vm.rss_peak();
+        usage
+    }
+
+    /// Returns the resource usage of the children that have been waited for.
+    ///
+    /// The usage of a child includes the usage of its own children that have
+    /// been waited for.
+    pub fn children_resource_usage(&self) -> ResourceUsage {
+        *self.children_rusage.lock()
+    }
+
+    /// Accumulates the resource usage of a child that is being reaped.
+    pub(super) fn add_children_resource_usage(&self, usage: &ResourceUsage) {
+        self.children_rusage.lock().accumulate(usage);
+    }
+
+    /// Folds the resource usage of an exited thread into the process.
+    ///
+    /// This should be called with the lock of [`Self::tasks`] held when the
+    /// thread is removed, so that its usage is counted exactly once.
+    pub(super) fn add_exited_thread_rusage(&self, rusage: &RusageCounters) {
+        self.exited_threads_rusage.add_usage(&rusage.snapshot());
+    }
+
+    /// Gets the time when the process was created, measured since boot.
+    pub fn start_time(&self) -> Duration {
+        self.start_time
+    }
+
     pub fn tasks(&self) -> &Mutex<TaskSet> {
         &self.tasks
     }
 
+    /// Gets the cgroup that the process belongs to.
+    pub fn cgroup(&self) -> Arc<Cgroup> {
+        self.cgroup.lock().clone()
+    }
+
     pub fn executable_path(&self) -> String {
         self.executable_path.read().clone()
     }
@@ -304,7 +394,7 @@ impl Process {
         *self.executable_path.write() = executable_path;
     }
 
-    pub fn resource_limits(&self) -> &Mutex<ResourceLimits> {
+    pub fn resource_limits(&self) -> &SpinLock<ResourceLimits> {
         &self.resource_limits
     }
 
@@ -312,6 +402,34 @@ impl Process {
         &self.nice
     }
 
+    /// Returns the adjustment to the badness score used by the OOM killer.
+    pub fn oom_score_adj(&self) -> i16 {
+        self.oom_score_adj.load(Ordering::Relaxed)
+    }
+
+    /// Sets the adjustment to the badness score used by the OOM killer.
+    ///
+    /// The adjustment should be in the range of
+    /// [`OOM_SCORE_ADJ_MIN`]..=[`OOM_SCORE_ADJ_MAX`].
+    ///
+    /// [`OOM_SCORE_ADJ_MIN`]: crate::process::oom::OOM_SCORE_ADJ_MIN
+    /// [`OOM_SCORE_ADJ_MAX`]: crate::process::oom::OOM_SCORE_ADJ_MAX
+    pub fn set_oom_score_adj(&self, oom_score_adj: i16) {
+        self.oom_score_adj.store(oom_score_adj, Ordering::Relaxed);
+    }
+
+    /// Returns whether the process can be dumped.
+    ///
+    /// A process that cannot be dumped will not produce core dumps.
+    pub fn is_dumpable(&self) -> bool {
+        self.is_dumpable.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether the process can be dumped.
+    pub fn set_dumpable(&self, is_dumpable: bool) {
+        self.is_dumpable.store(is_dumpable, Ordering::Relaxed);
+    }
+
     pub fn main_thread(&self) -> Arc<Thread> {
         self.tasks.lock().main().as_thread().unwrap().clone()
     }
@@ -321,6 +439,13 @@ impl Process {
         &self.parent
     }
 
+    /// Returns the pollee of the pidfds referring to the process.
+    ///
+    /// The pollee is notified when the process exits and when it is reaped.
+    pub(super) fn pidfd_pollee(&self) -> &Pollee {
+        &self.pidfd_pollee
+    }
+
     pub fn is_init_process(&self) -> bool {
         self.parent.lock().process().upgrade().is_none()
     }
@@ -613,7 +738,7 @@ impl Process {
     /// chooses an arbitrary thread to which to deliver the signal.
     ///
     /// TODO: restrict these method with access control tool.
-    pub fn enqueue_signal(&self, signal: impl Signal + Clone + 'static) {
+    pub fn enqueue_signal(&self, signal: impl Signal + 'static) {
         if self.status.is_zombie() {
             return;
         }
@@ -691,6 +816,7 @@ mod test {
             parent,
             String::new(),
             ProcessVm::alloc(),
+            Cgroup::root().clone(),
             ResourceLimits::default(),
             Nice::default(),
             Arc::new(Mutex::new(SigDispositions::default())),
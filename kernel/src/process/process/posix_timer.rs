@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::{
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{c_types::sigval_t, sig_num::SigNum, signals::timer::TimerSignal},
+        Process,
+    },
+    thread::{
+        work_queue::{submit_work_item, work_item::WorkItem, WorkPriority},
+        Thread,
+    },
+    time::{Timer, TimerManager},
+};
+
+/// The maximum overrun count that can be reported (`DELAYTIMER_MAX`).
+const DELAYTIMER_MAX: usize = i32::MAX as usize;
+
+/// A POSIX timer created by the `timer_create` syscall.
+///
+/// Each expiration of the timer sends a signal to its target. While the signal
+/// is still pending, no more signals are sent, and the further expirations are
+/// counted as overruns instead. The overrun count is reported in the signal
+/// information as well as by the `timer_getoverrun` syscall.
+pub struct PosixTimer {
+    id: usize,
+    timer: Arc<Timer>,
+    notify: TimerNotify,
+    /// The work item that sends the signal out of the timer callback.
+    send_signal_work: Arc<WorkItem>,
+    is_signal_pending: AtomicBool,
+    /// The overrun count accumulated since the last signal delivery.
+    overrun: AtomicUsize,
+    /// The overrun count of the last delivered signal.
+    overrun_last: AtomicUsize,
+}
+
+/// The way to notify the expiration of a POSIX timer.
+pub enum TimerNotify {
+    /// Does not notify (`SIGEV_NONE`).
+    None,
+    /// Sends a signal to the process (`SIGEV_SIGNAL`).
+    Process {
+        process: Weak<Process>,
+        signum: SigNum,
+        value: sigval_t,
+    },
+    /// Sends a signal to the specific thread (`SIGEV_THREAD_ID`).
+    Thread {
+        thread: Weak<Thread>,
+        signum: SigNum,
+        value: sigval_t,
+    },
+}
+
+impl PosixTimer {
+    /// Creates a new POSIX timer with the ID, which is managed by the timer manager.
+    pub fn new(id: usize, timer_manager: &Arc<TimerManager>, notify: TimerNotify) -> Arc<Self> {
+        Arc::new_cyclic(|weak_self: &Weak<Self>| {
+            let weak_timer = weak_self.clone();
+            let timer = timer_manager.create_timer(move || {
+                if let Some(posix_timer) = weak_timer.upgrade() {
+                    posix_timer.on_expired();
+                }
+            });
+
+            let weak_timer = weak_self.clone();
+            let send_signal_work = WorkItem::new(Box::new(move || {
+                if let Some(posix_timer) = weak_timer.upgrade() {
+                    posix_timer.send_signal();
+                }
+            }));
+
+            Self {
+                id,
+                timer,
+                notify,
+                send_signal_work,
+                is_signal_pending: AtomicBool::new(false),
+                overrun: AtomicUsize::new(0),
+                overrun_last: AtomicUsize::new(0),
+            }
+        })
+    }
+
+    /// Returns the ID of the timer.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the underlying timer.
+    pub fn timer(&self) -> &Arc<Timer> {
+        &self.timer
+    }
+
+    /// Returns the overrun count of the last delivered signal.
+    pub fn overrun(&self) -> usize {
+        self.overrun_last.load(Ordering::Relaxed)
+    }
+
+    /// Resets the overrun counts when the timer is rearmed.
+    pub fn reset_overrun(&self) {
+        self.overrun.store(0, Ordering::Relaxed);
+        self.overrun_last.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns the overrun count to be reported in the pending signal.
+    pub(in crate::process) fn pending_overrun(&self) -> usize {
+        self.overrun.load(Ordering::Relaxed).min(DELAYTIMER_MAX)
+    }
+
+    /// Marks that the pending signal is delivered (or discarded).
+    pub(in crate::process) fn on_signal_delivered(&self) {
+        let overrun = self.overrun.swap(0, Ordering::Relaxed);
+        self.overrun_last
+            .store(overrun.min(DELAYTIMER_MAX), Ordering::Relaxed);
+        self.is_signal_pending.store(false, Ordering::Release);
+    }
+
+    /// Handles the expiration in the timer callback.
+    fn on_expired(&self) {
+        if matches!(self.notify, TimerNotify::None) {
+            return;
+        }
+
+        let missed_expirations = self.timer.missed_expirations();
+        if self.is_signal_pending.swap(true, Ordering::AcqRel) {
+            self.overrun
+                .fetch_add(missed_expirations + 1, Ordering::Relaxed);
+            return;
+        }
+        self.overrun
+            .fetch_add(missed_expirations, Ordering::Relaxed);
+
+        // Enqueuing signals may sleep, so it cannot be done in the timer callback.
+        submit_work_item(self.send_signal_work.clone(), WorkPriority::High);
+    }
+
+    fn send_signal(self: Arc<Self>) {
+        match &self.notify {
+            TimerNotify::None => (),
+            TimerNotify::Process {
+                process,
+                signum,
+                value,
+            } => {
+                if let Some(process) = process.upgrade() {
+                    process.enqueue_signal(TimerSignal::new(*signum, *value, self.clone()));
+                    return;
+                }
+            }
+            TimerNotify::Thread {
+                thread,
+                signum,
+                value,
+            } => {
+                if let Some(thread) = thread.upgrade()
+                    && let Some(posix_thread) = thread.as_posix_thread()
+                {
+                    let signal = TimerSignal::new(*signum, *value, self.clone());
+                    posix_thread.enqueue_signal(Box::new(signal));
+                    return;
+                }
+            }
+        }
+
+        // The target has gone, so the signal is discarded.
+        self.on_signal_delivered();
+    }
+}
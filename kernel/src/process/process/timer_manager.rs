@@ -14,11 +14,18 @@ use ostd::{
     timer,
 };
 
-use super::Process;
+use super::{PosixTimer, Process};
 use crate::{
+    prelude::*,
     process::{
         posix_thread::AsPosixThread,
-        signal::{constants::SIGALRM, signals::kernel::KernelSignal},
+        rlimit::{RLimit64, RLIM_INFINITY},
+        signal::{
+            constants::{SIGALRM, SIGKILL, SIGPROF, SIGVTALRM, SIGXCPU},
+            sig_num::SigNum,
+            signals::kernel::KernelSignal,
+        },
+        ResourceType,
     },
     thread::{
         work_queue::{submit_work_item, work_item::WorkItem},
@@ -26,7 +33,8 @@ use crate::{
     },
     time::{
         clocks::{ProfClock, RealTimeClock},
-        Timer, TimerManager,
+        timer::Timeout,
+        Clock, Timer, TimerManager,
     },
 };
 
@@ -44,7 +52,7 @@ fn update_cpu_time() {
     };
     let process = posix_thread.process();
     let timer_manager = process.timer_manager();
-    let jiffies_interval = Duration::from_millis(1000 / TIMER_FREQ);
+    let jiffies_interval = Duration::from_nanos(1_000_000_000 / TIMER_FREQ);
     // Based on whether the timer interrupt occurs in kernel mode or user mode,
     // the function will add the duration of one timer interrupt interval to the
     // corresponding CPU clocks.
@@ -67,12 +75,15 @@ fn update_cpu_time() {
             .virtual_timer()
             .timer_manager()
             .process_expired_timers();
+        posix_thread
+            .virtual_timer_manager()
+            .process_expired_timers();
     }
     timer_manager
         .prof_timer()
         .timer_manager()
         .process_expired_timers();
-    posix_thread.process_expired_timers();
+    posix_thread.prof_timer_manager().process_expired_timers();
 }
 
 /// Registers a function to update the CPU clock in processes and
@@ -85,21 +96,23 @@ pub(super) fn init() {
 pub struct PosixTimerManager {
     /// A real-time countdown timer, measuring in wall clock time.
     alarm_timer: Arc<Timer>,
-    /// A timer based on user CPU clock.
+    /// A timer based on user CPU clock, which sends `SIGVTALRM`.
     virtual_timer: Arc<Timer>,
-    /// A timer based on the profiling clock.
+    /// A timer based on the profiling clock, which sends `SIGPROF`.
     prof_timer: Arc<Timer>,
+    /// A timer based on the profiling clock that enforces `RLIMIT_CPU`.
+    cpu_limit_timer: Arc<Timer>,
     /// An ID allocator to allocate unique timer IDs.
     id_allocator: Mutex<IdAlloc>,
     /// A container managing all POSIX timers created by `timer_create()` syscall
     /// within the process context.
-    posix_timers: Mutex<Vec<Option<Arc<Timer>>>>,
+    posix_timers: Mutex<Vec<Option<Arc<PosixTimer>>>>,
 }
 
-fn create_process_timer_callback(process_ref: &Weak<Process>) -> impl Fn() + Clone {
+fn create_process_timer_callback(process_ref: &Weak<Process>, signum: SigNum) -> impl Fn() {
     let current_process = process_ref.clone();
     let sent_signal = move || {
-        let signal = KernelSignal::new(SIGALRM);
+        let signal = KernelSignal::new(signum);
         if let Some(process) = current_process.upgrade() {
             process.enqueue_signal(signal);
         }
@@ -116,22 +129,61 @@ fn create_process_timer_callback(process_ref: &Weak<Process>) -> impl Fn() + Clo
     }
 }
 
+/// Creates the callback of the timer enforcing `RLIMIT_CPU`.
+///
+/// Similar to Linux, `SIGXCPU` is sent when the CPU time reaches the soft limit
+/// and then every second, until the hard limit is reached and `SIGKILL` is sent.
+fn create_cpu_limit_timer_callback(process_ref: &Weak<Process>) -> impl Fn() + Clone {
+    let current_process = process_ref.clone();
+    let sent_signal = move || {
+        let Some(process) = current_process.upgrade() else {
+            return;
+        };
+        let hard_limit = process
+            .resource_limits()
+            .lock()
+            .get_rlimit(ResourceType::RLIMIT_CPU)
+            .get_max();
+        let cpu_time = process.prof_clock().read_time().as_secs();
+        let signal = if cpu_time >= hard_limit {
+            SIGKILL
+        } else {
+            SIGXCPU
+        };
+        process.enqueue_signal(KernelSignal::new(signal));
+    };
+
+    let work_func = Box::new(sent_signal);
+    let work_item = WorkItem::new(work_func);
+
+    move || {
+        submit_work_item(
+            work_item.clone(),
+            crate::thread::work_queue::WorkPriority::High,
+        );
+    }
+}
+
 impl PosixTimerManager {
     pub(super) fn new(prof_clock: &Arc<ProfClock>, process_ref: &Weak<Process>) -> Self {
         const MAX_NUM_OF_POSIX_TIMERS: usize = 10000;
 
-        let callback = create_process_timer_callback(process_ref);
-
-        let alarm_timer = RealTimeClock::timer_manager().create_timer(callback.clone());
+        let alarm_timer = RealTimeClock::timer_manager()
+            .create_timer(create_process_timer_callback(process_ref, SIGALRM));
 
-        let virtual_timer =
-            TimerManager::new(prof_clock.user_clock().clone()).create_timer(callback.clone());
-        let prof_timer = TimerManager::new(prof_clock.clone()).create_timer(callback);
+        let virtual_timer = TimerManager::new(prof_clock.user_clock().clone())
+            .create_timer(create_process_timer_callback(process_ref, SIGVTALRM));
+        let prof_timer = TimerManager::new(prof_clock.clone())
+            .create_timer(create_process_timer_callback(process_ref, SIGPROF));
+        let cpu_limit_timer = prof_timer
+            .timer_manager()
+            .create_timer(create_cpu_limit_timer_callback(process_ref));
 
         Self {
             alarm_timer,
             virtual_timer,
             prof_timer,
+            cpu_limit_timer,
             id_allocator: Mutex::new(IdAlloc::with_capacity(MAX_NUM_OF_POSIX_TIMERS)),
             posix_timers: Mutex::new(Vec::new()),
         }
@@ -152,39 +204,53 @@ impl PosixTimerManager {
         &self.prof_timer
     }
 
-    /// Creates a timer based on the profiling CPU clock of the current process.
-    pub fn create_prof_timer<F>(&self, func: F) -> Arc<Timer>
-    where
-        F: Fn() + Send + Sync + 'static,
-    {
-        self.prof_timer.timer_manager().create_timer(func)
+    /// Updates the timer that enforces the `RLIMIT_CPU` limit of the process.
+    pub fn set_cpu_limit(&self, cpu_limit: &RLimit64) {
+        let soft_limit = cpu_limit.get_cur();
+        if soft_limit == RLIM_INFINITY {
+            self.cpu_limit_timer.cancel();
+            return;
+        }
+
+        self.cpu_limit_timer.set_interval(Duration::from_secs(1));
+        self.cpu_limit_timer
+            .set_timeout(Timeout::When(Duration::from_secs(soft_limit)));
     }
 
-    /// Creates a timer based on the user CPU clock of the current process.
-    pub fn create_virtual_timer<F>(&self, func: F) -> Arc<Timer>
-    where
-        F: Fn() + Send + Sync + 'static,
-    {
-        self.virtual_timer.timer_manager().create_timer(func)
+    /// Returns the manager of the timers based on the profiling CPU clock of the process.
+    pub fn prof_timer_manager(&self) -> &Arc<TimerManager> {
+        self.prof_timer.timer_manager()
     }
 
-    /// Adds a POSIX timer to the managed `posix_timers`, and allocate a timer ID for this timer.
-    /// Return the timer ID.
-    pub fn add_posix_timer(&self, posix_timer: Arc<Timer>) -> usize {
+    /// Returns the manager of the timers based on the user CPU clock of the process.
+    pub fn virtual_timer_manager(&self) -> &Arc<TimerManager> {
+        self.virtual_timer.timer_manager()
+    }
+
+    /// Allocates a timer ID, and adds the POSIX timer created with the ID to the managed
+    /// `posix_timers`.
+    ///
+    /// Returns the timer ID, or `EAGAIN` if there are too many timers.
+    pub fn add_posix_timer<F>(&self, new_timer: F) -> Result<usize>
+    where
+        F: FnOnce(usize) -> Arc<PosixTimer>,
+    {
         let mut timers = self.posix_timers.lock();
         // Holding the lock of `posix_timers` is required to operate the `id_allocator`.
-        let timer_id = self.id_allocator.lock().alloc().unwrap();
+        let Some(timer_id) = self.id_allocator.lock().alloc() else {
+            return_errno_with_message!(Errno::EAGAIN, "too many POSIX timers");
+        };
         if timers.len() < timer_id + 1 {
             timers.resize(timer_id + 1, None);
         }
         // The ID allocated is not used by any other timers so this index in `timers`
         // must be `None`.
-        timers[timer_id] = Some(posix_timer);
-        timer_id
+        timers[timer_id] = Some(new_timer(timer_id));
+        Ok(timer_id)
     }
 
     /// Finds a POSIX timer by the input `timer_id`.
-    pub fn find_posix_timer(&self, timer_id: usize) -> Option<Arc<Timer>> {
+    pub fn find_posix_timer(&self, timer_id: usize) -> Option<Arc<PosixTimer>> {
         let timers = self.posix_timers.lock();
         if timer_id >= timers.len() {
             return None;
@@ -194,7 +260,7 @@ impl PosixTimerManager {
     }
 
     /// Removes the POSIX timer with the ID `timer_id`.
-    pub fn remove_posix_timer(&self, timer_id: usize) -> Option<Arc<Timer>> {
+    pub fn remove_posix_timer(&self, timer_id: usize) -> Option<Arc<PosixTimer>> {
         let mut timers = self.posix_timers.lock();
         if timer_id >= timers.len() {
             return None;
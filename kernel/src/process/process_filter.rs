@@ -14,14 +14,15 @@ pub enum ProcessFilter {
 
 impl ProcessFilter {
     // used for waitid
+    //
+    // `P_PIDFD` (which = 3) is not handled here, since the pidfd should be resolved to the
+    // process with the file table of the caller.
+    // https://elixir.bootlin.com/linux/latest/source/include/uapi/linux/wait.h#L20
     pub fn from_which_and_id(which: u64, id: u64) -> Result<Self> {
-        // Does not support PID_FD now(which = 3)
-        // https://elixir.bootlin.com/linux/latest/source/include/uapi/linux/wait.h#L20
         match which {
             0 => Ok(ProcessFilter::Any),
             1 => Ok(ProcessFilter::WithPid(id as Pid)),
             2 => Ok(ProcessFilter::WithPgid(id as Pgid)),
-            3 => todo!(),
             _ => return_errno_with_message!(Errno::EINVAL, "invalid which"),
         }
     }
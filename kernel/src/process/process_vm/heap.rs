@@ -1,12 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use align_ext::AlignExt;
 use aster_rights::Full;
 
 use crate::{
     prelude::*,
+    process::{rlimit::check_address_space_growth, ResourceType},
     vm::{perms::VmPerms, vmar::Vmar},
 };
 
@@ -84,6 +88,15 @@ impl Heap {
                 let current_heap_end = current_heap_end.align_up(PAGE_SIZE);
                 let new_heap_end = new_heap_end.align_up(PAGE_SIZE);
 
+                let data_limit = *current
+                    .resource_limits()
+                    .lock()
+                    .get_rlimit(ResourceType::RLIMIT_DATA);
+                if data_limit.is_exceeded_by((new_heap_end - self.base) as u64) {
+                    return_errno_with_message!(Errno::ENOMEM, "the heap size exceeds RLIMIT_DATA");
+                }
+                check_address_space_growth(&current, new_heap_end - current_heap_end)?;
+
                 // Remove the reserved space.
                 root_vmar.remove_mapping(current_heap_end..new_heap_end)?;
 
@@ -99,6 +112,11 @@ impl Heap {
         }
     }
 
+    /// Returns the range of the heap that is currently in use.
+    pub(super) fn range(&self) -> Range<Vaddr> {
+        self.base..self.current_heap_end.load(Ordering::Relaxed)
+    }
+
     pub(super) fn set_uninitialized(&self) {
         self.current_heap_end
             .store(self.base + PAGE_SIZE, Ordering::Relaxed);
@@ -15,6 +15,7 @@
 
 use core::{
     mem,
+    ops::Range,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
@@ -37,6 +38,11 @@ pub mod aux_vec;
 
 /// Set the initial stack size to 8 megabytes, following the default Linux stack size limit.
 pub const INIT_STACK_SIZE: usize = 8 * 1024 * 1024; // 8 MB
+/// The minimum size of the init stack, regardless of `RLIMIT_STACK`.
+const MIN_INIT_STACK_SIZE: usize = 128 * 1024; // 128 KB
+/// The maximum size of the init stack, which is used if `RLIMIT_STACK` is
+/// unlimited or larger than this value.
+const MAX_INIT_STACK_SIZE: usize = 256 * 1024 * 1024; // 256 MB
 
 /// The max number of arguments that can be used to creating a new process.
 pub const MAX_ARGV_NUMBER: usize = 128;
@@ -99,8 +105,9 @@ pub struct InitStack {
     /// The initial highest address.
     /// The stack grows down from this address
     initial_top: Vaddr,
-    /// The max allowed stack size
-    max_size: usize,
+    /// The max allowed stack size, which is determined by `RLIMIT_STACK`
+    /// when the stack is mapped.
+    max_size: AtomicUsize,
     /// The current stack pointer.
     /// Before initialized, `pos` points to the `initial_top`,
     /// After initialized, `pos` points to the user stack pointer(rsp)
@@ -112,7 +119,7 @@ impl Clone for InitStack {
     fn clone(&self) -> Self {
         Self {
             initial_top: self.initial_top,
-            max_size: self.max_size,
+            max_size: AtomicUsize::new(self.max_size()),
             pos: Arc::new(AtomicUsize::new(self.pos.load(Ordering::Relaxed))),
         }
     }
@@ -134,11 +141,9 @@ impl InitStack {
             nr_random_padding_pages as usize + NR_FIXED_PADDING_PAGES
         };
         let initial_top = MAX_USERSPACE_VADDR - PAGE_SIZE * nr_pages_padding;
-        let max_size = INIT_STACK_SIZE;
-
         Self {
             initial_top,
-            max_size,
+            max_size: AtomicUsize::new(INIT_STACK_SIZE),
             pos: Arc::new(AtomicUsize::new(initial_top)),
         }
     }
@@ -153,26 +158,45 @@ impl InitStack {
         stack_top
     }
 
+    /// Returns the range where the stack is mapped.
+    pub(super) fn range(&self) -> Range<Vaddr> {
+        (self.initial_top - self.max_size())..self.initial_top
+    }
+
+    fn max_size(&self) -> usize {
+        self.max_size.load(Ordering::Relaxed)
+    }
+
     /// Maps the VMO of the init stack and constructs a writer to initialize its content.
+    ///
+    /// The size of the stack is `stack_limit` (i.e., the soft limit of
+    /// `RLIMIT_STACK`), clamped to a reasonable range.
     pub(super) fn map_and_write(
         &self,
         root_vmar: &Vmar<Full>,
+        stack_limit: u64,
         argv: Vec<CString>,
         envp: Vec<CString>,
         auxvec: AuxVec,
     ) -> Result<()> {
         self.set_uninitialized();
 
+        let max_size = usize::try_from(stack_limit)
+            .unwrap_or(usize::MAX)
+            .clamp(MIN_INIT_STACK_SIZE, MAX_INIT_STACK_SIZE)
+            .align_down(PAGE_SIZE);
+        self.max_size.store(max_size, Ordering::Relaxed);
+
         let vmo = {
-            let vmo_options = VmoOptions::<Full>::new(self.max_size);
+            let vmo_options = VmoOptions::<Full>::new(max_size);
             vmo_options.alloc()?
         };
         let vmar_map_options = {
             let perms = VmPerms::READ | VmPerms::WRITE;
-            let map_addr = self.initial_top - self.max_size;
+            let map_addr = self.initial_top - max_size;
             debug_assert!(map_addr % PAGE_SIZE == 0);
             root_vmar
-                .new_map(self.max_size, perms)?
+                .new_map(max_size, perms)?
                 .offset(map_addr)
                 .vmo(vmo.dup().to_dyn())
         };
@@ -184,7 +208,7 @@ impl InitStack {
             argv,
             envp,
             auxvec,
-            map_addr: self.initial_top - self.max_size,
+            map_addr: self.initial_top - max_size,
         };
         writer.write()
     }
@@ -196,7 +220,7 @@ impl InitStack {
         InitStackReader {
             base: self.pos(),
             vm_space,
-            map_addr: self.initial_top - self.max_size,
+            map_addr: self.initial_top - self.max_size(),
         }
     }
 
@@ -12,6 +12,8 @@
 mod heap;
 mod init_stack;
 
+use core::ops::Range;
+
 use aster_rights::Full;
 pub use heap::Heap;
 
@@ -23,7 +25,11 @@ pub use self::{
         MAX_ENV_LEN,
     },
 };
-use crate::{prelude::*, vm::vmar::Vmar};
+use crate::{
+    prelude::*,
+    process::{Process, ResourceType},
+    vm::vmar::Vmar,
+};
 
 /*
  * The user's virtual memory space layout looks like below.
@@ -119,14 +125,48 @@ impl ProcessVm {
         self.init_stack.user_stack_top()
     }
 
+    /// Returns the address range of the user heap that is in use.
+    pub fn heap_range(&self) -> Range<Vaddr> {
+        self.heap.range()
+    }
+
+    /// Returns the address range where the user stack is mapped.
+    pub fn stack_range(&self) -> Range<Vaddr> {
+        self.init_stack.range()
+    }
+
+    /// Returns the resident set size in bytes.
+    ///
+    /// The frames shared with other processes are also counted.
+    pub fn rss(&self) -> usize {
+        self.root_vmar.vm_space().rss()
+    }
+
+    /// Returns the peak resident set size in bytes.
+    ///
+    /// The peak is kept across `execve`, as in Linux.
+    pub fn rss_peak(&self) -> usize {
+        self.root_vmar.vm_space().rss_peak()
+    }
+
+    /// Maps and writes the init stack, whose size is determined by the
+    /// `RLIMIT_STACK` limit of the current process.
     pub(super) fn map_and_write_init_stack(
         &self,
         argv: Vec<CString>,
         envp: Vec<CString>,
         aux_vec: AuxVec,
     ) -> Result<()> {
+        let stack_limit = match Process::current() {
+            Some(process) => process
+                .resource_limits()
+                .lock()
+                .get_rlimit(ResourceType::RLIMIT_STACK)
+                .get_cur(),
+            None => INIT_STACK_SIZE as u64,
+        };
         self.init_stack
-            .map_and_write(self.root_vmar(), argv, envp, aux_vec)
+            .map_and_write(self.root_vmar(), stack_limit, argv, envp, aux_vec)
     }
 
     pub(super) fn heap(&self) -> &Heap {
@@ -11,7 +11,7 @@ use super::process_vm::ProcessVm;
 use crate::{
     fs::{
         fs_resolver::{FsPath, FsResolver, AT_FDCWD},
-        path::Dentry,
+        path::{Dentry, PerMountFlags},
         utils::{InodeType, Permission},
     },
     prelude::*,
@@ -82,6 +82,10 @@ pub fn check_executable_file(dentry: &Dentry) -> Result<()> {
         return_errno_with_message!(Errno::EACCES, "the dentry is not a regular file");
     }
 
+    if dentry.mount_node().flags().contains(PerMountFlags::NOEXEC) {
+        return_errno_with_message!(Errno::EACCES, "the mount disallows program execution");
+    }
+
     if dentry
         .inode()
         .check_permission(Permission::MAY_EXEC)
@@ -1,15 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
-// FIXME: The resource limits should be respected by the corresponding subsystems of the kernel.
 
 #![allow(non_camel_case_types)]
 
-use super::process_vm::{INIT_STACK_SIZE, USER_HEAP_SIZE_LIMIT};
-use crate::prelude::*;
+use ostd::mm::stat;
+
+use super::{
+    credentials::capabilities::CapSet,
+    posix_thread::AsPosixThread,
+    process_vm::{INIT_STACK_SIZE, USER_HEAP_SIZE_LIMIT},
+    signal::{constants::SIGXFSZ, signals::kernel::KernelSignal},
+    Process,
+};
+use crate::{prelude::*, thread::Thread};
 
 // Constants for the boot-time rlimit defaults
 // See https://github.com/torvalds/linux/blob/fac04efc5c793dccbd07e2d59af9f90b7fc0dca4/include/asm-generic/resource.h#L11
-const RLIM_INFINITY: u64 = u64::MAX;
-const INIT_RLIMIT_NPROC: u64 = 0;
+pub const RLIM_INFINITY: u64 = u64::MAX;
 const INIT_RLIMIT_NICE: u64 = 0;
 const INIT_RLIMIT_SIGPENDING: u64 = 0;
 const INIT_RLIMIT_RTPRIO: u64 = 0;
@@ -21,6 +27,20 @@ const INIT_RLIMIT_MEMLOCK: u64 = 8 * 1024 * 1024;
 // https://github.com/torvalds/linux/blob/fac04efc5c793dccbd07e2d59af9f90b7fc0dca4/include/uapi/linux/mqueue.h#L26
 const INIT_RLIMIT_MSGQUEUE: u64 = 819200;
 
+/// Returns the default value of `RLIMIT_NPROC`.
+///
+/// Similar to Linux, the value is half of the maximum number of threads, which
+/// is limited so that the kernel stacks occupy at most 1/8 of the memory.
+/// See <https://github.com/torvalds/linux/blob/fac04efc5c793dccbd07e2d59af9f90b7fc0dca4/kernel/fork.c#L998>.
+fn init_rlimit_nproc() -> u64 {
+    const THREAD_SIZE: usize = 16 * 1024;
+    const MIN_THREADS: usize = 20;
+
+    let max_threads = (stat::mem_total() / (8 * THREAD_SIZE)).max(MIN_THREADS);
+    (max_threads / 2) as u64
+}
+
+#[derive(Clone)]
 pub struct ResourceLimits {
     rlimits: [RLimit64; RLIMIT_COUNT],
 }
@@ -50,8 +70,9 @@ impl Default for ResourceLimits {
             RLimit64::new(INIT_STACK_SIZE as u64, RLIM_INFINITY);
         rlimits[ResourceType::RLIMIT_CORE as usize] = RLimit64::new(0, RLIM_INFINITY);
         rlimits[ResourceType::RLIMIT_RSS as usize] = RLimit64::new(RLIM_INFINITY, RLIM_INFINITY);
+        let init_rlimit_nproc = init_rlimit_nproc();
         rlimits[ResourceType::RLIMIT_NPROC as usize] =
-            RLimit64::new(INIT_RLIMIT_NPROC, INIT_RLIMIT_NPROC);
+            RLimit64::new(init_rlimit_nproc, init_rlimit_nproc);
         rlimits[ResourceType::RLIMIT_NOFILE as usize] =
             RLimit64::new(INIT_RLIMIT_NOFILE_CUR, INIT_RLIMIT_NOFILE_MAX);
         rlimits[ResourceType::RLIMIT_MEMLOCK as usize] =
@@ -118,6 +139,96 @@ impl RLimit64 {
     pub fn is_valid(&self) -> bool {
         self.cur <= self.max
     }
+
+    /// Returns whether `value` exceeds the soft limit.
+    pub fn is_exceeded_by(&self, value: u64) -> bool {
+        self.cur != RLIM_INFINITY && value > self.cur
+    }
+}
+
+/// Checks whether the address space of the process can grow by `len` bytes
+/// under the `RLIMIT_AS` limit.
+pub fn check_address_space_growth(process: &Process, len: usize) -> Result<()> {
+    let as_limit = *process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_AS);
+    if as_limit.get_cur() == RLIM_INFINITY {
+        return Ok(());
+    }
+
+    let total_vm = process.root_vmar().total_vm();
+    if as_limit.is_exceeded_by(total_vm.saturating_add(len) as u64) {
+        return_errno_with_message!(Errno::ENOMEM, "the address space exceeds RLIMIT_AS");
+    }
+    Ok(())
+}
+
+/// Checks whether the process can have `locked` bytes of locked memory under
+/// the `RLIMIT_MEMLOCK` limit.
+///
+/// Returns `Err` with `EPERM` if the limit is zero, or `ENOMEM` if the limit
+/// is exceeded. The threads with `CAP_IPC_LOCK` are not limited.
+pub fn check_locked_memory(ctx: &Context, locked: usize) -> Result<()> {
+    if ctx
+        .posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::IPC_LOCK)
+    {
+        return Ok(());
+    }
+
+    let memlock_limit = *ctx
+        .process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_MEMLOCK);
+    if memlock_limit.get_cur() == 0 {
+        return_errno_with_message!(Errno::EPERM, "RLIMIT_MEMLOCK is zero");
+    }
+    if memlock_limit.is_exceeded_by(locked as u64) {
+        return_errno_with_message!(Errno::ENOMEM, "the locked memory exceeds RLIMIT_MEMLOCK");
+    }
+    Ok(())
+}
+
+/// Checks whether a file can be extended to `new_size` bytes under the
+/// `RLIMIT_FSIZE` limit of the current process.
+///
+/// If the limit is exceeded, `SIGXFSZ` is sent to the current thread and
+/// `EFBIG` is returned.
+pub fn check_file_size(new_size: usize) -> Result<()> {
+    let max_size = max_file_size();
+    if new_size as u64 > max_size {
+        send_sigxfsz();
+        return_errno_with_message!(Errno::EFBIG, "the file size exceeds RLIMIT_FSIZE");
+    }
+    Ok(())
+}
+
+/// Returns the maximum size in bytes that a file can be extended to by the
+/// current process, i.e., the soft limit of `RLIMIT_FSIZE`.
+pub fn max_file_size() -> u64 {
+    let Some(process) = Process::current() else {
+        return RLIM_INFINITY;
+    };
+    process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_FSIZE)
+        .get_cur()
+}
+
+/// Sends `SIGXFSZ` to the current thread, which attempts to extend a file
+/// beyond the `RLIMIT_FSIZE` limit.
+pub fn send_sigxfsz() {
+    let Some(thread) = Thread::current() else {
+        return;
+    };
+    if let Some(posix_thread) = thread.as_posix_thread() {
+        posix_thread.enqueue_signal(Box::new(KernelSignal::new(SIGXFSZ)));
+    }
 }
 
 impl Default for RLimit64 {
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Resource usage accounting.
+//!
+//! Each POSIX thread counts its own page faults, context switches and block
+//! I/O in [`RusageCounters`]. The counters of a thread are folded into its
+//! process when the thread exits, and the usage of a process is folded into
+//! its parent when the process is reaped. This follows the Linux design, where
+//! the usage of a process is the sum over its live threads and the usage left
+//! by its exited threads.
+//!
+//! The CPU times are not counted here, since they are already measured by the
+//! profiling clocks of threads and processes.
+
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use aster_block::bio::BioType;
+
+use super::posix_thread::AsPosixThread;
+use crate::{prelude::*, thread::Thread};
+
+/// The counters of the resource usage of a thread.
+#[derive(Debug, Default)]
+pub struct RusageCounters {
+    /// The page faults that are served without doing I/O.
+    minflt: AtomicU64,
+    /// The page faults that require I/O.
+    majflt: AtomicU64,
+    /// The sectors read from the block devices.
+    inblock: AtomicU64,
+    /// The sectors written to the block devices.
+    oublock: AtomicU64,
+    /// The context switches due to blocking.
+    nvcsw: AtomicU64,
+    /// The context switches due to preemption or yielding.
+    nivcsw: AtomicU64,
+}
+
+impl RusageCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a page fault, which is major if `is_major` is `true`.
+    pub fn add_page_fault(&self, is_major: bool) {
+        if is_major {
+            self.majflt.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.minflt.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts a context switch, which is voluntary if `is_voluntary` is `true`.
+    pub fn add_context_switch(&self, is_voluntary: bool) {
+        if is_voluntary {
+            self.nvcsw.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.nivcsw.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the number of sectors read from the block devices.
+    pub fn inblock(&self) -> u64 {
+        self.inblock.load(Ordering::Relaxed)
+    }
+
+    /// Adds the counters in `usage` to `self`.
+    pub(super) fn add_usage(&self, usage: &ResourceUsage) {
+        self.minflt.fetch_add(usage.minflt, Ordering::Relaxed);
+        self.majflt.fetch_add(usage.majflt, Ordering::Relaxed);
+        self.inblock.fetch_add(usage.inblock, Ordering::Relaxed);
+        self.oublock.fetch_add(usage.oublock, Ordering::Relaxed);
+        self.nvcsw.fetch_add(usage.nvcsw, Ordering::Relaxed);
+        self.nivcsw.fetch_add(usage.nivcsw, Ordering::Relaxed);
+    }
+
+    /// Returns a snapshot of the counters, leaving the other fields zero.
+    pub fn snapshot(&self) -> ResourceUsage {
+        ResourceUsage {
+            minflt: self.minflt.load(Ordering::Relaxed),
+            majflt: self.majflt.load(Ordering::Relaxed),
+            inblock: self.inblock.load(Ordering::Relaxed),
+            oublock: self.oublock.load(Ordering::Relaxed),
+            nvcsw: self.nvcsw.load(Ordering::Relaxed),
+            nivcsw: self.nivcsw.load(Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+}
+
+/// The resource usage of a thread, a process, or the reaped children of a process.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ResourceUsage {
+    /// The user CPU time.
+    pub utime: Duration,
+    /// The system CPU time.
+    pub stime: Duration,
+    /// The peak resident set size in bytes.
+    pub maxrss: usize,
+    /// The number of minor page faults.
+    pub minflt: u64,
+    /// The number of major page faults.
+    pub majflt: u64,
+    /// The number of sectors read from the block devices.
+    pub inblock: u64,
+    /// The number of sectors written to the block devices.
+    pub oublock: u64,
+    /// The number of voluntary context switches.
+    pub nvcsw: u64,
+    /// The number of involuntary context switches.
+    pub nivcsw: u64,
+}
+
+impl ResourceUsage {
+    /// Accumulates `other` into `self`.
+    ///
+    /// The peak resident set sizes are not summed up. The larger one is kept.
+    pub fn accumulate(&mut self, other: &ResourceUsage) {
+        self.utime += other.utime;
+        self.stime += other.stime;
+        self.maxrss = self.maxrss.max(other.maxrss);
+        self.minflt += other.minflt;
+        self.majflt += other.majflt;
+        self.inblock += other.inblock;
+        self.oublock += other.oublock;
+        self.nvcsw += other.nvcsw;
+        self.nivcsw += other.nivcsw;
+    }
+}
+
+pub(super) fn init() {
+    aster_block::bio::BIO_SUBMIT_ACCOUNT_FN.call_once(|| Arc::new(account_block_io));
+}
+
+/// Accounts the block I/O to the current thread.
+fn account_block_io(bio_type: BioType, nsectors: usize) {
+    let Some(current_thread) = Thread::current() else {
+        return;
+    };
+    let Some(posix_thread) = current_thread.as_posix_thread() else {
+        return;
+    };
+
+    let counters = posix_thread.rusage();
+    let counter = match bio_type {
+        BioType::Read => &counters.inblock,
+        BioType::Write => &counters.oublock,
+        BioType::Flush | BioType::Discard => return,
+    };
+    counter.fetch_add(nsectors as u64, Ordering::Relaxed);
+}
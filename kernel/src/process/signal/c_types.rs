@@ -58,6 +58,10 @@ impl siginfo_t {
         // let siginfo = *self;
         read_union_fields!(self.siginfo_fields.sigfault.addr)
     }
+    pub fn set_si_timer(&mut self, timerid: i32, overrun: i32, value: sigval_t) {
+        self.siginfo_fields.common.first.timer = siginfo_timer_t { timerid, overrun };
+        self.siginfo_fields.common.second.value = value;
+    }
 }
 
 #[derive(Clone, Copy, Pod)]
@@ -119,6 +123,13 @@ pub union sigval_t {
 }
 
 impl sigval_t {
+    pub fn from_int(value: i32) -> Self {
+        // Zero-extend the value, so that the upper bytes are initialized.
+        Self {
+            sigval_ptr: value as u32 as Vaddr,
+        }
+    }
+
     pub fn read_int(&self) -> i32 {
         read_union_fields!(self.sigval_int)
     }
@@ -158,7 +169,7 @@ union siginfo_addr_bnd_t {
     upper: Vaddr, // *const c_void,
 }
 
-#[derive(Clone, Copy, Debug, Pod)]
+#[derive(Clone, Copy, Debug, Pod, Default)]
 #[repr(C)]
 pub struct ucontext_t {
     pub uc_flags: u64,
@@ -166,20 +177,6 @@ pub struct ucontext_t {
     pub uc_stack: stack_t,
     pub uc_mcontext: mcontext_t,
     pub uc_sigmask: sigset_t,
-    pub fpregs: [u8; 64 * 8], //fxsave structure
-}
-
-impl Default for ucontext_t {
-    fn default() -> Self {
-        Self {
-            uc_flags: Default::default(),
-            uc_link: Default::default(),
-            uc_stack: Default::default(),
-            uc_mcontext: Default::default(),
-            uc_sigmask: Default::default(),
-            fpregs: [0u8; 64 * 8],
-        }
-    }
 }
 
 pub type stack_t = sigaltstack_t;
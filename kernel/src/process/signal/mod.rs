@@ -16,10 +16,13 @@ pub mod signals;
 use core::{mem, sync::atomic::Ordering};
 
 use align_ext::AlignExt;
-use c_types::{siginfo_t, ucontext_t};
+use c_types::{siginfo_t, stack_t, ucontext_t};
 use constants::SIGKILL;
 pub use events::{SigEvents, SigEventsFilter};
-use ostd::{cpu::UserContext, user::UserContextApi};
+use ostd::{
+    cpu::{FpuState, UserContext},
+    user::UserContextApi,
+};
 pub use pause::{with_signal_blocked, Pause};
 pub use poll::{PollAdaptor, PollHandle, Pollable, Pollee, Poller};
 use sig_action::{SigAction, SigActionFlags, SigDefaultAction};
@@ -32,7 +35,11 @@ use crate::{
     cpu::LinuxAbi,
     current_userspace,
     prelude::*,
-    process::{posix_thread::do_exit_group, TermStatus},
+    process::{
+        coredump::{do_coredump, exit_for_coredump},
+        posix_thread::do_exit_group,
+        TermStatus,
+    },
 };
 
 pub trait SignalContext {
@@ -59,6 +66,11 @@ pub fn handle_pending_signal(
         }
     };
 
+    // Another thread that is dumping the core has killed the current thread.
+    if exit_for_coredump(ctx, user_ctx) {
+        return;
+    }
+
     let sig_num = signal.num();
     trace!("sig_num = {:?}, sig_name = {}", sig_num, sig_num.sig_name());
     let current = posix_thread.process();
@@ -120,8 +132,15 @@ pub fn handle_pending_signal(
                         current.executable_path(),
                         sig_num.sig_name()
                     );
+                    let term_status = if matches!(sig_default_action, SigDefaultAction::Core)
+                        && do_coredump(ctx, user_ctx, &signal.to_info())
+                    {
+                        TermStatus::CoreDumped(sig_num)
+                    } else {
+                        TermStatus::Killed(sig_num)
+                    };
                     // We should exit current here, since we cannot restore a valid status from trap now.
-                    do_exit_group(TermStatus::Killed(sig_num));
+                    do_exit_group(term_status);
                 }
                 SigDefaultAction::Ign => {}
                 SigDefaultAction::Stop => {
@@ -166,6 +185,10 @@ pub fn handle_user_signal(
         .sig_mask()
         .store(old_mask + mask, Ordering::Relaxed);
 
+    // Save the signal stack before using it, so that `rt_sigreturn` can restore it if it is
+    // disarmed due to `SS_AUTODISARM`.
+    let uc_stack = stack_t::from(ctx.thread_local.sig_stack().borrow().as_ref());
+
     // Set up signal stack.
     let mut stack_pointer = if let Some(sp) = use_alternate_signal_stack(ctx.thread_local) {
         sp as u64
@@ -179,14 +202,25 @@ pub fn handle_user_signal(
 
     let user_space = ctx.user_space();
 
-    // 1. write siginfo_t
+    // 1. write the FPU state, which must be aligned at 64 bytes in the XSAVE format.
+    let fpu_state = user_ctx.fpu_state();
+    fpu_state.save();
+    stack_pointer = alloc_aligned_in_user_stack(stack_pointer, fpu_state.size(), 64)?;
+    user_space.write_bytes(
+        stack_pointer as Vaddr,
+        &mut VmReader::from(fpu_state.as_bytes()),
+    )?;
+    let fpregs_addr = stack_pointer;
+
+    // 2. write siginfo_t
     stack_pointer -= mem::size_of::<siginfo_t>() as u64;
     user_space.write_val(stack_pointer as _, &sig_info)?;
     let siginfo_addr = stack_pointer;
 
-    // 2. write ucontext_t.
+    // 3. write ucontext_t.
     stack_pointer = alloc_aligned_in_user_stack(stack_pointer, mem::size_of::<ucontext_t>(), 16)?;
     let mut ucontext = ucontext_t {
+        uc_stack,
         uc_sigmask: mask.into(),
         ..Default::default()
     };
@@ -195,13 +229,13 @@ pub fn handle_user_signal(
         .inner
         .gp_regs
         .copy_from_raw(user_ctx.general_regs());
+    ucontext.uc_mcontext.inner.fpregs = fpregs_addr as Vaddr;
     let sig_context = ctx.thread_local.sig_context().get();
     if let Some(sig_context_addr) = sig_context {
         ucontext.uc_link = sig_context_addr;
     } else {
         ucontext.uc_link = 0;
     }
-    // TODO: store fp regs in ucontext
     user_space.write_val(stack_pointer as _, &ucontext)?;
     let ucontext_addr = stack_pointer;
     // Store the ucontext addr in sig context of current thread.
@@ -209,7 +243,7 @@ pub fn handle_user_signal(
         .sig_context()
         .set(Some(ucontext_addr as Vaddr));
 
-    // 3. Set the address of the trampoline code.
+    // 4. Set the address of the trampoline code.
     if flags.contains(SigActionFlags::SA_RESTORER) {
         // If contains SA_RESTORER flag, trampoline code is provided by libc in restorer_addr.
         // We just store restorer_addr on user stack to allow user code just to trampoline code.
@@ -229,7 +263,7 @@ pub fn handle_user_signal(
         stack_pointer = write_u64_to_user_stack(stack_pointer, trampoline_rip)?;
     }
 
-    // 4. Set correct register values
+    // 5. Set correct register values
     user_ctx.set_instruction_pointer(handler_addr as _);
     user_ctx.set_stack_pointer(stack_pointer as usize);
     // parameters of signal handler
@@ -238,6 +272,9 @@ pub fn handle_user_signal(
     } else {
         user_ctx.set_arguments(sig_num, 0, 0);
     }
+    // The signal handler starts with a clean FPU state.
+    *user_ctx.fpu_state_mut() = FpuState::default();
+    user_ctx.fpu_state().restore();
     // CPU architecture-dependent logic
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "x86_64")] {
@@ -255,9 +292,10 @@ pub fn handle_user_signal(
 /// It the stack is already active, we just increase the handler counter and return None, since
 /// the stack pointer can be read from context.
 /// It the stack is not used by any handler, we will return the new sp in alternate signal stack.
+/// If the stack was installed with `SS_AUTODISARM`, it is disarmed until the handler returns.
 fn use_alternate_signal_stack(thread_local: &ThreadLocal) -> Option<usize> {
-    let mut sig_stack = thread_local.sig_stack().borrow_mut();
-    let sig_stack = (*sig_stack).as_mut()?;
+    let mut sig_stack_slot = thread_local.sig_stack().borrow_mut();
+    let sig_stack = (*sig_stack_slot).as_mut()?;
 
     if sig_stack.is_disabled() {
         return None;
@@ -269,10 +307,16 @@ fn use_alternate_signal_stack(thread_local: &ThreadLocal) -> Option<usize> {
         return None;
     }
 
-    sig_stack.increase_handler_counter();
-
     // Make sp align at 16. FIXME: is this required?
     let stack_pointer = (sig_stack.base() + sig_stack.size()).align_down(16);
+
+    if sig_stack.flags().contains(SigStackFlags::SS_AUTODISARM) {
+        // The stack will be restored from `uc_stack` in `rt_sigreturn`.
+        *sig_stack_slot = None;
+    } else {
+        sig_stack.increase_handler_counter();
+    }
+
     Some(stack_pointer)
 }
 
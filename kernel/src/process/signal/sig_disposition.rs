@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::{constants::*, sig_action::SigAction, sig_num::SigNum};
+use super::{constants::*, sig_action::SigAction, sig_mask::SigSet, sig_num::SigNum};
 
 #[derive(Copy, Clone)]
 pub struct SigDispositions {
@@ -48,6 +48,26 @@ impl SigDispositions {
         }
     }
 
+    /// Returns the set of signals that are ignored.
+    pub fn ignored_signals(&self) -> SigSet {
+        self.signals_matching(|sigaction| matches!(sigaction, SigAction::Ign))
+    }
+
+    /// Returns the set of signals that are caught by user-given handlers.
+    pub fn caught_signals(&self) -> SigSet {
+        self.signals_matching(|sigaction| matches!(sigaction, SigAction::User { .. }))
+    }
+
+    fn signals_matching(&self, predicate: impl Fn(&SigAction) -> bool) -> SigSet {
+        let mut set = SigSet::new_empty();
+        for (idx, sigaction) in self.map.iter().enumerate() {
+            if predicate(sigaction) {
+                set += SigNum::from_u8(idx as u8 + MIN_STD_SIG_NUM);
+            }
+        }
+        set
+    }
+
     fn num_to_idx(num: SigNum) -> usize {
         (num.as_u8() - MIN_STD_SIG_NUM) as usize
     }
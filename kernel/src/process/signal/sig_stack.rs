@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use super::c_types::stack_t;
 use crate::prelude::*;
 
 /// User-provided signal stack. `SigStack` is per-thread, and each thread can have
@@ -59,16 +60,8 @@ impl SigStack {
     }
 
     pub fn status(&self) -> SigStackStatus {
-        // Learning From [sigaltstack doc](https://man7.org/linux/man-pages/man2/sigaltstack.2.html):
-        // If the stack is currently executed on,
-        // 1. If the stack was established with flag SS_AUTODISARM, the stack status is DISABLE,
-        // 2. otherwise, the stack status is ONSTACK
         if self.handler_counter == 0 {
-            if self.flags.contains(SigStackFlags::SS_AUTODISARM) {
-                SigStackStatus::SS_DISABLE
-            } else {
-                SigStackStatus::SS_INACTIVE
-            }
+            SigStackStatus::SS_INACTIVE
         } else {
             SigStackStatus::SS_ONSTACK
         }
@@ -81,19 +74,66 @@ impl SigStack {
 
     // Mark the stack is freed by current handler.
     pub fn decrease_handler_counter(&mut self) {
-        // FIXME: deal with SS_AUTODISARM flag
         self.handler_counter -= 1
     }
 
     /// Determines whether the stack is executed on by any signal handler
     pub fn is_active(&self) -> bool {
-        (self.handler_counter > 0)
-            && !(self.flags.intersects(SigStackFlags::SS_AUTODISARM)
-                || self.flags.intersects(SigStackFlags::SS_DISABLE))
+        self.handler_counter > 0
     }
 
     pub fn is_disabled(&self) -> bool {
         self.flags.contains(SigStackFlags::SS_DISABLE)
-            || (self.handler_counter > 0 && self.flags.contains(SigStackFlags::SS_AUTODISARM))
     }
 }
+
+impl TryFrom<stack_t> for SigStack {
+    type Error = Error;
+
+    fn try_from(stack: stack_t) -> Result<Self> {
+        if stack.ss_flags < 0 {
+            return_errno_with_message!(Errno::EINVAL, "negative flags");
+        }
+
+        let mut flags = SigStackFlags::from_bits(stack.ss_flags as u32)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+
+        if flags.contains(SigStackFlags::SS_DISABLE) {
+            return Ok(Self::new(0, flags, 0));
+        }
+        if stack.ss_size < MINSTKSZ {
+            return_errno_with_message!(Errno::ENOMEM, "stack size is less than MINSTKSZ");
+        }
+        if stack.ss_sp.checked_add(stack.ss_size).is_none() {
+            return_errno_with_message!(Errno::EINVAL, "overflow for given stack addr and size");
+        }
+
+        if flags.is_empty() {
+            flags.insert(SigStackFlags::SS_ONSTACK);
+        }
+
+        Ok(Self::new(stack.ss_sp, flags, stack.ss_size))
+    }
+}
+
+impl From<Option<&SigStack>> for stack_t {
+    fn from(stack: Option<&SigStack>) -> Self {
+        let Some(stack) = stack else {
+            return Self {
+                ss_sp: 0,
+                ss_flags: SigStackFlags::SS_DISABLE.bits() as i32,
+                ss_size: 0,
+            };
+        };
+
+        Self {
+            ss_sp: stack.base(),
+            ss_flags: stack.flags().bits() as i32 | stack.status() as i32,
+            ss_size: stack.size(),
+        }
+    }
+}
+
+#[allow(unused)]
+const SIGSTKSZ: usize = 8192;
+const MINSTKSZ: usize = 2048;
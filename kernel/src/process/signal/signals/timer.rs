@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Debug;
+
+use super::Signal;
+use crate::{
+    prelude::*,
+    process::{
+        signal::{
+            c_types::{siginfo_t, sigval_t},
+            constants::SI_TIMER,
+            sig_num::SigNum,
+        },
+        PosixTimer,
+    },
+};
+
+/// A signal sent on the expiration of a POSIX timer.
+///
+/// The signal is pending until it is dropped, after which the timer can send
+/// the next signal.
+pub struct TimerSignal {
+    num: SigNum,
+    value: sigval_t,
+    timer: Arc<PosixTimer>,
+}
+
+impl TimerSignal {
+    pub fn new(num: SigNum, value: sigval_t, timer: Arc<PosixTimer>) -> Self {
+        Self { num, value, timer }
+    }
+}
+
+impl Signal for TimerSignal {
+    fn num(&self) -> SigNum {
+        self.num
+    }
+
+    fn to_info(&self) -> siginfo_t {
+        let mut info = siginfo_t::new(self.num, SI_TIMER);
+        info.set_si_timer(
+            self.timer.id() as i32,
+            self.timer.pending_overrun() as i32,
+            self.value,
+        );
+        info
+    }
+}
+
+impl Drop for TimerSignal {
+    fn drop(&mut self) {
+        self.timer.on_signal_delivered();
+    }
+}
+
+impl Debug for TimerSignal {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TimerSignal")
+            .field("num", &self.num)
+            .field("timer_id", &self.timer.id())
+            .finish()
+    }
+}
@@ -10,10 +10,12 @@ use super::ExitCode;
 ///
 /// This maintains:
 /// 1. Whether the process is a zombie (i.e., all its threads have exited);
-/// 2. The exit code of the process.
+/// 2. Whether the zombie process has been reaped by its parent;
+/// 3. The exit code of the process.
 #[derive(Debug)]
 pub struct ProcessStatus {
     is_zombie: AtomicBool,
+    is_reaped: AtomicBool,
     exit_code: AtomicU32,
 }
 
@@ -21,6 +23,7 @@ impl Default for ProcessStatus {
     fn default() -> Self {
         Self {
             is_zombie: AtomicBool::new(false),
+            is_reaped: AtomicBool::new(false),
             exit_code: AtomicU32::new(0),
         }
     }
@@ -42,6 +45,19 @@ impl ProcessStatus {
         // Use the `Release` memory order to make the exit code visible.
         self.is_zombie.store(true, Ordering::Release);
     }
+
+    /// Returns whether the process has been reaped by its parent.
+    ///
+    /// A reaped process is no longer in the process table, though it may
+    /// still be referred to, e.g., by pidfds.
+    pub fn is_reaped(&self) -> bool {
+        self.is_reaped.load(Ordering::Acquire)
+    }
+
+    /// Sets the zombie process to be reaped.
+    pub(super) fn set_reaped(&self) {
+        self.is_reaped.store(true, Ordering::Release);
+    }
 }
 
 impl ProcessStatus {
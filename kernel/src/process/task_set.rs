@@ -4,6 +4,7 @@
 
 use ostd::task::{CurrentTask, Task};
 
+use super::coredump::CoreDumpState;
 use crate::prelude::*;
 
 /// A task set that maintains all tasks in a POSIX process.
@@ -11,6 +12,7 @@ pub struct TaskSet {
     tasks: Vec<Arc<Task>>,
     has_exited_main: bool,
     has_exited_group: bool,
+    core_dump: Option<Arc<CoreDumpState>>,
 }
 
 impl TaskSet {
@@ -20,6 +22,7 @@ impl TaskSet {
             tasks: Vec::new(),
             has_exited_main: false,
             has_exited_group: false,
+            core_dump: None,
         }
     }
 
@@ -71,6 +74,17 @@ impl TaskSet {
     pub(super) fn has_exited_group(&self) -> bool {
         self.has_exited_group
     }
+
+    /// Sets the state of the core dump, which initiates an `exit_group`.
+    pub(super) fn set_core_dump(&mut self, core_dump: Arc<CoreDumpState>) {
+        self.has_exited_group = true;
+        self.core_dump = Some(core_dump);
+    }
+
+    /// Returns the state of the core dump if a thread is dumping the core.
+    pub(super) fn core_dump(&self) -> Option<&Arc<CoreDumpState>> {
+        self.core_dump.as_ref()
+    }
 }
 
 impl TaskSet {
@@ -4,6 +4,7 @@
 
 use super::{process_filter::ProcessFilter, signal::constants::SIGCHLD, ExitCode, Pid, Process};
 use crate::{
+    events::IoEvents,
     prelude::*,
     process::{
         posix_thread::{thread_table, AsPosixThread},
@@ -93,6 +94,11 @@ pub fn wait_child_exit(
 fn reap_zombie_child(process: &Process, pid: Pid) -> ExitCode {
     let child_process = process.children().lock().remove(&pid).unwrap();
     assert!(child_process.status().is_zombie());
+
+    let mut child_usage = child_process.resource_usage();
+    child_usage.accumulate(&child_process.children_resource_usage());
+    process.add_children_resource_usage(&child_usage);
+
     for task in child_process.tasks().lock().as_slice() {
         thread_table::remove_thread(task.as_posix_thread().unwrap().tid());
     }
@@ -124,5 +130,8 @@ fn reap_zombie_child(process: &Process, pid: Pid) -> ExitCode {
     }
 
     process_table_mut.remove(child_process.pid());
+    child_process.status().set_reaped();
+    child_process.pidfd_pollee().notify(IoEvents::HUP);
+
     child_process.status().exit_code()
 }
@@ -7,10 +7,10 @@ mod sched_class;
 mod stats;
 
 // Export the stats getter functions.
-pub use stats::{loadavg, nr_queued_and_running};
+pub use stats::{cpu_time, loadavg, nr_queued_and_running};
 
-// TODO: Use `sched_class::init` instead after the completion of #1676.
-pub use self::priority_scheduler::init;
 // There may be multiple scheduling policies in the system,
 // and subsequent schedulers can be placed under this module.
-pub use self::sched_class::SchedAttr;
+pub use self::sched_class::{
+    init, DeadlineParams, FairGroup, RealTimePolicy, RtPrio, SchedAttr, SchedPolicy,
+};
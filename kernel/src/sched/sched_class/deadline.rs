@@ -0,0 +1,336 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{collections::binary_heap::BinaryHeap, sync::Arc, vec::Vec};
+use core::{
+    cmp::{self, Reverse},
+    mem,
+    sync::atomic::{AtomicU64, Ordering::*},
+    time::Duration,
+};
+
+use ostd::{
+    cpu::num_cpus,
+    sync::SpinLock,
+    task::{
+        scheduler::{EnqueueFlags, UpdateFlags},
+        Task,
+    },
+};
+
+use super::{
+    sched_clock, time::ns_to_clocks, CurrentRuntime, SchedAttr, SchedClassRq, SchedPolicy,
+    SchedPolicyKind,
+};
+use crate::{
+    prelude::{Errno, Result},
+    return_errno_with_message,
+    thread::AsThread,
+};
+
+/// The parameters of the DEADLINE scheduling class.
+///
+/// A thread with the parameters is guaranteed to run for `runtime` in every
+/// `period`, before the relative `deadline` in the period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DeadlineParams {
+    pub runtime: Duration,
+    pub deadline: Duration,
+    pub period: Duration,
+}
+
+impl DeadlineParams {
+    /// Returns the bandwidth of the thread, i.e., `runtime / period`,
+    /// measured in [`BW_UNIT`]s.
+    fn bandwidth(&self) -> u64 {
+        (self.runtime.as_nanos() * u128::from(BW_UNIT) / self.period.as_nanos()) as u64
+    }
+}
+
+/// The bandwidth of a whole CPU.
+const BW_UNIT: u64 = 1 << 20;
+
+/// The maximum bandwidth of the DEADLINE threads on each CPU.
+///
+/// Like Linux, 5% of the CPU time is reserved for the threads of the other
+/// scheduling classes.
+const MAX_BW_PER_CPU: u64 = BW_UNIT * 95 / 100;
+
+/// The total bandwidth of all the DEADLINE threads.
+static TOTAL_BW: SpinLock<u64> = SpinLock::new(0);
+
+/// Updates the total bandwidth for a thread whose policy is changed from
+/// `old_policy` to `new_policy`.
+///
+/// This is the admission control of the DEADLINE class. A new bandwidth is
+/// rejected with `EBUSY` if it exceeds the maximum bandwidth of a CPU, or if
+/// the total bandwidth exceeds the maximum bandwidth of all the CPUs.
+pub(super) fn update_bandwidth(old_policy: SchedPolicy, new_policy: SchedPolicy) -> Result<()> {
+    let bandwidth = |policy| match policy {
+        SchedPolicy::Deadline(params) => params.bandwidth(),
+        _ => 0,
+    };
+    let (old_bw, new_bw) = (bandwidth(old_policy), bandwidth(new_policy));
+    if old_bw == new_bw {
+        return Ok(());
+    }
+
+    let mut total_bw = TOTAL_BW.disable_irq().lock();
+    let new_total_bw = *total_bw - old_bw + new_bw;
+    if new_bw > old_bw
+        && (new_bw > MAX_BW_PER_CPU || new_total_bw > MAX_BW_PER_CPU * num_cpus() as u64)
+    {
+        return_errno_with_message!(Errno::EBUSY, "the deadline bandwidth is exhausted");
+    }
+    *total_bw = new_total_bw;
+
+    Ok(())
+}
+
+/// The scheduling attribute for the DEADLINE scheduling class.
+///
+/// The threads are scheduled in the order of their absolute deadlines (EDF),
+/// while the runtime is enforced by the constant bandwidth server (CBS):
+///
+/// - A running thread consumes its remaining runtime. Once the runtime is
+///   exhausted, the thread is throttled until the next period, when the
+///   runtime is replenished and the absolute deadline is postponed by a period.
+/// - When a thread wakes up, its absolute deadline and runtime are reset if
+///   the remaining runtime cannot be consumed before the absolute deadline
+///   without exceeding the bandwidth of the thread.
+///
+/// All the times are measured in [`sched_clock`]s.
+#[derive(Debug)]
+pub struct DeadlineAttr {
+    runtime: AtomicU64,
+    deadline: AtomicU64,
+    period: AtomicU64,
+    /// The remaining runtime in the current period.
+    remaining: AtomicU64,
+    /// The absolute deadline of the current period.
+    abs_deadline: AtomicU64,
+}
+
+impl DeadlineAttr {
+    pub fn new() -> Self {
+        DeadlineAttr {
+            runtime: AtomicU64::new(0),
+            deadline: AtomicU64::new(0),
+            period: AtomicU64::new(0),
+            remaining: AtomicU64::new(0),
+            abs_deadline: AtomicU64::new(0),
+        }
+    }
+
+    /// Updates the parameters, and starts a new period at once.
+    pub fn update(&self, params: &DeadlineParams) {
+        let clocks = |duration: Duration| ns_to_clocks(duration.as_nanos() as u64);
+        let (runtime, deadline) = (clocks(params.runtime), clocks(params.deadline));
+
+        self.runtime.store(runtime, Relaxed);
+        self.deadline.store(deadline, Relaxed);
+        self.period.store(clocks(params.period), Relaxed);
+        self.remaining.store(runtime, Relaxed);
+        self.abs_deadline.store(sched_clock() + deadline, Relaxed);
+    }
+
+    fn is_throttled(&self) -> bool {
+        self.remaining.load(Relaxed) == 0
+    }
+
+    /// Consumes the runtime, and returns whether the runtime is exhausted.
+    fn consume(&self, delta: u64) -> bool {
+        let remaining = self.remaining.load(Relaxed).saturating_sub(delta);
+        self.remaining.store(remaining, Relaxed);
+        remaining == 0
+    }
+
+    /// Returns the start of the next period, when the runtime is replenished.
+    fn next_period(&self) -> u64 {
+        self.abs_deadline.load(Relaxed) - self.deadline.load(Relaxed) + self.period.load(Relaxed)
+    }
+
+    fn replenish(&self, now: u64) {
+        let abs_deadline = self.abs_deadline.load(Relaxed) + self.period.load(Relaxed);
+        // If the thread lags behind too much, a new period starts from now.
+        let abs_deadline = if abs_deadline < now {
+            now + self.deadline.load(Relaxed)
+        } else {
+            abs_deadline
+        };
+
+        self.abs_deadline.store(abs_deadline, Relaxed);
+        self.remaining.store(self.runtime.load(Relaxed), Relaxed);
+    }
+
+    /// Applies the wakeup rule of CBS.
+    fn wake_up(&self, now: u64) {
+        let abs_deadline = self.abs_deadline.load(Relaxed);
+        let remaining = u128::from(self.remaining.load(Relaxed));
+
+        // The bandwidth is exceeded if `remaining / (abs_deadline - now)` is
+        // greater than `runtime / deadline`.
+        let is_overflowed = abs_deadline <= now
+            || remaining * u128::from(self.deadline.load(Relaxed))
+                > u128::from(abs_deadline - now) * u128::from(self.runtime.load(Relaxed));
+        if is_overflowed {
+            self.abs_deadline
+                .store(now + self.deadline.load(Relaxed), Relaxed);
+            self.remaining.store(self.runtime.load(Relaxed), Relaxed);
+        }
+    }
+}
+
+/// The wrapper for threads in the DEADLINE run queue.
+///
+/// The threads are keyed by their absolute deadlines at enqueuing time.
+struct DeadlineQueueItem(Arc<Task>, u64);
+
+impl core::fmt::Debug for DeadlineQueueItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.key())
+    }
+}
+
+impl DeadlineQueueItem {
+    fn key(&self) -> u64 {
+        self.1
+    }
+}
+
+impl PartialEq for DeadlineQueueItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.key().eq(&other.key())
+    }
+}
+
+impl Eq for DeadlineQueueItem {}
+
+impl PartialOrd for DeadlineQueueItem {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DeadlineQueueItem {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The per-cpu run queue for the DEADLINE scheduling class.
+///
+/// See [`DeadlineAttr`] for the explanation of the scheduling algorithm.
+#[derive(Debug)]
+pub(super) struct DeadlineClassRq {
+    /// The ready-to-run threads, ordered by their absolute deadlines.
+    entities: BinaryHeap<Reverse<DeadlineQueueItem>>,
+    /// The threads that have exhausted their runtime in the current period.
+    throttled: Vec<Arc<Task>>,
+}
+
+impl DeadlineClassRq {
+    pub fn new() -> Self {
+        Self {
+            entities: BinaryHeap::new(),
+            throttled: Vec::new(),
+        }
+    }
+
+    /// Moves the throttled threads whose next periods have started to the
+    /// ready-to-run threads.
+    fn unthrottle(&mut self) {
+        if self.throttled.is_empty() {
+            return;
+        }
+
+        let now = sched_clock();
+        let mut index = 0;
+        while index < self.throttled.len() {
+            let sched_attr = self.throttled[index].as_thread().unwrap().sched_attr();
+            let key = if sched_attr.policy_kind() != SchedPolicyKind::Deadline {
+                // The thread has left the DEADLINE class. It should run at once,
+                // and will be enqueued into the run queue of its new class after
+                // being preempted.
+                0
+            } else if sched_attr.deadline.next_period() <= now {
+                sched_attr.deadline.replenish(now);
+                sched_attr.deadline.abs_deadline.load(Relaxed)
+            } else {
+                index += 1;
+                continue;
+            };
+
+            let entity = self.throttled.swap_remove(index);
+            self.entities.push(Reverse(DeadlineQueueItem(entity, key)));
+        }
+    }
+}
+
+impl SchedClassRq for DeadlineClassRq {
+    fn enqueue(&mut self, entity: Arc<Task>, flags: Option<EnqueueFlags>) {
+        let deadline_attr = &entity.as_thread().unwrap().sched_attr().deadline;
+        if flags.is_some() {
+            deadline_attr.wake_up(sched_clock());
+        }
+
+        if deadline_attr.is_throttled() {
+            self.throttled.push(entity);
+        } else {
+            let key = deadline_attr.abs_deadline.load(Relaxed);
+            self.entities.push(Reverse(DeadlineQueueItem(entity, key)));
+        }
+    }
+
+    fn len(&mut self) -> usize {
+        self.entities.len() + self.throttled.len()
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.unthrottle();
+        self.entities.is_empty()
+    }
+
+    fn pick_next(&mut self) -> Option<Arc<Task>> {
+        self.unthrottle();
+        let Reverse(DeadlineQueueItem(entity, _)) = self.entities.pop()?;
+        Some(entity)
+    }
+
+    fn update_current(
+        &mut self,
+        rt: &CurrentRuntime,
+        attr: &SchedAttr,
+        flags: UpdateFlags,
+    ) -> bool {
+        let attr = &attr.deadline;
+        let is_exhausted = attr.consume(rt.delta);
+
+        match flags {
+            // Like Linux, a yielding thread gives up its runtime in the current period.
+            UpdateFlags::Yield => {
+                attr.remaining.store(0, Relaxed);
+                true
+            }
+            UpdateFlags::Tick | UpdateFlags::Wait => {
+                if is_exhausted {
+                    return true;
+                }
+                self.unthrottle();
+                let abs_deadline = attr.abs_deadline.load(Relaxed);
+                self.entities
+                    .peek()
+                    .is_some_and(|Reverse(earliest)| earliest.key() < abs_deadline)
+            }
+        }
+    }
+
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        let mut items = mem::take(&mut self.entities).into_vec();
+        let index = items.iter().position(|Reverse(item)| can_migrate(&item.0));
+        let stolen = index.map(|index| items.swap_remove(index));
+        self.entities = BinaryHeap::from(items);
+
+        let Reverse(DeadlineQueueItem(entity, _)) = stolen?;
+        Some(entity)
+    }
+}
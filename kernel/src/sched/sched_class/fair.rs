@@ -1,13 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{collections::binary_heap::BinaryHeap, sync::Arc};
+use alloc::{collections::binary_heap::BinaryHeap, sync::Arc, vec::Vec};
 use core::{
     cmp::{self, Reverse},
+    mem,
     sync::atomic::{AtomicU64, Ordering::*},
+    time::Duration,
 };
 
 use ostd::{
     cpu::{num_cpus, CpuId},
+    sync::SpinLock,
     task::{
         scheduler::{EnqueueFlags, UpdateFlags},
         Task,
@@ -15,7 +18,8 @@ use ostd::{
 };
 
 use super::{
-    time::{base_slice_clocks, min_period_clocks},
+    sched_clock,
+    time::{base_slice_clocks, clocks_to_ns, min_period_clocks, ns_to_clocks},
     CurrentRuntime, SchedAttr, SchedClassRq,
 };
 use crate::{
@@ -97,6 +101,7 @@ pub const fn nice_to_weight(nice: Nice) -> u64 {
 pub struct FairAttr {
     weight: AtomicU64,
     vruntime: AtomicU64,
+    group: SpinLock<Option<Arc<FairGroup>>>,
 }
 
 impl FairAttr {
@@ -104,6 +109,7 @@ impl FairAttr {
         FairAttr {
             weight: nice_to_weight(nice).into(),
             vruntime: Default::default(),
+            group: SpinLock::new(None),
         }
     }
 
@@ -111,19 +117,206 @@ impl FairAttr {
         self.weight.store(nice_to_weight(nice), Relaxed);
     }
 
-    fn update_vruntime(&self, delta: u64) -> (u64, u64) {
+    pub fn group(&self) -> Option<Arc<FairGroup>> {
+        self.group.disable_irq().lock().clone()
+    }
+
+    pub fn set_group(&self, group: Option<Arc<FairGroup>>) {
+        *self.group.disable_irq().lock() = group;
+    }
+
+    /// Returns the weight of the thread, scaled by the weights of its groups.
+    fn weight(&self) -> u64 {
         let weight = self.weight.load(Relaxed);
+        match self.group() {
+            Some(group) => group.scale_weight(weight),
+            None => weight,
+        }
+    }
+
+    fn update_vruntime(&self, delta: u64) -> (u64, u64) {
+        let weight = self.weight();
         let delta = delta * WEIGHT_0 / weight;
         let vruntime = self.vruntime.fetch_add(delta, Relaxed) + delta;
         (vruntime, weight)
     }
+
+    /// Charges the runtime to the groups of the thread, and returns whether
+    /// the thread is throttled.
+    fn charge_group(&self, delta: u64) -> bool {
+        self.group()
+            .is_some_and(|group| group.charge(delta, sched_clock()))
+    }
+
+    fn is_throttled(&self, now: u64) -> bool {
+        self.group().is_some_and(|group| group.is_throttled(now))
+    }
+}
+
+/// A group of threads that share the CPU time in the FAIR class.
+///
+/// This implements the `cpu` controller of cgroups:
+///
+/// - The weights of the threads in the group are scaled by the weight of the
+///   group (`cpu.weight`), where [`FairGroup::DEFAULT_WEIGHT`] means no scaling.
+/// - Once the threads in the group have run for the quota in the current
+///   period (`cpu.max`), they are throttled until the next period starts.
+///
+/// The weights and the quotas of the ancestors apply to the group as well.
+#[derive(Debug)]
+pub struct FairGroup {
+    parent: Option<Arc<FairGroup>>,
+    weight: AtomicU64,
+    /// The quota in each period, measured in nanoseconds. [`u64::MAX`] means
+    /// that the group is not limited.
+    quota_ns: AtomicU64,
+    period_ns: AtomicU64,
+    /// The quota and the period, measured in [`sched_clock`]s.
+    quota: AtomicU64,
+    period: AtomicU64,
+    /// The start of the current period, measured in [`sched_clock`]s.
+    period_start: AtomicU64,
+    /// The runtime in the current period, measured in [`sched_clock`]s.
+    runtime: AtomicU64,
+    /// The total runtime, measured in [`sched_clock`]s.
+    total_runtime: AtomicU64,
+    /// The number of periods in which the group is throttled.
+    nr_throttled: AtomicU64,
+}
+
+impl FairGroup {
+    pub const DEFAULT_WEIGHT: u64 = 100;
+    pub const MIN_WEIGHT: u64 = 1;
+    pub const MAX_WEIGHT: u64 = 10000;
+    pub const DEFAULT_PERIOD: Duration = Duration::from_millis(100);
+
+    pub fn new(parent: Option<Arc<FairGroup>>) -> Self {
+        let period_ns = Self::DEFAULT_PERIOD.as_nanos() as u64;
+        Self {
+            parent,
+            weight: AtomicU64::new(Self::DEFAULT_WEIGHT),
+            quota_ns: AtomicU64::new(u64::MAX),
+            period_ns: AtomicU64::new(period_ns),
+            quota: AtomicU64::new(u64::MAX),
+            period: AtomicU64::new(ns_to_clocks(period_ns)),
+            period_start: AtomicU64::new(sched_clock()),
+            runtime: AtomicU64::new(0),
+            total_runtime: AtomicU64::new(0),
+            nr_throttled: AtomicU64::new(0),
+        }
+    }
+
+    pub fn weight(&self) -> u64 {
+        self.weight.load(Relaxed)
+    }
+
+    /// Sets the weight of the group.
+    ///
+    /// The weight must be within [`Self::MIN_WEIGHT`] and [`Self::MAX_WEIGHT`].
+    pub fn set_weight(&self, weight: u64) {
+        debug_assert!((Self::MIN_WEIGHT..=Self::MAX_WEIGHT).contains(&weight));
+        self.weight.store(weight, Relaxed);
+    }
+
+    /// Returns the quota and the period of the group.
+    ///
+    /// A `None` quota means that the group is not limited.
+    pub fn max(&self) -> (Option<Duration>, Duration) {
+        let quota = match self.quota_ns.load(Relaxed) {
+            u64::MAX => None,
+            quota_ns => Some(Duration::from_nanos(quota_ns)),
+        };
+        (quota, Duration::from_nanos(self.period_ns.load(Relaxed)))
+    }
+
+    /// Sets the quota and the period of the group.
+    pub fn set_max(&self, quota: Option<Duration>, period: Duration) {
+        let quota_ns = quota.map_or(u64::MAX, |quota| quota.as_nanos() as u64);
+        let period_ns = period.as_nanos() as u64;
+
+        self.quota_ns.store(quota_ns, Relaxed);
+        self.period_ns.store(period_ns, Relaxed);
+        self.period.store(ns_to_clocks(period_ns), Relaxed);
+        self.quota
+            .store(quota.map_or(u64::MAX, |_| ns_to_clocks(quota_ns)), Relaxed);
+    }
+
+    /// Returns the total CPU time consumed by the group.
+    pub fn usage(&self) -> Duration {
+        Duration::from_nanos(clocks_to_ns(self.total_runtime.load(Relaxed)))
+    }
+
+    /// Returns the number of periods in which the group is throttled.
+    pub fn nr_throttled(&self) -> u64 {
+        self.nr_throttled.load(Relaxed)
+    }
+
+    fn ancestors(&self) -> impl Iterator<Item = &FairGroup> {
+        core::iter::successors(Some(self), |group| group.parent.as_deref())
+    }
+
+    fn scale_weight(&self, weight: u64) -> u64 {
+        self.ancestors()
+            .fold(weight, |weight, group| {
+                weight * group.weight() / Self::DEFAULT_WEIGHT
+            })
+            .max(1)
+    }
+
+    /// Starts a new period if the current one has ended.
+    fn refresh_period(&self, now: u64) {
+        let period = self.period.load(Relaxed);
+        let period_start = self.period_start.load(Relaxed);
+        if now < period_start + period {
+            return;
+        }
+
+        let new_period_start = now - (now - period_start) % period;
+        if self
+            .period_start
+            .compare_exchange(period_start, new_period_start, Relaxed, Relaxed)
+            .is_ok()
+        {
+            self.runtime.store(0, Relaxed);
+        }
+    }
+
+    /// Charges the runtime to the group and its ancestors, and returns
+    /// whether the group is throttled.
+    fn charge(&self, delta: u64, now: u64) -> bool {
+        let mut is_throttled = false;
+        for group in self.ancestors() {
+            group.refresh_period(now);
+            group.total_runtime.fetch_add(delta, Relaxed);
+
+            let quota = group.quota.load(Relaxed);
+            let runtime = group.runtime.fetch_add(delta, Relaxed);
+            if runtime + delta >= quota {
+                if runtime < quota {
+                    group.nr_throttled.fetch_add(1, Relaxed);
+                }
+                is_throttled = true;
+            }
+        }
+        is_throttled
+    }
+
+    fn is_throttled(&self, now: u64) -> bool {
+        self.ancestors().any(|group| {
+            group.refresh_period(now);
+            group.runtime.load(Relaxed) >= group.quota.load(Relaxed)
+        })
+    }
 }
 
 /// The wrapper for threads in the FAIR run queue.
 ///
 /// This structure is used to provide the capability for keying in the
 /// run queue implemented by `BTreeSet` in the `FairClassRq`.
-struct FairQueueItem(Arc<Task>, u64);
+///
+/// The weight of the thread is recorded at enqueuing time, since it may be
+/// changed by the user while the thread is in the run queue.
+struct FairQueueItem(Arc<Task>, u64, u64);
 
 impl core::fmt::Debug for FairQueueItem {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -169,6 +362,8 @@ pub(super) struct FairClassRq {
     cpu: CpuId,
     /// The ready-to-run threads.
     entities: BinaryHeap<Reverse<FairQueueItem>>,
+    /// The threads whose groups have run out of their quotas.
+    throttled: Vec<Arc<Task>>,
     /// The minimum of vruntime in the run queue. Serves as the initial
     /// value of newly-enqueued threads.
     min_vruntime: u64,
@@ -180,6 +375,7 @@ impl FairClassRq {
         Self {
             cpu,
             entities: BinaryHeap::new(),
+            throttled: Vec::new(),
             min_vruntime: 0,
             total_weight: 0,
         }
@@ -217,6 +413,26 @@ impl FairClassRq {
     fn time_slice(&self, cur_weight: u64) -> u64 {
         self.period() * cur_weight / (self.total_weight + cur_weight)
     }
+
+    /// Moves the throttled threads whose groups have new periods back to the
+    /// ready-to-run threads.
+    fn unthrottle(&mut self) {
+        if self.throttled.is_empty() {
+            return;
+        }
+
+        let now = sched_clock();
+        let mut index = 0;
+        while index < self.throttled.len() {
+            let fair_attr = &self.throttled[index].as_thread().unwrap().sched_attr().fair;
+            if fair_attr.is_throttled(now) {
+                index += 1;
+                continue;
+            }
+            let entity = self.throttled.swap_remove(index);
+            self.enqueue(entity, None);
+        }
+    }
 }
 
 impl SchedClassRq for FairClassRq {
@@ -231,25 +447,37 @@ impl SchedClassRq for FairClassRq {
             .fetch_max(vruntime, Relaxed)
             .max(vruntime);
 
-        self.total_weight += fair_attr.weight.load(Relaxed);
-        self.entities.push(Reverse(FairQueueItem(entity, vruntime)));
+        let weight = fair_attr.weight();
+        self.total_weight += weight;
+        self.entities
+            .push(Reverse(FairQueueItem(entity, vruntime, weight)));
     }
 
     fn len(&mut self) -> usize {
-        self.entities.len()
+        self.entities.len() + self.throttled.len()
     }
 
     fn is_empty(&mut self) -> bool {
+        self.unthrottle();
         self.entities.is_empty()
     }
 
     fn pick_next(&mut self) -> Option<Arc<Task>> {
-        let Reverse(FairQueueItem(entity, _)) = self.entities.pop()?;
+        self.unthrottle();
 
-        let sched_attr = entity.as_thread().unwrap().sched_attr();
-        self.total_weight -= sched_attr.fair.weight.load(Relaxed);
+        let now = sched_clock();
+        while let Some(Reverse(FairQueueItem(entity, _, weight))) = self.entities.pop() {
+            self.total_weight -= weight;
 
-        Some(entity)
+            let fair_attr = &entity.as_thread().unwrap().sched_attr().fair;
+            if fair_attr.is_throttled(now) {
+                self.throttled.push(entity);
+                continue;
+            }
+            return Some(entity);
+        }
+
+        None
     }
 
     fn update_current(
@@ -258,6 +486,8 @@ impl SchedClassRq for FairClassRq {
         attr: &SchedAttr,
         flags: UpdateFlags,
     ) -> bool {
+        let is_throttled = attr.fair.charge_group(rt.delta);
+
         match flags {
             UpdateFlags::Yield => true,
             UpdateFlags::Tick | UpdateFlags::Wait => {
@@ -267,9 +497,28 @@ impl SchedClassRq for FairClassRq {
                     None => vruntime,
                 };
 
-                rt.period_delta > self.time_slice(weight)
+                is_throttled
+                    || rt.period_delta > self.time_slice(weight)
                     || vruntime > self.min_vruntime + self.vtime_slice()
             }
         }
     }
+
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        let mut items = mem::take(&mut self.entities).into_vec();
+        let index = items.iter().position(|Reverse(item)| can_migrate(&item.0));
+        let stolen = index.map(|index| items.swap_remove(index));
+        self.entities = BinaryHeap::from(items);
+
+        let Reverse(FairQueueItem(entity, _, weight)) = stolen?;
+        self.total_weight -= weight;
+
+        // The vruntimes of different run queues are not comparable. Resetting
+        // the vruntime makes the thread start from the minimum vruntime of the
+        // run queue that it is migrated to.
+        let fair_attr = &entity.as_thread().unwrap().sched_attr().fair;
+        fair_attr.vruntime.store(0, Relaxed);
+
+        Some(entity)
+    }
 }
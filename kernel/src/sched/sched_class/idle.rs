@@ -1,55 +1,56 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use alloc::collections::vec_deque::VecDeque;
+
 use super::*;
 
 /// The per-cpu run queue for the IDLE scheduling class.
 ///
-/// This run queue is used for the per-cpu idle entity, if any.
+/// This run queue is used for the threads with the `SCHED_IDLE` policy, which
+/// only run when no thread of the other classes is runnable. The threads are
+/// scheduled in a round-robin manner.
 pub(super) struct IdleClassRq {
-    entity: Option<Arc<Task>>,
+    entities: VecDeque<Arc<Task>>,
 }
 
 impl IdleClassRq {
     pub fn new() -> Self {
-        Self { entity: None }
+        Self {
+            entities: VecDeque::new(),
+        }
     }
 }
 
 impl core::fmt::Debug for IdleClassRq {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        if self.entity.is_some() {
-            write!(f, "Idle: occupied")?;
-        } else {
-            write!(f, "Idle: empty")?;
-        }
-        Ok(())
+        write!(f, "Idle: {} entities", self.entities.len())
     }
 }
 
 impl SchedClassRq for IdleClassRq {
     fn enqueue(&mut self, entity: Arc<Task>, _: Option<EnqueueFlags>) {
-        let ptr = Arc::as_ptr(&entity);
-        if let Some(t) = self.entity.replace(entity)
-            && ptr != Arc::as_ptr(&t)
-        {
-            panic!("Multiple `idle` entities spawned")
-        }
+        self.entities.push_back(entity);
     }
 
     fn len(&mut self) -> usize {
-        usize::from(!self.is_empty())
+        self.entities.len()
     }
 
     fn is_empty(&mut self) -> bool {
-        self.entity.is_none()
+        self.entities.is_empty()
     }
 
     fn pick_next(&mut self) -> Option<Arc<Task>> {
-        self.entity.clone()
+        self.entities.pop_front()
     }
 
     fn update_current(&mut self, _: &CurrentRuntime, _: &SchedAttr, _flags: UpdateFlags) -> bool {
         // Idle entities has the greatest priority value. They should always be preempted.
         true
     }
+
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        let index = self.entities.iter().position(can_migrate)?;
+        self.entities.remove(index)
+    }
 }
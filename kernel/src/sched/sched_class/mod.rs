@@ -2,11 +2,17 @@
 
 #![warn(unused)]
 
-use alloc::{boxed::Box, sync::Arc};
-use core::{fmt, sync::atomic::AtomicU64};
+use alloc::{
+    boxed::Box,
+    sync::{Arc, Weak},
+};
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+};
 
 use ostd::{
-    cpu::{all_cpus, AtomicCpuSet, CpuId, PinCurrentCpu},
+    cpu::{all_cpus, CpuId, PinCurrentCpu},
     sync::SpinLock,
     task::{
         scheduler::{
@@ -21,6 +27,7 @@ use ostd::{
 mod policy;
 mod time;
 
+mod deadline;
 mod fair;
 mod idle;
 mod real_time;
@@ -28,19 +35,39 @@ mod stop;
 
 use ostd::arch::read_tsc as sched_clock;
 
-pub use self::policy::*;
 use self::policy::{SchedPolicyKind, SchedPolicyState};
+pub use self::{fair::FairGroup, policy::*};
 use super::{
     priority::{Nice, RangedU8},
-    stats::SchedulerStats,
+    stats::{set_stats_from_scheduler, SchedulerStats},
+};
+use crate::{
+    process::posix_thread::AsPosixThread,
+    thread::{AsThread, Thread},
 };
-use crate::thread::{AsThread, Thread};
 
 type SchedEntity = (Arc<Task>, Arc<Thread>);
 
-#[allow(unused)]
+/// Counts a context switch away from `thread` in its resource usage.
+///
+/// A switch is voluntary if the thread blocks, and involuntary if the thread
+/// is still runnable, i.e., it is preempted or it yields.
+fn account_context_switch(thread: &Thread, is_voluntary: bool) {
+    if let Some(posix_thread) = thread.as_posix_thread() {
+        posix_thread.rusage().add_context_switch(is_voluntary);
+    }
+}
+
 pub fn init() {
-    inject_scheduler(Box::leak(Box::new(ClassScheduler::new())));
+    let scheduler = Box::leak(Box::new(ClassScheduler::new()));
+
+    // Inject the scheduler into the ostd for actual scheduling work.
+    inject_scheduler(scheduler);
+
+    // Set the scheduler into the system for statistics.
+    // We set this after injecting the scheduler into ostd,
+    // so that the loadavg statistics are updated after the scheduler is used.
+    set_stats_from_scheduler(scheduler);
 }
 
 /// Represents the middle layer between scheduling classes and generic scheduler
@@ -48,6 +75,12 @@ pub fn init() {
 /// information may also be stored here.
 pub struct ClassScheduler {
     rqs: Box<[SpinLock<PerCpuClassRqSet>]>,
+    /// The number of runnable tasks (including the current one) of each CPU.
+    ///
+    /// This is updated whenever the run queue of the CPU is changed, so that
+    /// the least or the most loaded CPU can be found without locking all the
+    /// run queues.
+    loads: Box<[AtomicUsize]>,
 }
 
 /// Represents the run queue for each CPU core. It stores a list of run queues for
@@ -55,10 +88,18 @@ pub struct ClassScheduler {
 /// core is also stored in this structure.
 struct PerCpuClassRqSet {
     stop: stop::StopClassRq,
+    deadline: deadline::DeadlineClassRq,
     real_time: real_time::RealTimeClassRq,
     fair: fair::FairClassRq,
     idle: idle::IdleClassRq,
     current: Option<(SchedEntity, CurrentRuntime)>,
+    /// The task that was last switched out of this CPU.
+    ///
+    /// The context of the task may not be saved yet even if it is in the run
+    /// queue, so it must not be migrated to other CPUs.
+    last_preempted: Weak<Task>,
+    /// The time of the next load balancing, measured in [`sched_clock`]s.
+    next_balance: u64,
 }
 
 /// Stores the runtime information of the current task.
@@ -110,6 +151,10 @@ trait SchedClassRq: Send + fmt::Debug {
     /// Update the information of the current task.
     fn update_current(&mut self, rt: &CurrentRuntime, attr: &SchedAttr, flags: UpdateFlags)
         -> bool;
+
+    /// Removes a task that satisfies `can_migrate` from the run queue, so that
+    /// it can be migrated to another CPU.
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>>;
 }
 
 /// The scheduling attribute for a thread.
@@ -120,15 +165,22 @@ trait SchedClassRq: Send + fmt::Debug {
 pub struct SchedAttr {
     policy: SchedPolicyState,
 
+    deadline: deadline::DeadlineAttr,
     real_time: real_time::RealTimeAttr,
     fair: fair::FairAttr,
 }
 
 impl SchedAttr {
     /// Constructs a new `SchedAttr` with the given scheduling policy.
+    ///
+    /// The policy must not be [`SchedPolicy::Deadline`], which is subject to
+    /// admission control and can only be set by [`Self::set_policy`].
     pub fn new(policy: SchedPolicy) -> Self {
+        debug_assert!(!matches!(policy, SchedPolicy::Deadline(_)));
+
         Self {
             policy: SchedPolicyState::new(policy),
+            deadline: deadline::DeadlineAttr::new(),
             real_time: {
                 let (prio, policy) = match policy {
                     SchedPolicy::RealTime { rt_prio, rt_policy } => (rt_prio.get(), rt_policy),
@@ -137,7 +189,7 @@ impl SchedAttr {
                 real_time::RealTimeAttr::new(prio, policy)
             },
             fair: fair::FairAttr::new(match policy {
-                SchedPolicy::Fair(nice) => nice,
+                SchedPolicy::Fair(nice) | SchedPolicy::Batch(nice) => nice,
                 _ => Nice::default(),
             }),
         }
@@ -156,14 +208,42 @@ impl SchedAttr {
     ///
     /// Specifically for real-time policies, if the new policy doesn't
     /// specify a base slice factor for RR, the old one will be kept.
-    pub fn set_policy(&self, policy: SchedPolicy) {
-        self.policy.set(policy, |policy| match policy {
-            SchedPolicy::RealTime { rt_prio, rt_policy } => {
-                self.real_time.update(rt_prio.get(), rt_policy);
+    ///
+    /// For the deadline policy, this method fails with `EBUSY` if the
+    /// bandwidth of the new policy cannot be admitted.
+    pub fn set_policy(&self, policy: SchedPolicy) -> crate::prelude::Result<()> {
+        self.policy.set(policy, |old_policy, policy| {
+            deadline::update_bandwidth(old_policy, policy)?;
+            match policy {
+                SchedPolicy::Deadline(params) => self.deadline.update(&params),
+                SchedPolicy::RealTime { rt_prio, rt_policy } => {
+                    self.real_time.update(rt_prio.get(), rt_policy);
+                }
+                SchedPolicy::Fair(nice) | SchedPolicy::Batch(nice) => self.fair.update(nice),
+                _ => {}
             }
-            SchedPolicy::Fair(nice) => self.fair.update(nice),
-            _ => {}
-        });
+            Ok(())
+        })
+    }
+
+    /// Retrieves the group of the thread in the FAIR class.
+    pub fn fair_group(&self) -> Option<Arc<FairGroup>> {
+        self.fair.group()
+    }
+
+    /// Moves the thread to another group in the FAIR class.
+    ///
+    /// The new group takes effect the next time the thread is enqueued or
+    /// runs.
+    pub fn set_fair_group(&self, group: Option<Arc<FairGroup>>) {
+        self.fair.set_group(group);
+    }
+}
+
+impl Drop for SchedAttr {
+    fn drop(&mut self) {
+        // Release the bandwidth of the deadline policy, which never fails.
+        let _ = deadline::update_bandwidth(self.policy(), SchedPolicy::Idle);
     }
 }
 
@@ -172,7 +252,7 @@ impl Scheduler for ClassScheduler {
         let thread = task.as_thread()?.clone();
 
         let (still_in_rq, cpu) = {
-            let selected_cpu_id = self.select_cpu(thread.atomic_cpu_affinity());
+            let selected_cpu_id = self.select_cpu(&thread, flags);
 
             if let Err(task_cpu_id) = task.cpu().set_if_is_none(selected_cpu_id) {
                 debug_assert!(flags != EnqueueFlags::Spawn);
@@ -190,13 +270,17 @@ impl Scheduler for ClassScheduler {
         }
 
         rq.enqueue_entity((task, thread), Some(flags));
+        self.update_load(cpu, &mut rq);
         Some(cpu)
     }
 
     fn local_mut_rq_with(&self, f: &mut dyn FnMut(&mut dyn LocalRunQueue)) {
         let guard = disable_local();
-        let mut lock = self.rqs[guard.current_cpu().as_usize()].lock();
-        f(&mut *lock)
+        let cpu = guard.current_cpu();
+        let mut lock = self.rqs[cpu.as_usize()].lock();
+        self.balance(cpu, &mut lock);
+        f(&mut *lock);
+        self.update_load(cpu, &mut lock);
     }
 
     fn local_rq_with(&self, f: &mut dyn FnMut(&dyn LocalRunQueue)) {
@@ -210,33 +294,124 @@ impl ClassScheduler {
         let class_rq = |cpu| {
             SpinLock::new(PerCpuClassRqSet {
                 stop: stop::StopClassRq::new(),
+                deadline: deadline::DeadlineClassRq::new(),
                 real_time: real_time::RealTimeClassRq::new(cpu),
                 fair: fair::FairClassRq::new(cpu),
                 idle: idle::IdleClassRq::new(),
                 current: None,
+                last_preempted: Weak::new(),
+                next_balance: 0,
             })
         };
         ClassScheduler {
             rqs: all_cpus().map(class_rq).collect(),
+            loads: all_cpus().map(|_| AtomicUsize::new(0)).collect(),
         }
     }
 
-    // TODO: Implement a better algorithm and replace the current naive implementation.
-    fn select_cpu(&self, affinity: &AtomicCpuSet) -> CpuId {
+    fn load(&self, cpu: CpuId) -> usize {
+        self.loads[cpu.as_usize()].load(Relaxed)
+    }
+
+    fn update_load(&self, cpu: CpuId, rq: &mut PerCpuClassRqSet) {
+        let (queued, running) = rq.nr_queued_and_running();
+        self.loads[cpu.as_usize()].store((queued + running) as usize, Relaxed);
+    }
+
+    /// Selects the CPU to enqueue the task of `thread`.
+    ///
+    /// A new task is placed on the least loaded CPU. A woken task is placed
+    /// on the current CPU if it is not much busier than the least loaded one,
+    /// since the wakee is likely to share data with the waker (wake-affine).
+    /// The waker is counted in the load of the current CPU, but it is likely
+    /// to sleep soon, so one more task is tolerated.
+    fn select_cpu(&self, thread: &Thread, flags: EnqueueFlags) -> CpuId {
         let guard = disable_local();
-        let affinity = affinity.load();
+        let affinity = thread.atomic_cpu_affinity().load();
         let cur = guard.current_cpu();
-        if affinity.contains(cur) {
-            cur
-        } else {
-            affinity.iter().next().expect("empty affinity")
+
+        let (idlest, min_load) = affinity
+            .iter()
+            .map(|cpu| (cpu, self.load(cpu)))
+            .min_by_key(|(_, load)| *load)
+            .expect("empty affinity");
+        if !affinity.contains(cur) {
+            return idlest;
+        }
+
+        let cur_load = self.load(cur);
+        match flags {
+            EnqueueFlags::Wake if cur_load <= min_load + 1 => cur,
+            EnqueueFlags::Spawn if cur_load == min_load => cur,
+            _ => idlest,
+        }
+    }
+
+    /// Pulls tasks from the busiest CPU to balance the loads.
+    ///
+    /// The balancing is done periodically, and more frequently if the local
+    /// CPU is idle. Only the tasks whose affinities contain the local CPU are
+    /// migrated.
+    fn balance(&self, cpu: CpuId, local_rq: &mut PerCpuClassRqSet) {
+        let now = sched_clock();
+        if now < local_rq.next_balance {
+            return;
         }
+
+        self.update_load(cpu, local_rq);
+        let local_load = self.load(cpu);
+        // Every CPU has an idle thread, which is always runnable.
+        let is_idle = local_load <= 1;
+        local_rq.next_balance = now
+            + if is_idle {
+                time::base_slice_clocks()
+            } else {
+                time::min_period_clocks()
+            };
+
+        let Some((busiest, busiest_load)) = all_cpus()
+            .filter(|other| *other != cpu)
+            .map(|other| (other, self.load(other)))
+            .max_by_key(|(_, load)| *load)
+        else {
+            return;
+        };
+        // Pull a half of the imbalance, so that the two CPUs end up with
+        // similar loads.
+        let nr_to_pull = busiest_load.saturating_sub(local_load) / 2;
+        if nr_to_pull == 0 {
+            return;
+        }
+
+        // The local run queue is locked, so the busiest one is only tried to
+        // avoid deadlocks.
+        let Some(mut busiest_rq) = self.rqs[busiest.as_usize()].try_lock() else {
+            return;
+        };
+        let last_preempted = busiest_rq.last_preempted.clone();
+        let can_migrate = |task: &Arc<Task>| {
+            !core::ptr::eq(Arc::as_ptr(task), last_preempted.as_ptr())
+                && task
+                    .as_thread()
+                    .is_some_and(|thread| thread.atomic_cpu_affinity().load().contains(cpu))
+        };
+        for _ in 0..nr_to_pull {
+            let Some(task) = busiest_rq.steal(&can_migrate) else {
+                break;
+            };
+            task.cpu().set_anyway(cpu);
+            let thread = task.as_thread().unwrap().clone();
+            local_rq.enqueue_entity((task, thread), None);
+        }
+        self.update_load(busiest, &mut busiest_rq);
+        self.update_load(cpu, local_rq);
     }
 }
 
 impl PerCpuClassRqSet {
     fn pick_next_entity(&mut self) -> Option<SchedEntity> {
         (self.stop.pick_next())
+            .or_else(|| self.deadline.pick_next())
             .or_else(|| self.real_time.pick_next())
             .or_else(|| self.fair.pick_next())
             .or_else(|| self.idle.pick_next())
@@ -249,14 +424,26 @@ impl PerCpuClassRqSet {
     fn enqueue_entity(&mut self, (task, thread): SchedEntity, flags: Option<EnqueueFlags>) {
         match thread.sched_attr().policy_kind() {
             SchedPolicyKind::Stop => self.stop.enqueue(task, flags),
+            SchedPolicyKind::Deadline => self.deadline.enqueue(task, flags),
             SchedPolicyKind::RealTime => self.real_time.enqueue(task, flags),
             SchedPolicyKind::Fair => self.fair.enqueue(task, flags),
             SchedPolicyKind::Idle => self.idle.enqueue(task, flags),
         }
     }
 
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        (self.deadline.steal(can_migrate))
+            .or_else(|| self.real_time.steal(can_migrate))
+            .or_else(|| self.fair.steal(can_migrate))
+            .or_else(|| self.idle.steal(can_migrate))
+    }
+
     fn nr_queued_and_running(&mut self) -> (u32, u32) {
-        let queued = self.stop.len() + self.real_time.len() + self.fair.len() + self.idle.len();
+        let queued = self.stop.len()
+            + self.deadline.len()
+            + self.real_time.len()
+            + self.fair.len()
+            + self.idle.len();
         let running = usize::from(self.current.is_some());
         (queued as u32, running as u32)
     }
@@ -274,6 +461,8 @@ impl LocalRunQueue for PerCpuClassRqSet {
                 if Arc::as_ptr(&old.0) == next_ptr {
                     return None;
                 }
+                account_context_switch(&old.1, false);
+                self.last_preempted = Arc::downgrade(&old.0);
                 self.enqueue_entity(old, None);
             }
             self.current.as_ref().map(|((task, _), _)| task)
@@ -287,22 +476,25 @@ impl LocalRunQueue for PerCpuClassRqSet {
 
             let (current_expired, lookahead) = match attr.policy_kind() {
                 SchedPolicyKind::Stop => (self.stop.update_current(rt, attr, flags), 0),
-                SchedPolicyKind::RealTime => (self.real_time.update_current(rt, attr, flags), 1),
-                SchedPolicyKind::Fair => (self.fair.update_current(rt, attr, flags), 2),
-                SchedPolicyKind::Idle => (self.idle.update_current(rt, attr, flags), 3),
+                SchedPolicyKind::Deadline => (self.deadline.update_current(rt, attr, flags), 1),
+                SchedPolicyKind::RealTime => (self.real_time.update_current(rt, attr, flags), 2),
+                SchedPolicyKind::Fair => (self.fair.update_current(rt, attr, flags), 3),
+                SchedPolicyKind::Idle => (self.idle.update_current(rt, attr, flags), 4),
             };
 
             current_expired
                 || (lookahead >= 1 && !self.stop.is_empty())
-                || (lookahead >= 2 && !self.real_time.is_empty())
-                || (lookahead >= 3 && !self.fair.is_empty())
+                || (lookahead >= 2 && !self.deadline.is_empty())
+                || (lookahead >= 3 && !self.real_time.is_empty())
+                || (lookahead >= 4 && !self.fair.is_empty())
         } else {
             true
         }
     }
 
     fn dequeue_current(&mut self) -> Option<Arc<Task>> {
-        self.current.take().map(|((cur_task, _), _)| {
+        self.current.take().map(|((cur_task, cur), _)| {
+            account_context_switch(&cur, true);
             cur_task.schedule_info().cpu.set_to_none();
             cur_task
         })
@@ -1,13 +1,23 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU8, Ordering::Relaxed};
+use core::{
+    sync::atomic::{AtomicU8, Ordering::Relaxed},
+    time::Duration,
+};
 
 use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 use int_to_c_enum::TryFromInt;
 use ostd::sync::SpinLock;
 
-pub use super::real_time::RealTimePolicy;
-use crate::sched::priority::{Nice, Priority, RangedU8};
+use super::time::BASE_SLICE_NS;
+pub use super::{
+    deadline::DeadlineParams,
+    real_time::{RealTimePolicy, RtPrio},
+};
+use crate::{
+    prelude::Result,
+    sched::priority::{Nice, Priority, RangedU8},
+};
 
 /// The User-chosen scheduling policy.
 ///
@@ -15,11 +25,17 @@ use crate::sched::priority::{Nice, Priority, RangedU8};
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SchedPolicy {
     Stop,
+    /// The deadline policy (`SCHED_DEADLINE`), which takes precedence over
+    /// all the other policies except [`SchedPolicy::Stop`].
+    Deadline(DeadlineParams),
     RealTime {
-        rt_prio: super::real_time::RtPrio,
+        rt_prio: RtPrio,
         rt_policy: RealTimePolicy,
     },
     Fair(Nice),
+    /// The batch policy (`SCHED_BATCH`), which is scheduled by the FAIR class
+    /// as [`SchedPolicy::Fair`] but is reported to the user separately.
+    Batch(Nice),
     Idle,
 }
 
@@ -27,9 +43,10 @@ pub enum SchedPolicy {
 #[repr(u8)]
 pub(super) enum SchedPolicyKind {
     Stop = 0,
-    RealTime = 1,
-    Fair = 2,
-    Idle = 3,
+    Deadline = 1,
+    RealTime = 2,
+    Fair = 3,
+    Idle = 4,
 }
 
 impl From<Priority> for SchedPolicy {
@@ -50,11 +67,24 @@ impl SchedPolicy {
     pub(super) fn kind(&self) -> SchedPolicyKind {
         match self {
             SchedPolicy::Stop => SchedPolicyKind::Stop,
+            SchedPolicy::Deadline(_) => SchedPolicyKind::Deadline,
             SchedPolicy::RealTime { .. } => SchedPolicyKind::RealTime,
-            SchedPolicy::Fair(_) => SchedPolicyKind::Fair,
+            SchedPolicy::Fair(_) | SchedPolicy::Batch(_) => SchedPolicyKind::Fair,
             SchedPolicy::Idle => SchedPolicyKind::Idle,
         }
     }
+
+    /// Returns the time slice of the policy.
+    ///
+    /// A zero time slice means that the thread runs until it blocks, yields or
+    /// is preempted by a thread with a higher priority.
+    pub fn time_slice(&self) -> Duration {
+        match self {
+            SchedPolicy::RealTime { rt_policy, .. } => rt_policy.time_slice(),
+            SchedPolicy::Fair(_) | SchedPolicy::Batch(_) => Duration::from_nanos(BASE_SLICE_NS),
+            SchedPolicy::Stop | SchedPolicy::Deadline(_) | SchedPolicy::Idle => Duration::ZERO,
+        }
+    }
 }
 
 define_atomic_version_of_integer_like_type!(SchedPolicyKind, try_from = true, {
@@ -90,7 +120,15 @@ impl SchedPolicyState {
         *self.policy.disable_irq().lock()
     }
 
-    pub fn set(&self, mut policy: SchedPolicy, update: impl FnOnce(SchedPolicy)) {
+    /// Sets the policy.
+    ///
+    /// `update` is called with the old and the new policies. If it fails, the
+    /// policy is left unchanged.
+    pub fn set(
+        &self,
+        mut policy: SchedPolicy,
+        update: impl FnOnce(SchedPolicy, SchedPolicy) -> Result<()>,
+    ) -> Result<()> {
         let mut this = self.policy.disable_irq().lock();
 
         // Keep the old base slice factor if the new policy doesn't specify one.
@@ -111,8 +149,10 @@ impl SchedPolicyState {
             *base_slice_factor = slot.or(*base_slice_factor);
         }
 
-        update(policy);
+        update(*this, policy)?;
         self.kind.store(policy.kind(), Relaxed);
         *this = policy;
+
+        Ok(())
     }
 }
@@ -5,11 +5,15 @@ use core::{
     array,
     num::NonZero,
     sync::atomic::{AtomicU8, Ordering::*},
+    time::Duration,
 };
 
 use bitvec::{bitarr, BitArr};
 
-use super::{time::base_slice_clocks, *};
+use super::{
+    time::{base_slice_clocks, BASE_SLICE_NS},
+    *,
+};
 
 pub type RtPrio = RangedU8<1, 99>;
 
@@ -40,6 +44,18 @@ impl RealTimePolicy {
             RealTimePolicy::Fifo => 0,
         }
     }
+
+    /// Returns the time slice of the policy, which is zero for FIFO.
+    pub(super) fn time_slice(self) -> Duration {
+        match self {
+            RealTimePolicy::RoundRobin { base_slice_factor } => Duration::from_nanos(
+                BASE_SLICE_NS
+                    * base_slice_factor
+                        .map_or(DEFAULT_BASE_SLICE_FACTOR, |factor| u64::from(factor.get())),
+            ),
+            RealTimePolicy::Fifo => Duration::ZERO,
+        }
+    }
 }
 
 /// The scheduling attribute for the REAL-TIME scheduling class.
@@ -120,6 +136,22 @@ impl PrioArray {
         }
         Some(thread)
     }
+
+    /// Removes the task with the highest priority that satisfies `can_migrate`.
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        let (prio, index) = self.map.iter_ones().find_map(|prio| {
+            let index = self.queue[prio].iter().position(can_migrate)?;
+            Some((prio, index))
+        })?;
+
+        let queue = &mut self.queue[prio];
+        let thread = queue.remove(index)?;
+
+        if queue.is_empty() {
+            self.map.set(prio, false);
+        }
+        Some(thread)
+    }
 }
 
 /// The per-cpu run queue for the REAL-TIME scheduling class.
@@ -214,4 +246,11 @@ impl SchedClassRq for RealTimeClassRq {
             UpdateFlags::Yield => true,
         }
     }
+
+    fn steal(&mut self, can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        let thread = (self.active_array().steal(can_migrate))
+            .or_else(|| self.inactive_array().steal(can_migrate))?;
+        self.nr_running -= 1;
+        Some(thread)
+    }
 }
@@ -50,4 +50,9 @@ impl SchedClassRq for StopClassRq {
         // Stop threads has the lowest priority value. They should never be preempted.
         false
     }
+
+    fn steal(&mut self, _can_migrate: &dyn Fn(&Arc<Task>) -> bool) -> Option<Arc<Task>> {
+        // Stop threads are bound to their CPUs.
+        None
+    }
 }
@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Per-CPU time accounting.
+//!
+//! At every system timer interrupt, the elapsed tick is charged to one of the
+//! categories of [`CpuTime`] according to what the CPU was doing when it was
+//! interrupted. The accumulated ticks are reported to the user space through
+//! `/proc/stat` and `/proc/uptime`.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use ostd::{
+    arch::trap::is_kernel_interrupted,
+    cpu::{all_cpus, num_cpus, CpuId, PinCurrentCpu},
+    timer, trap,
+};
+use spin::Once;
+
+use crate::{process::posix_thread::AsPosixThread, sched::priority::Priority, thread::Thread};
+
+/// The CPU time spent in each category, in ticks of the system timer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuTime {
+    /// The time spent in the user mode by tasks with a non-positive nice value.
+    pub user: u64,
+    /// The time spent in the user mode by tasks with a positive nice value.
+    pub nice: u64,
+    /// The time spent in the kernel mode.
+    pub system: u64,
+    /// The time spent in the idle task.
+    pub idle: u64,
+}
+
+impl CpuTime {
+    fn add(&mut self, other: &CpuTime) {
+        self.user += other.user;
+        self.nice += other.nice;
+        self.system += other.system;
+        self.idle += other.idle;
+    }
+}
+
+#[derive(Default)]
+struct CpuTimeCounters {
+    user: AtomicU64,
+    nice: AtomicU64,
+    system: AtomicU64,
+    idle: AtomicU64,
+}
+
+static CPU_TIME_COUNTERS: Once<Box<[CpuTimeCounters]>> = Once::new();
+
+/// Registers the accounting callback on the current CPU.
+///
+/// This function must be called once on every CPU.
+pub fn init_on_current_cpu() {
+    CPU_TIME_COUNTERS.call_once(|| {
+        (0..num_cpus())
+            .map(|_| CpuTimeCounters::default())
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    });
+
+    timer::register_callback(account_tick);
+}
+
+fn account_tick() {
+    let Some(counters) = CPU_TIME_COUNTERS.get() else {
+        return;
+    };
+    let irq_guard = trap::disable_local();
+    let counters = &counters[irq_guard.current_cpu().as_usize()];
+
+    let current_thread = Thread::current();
+    let posix_thread = current_thread
+        .as_ref()
+        .and_then(|thread| thread.as_posix_thread());
+
+    // The idle threads are the kernel threads with the idle priority.
+    let is_idle = match &current_thread {
+        None => true,
+        Some(thread) => {
+            posix_thread.is_none()
+                && thread.atomic_priority().load(Ordering::Relaxed) == Priority::idle()
+        }
+    };
+
+    let counter = if is_idle {
+        &counters.idle
+    } else if is_kernel_interrupted() {
+        &counters.system
+    } else if posix_thread.is_some_and(|posix_thread| {
+        i8::from(posix_thread.process().nice().load(Ordering::Relaxed)) > 0
+    }) {
+        &counters.nice
+    } else {
+        &counters.user
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the CPU time of the given CPU.
+pub fn cpu_time(cpu: CpuId) -> CpuTime {
+    let Some(counters) = CPU_TIME_COUNTERS.get() else {
+        return CpuTime::default();
+    };
+    let counters = &counters[cpu.as_usize()];
+    CpuTime {
+        user: counters.user.load(Ordering::Relaxed),
+        nice: counters.nice.load(Ordering::Relaxed),
+        system: counters.system.load(Ordering::Relaxed),
+        idle: counters.idle.load(Ordering::Relaxed),
+    }
+}
+
+/// Returns the CPU time summed over all CPUs.
+pub fn total_cpu_time() -> CpuTime {
+    let mut total = CpuTime::default();
+    for cpu in all_cpus() {
+        total.add(&cpu_time(cpu));
+    }
+    total
+}
@@ -3,7 +3,7 @@
 use ostd::timer;
 use spin::Once;
 
-use super::loadavg;
+use super::{cpu_time, loadavg};
 
 /// The global scheduler statistic singleton
 static SCHEDULER_STATS: Once<&'static dyn SchedulerStats> = Once::new();
@@ -11,8 +11,9 @@ static SCHEDULER_STATS: Once<&'static dyn SchedulerStats> = Once::new();
 /// Set the global scheduler statistics singleton.
 ///
 /// This function should be called once to set the scheduler statistics system.
-/// It is used to get running stats from the scheduler and to periodically
-/// calculate the system load average.
+/// It is used to get running stats from the scheduler, to periodically
+/// calculate the system load average and to account the CPU time of the
+/// bootstrap processor.
 pub fn set_stats_from_scheduler(scheduler: &'static dyn SchedulerStats) {
     SCHEDULER_STATS.call_once(|| scheduler);
 
@@ -20,6 +21,8 @@ pub fn set_stats_from_scheduler(scheduler: &'static dyn SchedulerStats) {
     timer::register_callback(|| {
         loadavg::update_loadavg(|| nr_queued_and_running().0);
     });
+
+    cpu_time::init_on_current_cpu();
 }
 
 /// The trait for the scheduler statistics.
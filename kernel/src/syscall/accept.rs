@@ -75,7 +75,7 @@ fn do_accept(
 
     let fd = {
         let mut file_table_locked = file_table.write();
-        file_table_locked.insert(connected_socket, fd_flags)
+        file_table_locked.insert(connected_socket, fd_flags)?
     };
 
     Ok(fd)
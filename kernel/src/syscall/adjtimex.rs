@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{clock_settime::check_sys_time_capability, SyscallReturn};
+use crate::{
+    prelude::*,
+    syscall::ClockId,
+    time::{
+        clockid_t,
+        timekeeping::{self, NtpState, MAX_FREQ_PPM, USER_TICK_USEC},
+    },
+};
+
+pub fn sys_adjtimex(timex_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let state = do_adjtimex(timex_addr, ctx)?;
+    Ok(SyscallReturn::Return(state as _))
+}
+
+pub fn sys_clock_adjtime(
+    clockid: clockid_t,
+    timex_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("clockid = {:?}", clockid);
+
+    // Only `CLOCK_REALTIME` is adjustable.
+    if ClockId::try_from(clockid)? != ClockId::CLOCK_REALTIME {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the clock cannot be adjusted");
+    }
+
+    let state = do_adjtimex(timex_addr, ctx)?;
+    Ok(SyscallReturn::Return(state as _))
+}
+
+fn do_adjtimex(timex_addr: Vaddr, ctx: &Context) -> Result<i32> {
+    let user_space = ctx.user_space();
+    let mut timex = user_space.read_val::<timex>(timex_addr)?;
+    debug!("timex = {:?}", timex);
+
+    let modes = AdjModes::from_bits_truncate(timex.modes);
+    validate_timex(&timex, modes, ctx)?;
+
+    if modes.contains(AdjModes::ADJ_SETOFFSET) {
+        let scale = if modes.contains(AdjModes::ADJ_NANO) {
+            1
+        } else {
+            1000
+        };
+        let delta = timex
+            .time
+            .sec
+            .checked_mul(1_000_000_000)
+            .and_then(|nanos| nanos.checked_add(timex.time.usec * scale))
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the offset is too large"))?;
+        timekeeping::step_realtime(delta)?;
+    }
+
+    let state = timekeeping::with_ntp_state(|ntp| {
+        if modes.is_adjtime() {
+            // The `adjtime` interface sets the offset in microseconds and
+            // returns the remaining offset.
+            let old_offset = ntp.offset / 1000;
+            if modes == AdjModes::ADJ_OFFSET_SINGLESHOT {
+                ntp.offset = timex.offset.saturating_mul(1000);
+            }
+            timex.offset = old_offset;
+        } else {
+            apply_modes(ntp, &timex, modes);
+            timex.offset = if ntp.status & NtpState::STA_NANO != 0 {
+                ntp.offset
+            } else {
+                ntp.offset / 1000
+            };
+        }
+
+        timex.freq = ntp.freq;
+        timex.maxerror = ntp.maxerror;
+        timex.esterror = ntp.esterror;
+        timex.status = ntp.status;
+        timex.constant = ntp.constant;
+        timex.precision = 1;
+        timex.tolerance = timekeeping::scaled_ppm(MAX_FREQ_PPM);
+        timex.tick = ntp.tick;
+        timex.tai = ntp.tai;
+
+        if ntp.status & NtpState::STA_UNSYNC != 0 {
+            TIME_ERROR
+        } else {
+            TIME_OK
+        }
+    });
+
+    let now = timekeeping::read_realtime();
+    timex.time.sec = now.as_secs() as i64;
+    timex.time.usec = if timex.status & NtpState::STA_NANO != 0 {
+        now.subsec_nanos() as i64
+    } else {
+        now.subsec_micros() as i64
+    };
+
+    user_space.write_val(timex_addr, &timex)?;
+
+    Ok(state)
+}
+
+/// Checks the permission and the values to be set before any change is made.
+fn validate_timex(timex: &timex, modes: AdjModes, ctx: &Context) -> Result<()> {
+    if modes.is_adjtime() {
+        // The `adjtime` modes cannot be combined with other modes.
+        if modes != AdjModes::ADJ_OFFSET_SINGLESHOT && modes != AdjModes::ADJ_OFFSET_SS_READ {
+            return_errno_with_message!(Errno::EINVAL, "invalid adjtime modes");
+        }
+        if modes == AdjModes::ADJ_OFFSET_SINGLESHOT {
+            check_sys_time_capability(ctx)?;
+        }
+        return Ok(());
+    }
+
+    if !modes.is_empty() {
+        check_sys_time_capability(ctx)?;
+    }
+
+    if modes.contains(AdjModes::ADJ_TICK)
+        && !(USER_TICK_USEC * 9 / 10..=USER_TICK_USEC * 11 / 10).contains(&timex.tick)
+    {
+        return_errno_with_message!(Errno::EINVAL, "the tick is out of range");
+    }
+
+    if modes.contains(AdjModes::ADJ_SETOFFSET) {
+        let max_usec = if modes.contains(AdjModes::ADJ_NANO) {
+            1_000_000_000
+        } else {
+            1_000_000
+        };
+        if !(0..max_usec).contains(&timex.time.usec) {
+            return_errno_with_message!(Errno::EINVAL, "the offset is not normalized");
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the modes other than `ADJ_SETOFFSET` to the NTP state.
+fn apply_modes(ntp: &mut NtpState, timex: &timex, modes: AdjModes) {
+    if modes.contains(AdjModes::ADJ_STATUS) {
+        ntp.status = (ntp.status & NtpState::STA_RONLY) | (timex.status & !NtpState::STA_RONLY);
+    }
+    if modes.contains(AdjModes::ADJ_NANO) {
+        ntp.status |= NtpState::STA_NANO;
+    }
+    if modes.contains(AdjModes::ADJ_MICRO) {
+        ntp.status &= !NtpState::STA_NANO;
+    }
+
+    if modes.contains(AdjModes::ADJ_FREQUENCY) {
+        let max_freq = timekeeping::scaled_ppm(MAX_FREQ_PPM);
+        ntp.freq = timex.freq.clamp(-max_freq, max_freq);
+    }
+    if modes.contains(AdjModes::ADJ_MAXERROR) {
+        ntp.maxerror = timex.maxerror.clamp(0, MAX_PHASE_ERROR);
+    }
+    if modes.contains(AdjModes::ADJ_ESTERROR) {
+        ntp.esterror = timex.esterror.clamp(0, MAX_PHASE_ERROR);
+    }
+    if modes.contains(AdjModes::ADJ_TIMECONST) {
+        ntp.constant = timex.constant.clamp(0, MAX_TIME_CONSTANT);
+    }
+    if modes.contains(AdjModes::ADJ_TAI) && timex.constant >= 0 {
+        ntp.tai = timex.constant as i32;
+    }
+    if modes.contains(AdjModes::ADJ_TICK) {
+        ntp.tick = timex.tick;
+    }
+
+    if modes.contains(AdjModes::ADJ_OFFSET) {
+        let offset = if ntp.status & NtpState::STA_NANO != 0 {
+            timex.offset
+        } else {
+            timex.offset.saturating_mul(1000)
+        };
+        ntp.offset = offset.clamp(-MAX_PHASE_NANOS, MAX_PHASE_NANOS);
+    }
+}
+
+/// The clock is synchronized.
+const TIME_OK: i32 = 0;
+/// The clock is not synchronized.
+const TIME_ERROR: i32 = 5;
+
+/// The maximum offset to slew with `ADJ_OFFSET`, in nanoseconds.
+const MAX_PHASE_NANOS: i64 = 500_000_000;
+/// The maximum value of the errors, in microseconds.
+const MAX_PHASE_ERROR: i64 = 16_000_000;
+/// The maximum PLL time constant.
+const MAX_TIME_CONSTANT: i64 = 10;
+
+bitflags! {
+    struct AdjModes: u32 {
+        const ADJ_OFFSET = 0x0001;
+        const ADJ_FREQUENCY = 0x0002;
+        const ADJ_MAXERROR = 0x0004;
+        const ADJ_ESTERROR = 0x0008;
+        const ADJ_STATUS = 0x0010;
+        const ADJ_TIMECONST = 0x0020;
+        const ADJ_TAI = 0x0080;
+        const ADJ_SETOFFSET = 0x0100;
+        const ADJ_MICRO = 0x1000;
+        const ADJ_NANO = 0x2000;
+        const ADJ_TICK = 0x4000;
+        const ADJ_ADJTIME = 0x8000;
+        const ADJ_OFFSET_SINGLESHOT = 0x8001;
+        const ADJ_OFFSET_SS_READ = 0xa001;
+    }
+}
+
+impl AdjModes {
+    /// Returns whether the modes are for the `adjtime` interface.
+    fn is_adjtime(&self) -> bool {
+        self.contains(Self::ADJ_ADJTIME)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[allow(non_camel_case_types)]
+struct timex_timeval {
+    sec: i64,
+    usec: i64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+#[allow(non_camel_case_types)]
+struct timex {
+    modes: u32,
+    _pad0: u32,
+    offset: i64,
+    freq: i64,
+    maxerror: i64,
+    esterror: i64,
+    status: i32,
+    _pad1: u32,
+    constant: i64,
+    precision: i64,
+    tolerance: i64,
+    time: timex_timeval,
+    tick: i64,
+    ppsfreq: i64,
+    jitter: i64,
+    shift: i32,
+    _pad2: u32,
+    stabil: i64,
+    jitcnt: i64,
+    calcnt: i64,
+    errcnt: i64,
+    stbcnt: i64,
+    tai: i32,
+    _pad3: [u32; 11],
+}
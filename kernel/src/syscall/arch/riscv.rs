@@ -3,6 +3,7 @@
 use crate::syscall::{
     accept::{sys_accept, sys_accept4},
     access::sys_faccessat,
+    adjtimex::{sys_adjtimex, sys_clock_adjtime},
     bind::sys_bind,
     brk::sys_brk,
     capget::sys_capget,
@@ -11,7 +12,9 @@ use crate::syscall::{
     chmod::{sys_fchmod, sys_fchmodat},
     chown::{sys_fchown, sys_fchownat},
     chroot::sys_chroot,
+    clock_getres::sys_clock_getres,
     clock_gettime::sys_clock_gettime,
+    clock_settime::sys_clock_settime,
     clone::{sys_clone, sys_clone3},
     close::sys_close,
     connect::sys_connect,
@@ -53,15 +56,19 @@ use crate::syscall::{
     listen::sys_listen,
     lseek::sys_lseek,
     madvise::sys_madvise,
+    mincore::sys_mincore,
     mkdir::sys_mkdirat,
     mknod::sys_mknodat,
+    mlock::{sys_mlock, sys_mlock2, sys_mlockall, sys_munlock, sys_munlockall},
     mmap::sys_mmap,
     mount::sys_mount,
     mprotect::sys_mprotect,
+    mremap::sys_mremap,
     msync::sys_msync,
     munmap::sys_munmap,
     nanosleep::{sys_clock_nanosleep, sys_nanosleep},
     open::sys_openat,
+    pidfd::{sys_pidfd_getfd, sys_pidfd_open, sys_pidfd_send_signal},
     pipe::sys_pipe2,
     prctl::sys_prctl,
     pread64::sys_pread64,
@@ -80,6 +87,11 @@ use crate::syscall::{
     rt_sigprocmask::sys_rt_sigprocmask,
     rt_sigsuspend::sys_rt_sigsuspend,
     sched_affinity::{sys_sched_getaffinity, sys_sched_setaffinity},
+    sched_policy::{
+        sys_sched_get_priority_max, sys_sched_get_priority_min, sys_sched_getattr,
+        sys_sched_getparam, sys_sched_getscheduler, sys_sched_rr_get_interval, sys_sched_setattr,
+        sys_sched_setparam, sys_sched_setscheduler,
+    },
     sched_yield::sys_sched_yield,
     semctl::sys_semctl,
     semget::sys_semget,
@@ -102,6 +114,7 @@ use crate::syscall::{
     setreuid::sys_setreuid,
     setsid::sys_setsid,
     setsockopt::sys_setsockopt,
+    settimeofday::sys_settimeofday,
     setuid::sys_setuid,
     shutdown::sys_shutdown,
     sigaltstack::sys_sigaltstack,
@@ -109,16 +122,19 @@ use crate::syscall::{
     socketpair::sys_socketpair,
     stat::{sys_fstat, sys_fstatat},
     statfs::{sys_fstatfs, sys_statfs},
+    swapon::{sys_swapoff, sys_swapon},
     symlink::sys_symlinkat,
     sync::sys_sync,
     tgkill::sys_tgkill,
     timer_create::{sys_timer_create, sys_timer_delete},
-    timer_settime::{sys_timer_gettime, sys_timer_settime},
+    timer_settime::{sys_timer_getoverrun, sys_timer_gettime, sys_timer_settime},
+    times::sys_times,
     truncate::{sys_ftruncate, sys_truncate},
     umask::sys_umask,
     umount::sys_umount,
     uname::sys_uname,
     unlink::sys_unlinkat,
+    userfaultfd::sys_userfaultfd,
     utimens::sys_utimensat,
     wait4::sys_wait4,
     waitid::sys_waitid,
@@ -190,10 +206,18 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_GETITIMER = 102          => sys_getitimer(args[..2]);
     SYS_SETITIMER = 103          => sys_setitimer(args[..3]);
     SYS_TIMER_CREATE = 107       => sys_timer_create(args[..3]);
+    SYS_TIMER_GETOVERRUN = 109   => sys_timer_getoverrun(args[..1]);
     SYS_TIMER_DELETE = 111       => sys_timer_delete(args[..1]);
+    SYS_SCHED_SETPARAM = 118     => sys_sched_setparam(args[..2]);
+    SYS_SCHED_SETSCHEDULER = 119 => sys_sched_setscheduler(args[..3]);
+    SYS_SCHED_GETSCHEDULER = 120 => sys_sched_getscheduler(args[..1]);
+    SYS_SCHED_GETPARAM = 121     => sys_sched_getparam(args[..2]);
     SYS_SCHED_SETAFFINITY = 122  => sys_sched_setaffinity(args[..3]);
     SYS_SCHED_GETAFFINITY = 123  => sys_sched_getaffinity(args[..3]);
     SYS_SCHED_YIELD = 124        => sys_sched_yield(args[..0]);
+    SYS_SCHED_GET_PRIORITY_MAX = 125 => sys_sched_get_priority_max(args[..1]);
+    SYS_SCHED_GET_PRIORITY_MIN = 126 => sys_sched_get_priority_min(args[..1]);
+    SYS_SCHED_RR_GET_INTERVAL = 127 => sys_sched_rr_get_interval(args[..2]);
     SYS_KILL = 129               => sys_kill(args[..2]);
     SYS_TGKILL = 131             => sys_tgkill(args[..3]);
     SYS_SIGALTSTACK = 132        => sys_sigaltstack(args[..2]);
@@ -213,6 +237,7 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_GETRESGID = 150          => sys_getresgid(args[..3]);
     SYS_SETFSUID = 151           => sys_setfsuid(args[..1]);
     SYS_SETFSGID = 152           => sys_setfsgid(args[..1]);
+    SYS_TIMES = 153              => sys_times(args[..1]);
     SYS_SETPGID = 154            => sys_setpgid(args[..2]);
     SYS_GETPGID = 155            => sys_getpgid(args[..1]);
     SYS_GETSID = 156             => sys_getsid(args[..1]);
@@ -226,6 +251,8 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_UMASK = 166              => sys_umask(args[..1]);
     SYS_PRCTL = 167              => sys_prctl(args[..5]);
     SYS_GETTIMEOFDAY = 169       => sys_gettimeofday(args[..1]);
+    SYS_SETTIMEOFDAY = 170       => sys_settimeofday(args[..2]);
+    SYS_ADJTIMEX = 171           => sys_adjtimex(args[..1]);
     SYS_GETPID = 172             => sys_getpid(args[..0]);
     SYS_GETPPID = 173            => sys_getppid(args[..0]);
     SYS_GETUID = 174             => sys_getuid(args[..0]);
@@ -253,25 +280,43 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_RECVMSG = 212            => sys_recvmsg(args[..3]);
     SYS_BRK = 214                => sys_brk(args[..1]);
     SYS_MUNMAP = 215             => sys_munmap(args[..2]);
+    SYS_MREMAP = 216             => sys_mremap(args[..5]);
     SYS_CLONE = 220              => sys_clone(args[..5], &user_ctx);
     SYS_EXECVE = 221             => sys_execve(args[..3], &mut user_ctx);
     SYS_MMAP = 222               => sys_mmap(args[..6]);
+    SYS_SWAPON = 224             => sys_swapon(args[..2]);
+    SYS_SWAPOFF = 225            => sys_swapoff(args[..1]);
     SYS_MPROTECT = 226           => sys_mprotect(args[..3]);
     SYS_MSYNC = 227              => sys_msync(args[..3]);
+    SYS_MLOCK = 228              => sys_mlock(args[..2]);
+    SYS_MUNLOCK = 229            => sys_munlock(args[..2]);
+    SYS_MLOCKALL = 230           => sys_mlockall(args[..1]);
+    SYS_MUNLOCKALL = 231         => sys_munlockall(args[..0]);
+    SYS_MINCORE = 232            => sys_mincore(args[..3]);
     SYS_MADVISE = 233            => sys_madvise(args[..3]);
     SYS_ACCEPT4 = 242            => sys_accept4(args[..4]);
     SYS_WAIT4 = 260              => sys_wait4(args[..4]);
     SYS_PRLIMIT64 = 261          => sys_prlimit64(args[..4]);
+    SYS_SCHED_SETATTR = 274      => sys_sched_setattr(args[..3]);
+    SYS_SCHED_GETATTR = 275      => sys_sched_getattr(args[..4]);
     SYS_GETRANDOM = 278          => sys_getrandom(args[..3]);
     SYS_EXECVEAT = 281           => sys_execveat(args[..5], &mut user_ctx);
+    SYS_USERFAULTFD = 282        => sys_userfaultfd(args[..1]);
+    SYS_MLOCK2 = 284             => sys_mlock2(args[..3]);
     SYS_PREADV2 = 286            => sys_preadv2(args[..5]);
     SYS_PWRITEV2 = 287           => sys_pwritev2(args[..5]);
     SYS_PRLIMIT64 = 302          => sys_prlimit64(args[..4]);
     SYS_CLOCK_GETTIME = 403      => sys_clock_gettime(args[..2]);
+    SYS_CLOCK_SETTIME = 404      => sys_clock_settime(args[..2]);
+    SYS_CLOCK_ADJTIME = 405      => sys_clock_adjtime(args[..2]);
+    SYS_CLOCK_GETRES = 406       => sys_clock_getres(args[..2]);
     SYS_CLOCK_NANOSLEEP = 407    => sys_clock_nanosleep(args[..4]);
     SYS_TIMER_GETTIME = 408      => sys_timer_gettime(args[..2]);
     SYS_TIMER_SETTIME = 409      => sys_timer_settime(args[..4]);
     SYS_UTIMENSAT = 412          => sys_utimensat(args[..4]);
     SYS_SEMTIMEDOP = 420         => sys_semtimedop(args[..4]);
+    SYS_PIDFD_SEND_SIGNAL = 424  => sys_pidfd_send_signal(args[..4]);
+    SYS_PIDFD_OPEN = 434         => sys_pidfd_open(args[..2]);
     SYS_CLONE3 = 435             => sys_clone3(args[..2], &user_ctx);
+    SYS_PIDFD_GETFD = 438        => sys_pidfd_getfd(args[..3]);
 }
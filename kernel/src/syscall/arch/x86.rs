@@ -3,6 +3,7 @@
 use crate::syscall::{
     accept::{sys_accept, sys_accept4},
     access::{sys_access, sys_faccessat},
+    adjtimex::{sys_adjtimex, sys_clock_adjtime},
     alarm::sys_alarm,
     arch_prctl::sys_arch_prctl,
     bind::sys_bind,
@@ -13,7 +14,9 @@ use crate::syscall::{
     chmod::{sys_chmod, sys_fchmod, sys_fchmodat},
     chown::{sys_chown, sys_fchown, sys_fchownat, sys_lchown},
     chroot::sys_chroot,
+    clock_getres::sys_clock_getres,
     clock_gettime::sys_clock_gettime,
+    clock_settime::sys_clock_settime,
     clone::{sys_clone, sys_clone3},
     close::sys_close,
     connect::sys_connect,
@@ -57,16 +60,20 @@ use crate::syscall::{
     listen::sys_listen,
     lseek::sys_lseek,
     madvise::sys_madvise,
+    mincore::sys_mincore,
     mkdir::{sys_mkdir, sys_mkdirat},
     mknod::{sys_mknod, sys_mknodat},
+    mlock::{sys_mlock, sys_mlock2, sys_mlockall, sys_munlock, sys_munlockall},
     mmap::sys_mmap,
     mount::sys_mount,
     mprotect::sys_mprotect,
+    mremap::sys_mremap,
     msync::sys_msync,
     munmap::sys_munmap,
     nanosleep::{sys_clock_nanosleep, sys_nanosleep},
     open::{sys_creat, sys_open, sys_openat},
     pause::sys_pause,
+    pidfd::{sys_pidfd_getfd, sys_pidfd_open, sys_pidfd_send_signal},
     pipe::{sys_pipe, sys_pipe2},
     poll::sys_poll,
     prctl::sys_prctl,
@@ -88,6 +95,11 @@ use crate::syscall::{
     rt_sigreturn::sys_rt_sigreturn,
     rt_sigsuspend::sys_rt_sigsuspend,
     sched_affinity::{sys_sched_getaffinity, sys_sched_setaffinity},
+    sched_policy::{
+        sys_sched_get_priority_max, sys_sched_get_priority_min, sys_sched_getattr,
+        sys_sched_getparam, sys_sched_getscheduler, sys_sched_rr_get_interval, sys_sched_setattr,
+        sys_sched_setparam, sys_sched_setscheduler,
+    },
     sched_yield::sys_sched_yield,
     select::sys_select,
     semctl::sys_semctl,
@@ -111,6 +123,7 @@ use crate::syscall::{
     setreuid::sys_setreuid,
     setsid::sys_setsid,
     setsockopt::sys_setsockopt,
+    settimeofday::sys_settimeofday,
     setuid::sys_setuid,
     shutdown::sys_shutdown,
     sigaltstack::sys_sigaltstack,
@@ -118,18 +131,21 @@ use crate::syscall::{
     socketpair::sys_socketpair,
     stat::{sys_fstat, sys_fstatat, sys_lstat, sys_stat},
     statfs::{sys_fstatfs, sys_statfs},
+    swapon::{sys_swapoff, sys_swapon},
     symlink::{sys_symlink, sys_symlinkat},
     sync::sys_sync,
     sysinfo::sys_sysinfo,
     tgkill::sys_tgkill,
     time::sys_time,
     timer_create::{sys_timer_create, sys_timer_delete},
-    timer_settime::{sys_timer_gettime, sys_timer_settime},
+    timer_settime::{sys_timer_getoverrun, sys_timer_gettime, sys_timer_settime},
+    times::sys_times,
     truncate::{sys_ftruncate, sys_truncate},
     umask::sys_umask,
     umount::sys_umount,
     uname::sys_uname,
     unlink::{sys_unlink, sys_unlinkat},
+    userfaultfd::sys_userfaultfd,
     utimens::{sys_futimesat, sys_utime, sys_utimensat, sys_utimes},
     wait4::sys_wait4,
     waitid::sys_waitid,
@@ -161,8 +177,10 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_ACCESS = 21            => sys_access(args[..2]);
     SYS_PIPE = 22              => sys_pipe(args[..1]);
     SYS_SELECT = 23            => sys_select(args[..5]);
+    SYS_MREMAP = 25            => sys_mremap(args[..5]);
     SYS_MSYNC = 26             => sys_msync(args[..3]);
     SYS_SCHED_YIELD = 24       => sys_sched_yield(args[..0]);
+    SYS_MINCORE = 27           => sys_mincore(args[..3]);
     SYS_MADVISE = 28           => sys_madvise(args[..3]);
     SYS_DUP = 32               => sys_dup(args[..1]);
     SYS_DUP2 = 33              => sys_dup2(args[..2]);
@@ -226,6 +244,7 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_GETRLIMIT = 97         => sys_getrlimit(args[..2]);
     SYS_GETRUSAGE = 98         => sys_getrusage(args[..2]);
     SYS_SYSINFO = 99           => sys_sysinfo(args[..1]);
+    SYS_TIMES = 100            => sys_times(args[..1]);
     SYS_GETUID = 102           => sys_getuid(args[..0]);
     SYS_GETGID = 104           => sys_getgid(args[..0]);
     SYS_SETUID = 105           => sys_setuid(args[..1]);
@@ -259,13 +278,28 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_FSTATFS = 138          => sys_fstatfs(args[..2]);
     SYS_GET_PRIORITY = 140     => sys_get_priority(args[..2]);
     SYS_SET_PRIORITY = 141     => sys_set_priority(args[..3]);
+    SYS_SCHED_SETPARAM = 142   => sys_sched_setparam(args[..2]);
+    SYS_SCHED_GETPARAM = 143   => sys_sched_getparam(args[..2]);
+    SYS_SCHED_SETSCHEDULER = 144 => sys_sched_setscheduler(args[..3]);
+    SYS_SCHED_GETSCHEDULER = 145 => sys_sched_getscheduler(args[..1]);
+    SYS_SCHED_GET_PRIORITY_MAX = 146 => sys_sched_get_priority_max(args[..1]);
+    SYS_SCHED_GET_PRIORITY_MIN = 147 => sys_sched_get_priority_min(args[..1]);
+    SYS_SCHED_RR_GET_INTERVAL = 148 => sys_sched_rr_get_interval(args[..2]);
+    SYS_MLOCK = 149            => sys_mlock(args[..2]);
+    SYS_MUNLOCK = 150          => sys_munlock(args[..2]);
+    SYS_MLOCKALL = 151         => sys_mlockall(args[..1]);
+    SYS_MUNLOCKALL = 152       => sys_munlockall(args[..0]);
     SYS_PRCTL = 157            => sys_prctl(args[..5]);
     SYS_ARCH_PRCTL = 158       => sys_arch_prctl(args[..2], &mut user_ctx);
+    SYS_ADJTIMEX = 159         => sys_adjtimex(args[..1]);
     SYS_SETRLIMIT = 160        => sys_setrlimit(args[..2]);
     SYS_CHROOT = 161           => sys_chroot(args[..1]);
     SYS_SYNC = 162             => sys_sync(args[..0]);
+    SYS_SETTIMEOFDAY = 164     => sys_settimeofday(args[..2]);
     SYS_MOUNT = 165            => sys_mount(args[..5]);
     SYS_UMOUNT2 = 166           => sys_umount(args[..2]);
+    SYS_SWAPON = 167           => sys_swapon(args[..2]);
+    SYS_SWAPOFF = 168          => sys_swapoff(args[..1]);
     SYS_GETTID = 186           => sys_gettid(args[..0]);
     SYS_TIME = 201             => sys_time(args[..1]);
     SYS_FUTEX = 202            => sys_futex(args[..6]);
@@ -278,8 +312,11 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_TIMER_CREATE = 222     => sys_timer_create(args[..3]);
     SYS_TIMER_SETTIME = 223    => sys_timer_settime(args[..4]);
     SYS_TIMER_GETTIME = 224    => sys_timer_gettime(args[..2]);
+    SYS_TIMER_GETOVERRUN = 225 => sys_timer_getoverrun(args[..1]);
     SYS_TIMER_DELETE = 226     => sys_timer_delete(args[..1]);
+    SYS_CLOCK_SETTIME = 227    => sys_clock_settime(args[..2]);
     SYS_CLOCK_GETTIME = 228    => sys_clock_gettime(args[..2]);
+    SYS_CLOCK_GETRES = 229     => sys_clock_getres(args[..2]);
     SYS_CLOCK_NANOSLEEP = 230  => sys_clock_nanosleep(args[..4]);
     SYS_EXIT_GROUP = 231       => sys_exit_group(args[..1]);
     SYS_EPOLL_WAIT = 232       => sys_epoll_wait(args[..4]);
@@ -314,9 +351,17 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_PREADV = 295           => sys_preadv(args[..4]);
     SYS_PWRITEV = 296          => sys_pwritev(args[..4]);
     SYS_PRLIMIT64 = 302        => sys_prlimit64(args[..4]);
+    SYS_CLOCK_ADJTIME = 305    => sys_clock_adjtime(args[..2]);
+    SYS_SCHED_SETATTR = 314    => sys_sched_setattr(args[..3]);
+    SYS_SCHED_GETATTR = 315    => sys_sched_getattr(args[..4]);
     SYS_GETRANDOM = 318        => sys_getrandom(args[..3]);
     SYS_EXECVEAT = 322         => sys_execveat(args[..5], &mut user_ctx);
+    SYS_USERFAULTFD = 323      => sys_userfaultfd(args[..1]);
+    SYS_MLOCK2 = 325           => sys_mlock2(args[..3]);
     SYS_PREADV2 = 327          => sys_preadv2(args[..5]);
     SYS_PWRITEV2 = 328         => sys_pwritev2(args[..5]);
+    SYS_PIDFD_SEND_SIGNAL = 424 => sys_pidfd_send_signal(args[..4]);
+    SYS_PIDFD_OPEN = 434       => sys_pidfd_open(args[..2]);
     SYS_CLONE3 = 435           => sys_clone3(args[..2], &user_ctx);
+    SYS_PIDFD_GETFD = 438      => sys_pidfd_getfd(args[..3]);
 }
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use ostd::arch::timer::TIMER_FREQ;
+
+use super::{
+    clock_gettime::{DynamicClockIdInfo, DynamicClockType},
+    SyscallReturn,
+};
+use crate::{
+    prelude::*,
+    process::{posix_thread::thread_table, process_table},
+    syscall::ClockId,
+    time::{clockid_t, timespec_t},
+};
+
+pub fn sys_clock_getres(
+    clockid: clockid_t,
+    timespec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("clockid = {:?}", clockid);
+
+    let resolution = clock_resolution(clockid)?;
+
+    if timespec_addr != 0 {
+        let timespec = timespec_t::from(resolution);
+        ctx.user_space().write_val(timespec_addr, &timespec)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Returns the resolution of a clock specified by the input clock ID.
+///
+/// The clocks read from the clocksource have a resolution of one nanosecond,
+/// while the clocks updated in each tick have a resolution of one tick.
+fn clock_resolution(clockid: clockid_t) -> Result<Duration> {
+    let tick = Duration::from_nanos(1_000_000_000 / TIMER_FREQ);
+
+    if clockid >= 0 {
+        let clock_id = ClockId::try_from(clockid)?;
+        let resolution = match clock_id {
+            ClockId::CLOCK_REALTIME_COARSE | ClockId::CLOCK_MONOTONIC_COARSE => tick,
+            _ => Duration::from_nanos(1),
+        };
+        return Ok(resolution);
+    }
+
+    let (clock_type, is_valid) = match DynamicClockIdInfo::try_from(clockid)? {
+        DynamicClockIdInfo::Pid(pid, clock_type) => {
+            (clock_type, process_table::get_process(pid).is_some())
+        }
+        DynamicClockIdInfo::Tid(tid, clock_type) => {
+            (clock_type, thread_table::get_thread(tid).is_some())
+        }
+        DynamicClockIdInfo::Fd(_) => {
+            return_errno_with_message!(Errno::EINVAL, "the fd clocks are not supported")
+        }
+    };
+    if !is_valid {
+        return_errno_with_message!(Errno::EINVAL, "invalid clock ID");
+    }
+
+    // The profiling and virtual clocks are accounted in ticks.
+    let resolution = match clock_type {
+        DynamicClockType::Scheduling => Duration::from_nanos(1),
+        _ => tick,
+    };
+    Ok(resolution)
+}
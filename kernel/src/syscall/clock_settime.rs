@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::credentials::capabilities::CapSet,
+    syscall::ClockId,
+    time::{clockid_t, timekeeping, timespec_t},
+};
+
+pub fn sys_clock_settime(
+    clockid: clockid_t,
+    timespec_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("clockid = {:?}", clockid);
+
+    // Only `CLOCK_REALTIME` is settable. Other clocks (including the CPU-time
+    // clocks) cannot be set, as in Linux.
+    if ClockId::try_from(clockid)? != ClockId::CLOCK_REALTIME {
+        return_errno_with_message!(Errno::EINVAL, "the clock cannot be set");
+    }
+
+    let timespec = ctx.user_space().read_val::<timespec_t>(timespec_addr)?;
+    let time = Duration::try_from(timespec)?;
+
+    check_sys_time_capability(ctx)?;
+    timekeeping::set_realtime(time)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Checks whether the current thread can change the system time.
+pub(super) fn check_sys_time_capability(ctx: &Context) -> Result<()> {
+    let credentials = ctx.posix_thread.credentials();
+    if !credentials.effective_capset().contains(CapSet::SYS_TIME) {
+        return_errno_with_message!(Errno::EPERM, "setting the time requires CAP_SYS_TIME");
+    }
+    Ok(())
+}
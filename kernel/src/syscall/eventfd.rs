@@ -35,7 +35,7 @@ use crate::{
 pub fn sys_eventfd(init_val: u64, ctx: &Context) -> Result<SyscallReturn> {
     debug!("init_val = 0x{:x}", init_val);
 
-    let fd = do_sys_eventfd2(init_val, Flags::empty(), ctx);
+    let fd = do_sys_eventfd2(init_val, Flags::empty(), ctx)?;
 
     Ok(SyscallReturn::Return(fd as _))
 }
@@ -46,12 +46,12 @@ pub fn sys_eventfd2(init_val: u64, flags: u32, ctx: &Context) -> Result<SyscallR
         .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
     debug!("init_val = 0x{:x}, flags = {:?}", init_val, flags);
 
-    let fd = do_sys_eventfd2(init_val, flags, ctx);
+    let fd = do_sys_eventfd2(init_val, flags, ctx)?;
 
     Ok(SyscallReturn::Return(fd as _))
 }
 
-fn do_sys_eventfd2(init_val: u64, flags: Flags, ctx: &Context) -> FileDesc {
+fn do_sys_eventfd2(init_val: u64, flags: Flags, ctx: &Context) -> Result<FileDesc> {
     let event_file = EventFile::new(init_val, flags);
     let fd = {
         let file_table = ctx.thread_local.file_table().borrow();
@@ -61,9 +61,9 @@ fn do_sys_eventfd2(init_val: u64, flags: Flags, ctx: &Context) -> FileDesc {
         } else {
             FdFlags::empty()
         };
-        file_table_locked.insert(Arc::new(event_file), fd_flags)
+        file_table_locked.insert(Arc::new(event_file), fd_flags)?
     };
-    fd
+    Ok(fd)
 }
 
 bitflags! {
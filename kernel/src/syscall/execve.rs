@@ -11,7 +11,7 @@ use crate::{
     fs::{
         file_table::{get_file_fast, FileDesc},
         fs_resolver::{FsPath, AT_FDCWD},
-        path::Dentry,
+        path::{Dentry, PerMountFlags},
     },
     prelude::*,
     process::{
@@ -133,6 +133,11 @@ fn do_execve(
     set_uid_from_elf(process, &credentials, &elf_file)?;
     set_gid_from_elf(process, &credentials, &elf_file)?;
     credentials.set_keep_capabilities(false);
+    // A process that gains privileges from a set-user-ID or set-group-ID program
+    // cannot be dumped, since the core dump may leak the privileged data.
+    process.set_dumpable(
+        credentials.euid() == credentials.ruid() && credentials.egid() == credentials.rgid(),
+    );
 
     // set executable path
     process.set_executable_path(new_executable_path);
@@ -207,7 +212,7 @@ fn set_uid_from_elf(
     credentials: &Credentials<WriteOp>,
     elf_file: &Dentry,
 ) -> Result<()> {
-    if elf_file.mode()?.has_set_uid() {
+    if elf_file.mode()?.has_set_uid() && !is_nosuid_mount(elf_file) {
         let uid = elf_file.owner()?;
         credentials.set_euid(uid);
 
@@ -225,7 +230,7 @@ fn set_gid_from_elf(
     credentials: &Credentials<WriteOp>,
     elf_file: &Dentry,
 ) -> Result<()> {
-    if elf_file.mode()?.has_set_gid() {
+    if elf_file.mode()?.has_set_gid() && !is_nosuid_mount(elf_file) {
         let gid = elf_file.group()?;
         credentials.set_egid(gid);
 
@@ -236,3 +241,12 @@ fn set_gid_from_elf(
     credentials.reset_sgid();
     Ok(())
 }
+
+/// Returns whether the set-user-ID and set-group-ID bits of the file are ignored
+/// because of the mount.
+fn is_nosuid_mount(elf_file: &Dentry) -> bool {
+    elf_file
+        .mount_node()
+        .flags()
+        .contains(PerMountFlags::NOSUID)
+}
@@ -10,7 +10,7 @@ use crate::{
         },
     },
     prelude::*,
-    process::{process_table, Pid},
+    process::{process_table, Pid, ResourceType},
 };
 
 pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64, ctx: &Context) -> Result<SyscallReturn> {
@@ -35,6 +35,16 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64, ctx: &Context) -> Result<Sysc
 }
 
 fn handle_dupfd(fd: FileDesc, arg: u64, flags: FdFlags, ctx: &Context) -> Result<SyscallReturn> {
+    let max_fd_count = ctx
+        .process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_NOFILE)
+        .get_cur();
+    if arg >= max_fd_count {
+        return_errno_with_message!(Errno::EINVAL, "the file descriptor exceeds RLIMIT_NOFILE");
+    }
+
     let file_table = ctx.thread_local.file_table().borrow();
     let new_fd = file_table.write().dup(fd, arg as FileDesc, flags)?;
     Ok(SyscallReturn::Return(new_fd as _))
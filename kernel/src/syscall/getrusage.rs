@@ -3,7 +3,7 @@
 use int_to_c_enum::TryFromInt;
 
 use super::SyscallReturn;
-use crate::{prelude::*, time::timeval_t};
+use crate::{prelude::*, process::ResourceUsage, time::timeval_t};
 
 #[derive(Debug, Copy, Clone, TryFromInt, PartialEq)]
 #[repr(i32)]
@@ -23,32 +23,27 @@ pub fn sys_getrusage(target: i32, rusage_addr: Vaddr, ctx: &Context) -> Result<S
     );
 
     if rusage_addr != 0 {
-        let rusage = match rusage_target {
-            RusageTarget::ForSelf => {
-                let process = ctx.process;
-                rusage_t {
-                    ru_utime: process.prof_clock().user_clock().read_time().into(),
-                    ru_stime: process.prof_clock().kernel_clock().read_time().into(),
-                    ..Default::default()
-                }
+        let usage = match rusage_target {
+            RusageTarget::ForSelf => ctx.process.resource_usage(),
+            RusageTarget::Children => ctx.process.children_resource_usage(),
+            // Linux only uses `RUSAGE_BOTH` internally, e.g., for `wait4`.
+            RusageTarget::Both => {
+                return_errno_with_message!(Errno::EINVAL, "RUSAGE_BOTH is not allowed")
             }
             RusageTarget::Thread => {
                 let posix_thread = ctx.posix_thread;
-                rusage_t {
-                    ru_utime: posix_thread.prof_clock().user_clock().read_time().into(),
-                    ru_stime: posix_thread.prof_clock().kernel_clock().read_time().into(),
-                    ..Default::default()
+                ResourceUsage {
+                    utime: posix_thread.prof_clock().user_clock().read_time(),
+                    stime: posix_thread.prof_clock().kernel_clock().read_time(),
+                    // The peak resident set size is a per-process value, as in Linux.
+                    maxrss: ctx.process.vm().rss_peak(),
+                    ..posix_thread.rusage().snapshot()
                 }
             }
-            // To support `Children` and `Both` we need to implement the functionality to
-            // accumulate the resources of a child process back to the parent process
-            // upon the child's termination.
-            _ => {
-                return_errno_with_message!(Errno::EINVAL, "the target type is not supported")
-            }
         };
 
-        ctx.user_space().write_val(rusage_addr, &rusage)?;
+        ctx.user_space()
+            .write_val(rusage_addr, &rusage_t::from(usage))?;
     }
 
     Ok(SyscallReturn::Return(0))
@@ -90,3 +85,21 @@ pub struct rusage_t {
     /// involuntary
     pub ru_nivcsw: u64,
 }
+
+impl From<ResourceUsage> for rusage_t {
+    fn from(usage: ResourceUsage) -> Self {
+        Self {
+            ru_utime: usage.utime.into(),
+            ru_stime: usage.stime.into(),
+            // The peak resident set size is reported in kilobytes.
+            ru_maxrss: (usage.maxrss / 1024) as u64,
+            ru_minflt: usage.minflt,
+            ru_majflt: usage.majflt,
+            ru_inblock: usage.inblock,
+            ru_oublock: usage.oublock,
+            ru_nvcsw: usage.nvcsw,
+            ru_nivcsw: usage.nivcsw,
+            ..Default::default()
+        }
+    }
+}
@@ -1,9 +1,18 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::ops::Range;
+
 use align_ext::AlignExt;
+use aster_rights::Full;
 
 use super::SyscallReturn;
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    vm::{
+        perms::VmPerms,
+        vmar::{vm_mapping::VmMappingFlags, Vmar},
+    },
+};
 
 pub fn sys_madvise(
     start: Vaddr,
@@ -32,29 +41,118 @@ pub fn sys_madvise(
         Errno::EINVAL,
         "integer overflow when (start + len)",
     ))?;
+    let range = start..end;
+
+    let root_vmar = ctx.process.root_vmar();
     match behavior {
-        MadviseBehavior::MADV_NORMAL
-        | MadviseBehavior::MADV_SEQUENTIAL
-        | MadviseBehavior::MADV_WILLNEED => {
-            // perform a read at first
-            let mut buffer = vec![0u8; len];
-            ctx.user_space()
-                .read_bytes(start, &mut VmWriter::from(buffer.as_mut_slice()))?;
-        }
+        MadviseBehavior::MADV_NORMAL => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::empty(),
+            VmMappingFlags::SEQ_READ | VmMappingFlags::RAND_READ,
+        )?,
+        MadviseBehavior::MADV_SEQUENTIAL => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::SEQ_READ,
+            VmMappingFlags::RAND_READ,
+        )?,
+        MadviseBehavior::MADV_RANDOM => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::RAND_READ,
+            VmMappingFlags::SEQ_READ,
+        )?,
+        MadviseBehavior::MADV_WILLNEED => root_vmar.prefetch_pages(range)?,
         MadviseBehavior::MADV_DONTNEED => {
-            warn!("MADV_DONTNEED isn't implemented, do nothing for now.");
+            check_not_locked(&root_vmar, &range)?;
+            root_vmar.discard_pages(range, false)?
+        }
+        MadviseBehavior::MADV_DONTNEED_LOCKED => root_vmar.discard_pages(range, false)?,
+        // The pages are freed immediately rather than on memory pressure,
+        // which is allowed since the content of the pages becomes undefined.
+        MadviseBehavior::MADV_FREE => {
+            check_not_locked(&root_vmar, &range)?;
+            root_vmar.discard_pages(range, true)?
+        }
+        MadviseBehavior::MADV_REMOVE => root_vmar.remove_pages(range)?,
+        MadviseBehavior::MADV_DONTFORK => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::DONT_FORK,
+            VmMappingFlags::empty(),
+        )?,
+        MadviseBehavior::MADV_DOFORK => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::empty(),
+            VmMappingFlags::DONT_FORK,
+        )?,
+        MadviseBehavior::MADV_WIPEONFORK => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::WIPE_ON_FORK,
+            VmMappingFlags::empty(),
+        )?,
+        MadviseBehavior::MADV_KEEPONFORK => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::empty(),
+            VmMappingFlags::WIPE_ON_FORK,
+        )?,
+        MadviseBehavior::MADV_DONTDUMP => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::DONT_DUMP,
+            VmMappingFlags::empty(),
+        )?,
+        MadviseBehavior::MADV_DODUMP => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::empty(),
+            VmMappingFlags::DONT_DUMP,
+        )?,
+        MadviseBehavior::MADV_HUGEPAGE => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::HUGEPAGE,
+            VmMappingFlags::NO_HUGEPAGE,
+        )?,
+        MadviseBehavior::MADV_NOHUGEPAGE => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::NO_HUGEPAGE,
+            VmMappingFlags::HUGEPAGE,
+        )?,
+        MadviseBehavior::MADV_MERGEABLE => root_vmar.update_mapping_flags(
+            range,
+            VmMappingFlags::MERGEABLE,
+            VmMappingFlags::empty(),
+        )?,
+        MadviseBehavior::MADV_UNMERGEABLE => {
+            root_vmar.update_mapping_flags(
+                range.clone(),
+                VmMappingFlags::empty(),
+                VmMappingFlags::MERGEABLE,
+            )?;
+            root_vmar.unmerge_pages(range)?
+        }
+        // Only the anonymous pages are reclaimed by swapping them out. The
+        // pages of the page caches are left to the page cache reclaimer.
+        MadviseBehavior::MADV_COLD => {
+            check_not_locked(&root_vmar, &range)?;
+            root_vmar.deactivate_pages(range)?
+        }
+        MadviseBehavior::MADV_PAGEOUT => {
+            check_not_locked(&root_vmar, &range)?;
+            root_vmar.page_out_pages(range)?
+        }
+        MadviseBehavior::MADV_POPULATE_READ => root_vmar.populate_pages(range, VmPerms::READ)?,
+        MadviseBehavior::MADV_POPULATE_WRITE => {
+            root_vmar.populate_pages(range, VmPerms::READ | VmPerms::WRITE)?
+        }
+        MadviseBehavior::MADV_HWPOISON | MadviseBehavior::MADV_SOFT_OFFLINE => {
+            return_errno_with_message!(Errno::EINVAL, "memory failure injection is not supported")
         }
-        MadviseBehavior::MADV_FREE => madv_free(start, end, ctx)?,
-        _ => todo!(),
     }
     Ok(SyscallReturn::Return(0))
 }
 
-fn madv_free(start: Vaddr, end: Vaddr, ctx: &Context) -> Result<()> {
-    let root_vmar = ctx.process.root_vmar();
-    let advised_range = start..end;
-    let _ = root_vmar.remove_mapping(advised_range);
-
+/// Checks that the range does not contain locked pages, which cannot be
+/// discarded except with `MADV_DONTNEED_LOCKED`.
+fn check_not_locked(root_vmar: &Vmar<Full>, range: &Range<Vaddr>) -> Result<()> {
+    if root_vmar.locked_vm_in(range.clone()) > 0 {
+        return_errno_with_message!(Errno::EINVAL, "the range contains locked pages");
+    }
     Ok(())
 }
 
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::prelude::*;
+
+pub fn sys_mincore(start: Vaddr, len: usize, vec: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!(
+        "start = 0x{:x}, len = 0x{:x}, vec = 0x{:x}",
+        start, len, vec
+    );
+
+    if start % PAGE_SIZE != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the start address should be page aligned");
+    }
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+    let end = start
+        .checked_add(len)
+        .filter(|end| *end <= isize::MAX as usize)
+        .ok_or(Error::with_message(
+            Errno::ENOMEM,
+            "the range is not in the user space",
+        ))?;
+
+    let residency = ctx
+        .process
+        .root_vmar()
+        .page_residency(start..end.align_up(PAGE_SIZE))?;
+    // The least significant bit of each byte tells whether the page is
+    // resident, and the other bits are reserved.
+    let bytes = residency
+        .into_iter()
+        .map(|is_resident| is_resident as u8)
+        .collect::<Vec<_>>();
+    ctx.user_space()
+        .write_bytes(vec, &mut VmReader::from(bytes.as_slice()))?;
+
+    Ok(SyscallReturn::Return(0))
+}
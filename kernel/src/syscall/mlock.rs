@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*, process::rlimit::check_locked_memory, vm::vmar::vm_mapping::VmMappingFlags,
+};
+
+pub fn sys_mlock(start: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("start = 0x{:x}, len = 0x{:x}", start, len);
+
+    do_mlock(start, len, false, ctx)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mlock2(start: Vaddr, len: usize, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = Mlock2Flags::from_bits(flags)
+        .ok_or(Error::with_message(Errno::EINVAL, "unknown mlock2 flags"))?;
+    debug!(
+        "start = 0x{:x}, len = 0x{:x}, flags = {:?}",
+        start, len, flags
+    );
+
+    do_mlock(start, len, flags.contains(Mlock2Flags::MLOCK_ONFAULT), ctx)?;
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_munlock(start: Vaddr, len: usize, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("start = 0x{:x}, len = 0x{:x}", start, len);
+
+    let range = page_range(start, len)?;
+    if !range.is_empty() {
+        ctx.process.root_vmar().unlock_pages(range)?;
+    }
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mlockall(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = MlockallFlags::from_bits(flags)
+        .ok_or(Error::with_message(Errno::EINVAL, "unknown mlockall flags"))?;
+    debug!("flags = {:?}", flags);
+
+    if !flags.intersects(MlockallFlags::MCL_CURRENT | MlockallFlags::MCL_FUTURE) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "either MCL_CURRENT or MCL_FUTURE should be specified"
+        );
+    }
+    let on_fault = flags.contains(MlockallFlags::MCL_ONFAULT);
+
+    let root_vmar = ctx.process.root_vmar();
+    let locked = if flags.contains(MlockallFlags::MCL_CURRENT) {
+        root_vmar.total_vm()
+    } else {
+        0
+    };
+    check_locked_memory(ctx, locked)?;
+
+    let future_flags = if !flags.contains(MlockallFlags::MCL_FUTURE) {
+        VmMappingFlags::empty()
+    } else if on_fault {
+        VmMappingFlags::LOCKED | VmMappingFlags::LOCKONFAULT
+    } else {
+        VmMappingFlags::LOCKED
+    };
+    root_vmar.set_default_mapping_flags(future_flags);
+
+    if flags.contains(MlockallFlags::MCL_CURRENT) {
+        let all_pages = root_vmar.base()..root_vmar.base() + root_vmar.size();
+        ignore_holes(root_vmar.lock_pages(all_pages, on_fault))?;
+    }
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_munlockall(ctx: &Context) -> Result<SyscallReturn> {
+    let root_vmar = ctx.process.root_vmar();
+    root_vmar.set_default_mapping_flags(VmMappingFlags::empty());
+
+    let all_pages = root_vmar.base()..root_vmar.base() + root_vmar.size();
+    ignore_holes(root_vmar.unlock_pages(all_pages))?;
+    Ok(SyscallReturn::Return(0))
+}
+
+fn do_mlock(start: Vaddr, len: usize, on_fault: bool, ctx: &Context) -> Result<()> {
+    let range = page_range(start, len)?;
+    if range.is_empty() {
+        return Ok(());
+    }
+
+    // The pages that are already locked are not counted twice.
+    let root_vmar = ctx.process.root_vmar();
+    let locked = root_vmar.locked_vm() + range.len() - root_vmar.locked_vm_in(range.clone());
+    check_locked_memory(ctx, locked)?;
+
+    root_vmar.lock_pages(range, on_fault)
+}
+
+/// Returns the range of the pages that contain the `len` bytes from `start`.
+fn page_range(start: Vaddr, len: usize) -> Result<Range<Vaddr>> {
+    let end = start.checked_add(len).ok_or(Error::with_message(
+        Errno::ENOMEM,
+        "integer overflow when (start + len)",
+    ))?;
+    if end > isize::MAX as usize {
+        return_errno_with_message!(Errno::ENOMEM, "the range is not in the user space");
+    }
+
+    Ok(start.align_down(PAGE_SIZE)..end.align_up(PAGE_SIZE))
+}
+
+/// Ignores the error caused by the holes between the mappings, which is
+/// expected when operating on the whole address space.
+fn ignore_holes(result: Result<()>) -> Result<()> {
+    match result {
+        Err(err) if err.error() == Errno::ENOMEM => Ok(()),
+        result => result,
+    }
+}
+
+bitflags! {
+    struct Mlock2Flags: u32 {
+        const MLOCK_ONFAULT = 1 << 0;
+    }
+}
+
+bitflags! {
+    struct MlockallFlags: u32 {
+        const MCL_CURRENT = 1 << 0;
+        const MCL_FUTURE  = 1 << 1;
+        const MCL_ONFAULT = 1 << 2;
+    }
+}
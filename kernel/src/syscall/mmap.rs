@@ -10,11 +10,13 @@ use crate::{
     fs::{
         file_handle::FileLike,
         file_table::{get_file_fast, FileDesc},
+        path::PerMountFlags,
     },
     prelude::*,
+    process::rlimit::{check_address_space_growth, check_locked_memory},
     vm::{
         perms::VmPerms,
-        vmar::is_userspace_vaddr,
+        vmar::{is_userspace_vaddr, vm_mapping::VmMappingFlags},
         vmo::{VmoOptions, VmoRightsOp},
     },
 };
@@ -92,6 +94,24 @@ fn do_sys_mmap(
     };
 
     let root_vmar = ctx.process.root_vmar();
+
+    // Mappings overwritten by `MAP_FIXED` do not count toward the growth.
+    let growth = if option.flags.contains(MMapFlags::MAP_FIXED) {
+        len - root_vmar.total_vm_in(addr..addr + len)
+    } else {
+        len
+    };
+    check_address_space_growth(&ctx.process, growth)?;
+
+    if !vm_perms.is_empty()
+        && root_vmar
+            .default_mapping_flags()
+            .contains(VmMappingFlags::LOCKED)
+    {
+        // The new mapping is locked because of `mlockall(MCL_FUTURE)`.
+        check_locked_memory(ctx, root_vmar.locked_vm() + len)
+            .map_err(|_| Error::with_message(Errno::EAGAIN, "the mapping cannot be locked"))?;
+    }
     let vm_map_options = {
         let mut options = root_vmar.new_map(len, vm_perms)?;
         let flags = option.flags;
@@ -117,13 +137,14 @@ fn do_sys_mmap(
             // Anonymous shared mapping should share the same memory pages.
             if option.typ() == MMapType::Shared {
                 let shared_vmo = {
-                    let vmo_options: VmoOptions<Rights> = VmoOptions::new(len);
+                    let vmo_options: VmoOptions<Rights> =
+                        VmoOptions::new(len).charge_to(ctx.process.cgroup());
                     vmo_options.alloc()?
                 };
                 options = options.vmo(shared_vmo);
             }
         } else {
-            let vmo = {
+            let (vmo, dentry) = {
                 let mut file_table = ctx.thread_local.file_table().borrow_mut();
                 let file = get_file_fast!(&mut file_table, fd);
                 let inode_handle = file.as_inode_or_err()?;
@@ -139,18 +160,30 @@ fn do_sys_mmap(
                     return_errno!(Errno::EACCES);
                 }
 
-                let inode = inode_handle.dentry().inode();
-                inode
+                let dentry = inode_handle.dentry();
+                if vm_perms.contains(VmPerms::EXEC)
+                    && dentry.mount_node().flags().contains(PerMountFlags::NOEXEC)
+                {
+                    return_errno_with_message!(
+                        Errno::EPERM,
+                        "the mount disallows program execution"
+                    );
+                }
+
+                let vmo = dentry
+                    .inode()
                     .page_cache()
                     .ok_or(Error::with_message(
                         Errno::EBADF,
                         "File does not have page cache",
                     ))?
-                    .to_dyn()
+                    .to_dyn();
+                (vmo, dentry.clone())
             };
 
             options = options
                 .vmo(vmo)
+                .mapped_file(dentry)
                 .vmo_offset(offset)
                 .handle_page_faults_around();
         }
@@ -9,6 +9,7 @@ use crate::{context::Context, cpu::LinuxAbi, prelude::*};
 
 mod accept;
 mod access;
+mod adjtimex;
 mod alarm;
 mod arch;
 mod arch_prctl;
@@ -20,7 +21,9 @@ mod chdir;
 mod chmod;
 mod chown;
 mod chroot;
+mod clock_getres;
 mod clock_gettime;
+mod clock_settime;
 mod clone;
 mod close;
 mod connect;
@@ -64,16 +67,20 @@ mod link;
 mod listen;
 mod lseek;
 mod madvise;
+mod mincore;
 mod mkdir;
 mod mknod;
+mod mlock;
 mod mmap;
 mod mount;
 mod mprotect;
+mod mremap;
 mod msync;
 mod munmap;
 mod nanosleep;
 mod open;
 mod pause;
+mod pidfd;
 mod pipe;
 mod poll;
 mod prctl;
@@ -95,6 +102,7 @@ mod rt_sigprocmask;
 mod rt_sigreturn;
 mod rt_sigsuspend;
 mod sched_affinity;
+mod sched_policy;
 mod sched_yield;
 mod select;
 mod semctl;
@@ -118,6 +126,7 @@ mod setresuid;
 mod setreuid;
 mod setsid;
 mod setsockopt;
+mod settimeofday;
 mod setuid;
 mod shutdown;
 mod sigaltstack;
@@ -125,6 +134,7 @@ mod socket;
 mod socketpair;
 mod stat;
 mod statfs;
+mod swapon;
 mod symlink;
 mod sync;
 mod sysinfo;
@@ -132,11 +142,13 @@ mod tgkill;
 mod time;
 mod timer_create;
 mod timer_settime;
+mod times;
 mod truncate;
 mod umask;
 mod umount;
 mod uname;
 mod unlink;
+mod userfaultfd;
 mod utimens;
 mod wait4;
 mod waitid;
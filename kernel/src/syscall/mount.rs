@@ -3,11 +3,14 @@
 use super::SyscallReturn;
 use crate::{
     fs::{
+        cgroupfs::CgroupFS,
         exfat::{ExfatFS, ExfatMountOptions},
         ext2::Ext2,
         fs_resolver::{FsPath, AT_FDCWD},
-        path::Dentry,
+        path::{Dentry, MountPropagation, PerMountFlags},
+        ramfs::{RamFS, TmpfsMountOptions},
         utils::{FileSystem, InodeType},
+        vfat::{VfatFS, VfatMountOptions},
     },
     prelude::*,
     syscall::constants::MAX_FILENAME_LEN,
@@ -15,8 +18,7 @@ use crate::{
 
 /// The `data` argument is interpreted by the different filesystems.
 /// Typically it is a string of comma-separated options understood by
-/// this filesystem. The current implementation only considers the case
-/// where it is `NULL`. Because it should be interpreted by the specific filesystems.
+/// this filesystem, e.g., `uid=1000,umask=022` for exfat.
 pub fn sys_mount(
     devname_addr: Vaddr,
     dirname_addr: Vaddr,
@@ -29,8 +31,14 @@ pub fn sys_mount(
     let devname = user_space.read_cstring(devname_addr, MAX_FILENAME_LEN)?;
     let dirname = user_space.read_cstring(dirname_addr, MAX_FILENAME_LEN)?;
     let mount_flags = MountFlags::from_bits_truncate(flags as u32);
+    let data = if data == 0 {
+        None
+    } else {
+        let data = user_space.read_cstring(data, PAGE_SIZE)?;
+        Some(data.to_string_lossy().into_owned())
+    };
     debug!(
-        "devname = {:?}, dirname = {:?}, fstype = 0x{:x}, flags = {:?}, data = 0x{:x}",
+        "devname = {:?}, dirname = {:?}, fstype = 0x{:x}, flags = {:?}, data = {:?}",
         devname, dirname, fstype_addr, mount_flags, data,
     );
 
@@ -44,9 +52,9 @@ pub fn sys_mount(
     };
 
     if mount_flags.contains(MountFlags::MS_REMOUNT) && mount_flags.contains(MountFlags::MS_BIND) {
-        do_reconfigure_mnt()?;
+        do_reconfigure_mnt(dst_dentry, mount_flags)?;
     } else if mount_flags.contains(MountFlags::MS_REMOUNT) {
-        do_remount()?;
+        do_remount(dst_dentry, mount_flags, data.as_deref())?;
     } else if mount_flags.contains(MountFlags::MS_BIND) {
         do_bind_mount(
             devname,
@@ -59,22 +67,53 @@ pub fn sys_mount(
         | mount_flags.contains(MountFlags::MS_SLAVE)
         | mount_flags.contains(MountFlags::MS_UNBINDABLE)
     {
-        do_change_type()?;
+        do_change_type(dst_dentry, mount_flags)?;
     } else if mount_flags.contains(MountFlags::MS_MOVE) {
         do_move_mount_old(devname, dst_dentry, ctx)?;
     } else {
-        do_new_mount(devname, fstype_addr, dst_dentry, ctx)?;
+        do_new_mount(
+            devname,
+            fstype_addr,
+            dst_dentry,
+            mount_flags,
+            data.as_deref(),
+            ctx,
+        )?;
     }
 
     Ok(SyscallReturn::Return(0))
 }
 
-fn do_reconfigure_mnt() -> Result<()> {
-    return_errno_with_message!(Errno::EINVAL, "do_reconfigure_mnt is not supported");
+/// Changes the per-mount flags of a mount without touching the filesystem.
+///
+/// Such as use user command `mount -o remount,bind,ro dst`.
+fn do_reconfigure_mnt(target_dentry: Dentry, flags: MountFlags) -> Result<()> {
+    if !target_dentry.is_mount_root() {
+        return_errno_with_message!(Errno::EINVAL, "the target is not a mount");
+    }
+
+    target_dentry
+        .mount_node()
+        .set_flags(flags.per_mount_flags());
+    Ok(())
 }
 
-fn do_remount() -> Result<()> {
-    return_errno_with_message!(Errno::EINVAL, "do_remount is not supported");
+/// Changes the flags and the options of a mounted filesystem.
+///
+/// Such as use user command `mount -o remount,ro dst`.
+fn do_remount(target_dentry: Dentry, flags: MountFlags, data: Option<&str>) -> Result<()> {
+    if !target_dentry.is_mount_root() {
+        return_errno_with_message!(Errno::EINVAL, "the target is not a mount");
+    }
+
+    let mount_node = target_dentry.mount_node();
+    if let Some(data) = data
+        && !data.is_empty()
+    {
+        mount_node.fs().reconfigure(data)?;
+    }
+    mount_node.set_flags(flags.per_mount_flags());
+    Ok(())
 }
 
 /// Bind a mount to a dst location.
@@ -104,8 +143,36 @@ fn do_bind_mount(
     Ok(())
 }
 
-fn do_change_type() -> Result<()> {
-    return_errno_with_message!(Errno::EINVAL, "do_change_type is not supported");
+/// Changes the propagation type of a mount.
+///
+/// If `MS_REC` is set, the propagation types of all the mounts
+/// in the mount tree are changed, such as use user command `mount --make-rshared dst`.
+fn do_change_type(target_dentry: Dentry, flags: MountFlags) -> Result<()> {
+    if !target_dentry.is_mount_root() {
+        return_errno_with_message!(Errno::EINVAL, "the target is not a mount");
+    }
+
+    let type_flags = flags
+        & (MountFlags::MS_SHARED
+            | MountFlags::MS_PRIVATE
+            | MountFlags::MS_SLAVE
+            | MountFlags::MS_UNBINDABLE);
+    let propagation = if type_flags == MountFlags::MS_SHARED {
+        MountPropagation::Shared
+    } else if type_flags == MountFlags::MS_PRIVATE {
+        MountPropagation::Private
+    } else if type_flags == MountFlags::MS_SLAVE {
+        MountPropagation::Slave
+    } else if type_flags == MountFlags::MS_UNBINDABLE {
+        MountPropagation::Unbindable
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "only one propagation type can be set");
+    };
+
+    target_dentry
+        .mount_node()
+        .set_propagation(propagation, flags.contains(MountFlags::MS_REC));
+    Ok(())
 }
 
 /// Move a mount from src location to dst location.
@@ -136,6 +203,8 @@ fn do_new_mount(
     devname: CString,
     fs_type: Vaddr,
     target_dentry: Dentry,
+    flags: MountFlags,
+    data: Option<&str>,
     ctx: &Context,
 ) -> Result<()> {
     if target_dentry.type_() != InodeType::Dir {
@@ -146,28 +215,45 @@ fn do_new_mount(
     if fs_type.is_empty() {
         return_errno_with_message!(Errno::EINVAL, "fs_type is empty");
     }
-    let fs = get_fs(fs_type, devname)?;
-    target_dentry.mount(fs)?;
+    let fs = get_fs(fs_type, devname, data.unwrap_or(""))?;
+    target_dentry.mount(fs, flags.per_mount_flags())?;
     Ok(())
 }
 
 /// Get the filesystem by fs_type and devname.
-fn get_fs(fs_type: CString, devname: CString) -> Result<Arc<dyn FileSystem>> {
+///
+/// The `data` is the filesystem-specific options.
+fn get_fs(fs_type: CString, devname: CString, data: &str) -> Result<Arc<dyn FileSystem>> {
+    let fs_type = fs_type.to_str().unwrap();
+    // The pseudo filesystems do not need a device.
+    match fs_type {
+        "ramfs" => return Ok(RamFS::new()),
+        "tmpfs" => return Ok(RamFS::new_tmpfs(&TmpfsMountOptions::parse(data)?)),
+        "cgroup2" => return Ok(CgroupFS::singleton().clone()),
+        _ => {}
+    }
+
     let devname = devname.to_str().unwrap();
     let device = match aster_block::get_device(devname) {
         Some(device) => device,
         None => return_errno_with_message!(Errno::ENOENT, "Device does not exist"),
     };
-    let fs_type = fs_type.to_str().unwrap();
     match fs_type {
         "ext2" => {
+            if !data.is_empty() {
+                warn!("ext2 mount options are not supported: {:?}", data);
+            }
             let ext2_fs = Ext2::open(device)?;
             Ok(ext2_fs)
         }
         "exfat" => {
-            let exfat_fs = ExfatFS::open(device, ExfatMountOptions::default())?;
+            let exfat_fs = ExfatFS::open(device, ExfatMountOptions::parse(data)?)?;
             Ok(exfat_fs)
         }
+        "vfat" => {
+            let vfat_fs = VfatFS::open(device, VfatMountOptions::parse(data)?)?;
+            Ok(vfat_fs)
+        }
         _ => return_errno_with_message!(Errno::EINVAL, "Invalid fs type"),
     }
 }
@@ -198,3 +284,10 @@ bitflags! {
         const MS_KERNMOUNT     =   1 << 22;      // This is a kern_mount call.
     }
 }
+
+impl MountFlags {
+    /// Gets the flags that apply to a single mount.
+    fn per_mount_flags(&self) -> PerMountFlags {
+        PerMountFlags::from_bits_truncate(self.bits())
+    }
+}
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use align_ext::AlignExt;
+
+use super::SyscallReturn;
+use crate::{prelude::*, process::rlimit::check_address_space_growth};
+
+pub fn sys_mremap(
+    old_addr: Vaddr,
+    old_size: usize,
+    new_size: usize,
+    flags: i32,
+    new_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = MremapFlags::from_bits(flags)
+        .ok_or(Error::with_message(Errno::EINVAL, "unknown mremap flags"))?;
+    debug!(
+        "old_addr = 0x{:x}, old_size = 0x{:x}, new_size = 0x{:x}, flags = {:?}, new_addr = 0x{:x}",
+        old_addr, old_size, new_size, flags, new_addr
+    );
+
+    let res = do_sys_mremap(old_addr, old_size, new_size, flags, new_addr, ctx)?;
+    Ok(SyscallReturn::Return(res as _))
+}
+
+fn do_sys_mremap(
+    old_addr: Vaddr,
+    old_size: usize,
+    new_size: usize,
+    flags: MremapFlags,
+    new_addr: Vaddr,
+    ctx: &Context,
+) -> Result<Vaddr> {
+    if old_addr % PAGE_SIZE != 0 {
+        return_errno_with_message!(Errno::EINVAL, "mremap: `old_addr` must be page-aligned");
+    }
+    let may_move = flags.contains(MremapFlags::MREMAP_MAYMOVE);
+    if flags.intersects(MremapFlags::MREMAP_FIXED | MremapFlags::MREMAP_DONTUNMAP) && !may_move {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "mremap: MREMAP_FIXED and MREMAP_DONTUNMAP require MREMAP_MAYMOVE"
+        );
+    }
+    let dont_unmap = flags.contains(MremapFlags::MREMAP_DONTUNMAP);
+    if dont_unmap && old_size != new_size {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "mremap: MREMAP_DONTUNMAP does not support resizing"
+        );
+    }
+    if new_size == 0 {
+        return_errno_with_message!(Errno::EINVAL, "mremap: `new_size` cannot be zero");
+    }
+    if old_size == 0 {
+        // Linux duplicates a shared mapping in this case, which is an obsolete
+        // usage that we do not support.
+        return_errno_with_message!(Errno::EINVAL, "mremap: `old_size` cannot be zero");
+    }
+    if old_size > isize::MAX as usize || new_size > isize::MAX as usize {
+        return_errno_with_message!(Errno::ENOMEM, "mremap: the size is too large");
+    }
+
+    let old_size = old_size.align_up(PAGE_SIZE);
+    let new_size = new_size.align_up(PAGE_SIZE);
+    let old_end = old_addr.checked_add(old_size).ok_or(Error::with_message(
+        Errno::EINVAL,
+        "integer overflow when (old_addr + old_size)",
+    ))?;
+
+    // With `MREMAP_DONTUNMAP`, the old range is kept, so the whole new range
+    // counts toward the address space limit.
+    let growth = if dont_unmap {
+        new_size
+    } else {
+        new_size.saturating_sub(old_size)
+    };
+    check_address_space_growth(&ctx.process, growth)?;
+
+    let root_vmar = ctx.process.root_vmar();
+
+    if flags.contains(MremapFlags::MREMAP_FIXED) {
+        if new_addr % PAGE_SIZE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "mremap: `new_addr` must be page-aligned");
+        }
+        if new_addr < old_end && old_addr < new_addr.saturating_add(new_size) {
+            return_errno_with_message!(Errno::EINVAL, "mremap: the new range overlaps the old one");
+        }
+        // If the mapping is shrunk, its tail is unmapped only if it can be moved.
+        return root_vmar.move_mapping(old_addr..old_end, new_size, Some(new_addr), dont_unmap);
+    }
+
+    if dont_unmap {
+        return root_vmar.move_mapping(old_addr..old_end, new_size, None, true);
+    }
+
+    if new_size <= old_size {
+        root_vmar.remove_mapping(old_addr + new_size..old_end)?;
+        return Ok(old_addr);
+    }
+
+    match root_vmar.expand_mapping(old_addr..old_end, new_size) {
+        Ok(()) => Ok(old_addr),
+        Err(err) if err.error() == Errno::ENOMEM && may_move => {
+            root_vmar.move_mapping(old_addr..old_end, new_size, None, false)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+bitflags! {
+    struct MremapFlags: i32 {
+        const MREMAP_MAYMOVE   = 1 << 0;
+        const MREMAP_FIXED     = 1 << 1;
+        const MREMAP_DONTUNMAP = 1 << 2;
+    }
+}
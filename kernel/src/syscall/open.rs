@@ -50,7 +50,7 @@ pub fn sys_openat(
             } else {
                 FdFlags::empty()
             };
-        file_table_locked.insert(file_handle, fd_flags)
+        file_table_locked.insert(file_handle, fd_flags)?
     };
 
     Ok(SyscallReturn::Return(fd as _))
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file_table::{get_file_fast, FdFlags, FileDesc},
+        utils::StatusFlags,
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        kill_pidfd,
+        posix_thread::{thread_table, AsPosixThread},
+        process_table,
+        signal::{
+            c_types::siginfo_t,
+            constants::SI_TKILL,
+            sig_num::SigNum,
+            signals::user::{UserSignal, UserSignalKind},
+        },
+        Pid, PidFile, Process,
+    },
+};
+
+pub fn sys_pidfd_open(pid: Pid, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("pid = {}, flags = {:#x}", pid, flags);
+
+    let flags = PidfdFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    if pid as i32 <= 0 {
+        return_errno_with_message!(Errno::EINVAL, "the PID is not positive");
+    }
+
+    let Some(process) = process_table::get_process(pid) else {
+        if thread_table::get_thread(pid).is_some() {
+            return_errno_with_message!(Errno::EINVAL, "the thread is not a thread-group leader");
+        }
+        return_errno_with_message!(Errno::ESRCH, "the target process does not exist");
+    };
+
+    let pid_file = PidFile::new(process, flags.contains(PidfdFlags::PIDFD_NONBLOCK));
+    let fd = {
+        let file_table = ctx.thread_local.file_table().borrow();
+        let mut file_table_locked = file_table.write();
+        // A pidfd is always close-on-exec.
+        file_table_locked.insert(Arc::new(pid_file), FdFlags::CLOEXEC)?
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+pub fn sys_pidfd_send_signal(
+    pidfd: FileDesc,
+    sig_num: u64,
+    siginfo_addr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "pidfd = {}, sig_num = {}, siginfo_addr = {:#x}, flags = {:#x}",
+        pidfd, sig_num, siginfo_addr, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "unknown flags");
+    }
+
+    let process = get_pidfd_process(pidfd, ctx)?;
+    let sig_num = if sig_num == 0 {
+        None
+    } else {
+        Some(SigNum::try_from(sig_num as u8)?)
+    };
+
+    let kind = if siginfo_addr == 0 {
+        UserSignalKind::Kill
+    } else {
+        let siginfo = ctx.user_space().read_val::<siginfo_t>(siginfo_addr)?;
+        if siginfo.si_signo != sig_num.map_or(0, |sig_num| sig_num.as_u8() as i32) {
+            return_errno_with_message!(Errno::EINVAL, "the signal numbers do not match");
+        }
+        // Like `rt_sigqueueinfo`, a process cannot impersonate the kernel or `tgkill` when
+        // sending signals to other processes.
+        if !core::ptr::eq(process.as_ref(), ctx.process)
+            && (siginfo.si_code >= 0 || siginfo.si_code == SI_TKILL)
+        {
+            return_errno_with_message!(Errno::EPERM, "the signal code is not allowed");
+        }
+        UserSignalKind::Sigqueue
+    };
+
+    let signal = sig_num.map(|sig_num| {
+        let pid = ctx.process.pid();
+        let uid = ctx.posix_thread.credentials().ruid();
+        UserSignal::new(sig_num, kind, pid, uid)
+    });
+    kill_pidfd(&process, signal, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_pidfd_getfd(
+    pidfd: FileDesc,
+    target_fd: FileDesc,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "pidfd = {}, target_fd = {}, flags = {:#x}",
+        pidfd, target_fd, flags
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "unknown flags");
+    }
+
+    let process = get_pidfd_process(pidfd, ctx)?;
+    if process.status().is_reaped() {
+        return_errno_with_message!(Errno::ESRCH, "the target process has been reaped");
+    }
+    check_ptrace_attach_perm(&process, ctx)?;
+
+    let file = {
+        let main_thread = process.main_thread();
+        let file_table = main_thread.as_posix_thread().unwrap().file_table();
+        let file_table_locked = file_table.read();
+        file_table_locked.get_file(target_fd)?.clone()
+    };
+
+    let fd = {
+        let file_table = ctx.thread_local.file_table().borrow();
+        let mut file_table_locked = file_table.write();
+        file_table_locked.insert(file, FdFlags::CLOEXEC)?
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+/// Gets the process referred to by a pidfd.
+pub(super) fn get_pidfd_process(pidfd: FileDesc, ctx: &Context) -> Result<Arc<Process>> {
+    let mut file_table = ctx.thread_local.file_table().borrow_mut();
+    let file = get_file_fast!(&mut file_table, pidfd);
+    let pid_file = file
+        .downcast_ref::<PidFile>()
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a pidfd"))?;
+    Ok(pid_file.process().clone())
+}
+
+/// Checks whether the current process can access the target process like a
+/// tracer, which is required to steal the file descriptors of the target.
+///
+/// This follows the `PTRACE_MODE_ATTACH_REALCREDS` check in Linux: the real
+/// user and group IDs of the current thread must match all the user and group
+/// IDs of the target, unless the current thread has `CAP_SYS_PTRACE`.
+fn check_ptrace_attach_perm(target: &Process, ctx: &Context) -> Result<()> {
+    if core::ptr::eq(target, ctx.process) {
+        return Ok(());
+    }
+
+    let credentials = ctx.posix_thread.credentials();
+    if credentials.effective_capset().contains(CapSet::SYS_PTRACE) {
+        return Ok(());
+    }
+
+    let main_thread = target.main_thread();
+    let target_credentials = main_thread.as_posix_thread().unwrap().credentials();
+    let ruid = credentials.ruid();
+    let rgid = credentials.rgid();
+    let is_uid_matched = [
+        target_credentials.ruid(),
+        target_credentials.euid(),
+        target_credentials.suid(),
+    ]
+    .iter()
+    .all(|uid| *uid == ruid);
+    let is_gid_matched = [
+        target_credentials.rgid(),
+        target_credentials.egid(),
+        target_credentials.sgid(),
+    ]
+    .iter()
+    .all(|gid| *gid == rgid);
+
+    if !is_uid_matched || !is_gid_matched {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "the target process cannot be accessed by the current process"
+        );
+    }
+
+    Ok(())
+}
+
+bitflags! {
+    struct PidfdFlags: u32 {
+        const PIDFD_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+    }
+}
@@ -24,9 +24,17 @@ pub fn sys_pipe2(fds: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn>
     let file_table = ctx.thread_local.file_table().borrow();
     let mut file_table_locked = file_table.write();
 
+    let reader_fd = file_table_locked.insert(pipe_reader, fd_flags)?;
+    let writer_fd = match file_table_locked.insert(pipe_writer, fd_flags) {
+        Ok(writer_fd) => writer_fd,
+        Err(err) => {
+            file_table_locked.close_file(reader_fd).unwrap();
+            return Err(err);
+        }
+    };
     let pipe_fds = PipeFds {
-        reader_fd: file_table_locked.insert(pipe_reader, fd_flags),
-        writer_fd: file_table_locked.insert(pipe_writer, fd_flags),
+        reader_fd,
+        writer_fd,
     };
     debug!("pipe_fds: {:?}", pipe_fds);
 
@@ -3,15 +3,17 @@
 use super::SyscallReturn;
 use crate::{
     prelude::*,
-    process::{rlimit::RLimit64, Pid, ResourceType},
+    process::{
+        credentials::capabilities::CapSet, process_table, rlimit::RLimit64, Pid, Process,
+        ResourceType,
+    },
 };
 
 pub fn sys_getrlimit(resource: u32, rlim_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
     let resource = ResourceType::try_from(resource)?;
     debug!("resource = {:?}, rlim_addr = 0x{:x}", resource, rlim_addr);
-    let resource_limits = ctx.process.resource_limits().lock();
-    let rlimit = resource_limits.get_rlimit(resource);
-    ctx.user_space().write_val(rlim_addr, rlimit)?;
+    let rlimit = *ctx.process.resource_limits().lock().get_rlimit(resource);
+    ctx.user_space().write_val(rlim_addr, &rlimit)?;
     Ok(SyscallReturn::Return(0))
 }
 
@@ -22,11 +24,7 @@ pub fn sys_setrlimit(resource: u32, new_rlim_addr: Vaddr, ctx: &Context) -> Resu
         resource, new_rlim_addr
     );
     let new_rlimit: RLimit64 = ctx.user_space().read_val(new_rlim_addr)?;
-    if !new_rlimit.is_valid() {
-        return_errno_with_message!(Errno::EINVAL, "invalid rlimit");
-    }
-    let mut resource_limits = ctx.process.resource_limits().lock();
-    *resource_limits.get_rlimit_mut(resource) = new_rlimit;
+    set_rlimit(&ctx.process, resource, new_rlimit, ctx)?;
     Ok(SyscallReturn::Return(0))
 }
 
@@ -42,18 +40,110 @@ pub fn sys_prlimit64(
         "pid = {}, resource = {:?}, new_rlim_addr = 0x{:x}, old_rlim_addr = 0x{:x}",
         pid, resource, new_rlim_addr, old_rlim_addr
     );
-    let mut resource_limits = ctx.process.resource_limits().lock();
-    if old_rlim_addr != 0 {
-        let rlimit = resource_limits.get_rlimit(resource);
-        ctx.user_space().write_val(old_rlim_addr, rlimit)?;
-    }
-    if new_rlim_addr != 0 {
+
+    let process = if pid == 0 {
+        ctx.process.clone()
+    } else {
+        process_table::get_process(pid)
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?
+    };
+    check_prlimit_perm(&process, ctx)?;
+
+    let new_rlimit = if new_rlim_addr != 0 {
         let new_rlimit: RLimit64 = ctx.user_space().read_val(new_rlim_addr)?;
         debug!("new_rlimit = {:?}", new_rlimit);
-        if !new_rlimit.is_valid() {
-            return_errno_with_message!(Errno::EINVAL, "invalid rlimit");
-        }
-        *resource_limits.get_rlimit_mut(resource) = new_rlimit;
+        Some(new_rlimit)
+    } else {
+        None
+    };
+
+    let old_rlimit = *process.resource_limits().lock().get_rlimit(resource);
+    if let Some(new_rlimit) = new_rlimit {
+        set_rlimit(&process, resource, new_rlimit, ctx)?;
+    }
+    if old_rlim_addr != 0 {
+        ctx.user_space().write_val(old_rlim_addr, &old_rlimit)?;
     }
     Ok(SyscallReturn::Return(0))
 }
+
+/// Checks whether the current process can get or set the resource limits of
+/// `process`.
+///
+/// Similar to Linux, it is allowed if the real, effective, and saved user and
+/// group IDs of `process` match the real IDs of the caller, or if the caller
+/// has `CAP_SYS_RESOURCE`.
+fn check_prlimit_perm(process: &Arc<Process>, ctx: &Context) -> Result<()> {
+    if Arc::ptr_eq(process, &ctx.process) {
+        return Ok(());
+    }
+
+    let credentials = ctx.posix_thread.credentials();
+    if credentials
+        .effective_capset()
+        .contains(CapSet::SYS_RESOURCE)
+    {
+        return Ok(());
+    }
+
+    let main_thread = process.main_thread();
+    let target_credentials = main_thread.as_posix_thread().unwrap().credentials();
+    let ruid = credentials.ruid();
+    let rgid = credentials.rgid();
+    let is_uid_matched = [
+        target_credentials.ruid(),
+        target_credentials.euid(),
+        target_credentials.suid(),
+    ]
+    .iter()
+    .all(|uid| *uid == ruid);
+    let is_gid_matched = [
+        target_credentials.rgid(),
+        target_credentials.egid(),
+        target_credentials.sgid(),
+    ]
+    .iter()
+    .all(|gid| *gid == rgid);
+
+    if !is_uid_matched || !is_gid_matched {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "the resource limits of the process cannot be accessed"
+        );
+    }
+    Ok(())
+}
+
+fn set_rlimit(
+    process: &Process,
+    resource: ResourceType,
+    new_rlimit: RLimit64,
+    ctx: &Context,
+) -> Result<()> {
+    if !new_rlimit.is_valid() {
+        return_errno_with_message!(Errno::EINVAL, "invalid rlimit");
+    }
+
+    let has_sys_resource = ctx
+        .posix_thread
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::SYS_RESOURCE);
+
+    {
+        let mut resource_limits = process.resource_limits().lock();
+        let rlimit = resource_limits.get_rlimit_mut(resource);
+        if new_rlimit.get_max() > rlimit.get_max() && !has_sys_resource {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "raising the hard limit requires CAP_SYS_RESOURCE"
+            );
+        }
+        *rlimit = new_rlimit;
+    }
+
+    if let ResourceType::RLIMIT_CPU = resource {
+        process.timer_manager().set_cpu_limit(&new_rlimit);
+    }
+    Ok(())
+}
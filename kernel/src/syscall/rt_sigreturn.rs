@@ -2,10 +2,16 @@
 
 use core::sync::atomic::Ordering;
 
-use ostd::{cpu::UserContext, user::UserContextApi};
+use ostd::{
+    cpu::{FpuState, UserContext},
+    user::UserContextApi,
+};
 
 use super::SyscallReturn;
-use crate::{prelude::*, process::signal::c_types::ucontext_t};
+use crate::{
+    prelude::*,
+    process::signal::{c_types::ucontext_t, SigStack, SigStackFlags},
+};
 
 pub fn sys_rt_sigreturn(ctx: &Context, user_ctx: &mut UserContext) -> Result<SyscallReturn> {
     let Context {
@@ -26,15 +32,36 @@ pub fn sys_rt_sigreturn(ctx: &Context, user_ctx: &mut UserContext) -> Result<Sys
     debug_assert!(sig_context_addr == user_ctx.stack_pointer() as Vaddr);
 
     let ucontext = ctx.user_space().read_val::<ucontext_t>(sig_context_addr)?;
+    let fpregs_addr = ucontext.uc_mcontext.inner.fpregs;
+    let fpregs = if fpregs_addr != 0 {
+        let mut fpregs = vec![0u8; user_ctx.fpu_state().size()];
+        ctx.user_space()
+            .read_bytes(fpregs_addr, &mut VmWriter::from(fpregs.as_mut_slice()))?;
+        Some(fpregs)
+    } else {
+        None
+    };
 
     // If the sig stack is active and used by current handler, decrease handler counter.
-    if let Some(sig_stack) = &mut *thread_local.sig_stack().borrow_mut() {
+    let mut sig_stack = thread_local.sig_stack().borrow_mut();
+    if let Some(sig_stack) = &mut *sig_stack {
         let rsp = user_ctx.stack_pointer();
         if rsp >= sig_stack.base() && rsp <= sig_stack.base() + sig_stack.size() {
             sig_stack.decrease_handler_counter();
         }
     }
 
+    // Restore the sig stack that was disarmed when the handler was set up. As in Linux, an
+    // invalid stack is ignored, and the active stack cannot be replaced.
+    let uc_stack_flags = SigStackFlags::from_bits_truncate(ucontext.uc_stack.ss_flags as u32);
+    if uc_stack_flags.contains(SigStackFlags::SS_AUTODISARM)
+        && !sig_stack.as_ref().is_some_and(SigStack::is_active)
+        && let Ok(old_sig_stack) = SigStack::try_from(ucontext.uc_stack)
+    {
+        *sig_stack = Some(old_sig_stack);
+    }
+    drop(sig_stack);
+
     // Set previous ucontext address
     if ucontext.uc_link == 0 {
         thread_local.sig_context().set(None);
@@ -47,6 +74,14 @@ pub fn sys_rt_sigreturn(ctx: &Context, user_ctx: &mut UserContext) -> Result<Sys
         .gp_regs
         .copy_to_raw(user_ctx.general_regs_mut());
 
+    // Restore the FPU state. If there is no FPU state, the FPU is reset to the initial state.
+    if let Some(fpregs) = fpregs {
+        user_ctx.fpu_state_mut().load_from_bytes(&fpregs);
+    } else {
+        *user_ctx.fpu_state_mut() = FpuState::default();
+    }
+    user_ctx.fpu_state().restore();
+
     // unblock sig mask
     let sig_mask = ucontext.uc_sigmask;
     let old_mask = posix_thread.sig_mask().load(Ordering::Relaxed);
@@ -28,8 +28,9 @@ pub fn sys_sched_getaffinity(
 
 // TODO: The manual page of `sched_setaffinity` says that if the thread is not
 // running on the CPU specified in the affinity mask, it would be migrated to
-// one of the CPUs specified in the mask. We currently do not support this
-// feature as the scheduler is not ready for migration yet.
+// one of the CPUs specified in the mask. We currently do not migrate it at
+// once. It is moved to an allowed CPU when it is woken up next time, or when
+// it is pulled by the load balancing of an allowed CPU.
 pub fn sys_sched_setaffinity(
     tid: Tid,
     cpuset_size: usize,
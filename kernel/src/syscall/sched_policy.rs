@@ -0,0 +1,527 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{mem, sync::atomic::Ordering, time::Duration};
+
+use int_to_c_enum::TryFromInt;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        posix_thread::{thread_table, AsPosixThread},
+        ResourceType,
+    },
+    sched::{
+        priority::{Nice, NiceRange},
+        DeadlineParams, RealTimePolicy, RtPrio, SchedPolicy,
+    },
+    thread::{Thread, Tid},
+    time::timespec_t,
+};
+
+pub fn sys_sched_setscheduler(
+    tid: Tid,
+    policy: i32,
+    param_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    if (tid as i32) < 0 || param_addr == 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid arguments");
+    }
+
+    let param: SchedParam = ctx.user_space().read_val(param_addr)?;
+    let thread = thread_by_tid(tid, ctx)?;
+    let linux_policy = LinuxSchedPolicy::try_from(policy as u32)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "invalid scheduling policy"))?;
+    debug!(
+        "tid = {}, policy = {:?}, priority = {}",
+        tid, linux_policy, param.sched_priority
+    );
+
+    let new_policy = to_sched_policy(linux_policy, param.sched_priority, current_nice(&thread))?;
+    set_sched_policy(&thread, new_policy, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_sched_getscheduler(tid: Tid, ctx: &Context) -> Result<SyscallReturn> {
+    if (tid as i32) < 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid tid");
+    }
+
+    let thread = thread_by_tid(tid, ctx)?;
+    let (linux_policy, _) = to_linux_policy(thread.sched_attr().policy());
+
+    Ok(SyscallReturn::Return(linux_policy as _))
+}
+
+pub fn sys_sched_setparam(tid: Tid, param_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    if (tid as i32) < 0 || param_addr == 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid arguments");
+    }
+
+    let param: SchedParam = ctx.user_space().read_val(param_addr)?;
+    let thread = thread_by_tid(tid, ctx)?;
+    debug!("tid = {}, priority = {}", tid, param.sched_priority);
+
+    let (linux_policy, _) = to_linux_policy(thread.sched_attr().policy());
+    let new_policy = to_sched_policy(linux_policy, param.sched_priority, current_nice(&thread))?;
+    set_sched_policy(&thread, new_policy, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_sched_getparam(tid: Tid, param_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    if (tid as i32) < 0 || param_addr == 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid arguments");
+    }
+
+    let thread = thread_by_tid(tid, ctx)?;
+    let (_, sched_priority) = to_linux_policy(thread.sched_attr().policy());
+    ctx.user_space()
+        .write_val(param_addr, &SchedParam { sched_priority })?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_sched_get_priority_max(policy: i32, _ctx: &Context) -> Result<SyscallReturn> {
+    let max_priority = match LinuxSchedPolicy::try_from(policy as u32) {
+        Ok(LinuxSchedPolicy::Fifo | LinuxSchedPolicy::RoundRobin) => MAX_RT_PRIORITY,
+        Ok(
+            LinuxSchedPolicy::Normal
+            | LinuxSchedPolicy::Batch
+            | LinuxSchedPolicy::Idle
+            | LinuxSchedPolicy::Deadline,
+        ) => 0,
+        Err(_) => return_errno_with_message!(Errno::EINVAL, "invalid scheduling policy"),
+    };
+
+    Ok(SyscallReturn::Return(max_priority as _))
+}
+
+pub fn sys_sched_get_priority_min(policy: i32, _ctx: &Context) -> Result<SyscallReturn> {
+    let min_priority = match LinuxSchedPolicy::try_from(policy as u32) {
+        Ok(LinuxSchedPolicy::Fifo | LinuxSchedPolicy::RoundRobin) => MIN_RT_PRIORITY,
+        Ok(
+            LinuxSchedPolicy::Normal
+            | LinuxSchedPolicy::Batch
+            | LinuxSchedPolicy::Idle
+            | LinuxSchedPolicy::Deadline,
+        ) => 0,
+        Err(_) => return_errno_with_message!(Errno::EINVAL, "invalid scheduling policy"),
+    };
+
+    Ok(SyscallReturn::Return(min_priority as _))
+}
+
+pub fn sys_sched_rr_get_interval(
+    tid: Tid,
+    interval_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    if (tid as i32) < 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid tid");
+    }
+
+    let thread = thread_by_tid(tid, ctx)?;
+    let interval = timespec_t::from(thread.sched_attr().policy().time_slice());
+    ctx.user_space().write_val(interval_addr, &interval)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_sched_setattr(
+    tid: Tid,
+    attr_addr: Vaddr,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    if (tid as i32) < 0 || attr_addr == 0 || flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid arguments");
+    }
+
+    let attr = read_sched_attr(attr_addr, ctx)?;
+    let thread = thread_by_tid(tid, ctx)?;
+    debug!("tid = {}, attr = {:?}", tid, attr);
+
+    let sched_flags = SchedFlags::from_bits(attr.sched_flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unsupported scheduling flags"))?;
+
+    let linux_policy = if sched_flags.contains(SchedFlags::KEEP_POLICY) {
+        to_linux_policy(thread.sched_attr().policy()).0
+    } else {
+        LinuxSchedPolicy::try_from(attr.sched_policy)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "invalid scheduling policy"))?
+    };
+    // Like Linux, the out-of-range nice values are clamped instead of rejected.
+    let nice = {
+        let nice_raw = attr
+            .sched_nice
+            .clamp(NiceRange::MIN as i32, NiceRange::MAX as i32) as i8;
+        Nice::new(NiceRange::new(nice_raw))
+    };
+
+    let new_policy = match (linux_policy, thread.sched_attr().policy()) {
+        (LinuxSchedPolicy::Deadline, SchedPolicy::Deadline(params))
+            if sched_flags.contains(SchedFlags::KEEP_PARAMS) =>
+        {
+            SchedPolicy::Deadline(params)
+        }
+        (LinuxSchedPolicy::Deadline, _) => SchedPolicy::Deadline(to_deadline_params(&attr)?),
+        _ => to_sched_policy(linux_policy, attr.sched_priority, nice)?,
+    };
+    set_sched_policy(&thread, new_policy, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_sched_getattr(
+    tid: Tid,
+    attr_addr: Vaddr,
+    size: u32,
+    flags: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let size = size as usize;
+    if (tid as i32) < 0
+        || attr_addr == 0
+        || flags != 0
+        || !(SCHED_ATTR_SIZE_VER0..=PAGE_SIZE).contains(&size)
+    {
+        return_errno_with_message!(Errno::EINVAL, "invalid arguments");
+    }
+
+    let thread = thread_by_tid(tid, ctx)?;
+    let policy = thread.sched_attr().policy();
+    let (linux_policy, sched_priority) = to_linux_policy(policy);
+    let params = match policy {
+        SchedPolicy::Deadline(params) => params,
+        _ => DeadlineParams {
+            runtime: Duration::ZERO,
+            deadline: Duration::ZERO,
+            period: Duration::ZERO,
+        },
+    };
+
+    // The user may be built with an older or a newer version of the structure.
+    // Only the fields known by both sides are written.
+    let size = size.min(mem::size_of::<LinuxSchedAttr>());
+    let attr = LinuxSchedAttr {
+        size: size as u32,
+        sched_policy: linux_policy as u32,
+        sched_nice: i8::from(current_nice(&thread)) as i32,
+        sched_priority,
+        sched_runtime: params.runtime.as_nanos() as u64,
+        sched_deadline: params.deadline.as_nanos() as u64,
+        sched_period: params.period.as_nanos() as u64,
+        ..LinuxSchedAttr::new_zeroed()
+    };
+    ctx.user_space()
+        .write_bytes(attr_addr, &mut VmReader::from(&attr.as_bytes()[..size]))?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+/// The scheduling policies of Linux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromInt)]
+#[repr(u32)]
+enum LinuxSchedPolicy {
+    Normal = 0,
+    Fifo = 1,
+    RoundRobin = 2,
+    Batch = 3,
+    Idle = 5,
+    Deadline = 6,
+}
+
+const MIN_RT_PRIORITY: u32 = 1;
+const MAX_RT_PRIORITY: u32 = 99;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct SchedParam {
+    sched_priority: u32,
+}
+
+/// The size of the first published version of `struct sched_attr`.
+const SCHED_ATTR_SIZE_VER0: usize = 48;
+
+/// The `struct sched_attr` of Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct LinuxSchedAttr {
+    size: u32,
+    sched_policy: u32,
+    sched_flags: u64,
+    /// The nice value for `SCHED_NORMAL` and `SCHED_BATCH`.
+    sched_nice: i32,
+    /// The static priority for `SCHED_FIFO` and `SCHED_RR`.
+    sched_priority: u32,
+    /// The parameters for `SCHED_DEADLINE`.
+    sched_runtime: u64,
+    sched_deadline: u64,
+    sched_period: u64,
+    /// The utilization clamping values.
+    sched_util_min: u32,
+    sched_util_max: u32,
+}
+
+bitflags! {
+    struct SchedFlags: u64 {
+        /// Keeps the current scheduling policy.
+        const KEEP_POLICY = 0x08;
+        /// Keeps the current `SCHED_DEADLINE` parameters.
+        const KEEP_PARAMS = 0x10;
+    }
+}
+
+/// Reads a `struct sched_attr` from the user space.
+///
+/// If the size specified by the user is not supported, the supported size is
+/// written back to the user and `E2BIG` is returned.
+fn read_sched_attr(attr_addr: Vaddr, ctx: &Context) -> Result<LinuxSchedAttr> {
+    let user_space = ctx.user_space();
+
+    let size = match user_space.read_val::<u32>(attr_addr)? as usize {
+        0 => SCHED_ATTR_SIZE_VER0,
+        size => size,
+    };
+    let kernel_size = mem::size_of::<LinuxSchedAttr>();
+
+    let mut is_too_big = !(SCHED_ATTR_SIZE_VER0..=PAGE_SIZE).contains(&size);
+    if !is_too_big && size > kernel_size {
+        // The fields unknown to the kernel are acceptable only if they are zero.
+        let mut unknown = vec![0u8; size - kernel_size];
+        user_space.read_bytes(
+            attr_addr + kernel_size,
+            &mut VmWriter::from(unknown.as_mut_slice()),
+        )?;
+        is_too_big = unknown.iter().any(|byte| *byte != 0);
+    }
+    if is_too_big {
+        user_space.write_val(attr_addr, &(kernel_size as u32))?;
+        return_errno_with_message!(Errno::E2BIG, "the size of sched_attr is not supported");
+    }
+
+    let mut attr = LinuxSchedAttr::new_zeroed();
+    let size = size.min(kernel_size);
+    user_space.read_bytes(
+        attr_addr,
+        &mut VmWriter::from(&mut attr.as_bytes_mut()[..size]),
+    )?;
+    Ok(attr)
+}
+
+/// Returns the thread specified by `tid`, or the current thread if `tid` is zero.
+fn thread_by_tid(tid: Tid, ctx: &Context) -> Result<Arc<Thread>> {
+    let tid = if tid == 0 {
+        ctx.posix_thread.tid()
+    } else {
+        tid
+    };
+
+    thread_table::get_thread(tid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "thread does not exist"))
+}
+
+/// Converts the Linux scheduling policy and parameters to a [`SchedPolicy`].
+fn to_sched_policy(
+    linux_policy: LinuxSchedPolicy,
+    sched_priority: u32,
+    nice: Nice,
+) -> Result<SchedPolicy> {
+    let rt_policy = match linux_policy {
+        LinuxSchedPolicy::Fifo => RealTimePolicy::Fifo,
+        LinuxSchedPolicy::RoundRobin => RealTimePolicy::RoundRobin {
+            base_slice_factor: None,
+        },
+        _ if sched_priority != 0 => {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the priority of a non-real-time policy must be zero"
+            );
+        }
+        LinuxSchedPolicy::Normal => return Ok(SchedPolicy::Fair(nice)),
+        LinuxSchedPolicy::Batch => return Ok(SchedPolicy::Batch(nice)),
+        LinuxSchedPolicy::Idle => return Ok(SchedPolicy::Idle),
+        LinuxSchedPolicy::Deadline => {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the deadline parameters can only be set by sched_setattr"
+            );
+        }
+    };
+
+    if !(MIN_RT_PRIORITY..=MAX_RT_PRIORITY).contains(&sched_priority) {
+        return_errno_with_message!(Errno::EINVAL, "the real-time priority is out of range");
+    }
+
+    // The real-time priority of Linux is in the reverse order of `RtPrio`,
+    // where a smaller value means a higher priority.
+    Ok(SchedPolicy::RealTime {
+        rt_prio: RtPrio::new((MAX_RT_PRIORITY + MIN_RT_PRIORITY - sched_priority) as u8),
+        rt_policy,
+    })
+}
+
+/// The minimum runtime of `SCHED_DEADLINE`, in nanoseconds.
+const MIN_DL_RUNTIME_NS: u64 = 1 << 10;
+/// The minimum period of `SCHED_DEADLINE`, in nanoseconds.
+const MIN_DL_PERIOD_NS: u64 = 100 * 1000;
+/// The maximum period of `SCHED_DEADLINE`, in nanoseconds.
+const MAX_DL_PERIOD_NS: u64 = (1 << 22) * 1000;
+
+/// Converts the `SCHED_DEADLINE` parameters in `attr` to [`DeadlineParams`].
+///
+/// Like Linux, the parameters must satisfy `runtime <= deadline <= period`.
+/// If the period is zero, it defaults to the deadline.
+fn to_deadline_params(attr: &LinuxSchedAttr) -> Result<DeadlineParams> {
+    if attr.sched_priority != 0 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the priority of a non-real-time policy must be zero"
+        );
+    }
+
+    let runtime = attr.sched_runtime;
+    let deadline = attr.sched_deadline;
+    let period = match attr.sched_period {
+        0 => deadline,
+        period => period,
+    };
+    if deadline == 0
+        || runtime < MIN_DL_RUNTIME_NS
+        || runtime > deadline
+        || deadline > period
+        || !(MIN_DL_PERIOD_NS..=MAX_DL_PERIOD_NS).contains(&period)
+    {
+        return_errno_with_message!(Errno::EINVAL, "invalid deadline parameters");
+    }
+
+    Ok(DeadlineParams {
+        runtime: Duration::from_nanos(runtime),
+        deadline: Duration::from_nanos(deadline),
+        period: Duration::from_nanos(period),
+    })
+}
+
+/// Converts a [`SchedPolicy`] to the Linux scheduling policy and priority.
+fn to_linux_policy(policy: SchedPolicy) -> (LinuxSchedPolicy, u32) {
+    match policy {
+        // The STOP class is only used by kernel threads. It is reported as the
+        // real-time policy with the highest priority.
+        SchedPolicy::Stop => (LinuxSchedPolicy::Fifo, MAX_RT_PRIORITY),
+        SchedPolicy::Deadline(_) => (LinuxSchedPolicy::Deadline, 0),
+        SchedPolicy::RealTime { rt_prio, rt_policy } => {
+            let linux_policy = match rt_policy {
+                RealTimePolicy::Fifo => LinuxSchedPolicy::Fifo,
+                RealTimePolicy::RoundRobin { .. } => LinuxSchedPolicy::RoundRobin,
+            };
+            let sched_priority = MAX_RT_PRIORITY + MIN_RT_PRIORITY - u32::from(rt_prio.get());
+            (linux_policy, sched_priority)
+        }
+        SchedPolicy::Fair(_) => (LinuxSchedPolicy::Normal, 0),
+        SchedPolicy::Batch(_) => (LinuxSchedPolicy::Batch, 0),
+        SchedPolicy::Idle => (LinuxSchedPolicy::Idle, 0),
+    }
+}
+
+/// Returns the nice value of the thread.
+///
+/// The threads that are not in the FAIR class use the nice value of their
+/// process, which takes effect once they return to the FAIR class.
+fn current_nice(thread: &Thread) -> Nice {
+    match thread.sched_attr().policy() {
+        SchedPolicy::Fair(nice) | SchedPolicy::Batch(nice) => nice,
+        _ => thread
+            .as_posix_thread()
+            .unwrap()
+            .process()
+            .nice()
+            .load(Ordering::Relaxed),
+    }
+}
+
+fn set_sched_policy(thread: &Thread, new_policy: SchedPolicy, ctx: &Context) -> Result<()> {
+    check_sched_permission(thread, new_policy, ctx)?;
+    thread.sched_attr().set_policy(new_policy)
+}
+
+/// Checks whether the current thread can change the scheduling policy of
+/// `thread` to `new_policy`.
+///
+/// Without `CAP_SYS_NICE`, a thread can only change the policies of the
+/// threads of the same user, and cannot set `SCHED_DEADLINE` at all. Raising
+/// the real-time priority is limited by `RLIMIT_RTPRIO`, while lowering the
+/// nice value or leaving `SCHED_IDLE` is limited by `RLIMIT_NICE`.
+fn check_sched_permission(thread: &Thread, new_policy: SchedPolicy, ctx: &Context) -> Result<()> {
+    let credentials = ctx.posix_thread.credentials();
+    if credentials.effective_capset().contains(CapSet::SYS_NICE) {
+        return Ok(());
+    }
+
+    if matches!(new_policy, SchedPolicy::Deadline(_)) {
+        return_errno_with_message!(Errno::EPERM, "setting SCHED_DEADLINE requires CAP_SYS_NICE");
+    }
+
+    let posix_thread = thread.as_posix_thread().unwrap();
+    let target_credentials = posix_thread.credentials();
+    if credentials.euid() != target_credentials.euid()
+        && credentials.euid() != target_credentials.ruid()
+    {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "changing the policy of other users' threads requires CAP_SYS_NICE"
+        );
+    }
+
+    let (rtprio_limit, nice_limit) = {
+        let process = posix_thread.process();
+        let resource_limits = process.resource_limits().lock();
+        (
+            resource_limits
+                .get_rlimit(ResourceType::RLIMIT_RTPRIO)
+                .get_cur(),
+            resource_limits
+                .get_rlimit(ResourceType::RLIMIT_NICE)
+                .get_cur(),
+        )
+    };
+    // The nice value `n` is allowed if `20 - n` does not exceed `RLIMIT_NICE`.
+    let can_nice = |nice: Nice| (20 - i8::from(nice) as i64) as u64 <= nice_limit;
+
+    let old_policy = thread.sched_attr().policy();
+    let old_nice = current_nice(thread);
+    let (old_linux_policy, old_priority) = to_linux_policy(old_policy);
+    let (new_linux_policy, new_priority) = to_linux_policy(new_policy);
+
+    match new_policy {
+        SchedPolicy::RealTime { .. } => {
+            if old_linux_policy != new_linux_policy && rtprio_limit == 0 {
+                return_errno_with_message!(Errno::EPERM, "RLIMIT_RTPRIO is zero");
+            }
+            if new_priority > old_priority && u64::from(new_priority) > rtprio_limit {
+                return_errno_with_message!(
+                    Errno::EPERM,
+                    "the real-time priority exceeds RLIMIT_RTPRIO"
+                );
+            }
+        }
+        SchedPolicy::Fair(nice) | SchedPolicy::Batch(nice) => {
+            if nice < old_nice && !can_nice(nice) {
+                return_errno_with_message!(Errno::EPERM, "the nice value exceeds RLIMIT_NICE");
+            }
+        }
+        _ => {}
+    }
+
+    if old_policy == SchedPolicy::Idle && new_policy != SchedPolicy::Idle && !can_nice(old_nice) {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "leaving SCHED_IDLE is not allowed by RLIMIT_NICE"
+        );
+    }
+
+    Ok(())
+}
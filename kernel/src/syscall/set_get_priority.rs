@@ -6,7 +6,11 @@ use super::SyscallReturn;
 use crate::{
     prelude::*,
     process::{posix_thread::AsPosixThread, process_table, Pgid, Pid, Process, Uid},
-    sched::priority::{Nice, NiceRange},
+    sched::{
+        priority::{Nice, NiceRange},
+        SchedPolicy,
+    },
+    thread::AsThread,
 };
 
 pub fn sys_set_priority(which: i32, who: u32, prio: i32, ctx: &Context) -> Result<SyscallReturn> {
@@ -24,6 +28,16 @@ pub fn sys_set_priority(which: i32, who: u32, prio: i32, ctx: &Context) -> Resul
     let processes = get_processes(prio_target)?;
     for process in processes.iter() {
         process.nice().store(new_nice, Ordering::Relaxed);
+
+        // The nice value only takes effect on the threads of the FAIR class.
+        for task in process.tasks().lock().as_slice() {
+            let sched_attr = task.as_thread().unwrap().sched_attr();
+            match sched_attr.policy() {
+                SchedPolicy::Fair(_) => sched_attr.set_policy(SchedPolicy::Fair(new_nice))?,
+                SchedPolicy::Batch(_) => sched_attr.set_policy(SchedPolicy::Batch(new_nice))?,
+                _ => {}
+            }
+        }
     }
 
     Ok(SyscallReturn::Return(0))
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::{clock_settime::check_sys_time_capability, SyscallReturn};
+use crate::{
+    prelude::*,
+    time::{timekeeping, timeval_t},
+};
+
+// The use of the timezone structure is obsolete.
+// The kernel does not keep the timezone, so just ignore it.
+pub fn sys_settimeofday(
+    timeval_addr: Vaddr,
+    timezone_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "timeval_addr = 0x{:x}, timezone_addr = 0x{:x}",
+        timeval_addr, timezone_addr
+    );
+
+    let time = if timeval_addr == 0 {
+        None
+    } else {
+        let timeval = ctx.user_space().read_val::<timeval_t>(timeval_addr)?;
+        Some(Duration::try_from(timeval)?)
+    };
+
+    check_sys_time_capability(ctx)?;
+    if let Some(time) = time {
+        timekeeping::set_realtime(time)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
@@ -50,7 +50,7 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
         } else {
             FdFlags::empty()
         };
-        file_table_locked.insert(file_like, fd_flags)
+        file_table_locked.insert(file_like, fd_flags)?
     };
     Ok(SyscallReturn::Return(fd as _))
 }
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{fs_resolver::FsPath, utils::InodeType},
+    prelude::*,
+    syscall::constants::MAX_FILENAME_LEN,
+    vm::swap::{swap_off, swap_on, SwapBackend},
+};
+
+pub fn sys_swapon(path_addr: Vaddr, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let swap_flags = SwapFlags::from_bits(flags & !SWAP_FLAG_PRIO_MASK)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("path_addr = 0x{:x}, flags = {:?}", path_addr, swap_flags);
+
+    let priority = swap_flags
+        .contains(SwapFlags::SWAP_FLAG_PREFER)
+        .then_some((flags & SWAP_FLAG_PRIO_MASK) as i16);
+    // The discard flags are ignored, since the swap areas are never discarded.
+
+    let backend = lookup_backend(path_addr, ctx)?;
+    swap_on(backend, priority)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_swapoff(path_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("path_addr = 0x{:x}", path_addr);
+
+    let backend = lookup_backend(path_addr, ctx)?;
+    swap_off(&backend)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn lookup_backend(path_addr: Vaddr, ctx: &Context) -> Result<SwapBackend> {
+    let path = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
+    let path = path.to_string_lossy();
+    if path.is_empty() {
+        return_errno_with_message!(Errno::ENOENT, "path is empty");
+    }
+
+    // Block devices have no device files. So they are specified by their
+    // names, which is the same as `mount`.
+    let device_name = path.strip_prefix("/dev/").unwrap_or(path.as_ref());
+    if let Some(device) = aster_block::get_device(device_name) {
+        return Ok(SwapBackend::Device(device));
+    }
+
+    let fs_path = FsPath::try_from(path.as_ref())?;
+    let dentry = ctx.posix_thread.fs().resolver().read().lookup(&fs_path)?;
+    if dentry.type_() != InodeType::File {
+        return_errno_with_message!(Errno::EINVAL, "the swap area is not a regular file");
+    }
+
+    Ok(SwapBackend::File(dentry.inode().clone()))
+}
+
+const SWAP_FLAG_PRIO_MASK: u32 = 0x7fff;
+
+bitflags! {
+    struct SwapFlags: u32 {
+        const SWAP_FLAG_PREFER        = 0x8000;   // Set if the priority is specified.
+        const SWAP_FLAG_DISCARD       = 0x10000;  // Enable discard for swap.
+        const SWAP_FLAG_DISCARD_ONCE  = 0x20000;  // Discard the swap area at swapon.
+        const SWAP_FLAG_DISCARD_PAGES = 0x40000;  // Discard the freed pages.
+    }
+}
@@ -4,7 +4,7 @@ use aster_time::read_monotonic_time;
 use ostd::mm::stat::{mem_available, mem_total};
 
 use super::SyscallReturn;
-use crate::prelude::*;
+use crate::{prelude::*, vm::swap::swap_stat};
 
 #[derive(Debug, Default, Clone, Copy, Pod)]
 #[repr(C)]
@@ -24,10 +24,13 @@ pub struct sysinfo {
 }
 
 pub fn sys_sysinfo(sysinfo_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let (totalswap, freeswap) = swap_stat();
     let info = sysinfo {
         uptime: read_monotonic_time().as_secs() as i64,
         totalram: mem_total() as u64,
         freeram: mem_available() as u64,
+        totalswap: totalswap as u64,
+        freeswap: freeswap as u64,
         ..Default::default() // TODO: add other system information
     };
     ctx.user_space().write_val(sysinfo_addr, &info)?;
@@ -10,17 +10,17 @@ use crate::{
         posix_thread::{thread_table, AsPosixThread},
         process_table,
         signal::{
-            c_types::{sigevent_t, SigNotify},
+            c_types::{sigevent_t, sigval_t, SigNotify},
             constants::SIGALRM,
             sig_num::SigNum,
-            signals::kernel::KernelSignal,
         },
+        PosixTimer, TimerNotify,
     },
     syscall::ClockId,
-    thread::work_queue::{submit_work_item, work_item::WorkItem},
     time::{
         clockid_t,
         clocks::{BootTimeClock, MonotonicClock, RealTimeClock},
+        TimerManager,
     },
 };
 
@@ -37,95 +37,115 @@ pub fn sys_timer_create(
         );
     }
 
-    let current_process = current!();
-    let sent_signal: Box<dyn Fn() + Send + Sync + 'static> = {
-        // If `sigevent_addr` is NULL, use the default method (like `sys_alarm`) to send signal.
+    let notify = {
+        // If `sigevent_addr` is NULL, send `SIGALRM` to the current process, with the timer ID
+        // as the signal value.
         if sigevent_addr == 0 {
-            let process = current_process.clone();
-            let signal = KernelSignal::new(SIGALRM);
-            Box::new(move || {
-                process.enqueue_signal(signal);
-            })
+            None
         // Determine the timeout action through `sigevent`.
         } else {
             let sig_event = ctx.user_space().read_val::<sigevent_t>(sigevent_addr)?;
-            let sigev_notify = SigNotify::try_from(sig_event.sigev_notify)?;
-            let signo = sig_event.sigev_signo;
-            match sigev_notify {
-                // Do nothing when the timer is expired.
-                SigNotify::SIGEV_NONE => Box::new(|| {}),
-                // Send a signal to the current process when the timer is expired.
-                SigNotify::SIGEV_SIGNAL => {
-                    let process = current_process.clone();
-                    let signal = KernelSignal::new(SigNum::try_from(signo as u8)?);
-                    Box::new(move || {
-                        process.enqueue_signal(signal);
-                    })
-                }
-                // Spawn a posix thread to run the `sigev_function`, which is stored in
-                // `sig_event.sigev_un._sigev_thread`.
-                //
-                // TODO: enable this instructions. Currently the system does not provide an API to spawn
-                // a posix thread to run a specified function.
-                SigNotify::SIGEV_THREAD => {
-                    unimplemented!()
-                }
-                // Send a signal to the specified thread when the timer is expired.
-                SigNotify::SIGEV_THREAD_ID => {
-                    let tid = sig_event.sigev_un.read_tid() as u32;
-                    let thread = thread_table::get_thread(tid).ok_or_else(|| {
-                        Error::with_message(Errno::EINVAL, "target thread does not exist")
-                    })?;
-                    let posix_thread = thread.as_posix_thread().unwrap();
-                    if posix_thread.process().pid() != current_process.pid() {
-                        return_errno_with_message!(
-                            Errno::EINVAL,
-                            "target thread should belong to current process"
-                        );
-                    }
-                    let signal = KernelSignal::new(SigNum::try_from(signo as u8)?);
-                    Box::new(move || {
-                        if let Some(thread) = thread.as_posix_thread() {
-                            thread.enqueue_signal(Box::new(signal));
-                        }
-                    })
-                }
-            }
+            Some(parse_sig_event(&sig_event, ctx)?)
         }
     };
 
-    let work_func = sent_signal;
-    let work_item = WorkItem::new(work_func);
-    let func = move || {
-        submit_work_item(
-            work_item.clone(),
-            crate::thread::work_queue::WorkPriority::High,
-        );
+    let timer_manager = timer_manager_of_clock(clockid, ctx)?;
+
+    let process_timer_manager = ctx.process.timer_manager();
+    let timer_id = process_timer_manager.add_posix_timer(|timer_id| {
+        let notify = notify.unwrap_or_else(|| TimerNotify::Process {
+            process: ctx.posix_thread.weak_process(),
+            signum: SIGALRM,
+            value: sigval_t::from_int(timer_id as i32),
+        });
+        PosixTimer::new(timer_id, &timer_manager, notify)
+    })?;
+
+    if let Err(err) = ctx.user_space().write_val(timer_id_addr, &timer_id) {
+        process_timer_manager.remove_posix_timer(timer_id);
+        return Err(err);
+    }
+    Ok(SyscallReturn::Return(0))
+}
+
+/// Parses the way to notify the timer expiration from `sigevent`.
+fn parse_sig_event(sig_event: &sigevent_t, ctx: &Context) -> Result<TimerNotify> {
+    let sigev_notify = SigNotify::try_from(sig_event.sigev_notify)?;
+    if sigev_notify == SigNotify::SIGEV_NONE {
+        // Do nothing when the timer is expired.
+        return Ok(TimerNotify::None);
+    }
+
+    let signum = u8::try_from(sig_event.sigev_signo)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "invalid signal number"))
+        .and_then(SigNum::try_from)?;
+    let value = sig_event.sigev_value;
+
+    let notify = match sigev_notify {
+        SigNotify::SIGEV_NONE => unreachable!(),
+        // Send a signal to the current process when the timer is expired.
+        //
+        // `SIGEV_THREAD` is implemented by the C library, which creates a thread and lets
+        // the thread receive the signal. Like Linux, the kernel treats it as `SIGEV_SIGNAL`.
+        SigNotify::SIGEV_SIGNAL | SigNotify::SIGEV_THREAD => TimerNotify::Process {
+            process: ctx.posix_thread.weak_process(),
+            signum,
+            value,
+        },
+        // Send a signal to the specified thread when the timer is expired.
+        SigNotify::SIGEV_THREAD_ID => {
+            let tid = sig_event.sigev_un.read_tid() as u32;
+            let thread = thread_table::get_thread(tid).ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "target thread does not exist")
+            })?;
+            let posix_thread = thread.as_posix_thread().unwrap();
+            if posix_thread.process().pid() != ctx.process.pid() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "target thread should belong to current process"
+                );
+            }
+            TimerNotify::Thread {
+                thread: Arc::downgrade(&thread),
+                signum,
+                value,
+            }
+        }
     };
+    Ok(notify)
+}
 
-    let process_timer_manager = current_process.timer_manager();
-    let timer = if clockid >= 0 {
+/// Returns the manager of the timers based on the clock.
+fn timer_manager_of_clock(clockid: clockid_t, ctx: &Context) -> Result<Arc<TimerManager>> {
+    let process_timer_manager = ctx.process.timer_manager();
+    let timer_manager = if clockid >= 0 {
         let clock_id = ClockId::try_from(clockid)?;
         match clock_id {
-            ClockId::CLOCK_PROCESS_CPUTIME_ID => process_timer_manager.create_prof_timer(func),
-            ClockId::CLOCK_THREAD_CPUTIME_ID => ctx.posix_thread.create_prof_timer(func),
-            ClockId::CLOCK_REALTIME => RealTimeClock::timer_manager().create_timer(func),
-            ClockId::CLOCK_MONOTONIC => MonotonicClock::timer_manager().create_timer(func),
-            ClockId::CLOCK_BOOTTIME => BootTimeClock::timer_manager().create_timer(func),
+            ClockId::CLOCK_PROCESS_CPUTIME_ID => process_timer_manager.prof_timer_manager(),
+            ClockId::CLOCK_THREAD_CPUTIME_ID => ctx.posix_thread.prof_timer_manager(),
+            ClockId::CLOCK_REALTIME => RealTimeClock::timer_manager(),
+            ClockId::CLOCK_MONOTONIC => MonotonicClock::timer_manager(),
+            ClockId::CLOCK_BOOTTIME => BootTimeClock::timer_manager(),
             _ => return_errno_with_message!(Errno::EINVAL, "invalid clock ID"),
         }
+        .clone()
     } else {
         let dynamic_clockid_info = DynamicClockIdInfo::try_from(clockid)?;
         match dynamic_clockid_info {
             DynamicClockIdInfo::Pid(pid, clock_type) => {
                 let process = process_table::get_process(pid)
-                    .ok_or_else(|| crate::Error::with_message(Errno::EINVAL, "invalid clock id"))?;
+                    .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid clock id"))?;
                 let process_timer_manager = process.timer_manager();
                 match clock_type {
-                    DynamicClockType::Profiling => process_timer_manager.create_prof_timer(func),
-                    DynamicClockType::Virtual => process_timer_manager.create_virtual_timer(func),
-                    // TODO: support scheduling clock and fd clock.
-                    _ => unimplemented!(),
+                    // The scheduling clock is the CPU time of the process, which is
+                    // measured by the profiling clock.
+                    DynamicClockType::Profiling | DynamicClockType::Scheduling => {
+                        process_timer_manager.prof_timer_manager().clone()
+                    }
+                    DynamicClockType::Virtual => {
+                        process_timer_manager.virtual_timer_manager().clone()
+                    }
+                    DynamicClockType::FD => unreachable!(),
                 }
             }
             DynamicClockIdInfo::Tid(tid, clock_type) => {
@@ -133,26 +153,26 @@ pub fn sys_timer_create(
                     .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid clock id"))?;
                 let posix_thread = thread.as_posix_thread().unwrap();
                 match clock_type {
-                    DynamicClockType::Profiling => posix_thread.create_prof_timer(func),
-                    DynamicClockType::Virtual => posix_thread.create_virtual_timer(func),
-                    _ => unimplemented!(),
+                    DynamicClockType::Profiling | DynamicClockType::Scheduling => {
+                        posix_thread.prof_timer_manager().clone()
+                    }
+                    DynamicClockType::Virtual => posix_thread.virtual_timer_manager().clone(),
+                    DynamicClockType::FD => unreachable!(),
                 }
             }
-            DynamicClockIdInfo::Fd(_) => unimplemented!(),
+            DynamicClockIdInfo::Fd(_) => {
+                return_errno_with_message!(Errno::EINVAL, "the fd clocks are not supported")
+            }
         }
     };
-
-    let timer_id = process_timer_manager.add_posix_timer(timer);
-    ctx.user_space().write_val(timer_id_addr, &timer_id)?;
-    Ok(SyscallReturn::Return(0))
+    Ok(timer_manager)
 }
 
-pub fn sys_timer_delete(timer_id: usize, _ctx: &Context) -> Result<SyscallReturn> {
-    let current_process = current!();
-    let Some(timer) = current_process.timer_manager().remove_posix_timer(timer_id) else {
+pub fn sys_timer_delete(timer_id: usize, ctx: &Context) -> Result<SyscallReturn> {
+    let Some(timer) = ctx.process.timer_manager().remove_posix_timer(timer_id) else {
         return_errno_with_message!(Errno::EINVAL, "invalid timer ID");
     };
 
-    timer.cancel();
+    timer.timer().cancel();
     Ok(SyscallReturn::Return(0))
 }
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    time::{clock_t, clocks::BootTimeClock, Clock, USER_HZ},
+};
+
+pub fn sys_times(tms_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("tms_addr = 0x{:x}", tms_addr);
+
+    if tms_addr != 0 {
+        let usage = ctx.process.resource_usage();
+        let children_usage = ctx.process.children_resource_usage();
+        let tms = tms_t {
+            tms_utime: duration_to_clock_t(usage.utime),
+            tms_stime: duration_to_clock_t(usage.stime),
+            tms_cutime: duration_to_clock_t(children_usage.utime),
+            tms_cstime: duration_to_clock_t(children_usage.stime),
+        };
+        ctx.user_space().write_val(tms_addr, &tms)?;
+    }
+
+    // The return value is the number of clock ticks since boot.
+    let ticks = duration_to_clock_t(BootTimeClock::get().read_time());
+    Ok(SyscallReturn::Return(ticks as _))
+}
+
+fn duration_to_clock_t(duration: Duration) -> clock_t {
+    (duration.as_millis() as u64 * USER_HZ / 1000) as clock_t
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Pod)]
+struct tms_t {
+    /// user time
+    tms_utime: clock_t,
+    /// system time
+    tms_stime: clock_t,
+    /// user time of children
+    tms_cutime: clock_t,
+    /// system time of children
+    tms_cstime: clock_t,
+}
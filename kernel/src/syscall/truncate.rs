@@ -8,13 +8,13 @@ use crate::{
         utils::PATH_MAX,
     },
     prelude::*,
-    process::ResourceType,
+    process::rlimit::check_file_size,
 };
 
 pub fn sys_ftruncate(fd: FileDesc, len: isize, ctx: &Context) -> Result<SyscallReturn> {
     debug!("fd = {}, length = {}", fd, len);
 
-    check_length(len, ctx)?;
+    check_length(len)?;
 
     let mut file_table = ctx.thread_local.file_table().borrow_mut();
     let file = get_file_fast!(&mut file_table, fd);
@@ -26,7 +26,7 @@ pub fn sys_truncate(path_ptr: Vaddr, len: isize, ctx: &Context) -> Result<Syscal
     let path = ctx.user_space().read_cstring(path_ptr, PATH_MAX)?;
     debug!("path = {:?}, length = {}", path, len);
 
-    check_length(len, ctx)?;
+    check_length(len)?;
 
     let dir_dentry = {
         let path = path.to_string_lossy();
@@ -41,19 +41,10 @@ pub fn sys_truncate(path_ptr: Vaddr, len: isize, ctx: &Context) -> Result<Syscal
 }
 
 #[inline]
-fn check_length(len: isize, ctx: &Context) -> Result<()> {
+fn check_length(len: isize) -> Result<()> {
     if len < 0 {
         return_errno_with_message!(Errno::EINVAL, "length is negative");
     }
 
-    let max_file_size = {
-        let resource_limits = ctx.process.resource_limits().lock();
-        resource_limits
-            .get_rlimit(ResourceType::RLIMIT_FSIZE)
-            .get_cur() as usize
-    };
-    if len as usize > max_file_size {
-        return_errno_with_message!(Errno::EFBIG, "length is larger than the maximum file size");
-    }
-    Ok(())
+    check_file_size(len as usize)
 }
@@ -0,0 +1,458 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! `userfaultfd()` creates a file (we name it as `UserFaultFile`) that
+//! handles the page faults in the user space.
+//!
+//! After a memory range is registered with the file, the page faults on the
+//! missing pages in the range are not resolved by the kernel. Instead, the
+//! faulting thread is parked, and a message describing the fault can be read
+//! from the file. The handler resolves the fault by providing the page with
+//! `UFFDIO_COPY` or `UFFDIO_ZEROPAGE`, which wakes the faulting thread up.
+//!
+//! For more detailed information about this syscall,
+//! refer to the man 2 userfaultfd documentation.
+//!
+
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use aster_rights::Full;
+use ostd::mm::{FrameAllocOptions, UFrame, UntypedMem};
+
+use super::SyscallReturn;
+use crate::{
+    current_userspace,
+    events::IoEvents,
+    fs::{
+        file_handle::FileLike,
+        file_table::FdFlags,
+        utils::{CreationFlags, InodeMode, InodeType, IoctlCmd, Metadata, StatusFlags},
+    },
+    prelude::*,
+    process::{
+        signal::{PollHandle, Pollable},
+        Gid, Uid,
+    },
+    time::clocks::RealTimeClock,
+    vm::{page_fault_handler::UserFaultHandler, vmar::Vmar},
+};
+
+pub fn sys_userfaultfd(flags: u32, ctx: &Context) -> Result<SyscallReturn> {
+    let flags = Flags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "unknown flags"))?;
+    debug!("flags = {:?}", flags);
+
+    if flags.contains(Flags::UFFD_USER_MODE_ONLY) {
+        return_errno_with_message!(Errno::EINVAL, "UFFD_USER_MODE_ONLY is not supported");
+    }
+
+    let user_fault_file = UserFaultFile::new(ctx.process.root_vmar().dup()?, flags);
+    let fd = {
+        let file_table = ctx.thread_local.file_table().borrow();
+        let mut file_table_locked = file_table.write();
+        let fd_flags = if flags.contains(Flags::O_CLOEXEC) {
+            FdFlags::CLOEXEC
+        } else {
+            FdFlags::empty()
+        };
+        file_table_locked.insert(Arc::new(user_fault_file), fd_flags)?
+    };
+
+    Ok(SyscallReturn::Return(fd as _))
+}
+
+bitflags! {
+    struct Flags: u32 {
+        const O_CLOEXEC = CreationFlags::O_CLOEXEC.bits();
+        const O_NONBLOCK = StatusFlags::O_NONBLOCK.bits();
+        const UFFD_USER_MODE_ONLY = 1;
+    }
+}
+
+struct UserFaultFile {
+    handler: Arc<UserFaultHandler>,
+    root_vmar: Vmar<Full>,
+    /// Whether the API handshake is done with `UFFDIO_API`.
+    is_api_enabled: AtomicBool,
+    is_nonblocking: AtomicBool,
+}
+
+impl UserFaultFile {
+    fn new(root_vmar: Vmar<Full>, flags: Flags) -> Self {
+        Self {
+            handler: UserFaultHandler::new(),
+            root_vmar,
+            is_api_enabled: AtomicBool::new(false),
+            is_nonblocking: AtomicBool::new(flags.contains(Flags::O_NONBLOCK)),
+        }
+    }
+
+    fn check_api_enabled(&self) -> Result<()> {
+        if !self.is_api_enabled.load(Ordering::Relaxed) {
+            return_errno_with_message!(Errno::EINVAL, "the API handshake is not done");
+        }
+        Ok(())
+    }
+
+    fn try_read(&self, writer: &mut VmWriter) -> Result<()> {
+        let fault = self.handler.try_read_fault()?;
+
+        let mut flags = 0;
+        if fault.is_write {
+            flags |= UFFD_PAGEFAULT_FLAG_WRITE;
+        }
+        let msg = UffdMsg {
+            event: UFFD_EVENT_PAGEFAULT,
+            flags,
+            address: fault.address as u64,
+            ..UffdMsg::new_zeroed()
+        };
+        writer.write_val(&msg)?;
+
+        Ok(())
+    }
+
+    fn api(&self, arg: usize) -> Result<()> {
+        let mut uffdio_api: UffdioApi = current_userspace!().read_val(arg)?;
+        if uffdio_api.api != UFFD_API {
+            return_errno_with_message!(Errno::EINVAL, "the API version is not supported");
+        }
+        // No optional features are supported yet.
+        if uffdio_api.features != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the features are not supported");
+        }
+
+        uffdio_api.ioctls = UFFD_API_IOCTLS;
+        current_userspace!().write_val(arg, &uffdio_api)?;
+        self.is_api_enabled.store(true, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    fn register(&self, arg: usize) -> Result<()> {
+        let mut uffdio_register: UffdioRegister = current_userspace!().read_val(arg)?;
+        if uffdio_register.mode != UFFDIO_REGISTER_MODE_MISSING {
+            return_errno_with_message!(Errno::EINVAL, "only the missing mode is supported");
+        }
+        let range = uffdio_register.range.to_range()?;
+
+        self.root_vmar
+            .register_user_fault_handler(range, &self.handler)?;
+
+        uffdio_register.ioctls = UFFD_API_RANGE_IOCTLS;
+        current_userspace!().write_val(arg, &uffdio_register)?;
+
+        Ok(())
+    }
+
+    fn unregister(&self, arg: usize) -> Result<()> {
+        let uffdio_range: UffdioRange = current_userspace!().read_val(arg)?;
+        let range = uffdio_range.to_range()?;
+
+        self.root_vmar
+            .unregister_user_fault_handler(range.clone(), &self.handler)?;
+        self.handler.wake(&range);
+
+        Ok(())
+    }
+
+    fn wake(&self, arg: usize) -> Result<()> {
+        let uffdio_range: UffdioRange = current_userspace!().read_val(arg)?;
+        let range = uffdio_range.to_range()?;
+
+        self.handler.wake(&range);
+
+        Ok(())
+    }
+
+    fn copy(&self, arg: usize) -> Result<()> {
+        let mut uffdio_copy: UffdioCopy = current_userspace!().read_val(arg)?;
+        if uffdio_copy.mode & !UFFDIO_COPY_MODE_DONTWAKE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the copy mode is not supported");
+        }
+        let src = uffdio_copy.src as Vaddr;
+        if src % PAGE_SIZE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the source address is not page aligned");
+        }
+        let range = UffdioRange {
+            start: uffdio_copy.dst,
+            len: uffdio_copy.len,
+        }
+        .to_range()?;
+
+        let (copied, res) = self.fill_pages(&range, |offset| {
+            let frame = FrameAllocOptions::new().zeroed(false).alloc_frame()?;
+            current_userspace!().read_bytes(src + offset, &mut frame.writer())?;
+            Ok(frame.into())
+        });
+        uffdio_copy.copy = filled_len_or_errno(copied, &res);
+        current_userspace!().write_val(arg, &uffdio_copy)?;
+
+        let should_wake = uffdio_copy.mode & UFFDIO_COPY_MODE_DONTWAKE == 0;
+        self.finish_filling(&range, copied, res, should_wake)
+    }
+
+    fn zeropage(&self, arg: usize) -> Result<()> {
+        let mut uffdio_zeropage: UffdioZeropage = current_userspace!().read_val(arg)?;
+        if uffdio_zeropage.mode & !UFFDIO_ZEROPAGE_MODE_DONTWAKE != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the zeropage mode is not supported");
+        }
+        let range = uffdio_zeropage.range.to_range()?;
+
+        let (filled, res) = self.fill_pages(&range, |_| {
+            Ok(FrameAllocOptions::new().alloc_frame()?.into())
+        });
+        uffdio_zeropage.zeropage = filled_len_or_errno(filled, &res);
+        current_userspace!().write_val(arg, &uffdio_zeropage)?;
+
+        let should_wake = uffdio_zeropage.mode & UFFDIO_ZEROPAGE_MODE_DONTWAKE == 0;
+        self.finish_filling(&range, filled, res, should_wake)
+    }
+
+    /// Maps the pages provided by `new_frame` at the missing pages in the
+    /// range, until an error occurs.
+    ///
+    /// Returns the number of bytes that are filled, and the error if any.
+    fn fill_pages(
+        &self,
+        range: &Range<Vaddr>,
+        new_frame: impl Fn(usize) -> Result<UFrame>,
+    ) -> (usize, Result<()>) {
+        for offset in (0..range.len()).step_by(PAGE_SIZE) {
+            let res = new_frame(offset).and_then(|frame| {
+                self.root_vmar
+                    .map_missing_page(range.start + offset, frame, &self.handler)
+            });
+            if res.is_err() {
+                return (offset, res);
+            }
+        }
+        (range.len(), Ok(()))
+    }
+
+    /// Wakes up the threads that fault on the filled pages if `should_wake`
+    /// is true, and returns the result of [`Self::fill_pages`].
+    fn finish_filling(
+        &self,
+        range: &Range<Vaddr>,
+        filled: usize,
+        res: Result<()>,
+        should_wake: bool,
+    ) -> Result<()> {
+        if filled > 0 && should_wake {
+            self.handler.wake(&(range.start..range.start + filled));
+        }
+
+        match res {
+            // The caller should retry the rest of the range.
+            Err(_) if filled > 0 => {
+                return_errno_with_message!(Errno::EAGAIN, "the range is partially filled")
+            }
+            res => res,
+        }
+    }
+}
+
+/// Returns the value reported to the user space for the result of
+/// [`UserFaultFile::fill_pages`], which is the number of bytes that are
+/// filled, or the negated error number if no bytes are filled.
+fn filled_len_or_errno(filled: usize, res: &Result<()>) -> i64 {
+    match res {
+        Err(err) if filled == 0 => -(err.error() as i64),
+        _ => filled as i64,
+    }
+}
+
+impl Pollable for UserFaultFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.handler.poll(mask, poller)
+    }
+}
+
+impl FileLike for UserFaultFile {
+    fn read(&self, writer: &mut VmWriter) -> Result<usize> {
+        self.check_api_enabled()?;
+
+        let read_len = core::mem::size_of::<UffdMsg>();
+        if writer.avail() < read_len {
+            return_errno_with_message!(Errno::EINVAL, "buf len is less than the message size");
+        }
+
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            self.try_read(writer)?;
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.try_read(writer))?;
+        }
+
+        Ok(read_len)
+    }
+
+    fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
+        if !matches!(cmd, IoctlCmd::UFFDIO_API) {
+            self.check_api_enabled()?;
+        }
+
+        match cmd {
+            IoctlCmd::UFFDIO_API => self.api(arg)?,
+            IoctlCmd::UFFDIO_REGISTER => self.register(arg)?,
+            IoctlCmd::UFFDIO_UNREGISTER => self.unregister(arg)?,
+            IoctlCmd::UFFDIO_WAKE => self.wake(arg)?,
+            IoctlCmd::UFFDIO_COPY => self.copy(arg)?,
+            IoctlCmd::UFFDIO_ZEROPAGE => self.zeropage(arg)?,
+            _ => return_errno_with_message!(Errno::EINVAL, "the ioctl command is not supported"),
+        }
+        Ok(0)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        if self.is_nonblocking.load(Ordering::Relaxed) {
+            StatusFlags::O_NONBLOCK
+        } else {
+            StatusFlags::empty()
+        }
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        self.is_nonblocking.store(
+            new_flags.contains(StatusFlags::O_NONBLOCK),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn metadata(&self) -> Metadata {
+        // This is a dummy implementation.
+        // TODO: Add "anonymous inode fs" and link `UserFaultFile` to it.
+        let now = RealTimeClock::get().read_time();
+        Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: now,
+            mtime: now,
+            ctime: now,
+            type_: InodeType::NamedPipe,
+            mode: InodeMode::from_bits_truncate(0o600),
+            nlinks: 1,
+            uid: Uid::new_root(),
+            gid: Gid::new_root(),
+            rdev: 0,
+        }
+    }
+}
+
+impl Drop for UserFaultFile {
+    fn drop(&mut self) {
+        // Unregister the mappings first, so that the woken threads will not
+        // deliver the faults again.
+        let all_pages = self.root_vmar.base()..self.root_vmar.base() + self.root_vmar.size();
+        let _ = self
+            .root_vmar
+            .unregister_user_fault_handler(all_pages, &self.handler);
+        self.handler.release();
+    }
+}
+
+const UFFD_API: u64 = 0xaa;
+
+const UFFD_EVENT_PAGEFAULT: u8 = 0x12;
+const UFFD_PAGEFAULT_FLAG_WRITE: u64 = 1 << 0;
+
+const UFFDIO_REGISTER_MODE_MISSING: u64 = 1 << 0;
+const UFFDIO_COPY_MODE_DONTWAKE: u64 = 1 << 0;
+const UFFDIO_ZEROPAGE_MODE_DONTWAKE: u64 = 1 << 0;
+
+// The numbers of the ioctl commands.
+const _UFFDIO_REGISTER: u64 = 0x00;
+const _UFFDIO_UNREGISTER: u64 = 0x01;
+const _UFFDIO_WAKE: u64 = 0x02;
+const _UFFDIO_COPY: u64 = 0x03;
+const _UFFDIO_ZEROPAGE: u64 = 0x04;
+const _UFFDIO_API: u64 = 0x3f;
+
+/// The ioctls that are supported by the file, which are reported by
+/// `UFFDIO_API`.
+const UFFD_API_IOCTLS: u64 =
+    (1 << _UFFDIO_REGISTER) | (1 << _UFFDIO_UNREGISTER) | (1 << _UFFDIO_API);
+
+/// The ioctls that are supported on the registered ranges, which are reported
+/// by `UFFDIO_REGISTER`.
+const UFFD_API_RANGE_IOCTLS: u64 =
+    (1 << _UFFDIO_WAKE) | (1 << _UFFDIO_COPY) | (1 << _UFFDIO_ZEROPAGE);
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct UffdMsg {
+    event: u8,
+    reserved1: u8,
+    reserved2: u16,
+    reserved3: u32,
+    flags: u64,
+    address: u64,
+    ptid: u32,
+    reserved4: u32,
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct UffdioApi {
+    api: u64,
+    features: u64,
+    ioctls: u64,
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct UffdioRange {
+    start: u64,
+    len: u64,
+}
+
+impl UffdioRange {
+    /// Converts to a non-empty, page-aligned range in the user space.
+    fn to_range(self) -> Result<Range<Vaddr>> {
+        let start = self.start as Vaddr;
+        let len = self.len as usize;
+        if start % PAGE_SIZE != 0 || len % PAGE_SIZE != 0 || len == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the range is not page aligned");
+        }
+        let end = start
+            .checked_add(len)
+            .filter(|end| *end <= isize::MAX as usize)
+            .ok_or(Error::with_message(
+                Errno::EINVAL,
+                "the range is not in the user space",
+            ))?;
+        Ok(start..end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct UffdioRegister {
+    range: UffdioRange,
+    mode: u64,
+    ioctls: u64,
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct UffdioCopy {
+    dst: u64,
+    src: u64,
+    len: u64,
+    mode: u64,
+    copy: i64,
+}
+
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct UffdioZeropage {
+    range: UffdioRange,
+    mode: u64,
+    zeropage: i64,
+}
@@ -38,13 +38,12 @@ pub fn sys_wait4(
     }
 
     if rusage_addr != 0 {
-        let rusage = rusage_t {
-            ru_utime: process.prof_clock().user_clock().read_time().into(),
-            ru_stime: process.prof_clock().kernel_clock().read_time().into(),
-            ..Default::default()
-        };
+        // The usage covers the child itself and the children that it has waited for.
+        let mut usage = process.resource_usage();
+        usage.accumulate(&process.children_resource_usage());
 
-        ctx.user_space().write_val(rusage_addr, &rusage)?;
+        ctx.user_space()
+            .write_val(rusage_addr, &rusage_t::from(usage))?;
     }
 
     Ok(SyscallReturn::Return(return_pid as _))
@@ -1,11 +1,13 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::SyscallReturn;
+use super::{pidfd::get_pidfd_process, SyscallReturn};
 use crate::{
     prelude::*,
     process::{wait_child_exit, ProcessFilter, WaitOptions},
 };
 
+const P_PIDFD: u64 = 3;
+
 pub fn sys_waitid(
     which: u64,
     upid: u64,
@@ -15,7 +17,16 @@ pub fn sys_waitid(
     ctx: &Context,
 ) -> Result<SyscallReturn> {
     // FIXME: what does infoq and rusage use for?
-    let process_filter = ProcessFilter::from_which_and_id(which, upid)?;
+    let process_filter = if which == P_PIDFD {
+        let process = get_pidfd_process(upid as _, ctx)?;
+        // The PID of a reaped process may have been reused by another child.
+        if process.status().is_reaped() {
+            return_errno_with_message!(Errno::ECHILD, "the process has been reaped");
+        }
+        ProcessFilter::WithPid(process.pid())
+    } else {
+        ProcessFilter::from_which_and_id(which, upid)?
+    };
     let wait_options = WaitOptions::from_bits(options as u32)
         .ok_or(Error::with_message(Errno::EINVAL, "invalid options"))?;
     let waited_process =
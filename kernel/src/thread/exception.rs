@@ -3,11 +3,16 @@
 #![allow(unused_variables)]
 
 use aster_rights::Full;
-use ostd::{cpu::*, mm::VmSpace};
+use ostd::{cpu::*, mm::VmSpace, task::Task};
 
 use crate::{
     prelude::*,
-    process::signal::signals::fault::FaultSignal,
+    process::{
+        oom,
+        posix_thread::{AsPosixThread, AsThreadLocal},
+        signal::signals::fault::FaultSignal,
+    },
+    thread::Thread,
     vm::{page_fault_handler::PageFaultHandler, perms::VmPerms, vmar::Vmar},
 };
 
@@ -31,8 +36,13 @@ pub fn handle_exception(ctx: &Context, context: &UserContext) {
     log_trap_info(trap_info);
 
     if let Ok(page_fault_info) = PageFaultInfo::try_from(trap_info) {
-        if handle_page_fault_from_vmar(ctx.process.root_vmar(), &page_fault_info).is_ok() {
-            return;
+        match handle_page_fault_from_vmar(ctx.process.root_vmar(), &page_fault_info) {
+            Ok(()) => return,
+            // The thread is interrupted while waiting for the userfaultfd to
+            // resolve the fault. The faulting access will be retried after
+            // the signal is handled.
+            Err(err) if err.error() == Errno::EINTR => return,
+            Err(_) => {}
         }
     }
 
@@ -53,20 +63,60 @@ pub(crate) fn handle_page_fault_from_vm_space(
         vm_space as *const VmSpace
     );
 
-    handle_page_fault_from_vmar(root_vmar, page_fault_info)
+    handle_page_fault_from_vmar(root_vmar, page_fault_info).map_err(|_| ())
 }
 
 /// Handles the page fault occurs in the input `Vmar`.
+///
+/// A handled page fault is counted to the current thread. It is a major fault
+/// if block I/O is done while handling it, e.g., reading the page from a file
+/// or from a swap area.
 pub(crate) fn handle_page_fault_from_vmar(
     root_vmar: &Vmar<Full>,
     page_fault_info: &PageFaultInfo,
-) -> core::result::Result<(), ()> {
+) -> Result<()> {
+    let current_thread = Thread::current();
+    let rusage = current_thread
+        .as_ref()
+        .and_then(|thread| thread.as_posix_thread())
+        .map(|posix_thread| posix_thread.rusage());
+    let inblock_before = rusage.map_or(0, |rusage| rusage.inblock());
+
+    let task = Task::current();
+    let thread_local = task.as_ref().and_then(|task| task.as_thread_local());
+    if let Some(thread_local) = thread_local {
+        thread_local.memory_oom_cgroup().take();
+    }
+
     if let Err(e) = root_vmar.handle_page_fault(page_fault_info) {
-        warn!(
-            "page fault handler failed: addr: 0x{:x}, err: {:?}",
-            page_fault_info.address, e
-        );
-        return Err(());
+        // If the memory is exhausted, kill a process to free memory. Then the
+        // faulting access is retried, which succeeds once the victim exits.
+        // If the memory of a cgroup exceeds its limit, the victim is selected
+        // in the cgroup.
+        if e.error() == Errno::ENOMEM {
+            let oom_cgroup =
+                thread_local.and_then(|thread_local| thread_local.memory_oom_cgroup().take());
+            let should_retry = match oom_cgroup {
+                Some(cgroup) => oom::memory_cgroup_out_of_memory(&cgroup),
+                None => oom::out_of_memory(),
+            };
+            if should_retry {
+                Thread::yield_now();
+                return Ok(());
+            }
+        }
+
+        if e.error() != Errno::EINTR {
+            warn!(
+                "page fault handler failed: addr: 0x{:x}, err: {:?}",
+                page_fault_info.address, e
+            );
+        }
+        return Err(e);
+    }
+
+    if let Some(rusage) = rusage {
+        rusage.add_page_fault(rusage.inblock() != inblock_before);
     }
     Ok(())
 }
@@ -65,6 +65,7 @@ impl ThreadOptions {
                     kernel_thread,
                     priority,
                     cpu_affinity,
+                    priority.into(),
                 ))
             };
 
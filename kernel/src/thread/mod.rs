@@ -14,7 +14,7 @@ use crate::{
     prelude::*,
     sched::{
         priority::{AtomicPriority, Priority},
-        SchedAttr,
+        SchedAttr, SchedPolicy,
     },
 };
 
@@ -53,6 +53,7 @@ impl Thread {
         data: impl Send + Sync + Any,
         priority: Priority,
         cpu_affinity: CpuSet,
+        sched_policy: SchedPolicy,
     ) -> Self {
         Thread {
             task,
@@ -60,7 +61,7 @@ impl Thread {
             status: AtomicThreadStatus::new(ThreadStatus::Init),
             priority: AtomicPriority::new(priority),
             cpu_affinity: AtomicCpuSet::new(cpu_affinity),
-            sched_attr: SchedAttr::new(priority.into()),
+            sched_attr: SchedAttr::new(sched_policy),
         }
     }
 
@@ -8,9 +8,7 @@ use ostd::{cpu::PinCurrentCpu, cpu_local, sync::SpinLock, task::disable_preempt,
 use paste::paste;
 use spin::Once;
 
-use crate::time::{
-    self, system_time::START_TIME_AS_DURATION, timer::TimerManager, Clock, SystemTime,
-};
+use crate::time::{self, timekeeping, timer::TimerManager, Clock, SystemTime};
 
 /// The Clock that reads the jiffies, and turn the counter into `Duration`.
 pub struct JiffiesClock {
@@ -62,10 +60,11 @@ impl MonotonicClock {
 
 /// `RealTimeCoarseClock` is a coarse-grained version of a real-time clock.
 ///
-/// This clock will maintain a record to `RealTimeClock`. This record
+/// This clock will maintain a record to `MonotonicClock`. This record
 /// will be updated during each system timer interruption. Reading this clock
-/// will directly reads the value of the record instead of calculating the time
-/// based on the clocksource. Hence it is faster but less accurate.
+/// will directly reads the value of the record and adds the offset of the real
+/// time instead of calculating the time based on the clocksource. Hence it is
+/// faster but less accurate.
 ///
 /// Usually it will not be used to create a timer.
 pub struct RealTimeCoarseClock {
@@ -73,7 +72,7 @@ pub struct RealTimeCoarseClock {
 }
 
 impl RealTimeCoarseClock {
-    /// A reference to the current value of this clock.
+    /// A reference to the current value of the monotonic time.
     fn current_ref() -> &'static Once<SpinLock<Duration>> {
         static CURRENT: Once<SpinLock<Duration>> = Once::new();
 
@@ -165,13 +164,17 @@ impl Clock for MonotonicClock {
 
 impl Clock for RealTimeCoarseClock {
     fn read_time(&self) -> Duration {
-        *Self::current_ref().get().unwrap().disable_irq().lock()
+        MonotonicCoarseClock::get().read_time() + timekeeping::realtime_offset()
     }
 }
 
 impl Clock for MonotonicCoarseClock {
     fn read_time(&self) -> Duration {
-        RealTimeCoarseClock::get().read_time() - *START_TIME_AS_DURATION.get().unwrap()
+        *RealTimeCoarseClock::current_ref()
+            .get()
+            .unwrap()
+            .disable_irq()
+            .lock()
     }
 }
 
@@ -276,14 +279,14 @@ fn init_jiffies_clock_manager() {
 }
 
 fn update_coarse_clock() {
-    let real_time = RealTimeClock::get().read_time();
+    let monotonic_time = MonotonicClock::get().read_time();
     let current = RealTimeCoarseClock::current_ref().get().unwrap();
-    *current.disable_irq().lock() = real_time;
+    *current.disable_irq().lock() = monotonic_time;
 }
 
 fn init_coarse_clock() {
-    let real_time = RealTimeClock::get().read_time();
-    RealTimeCoarseClock::current_ref().call_once(|| SpinLock::new(real_time));
+    let monotonic_time = MonotonicClock::get().read_time();
+    RealTimeCoarseClock::current_ref().call_once(|| SpinLock::new(monotonic_time));
     time::softirq::register_callback(update_coarse_clock);
 }
 
@@ -7,7 +7,7 @@ use alloc::{
     vec::Vec,
 };
 use core::{
-    sync::atomic::{AtomicBool, Ordering},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
     time::Duration,
 };
 
@@ -30,8 +30,14 @@ pub enum Timeout {
 /// the set time. To enable its periodic functionality, users should set
 /// its `interval` field with [`Timer::set_interval`]. By doing this,
 /// the timer will use the interval time to configure a new timing after expiration.
+///
+/// A periodic timer is rearmed based on its last expired time rather than the
+/// time when the expiration is handled, so the timing errors do not
+/// accumulate. If the expiration is handled too late, the periods in between
+/// are skipped, which can be queried with [`Timer::missed_expirations`].
 pub struct Timer {
     interval: SpinLock<Duration>,
+    missed_expirations: AtomicUsize,
     timer_manager: Arc<TimerManager>,
     registered_callback: Box<dyn Fn() + Send + Sync>,
     timer_callback: SpinLock<Weak<TimerCallback>>,
@@ -49,6 +55,7 @@ impl Timer {
     {
         Arc::new(Self {
             interval: SpinLock::new(Duration::ZERO),
+            missed_expirations: AtomicUsize::new(0),
             timer_manager,
             registered_callback: Box::new(registered_callback),
             timer_callback: SpinLock::new(Weak::default()),
@@ -127,6 +134,14 @@ impl Timer {
     pub fn interval(&self) -> Duration {
         *self.interval.disable_irq().lock()
     }
+
+    /// Returns the number of the periods skipped at the last expiration.
+    ///
+    /// This method is intended to be called in the registered callback
+    /// function, where the value refers to the expiration being handled.
+    pub fn missed_expirations(&self) -> usize {
+        self.missed_expirations.load(Ordering::Relaxed)
+    }
 }
 
 fn interval_timer_callback(timer: &Weak<Timer>) {
@@ -134,11 +149,23 @@ fn interval_timer_callback(timer: &Weak<Timer>) {
         return;
     };
 
-    (timer.registered_callback)();
-    let interval = timer.interval.disable_irq().lock();
-    if *interval != Duration::ZERO {
-        timer.set_timeout(Timeout::After(*interval));
+    let interval = timer.interval();
+    let mut missed_expirations = 0;
+    if interval != Duration::ZERO {
+        let now = timer.timer_manager.clock.read_time();
+        let mut next_expired_time = timer.expired_time() + interval;
+        if next_expired_time <= now {
+            let periods = (now - next_expired_time).as_nanos() / interval.as_nanos() + 1;
+            next_expired_time += Duration::from_nanos((periods * interval.as_nanos()) as u64);
+            missed_expirations = periods as usize;
+        }
+        timer.set_timeout(Timeout::When(next_expired_time));
     }
+    timer
+        .missed_expirations
+        .store(missed_expirations, Ordering::Relaxed);
+
+    (timer.registered_callback)();
 }
 
 /// `TimerManager` is used to create timers and manage their expiries. It holds a clock and can
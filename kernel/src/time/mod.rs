@@ -14,6 +14,7 @@ pub mod clocks;
 mod core;
 mod softirq;
 mod system_time;
+pub mod timekeeping;
 pub mod wait;
 
 pub type clockid_t = i32;
@@ -21,12 +22,16 @@ pub type time_t = i64;
 pub type suseconds_t = i64;
 pub type clock_t = i64;
 
+/// The unit of the times in clock ticks reported to the user space (`USER_HZ`).
+pub const USER_HZ: u64 = 100;
+
 const NSEC_PER_USEC: i64 = 1_000;
 const USEC_PER_SEC: i64 = 1_000_000;
 const NSEC_PER_SEC: i64 = 1_000_000_000;
 
 pub(super) fn init() {
     system_time::init();
+    timekeeping::init();
     clocks::init();
     softirq::init();
 }
@@ -2,7 +2,7 @@
 
 use core::time::Duration;
 
-use aster_time::{read_monotonic_time, read_start_time};
+use aster_time::read_start_time;
 use spin::Once;
 use time::{Date, Month, PrimitiveDateTime, Time};
 
@@ -41,10 +41,8 @@ impl SystemTime {
     /// Returns the current system time
     pub fn now() -> Self {
         // The get real time result should always be valid
-        START_TIME
-            .get()
-            .unwrap()
-            .checked_add(read_monotonic_time())
+        SystemTime::UNIX_EPOCH
+            .checked_add(super::timekeeping::read_realtime())
             .unwrap()
     }
 
@@ -79,6 +77,19 @@ impl SystemTime {
         let now = SystemTime::now();
         now.duration_since(self)
     }
+
+    /// Converts the time to the format of the RTC.
+    pub(super) fn to_rtc_time(&self) -> aster_time::SystemTime {
+        aster_time::SystemTime {
+            year: self.0.year() as u16,
+            month: self.0.month() as u8,
+            day: self.0.day(),
+            hour: self.0.hour(),
+            minute: self.0.minute(),
+            second: self.0.second(),
+            nanos: self.0.nanosecond() as u64,
+        }
+    }
 }
 
 /// convert ostd::time::Time to System time
@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timekeeping of the real time.
+//!
+//! The real time (`CLOCK_REALTIME`) is maintained as an offset to the
+//! monotonic time. The offset starts as the time read from the RTC at boot,
+//! and can later be
+//!
+//! - stepped to a new value (e.g., `clock_settime` and `settimeofday`), or
+//! - slewed gradually (e.g., `adjtimex`), so that the real time never jumps.
+//!
+//! Slewing happens in the timer softirq. In each tick, the offset is adjusted
+//! by the frequency correction (including the one implied by the tick length),
+//! as well as by a part of the remaining offset correction, which is bounded
+//! by [`MAX_SLEW_PPM`] of the elapsed time.
+
+use core::time::Duration;
+
+use aster_time::read_monotonic_time;
+use spin::Once;
+
+use super::{system_time::START_TIME_AS_DURATION, SystemTime};
+use crate::prelude::*;
+
+/// The maximum rate to slew the offset correction, in parts per million.
+pub const MAX_SLEW_PPM: i64 = 500;
+/// The maximum frequency correction, in parts per million.
+pub const MAX_FREQ_PPM: i64 = 500;
+
+/// The frequency is in parts per million with a 16-bit fractional part.
+const FREQ_SHIFT: u32 = 16;
+/// The nominal length of a clock tick seen by the user space, in
+/// microseconds.
+///
+/// The user space always sees ticks in `USER_HZ` (100), regardless of the
+/// frequency of the system timer.
+pub const USER_TICK_USEC: i64 = 10_000;
+
+static TIMEKEEPER: Once<SpinLock<Timekeeper>> = Once::new();
+
+struct Timekeeper {
+    /// The real time minus the monotonic time, in nanoseconds.
+    offset: u64,
+    /// The monotonic time when the offset is adjusted last time, in
+    /// nanoseconds.
+    last_update: u64,
+    ntp: NtpState,
+}
+
+/// The state of the NTP (Network Time Protocol) clock discipline.
+///
+/// The state is mostly set by the NTP daemons with `adjtimex`, and the kernel
+/// only acts on [`Self::offset`], [`Self::freq`] and [`Self::tick`].
+#[derive(Debug, Clone, Copy)]
+pub struct NtpState {
+    /// The remaining offset correction to slew, in nanoseconds.
+    pub offset: i64,
+    /// The frequency correction, in parts per million with a 16-bit
+    /// fractional part.
+    pub freq: i64,
+    /// The maximum error, in microseconds.
+    pub maxerror: i64,
+    /// The estimated error, in microseconds.
+    pub esterror: i64,
+    /// The clock status bits (`STA_*`).
+    pub status: i32,
+    /// The PLL (phase-locked loop) time constant.
+    pub constant: i64,
+    /// The length of a clock tick, in microseconds.
+    pub tick: i64,
+    /// The offset between the TAI (International Atomic Time) and the UTC,
+    /// in seconds.
+    pub tai: i32,
+}
+
+impl NtpState {
+    /// The clock is not synchronized (`STA_UNSYNC`).
+    pub const STA_UNSYNC: i32 = 0x0040;
+    /// The offsets are in nanoseconds instead of microseconds (`STA_NANO`).
+    pub const STA_NANO: i32 = 0x2000;
+    /// The status bits that cannot be set by the user space (`STA_RONLY`).
+    pub const STA_RONLY: i32 = 0xff00;
+
+    const fn new() -> Self {
+        Self {
+            offset: 0,
+            freq: 0,
+            maxerror: 16_000_000,
+            esterror: 16_000_000,
+            status: Self::STA_UNSYNC,
+            constant: 2,
+            tick: USER_TICK_USEC,
+            tai: 0,
+        }
+    }
+
+    /// Returns the total frequency correction, including the one implied by
+    /// the tick length.
+    fn total_freq(&self) -> i64 {
+        let tick_ppm = (self.tick - USER_TICK_USEC) * 1_000_000 / USER_TICK_USEC;
+        self.freq + scaled_ppm(tick_ppm)
+    }
+}
+
+impl Timekeeper {
+    /// Applies the corrections for the time elapsed since the last update.
+    ///
+    /// Returns whether the offset is changed.
+    fn update(&mut self, now: u64) -> bool {
+        let elapsed = now.saturating_sub(self.last_update) as i128;
+        self.last_update = now;
+        let freq = self.ntp.total_freq();
+        if self.ntp.offset == 0 && freq == 0 {
+            return false;
+        }
+
+        let freq_adjustment = elapsed * freq as i128 / (1_000_000 << FREQ_SHIFT);
+        let max_slew = (elapsed * MAX_SLEW_PPM as i128 / 1_000_000) as i64;
+        let slew = self.ntp.offset.clamp(-max_slew, max_slew);
+        self.ntp.offset -= slew;
+
+        let offset = self.offset as i128 + freq_adjustment + slew as i128;
+        self.offset = offset.clamp(0, u64::MAX as i128) as u64;
+        true
+    }
+
+    fn set_offset(&mut self, offset: u64) {
+        self.offset = offset;
+        // A step cancels the pending slew, as `settimeofday` does in Linux.
+        self.ntp.offset = 0;
+    }
+}
+
+fn now_ns() -> u64 {
+    read_monotonic_time().as_nanos() as u64
+}
+
+fn timekeeper() -> &'static SpinLock<Timekeeper> {
+    TIMEKEEPER.get().unwrap()
+}
+
+/// Returns the real time, measured since the Unix epoch.
+pub fn read_realtime() -> Duration {
+    let offset = timekeeper().disable_irq().lock().offset;
+    read_monotonic_time() + Duration::from_nanos(offset)
+}
+
+/// Returns the offset of the real time to the monotonic time.
+pub fn realtime_offset() -> Duration {
+    Duration::from_nanos(timekeeper().disable_irq().lock().offset)
+}
+
+/// Steps the real time to the new time, measured since the Unix epoch.
+///
+/// The new time cannot be earlier than the boot time, i.e., the monotonic
+/// time. The RTC is also updated with the new time.
+pub fn set_realtime(time: Duration) -> Result<()> {
+    {
+        let mut timekeeper = timekeeper().disable_irq().lock();
+        let now = now_ns();
+        let Some(offset) = (time.as_nanos() as u64).checked_sub(now) else {
+            return_errno_with_message!(Errno::EINVAL, "the time is earlier than the boot time");
+        };
+        timekeeper.last_update = now;
+        timekeeper.set_offset(offset);
+    }
+
+    on_realtime_changed(true);
+    Ok(())
+}
+
+/// Steps the real time by the signed delta, in nanoseconds.
+pub fn step_realtime(delta: i64) -> Result<()> {
+    {
+        let mut timekeeper = timekeeper().disable_irq().lock();
+        let now = now_ns();
+        timekeeper.update(now);
+        let Some(offset) = timekeeper.offset.checked_add_signed(delta) else {
+            return_errno_with_message!(Errno::EINVAL, "the time is earlier than the boot time");
+        };
+        timekeeper.set_offset(offset);
+    }
+
+    on_realtime_changed(true);
+    Ok(())
+}
+
+/// Reads or updates the NTP state with the closure.
+///
+/// The pending corrections are applied before the closure is called.
+pub fn with_ntp_state<R>(f: impl FnOnce(&mut NtpState) -> R) -> R {
+    let result = {
+        let mut timekeeper = timekeeper().disable_irq().lock();
+        timekeeper.update(now_ns());
+        f(&mut timekeeper.ntp)
+    };
+
+    on_realtime_changed(false);
+    result
+}
+
+fn on_realtime_changed(sync_rtc: bool) {
+    crate::vdso::update_vdso_realtime();
+
+    if sync_rtc {
+        let now = SystemTime::now();
+        aster_time::write_real_time(now.to_rtc_time());
+    }
+}
+
+/// Applies the pending corrections in each tick.
+fn update_on_tick() {
+    let is_changed = timekeeper().disable_irq().lock().update(now_ns());
+    if is_changed {
+        crate::vdso::update_vdso_realtime();
+    }
+}
+
+pub(super) fn init() {
+    let offset = START_TIME_AS_DURATION.get().unwrap().as_nanos() as u64;
+    TIMEKEEPER.call_once(|| {
+        SpinLock::new(Timekeeper {
+            offset,
+            last_update: now_ns(),
+            ntp: NtpState::new(),
+        })
+    });
+    super::softirq::register_callback(update_on_tick);
+}
+
+/// Converts a frequency in parts per million to the scaled form in
+/// [`NtpState::freq`].
+pub const fn scaled_ppm(ppm: i64) -> i64 {
+    ppm << FREQ_SHIFT
+}
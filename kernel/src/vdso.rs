@@ -11,8 +11,8 @@
 //! necessary time-related information, and a Virtual Memory Object (VMO) that encapsulates both the data and the
 //! VDSO routines. The VMO is intended to be mapped into the address space of every user space process for efficient access.
 //!
-//! The module is initialized with `init`, which sets up the `START_SECS_COUNT` and prepares the VDSO instance for
-//! use. It also hooks up the VDSO data update routine to the time management subsystem for periodic updates.
+//! The module is initialized with `init`, which prepares the VDSO instance for use. It also hooks up the VDSO data
+//! update routine to the time management subsystem for periodic updates.
 
 use alloc::{boxed::Box, sync::Arc};
 use core::{mem::ManuallyDrop, time::Duration};
@@ -30,7 +30,7 @@ use spin::Once;
 use crate::{
     fs::fs_resolver::{FsPath, FsResolver, AT_FDCWD},
     syscall::ClockId,
-    time::{clocks::MonotonicClock, timer::Timeout, SystemTime, START_TIME},
+    time::{clocks::MonotonicClock, timekeeping, timer::Timeout},
     vm::vmo::{Vmo, VmoOptions},
 };
 
@@ -38,7 +38,6 @@ const CLOCK_TAI: usize = 11;
 const VDSO_BASES: usize = CLOCK_TAI + 1;
 const DEFAULT_CLOCK_MODE: VdsoClockMode = VdsoClockMode::Tsc;
 
-static START_SECS_COUNT: Once<u64> = Once::new();
 static VDSO: Once<Arc<Vdso>> = Once::new();
 
 #[derive(Debug, Copy, Clone)]
@@ -161,29 +160,31 @@ impl VdsoData {
 
     fn update_high_res_instant(&mut self, instant: Instant, instant_cycles: u64) {
         self.last_cycles = instant_cycles;
+        let realtime_instant = instant + timekeeping::realtime_offset();
         for clock_id in HIGH_RES_CLOCK_IDS {
-            let secs = if clock_id == ClockId::CLOCK_REALTIME {
-                instant.secs() + START_SECS_COUNT.get().unwrap()
+            let instant = if clock_id == ClockId::CLOCK_REALTIME {
+                realtime_instant
             } else {
-                instant.secs()
+                instant
             };
 
             self.update_clock_instant(
                 clock_id as usize,
-                secs,
+                instant.secs(),
                 (instant.nanos() as u64) << self.shift as u64,
             );
         }
     }
 
     fn update_coarse_res_instant(&mut self, instant: Instant) {
+        let realtime_instant = instant + timekeeping::realtime_offset();
         for clock_id in COARSE_RES_CLOCK_IDS {
-            let secs = if clock_id == ClockId::CLOCK_REALTIME_COARSE {
-                instant.secs() + START_SECS_COUNT.get().unwrap()
+            let instant = if clock_id == ClockId::CLOCK_REALTIME_COARSE {
+                realtime_instant
             } else {
-                instant.secs()
+                instant
             };
-            self.update_clock_instant(clock_id as usize, secs, instant.nanos() as u64);
+            self.update_clock_instant(clock_id as usize, instant.secs(), instant.nanos() as u64);
         }
     }
 }
@@ -302,14 +303,17 @@ fn update_vdso_coarse_res_instant() {
     VDSO.get().unwrap().update_coarse_res_instant(instant);
 }
 
-/// Init `START_SECS_COUNT`, which is used to record the seconds passed since 1970-01-01 00:00:00.
-fn init_start_secs_count() {
-    let time_duration = START_TIME
-        .get()
-        .unwrap()
-        .duration_since(&SystemTime::UNIX_EPOCH)
-        .unwrap();
-    START_SECS_COUNT.call_once(|| time_duration.as_secs());
+/// Update the `VdsoInstant` for `CLOCK_REALTIME` and `CLOCK_REALTIME_COARSE` in Vdso
+/// after the offset of the real time is changed.
+pub(crate) fn update_vdso_realtime() {
+    // The real time can be changed before the VDSO is initialized.
+    let Some(vdso) = VDSO.get() else {
+        return;
+    };
+
+    let (last_instant, last_cycles) = aster_time::default_clocksource().last_record();
+    vdso.update_high_res_instant(last_instant, last_cycles);
+    vdso.update_coarse_res_instant(Instant::from(read_monotonic_time()));
 }
 
 fn init_vdso() {
@@ -319,7 +323,6 @@ fn init_vdso() {
 
 /// Init this module.
 pub(super) fn init() {
-    init_start_secs_count();
     init_vdso();
     aster_time::VDSO_DATA_HIGH_RES_UPDATE_FN.call_once(|| Arc::new(update_vdso_high_res_instant));
 
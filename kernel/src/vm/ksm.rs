@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Kernel same-page merging (KSM).
+//!
+//! The KSM daemon periodically scans the pages of the private anonymous
+//! mappings that are advised with `MADV_MERGEABLE`, and merges the pages with
+//! identical contents into a single frame, which is called a KSM page. A KSM
+//! page is always mapped read-only. Writing to it triggers a page fault, which
+//! breaks the sharing by copy-on-write, as for the pages shared after fork.
+//!
+//! Like Linux, the pages to merge are found with two tables, which are indexed
+//! by the checksums of the page contents:
+//!  - The stable table records the KSM pages, whose contents never change.
+//!  - The unstable table records the pages that are scanned but not merged in
+//!    the current pass. It is rebuilt in each pass, since the pages are still
+//!    writable and their contents may change after being scanned.
+//!
+//! A page is merged only if its checksum is unchanged since the last pass, so
+//! that the frequently written pages are not write-protected in vain.
+
+use alloc::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+use core::time::Duration;
+
+use ostd::{
+    mm::{Paddr, UFrame, UntypedMem},
+    sync::WaitQueue,
+};
+
+use crate::{
+    prelude::*,
+    process::{process_table, Process},
+    thread::kernel_thread::ThreadOptions,
+};
+
+/// The physical addresses of the KSM pages.
+static KSM_PAGES: SpinLock<BTreeSet<Paddr>> = SpinLock::new(BTreeSet::new());
+
+/// Returns whether the frame is a KSM page.
+pub fn is_ksm_page(frame: &UFrame) -> bool {
+    KSM_PAGES.lock().contains(&frame.start_paddr())
+}
+
+/// Spawns the kernel thread that merges the identical pages.
+pub(super) fn spawn_ksm_thread() {
+    ThreadOptions::new(ksm_loop).spawn();
+}
+
+fn ksm_loop() {
+    /// The interval between two rounds of scanning.
+    const SCAN_INTERVAL: Duration = Duration::from_millis(20);
+    /// The maximum number of pages to scan in each round, which is the same
+    /// as the default of Linux.
+    const PAGES_TO_SCAN: usize = 100;
+
+    let sleep_queue = WaitQueue::new();
+    let mut scanner = Scanner::new();
+
+    loop {
+        let _ = sleep_queue.wait_until_or_timeout(|| -> Option<()> { None }, &SCAN_INTERVAL);
+        scanner.scan(PAGES_TO_SCAN);
+    }
+}
+
+struct Scanner {
+    /// The stable table, which maps the checksums to the KSM pages.
+    stable: BTreeMap<u64, Vec<UFrame>>,
+    /// The unstable table of the current pass, which maps the checksums to
+    /// the scanned pages.
+    unstable: BTreeMap<u64, UnstablePage>,
+    /// The checksums of the pages computed in the last pass, indexed by the
+    /// physical addresses of the pages.
+    last_checksums: BTreeMap<Paddr, u64>,
+    /// The checksums of the pages computed in the current pass.
+    checksums: BTreeMap<Paddr, u64>,
+    /// The processes that are not fully scanned in the current pass.
+    processes: VecDeque<Weak<Process>>,
+    /// The address to resume scanning the first process in `processes` from.
+    next_addr: Vaddr,
+}
+
+/// A page recorded in the unstable table.
+struct UnstablePage {
+    process: Weak<Process>,
+    addr: Vaddr,
+    paddr: Paddr,
+}
+
+impl Scanner {
+    fn new() -> Self {
+        Self {
+            stable: BTreeMap::new(),
+            unstable: BTreeMap::new(),
+            last_checksums: BTreeMap::new(),
+            checksums: BTreeMap::new(),
+            processes: VecDeque::new(),
+            next_addr: 0,
+        }
+    }
+
+    /// Scans at most `nr_to_scan` mergeable pages.
+    ///
+    /// The scan resumes from where the last scan stops, and a new pass is
+    /// started if all the processes are scanned in the current pass. The pages
+    /// are scanned in small batches, and only the frames being compared are
+    /// held, so that the extra references do not prevent the frames from
+    /// being reused without copying on write faults or being swapped out.
+    fn scan(&mut self, mut nr_to_scan: usize) {
+        /// The maximum number of pages to collect from a process at a time.
+        const BATCH_SIZE: usize = 16;
+
+        let mut has_started_pass = false;
+        while nr_to_scan > 0 {
+            let Some(process) = self.processes.front() else {
+                // Do not start another pass in a round if there are no
+                // mergeable pages at all.
+                if has_started_pass {
+                    return;
+                }
+                self.start_pass();
+                has_started_pass = true;
+                continue;
+            };
+            let Some(process) = process.upgrade() else {
+                self.processes.pop_front();
+                self.next_addr = 0;
+                continue;
+            };
+
+            let root_vmar = process.root_vmar();
+            let pages = root_vmar.mergeable_pages(self.next_addr, BATCH_SIZE.min(nr_to_scan));
+            let Some(&last_addr) = pages.last() else {
+                self.processes.pop_front();
+                self.next_addr = 0;
+                continue;
+            };
+            self.next_addr = last_addr + PAGE_SIZE;
+            nr_to_scan -= pages.len();
+
+            for addr in pages {
+                let Some(frame) = root_vmar.mergeable_frame(addr) else {
+                    continue;
+                };
+                if is_ksm_page(&frame) {
+                    continue;
+                }
+
+                let checksum = checksum(&frame);
+                let last_checksum = self.last_checksums.get(&frame.start_paddr()).copied();
+                self.checksums.insert(frame.start_paddr(), checksum);
+                if last_checksum != Some(checksum) {
+                    continue;
+                }
+
+                self.merge_page(&process, addr, &frame, checksum);
+            }
+        }
+    }
+
+    /// Starts a new pass of scanning all the mergeable pages.
+    fn start_pass(&mut self) {
+        self.last_checksums = core::mem::take(&mut self.checksums);
+        self.unstable.clear();
+
+        // The KSM pages that are only referenced by the stable table are no
+        // longer mapped, and can be freed.
+        self.stable.retain(|_, frames| {
+            frames.retain(|frame| frame.reference_count() > 1);
+            !frames.is_empty()
+        });
+        *KSM_PAGES.lock() = self
+            .stable
+            .values()
+            .flatten()
+            .map(|frame| frame.start_paddr())
+            .collect();
+
+        self.processes = process_table::process_table_mut()
+            .iter()
+            .map(Arc::downgrade)
+            .collect();
+        self.next_addr = 0;
+    }
+
+    /// Merges the page mapped at `addr` of the process with an identical page
+    /// in the stable table or the unstable table.
+    ///
+    /// If no identical page is found, the page is inserted into the unstable
+    /// table.
+    fn merge_page(&mut self, process: &Arc<Process>, addr: Vaddr, frame: &UFrame, checksum: u64) {
+        let root_vmar = process.root_vmar();
+
+        if let Some(ksm_frames) = self.stable.get(&checksum) {
+            if let Some(ksm_frame) = ksm_frames
+                .iter()
+                .find(|ksm_frame| is_same_content(ksm_frame, frame))
+            {
+                root_vmar.merge_page(addr, frame, ksm_frame);
+                return;
+            }
+        }
+
+        let unstable_page = match self.unstable.entry(checksum) {
+            Entry::Vacant(entry) => {
+                entry.insert(UnstablePage {
+                    process: Arc::downgrade(process),
+                    addr,
+                    paddr: frame.start_paddr(),
+                });
+                return;
+            }
+            Entry::Occupied(entry) => entry.remove(),
+        };
+
+        // The page in the unstable table becomes a KSM page. It should be
+        // write-protected before its content is compared.
+        let ksm_frame = unstable_page
+            .process
+            .upgrade()
+            .and_then(|unstable_process| {
+                unstable_process
+                    .root_vmar()
+                    .write_protect_page(unstable_page.addr, unstable_page.paddr)
+            });
+        match ksm_frame {
+            Some(ksm_frame) if root_vmar.merge_page(addr, frame, &ksm_frame) => {
+                KSM_PAGES.lock().insert(ksm_frame.start_paddr());
+                self.stable.entry(checksum).or_default().push(ksm_frame);
+            }
+            _ => {
+                self.unstable.insert(
+                    checksum,
+                    UnstablePage {
+                        process: Arc::downgrade(process),
+                        addr,
+                        paddr: frame.start_paddr(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Computes the checksum of the page content with the FNV-1a hash.
+fn checksum(frame: &UFrame) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut reader = frame.reader();
+    let mut hash = FNV_OFFSET_BASIS;
+    while reader.has_remain() {
+        let word = reader.read_val::<u64>().unwrap();
+        hash = (hash ^ word).wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Returns whether the two pages have the same content.
+pub(super) fn is_same_content(frame: &UFrame, other: &UFrame) -> bool {
+    /// The number of words that are compared at a time.
+    const CHUNK_LEN: usize = 64;
+
+    let mut reader = frame.reader();
+    let mut other_reader = other.reader();
+    while reader.has_remain() {
+        let chunk = reader.read_val::<[u64; CHUNK_LEN]>().unwrap();
+        let other_chunk = other_reader.read_val::<[u64; CHUNK_LEN]>().unwrap();
+        if chunk != other_chunk {
+            return false;
+        }
+    }
+    true
+}
@@ -16,8 +16,18 @@
 //! In Asterinas, VMARs and VMOs, as well as other capabilities, are implemented
 //! as zero-cost capabilities.
 
+pub mod ksm;
 pub mod page_fault_handler;
 pub mod perms;
+pub mod swap;
 pub mod util;
 pub mod vmar;
 pub mod vmo;
+
+pub(super) fn init() {
+    swap::init();
+}
+
+pub(super) fn lazy_init() {
+    ksm::spawn_ksm_thread();
+}
@@ -1,6 +1,20 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::{prelude::*, thread::exception::PageFaultInfo};
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use align_ext::AlignExt;
+use ostd::sync::WaitQueue;
+
+use crate::{
+    events::IoEvents,
+    prelude::*,
+    process::signal::{PollHandle, Pollee},
+    thread::exception::PageFaultInfo,
+    vm::perms::VmPerms,
+};
 
 /// This trait is implemented by structs which can handle a user space page fault.
 pub trait PageFaultHandler {
@@ -9,3 +23,133 @@ pub trait PageFaultHandler {
     /// Returns `Ok` if the page fault is handled successfully, `Err` otherwise.
     fn handle_page_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()>;
 }
+
+/// The handler of the page faults on the missing pages in the mappings that
+/// are registered with a userfaultfd.
+///
+/// Instead of committing a page, the faulting thread is parked, and the fault
+/// is delivered to the user space, which resolves the fault by providing the
+/// page and wakes the thread up.
+pub struct UserFaultHandler {
+    faults: SpinLock<Vec<UserFault>>,
+    next_id: AtomicU64,
+    /// The wait queue of the faulting threads.
+    wait_queue: WaitQueue,
+    pollee: Pollee,
+    /// Whether the userfaultfd is closed.
+    is_released: AtomicBool,
+}
+
+/// A page fault that is delivered to the user space.
+#[derive(Debug, Clone, Copy)]
+pub struct UserFault {
+    id: u64,
+    /// The page-aligned address of the fault.
+    pub address: Vaddr,
+    /// Whether the fault is caused by a write access.
+    pub is_write: bool,
+    /// Whether the fault has been read by the user space.
+    is_delivered: bool,
+}
+
+impl UserFaultHandler {
+    /// Creates a new handler.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            faults: SpinLock::new(Vec::new()),
+            next_id: AtomicU64::new(0),
+            wait_queue: WaitQueue::new(),
+            pollee: Pollee::new(),
+            is_released: AtomicBool::new(false),
+        })
+    }
+
+    /// Delivers the page fault to the user space, and parks the faulting
+    /// thread until the fault is resolved.
+    ///
+    /// Returns `Ok` if the faulting access should be retried, or `Err` with
+    /// `EINTR` if the thread is interrupted by a signal before that.
+    pub fn handle_user_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut faults = self.faults.lock();
+            // The faults are no longer delivered once the userfaultfd is
+            // closed, and the access will be handled as usual on retry.
+            if self.is_released.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            faults.push(UserFault {
+                id,
+                address: page_fault_info.address.align_down(PAGE_SIZE),
+                is_write: page_fault_info.required_perms.contains(VmPerms::WRITE),
+                is_delivered: false,
+            });
+        }
+        self.pollee.notify(IoEvents::IN);
+
+        let res = self
+            .wait_queue
+            .pause_until(|| (!self.is_pending(id)).then_some(()));
+        if res.is_err() {
+            self.faults.lock().retain(|fault| fault.id != id);
+        }
+        res
+    }
+
+    fn is_pending(&self, id: u64) -> bool {
+        self.faults.lock().iter().any(|fault| fault.id == id)
+    }
+
+    /// Takes the oldest page fault that has not been read by the user space.
+    ///
+    /// The faulting thread keeps parked until it is woken up by
+    /// [`Self::wake`].
+    pub fn try_read_fault(&self) -> Result<UserFault> {
+        let mut faults = self.faults.lock();
+        let Some(fault) = faults.iter_mut().find(|fault| !fault.is_delivered) else {
+            return_errno_with_message!(Errno::EAGAIN, "there are no page faults to read");
+        };
+        fault.is_delivered = true;
+        Ok(*fault)
+    }
+
+    /// Wakes up the threads that fault on the pages within the range.
+    pub fn wake(&self, range: &Range<Vaddr>) {
+        self.faults
+            .lock()
+            .retain(|fault| !range.contains(&fault.address));
+        self.wait_queue.wake_all();
+    }
+
+    /// Wakes up all the faulting threads, and stops delivering the faults.
+    ///
+    /// This is called when the userfaultfd is closed.
+    pub fn release(&self) {
+        {
+            let mut faults = self.faults.lock();
+            self.is_released.store(true, Ordering::Relaxed);
+            faults.clear();
+        }
+        self.wait_queue.wake_all();
+    }
+
+    /// Polls the page faults that are not read by the user space.
+    pub fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee.poll_with(mask, poller, || {
+            if self.faults.lock().iter().any(|fault| !fault.is_delivered) {
+                IoEvents::IN
+            } else {
+                IoEvents::empty()
+            }
+        })
+    }
+}
+
+impl Debug for UserFaultHandler {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UserFaultHandler")
+            .field("faults", &*self.faults.lock())
+            .field("is_released", &self.is_released)
+            .finish_non_exhaustive()
+    }
+}
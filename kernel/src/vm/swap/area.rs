@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_block::{BlockDevice, SECTOR_SIZE};
+use ostd::mm::{FrameAllocOptions, UFrame, UntypedMem, VmIo};
+
+use crate::{fs::utils::Inode, prelude::*};
+
+/// The storage of a swap area.
+pub enum SwapBackend {
+    /// A block device.
+    Device(Arc<dyn BlockDevice>),
+    /// A regular file, which is accessed with direct I/O.
+    ///
+    /// The file system should support direct I/O, and the file should not
+    /// contain holes.
+    File(Arc<dyn Inode>),
+}
+
+impl SwapBackend {
+    /// Returns the number of pages in the backend.
+    fn npages(&self) -> usize {
+        match self {
+            SwapBackend::Device(device) => device.metadata().nr_sectors * SECTOR_SIZE / PAGE_SIZE,
+            SwapBackend::File(inode) => inode.size() / PAGE_SIZE,
+        }
+    }
+
+    /// Returns whether the two backends refer to the same storage.
+    pub fn is_same(&self, other: &SwapBackend) -> bool {
+        match (self, other) {
+            (SwapBackend::Device(this), SwapBackend::Device(other)) => Arc::ptr_eq(this, other),
+            (SwapBackend::File(this), SwapBackend::File(other)) => {
+                let (this, other) = (this.metadata(), other.metadata());
+                this.dev == other.dev && this.ino == other.ino
+            }
+            _ => false,
+        }
+    }
+
+    fn read_page(&self, idx: usize, frame: &UFrame) -> Result<()> {
+        let offset = idx * PAGE_SIZE;
+        let mut writer = frame.writer().to_fallible();
+        match self {
+            SwapBackend::Device(device) => device.read(offset, &mut writer)?,
+            SwapBackend::File(inode) => {
+                if inode.read_direct_at(offset, &mut writer)? != PAGE_SIZE {
+                    return_errno_with_message!(Errno::EIO, "the swap file is truncated");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_page(&self, idx: usize, frame: &UFrame) -> Result<()> {
+        let offset = idx * PAGE_SIZE;
+        let mut reader = frame.reader().to_fallible();
+        match self {
+            SwapBackend::Device(device) => device.write(offset, &mut reader)?,
+            SwapBackend::File(inode) => {
+                if inode.write_direct_at(offset, &mut reader)? != PAGE_SIZE {
+                    return_errno_with_message!(Errno::EIO, "the swap file is truncated");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A swap area, which is divided into page-sized slots.
+///
+/// The first slot holds the header written by `mkswap`, so it is never
+/// allocated.
+pub(super) struct SwapArea {
+    backend: SwapBackend,
+    priority: i16,
+    /// The number of usable slots, excluding the header and the bad slots.
+    nr_usable: usize,
+    inner: SpinLock<SwapAreaInner>,
+}
+
+struct SwapAreaInner {
+    /// The reference counts of the slots. A free slot has a count of zero.
+    refcounts: Vec<u32>,
+    nr_free: usize,
+    /// The slot to start searching for a free slot from.
+    next: usize,
+    /// Whether new slots can be allocated.
+    ///
+    /// It becomes `false` when the swap area is being disabled.
+    is_enabled: bool,
+    /// The pages that are being written to the slots.
+    ///
+    /// A page is also kept here if it fails to be written, until the slot is
+    /// freed.
+    cache: BTreeMap<usize, UFrame>,
+}
+
+/// The value of the reference count that marks a slot as never allocated.
+const RESERVED: u32 = u32::MAX;
+
+impl SwapArea {
+    /// Opens a swap area on the backend, which should be set up by `mkswap`.
+    pub(super) fn open(backend: SwapBackend, priority: i16) -> Result<Self> {
+        const MAGIC: &[u8; 10] = b"SWAPSPACE2";
+        // The offsets of the fields in the header, in bytes.
+        const VERSION_OFFSET: usize = 1024;
+        const LAST_PAGE_OFFSET: usize = 1028;
+        const NR_BAD_PAGES_OFFSET: usize = 1032;
+        const BAD_PAGES_OFFSET: usize = 1036;
+
+        let header: UFrame = FrameAllocOptions::new().zeroed(false).alloc_frame()?.into();
+        backend
+            .read_page(0, &header)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the swap header cannot be read"))?;
+
+        let mut magic = [0u8; 10];
+        header.read_bytes(PAGE_SIZE - MAGIC.len(), &mut magic)?;
+        if &magic != MAGIC {
+            return_errno_with_message!(Errno::EINVAL, "the swap signature is not found");
+        }
+        if header.read_val::<u32>(VERSION_OFFSET)? != 1 {
+            return_errno_with_message!(Errno::EINVAL, "the swap version is not supported");
+        }
+
+        let last_page = header.read_val::<u32>(LAST_PAGE_OFFSET)? as usize;
+        let nr_slots = (last_page + 1).min(backend.npages());
+        if nr_slots < 2 {
+            return_errno_with_message!(Errno::EINVAL, "the swap area is too small");
+        }
+
+        let mut refcounts = vec![0; nr_slots];
+        refcounts[0] = RESERVED;
+        let nr_bad_pages = header.read_val::<u32>(NR_BAD_PAGES_OFFSET)? as usize;
+        let max_nr_bad_pages = (PAGE_SIZE - MAGIC.len() - BAD_PAGES_OFFSET) / size_of::<u32>();
+        if nr_bad_pages > max_nr_bad_pages {
+            return_errno_with_message!(Errno::EINVAL, "too many bad pages in the swap area");
+        }
+        for i in 0..nr_bad_pages {
+            let bad_page = header.read_val::<u32>(BAD_PAGES_OFFSET + i * size_of::<u32>())?;
+            if let Some(refcount) = refcounts.get_mut(bad_page as usize) {
+                *refcount = RESERVED;
+            }
+        }
+
+        let nr_usable = refcounts.iter().filter(|count| **count == 0).count();
+        if nr_usable == 0 {
+            return_errno_with_message!(Errno::EINVAL, "no usable slots in the swap area");
+        }
+
+        Ok(Self {
+            backend,
+            priority,
+            nr_usable,
+            inner: SpinLock::new(SwapAreaInner {
+                refcounts,
+                nr_free: nr_usable,
+                next: 1,
+                is_enabled: true,
+                cache: BTreeMap::new(),
+            }),
+        })
+    }
+
+    pub(super) fn backend(&self) -> &SwapBackend {
+        &self.backend
+    }
+
+    pub(super) fn priority(&self) -> i16 {
+        self.priority
+    }
+
+    /// Returns the number of usable slots.
+    pub(super) fn nr_usable(&self) -> usize {
+        self.nr_usable
+    }
+
+    /// Returns the number of free slots.
+    pub(super) fn nr_free(&self) -> usize {
+        self.inner.lock().nr_free
+    }
+
+    /// Enables or disables the allocation of new slots.
+    pub(super) fn set_enabled(&self, is_enabled: bool) {
+        self.inner.lock().is_enabled = is_enabled;
+    }
+
+    /// Allocates a slot for the page, whose reference count is one.
+    ///
+    /// The page is put into the swap cache until it is written to the slot
+    /// with [`Self::write_slot`].
+    pub(super) fn alloc_slot(&self, page: UFrame) -> Option<usize> {
+        let mut inner = self.inner.lock();
+        if !inner.is_enabled || inner.nr_free == 0 {
+            return None;
+        }
+
+        let nr_slots = inner.refcounts.len();
+        let slot = (inner.next..nr_slots)
+            .chain(1..inner.next)
+            .find(|&slot| inner.refcounts[slot] == 0)
+            .unwrap();
+        inner.refcounts[slot] = 1;
+        inner.nr_free -= 1;
+        inner.next = if slot + 1 < nr_slots { slot + 1 } else { 1 };
+        inner.cache.insert(slot, page);
+
+        Some(slot)
+    }
+
+    /// Increases the reference count of the slot.
+    pub(super) fn dup_slot(&self, slot: usize) {
+        let mut inner = self.inner.lock();
+        let refcount = &mut inner.refcounts[slot];
+        debug_assert!(*refcount != 0 && *refcount != RESERVED);
+        *refcount += 1;
+    }
+
+    /// Decreases the reference count of the slot, and frees the slot if the
+    /// count reaches zero.
+    pub(super) fn free_slot(&self, slot: usize) {
+        let mut inner = self.inner.lock();
+        let refcount = &mut inner.refcounts[slot];
+        debug_assert!(*refcount != 0 && *refcount != RESERVED);
+        *refcount -= 1;
+        if *refcount == 0 {
+            inner.nr_free += 1;
+            inner.cache.remove(&slot);
+        }
+    }
+
+    /// Reads the page in the slot into the frame.
+    pub(super) fn read_slot(&self, slot: usize, frame: &UFrame) -> Result<()> {
+        let cached = self.inner.lock().cache.get(&slot).cloned();
+        if let Some(cached) = cached {
+            frame.writer().write(&mut cached.reader());
+        } else {
+            self.backend.read_page(slot, frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the page in the swap cache to the slot.
+    ///
+    /// The page is removed from the swap cache if it is written successfully.
+    pub(super) fn write_slot(&self, slot: usize) -> Result<()> {
+        let Some(page) = self.inner.lock().cache.get(&slot).cloned() else {
+            return Ok(());
+        };
+
+        self.backend.write_page(slot, &page)?;
+
+        // The slot may be freed and allocated again during the write.
+        let mut inner = self.inner.lock();
+        if inner
+            .cache
+            .get(&slot)
+            .is_some_and(|cached| cached.start_paddr() == page.start_paddr())
+        {
+            inner.cache.remove(&slot);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Swapping out anonymous pages.
+//!
+//! A swap area is a block device or a regular file that is set up by `mkswap`
+//! and enabled by `swapon`. It is divided into page-sized slots.
+//!
+//! When the available memory is low, the reclaimer swaps out the private
+//! anonymous pages that are not recently accessed. The page table entry of a
+//! swapped-out page records a [`SwapEntry`], which locates the slot that the
+//! page is written to. Accessing the page triggers a page fault, which reads
+//! the page back from the slot.
+//!
+//! The slots are reference counted, since the swap entries are duplicated
+//! when an address space is forked. A slot is freed once the last swap entry
+//! referring to it is dropped, which is notified by OSTD.
+//!
+//! A page stays in the swap cache until it is written to the slot. Reading
+//! the slot before that copies the page from the swap cache instead.
+
+mod area;
+
+use core::sync::atomic::{AtomicI16, Ordering};
+
+use area::SwapArea;
+pub use area::SwapBackend;
+use ostd::mm::{register_swap_entry_ops, SwapEntry, SwapEntryOps, UFrame};
+
+use crate::{prelude::*, process::process_table};
+
+/// The maximum number of swap areas.
+const MAX_SWAP_AREAS: usize = 32;
+/// The number of bits in the value of a swap entry that index the swap area.
+const AREA_BITS: u32 = MAX_SWAP_AREAS.trailing_zeros();
+/// The number of bits in the value of a swap entry that index the slot.
+const SLOT_BITS: u32 = SwapEntry::NR_BITS - AREA_BITS;
+
+/// The enabled swap areas, indexed by the swap entries.
+static SWAP_AREAS: SpinLock<Vec<Option<Arc<SwapArea>>>> = SpinLock::new(Vec::new());
+
+/// The priority of the next swap area enabled without a specified priority.
+static NEXT_DEFAULT_PRIORITY: AtomicI16 = AtomicI16::new(-2);
+
+pub(super) fn init() {
+    register_swap_entry_ops(SwapEntryOps {
+        dup: |val| {
+            let (area_idx, slot) = decode_entry(val);
+            SWAP_AREAS.lock()[area_idx].as_ref().unwrap().dup_slot(slot);
+        },
+        drop: |val| {
+            let (area_idx, slot) = decode_entry(val);
+            SWAP_AREAS.lock()[area_idx]
+                .as_ref()
+                .unwrap()
+                .free_slot(slot);
+        },
+    });
+}
+
+fn encode_entry(area_idx: usize, slot: usize) -> usize {
+    debug_assert!(slot < (1 << SLOT_BITS));
+    (area_idx << SLOT_BITS) | slot
+}
+
+fn decode_entry(val: usize) -> (usize, usize) {
+    (val >> SLOT_BITS, val & ((1 << SLOT_BITS) - 1))
+}
+
+fn area_of(entry: &SwapEntry) -> (Arc<SwapArea>, usize) {
+    let (area_idx, slot) = decode_entry(entry.val());
+    // The swap area cannot be disabled while the swap entry exists.
+    let area = SWAP_AREAS.lock()[area_idx].clone().unwrap();
+    (area, slot)
+}
+
+/// Enables swapping to the backend.
+///
+/// The swap areas with higher priorities are used first. If the priority is
+/// not specified, it is lower than those of all the existing swap areas.
+pub fn swap_on(backend: SwapBackend, priority: Option<i16>) -> Result<()> {
+    let priority =
+        priority.unwrap_or_else(|| NEXT_DEFAULT_PRIORITY.fetch_sub(1, Ordering::Relaxed));
+    let area = Arc::new(SwapArea::open(backend, priority)?);
+
+    let mut areas = SWAP_AREAS.lock();
+    if areas
+        .iter()
+        .flatten()
+        .any(|other| other.backend().is_same(area.backend()))
+    {
+        return_errno_with_message!(Errno::EBUSY, "the swap area is already enabled");
+    }
+    if let Some(vacant) = areas.iter_mut().find(|area| area.is_none()) {
+        *vacant = Some(area);
+    } else if areas.len() < MAX_SWAP_AREAS {
+        areas.push(Some(area));
+    } else {
+        return_errno_with_message!(Errno::EPERM, "too many swap areas");
+    }
+
+    Ok(())
+}
+
+/// Disables swapping to the backend.
+///
+/// All the pages that are swapped out to the backend are swapped in before
+/// the swap area is disabled.
+pub fn swap_off(backend: &SwapBackend) -> Result<()> {
+    let (area_idx, area) = {
+        let areas = SWAP_AREAS.lock();
+        let Some((area_idx, area)) = areas
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, area)| Some((idx, area.as_ref()?)))
+            .find(|(_, area)| area.backend().is_same(backend))
+        else {
+            return_errno_with_message!(Errno::EINVAL, "the swap area is not enabled");
+        };
+        (area_idx, area.clone())
+    };
+
+    area.set_enabled(false);
+
+    let processes = process_table::process_table_mut()
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    let is_in_area = |entry: &SwapEntry| decode_entry(entry.val()).0 == area_idx;
+    let mut result = Ok(());
+    for process in processes {
+        result = process.root_vmar().swap_in_pages(is_in_area);
+        if result.is_err() {
+            break;
+        }
+    }
+
+    let mut areas = SWAP_AREAS.lock();
+    // The slots may still be used if the pages fail to be swapped in, or if
+    // the swap entries are being duplicated to new processes.
+    if result.is_err() || area.nr_free() != area.nr_usable() {
+        drop(areas);
+        area.set_enabled(true);
+        result?;
+        return_errno_with_message!(Errno::EBUSY, "the swap area is still in use");
+    }
+    areas[area_idx] = None;
+
+    Ok(())
+}
+
+/// Returns the total size and the free size of the swap areas, in bytes.
+pub fn swap_stat() -> (usize, usize) {
+    let areas = SWAP_AREAS
+        .lock()
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    areas.iter().fold((0, 0), |(total, free), area| {
+        (
+            total + area.nr_usable() * PAGE_SIZE,
+            free + area.nr_free() * PAGE_SIZE,
+        )
+    })
+}
+
+/// Allocates a swap entry for the page.
+///
+/// The swap entry refers to a free slot in the swap area with the highest
+/// priority. The page should be unmapped and written to the slot with
+/// [`write_pages`] later.
+///
+/// Returns `None` if there are no free slots.
+pub fn alloc_entry(page: UFrame) -> Option<SwapEntry> {
+    let areas = SWAP_AREAS.lock();
+    let (area_idx, area) = areas
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, area)| Some((idx, area.as_ref()?)))
+        .filter(|(_, area)| area.nr_free() > 0)
+        .max_by_key(|(idx, area)| (area.priority(), core::cmp::Reverse(*idx)))?;
+    let slot = area.alloc_slot(page)?;
+    Some(SwapEntry::new(encode_entry(area_idx, slot)))
+}
+
+/// Writes the pages of the swap entries allocated by [`alloc_entry`] to the
+/// swap areas.
+///
+/// Returns the number of pages that are written successfully.
+pub fn write_pages(entries: Vec<SwapEntry>) -> usize {
+    let mut nr_written = 0;
+    for entry in entries {
+        let (area, slot) = area_of(&entry);
+        match area.write_slot(slot) {
+            Ok(()) => nr_written += 1,
+            Err(err) => warn!("failed to write the swap slot {}: {:?}", slot, err),
+        }
+    }
+    nr_written
+}
+
+/// Reads the page of the swap entry into the frame.
+pub fn read_page(entry: &SwapEntry, frame: &UFrame) -> Result<()> {
+    let (area, slot) = area_of(entry);
+    area.read_slot(slot, frame)
+}
+
+/// Swaps out at most `nr_to_scan` pages of each process that are not
+/// recently accessed.
+///
+/// Returns the number of pages that are swapped out.
+pub fn swap_out_pages(nr_to_scan: usize) -> usize {
+    if swap_stat().1 == 0 {
+        return 0;
+    }
+
+    let processes = process_table::process_table_mut()
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    processes
+        .iter()
+        .map(|process| process.root_vmar().swap_out_pages(nr_to_scan))
+        .sum()
+}
@@ -2,7 +2,7 @@
 
 use ostd::mm::{Frame, FrameAllocOptions, UFrame, UntypedMem};
 
-use crate::prelude::*;
+use crate::{prelude::*, process::cgroup::ChargedFrameMeta};
 
 /// Creates a new `Frame<()>` and initializes it with the contents of the `src`.
 ///
@@ -12,3 +12,21 @@ pub fn duplicate_frame(src: &UFrame) -> Result<Frame<()>> {
     new_frame.writer().write(&mut src.reader());
     Ok(new_frame)
 }
+
+/// Allocates a zeroed private frame, which is charged to the memory cgroup of
+/// the current process.
+pub fn alloc_private_frame() -> Result<Frame<ChargedFrameMeta>> {
+    let meta = ChargedFrameMeta::try_new()?;
+    Ok(FrameAllocOptions::new().alloc_frame_with(meta)?)
+}
+
+/// Creates a new private frame with the contents of the `src`, which is
+/// charged to the memory cgroup of the current process.
+pub fn duplicate_private_frame(src: &UFrame) -> Result<Frame<ChargedFrameMeta>> {
+    let meta = ChargedFrameMeta::try_new()?;
+    let new_frame = FrameAllocOptions::new()
+        .zeroed(false)
+        .alloc_frame_with(meta)?;
+    new_frame.writer().write(&mut src.reader());
+    Ok(new_frame)
+}
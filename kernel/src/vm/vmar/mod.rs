@@ -7,25 +7,34 @@ mod interval_set;
 mod static_cap;
 pub mod vm_mapping;
 
-use core::{num::NonZeroUsize, ops::Range};
+use core::{
+    num::NonZeroUsize,
+    ops::Range,
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+};
 
 use align_ext::AlignExt;
 use aster_rights::Rights;
 use ostd::{
     cpu::CpuExceptionInfo,
-    mm::{tlb::TlbFlushOp, PageFlags, PageProperty, VmSpace, MAX_USERSPACE_VADDR},
+    mm::{
+        tlb::TlbFlushOp, Paddr, PageFlags, PageProperty, SwapEntry, UFrame, VmSpace,
+        MAX_USERSPACE_VADDR,
+    },
 };
 
 use self::{
     interval_set::{Interval, IntervalSet},
-    vm_mapping::{MappedVmo, VmMapping},
+    vm_mapping::{MappedVmo, VmMapping, VmMappingFlags, VmMappingInfo},
 };
-use super::page_fault_handler::PageFaultHandler;
+use super::page_fault_handler::{PageFaultHandler, UserFaultHandler};
 use crate::{
+    fs::path::Dentry,
     prelude::*,
     thread::exception::{handle_page_fault_from_vm_space, PageFaultInfo},
     vm::{
         perms::VmPerms,
+        swap,
         vmo::{Vmo, VmoRightsOp},
     },
 };
@@ -104,6 +113,264 @@ impl<R> Vmar<R> {
     pub fn resize_mapping(&self, map_addr: Vaddr, old_size: usize, new_size: usize) -> Result<()> {
         self.0.resize_mapping(map_addr, old_size, new_size)
     }
+
+    /// Returns the information of all mappings in the VMAR, in the ascending
+    /// order of their addresses.
+    pub fn mappings_info(&self) -> Vec<VmMappingInfo> {
+        self.0.mappings_info()
+    }
+
+    /// Returns the total size of the mappings in the VMAR, excluding the
+    /// mappings that only reserve the address space.
+    pub fn total_vm(&self) -> usize {
+        self.0.total_vm(&(self.base()..self.base() + self.size()))
+    }
+
+    /// Returns the size of the mappings within the specified range, excluding
+    /// the mappings that only reserve the address space.
+    pub fn total_vm_in(&self, range: Range<Vaddr>) -> usize {
+        self.0.total_vm(&range)
+    }
+
+    /// Returns the total size of the locked mappings in the VMAR, excluding the
+    /// mappings that only reserve the address space.
+    pub fn locked_vm(&self) -> usize {
+        self.0.locked_vm(&(self.base()..self.base() + self.size()))
+    }
+
+    /// Returns the size of the locked mappings within the specified range,
+    /// excluding the mappings that only reserve the address space.
+    pub fn locked_vm_in(&self, range: Range<Vaddr>) -> usize {
+        self.0.locked_vm(&range)
+    }
+
+    /// Expands the mapped range `range` to `new_size` bytes in place.
+    ///
+    /// The range must be within a single mapping and end at the end of the
+    /// mapping. Also, the expanded part must not overlap with other mappings.
+    pub fn expand_mapping(&self, range: Range<Vaddr>, new_size: usize) -> Result<()> {
+        self.0.expand_mapping(range, new_size)
+    }
+
+    /// Moves the mapped range `range` to a new range of `new_size` bytes, and
+    /// returns the start address of the new range.
+    ///
+    /// The new range starts from `new_addr` if it is specified, in which case
+    /// the existing mappings in the new range are removed. Otherwise, a free
+    /// region is chosen. The mapped pages are moved by moving the page table
+    /// entries, not by copying.
+    ///
+    /// If `keep_old` is true, the mapping at the old range is kept, but its
+    /// pages are still moved to the new range.
+    ///
+    /// The range must be within a single mapping. If it is larger than
+    /// `new_size` bytes, the part beyond `new_size` bytes is unmapped after the
+    /// new range is allocated, so nothing is unmapped if the request is invalid.
+    pub fn move_mapping(
+        &self,
+        range: Range<Vaddr>,
+        new_size: usize,
+        new_addr: Option<Vaddr>,
+        keep_old: bool,
+    ) -> Result<Vaddr> {
+        self.0.move_mapping(range, new_size, new_addr, keep_old)
+    }
+
+    /// Sets and clears the flags of the mappings in the specified range.
+    ///
+    /// The mappings that fall partially within the range are split, so that
+    /// only the pages within the range are affected.
+    ///
+    /// Returns `Err` with `ENOMEM` if the range is not completely mapped,
+    /// in which case the mapped parts of the range are still updated.
+    pub fn update_mapping_flags(
+        &self,
+        range: Range<Vaddr>,
+        set: VmMappingFlags,
+        clear: VmMappingFlags,
+    ) -> Result<()> {
+        self.0.update_mapping_flags(range, set, clear)
+    }
+
+    /// Discards the pages in the specified range without removing the mappings.
+    ///
+    /// The following accesses to the range will fault in the pages again.
+    /// If `anonymous_only` is true, the range must only contain private
+    /// anonymous mappings.
+    ///
+    /// Returns `Err` with `ENOMEM` if the range is not completely mapped,
+    /// in which case the pages in the mapped parts are still discarded.
+    pub fn discard_pages(&self, range: Range<Vaddr>, anonymous_only: bool) -> Result<()> {
+        self.0.discard_pages(range, anonymous_only)
+    }
+
+    /// Frees the backing storage of the pages in the specified range, e.g.,
+    /// by punching a hole in the mapped file.
+    ///
+    /// The range must only contain shared writable mappings.
+    pub fn remove_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.remove_pages(range)
+    }
+
+    /// Faults in the pages in the specified range as if they are accessed
+    /// with `perms`.
+    pub fn populate_pages(&self, range: Range<Vaddr>, perms: VmPerms) -> Result<()> {
+        self.0.populate_pages(range, perms)
+    }
+
+    /// Locks the pages in the specified range in memory.
+    ///
+    /// The pages are faulted in immediately, unless `on_fault` is true, in
+    /// which case they are locked when they are faulted in. The locked pages
+    /// are never reclaimed.
+    ///
+    /// Returns `Err` with `ENOMEM` if the range is not completely mapped,
+    /// in which case the mapped parts of the range are still locked.
+    pub fn lock_pages(&self, range: Range<Vaddr>, on_fault: bool) -> Result<()> {
+        self.0.lock_pages(range, on_fault)
+    }
+
+    /// Unlocks the pages in the specified range.
+    ///
+    /// Returns `Err` with `ENOMEM` if the range is not completely mapped,
+    /// in which case the mapped parts of the range are still unlocked.
+    pub fn unlock_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.update_mapping_flags(
+            range,
+            VmMappingFlags::empty(),
+            VmMappingFlags::LOCKED | VmMappingFlags::LOCKONFAULT,
+        )
+    }
+
+    /// Sets the flags that the mappings created in the VMAR later will have.
+    ///
+    /// The mappings that only reserve the address space do not get the flags.
+    pub fn set_default_mapping_flags(&self, flags: VmMappingFlags) {
+        self.0.default_flags.store(flags.bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the flags that the mappings created in the VMAR later will have.
+    pub fn default_mapping_flags(&self) -> VmMappingFlags {
+        VmMappingFlags::from_bits_truncate(self.0.default_flags.load(Ordering::Relaxed))
+    }
+
+    /// Reports whether each page in the specified range is resident in
+    /// memory.
+    ///
+    /// Returns `Err` with `ENOMEM` if the range is not completely mapped.
+    pub fn page_residency(&self, range: Range<Vaddr>) -> Result<Vec<bool>> {
+        self.0.page_residency(range)
+    }
+
+    /// Registers the mappings in the specified range with a userfaultfd, so
+    /// that the faults on the missing pages are delivered to `handler`.
+    ///
+    /// The range must be completely mapped by private anonymous mappings that
+    /// are not registered with other userfaultfds.
+    pub fn register_user_fault_handler(
+        &self,
+        range: Range<Vaddr>,
+        handler: &Arc<UserFaultHandler>,
+    ) -> Result<()> {
+        self.0.register_user_fault_handler(range, handler)
+    }
+
+    /// Unregisters the mappings in the specified range from the userfaultfd
+    /// whose handler is `handler`.
+    pub fn unregister_user_fault_handler(
+        &self,
+        range: Range<Vaddr>,
+        handler: &Arc<UserFaultHandler>,
+    ) -> Result<()> {
+        self.0.unregister_user_fault_handler(range, handler)
+    }
+
+    /// Maps `frame` at the missing page at `page_addr`, which resolves the
+    /// faults delivered to `handler`.
+    ///
+    /// The page must be in a mapping registered with the userfaultfd, or
+    /// `Err` with `ENOENT` is returned. If the page is already present, `Err`
+    /// with `EEXIST` is returned.
+    pub fn map_missing_page(
+        &self,
+        page_addr: Vaddr,
+        frame: UFrame,
+        handler: &Arc<UserFaultHandler>,
+    ) -> Result<()> {
+        self.0.map_missing_page(page_addr, frame, handler)
+    }
+
+    /// Reads the file pages in the specified range in advance.
+    pub fn prefetch_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.prefetch_pages(range)
+    }
+
+    /// Swaps out at most `nr_to_scan` pages that are not recently accessed.
+    ///
+    /// The scan resumes from where the last scan stops. Returns the number of
+    /// pages that are swapped out.
+    pub fn swap_out_pages(&self, nr_to_scan: usize) -> usize {
+        self.0.swap_out_pages(nr_to_scan)
+    }
+
+    /// Marks the pages in the specified range as not accessed, so that they
+    /// are the first to be swapped out.
+    ///
+    /// Returns `Err` with `ENOMEM` if the range is not completely mapped,
+    /// in which case the pages in the mapped parts are still deactivated.
+    pub fn deactivate_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.deactivate_pages(range)
+    }
+
+    /// Swaps out the pages in the specified range immediately.
+    ///
+    /// Only the pages that can be swapped out by [`Self::swap_out_pages`]
+    /// are swapped out, and nothing is done if no swap areas are enabled.
+    pub fn page_out_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.page_out_pages(range)
+    }
+
+    /// Swaps in the pages whose swap entries satisfy `filter`.
+    pub fn swap_in_pages(&self, filter: impl Fn(&SwapEntry) -> bool) -> Result<()> {
+        self.0.swap_in_pages(filter)
+    }
+
+    /// Returns the addresses of at most `max_pages` pages from `from` that
+    /// are mapped in the mappings whose pages can be merged by KSM.
+    pub fn mergeable_pages(&self, from: Vaddr, max_pages: usize) -> Vec<Vaddr> {
+        self.0.mergeable_pages(from, max_pages)
+    }
+
+    /// Returns the frame that is mapped at the mergeable page at `page_addr`.
+    pub fn mergeable_frame(&self, page_addr: Vaddr) -> Option<UFrame> {
+        self.0.mergeable_frame(page_addr)
+    }
+
+    /// Write-protects the mergeable page at `page_addr` if the frame at
+    /// `paddr` is still mapped there.
+    ///
+    /// Returns the frame if it is write-protected.
+    pub fn write_protect_page(&self, page_addr: Vaddr, paddr: Paddr) -> Option<UFrame> {
+        self.0.write_protect_page(page_addr, paddr)
+    }
+
+    /// Replaces `frame` that is mapped at the mergeable page at `page_addr`
+    /// with `ksm_frame`, if they have the same content.
+    ///
+    /// Returns whether the page is merged.
+    pub fn merge_page(&self, page_addr: Vaddr, frame: &UFrame, ksm_frame: &UFrame) -> bool {
+        self.0.merge_page(page_addr, frame, ksm_frame)
+    }
+
+    /// Replaces the pages merged by KSM in the range with private copies.
+    pub fn unmerge_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        self.0.unmerge_pages(range)
+    }
+
+    /// Returns the number of the pages that are merged by KSM.
+    pub fn nr_ksm_pages(&self) -> usize {
+        self.0.nr_ksm_pages()
+    }
 }
 
 pub(super) struct Vmar_ {
@@ -115,6 +382,11 @@ pub(super) struct Vmar_ {
     size: usize,
     /// The attached `VmSpace`
     vm_space: Arc<VmSpace>,
+    /// The address to resume scanning the pages to swap out from.
+    swap_scan_addr: AtomicUsize,
+    /// The flags of the mappings that are created later, which are set by
+    /// `mlockall(MCL_FUTURE)`.
+    default_flags: AtomicU32,
 }
 
 struct VmarInner {
@@ -162,11 +434,12 @@ impl VmarInner {
         }
 
         for vm_mapping_addr in mappings_to_remove {
-            let vm_mapping = self.vm_mappings.remove(&vm_mapping_addr).unwrap();
+            let vm_mapping = self.vm_mappings.find_one(&vm_mapping_addr).unwrap();
             let vm_mapping_range = vm_mapping.range();
             let intersected_range = get_intersected_range(&range, &vm_mapping_range);
 
             let (left, taken, right) = vm_mapping.split_range(&intersected_range)?;
+            self.vm_mappings.remove(&vm_mapping_addr);
             if let Some(left) = left {
                 self.vm_mappings.insert(left);
             }
@@ -180,6 +453,74 @@ impl VmarInner {
         Ok(offset..(offset + size))
     }
 
+    /// Splits the mappings that fall partially within the range, and replaces
+    /// the mappings within the range that satisfy `need_update` with the ones
+    /// returned by `update`.
+    fn update_mappings(
+        &mut self,
+        range: &Range<Vaddr>,
+        need_update: impl Fn(&VmMapping) -> bool,
+        update: impl Fn(VmMapping) -> VmMapping,
+    ) -> Result<()> {
+        let update_mappings = self
+            .vm_mappings
+            .find(range)
+            .filter(|vm_mapping| need_update(vm_mapping))
+            .map(|vm_mapping| vm_mapping.map_to_addr())
+            .collect::<Vec<_>>();
+
+        for vm_mapping_addr in update_mappings {
+            let vm_mapping = self.vm_mappings.find_one(&vm_mapping_addr).unwrap();
+            let vm_mapping_range = vm_mapping.range();
+            let intersected_range = get_intersected_range(range, &vm_mapping_range);
+
+            // Split before removing the mapping, so that it is kept on failure.
+            let (left, taken, right) = vm_mapping.split_range(&intersected_range)?;
+            self.vm_mappings.remove(&vm_mapping_addr);
+            self.vm_mappings.insert(update(taken));
+            if let Some(left) = left {
+                self.vm_mappings.insert(left);
+            }
+            if let Some(right) = right {
+                self.vm_mappings.insert(right);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the range of the mapping that contains the whole `range`.
+    ///
+    /// Returns `Err` with `EFAULT` if there is no such mapping.
+    fn find_mapping_range(&self, range: &Range<Vaddr>) -> Result<Range<Vaddr>> {
+        let Some(vm_mapping) = self.vm_mappings.find_one(&range.start) else {
+            return_errno_with_message!(Errno::EFAULT, "the range is not mapped");
+        };
+        let vm_mapping_range = vm_mapping.range();
+        if range.end > vm_mapping_range.end {
+            return_errno_with_message!(Errno::EFAULT, "the range spans multiple mappings");
+        }
+        Ok(vm_mapping_range)
+    }
+
+    /// Checks whether the range is completely covered by mappings.
+    ///
+    /// Returns `Err` with `ENOMEM` if there are unmapped holes in the range.
+    fn check_fully_mapped(&self, range: &Range<Vaddr>) -> Result<()> {
+        let mut last_end = range.start;
+        for vm_mapping in self.vm_mappings.find(range) {
+            let vm_mapping_range = vm_mapping.range();
+            if vm_mapping_range.start > last_end {
+                break;
+            }
+            last_end = vm_mapping_range.end;
+        }
+        if last_end < range.end {
+            return_errno_with_message!(Errno::ENOMEM, "the range is not completely mapped");
+        }
+        Ok(())
+    }
+
     /// Allocates a free region for mapping.
     ///
     /// If no such region is found, return an error.
@@ -245,6 +586,8 @@ impl Vmar_ {
             base,
             size,
             vm_space,
+            swap_scan_addr: AtomicUsize::new(base),
+            default_flags: AtomicU32::new(VmMappingFlags::empty().bits()),
         })
     }
 
@@ -279,12 +622,14 @@ impl Vmar_ {
             if perms == vm_mapping_perms {
                 continue;
             }
-            let vm_mapping = inner.vm_mappings.remove(&vm_mapping_addr).unwrap();
+            let vm_mapping = inner.vm_mappings.find_one(&vm_mapping_addr).unwrap();
             let vm_mapping_range = vm_mapping.range();
             let intersected_range = get_intersected_range(&range, &vm_mapping_range);
 
-            // Protects part of the taken `VmMapping`.
+            // Protects part of the taken `VmMapping`. It is split before being
+            // removed, so that it is kept on failure.
             let (left, taken, right) = vm_mapping.split_range(&intersected_range)?;
+            inner.vm_mappings.remove(&vm_mapping_addr);
 
             let taken = taken.protect(vm_space.as_ref(), perms);
             inner.vm_mappings.insert(taken);
@@ -301,6 +646,368 @@ impl Vmar_ {
         Ok(())
     }
 
+    fn update_mapping_flags(
+        &self,
+        range: Range<Vaddr>,
+        set: VmMappingFlags,
+        clear: VmMappingFlags,
+    ) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        if set.contains(VmMappingFlags::WIPE_ON_FORK)
+            && inner
+                .vm_mappings
+                .find(&range)
+                .any(|vm_mapping| !vm_mapping.is_private_anonymous())
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "only private anonymous mappings can be wiped on fork"
+            );
+        }
+
+        inner.update_mappings(
+            &range,
+            |vm_mapping| (vm_mapping.flags() | set) - clear != vm_mapping.flags(),
+            |vm_mapping| {
+                let new_flags = (vm_mapping.flags() | set) - clear;
+                vm_mapping.set_flags(new_flags)
+            },
+        )?;
+
+        inner.check_fully_mapped(&range)
+    }
+
+    fn register_user_fault_handler(
+        &self,
+        range: Range<Vaddr>,
+        handler: &Arc<UserFaultHandler>,
+    ) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        inner.check_fully_mapped(&range).map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the range is not completely mapped")
+        })?;
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            if !vm_mapping.is_private_anonymous() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "only private anonymous mappings can be registered"
+                );
+            }
+            if vm_mapping
+                .user_fault_handler()
+                .is_some_and(|registered| !Arc::ptr_eq(registered, handler))
+            {
+                return_errno_with_message!(
+                    Errno::EBUSY,
+                    "the mapping is registered with another userfaultfd"
+                );
+            }
+        }
+
+        inner.update_mappings(
+            &range,
+            |vm_mapping| vm_mapping.user_fault_handler().is_none(),
+            |vm_mapping| vm_mapping.set_user_fault_handler(Some(handler.clone())),
+        )
+    }
+
+    fn unregister_user_fault_handler(
+        &self,
+        range: Range<Vaddr>,
+        handler: &Arc<UserFaultHandler>,
+    ) -> Result<()> {
+        let mut inner = self.inner.write();
+
+        inner.update_mappings(
+            &range,
+            |vm_mapping| {
+                vm_mapping
+                    .user_fault_handler()
+                    .is_some_and(|registered| Arc::ptr_eq(registered, handler))
+            },
+            |vm_mapping| vm_mapping.set_user_fault_handler(None),
+        )
+    }
+
+    fn map_missing_page(
+        &self,
+        page_addr: Vaddr,
+        frame: UFrame,
+        handler: &Arc<UserFaultHandler>,
+    ) -> Result<()> {
+        let inner = self.inner.read();
+
+        let Some(vm_mapping) = inner.vm_mappings.find_one(&page_addr) else {
+            return_errno_with_message!(Errno::ENOENT, "the page is not mapped");
+        };
+        if !vm_mapping
+            .user_fault_handler()
+            .is_some_and(|registered| Arc::ptr_eq(registered, handler))
+        {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the mapping is not registered with the userfaultfd"
+            );
+        }
+
+        vm_mapping.map_missing_page(&self.vm_space, page_addr, frame)
+    }
+
+    fn discard_pages(&self, range: Range<Vaddr>, anonymous_only: bool) -> Result<()> {
+        let inner = self.inner.read();
+
+        if anonymous_only
+            && inner
+                .vm_mappings
+                .find(&range)
+                .any(|vm_mapping| !vm_mapping.is_private_anonymous())
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the pages of non-anonymous mappings cannot be freed"
+            );
+        }
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.zap_pages(&self.vm_space, &intersected_range)?;
+        }
+
+        inner.check_fully_mapped(&range)
+    }
+
+    fn remove_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.remove_pages(&self.vm_space, &intersected_range)?;
+        }
+
+        inner.check_fully_mapped(&range)
+    }
+
+    fn populate_pages(&self, range: Range<Vaddr>, perms: VmPerms) -> Result<()> {
+        let inner = self.inner.read();
+
+        // Check before populating any pages, since the range may be too large.
+        inner.check_fully_mapped(&range)?;
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.populate_pages(&self.vm_space, &intersected_range, perms)?;
+        }
+
+        Ok(())
+    }
+
+    fn lock_pages(&self, range: Range<Vaddr>, on_fault: bool) -> Result<()> {
+        let result = if on_fault {
+            self.update_mapping_flags(
+                range.clone(),
+                VmMappingFlags::LOCKED | VmMappingFlags::LOCKONFAULT,
+                VmMappingFlags::empty(),
+            )
+        } else {
+            self.update_mapping_flags(
+                range.clone(),
+                VmMappingFlags::LOCKED,
+                VmMappingFlags::LOCKONFAULT,
+            )
+        };
+
+        if !on_fault {
+            let inner = self.inner.read();
+            for vm_mapping in inner.vm_mappings.find(&range) {
+                let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+                vm_mapping.populate_locked_pages(&self.vm_space, &intersected_range);
+            }
+        }
+
+        result
+    }
+
+    fn page_residency(&self, range: Range<Vaddr>) -> Result<Vec<bool>> {
+        let inner = self.inner.read();
+
+        inner.check_fully_mapped(&range)?;
+        let mut residency = Vec::with_capacity(range.len() / PAGE_SIZE);
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.page_residency(&self.vm_space, &intersected_range, &mut residency)?;
+        }
+
+        Ok(residency)
+    }
+
+    fn prefetch_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.prefetch_pages(&intersected_range)?;
+        }
+
+        inner.check_fully_mapped(&range)
+    }
+
+    fn swap_out_pages(&self, nr_to_scan: usize) -> usize {
+        let mut entries = Vec::new();
+
+        {
+            let inner = self.inner.read();
+            let scan_addr = self.swap_scan_addr.load(Ordering::Relaxed);
+            let mut nr_to_scan = nr_to_scan;
+            let mut next_scan_addr = self.base;
+
+            // Scan from `scan_addr` to the end, then wrap around.
+            'scan: for scan_range in [scan_addr..self.base + self.size, self.base..scan_addr] {
+                if scan_range.is_empty() {
+                    continue;
+                }
+                for vm_mapping in inner.vm_mappings.find(&scan_range) {
+                    let range = get_intersected_range(&scan_range, &vm_mapping.range());
+                    let stop_addr = vm_mapping.swap_out_pages(
+                        &self.vm_space,
+                        &range,
+                        &mut nr_to_scan,
+                        false,
+                        &mut entries,
+                    );
+                    if nr_to_scan == 0 {
+                        next_scan_addr = stop_addr;
+                        break 'scan;
+                    }
+                }
+            }
+
+            self.swap_scan_addr.store(next_scan_addr, Ordering::Relaxed);
+        }
+
+        // The pages are written without holding the lock, since it may take
+        // a long time.
+        swap::write_pages(entries)
+    }
+
+    fn deactivate_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.deactivate_pages(&self.vm_space, &intersected_range)?;
+        }
+
+        inner.check_fully_mapped(&range)
+    }
+
+    fn page_out_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        let mut entries = Vec::new();
+
+        {
+            let inner = self.inner.read();
+            inner.check_fully_mapped(&range)?;
+
+            let mut nr_to_scan = usize::MAX;
+            for vm_mapping in inner.vm_mappings.find(&range) {
+                let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+                vm_mapping.swap_out_pages(
+                    &self.vm_space,
+                    &intersected_range,
+                    &mut nr_to_scan,
+                    true,
+                    &mut entries,
+                );
+            }
+        }
+
+        swap::write_pages(entries);
+        Ok(())
+    }
+
+    fn swap_in_pages(&self, filter: impl Fn(&SwapEntry) -> bool) -> Result<()> {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.iter() {
+            vm_mapping.swap_in_pages(&self.vm_space, &filter)?;
+        }
+
+        Ok(())
+    }
+
+    fn mergeable_pages(&self, from: Vaddr, max_pages: usize) -> Vec<Vaddr> {
+        let inner = self.inner.read();
+
+        let mut pages = Vec::new();
+        let range = from.max(self.base)..self.base + self.size;
+        if range.is_empty() {
+            return pages;
+        }
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            if pages.len() >= max_pages {
+                break;
+            }
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.mergeable_pages(
+                &self.vm_space,
+                &intersected_range,
+                max_pages - pages.len(),
+                &mut pages,
+            );
+        }
+        pages
+    }
+
+    fn mergeable_frame(&self, page_addr: Vaddr) -> Option<UFrame> {
+        let inner = self.inner.read();
+
+        let vm_mapping = inner.vm_mappings.find_one(&page_addr)?;
+        vm_mapping.mergeable_frame(&self.vm_space, page_addr)
+    }
+
+    fn write_protect_page(&self, page_addr: Vaddr, paddr: Paddr) -> Option<UFrame> {
+        let inner = self.inner.read();
+
+        let vm_mapping = inner.vm_mappings.find_one(&page_addr)?;
+        if !vm_mapping.is_mergeable() {
+            return None;
+        }
+        vm_mapping.write_protect_page(&self.vm_space, page_addr, paddr)
+    }
+
+    fn merge_page(&self, page_addr: Vaddr, frame: &UFrame, ksm_frame: &UFrame) -> bool {
+        let inner = self.inner.read();
+
+        inner
+            .vm_mappings
+            .find_one(&page_addr)
+            .is_some_and(|vm_mapping| {
+                vm_mapping.is_mergeable()
+                    && vm_mapping.merge_page(&self.vm_space, page_addr, frame, ksm_frame)
+            })
+    }
+
+    fn unmerge_pages(&self, range: Range<Vaddr>) -> Result<()> {
+        let inner = self.inner.read();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            let intersected_range = get_intersected_range(&range, &vm_mapping.range());
+            vm_mapping.unmerge_pages(&self.vm_space, &intersected_range)?;
+        }
+
+        Ok(())
+    }
+
+    fn nr_ksm_pages(&self) -> usize {
+        let inner = self.inner.read();
+        inner
+            .vm_mappings
+            .iter()
+            .filter(|vm_mapping| vm_mapping.is_private_anonymous())
+            .map(|vm_mapping| vm_mapping.nr_ksm_pages(&self.vm_space))
+            .sum()
+    }
+
     /// Handles user space page fault, if the page fault is successfully handled, return Ok(()).
     pub fn handle_page_fault(&self, page_fault_info: &PageFaultInfo) -> Result<()> {
         let address = page_fault_info.address;
@@ -312,6 +1019,16 @@ impl Vmar_ {
 
         if let Some(vm_mapping) = inner.vm_mappings.find_one(&address) {
             debug_assert!(vm_mapping.range().contains(&address));
+
+            // The thread may be parked for a long time until the user space
+            // resolves the fault, so the lock should be released first.
+            if let Some(handler) =
+                vm_mapping.user_fault_handler_for(&self.vm_space, page_fault_info)
+            {
+                drop(inner);
+                return handler.handle_user_fault(page_fault_info);
+            }
+
             return vm_mapping.handle_page_fault(&self.vm_space, page_fault_info);
         }
 
@@ -323,6 +1040,8 @@ impl Vmar_ {
         self.vm_space.clear().unwrap();
         let mut inner = self.inner.write();
         inner.vm_mappings.clear();
+        self.default_flags
+            .store(VmMappingFlags::empty().bits(), Ordering::Relaxed);
         Ok(())
     }
 
@@ -367,6 +1086,137 @@ impl Vmar_ {
         Ok(())
     }
 
+    fn expand_mapping(&self, range: Range<Vaddr>, new_size: usize) -> Result<()> {
+        debug_assert!(range.start % PAGE_SIZE == 0);
+        debug_assert!(range.end % PAGE_SIZE == 0);
+        debug_assert!(new_size % PAGE_SIZE == 0 && new_size > range.len());
+
+        let mut inner = self.inner.write();
+        let vm_mapping_range = inner.find_mapping_range(&range)?;
+        if range.end != vm_mapping_range.end {
+            return_errno_with_message!(Errno::ENOMEM, "the range is not at the end of a mapping");
+        }
+        let new_end = range
+            .start
+            .checked_add(new_size)
+            .filter(|&new_end| new_end <= self.base + self.size)
+            .ok_or(Error::with_message(Errno::ENOMEM, "the range is too large"))?;
+        if inner
+            .vm_mappings
+            .find(&(range.end..new_end))
+            .next()
+            .is_some()
+        {
+            return_errno_with_message!(Errno::ENOMEM, "the expanded range is occupied");
+        }
+
+        let vm_mapping = inner.vm_mappings.remove(&vm_mapping_range.start).unwrap();
+        let vm_mapping = vm_mapping.enlarge(new_end - range.end);
+        inner.vm_mappings.insert(vm_mapping);
+        Ok(())
+    }
+
+    fn move_mapping(
+        &self,
+        range: Range<Vaddr>,
+        new_size: usize,
+        new_addr: Option<Vaddr>,
+        keep_old: bool,
+    ) -> Result<Vaddr> {
+        debug_assert!(range.start % PAGE_SIZE == 0);
+        debug_assert!(range.end % PAGE_SIZE == 0);
+        debug_assert!(new_size % PAGE_SIZE == 0 && new_size > 0);
+        debug_assert!(!keep_old || new_size >= range.len());
+
+        let mut inner = self.inner.write();
+        let vm_mapping_range = inner.find_mapping_range(&range)?;
+
+        // Allocate the new range before taking the old range out, so that they
+        // never overlap.
+        let new_range = match new_addr {
+            Some(new_addr) => {
+                let new_end = new_addr
+                    .checked_add(new_size)
+                    .filter(|&new_end| new_end <= self.base + self.size)
+                    .ok_or(Error::with_message(
+                        Errno::EINVAL,
+                        "the new range is too large",
+                    ))?;
+                if is_intersected(&range, &(new_addr..new_end)) {
+                    return_errno_with_message!(Errno::EINVAL, "the new range overlaps the old one");
+                }
+                inner.alloc_free_region_exact_truncate(&self.vm_space, new_addr, new_size)?
+            }
+            None => inner.alloc_free_region(new_size, PAGE_SIZE)?,
+        };
+
+        // Shrink the old range only after the request is validated.
+        let range = if new_size < range.len() {
+            let new_end = range.start + new_size;
+            inner.alloc_free_region_exact_truncate(&self.vm_space, new_end, range.end - new_end)?;
+            range.start..new_end
+        } else {
+            range
+        };
+
+        let vm_mapping = inner.vm_mappings.find_one(&vm_mapping_range.start).unwrap();
+        let (left, taken, right) = vm_mapping.split_range(&range)?;
+        inner.vm_mappings.remove(&vm_mapping_range.start);
+        if let Some(left) = left {
+            inner.vm_mappings.insert(left);
+        }
+        if let Some(right) = right {
+            inner.vm_mappings.insert(right);
+        }
+
+        taken.move_pages(&self.vm_space, new_range.start)?;
+
+        let new_mapping = if keep_old {
+            let new_mapping = taken.new_fork()?;
+            inner.vm_mappings.insert(taken);
+            new_mapping
+        } else {
+            taken
+        };
+        inner
+            .vm_mappings
+            .insert(new_mapping.relocate(new_range.start, new_size));
+
+        Ok(new_range.start)
+    }
+
+    fn mappings_info(&self) -> Vec<VmMappingInfo> {
+        let inner = self.inner.read();
+        inner
+            .vm_mappings
+            .iter()
+            .map(|vm_mapping| vm_mapping.info(&self.vm_space))
+            .collect()
+    }
+
+    fn total_vm(&self, range: &Range<Vaddr>) -> usize {
+        let inner = self.inner.read();
+        inner
+            .vm_mappings
+            .find(range)
+            .filter(|vm_mapping| !vm_mapping.perms().is_empty())
+            .map(|vm_mapping| get_intersected_range(range, &vm_mapping.range()).len())
+            .sum()
+    }
+
+    fn locked_vm(&self, range: &Range<Vaddr>) -> usize {
+        let inner = self.inner.read();
+        inner
+            .vm_mappings
+            .find(range)
+            .filter(|vm_mapping| {
+                !vm_mapping.perms().is_empty()
+                    && vm_mapping.flags().contains(VmMappingFlags::LOCKED)
+            })
+            .map(|vm_mapping| get_intersected_range(range, &vm_mapping.range()).len())
+            .sum()
+    }
+
     /// Returns the attached `VmSpace`.
     fn vm_space(&self) -> &Arc<VmSpace> {
         &self.vm_space
@@ -391,12 +1241,21 @@ impl Vmar_ {
             let cur_vmspace = self.vm_space();
             let mut cur_cursor = cur_vmspace.cursor_mut(&range).unwrap();
             for vm_mapping in inner.vm_mappings.iter() {
+                if vm_mapping.flags().contains(VmMappingFlags::DONT_FORK) {
+                    continue;
+                }
                 let base = vm_mapping.map_to_addr();
 
                 // Clone the `VmMapping` to the new VMAR.
                 let new_mapping = vm_mapping.new_fork()?;
                 new_inner.vm_mappings.insert(new_mapping);
 
+                // The child sees zero-filled pages, which will be faulted in
+                // on demand, so the pages need not be copied.
+                if vm_mapping.flags().contains(VmMappingFlags::WIPE_ON_FORK) {
+                    continue;
+                }
+
                 // Protect the mapping and copy to the new page table for COW.
                 cur_cursor.jump(base).unwrap();
                 new_cursor.jump(base).unwrap();
@@ -441,6 +1300,7 @@ impl<R> Vmar<R> {
 pub struct VmarMapOptions<R1, R2> {
     parent: Vmar<R1>,
     vmo: Option<Vmo<R2>>,
+    mapped_file: Option<Dentry>,
     perms: VmPerms,
     vmo_offset: usize,
     vmo_limit: usize,
@@ -465,6 +1325,7 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         Self {
             parent,
             vmo: None,
+            mapped_file: None,
             perms,
             vmo_offset: 0,
             vmo_limit: usize::MAX,
@@ -498,6 +1359,15 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         self
     }
 
+    /// Sets the file that the mapping is created from.
+    ///
+    /// The file is only used to describe the mapping. The pages of the
+    /// mapping are still provided by the VMO.
+    pub fn mapped_file(mut self, mapped_file: Dentry) -> Self {
+        self.mapped_file = Some(mapped_file);
+        self
+    }
+
     /// Sets the offset of the first memory page in the VMO that is to be
     /// mapped into the VMAR.
     ///
@@ -576,6 +1446,7 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
         let Self {
             parent,
             vmo,
+            mapped_file,
             perms,
             vmo_offset,
             vmo_limit,
@@ -608,14 +1479,24 @@ impl<R1, R2> VmarMapOptions<R1, R2> {
 
         // Build the mapping.
         let vmo = vmo.map(|vmo| MappedVmo::new(vmo.to_dyn(), vmo_offset..vmo_limit));
-        let vm_mapping = VmMapping::new(
+        let mut vm_mapping = VmMapping::new(
             NonZeroUsize::new(map_size).unwrap(),
             map_to_addr,
             vmo,
+            mapped_file,
             is_shared,
             handle_page_faults_around,
             perms,
         );
+        let default_flags = parent.default_mapping_flags();
+        if !perms.is_empty() && !default_flags.is_empty() {
+            vm_mapping = vm_mapping.set_flags(default_flags);
+            if default_flags.contains(VmMappingFlags::LOCKED)
+                && !default_flags.contains(VmMappingFlags::LOCKONFAULT)
+            {
+                vm_mapping.populate_locked_pages(parent.vm_space(), &vm_mapping.range());
+            }
+        }
 
         // Add the mapping to the VMAR.
         inner.vm_mappings.insert(vm_mapping);
@@ -672,3 +1553,53 @@ pub fn get_intersected_range(range1: &Range<usize>, range2: &Range<usize>) -> Ra
     debug_assert!(is_intersected(range1, range2));
     range1.start.max(range2.start)..range1.end.min(range2.end)
 }
+
+#[cfg(ktest)]
+mod test {
+    use ostd::{
+        mm::{vm_space::VmItem, HUGE_PAGE_SIZE, PAGE_SIZE},
+        prelude::ktest,
+    };
+
+    use super::*;
+
+    #[ktest]
+    fn huge_page_fault() {
+        let vmar = Vmar::<Rights>::new_root();
+        let addr = vmar
+            .new_map(2 * HUGE_PAGE_SIZE, VmPerms::READ | VmPerms::WRITE)
+            .unwrap()
+            .align(HUGE_PAGE_SIZE)
+            .build()
+            .unwrap();
+        let range = addr..addr + HUGE_PAGE_SIZE;
+        vmar.update_mapping_flags(
+            range.clone(),
+            VmMappingFlags::HUGEPAGE,
+            VmMappingFlags::empty(),
+        )
+        .unwrap();
+
+        let page_fault_info = PageFaultInfo {
+            address: addr + 5 * PAGE_SIZE,
+            required_perms: VmPerms::WRITE,
+        };
+        vmar.handle_page_fault(&page_fault_info).unwrap();
+
+        // The whole huge page should be mapped to physically contiguous and
+        // huge-page-aligned frames.
+        let cursor = vmar.vm_space().cursor(&range).unwrap();
+        let mut nr_pages = 0;
+        let mut start_paddr = None;
+        for item in cursor {
+            let VmItem::Mapped { va, frame, .. } = item else {
+                panic!("the huge page is not fully mapped");
+            };
+            let start_paddr = *start_paddr.get_or_insert(frame.start_paddr());
+            assert_eq!(start_paddr % HUGE_PAGE_SIZE, 0);
+            assert_eq!(frame.start_paddr() - start_paddr, va - addr);
+            nr_pages += 1;
+        }
+        assert_eq!(nr_pages, HUGE_PAGE_SIZE / PAGE_SIZE);
+    }
+}
@@ -7,16 +7,26 @@ use core::{
 };
 
 use align_ext::AlignExt;
+use bitflags::bitflags;
 use ostd::mm::{
-    tlb::TlbFlushOp, vm_space::VmItem, CachePolicy, FrameAllocOptions, PageFlags, PageProperty,
-    UFrame, VmSpace,
+    tlb::TlbFlushOp, vm_space::VmItem, CachePolicy, FrameAllocOptions, Paddr, PageFlags,
+    PageProperty, SwapEntry, UFrame, VmSpace, HUGE_PAGE_SIZE,
 };
 
 use super::interval_set::Interval;
 use crate::{
+    fs::{path::Dentry, utils::FallocMode},
     prelude::*,
+    process::cgroup::ChargedFrameMeta,
     thread::exception::PageFaultInfo,
-    vm::{perms::VmPerms, util::duplicate_frame, vmo::Vmo},
+    vm::{
+        ksm,
+        page_fault_handler::UserFaultHandler,
+        perms::VmPerms,
+        swap,
+        util::{alloc_private_frame, duplicate_private_frame},
+        vmo::Vmo,
+    },
 };
 
 /// Mapping a range of physical pages into a `Vmar`.
@@ -50,6 +60,11 @@ pub(super) struct VmMapping {
     /// The start of the virtual address maps to the start of the range
     /// specified in [`MappedVmo`].
     vmo: Option<MappedVmo>,
+    /// The file that the mapping is created from, if any.
+    ///
+    /// This is only used to describe the mapping to the user space, e.g., in
+    /// `/proc/[pid]/maps`. The pages are always provided by `vmo`.
+    mapped_file: Option<Dentry>,
     /// Whether the mapping is shared.
     ///
     /// The updates to a shared mapping are visible among processes, or carried
@@ -62,6 +77,39 @@ pub(super) struct VmMapping {
     ///
     /// All pages within the same `VmMapping` have the same permissions.
     perms: VmPerms,
+    /// The flags that are set by `madvise` and `mlock`.
+    flags: VmMappingFlags,
+    /// The handler of the faults on the missing pages, which is set when the
+    /// mapping is registered with a userfaultfd.
+    user_fault_handler: Option<Arc<UserFaultHandler>>,
+}
+
+bitflags! {
+    /// The flags of a mapping that describe how the mapping is expected to be
+    /// used, which are set by `madvise` and `mlock`.
+    pub struct VmMappingFlags: u32 {
+        /// The pages are expected to be accessed sequentially.
+        const SEQ_READ     = 1 << 0;
+        /// The pages are expected to be accessed randomly.
+        const RAND_READ    = 1 << 1;
+        /// The mapping is not inherited by the child process on fork.
+        const DONT_FORK    = 1 << 2;
+        /// The child process sees zero-filled pages on fork.
+        const WIPE_ON_FORK = 1 << 3;
+        /// The mapping is excluded from core dumps.
+        const DONT_DUMP    = 1 << 4;
+        /// The mapping is worth backing with huge pages.
+        const HUGEPAGE     = 1 << 5;
+        /// The mapping is not worth backing with huge pages.
+        const NO_HUGEPAGE  = 1 << 6;
+        /// The pages may be merged with identical pages.
+        const MERGEABLE    = 1 << 7;
+        /// The pages are locked in memory and are never reclaimed.
+        const LOCKED       = 1 << 8;
+        /// The pages of a locked mapping are populated on fault, instead of
+        /// when the mapping is locked.
+        const LOCKONFAULT  = 1 << 9;
+    }
 }
 
 impl Interval<Vaddr> for VmMapping {
@@ -77,6 +125,7 @@ impl VmMapping {
         map_size: NonZeroUsize,
         map_to_addr: Vaddr,
         vmo: Option<MappedVmo>,
+        mapped_file: Option<Dentry>,
         is_shared: bool,
         handle_page_faults_around: bool,
         perms: VmPerms,
@@ -85,15 +134,24 @@ impl VmMapping {
             map_size,
             map_to_addr,
             vmo,
+            mapped_file,
             is_shared,
             handle_page_faults_around,
             perms,
+            flags: VmMappingFlags::empty(),
+            user_fault_handler: None,
         }
     }
 
     pub(super) fn new_fork(&self) -> Result<VmMapping> {
         Ok(VmMapping {
             vmo: self.vmo.as_ref().map(|vmo| vmo.dup()).transpose()?,
+            mapped_file: self.mapped_file.clone(),
+            // Memory locks are not inherited by the child process.
+            flags: self.flags - (VmMappingFlags::LOCKED | VmMappingFlags::LOCKONFAULT),
+            // The faults in the child process are not delivered to the
+            // userfaultfd of the parent process.
+            user_fault_handler: None,
             ..*self
         })
     }
@@ -117,11 +175,139 @@ impl VmMapping {
     pub fn perms(&self) -> VmPerms {
         self.perms
     }
+
+    /// Returns the flags of the mapping.
+    pub fn flags(&self) -> VmMappingFlags {
+        self.flags
+    }
+
+    /// Returns whether the mapping is a private anonymous mapping.
+    pub fn is_private_anonymous(&self) -> bool {
+        self.vmo.is_none()
+    }
+
+    /// Collects the information of the mapping, including the statistics of
+    /// the pages that are currently mapped in the VM space.
+    pub(super) fn info(&self, vm_space: &VmSpace) -> VmMappingInfo {
+        let mut info = VmMappingInfo {
+            range: self.range(),
+            perms: self.perms,
+            flags: self.flags,
+            is_shared: self.is_shared,
+            vmo_offset: self.vmo.as_ref().map_or(0, |vmo| vmo.range.start),
+            mapped_file: self.mapped_file.clone(),
+            rss: 0,
+            dirty: 0,
+            referenced: 0,
+            anonymous: 0,
+            swap: 0,
+        };
+
+        let Ok(cursor) = vm_space.cursor(&self.range()) else {
+            return info;
+        };
+        for item in cursor {
+            let prop = match item {
+                VmItem::Mapped { prop, .. } => prop,
+                VmItem::Swapped { .. } => {
+                    info.swap += PAGE_SIZE;
+                    continue;
+                }
+                VmItem::NotMapped { .. } => continue,
+            };
+            info.rss += PAGE_SIZE;
+            if prop.flags.contains(PageFlags::DIRTY) {
+                info.dirty += PAGE_SIZE;
+            }
+            if prop.flags.contains(PageFlags::ACCESSED) {
+                info.referenced += PAGE_SIZE;
+            }
+            // Pages of private mappings become anonymous once they are
+            // written, since they are copied from the VMO on write.
+            if self.vmo.is_none() || (!self.is_shared && prop.flags.contains(PageFlags::W)) {
+                info.anonymous += PAGE_SIZE;
+            }
+        }
+
+        info
+    }
+}
+
+/// A snapshot of a [`VmMapping`] that describes the mapping to the user space.
+///
+/// The sizes of pages are in bytes.
+#[derive(Debug, Clone)]
+pub struct VmMappingInfo {
+    /// The virtual address range of the mapping.
+    pub range: Range<Vaddr>,
+    /// The permissions of the mapping.
+    pub perms: VmPerms,
+    /// The flags of the mapping.
+    pub flags: VmMappingFlags,
+    /// Whether the mapping is shared.
+    pub is_shared: bool,
+    /// The offset in the VMO where the mapping starts.
+    pub vmo_offset: usize,
+    /// The file that the mapping is created from, if any.
+    pub mapped_file: Option<Dentry>,
+    /// The size of the resident pages.
+    pub rss: usize,
+    /// The size of the resident pages that are dirty.
+    pub dirty: usize,
+    /// The size of the resident pages that are accessed.
+    pub referenced: usize,
+    /// The size of the resident pages that are not backed by any file.
+    pub anonymous: usize,
+    /// The size of the pages that are swapped out.
+    pub swap: usize,
 }
 
 /****************************** Page faults **********************************/
 
 impl VmMapping {
+    /// Returns the handler that the page fault should be delivered to, if the
+    /// page is missing and the mapping is registered with a userfaultfd.
+    pub(super) fn user_fault_handler_for(
+        &self,
+        vm_space: &VmSpace,
+        page_fault_info: &PageFaultInfo,
+    ) -> Option<Arc<UserFaultHandler>> {
+        let handler = self.user_fault_handler.as_ref()?;
+        if !self.perms.contains(page_fault_info.required_perms) {
+            return None;
+        }
+
+        let page_addr = page_fault_info.address.align_down(PAGE_SIZE);
+        let mut cursor = vm_space.cursor(&(page_addr..page_addr + PAGE_SIZE)).ok()?;
+        match cursor.query() {
+            Ok(VmItem::NotMapped { .. }) => Some(handler.clone()),
+            _ => None,
+        }
+    }
+
+    /// Maps `frame` at the missing page at `page_addr`, which resolves the
+    /// faults delivered to the userfaultfd.
+    ///
+    /// Returns `Err` with `EEXIST` if the page is already present.
+    pub(super) fn map_missing_page(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        frame: UFrame,
+    ) -> Result<()> {
+        debug_assert!(self.range().contains(&page_addr));
+        debug_assert!(page_addr % PAGE_SIZE == 0);
+
+        let mut cursor = vm_space.cursor_mut(&(page_addr..page_addr + PAGE_SIZE))?;
+        if !matches!(cursor.query()?, VmItem::NotMapped { .. }) {
+            return_errno_with_message!(Errno::EEXIST, "the page is already present");
+        }
+
+        let page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED | PageFlags::DIRTY;
+        cursor.map(frame, PageProperty::new(page_flags, CachePolicy::Writeback));
+        Ok(())
+    }
+
     pub fn handle_page_fault(
         &self,
         vm_space: &VmSpace,
@@ -147,6 +333,10 @@ impl VmMapping {
             return Ok(());
         }
 
+        if self.try_map_huge_page(vm_space, address, is_write)? {
+            return Ok(());
+        }
+
         let mut cursor =
             vm_space.cursor_mut(&(page_aligned_addr..page_aligned_addr + PAGE_SIZE))?;
 
@@ -179,12 +369,16 @@ impl VmMapping {
 
                 let new_flags = PageFlags::W | PageFlags::ACCESSED | PageFlags::DIRTY;
 
+                if self.is_shared {
+                    self.mark_page_dirty(page_aligned_addr)?;
+                }
+
                 if self.is_shared || only_reference {
                     cursor.protect_next(PAGE_SIZE, |p| p.flags |= new_flags);
                     cursor.flusher().issue_tlb_flush(TlbFlushOp::Address(va));
                     cursor.flusher().dispatch_tlb_flush();
                 } else {
-                    let new_frame = duplicate_frame(&frame)?;
+                    let new_frame = duplicate_private_frame(&frame)?;
                     prop.flags |= new_flags;
                     cursor.map(new_frame.into(), prop);
                 }
@@ -193,6 +387,9 @@ impl VmMapping {
                 // Map a new frame to the page fault address.
 
                 let (frame, is_readonly) = self.prepare_page(address, is_write)?;
+                if self.is_shared && is_write {
+                    self.mark_page_dirty(page_aligned_addr)?;
+                }
 
                 let vm_perms = {
                     let mut perms = self.perms;
@@ -212,21 +409,120 @@ impl VmMapping {
 
                 cursor.map(frame, map_prop);
             }
+            VmItem::Swapped { va, entry } => {
+                drop(cursor);
+                self.swap_in_page(vm_space, va, entry, is_write)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the swapped-out page back and maps it at `page_addr`, where the
+    /// swap entry is recorded.
+    fn swap_in_page(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        entry: SwapEntry,
+        write: bool,
+    ) -> Result<()> {
+        // The page is read without holding the cursor, since it may sleep.
+        let frame: UFrame = alloc_private_frame()?.into();
+        swap::read_page(&entry, &frame)?;
+
+        let mut cursor = vm_space.cursor_mut(&(page_addr..page_addr + PAGE_SIZE))?;
+        match cursor.query().unwrap() {
+            VmItem::Swapped {
+                entry: cur_entry, ..
+            } if cur_entry == entry => {}
+            // The page is already swapped in by others, or unmapped.
+            _ => return Ok(()),
+        }
+
+        // Only private pages are swapped out, so the page is not shared and
+        // can be mapped with the permissions of the mapping.
+        let mut page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED;
+        if write {
+            page_flags |= PageFlags::DIRTY;
         }
+        let map_prop = PageProperty::new(page_flags, CachePolicy::Writeback);
+
+        cursor.map(frame, map_prop);
         Ok(())
     }
 
+    /// Tries to handle the page fault by mapping a huge page.
+    ///
+    /// Similar to the "madvise" mode of transparent huge pages in Linux, only
+    /// private anonymous mappings advised with `MADV_HUGEPAGE` are backed with
+    /// huge pages. A huge page is mapped only if the aligned huge page range
+    /// around the fault address is within the mapping and nothing in the range
+    /// is mapped yet.
+    ///
+    /// Returns `false` if the page fault should be handled with a base page,
+    /// which is also the case if no contiguous physical memory is available or
+    /// the huge page exceeds the `memory.max` of the cgroup.
+    fn try_map_huge_page(
+        &self,
+        vm_space: &VmSpace,
+        page_fault_addr: Vaddr,
+        write: bool,
+    ) -> Result<bool> {
+        if !self.is_private_anonymous() || !self.flags.contains(VmMappingFlags::HUGEPAGE) {
+            return Ok(false);
+        }
+
+        let huge_page_addr = page_fault_addr.align_down(HUGE_PAGE_SIZE);
+        let huge_page_range = huge_page_addr..huge_page_addr + HUGE_PAGE_SIZE;
+        if huge_page_range.start < self.map_to_addr || huge_page_range.end > self.map_end() {
+            return Ok(false);
+        }
+
+        let mut cursor = vm_space.cursor_mut(&huge_page_range)?;
+        // The slot that the cursor queries is not smaller than a huge page
+        // only if no page table node is allocated for the range.
+        match cursor.query().unwrap() {
+            VmItem::NotMapped { len, .. } if len >= HUGE_PAGE_SIZE => {}
+            _ => return Ok(false),
+        }
+
+        // Each frame of the huge page is charged to the memory cgroup. The
+        // charged pages are uncharged if the allocation fails.
+        let nr_pages = HUGE_PAGE_SIZE / PAGE_SIZE;
+        let Ok(mut metas) = (0..nr_pages)
+            .map(|_| ChargedFrameMeta::try_new())
+            .collect::<Result<Vec<_>>>()
+        else {
+            return Ok(false);
+        };
+        let Ok(frames) = FrameAllocOptions::new()
+            .align(HUGE_PAGE_SIZE)
+            .alloc_segment_with(nr_pages, |_| metas.pop().unwrap())
+        else {
+            return Ok(false);
+        };
+
+        let mut page_flags = PageFlags::from(self.perms) | PageFlags::ACCESSED;
+        if write {
+            page_flags |= PageFlags::DIRTY;
+        }
+        let map_prop = PageProperty::new(page_flags, CachePolicy::Writeback);
+
+        cursor.map_huge(frames.into(), map_prop);
+        Ok(true)
+    }
+
     fn prepare_page(&self, page_fault_addr: Vaddr, write: bool) -> Result<(UFrame, bool)> {
         let mut is_readonly = false;
         let Some(vmo) = &self.vmo else {
-            return Ok((FrameAllocOptions::new().alloc_frame()?.into(), is_readonly));
+            return Ok((alloc_private_frame()?.into(), is_readonly));
         };
 
         let page_offset = page_fault_addr.align_down(PAGE_SIZE) - self.map_to_addr;
         let Ok(page) = vmo.get_committed_frame(page_offset) else {
             if !self.is_shared {
                 // The page index is outside the VMO. This is only allowed in private mapping.
-                return Ok((FrameAllocOptions::new().alloc_frame()?.into(), is_readonly));
+                return Ok((alloc_private_frame()?.into(), is_readonly));
             } else {
                 return_errno_with_message!(
                     Errno::EFAULT,
@@ -237,17 +533,28 @@ impl VmMapping {
 
         if !self.is_shared && write {
             // Write access to private VMO-backed mapping. Performs COW directly.
-            Ok((duplicate_frame(&page)?.into(), is_readonly))
+            Ok((duplicate_private_frame(&page)?.into(), is_readonly))
         } else {
             // Operations to shared mapping or read access to private VMO-backed mapping.
             // If read access to private VMO-backed mapping triggers a page fault,
             // the map should be readonly. If user next tries to write to the frame,
             // another page fault will be triggered which will performs a COW (Copy-On-Write).
-            is_readonly = !self.is_shared;
+            // Read access to shared mapping is also mapped readonly, so that the
+            // page can be marked dirty when it is written for the first time.
+            is_readonly = !write;
             Ok((page, is_readonly))
         }
     }
 
+    /// Notifies the VMO that the page at the address is written via the
+    /// shared mapping.
+    fn mark_page_dirty(&self, page_addr: Vaddr) -> Result<()> {
+        let Some(vmo) = &self.vmo else {
+            return Ok(());
+        };
+        vmo.mark_page_dirty(page_addr - self.map_to_addr)
+    }
+
     fn handle_page_faults_around(&self, vm_space: &VmSpace, page_fault_addr: Vaddr) -> Result<()> {
         const SURROUNDING_PAGE_NUM: usize = 16;
         const SURROUNDING_PAGE_ADDR_MASK: usize = !(SURROUNDING_PAGE_NUM * PAGE_SIZE - 1);
@@ -263,20 +570,32 @@ impl VmMapping {
         );
 
         let vm_perms = self.perms - VmPerms::WRITE;
+        let page_addr = page_fault_addr.align_down(PAGE_SIZE);
+        let mut swapped_entry = None;
         let mut cursor = vm_space.cursor_mut(&(start_addr..end_addr))?;
-        let operate = move |commit_fn: &mut dyn FnMut() -> Result<UFrame>| {
-            if let VmItem::NotMapped { .. } = cursor.query().unwrap() {
-                // We regard all the surrounding pages as accessed, no matter
-                // if it is really so. Then the hardware won't bother to update
-                // the accessed bit of the page table on following accesses.
-                let page_flags = PageFlags::from(vm_perms) | PageFlags::ACCESSED;
-                let page_prop = PageProperty::new(page_flags, CachePolicy::Writeback);
-                let frame = commit_fn()?;
-                cursor.map(frame, page_prop);
-            } else {
-                let next_addr = cursor.virt_addr() + PAGE_SIZE;
-                if next_addr < end_addr {
-                    let _ = cursor.jump(next_addr);
+        let operate = |commit_fn: &mut dyn FnMut() -> Result<UFrame>| {
+            match cursor.query().unwrap() {
+                VmItem::NotMapped { .. } => {
+                    // We regard all the surrounding pages as accessed, no matter
+                    // if it is really so. Then the hardware won't bother to update
+                    // the accessed bit of the page table on following accesses.
+                    let page_flags = PageFlags::from(vm_perms) | PageFlags::ACCESSED;
+                    let page_prop = PageProperty::new(page_flags, CachePolicy::Writeback);
+                    let frame = commit_fn()?;
+                    cursor.map(frame, page_prop);
+                }
+                item => {
+                    // The page at the fault address is swapped in later
+                    // without holding the cursor.
+                    if let VmItem::Swapped { va, entry } = item {
+                        if va == page_addr {
+                            swapped_entry = Some(entry);
+                        }
+                    }
+                    let next_addr = cursor.virt_addr() + PAGE_SIZE;
+                    if next_addr < end_addr {
+                        let _ = cursor.jump(next_addr);
+                    }
                 }
             }
             Ok(())
@@ -285,6 +604,11 @@ impl VmMapping {
         let start_offset = start_addr - self.map_to_addr;
         let end_offset = end_addr - self.map_to_addr;
         vmo.operate_on_range(&(start_offset..end_offset), operate)?;
+        drop(cursor);
+
+        if let Some(entry) = swapped_entry {
+            self.swap_in_page(vm_space, page_addr, entry, false)?;
+        }
 
         Ok(())
     }
@@ -295,51 +619,63 @@ impl VmMapping {
 impl VmMapping {
     /// Enlarges the mapping by `extra_size` bytes to the high end.
     pub fn enlarge(self, extra_size: usize) -> Self {
+        let map_size = self.map_size.get() + extra_size;
         Self {
-            map_size: NonZeroUsize::new(self.map_size.get() + extra_size).unwrap(),
+            map_size: NonZeroUsize::new(map_size).unwrap(),
+            vmo: self.vmo.map(|vmo| vmo.grow_to(map_size)),
             ..self
         }
     }
 
-    /// Splits the mapping at the specified address.
+    /// Moves the mapping to `new_addr` and resizes it to `new_size` bytes.
     ///
-    /// The address must be within the mapping and page-aligned. The address
-    /// must not be either the start or the end of the mapping.
-    fn split(self, at: Vaddr) -> Result<(Self, Self)> {
-        debug_assert!(self.map_to_addr < at && at < self.map_end());
-        debug_assert!(at % PAGE_SIZE == 0);
-
-        let (mut l_vmo, mut r_vmo) = (None, None);
+    /// The pages that are mapped in the VM space are not moved. See
+    /// [`Self::move_pages`].
+    pub fn relocate(self, new_addr: Vaddr, new_size: usize) -> Self {
+        debug_assert!(new_addr % PAGE_SIZE == 0);
+        debug_assert!(new_size % PAGE_SIZE == 0);
 
-        if let Some(vmo) = self.vmo {
-            let at_offset = vmo.range.start + at - self.map_to_addr;
-
-            let l_range = vmo.range.start..at_offset;
-            let r_range = at_offset..vmo.range.end;
-
-            l_vmo = Some(MappedVmo::new(vmo.vmo.dup()?, l_range));
-            r_vmo = Some(MappedVmo::new(vmo.vmo.dup()?, r_range));
+        Self {
+            map_to_addr: new_addr,
+            map_size: NonZeroUsize::new(new_size).unwrap(),
+            vmo: self.vmo.map(|vmo| vmo.grow_to(new_size)),
+            ..self
         }
+    }
 
-        let left_size = at - self.map_to_addr;
-        let right_size = self.map_size.get() - left_size;
-        let left = Self {
-            map_to_addr: self.map_to_addr,
-            map_size: NonZeroUsize::new(left_size).unwrap(),
-            vmo: l_vmo,
-            ..self
-        };
-        let right = Self {
-            map_to_addr: at,
-            map_size: NonZeroUsize::new(right_size).unwrap(),
-            vmo: r_vmo,
-            ..self
+    /// Returns a new mapping of the part of the mapping within `range`.
+    ///
+    /// The range must be within the mapping and page-aligned. The mapping
+    /// itself is left intact.
+    fn sub_mapping(&self, range: Range<Vaddr>) -> Result<Self> {
+        debug_assert!(self.map_to_addr <= range.start && range.end <= self.map_end());
+        debug_assert!(range.start % PAGE_SIZE == 0 && range.end % PAGE_SIZE == 0);
+
+        let vmo = match &self.vmo {
+            Some(vmo) => {
+                let start = vmo.range.start + (range.start - self.map_to_addr);
+                // The end of the VMO range is a limit, which is kept by the last part.
+                let end = if range.end == self.map_end() {
+                    vmo.range.end
+                } else {
+                    (vmo.range.start + (range.end - self.map_to_addr)).min(vmo.range.end)
+                };
+                Some(MappedVmo::new(vmo.vmo.dup()?, start..end.max(start)))
+            }
+            None => None,
         };
 
-        Ok((left, right))
+        Ok(Self {
+            map_to_addr: range.start,
+            map_size: NonZeroUsize::new(range.len()).unwrap(),
+            vmo,
+            mapped_file: self.mapped_file.clone(),
+            user_fault_handler: self.user_fault_handler.clone(),
+            ..*self
+        })
     }
 
-    /// Splits the mapping at the specified address.
+    /// Splits the mapping by the specified range.
     ///
     /// There are four conditions:
     /// 1. |-outside `range`-| + |------------within `range`------------|
@@ -347,33 +683,35 @@ impl VmMapping {
     /// 3. |-outside `range`-| + |-within `range`-| + |-outside `range`-|
     /// 4. |----------------------within `range` -----------------------|
     ///
-    /// Returns (left outside, within, right outside) if successful.
+    /// Returns (left outside, within, right outside) if successful. The
+    /// mapping itself is left intact, so that it can be replaced with the
+    /// split ones only after the splitting succeeds.
     ///
     /// # Panics
     ///
-    /// Panics if the mapping does not contain the range, or if the start or
-    /// end of the range is not page-aligned.
-    pub fn split_range(self, range: &Range<Vaddr>) -> Result<(Option<Self>, Self, Option<Self>)> {
+    /// Panics if the mapping does not intersect with the range.
+    pub fn split_range(&self, range: &Range<Vaddr>) -> Result<(Option<Self>, Self, Option<Self>)> {
         let mapping_range = self.range();
-        if range.start <= mapping_range.start && mapping_range.end <= range.end {
-            // Condition 4.
-            return Ok((None, self, None));
-        } else if mapping_range.start < range.start {
-            let (left, within) = self.split(range.start).unwrap();
-            if range.end < mapping_range.end {
-                // Condition 3.
-                let (within, right) = within.split(range.end).unwrap();
-                return Ok((Some(left), within, Some(right)));
-            } else {
-                // Condition 1.
-                return Ok((Some(left), within, None));
-            }
-        } else if mapping_range.contains(&range.end) {
-            // Condition 2.
-            let (within, right) = self.split(range.end).unwrap();
-            return Ok((None, within, Some(right)));
-        }
-        panic!("The mapping does not contain the splitting range.");
+        let start = range.start.max(mapping_range.start);
+        let end = range.end.min(mapping_range.end);
+        assert!(
+            start < end,
+            "The mapping does not contain the splitting range."
+        );
+
+        let left = if mapping_range.start < start {
+            Some(self.sub_mapping(mapping_range.start..start)?)
+        } else {
+            None
+        };
+        let within = self.sub_mapping(start..end)?;
+        let right = if end < mapping_range.end {
+            Some(self.sub_mapping(end..mapping_range.end)?)
+        } else {
+            None
+        };
+
+        Ok((left, within, right))
     }
 }
 
@@ -395,7 +733,16 @@ impl VmMapping {
 
         let mut cursor = vm_space.cursor_mut(&range).unwrap();
 
-        let op = |p: &mut PageProperty| p.flags = perms.into();
+        // The read-only pages are not made writable here, since they may be
+        // shared, e.g., after fork or being merged by KSM. Writing to them
+        // triggers page faults, which handle the sharing.
+        let op = |p: &mut PageProperty| {
+            let is_writable = p.flags.contains(PageFlags::W);
+            p.flags = perms.into();
+            if !is_writable {
+                p.flags -= PageFlags::W;
+            }
+        };
         while cursor.virt_addr() < range.end {
             if let Some(va) = cursor.protect_next(range.end - cursor.virt_addr(), op) {
                 cursor.flusher().issue_tlb_flush(TlbFlushOp::Range(va));
@@ -407,6 +754,486 @@ impl VmMapping {
 
         Self { perms, ..self }
     }
+
+    /// Moves the pages mapped in the range of the mapping to the range that
+    /// starts from `new_addr`.
+    ///
+    /// Only the page table entries are moved, so the pages are neither copied
+    /// nor faulted in. The destination range must not contain mapped pages.
+    ///
+    /// Huge pages are split, i.e., they are moved as the base pages in them,
+    /// since the destination may not be aligned to the huge page size.
+    pub(super) fn move_pages(&self, vm_space: &VmSpace, new_addr: Vaddr) -> Result<()> {
+        let range = self.range();
+        let new_range = new_addr..new_addr + range.len();
+
+        let mut mapped_pages = Vec::new();
+        let mut swapped_pages = Vec::new();
+        // The cursor yields the base pages of huge pages, which are mapped
+        // one by one at the destination.
+        for item in vm_space.cursor(&range)? {
+            match item {
+                VmItem::Mapped { va, frame, prop } => mapped_pages.push((va, frame, prop)),
+                VmItem::Swapped { va, entry } => swapped_pages.push((va, entry)),
+                VmItem::NotMapped { .. } => {}
+            }
+        }
+        if mapped_pages.is_empty() && swapped_pages.is_empty() {
+            return Ok(());
+        }
+
+        let mut cursor = vm_space.cursor_mut(&range)?;
+        cursor.unmap(range.len());
+        drop(cursor);
+
+        let mut new_cursor = vm_space.cursor_mut(&new_range)?;
+        for (va, frame, prop) in mapped_pages {
+            new_cursor.jump(va - range.start + new_addr)?;
+            new_cursor.map(frame, prop);
+        }
+        for (va, entry) in swapped_pages {
+            new_cursor.jump(va - range.start + new_addr)?;
+            new_cursor.swap_out(entry);
+        }
+
+        Ok(())
+    }
+
+    /// Changes the flags of the mapping.
+    pub(super) fn set_flags(self, flags: VmMappingFlags) -> Self {
+        Self { flags, ..self }
+    }
+
+    /// Returns the handler of the faults on the missing pages, if any.
+    pub(super) fn user_fault_handler(&self) -> Option<&Arc<UserFaultHandler>> {
+        self.user_fault_handler.as_ref()
+    }
+
+    /// Changes the handler of the faults on the missing pages.
+    pub(super) fn set_user_fault_handler(
+        self,
+        user_fault_handler: Option<Arc<UserFaultHandler>>,
+    ) -> Self {
+        Self {
+            user_fault_handler,
+            ..self
+        }
+    }
+
+    /// Unmaps the pages in the range from the VM space, while keeping the mapping.
+    ///
+    /// The pages will be faulted in again on the next access, which gives
+    /// zero-filled pages for anonymous private mappings, and the up-to-date
+    /// content of the VMO for other mappings.
+    pub(super) fn zap_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        debug_assert!(self.range().start <= range.start && range.end <= self.range().end);
+
+        let mut cursor = vm_space.cursor_mut(range)?;
+        cursor.unmap(range.len());
+
+        Ok(())
+    }
+
+    /// Frees the backing storage of the pages in the range and unmaps them.
+    ///
+    /// This is only allowed for shared writable mappings. For file-backed
+    /// mappings, a hole is punched in the file.
+    pub(super) fn remove_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        if !self.is_shared || !self.perms.contains(VmPerms::WRITE) {
+            return_errno_with_message!(
+                Errno::EACCES,
+                "only shared writable mappings can be removed"
+            );
+        }
+
+        if let Some(vmo) = &self.vmo {
+            let offset = range.start - self.map_to_addr;
+            let len = range.len().min(vmo.size().saturating_sub(offset));
+            if len > 0 {
+                let vmo_offset = vmo.range.start + offset;
+                if let Some(mapped_file) = &self.mapped_file {
+                    mapped_file.inode().fallocate(
+                        FallocMode::PunchHoleKeepSize,
+                        vmo_offset,
+                        len,
+                    )?;
+                } else {
+                    vmo.vmo.decommit(vmo_offset..vmo_offset + len)?;
+                }
+            }
+        }
+
+        self.zap_pages(vm_space, range)
+    }
+
+    /// Faults in the pages in the range, as if they are accessed with `perms`.
+    pub(super) fn populate_pages(
+        &self,
+        vm_space: &VmSpace,
+        range: &Range<Vaddr>,
+        perms: VmPerms,
+    ) -> Result<()> {
+        if !self.perms.contains(perms) {
+            return_errno_with_message!(Errno::EINVAL, "the mapping cannot be populated");
+        }
+
+        for address in range.clone().step_by(PAGE_SIZE) {
+            let page_fault_info = PageFaultInfo {
+                address,
+                required_perms: perms,
+            };
+            self.handle_page_fault(vm_space, &page_fault_info)
+                .map_err(|_| Error::with_message(Errno::EFAULT, "failed to populate the page"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Faults in the pages in the range of a locked mapping.
+    ///
+    /// The private writable pages are faulted in with write accesses, so that
+    /// they are copied in advance. The pages that fail to be faulted in, e.g.,
+    /// the pages beyond the end of the mapped file, are left unpopulated, and
+    /// the mappings that only reserve the address space are skipped.
+    pub(super) fn populate_locked_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) {
+        let perms = if !self.is_shared && self.perms.contains(VmPerms::WRITE) {
+            VmPerms::WRITE
+        } else {
+            self.perms & VmPerms::READ
+        };
+        if perms.is_empty() {
+            return;
+        }
+
+        let _ = self.populate_pages(vm_space, range, perms);
+    }
+
+    /// Reports whether each page in the range is resident in memory, and
+    /// pushes the results to `residency`.
+    ///
+    /// A page is resident if it is mapped in the VM space, or if it is
+    /// committed in the VMO, e.g., cached in the page cache of the mapped
+    /// file. The pages that are swapped out are not resident.
+    pub(super) fn page_residency(
+        &self,
+        vm_space: &VmSpace,
+        range: &Range<Vaddr>,
+        residency: &mut Vec<bool>,
+    ) -> Result<()> {
+        debug_assert!(self.range().start <= range.start && range.end <= self.range().end);
+
+        let is_committed = |va: Vaddr| {
+            self.vmo.as_ref().is_some_and(|vmo| {
+                let offset = va - self.map_to_addr;
+                offset < vmo.size() && vmo.is_page_committed(offset)
+            })
+        };
+
+        let cursor = vm_space.cursor(range)?;
+        for item in cursor {
+            match item {
+                VmItem::Mapped { .. } => residency.push(true),
+                VmItem::Swapped { .. } => residency.push(false),
+                VmItem::NotMapped { va, len } => {
+                    let end = (va + len).min(range.end);
+                    for va in (va..end).step_by(PAGE_SIZE) {
+                        residency.push(is_committed(va));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the pages in the range into the page cache in advance.
+    ///
+    /// This is a no-op for mappings that are not backed by a VMO.
+    pub(super) fn prefetch_pages(&self, range: &Range<Vaddr>) -> Result<()> {
+        let Some(vmo) = &self.vmo else {
+            return Ok(());
+        };
+
+        let offset = range.start - self.map_to_addr;
+        let end = (range.end - self.map_to_addr).min(vmo.size());
+        if offset >= end {
+            return Ok(());
+        }
+        vmo.operate_on_range(&(offset..end), |commit_fn| commit_fn().map(|_| ()))
+    }
+
+    /// Swaps out the pages in the range that are not recently accessed.
+    ///
+    /// At most `nr_to_scan` mapped pages are scanned, and `nr_to_scan` is
+    /// decreased by the number of scanned pages. A scanned page that is
+    /// accessed is marked as not accessed, so that it can be swapped out in
+    /// the next scan if it is not accessed again by then. If
+    /// `ignore_accessed` is true, the scanned pages are swapped out even if
+    /// they are accessed.
+    ///
+    /// Only the pages that are private to the mapping are swapped out, which
+    /// excludes the huge pages and the pages of locked mappings. The swap
+    /// entries of the swapped-out pages are pushed to `entries`. The pages
+    /// should be written to the swap areas with [`swap::write_pages`] after
+    /// that.
+    ///
+    /// Returns the address where the scan stops.
+    pub(super) fn swap_out_pages(
+        &self,
+        vm_space: &VmSpace,
+        range: &Range<Vaddr>,
+        nr_to_scan: &mut usize,
+        ignore_accessed: bool,
+        entries: &mut Vec<SwapEntry>,
+    ) -> Vaddr {
+        debug_assert!(self.range().start <= range.start && range.end <= self.range().end);
+
+        if self.is_shared
+            || self
+                .flags
+                .intersects(VmMappingFlags::HUGEPAGE | VmMappingFlags::LOCKED)
+        {
+            return range.end;
+        }
+        let Ok(mut cursor) = vm_space.cursor_mut(range) else {
+            return range.end;
+        };
+
+        let mut addr = range.start;
+        while addr < range.end && *nr_to_scan > 0 {
+            addr = match cursor.query().unwrap() {
+                VmItem::NotMapped { va, len } => va + len,
+                VmItem::Swapped { va, .. } => va + PAGE_SIZE,
+                VmItem::Mapped { va, frame, prop } => {
+                    *nr_to_scan -= 1;
+                    if !ignore_accessed && prop.flags.contains(PageFlags::ACCESSED) {
+                        cursor.protect_next(PAGE_SIZE, |p| p.flags -= PageFlags::ACCESSED);
+                        cursor.flusher().issue_tlb_flush(TlbFlushOp::Address(va));
+                    } else if frame.reference_count() == 2 {
+                        // The frame is only referenced by the page table and
+                        // `frame`. So it is neither in a VMO nor shared with
+                        // other processes.
+                        let Some(entry) = swap::alloc_entry(frame) else {
+                            break;
+                        };
+                        cursor.swap_out(entry.clone());
+                        entries.push(entry);
+                    }
+                    va + PAGE_SIZE
+                }
+            };
+            if addr < range.end {
+                cursor.jump(addr).unwrap();
+            }
+        }
+        cursor.flusher().dispatch_tlb_flush();
+
+        addr.min(range.end)
+    }
+
+    /// Marks the pages in the range as not accessed, so that they are the
+    /// first to be swapped out.
+    pub(super) fn deactivate_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        debug_assert!(self.range().start <= range.start && range.end <= self.range().end);
+
+        let mut cursor = vm_space.cursor_mut(range)?;
+        while cursor.virt_addr() < range.end {
+            if let Some(va) = cursor.protect_next(range.end - cursor.virt_addr(), |p| {
+                p.flags -= PageFlags::ACCESSED
+            }) {
+                cursor.flusher().issue_tlb_flush(TlbFlushOp::Range(va));
+            } else {
+                break;
+            }
+        }
+        cursor.flusher().dispatch_tlb_flush();
+
+        Ok(())
+    }
+
+    /// Swaps in the pages of the mapping whose swap entries satisfy `filter`.
+    pub(super) fn swap_in_pages(
+        &self,
+        vm_space: &VmSpace,
+        filter: &impl Fn(&SwapEntry) -> bool,
+    ) -> Result<()> {
+        let swapped_pages = vm_space
+            .cursor(&self.range())?
+            .filter_map(|item| match item {
+                VmItem::Swapped { va, entry } if filter(&entry) => Some((va, entry)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (va, entry) in swapped_pages {
+            self.swap_in_page(vm_space, va, entry, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the pages of the mapping can be merged by KSM.
+    ///
+    /// Only the pages of private anonymous mappings advised with
+    /// `MADV_MERGEABLE` are merged. The mappings advised with `MADV_HUGEPAGE`
+    /// are skipped, since merging their pages splits the huge pages.
+    pub(super) fn is_mergeable(&self) -> bool {
+        self.is_private_anonymous()
+            && self.flags.contains(VmMappingFlags::MERGEABLE)
+            && !self.flags.contains(VmMappingFlags::HUGEPAGE)
+    }
+
+    /// Collects the addresses of at most `max_pages` pages that are mapped in
+    /// the range, if the mapping is mergeable.
+    ///
+    /// Only the addresses are collected, since holding the frames prevents
+    /// them from being reused without copying on write faults.
+    pub(super) fn mergeable_pages(
+        &self,
+        vm_space: &VmSpace,
+        range: &Range<Vaddr>,
+        max_pages: usize,
+        pages: &mut Vec<Vaddr>,
+    ) {
+        debug_assert!(self.range().start <= range.start && range.end <= self.range().end);
+
+        if !self.is_mergeable() {
+            return;
+        }
+        let Ok(cursor) = vm_space.cursor(range) else {
+            return;
+        };
+        pages.extend(
+            cursor
+                .filter_map(|item| match item {
+                    VmItem::Mapped { va, .. } => Some(va),
+                    _ => None,
+                })
+                .take(max_pages),
+        );
+    }
+
+    /// Returns the frame that is mapped at the page at `page_addr`, if the
+    /// mapping is mergeable.
+    pub(super) fn mergeable_frame(&self, vm_space: &VmSpace, page_addr: Vaddr) -> Option<UFrame> {
+        if !self.is_mergeable() {
+            return None;
+        }
+        let mut cursor = vm_space.cursor(&(page_addr..page_addr + PAGE_SIZE)).ok()?;
+        match cursor.query().ok()? {
+            VmItem::Mapped { frame, .. } => Some(frame),
+            _ => None,
+        }
+    }
+
+    /// Write-protects the page at `page_addr` if the frame at `paddr` is still
+    /// mapped there, so that the content of the frame cannot be changed via
+    /// the mapping.
+    ///
+    /// Returns the frame if it is write-protected.
+    pub(super) fn write_protect_page(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        paddr: Paddr,
+    ) -> Option<UFrame> {
+        let mut cursor = vm_space
+            .cursor_mut(&(page_addr..page_addr + PAGE_SIZE))
+            .ok()?;
+        let VmItem::Mapped { va, frame, prop } = cursor.query().ok()? else {
+            return None;
+        };
+        if frame.start_paddr() != paddr {
+            return None;
+        }
+
+        if prop.flags.contains(PageFlags::W) {
+            cursor.protect_next(PAGE_SIZE, |p| p.flags -= PageFlags::W);
+            cursor.flusher().issue_tlb_flush(TlbFlushOp::Address(va));
+            cursor.flusher().dispatch_tlb_flush();
+        }
+        Some(frame)
+    }
+
+    /// Replaces `frame` that is mapped at `page_addr` with `ksm_frame`, which
+    /// is shared read-only by the pages with the same content.
+    ///
+    /// The page is write-protected before the contents are compared. Returns
+    /// `false` if the contents differ, or if `frame` is no longer mapped at
+    /// the page.
+    pub(super) fn merge_page(
+        &self,
+        vm_space: &VmSpace,
+        page_addr: Vaddr,
+        frame: &UFrame,
+        ksm_frame: &UFrame,
+    ) -> bool {
+        if self
+            .write_protect_page(vm_space, page_addr, frame.start_paddr())
+            .is_none()
+            || !ksm::is_same_content(frame, ksm_frame)
+        {
+            return false;
+        }
+
+        let Ok(mut cursor) = vm_space.cursor_mut(&(page_addr..page_addr + PAGE_SIZE)) else {
+            return false;
+        };
+        // The page may be written and remapped after it is write-protected.
+        match cursor.query() {
+            Ok(VmItem::Mapped {
+                frame: cur_frame,
+                prop,
+                ..
+            }) if cur_frame.start_paddr() == frame.start_paddr()
+                && !prop.flags.contains(PageFlags::W) =>
+            {
+                cursor.map(ksm_frame.clone(), prop);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replaces the KSM pages mapped in the range with private copies.
+    pub(super) fn unmerge_pages(&self, vm_space: &VmSpace, range: &Range<Vaddr>) -> Result<()> {
+        let ksm_pages = vm_space
+            .cursor(range)?
+            .filter_map(|item| match item {
+                VmItem::Mapped { va, frame, prop } if ksm::is_ksm_page(&frame) => {
+                    Some((va, frame, prop))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (va, frame, prop) in ksm_pages {
+            let new_frame = duplicate_private_frame(&frame)?;
+            let mut cursor = vm_space.cursor_mut(&(va..va + PAGE_SIZE))?;
+            if let VmItem::Mapped {
+                frame: cur_frame, ..
+            } = cursor.query()?
+            {
+                if cur_frame.start_paddr() == frame.start_paddr() {
+                    // The copy stays read-only, and becomes writable without
+                    // copying on the next write fault.
+                    cursor.map(new_frame.into(), prop);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of the KSM pages that are mapped in the mapping.
+    pub(super) fn nr_ksm_pages(&self, vm_space: &VmSpace) -> usize {
+        let Ok(cursor) = vm_space.cursor(&self.range()) else {
+            return 0;
+        };
+        cursor
+            .filter(|item| matches!(item, VmItem::Mapped { frame, .. } if ksm::is_ksm_page(frame)))
+            .count()
+    }
 }
 
 /// A wrapper that represents a mapped [`Vmo`] and provide required functionalities
@@ -428,6 +1255,14 @@ impl MappedVmo {
         self.range.len()
     }
 
+    /// Grows the accessible range so that a mapping of `map_size` bytes can
+    /// access the VMO as far as it goes.
+    fn grow_to(mut self, map_size: usize) -> Self {
+        let end = self.range.start.saturating_add(map_size);
+        self.range.end = self.range.end.max(end);
+        self
+    }
+
     /// Gets the committed frame at the input offset in the mapped VMO.
     ///
     /// If the VMO has not committed a frame at this index, it will commit
@@ -438,6 +1273,21 @@ impl MappedVmo {
         self.vmo.commit_page(self.range.start + page_offset)
     }
 
+    /// Returns whether a page is committed at the input offset in the mapped VMO.
+    fn is_page_committed(&self, page_offset: usize) -> bool {
+        debug_assert!(page_offset < self.range.len());
+        debug_assert!(page_offset % PAGE_SIZE == 0);
+        self.vmo
+            .is_page_committed((self.range.start + page_offset) / PAGE_SIZE)
+    }
+
+    /// Marks the page at the input offset in the mapped VMO as dirty.
+    fn mark_page_dirty(&self, page_offset: usize) -> Result<()> {
+        debug_assert!(page_offset < self.range.len());
+        debug_assert!(page_offset % PAGE_SIZE == 0);
+        self.vmo.mark_page_dirty(self.range.start + page_offset)
+    }
+
     /// Traverses the indices within a specified range of a VMO sequentially.
     ///
     /// For each index position, you have the option to commit the page as well as
@@ -60,6 +60,19 @@ impl Vmo<Rights> {
         self.0.decommit(range)
     }
 
+    /// Marks the page at the offset (in bytes) as dirty.
+    ///
+    /// This is needed if the page is written without going through the VMO,
+    /// e.g., via a shared memory mapping, so that the pager can write it back.
+    ///
+    /// # Access rights
+    ///
+    /// The method requires the Write right.
+    pub fn mark_page_dirty(&self, offset: usize) -> Result<()> {
+        self.check_rights(Rights::WRITE)?;
+        self.0.mark_page_dirty(offset)
+    }
+
     /// Resizes the VMO by giving a new size.
     ///
     /// The VMO must be resizable.
@@ -14,7 +14,10 @@ use ostd::{
     mm::{FrameAllocOptions, UFrame, UntypedMem, VmReader, VmWriter},
 };
 
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    process::{cgroup::MemoryCharge, oom::alloc_or_oom},
+};
 
 mod dyn_cap;
 mod options;
@@ -174,6 +177,11 @@ pub(super) struct Vmo_ {
     flags: VmoFlags,
     /// The virtual pages where the VMO resides.
     pages: Pages,
+    /// The memory charged to a cgroup for the committed pages.
+    ///
+    /// Only the pages of anonymous VMOs that are allocated on demand are
+    /// charged.
+    memory_charge: Option<MemoryCharge>,
 }
 
 impl Debug for Vmo_ {
@@ -204,7 +212,7 @@ impl Vmo_ {
     /// Prepares a new `UFrame` for the target index in pages, returns this new frame.
     fn prepare_page(&self, page_idx: usize) -> Result<UFrame> {
         match &self.pager {
-            None => Ok(FrameAllocOptions::new().alloc_frame()?.into()),
+            None => self.alloc_anonymous_page(),
             Some(pager) => pager.commit_page(page_idx),
         }
     }
@@ -214,7 +222,31 @@ impl Vmo_ {
         if let Some(pager) = &self.pager {
             pager.commit_overwrite(page_idx)
         } else {
-            Ok(FrameAllocOptions::new().alloc_frame()?.into())
+            self.alloc_anonymous_page()
+        }
+    }
+
+    /// Allocates a new `UFrame` for an anonymous VMO, charging it to the cgroup.
+    fn alloc_anonymous_page(&self) -> Result<UFrame> {
+        let alloc_frame = || alloc_or_oom(|| FrameAllocOptions::new().alloc_frame());
+        let Some(memory_charge) = &self.memory_charge else {
+            return Ok(alloc_frame()?.into());
+        };
+
+        memory_charge.try_charge_page()?;
+        match alloc_frame() {
+            Ok(frame) => Ok(frame.into()),
+            Err(err) => {
+                memory_charge.uncharge_page();
+                Err(err)
+            }
+        }
+    }
+
+    /// Uncharges a decommitted page if it is charged.
+    fn uncharge_page(&self, page: Option<UFrame>) {
+        if let (Some(_), Some(memory_charge)) = (page, &self.memory_charge) {
+            memory_charge.uncharge_page();
         }
     }
 
@@ -260,15 +292,52 @@ impl Vmo_ {
                 return_errno_with_message!(Errno::EINVAL, "the offset is outside the VMO");
             }
             let mut cursor = pages.cursor_mut(page_idx as u64);
-            if cursor.remove().is_some()
-                && let Some(pager) = &self.pager
-            {
+            self.uncharge_page(cursor.remove());
+            // The pager may still hold the frame even if it is not committed
+            // in the VMO (e.g., the page cache after reclaiming the page).
+            if let Some(pager) = &self.pager {
                 pager.decommit_page(page_idx)?;
             }
             Ok(())
         })
     }
 
+    /// Decommits the page at the target index if it is only used by the VMO
+    /// and its pager.
+    ///
+    /// Unlike [`Self::decommit_page`], the pager is not notified. So the pager
+    /// can keep the frame and provide it again when the page is committed
+    /// next time.
+    ///
+    /// Returns whether the page is decommitted.
+    fn try_decommit_unused_page(&self, page_idx: usize) -> bool {
+        self.pages.with(|pages, size| {
+            if page_idx >= size / PAGE_SIZE {
+                return false;
+            }
+            let mut cursor = pages.cursor_mut(page_idx as u64);
+            // One reference is held by the VMO and the other by the pager. More
+            // references mean that the page is mapped or being accessed.
+            if cursor
+                .load()
+                .is_none_or(|frame| frame.reference_count() > 2)
+            {
+                return false;
+            }
+            self.uncharge_page(cursor.remove());
+            true
+        })
+    }
+
+    /// Notifies the pager that the page at the target offset is updated
+    /// without going through the VMO, e.g., via a shared memory mapping.
+    pub fn mark_page_dirty(&self, offset: usize) -> Result<()> {
+        let Some(pager) = &self.pager else {
+            return Ok(());
+        };
+        pager.update_page(offset / PAGE_SIZE)
+    }
+
     /// Traverses the indices within a specified range of a VMO sequentially.
     /// For each index position, you have the option to commit the page as well as
     /// perform other operations.
@@ -330,11 +399,19 @@ impl Vmo_ {
         let write_len = reader.remain();
         let write_range = offset..(offset + write_len);
         let mut write_offset = offset % PAGE_SIZE;
+        let mut page_idx = offset / PAGE_SIZE;
 
+        // The pager is notified while the page is still committed, i.e., with
+        // the lock of the pages held. Otherwise, the page may be reclaimed by
+        // the pager before it knows that the page is dirty.
         let mut write = move |commit_fn: &mut dyn FnMut() -> Result<UFrame>| {
             let frame = commit_fn()?;
             frame.writer().skip(write_offset).write_fallible(reader)?;
             write_offset = 0;
+            if let Some(pager) = &self.pager {
+                pager.update_page(page_idx)?;
+            }
+            page_idx += 1;
             Ok(())
         };
 
@@ -357,13 +434,6 @@ impl Vmo_ {
                 self.operate_on_range(&tail_range, &mut write, CommitFlags::empty())?;
             }
         }
-
-        if let Some(pager) = &self.pager {
-            let page_idx_range = get_page_idx_range(&write_range);
-            for page_idx in page_idx_range {
-                pager.update_page(page_idx)?;
-            }
-        }
         Ok(())
     }
 
@@ -405,9 +475,8 @@ impl Vmo_ {
         let page_idx_range = get_page_idx_range(&range);
         let mut cursor = pages.cursor_mut(page_idx_range.start as u64);
         for page_idx in page_idx_range {
-            if cursor.remove().is_some()
-                && let Some(pager) = &self.pager
-            {
+            self.uncharge_page(cursor.remove());
+            if let Some(pager) = &self.pager {
                 pager.decommit_page(page_idx)?;
             }
             cursor.next();
@@ -447,6 +516,40 @@ impl<R> Vmo<R> {
     pub fn flags(&self) -> VmoFlags {
         self.0.flags()
     }
+
+    /// Returns whether the page at the index is committed.
+    pub fn is_page_committed(&self, page_idx: usize) -> bool {
+        self.0.is_page_committed(page_idx)
+    }
+
+    /// Creates a weak reference to the VMO.
+    pub fn downgrade(&self) -> WeakVmo {
+        WeakVmo(Arc::downgrade(&self.0))
+    }
+}
+
+/// A weak reference to a VMO.
+///
+/// It does not keep the VMO alive, and it does not grant any access rights to
+/// the content of the VMO. It is used by the page cache to reclaim the pages
+/// that are committed to the VMO.
+#[derive(Debug, Clone)]
+pub struct WeakVmo(Weak<Vmo_>);
+
+impl WeakVmo {
+    /// Decommits the page at the target index if the page is only used by the
+    /// VMO and its pager.
+    ///
+    /// The pager is not notified, so it can keep the frame and provide it
+    /// again on the next commit.
+    ///
+    /// Returns `false` if the page is not decommitted, or the VMO has been
+    /// dropped.
+    pub fn try_decommit_unused_page(&self, page_idx: usize) -> bool {
+        self.0
+            .upgrade()
+            .is_some_and(|vmo| vmo.try_decommit_unused_page(page_idx))
+    }
 }
 
 /// Gets the page index range that contains the offset range of VMO.
@@ -12,7 +12,14 @@ use ostd::{
 };
 
 use super::{Pager, Pages, Vmo, VmoFlags};
-use crate::{prelude::*, vm::vmo::Vmo_};
+use crate::{
+    prelude::*,
+    process::{
+        cgroup::{Cgroup, MemoryCharge},
+        oom::alloc_or_oom,
+    },
+    vm::vmo::Vmo_,
+};
 
 /// Options for allocating a root VMO.
 ///
@@ -53,6 +60,7 @@ pub struct VmoOptions<R = Rights> {
     flags: VmoFlags,
     rights: Option<R>,
     pager: Option<Arc<dyn Pager>>,
+    cgroup: Option<Arc<Cgroup>>,
 }
 
 impl<R> VmoOptions<R> {
@@ -66,6 +74,7 @@ impl<R> VmoOptions<R> {
             flags: VmoFlags::empty(),
             rights: None,
             pager: None,
+            cgroup: None,
         }
     }
 
@@ -84,6 +93,17 @@ impl<R> VmoOptions<R> {
         self.pager = Some(pager);
         self
     }
+
+    /// Sets the cgroup that the memory of the VMO is charged to.
+    ///
+    /// Only the pages of anonymous VMOs that are allocated on demand are
+    /// charged, i.e., this takes no effect if a pager is set or the VMO is
+    /// contiguous. The allocation fails with `ENOMEM` if the `memory.max` of
+    /// the cgroup is exceeded.
+    pub fn charge_to(mut self, cgroup: Arc<Cgroup>) -> Self {
+        self.cgroup = Some(cgroup);
+        self
+    }
 }
 
 impl VmoOptions<Rights> {
@@ -94,9 +114,13 @@ impl VmoOptions<Rights> {
     /// The VMO is initially assigned full access rights.
     pub fn alloc(self) -> Result<Vmo<Rights>> {
         let VmoOptions {
-            size, flags, pager, ..
+            size,
+            flags,
+            pager,
+            cgroup,
+            ..
         } = self;
-        let vmo_ = alloc_vmo_(size, flags, pager)?;
+        let vmo_ = alloc_vmo_(size, flags, pager, cgroup)?;
         Ok(Vmo(Arc::new(vmo_), Rights::all()))
     }
 }
@@ -114,13 +138,19 @@ impl<R: TRights> VmoOptions<TRightSet<R>> {
             flags,
             rights,
             pager,
+            cgroup,
         } = self;
-        let vmo_ = alloc_vmo_(size, flags, pager)?;
+        let vmo_ = alloc_vmo_(size, flags, pager, cgroup)?;
         Ok(Vmo(Arc::new(vmo_), TRightSet(R::new())))
     }
 }
 
-fn alloc_vmo_(size: usize, flags: VmoFlags, pager: Option<Arc<dyn Pager>>) -> Result<Vmo_> {
+fn alloc_vmo_(
+    size: usize,
+    flags: VmoFlags,
+    pager: Option<Arc<dyn Pager>>,
+    cgroup: Option<Arc<Cgroup>>,
+) -> Result<Vmo_> {
     let size = size.align_up(PAGE_SIZE);
     let pages = {
         let pages = committed_pages_if_continuous(flags, size)?;
@@ -130,10 +160,14 @@ fn alloc_vmo_(size: usize, flags: VmoFlags, pager: Option<Arc<dyn Pager>>) -> Re
             Pages::Nonresizable(Mutex::new(pages), size)
         }
     };
+    let memory_charge = cgroup
+        .filter(|_| pager.is_none() && !flags.contains(VmoFlags::CONTIGUOUS))
+        .map(MemoryCharge::new);
     Ok(Vmo_ {
         pager,
         flags,
         pages,
+        memory_charge,
     })
 }
 
@@ -141,7 +175,8 @@ fn committed_pages_if_continuous(flags: VmoFlags, size: usize) -> Result<XArray<
     if flags.contains(VmoFlags::CONTIGUOUS) {
         // if the vmo is continuous, we need to allocate frames for the vmo
         let frames_num = size / PAGE_SIZE;
-        let segment: USegment = FrameAllocOptions::new().alloc_segment(frames_num)?.into();
+        let segment: USegment =
+            alloc_or_oom(|| FrameAllocOptions::new().alloc_segment(frames_num))?.into();
         let mut committed_pages = XArray::new();
         let mut cursor = committed_pages.cursor_mut(0);
         for frame in segment {
@@ -45,10 +45,12 @@ pub trait Pager: Send + Sync {
     /// Knowing that a frame is no longer needed, the pager (e.g., an inode)
     /// can free the frame after writing back its data to the disk.
     ///
-    /// The VMO will not call this method for an uncommitted page.
-    /// But a robust implementation of `Pager` should not make
-    /// such an assumption for its correctness; instead, it should simply ignore the
-    /// call or return an error.
+    /// The VMO may call this method for an uncommitted page, since the pager
+    /// may still hold a frame at the index after the VMO drops it (see
+    /// [`WeakVmo::try_decommit_unused_page`]). The pager should simply ignore
+    /// the call if there is no such frame.
+    ///
+    /// [`WeakVmo::try_decommit_unused_page`]: super::WeakVmo::try_decommit_unused_page
     fn decommit_page(&self, idx: usize) -> Result<()>;
 
     /// Ask the pager to provide a frame at a specified index.
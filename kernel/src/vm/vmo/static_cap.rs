@@ -61,6 +61,19 @@ impl<R: TRights> Vmo<TRightSet<R>> {
         self.0.decommit(range)
     }
 
+    /// Marks the page at the offset (in bytes) as dirty.
+    ///
+    /// This is needed if the page is written without going through the VMO,
+    /// e.g., via a shared memory mapping, so that the pager can write it back.
+    ///
+    /// # Access rights
+    ///
+    /// The method requires the Write right.
+    #[require(R > Write)]
+    pub fn mark_page_dirty(&self, offset: usize) -> Result<()> {
+        self.0.mark_page_dirty(offset)
+    }
+
     /// Resize the VMO by giving a new size.
     ///
     /// The VMO must be resizable.
@@ -211,6 +211,19 @@ impl PageTableEntryTrait for PageTableEntry {
         let rwx = PageTableFlags::READABLE | PageTableFlags::WRITABLE | PageTableFlags::EXECUTABLE;
         level == 1 || (self.0 & rwx.bits()) != 0
     }
+
+    fn new_swap(val: usize) -> Self {
+        // The bits other than `VALID` are ignored by the MMU if the entry is
+        // not valid. We use `RSV1` to mark the swap entry.
+        Self((val << 10) & Self::PHYS_ADDR_MASK | PageTableFlags::RSV1.bits())
+    }
+
+    fn swap_val(&self) -> Option<usize> {
+        if self.is_present() || self.0 & PageTableFlags::RSV1.bits() == 0 {
+            return None;
+        }
+        Some((self.0 & Self::PHYS_ADDR_MASK) >> 10)
+    }
 }
 
 impl fmt::Debug for PageTableEntry {
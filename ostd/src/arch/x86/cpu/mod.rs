@@ -8,6 +8,7 @@ use alloc::boxed::Box;
 use core::{
     arch::x86_64::{_fxrstor64, _fxsave64, _xrstor64, _xsave64},
     fmt::Debug,
+    mem::MaybeUninit,
     sync::atomic::{AtomicBool, Ordering::Relaxed},
 };
 
@@ -543,6 +544,74 @@ impl FpuState {
     pub fn clear(&self) {
         self.is_valid.store(false, Relaxed);
     }
+
+    /// Returns the size in bytes of the saved state.
+    ///
+    /// The state is in the XSAVE format if the processor supports `XSAVE`,
+    /// or in the legacy 512-byte FXSAVE format otherwise.
+    pub fn size(&self) -> usize {
+        self.area_size
+    }
+
+    /// Returns the saved state as bytes.
+    ///
+    /// The bytes reflect the CPU's FPU state only after [`Self::save`] is called.
+    pub fn as_bytes(&self) -> &[u8] {
+        let ptr = &*self.state_area as *const XSaveArea as *const u8;
+        // SAFETY: `XSaveArea` is plain old data without padding, and `area_size`
+        // never exceeds the size of `XSaveArea`.
+        unsafe { core::slice::from_raw_parts(ptr, self.area_size) }
+    }
+
+    /// Loads the state from bytes in the format returned by [`Self::as_bytes`].
+    ///
+    /// The bytes may come from untrusted sources (e.g., a user-space signal frame),
+    /// so the fields that would make `FXRSTOR` or `XRSTOR` fault are sanitized.
+    /// Bytes beyond [`Self::size`] are ignored. The loaded state takes effect after
+    /// [`Self::restore`] is called.
+    pub fn load_from_bytes(&mut self, bytes: &[u8]) {
+        let len = bytes.len().min(self.area_size);
+        let ptr = &mut *self.state_area as *mut XSaveArea as *mut u8;
+        // SAFETY: `XSaveArea` is plain old data, so any bytes form a valid instance,
+        // and `len` never exceeds the size of `XSaveArea`.
+        unsafe { core::slice::from_raw_parts_mut(ptr, len) }.copy_from_slice(&bytes[..len]);
+
+        let state_area = &mut *self.state_area;
+        state_area.fxsave_area.mxcsr &= mxcsr_mask();
+        if CPU_FEATURES.get().unwrap().has_xsave() {
+            // Only the standard (non-compacted) format with the enabled user features is
+            // allowed, and the reserved bytes of the XSAVE header must be zero.
+            state_area.features &= XCr0::read().bits() & XFEATURE_MASK_USER_RESTORE;
+            state_area.compaction = 0;
+            state_area.reserved = [0; 6];
+        }
+
+        self.is_valid.store(true, Relaxed);
+    }
+}
+
+/// Returns the mask of the MXCSR bits supported by the processor.
+///
+/// Setting any unsupported bit in MXCSR causes `FXRSTOR` and `XRSTOR` to fault.
+fn mxcsr_mask() -> u32 {
+    static MXCSR_MASK: Once<u32> = Once::new();
+
+    *MXCSR_MASK.call_once(|| {
+        /// The default mask if the processor reports zero in the `mxcsr_mask` field.
+        const DEFAULT_MXCSR_MASK: u32 = 0xFFBF;
+
+        let mut fxsave_area = MaybeUninit::<FxSaveArea>::zeroed();
+        // SAFETY: The area is 16-byte aligned and large enough for `FXSAVE`.
+        let mask = unsafe {
+            _fxsave64(fxsave_area.as_mut_ptr() as *mut u8);
+            fxsave_area.assume_init().mxcsr_mask
+        };
+        if mask == 0 {
+            DEFAULT_MXCSR_MASK
+        } else {
+            mask
+        }
+    })
 }
 
 impl Clone for FpuState {
@@ -10,7 +10,7 @@
 #![allow(unused_variables)]
 
 use acpi::fadt::Fadt;
-use x86_64::instructions::port::{ReadOnlyAccess, WriteOnlyAccess};
+use x86_64::instructions::port::{ReadWriteAccess, WriteOnlyAccess};
 
 use super::io_port::IoPort;
 use crate::arch::x86::kernel::acpi::ACPI_TABLES;
@@ -19,7 +19,7 @@ use crate::arch::x86::kernel::acpi::ACPI_TABLES;
 pub static CMOS_ADDRESS: IoPort<u8, WriteOnlyAccess> = unsafe { IoPort::new(0x70) };
 
 /// CMOS data I/O port
-pub static CMOS_DATA: IoPort<u8, ReadOnlyAccess> = unsafe { IoPort::new(0x71) };
+pub static CMOS_DATA: IoPort<u8, ReadWriteAccess> = unsafe { IoPort::new(0x71) };
 
 /// Gets the century register location. This function is used in RTC(Real Time Clock) module initialization.
 pub fn century_register() -> Option<u8> {
@@ -40,6 +40,9 @@ bitflags::bitflags! {
     pub struct PageTableFlags : u64{
         /// Whether accesses to this page must snoop processor caches.
         const SNOOP =           1 << 11;
+        /// Ignored by the hardware. Used to indicate that a non-present entry
+        /// records a swap entry.
+        const SWAP =            1 << 10;
 
         const DIRTY =           1 << 9;
 
@@ -72,6 +75,8 @@ pub struct PageTableEntry(u64);
 impl PageTableEntry {
     const PHYS_MASK: u64 = 0xFFFF_FFFF_F000;
     const PROP_MASK: u64 = !Self::PHYS_MASK & !PageTableFlags::LAST_PAGE.bits();
+    /// The bits that record the value of a swap entry in a non-present entry.
+    const SWAP_VAL_MASK: u64 = 0xF_FFFF_FFFF_F000;
 }
 
 impl PageTableEntryTrait for PageTableEntry {
@@ -142,4 +147,17 @@ impl PageTableEntryTrait for PageTableEntry {
     fn is_last(&self, level: PagingLevel) -> bool {
         level == 1
     }
+
+    fn new_swap(val: usize) -> Self {
+        // The entry is not present if it is neither readable nor writable,
+        // in which case the other bits are ignored by the hardware.
+        Self(((val as u64) << 12) & Self::SWAP_VAL_MASK | PageTableFlags::SWAP.bits())
+    }
+
+    fn swap_val(&self) -> Option<usize> {
+        if self.is_present() || self.0 & PageTableFlags::SWAP.bits() == 0 {
+            return None;
+        }
+        Some(((self.0 & Self::SWAP_VAL_MASK) >> 12) as usize)
+    }
 }
@@ -61,6 +61,9 @@ bitflags::bitflags! {
         /// Indicates that the mapping is present in all address spaces, so it isn't flushed from
         /// the TLB on an address space switch.
         const GLOBAL =          1 << 8;
+        /// Ignored by the hardware. Used to indicate that a non-present entry
+        /// records a swap entry.
+        const SWAP =            1 << 9;
         /// TDX shared bit.
         #[cfg(feature = "cvm_guest")]
         const SHARED =          1 << 51;
@@ -154,6 +157,8 @@ impl PageTableEntry {
         }
     }
     const PROP_MASK: usize = !Self::PHYS_ADDR_MASK & !PageTableFlags::HUGE.bits();
+    /// The bits that record the value of a swap entry in a non-present entry.
+    const SWAP_VAL_MASK: usize = 0xF_FFFF_FFFF_F000;
 }
 
 /// Parse a bit-flag bits `val` in the representation of `from` to `to` in bits.
@@ -278,6 +283,17 @@ impl PageTableEntryTrait for PageTableEntry {
     fn is_last(&self, _level: PagingLevel) -> bool {
         self.0 & PageTableFlags::HUGE.bits() != 0
     }
+
+    fn new_swap(val: usize) -> Self {
+        Self((val << 12) & Self::SWAP_VAL_MASK | PageTableFlags::SWAP.bits())
+    }
+
+    fn swap_val(&self) -> Option<usize> {
+        if self.is_present() || self.0 & PageTableFlags::SWAP.bits() == 0 {
+            return None;
+        }
+        Some((self.0 & Self::SWAP_VAL_MASK) >> 12)
+    }
 }
 
 impl fmt::Debug for PageTableEntry {
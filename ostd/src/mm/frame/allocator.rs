@@ -19,6 +19,7 @@ use crate::{
 /// Options for allocating physical memory frames.
 pub struct FrameAllocOptions {
     zeroed: bool,
+    align: usize,
 }
 
 impl Default for FrameAllocOptions {
@@ -30,7 +31,10 @@ impl Default for FrameAllocOptions {
 impl FrameAllocOptions {
     /// Creates new options for allocating the specified number of frames.
     pub fn new() -> Self {
-        Self { zeroed: true }
+        Self {
+            zeroed: true,
+            align: PAGE_SIZE,
+        }
     }
 
     /// Sets whether the allocated frames should be initialized with zeros.
@@ -45,6 +49,20 @@ impl FrameAllocOptions {
         self
     }
 
+    /// Sets the alignment of the physical address of the allocated segments.
+    ///
+    /// The alignment must be a power of two that is not smaller than
+    /// [`PAGE_SIZE`]. And it must not be larger than the size of the segments
+    /// rounded up to a power of two, otherwise the allocation fails with
+    /// [`Error::InvalidArgs`].
+    ///
+    /// By default, the segments are aligned to [`PAGE_SIZE`].
+    pub fn align(&mut self, align: usize) -> &mut Self {
+        assert!(align.is_power_of_two() && align >= PAGE_SIZE);
+        self.align = align;
+        self
+    }
+
     /// Allocates a single untyped frame without metadata.
     pub fn alloc_frame(&self) -> Result<Frame<()>> {
         self.alloc_frame_with(())
@@ -90,7 +108,8 @@ impl FrameAllocOptions {
     where
         F: FnMut(Paddr) -> M,
     {
-        if nframes == 0 {
+        let align = self.align / PAGE_SIZE;
+        if nframes == 0 || align > nframes.next_power_of_two() {
             return Err(Error::InvalidArgs);
         }
         let segment = FRAME_ALLOCATOR
@@ -98,7 +117,7 @@ impl FrameAllocOptions {
             .unwrap()
             .disable_irq()
             .lock()
-            .alloc(nframes)
+            .alloc_aligned(nframes, align)
             .map(|start| {
                 Segment::from_unused(
                     start * PAGE_SIZE..start * PAGE_SIZE + nframes * PAGE_SIZE,
@@ -139,6 +158,25 @@ fn test_alloc_dealloc() {
     }
 }
 
+#[cfg(ktest)]
+#[ktest]
+fn test_alloc_aligned_segment() {
+    const ALIGN: usize = PAGE_SIZE * 512;
+
+    let mut options = FrameAllocOptions::new();
+    options.zeroed(false).align(ALIGN);
+    let segments = (0..4)
+        .map(|_| options.alloc_segment(ALIGN / PAGE_SIZE).unwrap())
+        .collect::<Vec<_>>();
+    for segment in segments.iter() {
+        assert_eq!(segment.start_paddr() % ALIGN, 0);
+        assert_eq!(segment.size(), ALIGN);
+    }
+
+    // The alignment cannot be larger than the segment.
+    assert!(options.alloc_segment(ALIGN / PAGE_SIZE / 2).is_err());
+}
+
 /// FrameAllocator with a counter for allocated memory
 pub(in crate::mm) struct CountingFrameAllocator {
     allocator: FrameAllocator,
@@ -165,6 +203,19 @@ impl CountingFrameAllocator {
         }
     }
 
+    /// Allocates `count` frames, the first of which is aligned to `align` frames.
+    ///
+    /// The buddy allocator allocates a block of the smallest power-of-two
+    /// frames that is not less than `count`, and each block is aligned to its
+    /// size. So the alignment is satisfied as long as it is not larger than
+    /// the block size.
+    pub fn alloc_aligned(&mut self, count: usize, align: usize) -> Option<usize> {
+        debug_assert!(align.is_power_of_two() && align <= count.next_power_of_two());
+        let start = self.alloc(count)?;
+        debug_assert_eq!(start % align, 0);
+        Some(start)
+    }
+
     // TODO: this method should be marked unsafe as invalid arguments will mess
     // up the underlying allocator.
     pub fn dealloc(&mut self, start_frame: usize, count: usize) {
@@ -157,6 +157,36 @@ impl<M: AnyFrameMeta + ?Sized> Segment<M> {
             _marker: core::marker::PhantomData,
         }
     }
+
+    /// Forgets the handle to the frames.
+    ///
+    /// The physical address range of the frames is returned in case the frames
+    /// need to be restored using [`Segment::from_raw`] later. This is useful
+    /// when the page table holds the handle of a huge page.
+    pub(in crate::mm) fn into_raw(self) -> Range<Paddr> {
+        let range = self.range.clone();
+        core::mem::forget(self);
+        range
+    }
+
+    /// Restores a forgotten [`Segment`] from a physical address range.
+    ///
+    /// # Safety
+    ///
+    /// The caller should only restore a `Segment` that was previously forgotten
+    /// using [`Segment::into_raw`], or a range of frames each of which holds a
+    /// forgotten handle.
+    ///
+    /// And the restoring operation should only be done once for the forgotten
+    /// handles. Otherwise double-free will happen.
+    pub(in crate::mm) unsafe fn from_raw(range: Range<Paddr>) -> Self {
+        debug_assert!(range.start % PAGE_SIZE == 0 && range.end % PAGE_SIZE == 0);
+
+        Self {
+            range,
+            _marker: core::marker::PhantomData,
+        }
+    }
 }
 
 impl<M: AnyFrameMeta + ?Sized> From<Frame<M>> for Segment<M> {
@@ -231,6 +261,13 @@ impl<M: AnyUFrameMeta> From<Segment<M>> for USegment {
     }
 }
 
+impl From<USegment> for Segment<dyn AnyFrameMeta> {
+    fn from(seg: USegment) -> Self {
+        // SAFETY: The metadata is coerceable and the struct is transmutable.
+        unsafe { core::mem::transmute(seg) }
+    }
+}
+
 impl TryFrom<Segment<dyn AnyFrameMeta>> for USegment {
     type Error = Segment<dyn AnyFrameMeta>;
 
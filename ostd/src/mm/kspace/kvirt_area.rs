@@ -349,6 +349,12 @@ impl<M: AllocatorSelector + 'static> Drop for KVirtArea<M> {
                 PageTableItem::NotMapped { .. } => {
                     break;
                 }
+                PageTableItem::Swapped { .. } => {
+                    panic!("Found swap entries in `KVirtArea`");
+                }
+                PageTableItem::MappedHuge { .. } => {
+                    panic!("Found tracked huge pages in `KVirtArea`");
+                }
             }
         }
 
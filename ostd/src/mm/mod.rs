@@ -17,6 +17,7 @@ mod offset;
 pub(crate) mod page_prop;
 pub(crate) mod page_table;
 pub mod stat;
+mod swap;
 pub mod tlb;
 pub mod vm_space;
 
@@ -35,6 +36,7 @@ pub use self::{
         VmWriter,
     },
     page_prop::{CachePolicy, PageFlags, PageProperty},
+    swap::{register_swap_entry_ops, SwapEntry, SwapEntryOps},
     vm_space::VmSpace,
 };
 pub(crate) use self::{
@@ -75,6 +77,12 @@ pub(crate) trait PagingConstsTrait: Clone + Debug + Default + Send + Sync + 'sta
 /// The page size
 pub const PAGE_SIZE: usize = page_size::<PagingConsts>(1);
 
+/// The size of the smallest huge page.
+///
+/// A huge page of this size can be mapped into user space using
+/// [`vm_space::CursorMut::map_huge`].
+pub const HUGE_PAGE_SIZE: usize = page_size::<PagingConsts>(2);
+
 /// The page size at a given level.
 pub(crate) const fn page_size<C: PagingConstsTrait>(level: PagingLevel) -> usize {
     C::BASE_PAGE_SIZE << (nr_subpage_per_huge::<C>().ilog2() as usize * (level as usize - 1))
@@ -76,9 +76,9 @@ use super::{
 };
 use crate::{
     mm::{
-        frame::{meta::AnyFrameMeta, Frame},
+        frame::{meta::AnyFrameMeta, Frame, Segment},
         kspace::should_map_as_tracked,
-        paddr_to_vaddr, Paddr, PageProperty, Vaddr,
+        paddr_to_vaddr, Paddr, PageProperty, SwapEntry, Vaddr,
     },
     task::{disable_preempt, DisabledPreemptGuard},
 };
@@ -101,6 +101,16 @@ pub enum PageTableItem {
         len: usize,
         prop: PageProperty,
     },
+    Swapped {
+        va: Vaddr,
+        entry: SwapEntry,
+    },
+    /// A tracked huge page that is taken as a whole by [`CursorMut::take_next`].
+    MappedHuge {
+        va: Vaddr,
+        frames: Segment<dyn AnyFrameMeta>,
+        prop: PageProperty,
+    },
 }
 
 /// The cursor for traversal over the page table.
@@ -222,6 +232,14 @@ where
             let level = self.level;
             let va = self.va;
 
+            if self.cur_entry().is_tracked_huge() {
+                // Tracked huge pages are queried as base pages. Only the
+                // queried base page is referenced, not the whole huge page.
+                let va = va.align_down(C::BASE_PAGE_SIZE);
+                let (page, prop) = self.cur_entry().clone_base_page(va % page_size::<C>(level));
+                return Ok(PageTableItem::Mapped { va, page, prop });
+            }
+
             match self.cur_entry().to_owned() {
                 Child::PageTable(pt) => {
                     self.push_level(pt.lock());
@@ -236,6 +254,9 @@ where
                 Child::Frame(page, prop) => {
                     return Ok(PageTableItem::Mapped { va, page, prop });
                 }
+                Child::HugeFrame(_, _, _) => {
+                    unreachable!("Tracked huge pages are already handled");
+                }
                 Child::Untracked(pa, plevel, prop) => {
                     debug_assert_eq!(plevel, level);
                     return Ok(PageTableItem::MappedUntracked {
@@ -245,6 +266,9 @@ where
                         prop,
                     });
                 }
+                Child::Swap(entry) => {
+                    return Ok(PageTableItem::Swapped { va, entry });
+                }
             }
         }
     }
@@ -262,6 +286,22 @@ where
         self.va = next_va;
     }
 
+    /// Moves forward to the slot of the next item that [`Self::query`] yields.
+    ///
+    /// It is the same as [`Self::move_forward`], except that it moves to the
+    /// next base page within a tracked huge page, since tracked huge pages are
+    /// queried as base pages.
+    pub(in crate::mm) fn move_to_next_item(&mut self) {
+        if self.level > 1 && self.va < self.barrier_va.end {
+            let next_va = self.va.align_down(C::BASE_PAGE_SIZE) + C::BASE_PAGE_SIZE;
+            if self.cur_entry().is_tracked_huge() && next_va % page_size::<C>(self.level) != 0 {
+                self.va = next_va;
+                return;
+            }
+        }
+        self.move_forward();
+    }
+
     /// Jumps to the given virtual address.
     /// If the target address is out of the range, this method will return `Err`.
     ///
@@ -343,7 +383,7 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         let result = self.query();
         if result.is_ok() {
-            self.move_forward();
+            self.move_to_next_item();
         }
         result.ok()
     }
@@ -443,8 +483,12 @@ where
                     let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
                     self.0.push_level(pt);
                 }
-                Child::Frame(_, _) => {
-                    panic!("Mapping a smaller page in an already mapped huge page");
+                Child::Frame(_, _) | Child::Swap(_) => {
+                    unreachable!("Base pages are mapped at the lowest level");
+                }
+                Child::HugeFrame(_, _, _) => {
+                    let split_child = cur_entry.split_if_huge().unwrap();
+                    self.0.push_level(split_child);
                 }
                 Child::Untracked(_, _, _) => {
                     panic!("Mapping a tracked page in an untracked range");
@@ -460,14 +504,142 @@ where
 
         match old {
             Child::Frame(old_page, _) => Some(old_page),
-            Child::None => None,
+            Child::Swap(_) | Child::None => None,
             Child::PageTable(_) => {
                 todo!("Dropping page table nodes while mapping requires TLB flush")
             }
+            Child::HugeFrame(_, _, _) => unreachable!("Huge pages are split before mapping"),
             Child::Untracked(_, _, _) => panic!("Mapping a tracked page in an untracked range"),
         }
     }
 
+    /// Maps the range starting from the current address to a huge page made
+    /// of contiguous frames.
+    ///
+    /// The size of the frames must be the page size of a level higher than
+    /// the base level, and the range must not be mapped. The huge page can be
+    /// split into smaller pages later without copying, since the page table
+    /// holds the reference count of each of the frames.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if
+    ///  - the virtual address range to be mapped is out of the range;
+    ///  - the size of the frames is not the size of a huge page that can be
+    ///    mapped;
+    ///  - the alignment of the huge page is not satisfied by the virtual
+    ///    address or the physical address;
+    ///  - any part of the virtual address range is already mapped.
+    ///
+    /// # Safety
+    ///
+    /// The caller should ensure that the virtual range being mapped does
+    /// not affect kernel's memory safety.
+    pub unsafe fn map_huge(&mut self, frames: Segment<dyn AnyFrameMeta>, prop: PageProperty) {
+        let level = (2..=C::HIGHEST_TRANSLATION_LEVEL)
+            .find(|level| page_size::<C>(*level) == frames.size())
+            .expect("Mapping a huge page of an unsupported size");
+        let end = self.0.va + frames.size();
+        assert!(end <= self.0.barrier_va.end);
+        assert!(self.0.va % frames.size() == 0 && frames.start_paddr() % frames.size() == 0);
+
+        // Go down to the level of the huge page.
+        while self.0.level > level {
+            debug_assert!(self.0.should_map_as_tracked());
+            let cur_level = self.0.level;
+            let cur_entry = self.0.cur_entry();
+            match cur_entry.to_owned() {
+                Child::PageTable(pt) => {
+                    self.0.push_level(pt.lock());
+                }
+                Child::None => {
+                    let pt =
+                        PageTableNode::<E, C>::alloc(cur_level - 1, MapTrackingStatus::Tracked);
+                    let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
+                    self.0.push_level(pt);
+                }
+                Child::Frame(_, _) | Child::HugeFrame(_, _, _) => {
+                    panic!("Mapping a huge page in an already mapped range");
+                }
+                Child::Swap(_) => {
+                    unreachable!("Swap entries are recorded at the lowest level");
+                }
+                Child::Untracked(_, _, _) => {
+                    panic!("Mapping a tracked page in an untracked range");
+                }
+            }
+        }
+
+        // Map the current huge page.
+        let cur_entry = self.0.cur_entry();
+        assert!(
+            cur_entry.is_none(),
+            "Mapping a huge page in an already mapped range"
+        );
+        let _ = cur_entry.replace(Child::HugeFrame(frames, level, prop));
+        self.0.move_forward();
+    }
+
+    /// Replaces the mapping of the base page at the current address with a
+    /// swap entry.
+    ///
+    /// It returns the previously mapped [`Frame<dyn AnyFrameMeta>`] if that
+    /// exists. If the base page is a part of a huge page, the huge page is
+    /// split into smaller pages first.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the base page is out of the range.
+    ///
+    /// # Safety
+    ///
+    /// The caller should ensure that the virtual range being unmapped does
+    /// not affect kernel's memory safety.
+    pub unsafe fn map_swap(&mut self, entry: SwapEntry) -> Option<Frame<dyn AnyFrameMeta>> {
+        let end = self.0.va + C::BASE_PAGE_SIZE;
+        assert!(end <= self.0.barrier_va.end);
+
+        // Go down to the lowest level.
+        while self.0.level > 1 {
+            debug_assert!(self.0.should_map_as_tracked());
+            let cur_level = self.0.level;
+            let cur_entry = self.0.cur_entry();
+            match cur_entry.to_owned() {
+                Child::PageTable(pt) => {
+                    self.0.push_level(pt.lock());
+                }
+                Child::None => {
+                    let pt =
+                        PageTableNode::<E, C>::alloc(cur_level - 1, MapTrackingStatus::Tracked);
+                    let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
+                    self.0.push_level(pt);
+                }
+                Child::HugeFrame(_, _, _) => {
+                    let split_child = cur_entry.split_if_huge().unwrap();
+                    self.0.push_level(split_child);
+                }
+                Child::Frame(_, _) | Child::Swap(_) => {
+                    unreachable!("Base pages are mapped at the lowest level");
+                }
+                Child::Untracked(_, _, _) => {
+                    panic!("Mapping a tracked page in an untracked range");
+                }
+            }
+        }
+
+        // Record the swap entry.
+        let old = self.0.cur_entry().replace(Child::Swap(entry));
+        self.0.move_forward();
+
+        match old {
+            Child::Frame(old_page, _) => Some(old_page),
+            Child::Swap(_) | Child::None => None,
+            Child::PageTable(_) | Child::HugeFrame(_, _, _) | Child::Untracked(_, _, _) => {
+                unreachable!()
+            }
+        }
+    }
+
     /// Maps the range starting from the current address to a physical address range.
     ///
     /// The function will map as more huge pages as possible, and it will split
@@ -526,11 +698,11 @@ where
                         let _ = cur_entry.replace(Child::PageTable(pt.clone_raw()));
                         self.0.push_level(pt);
                     }
-                    Child::Frame(_, _) => {
+                    Child::Frame(_, _) | Child::HugeFrame(_, _, _) | Child::Swap(_) => {
                         panic!("Mapping a smaller page in an already mapped huge page");
                     }
                     Child::Untracked(_, _, _) => {
-                        let split_child = cur_entry.split_if_untracked_huge().unwrap();
+                        let split_child = cur_entry.split_if_huge().unwrap();
                         self.0.push_level(split_child);
                     }
                 }
@@ -566,15 +738,14 @@ where
     /// in the following range, the cursor will stop at the end of the range
     /// and return [`PageTableItem::NotMapped`].
     ///
+    /// Huge pages that are partially covered by the range are split into
+    /// smaller pages. Tracked huge pages that are fully covered are taken as a
+    /// whole and yielded as [`PageTableItem::MappedHuge`].
+    ///
     /// # Safety
     ///
     /// The caller should ensure that the range being unmapped does not affect
     /// kernel's memory safety.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if the end range covers a part of a huge page
-    /// and the next page is that huge page.
     pub unsafe fn take_next(&mut self, len: usize) -> PageTableItem {
         let start = self.0.va;
         assert!(len % page_size::<C>(1) == 0);
@@ -596,7 +767,8 @@ where
                 continue;
             }
 
-            // Go down if not applicable or if the entry points to a child page table.
+            // Go down if not applicable or if the entry points to a child page
+            // table.
             if cur_entry.is_node()
                 || cur_va % page_size::<C>(cur_level) != 0
                 || cur_va + page_size::<C>(cur_level) > end
@@ -620,11 +792,11 @@ where
                     Child::None => {
                         unreachable!("Already checked");
                     }
-                    Child::Frame(_, _) => {
-                        panic!("Removing part of a huge page");
+                    Child::Frame(_, _) | Child::Swap(_) => {
+                        unreachable!("Base pages are mapped at the lowest level");
                     }
-                    Child::Untracked(_, _, _) => {
-                        let split_child = cur_entry.split_if_untracked_huge().unwrap();
+                    Child::HugeFrame(_, _, _) | Child::Untracked(_, _, _) => {
+                        let split_child = cur_entry.split_if_huge().unwrap();
                         self.0.push_level(split_child);
                     }
                 }
@@ -638,19 +810,25 @@ where
 
             return match old {
                 Child::Frame(page, prop) => PageTableItem::Mapped {
-                    va: self.0.va,
+                    va: cur_va,
                     page,
                     prop,
                 },
+                Child::HugeFrame(frames, _, prop) => PageTableItem::MappedHuge {
+                    va: cur_va,
+                    frames,
+                    prop,
+                },
                 Child::Untracked(pa, level, prop) => {
-                    debug_assert_eq!(level, self.0.level);
+                    debug_assert_eq!(level, cur_level);
                     PageTableItem::MappedUntracked {
-                        va: self.0.va,
+                        va: cur_va,
                         pa,
                         len: page_size::<C>(level),
                         prop,
                     }
                 }
+                Child::Swap(entry) => PageTableItem::Swapped { va: cur_va, entry },
                 Child::PageTable(_) | Child::None => unreachable!(),
             };
         }
@@ -696,8 +874,8 @@ where
             let cur_level = self.0.level;
             let mut cur_entry = self.0.cur_entry();
 
-            // Skip if it is already absent.
-            if cur_entry.is_none() {
+            // Skip if it is already absent or swapped out.
+            if cur_entry.is_none() || cur_entry.is_swap() {
                 self.0.move_forward();
                 continue;
             }
@@ -719,10 +897,10 @@ where
             }
 
             // Go down if the page size is too big and we are protecting part
-            // of untracked huge pages.
+            // of huge pages.
             if cur_va % page_size::<C>(cur_level) != 0 || cur_va + page_size::<C>(cur_level) > end {
                 let split_child = cur_entry
-                    .split_if_untracked_huge()
+                    .split_if_huge()
                     .expect("Protecting part of a huge page");
                 self.0.push_level(split_child);
                 continue;
@@ -751,6 +929,7 @@ where
     /// mappings not useful to be copied.
     ///
     /// After the operation, both cursors will advance by the specified length.
+    /// It returns the total size of the mapped pages that are copied.
     ///
     /// # Safety
     ///
@@ -772,13 +951,14 @@ where
         src: &mut Self,
         len: usize,
         op: &mut impl FnMut(&mut PageProperty),
-    ) {
+    ) -> usize {
         assert!(len % page_size::<C>(1) == 0);
         let this_end = self.0.va + len;
         assert!(this_end <= self.0.barrier_va.end);
         let src_end = src.0.va + len;
         assert!(src_end <= src.0.barrier_va.end);
 
+        let mut copied_size = 0;
         while self.0.va < this_end && src.0.va < src_end {
             let src_va = src.0.va;
             let mut src_entry = src.0.cur_entry();
@@ -802,6 +982,16 @@ where
                 Child::Untracked(_, _, _) => {
                     panic!("Copying untracked mappings");
                 }
+                Child::Swap(entry) => {
+                    // The swap entry is shared, the same as the mapped pages.
+                    self.jump(src_va).unwrap();
+                    let original = self.map_swap(entry);
+                    assert!(original.is_none());
+
+                    // Only move the source cursor forward since `Self::map_swap`
+                    // will do it.
+                    src.0.move_forward();
+                }
                 Child::Frame(page, mut prop) => {
                     let mapped_page_size = page.size();
 
@@ -813,13 +1003,33 @@ where
                     self.jump(src_va).unwrap();
                     let original = self.map(page, prop);
                     assert!(original.is_none());
+                    copied_size += mapped_page_size;
 
                     // Only move the source cursor forward since `Self::map` will do it.
                     // This assertion is to ensure that they move by the same length.
                     debug_assert_eq!(mapped_page_size, page_size::<C>(src.0.level));
                     src.0.move_forward();
                 }
+                Child::HugeFrame(frames, _, mut prop) => {
+                    let mapped_page_size = frames.size();
+
+                    // Do protection.
+                    src_entry.protect(op);
+
+                    // Do copy. The huge page is shared rather than split.
+                    op(&mut prop);
+                    self.jump(src_va).unwrap();
+                    self.map_huge(frames, prop);
+                    copied_size += mapped_page_size;
+
+                    // Only move the source cursor forward since `Self::map_huge`
+                    // will do it.
+                    debug_assert_eq!(mapped_page_size, page_size::<C>(src.0.level));
+                    src.0.move_forward();
+                }
             }
         }
+
+        copied_size
     }
 }
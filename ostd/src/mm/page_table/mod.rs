@@ -380,4 +380,15 @@ pub trait PageTableEntryTrait:
     /// The level of the page table the entry resides is given since architectures
     /// like amd64 only uses a huge bit in intermediate levels.
     fn is_last(&self, level: PagingLevel) -> bool;
+
+    /// Create a PTE that is not present but records the value of a swap entry.
+    ///
+    /// The value fits in [`SwapEntry::NR_BITS`] bits. The PTE should not be
+    /// considered as present by [`Self::is_present`].
+    ///
+    /// [`SwapEntry::NR_BITS`]: crate::mm::SwapEntry::NR_BITS
+    fn new_swap(val: usize) -> Self;
+
+    /// Get the value of the swap entry if the PTE is created by [`Self::new_swap`].
+    fn swap_val(&self) -> Option<usize>;
 }
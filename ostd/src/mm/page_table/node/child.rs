@@ -8,9 +8,9 @@ use super::{MapTrackingStatus, PageTableEntryTrait, RawPageTableNode};
 use crate::{
     arch::mm::{PageTableEntry, PagingConsts},
     mm::{
-        frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame},
+        frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame, Segment},
         page_prop::PageProperty,
-        Paddr, PagingConstsTrait, PagingLevel,
+        page_size, Paddr, PagingConstsTrait, PagingLevel, SwapEntry, PAGE_SIZE,
     },
 };
 
@@ -28,8 +28,15 @@ pub(in crate::mm) enum Child<
 {
     PageTable(RawPageTableNode<E, C>),
     Frame(Frame<dyn AnyFrameMeta>, PageProperty),
+    /// Huge pages made of contiguous tracked frames.
+    ///
+    /// The child holds a reference count of each of the frames, so that the
+    /// huge page can be split into smaller pages without copying.
+    HugeFrame(Segment<dyn AnyFrameMeta>, PagingLevel, PageProperty),
     /// Pages not tracked by handles.
     Untracked(Paddr, PagingLevel, PageProperty),
+    /// Base pages that are swapped out.
+    Swap(SwapEntry),
     None,
 }
 
@@ -56,9 +63,15 @@ where
             Child::Frame(p, _) => {
                 node_level == p.level() && is_tracked == MapTrackingStatus::Tracked
             }
+            Child::HugeFrame(seg, level, _) => {
+                node_level == *level
+                    && seg.size() == page_size::<C>(*level)
+                    && is_tracked == MapTrackingStatus::Tracked
+            }
             Child::Untracked(_, level, _) => {
                 node_level == *level && is_tracked == MapTrackingStatus::Untracked
             }
+            Child::Swap(_) => node_level == 1 && is_tracked == MapTrackingStatus::Tracked,
             Child::None => true,
         }
     }
@@ -82,7 +95,9 @@ where
                 let level = page.level();
                 E::new_page(page.into_raw(), level, prop)
             }
+            Child::HugeFrame(seg, level, prop) => E::new_page(seg.into_raw().start, level, prop),
             Child::Untracked(pa, level, prop) => E::new_page(pa, level, prop),
+            Child::Swap(entry) => E::new_swap(entry.into_raw()),
             Child::None => E::new_absent(),
         }
     }
@@ -105,7 +120,12 @@ where
         is_tracked: MapTrackingStatus,
     ) -> Self {
         if !pte.is_present() {
-            return Child::None;
+            return match pte.swap_val() {
+                // SAFETY: The swap entry is forgotten when converted into the
+                // PTE, and now the ownership is transferred back.
+                Some(val) => Child::Swap(unsafe { SwapEntry::from_raw(val) }),
+                None => Child::None,
+            };
         }
 
         let paddr = pte.paddr();
@@ -117,6 +137,14 @@ where
         }
 
         match is_tracked {
+            MapTrackingStatus::Tracked if level > 1 => {
+                // SAFETY: The physical address range points to valid pages
+                // that the huge page holds.
+                let seg = unsafe {
+                    Segment::<dyn AnyFrameMeta>::from_raw(paddr..paddr + page_size::<C>(level))
+                };
+                Child::HugeFrame(seg, level, pte.prop())
+            }
             MapTrackingStatus::Tracked => {
                 // SAFETY: The physical address points to a valid page.
                 let page = unsafe { Frame::<dyn AnyFrameMeta>::from_raw(paddr) };
@@ -142,7 +170,15 @@ where
         is_tracked: MapTrackingStatus,
     ) -> Self {
         if !pte.is_present() {
-            return Child::None;
+            return match pte.swap_val() {
+                Some(val) => {
+                    // SAFETY: The PTE owns the swap entry. The restored handle
+                    // is cloned and then forgotten, so the PTE still owns it.
+                    let entry = ManuallyDrop::new(unsafe { SwapEntry::from_raw(val) });
+                    Child::Swap((*entry).clone())
+                }
+                None => Child::None,
+            };
         }
 
         let paddr = pte.paddr();
@@ -157,6 +193,18 @@ where
         }
 
         match is_tracked {
+            MapTrackingStatus::Tracked if level > 1 => {
+                let range = paddr..paddr + page_size::<C>(level);
+                for paddr in range.clone().step_by(PAGE_SIZE) {
+                    // SAFETY: The physical address is valid and the PTE already
+                    // owns the reference to the page.
+                    unsafe { inc_frame_ref_count(paddr) };
+                }
+                // SAFETY: The physical address range points to valid pages
+                // that the huge page holds.
+                let seg = unsafe { Segment::<dyn AnyFrameMeta>::from_raw(range) };
+                Child::HugeFrame(seg, level, pte.prop())
+            }
             MapTrackingStatus::Tracked => {
                 // SAFETY: The physical address is valid and the PTE already owns
                 // the reference to the page.
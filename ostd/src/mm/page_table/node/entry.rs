@@ -3,7 +3,12 @@
 //! This module provides accessors to the page table entries in a node.
 
 use super::{Child, MapTrackingStatus, PageTableEntryTrait, PageTableNode};
-use crate::mm::{nr_subpage_per_huge, page_prop::PageProperty, page_size, PagingConstsTrait};
+use crate::mm::{
+    frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame},
+    nr_subpage_per_huge,
+    page_prop::PageProperty,
+    page_size, PagingConstsTrait,
+};
 
 /// A view of an entry in a page table node.
 ///
@@ -36,7 +41,12 @@ where
 {
     /// Returns if the entry does not map to anything.
     pub(in crate::mm) fn is_none(&self) -> bool {
-        !self.pte.is_present()
+        !self.pte.is_present() && self.pte.swap_val().is_none()
+    }
+
+    /// Returns if the entry records a swapped-out page.
+    pub(in crate::mm) fn is_swap(&self) -> bool {
+        self.pte.swap_val().is_some()
     }
 
     /// Returns if the entry maps to a page table node.
@@ -108,33 +118,89 @@ where
         old_child
     }
 
-    /// Splits the entry to smaller pages if it maps to a untracked huge page.
+    /// Returns if the entry maps to a huge page.
+    pub(in crate::mm) fn is_huge(&self) -> bool {
+        let level = self.node.level();
+        self.pte.is_present() && self.pte.is_last(level) && level > 1
+    }
+
+    /// Returns if the entry maps to a tracked huge page.
+    pub(in crate::mm) fn is_tracked_huge(&self) -> bool {
+        self.is_huge() && self.node.is_tracked() == MapTrackingStatus::Tracked
+    }
+
+    /// Gets an owned handle to the base page at `offset` within the tracked
+    /// huge page, along with the mapping properties of the huge page.
     ///
-    /// If the entry does map to a untracked huge page, it is split into smaller
-    /// pages mapped by a child page table node. The new child page table node
-    /// is returned.
+    /// Unlike [`Self::to_owned`], only the base page is referenced.
     ///
-    /// If the entry does not map to a untracked huge page, the method returns
-    /// `None`.
-    pub(in crate::mm) fn split_if_untracked_huge(self) -> Option<PageTableNode<E, C>> {
-        let level = self.node.level();
+    /// # Panics
+    ///
+    /// The method panics if the entry does not map to a tracked huge page, or
+    /// if the offset is out of the huge page.
+    pub(in crate::mm) fn clone_base_page(
+        &self,
+        offset: usize,
+    ) -> (Frame<dyn AnyFrameMeta>, PageProperty) {
+        assert!(self.is_tracked_huge());
+        assert!(offset < page_size::<C>(self.node.level()));
+
+        let paddr = self.pte.paddr() + offset / C::BASE_PAGE_SIZE * C::BASE_PAGE_SIZE;
+        // SAFETY: The physical address is valid and the PTE already owns the
+        // reference to each page of the huge page.
+        unsafe { inc_frame_ref_count(paddr) };
+        // SAFETY: The physical address points to a valid page.
+        let page = unsafe { Frame::<dyn AnyFrameMeta>::from_raw(paddr) };
+        (page, self.pte.prop())
+    }
 
-        if !(self.pte.is_last(level)
-            && level > 1
-            && self.node.is_tracked() == MapTrackingStatus::Untracked)
-        {
+    /// Splits the entry to smaller pages if it maps to a huge page.
+    ///
+    /// If the entry does map to a huge page, it is split into smaller pages
+    /// mapped by a child page table node. The new child page table node is
+    /// returned. For a tracked huge page, the reference counts of the frames
+    /// held by the huge page are handed over to the smaller pages.
+    ///
+    /// If the entry does not map to a huge page, the method returns `None`.
+    pub(in crate::mm) fn split_if_huge(self) -> Option<PageTableNode<E, C>> {
+        if !self.is_huge() {
             return None;
         }
 
-        let pa = self.pte.paddr();
-        let prop = self.pte.prop();
-
-        let mut new_page = PageTableNode::<E, C>::alloc(level - 1, MapTrackingStatus::Untracked);
-        for i in 0..nr_subpage_per_huge::<C>() {
-            let small_pa = pa + i * page_size::<C>(level - 1);
-            let _ = new_page
-                .entry(i)
-                .replace(Child::Untracked(small_pa, level - 1, prop));
+        let level = self.node.level();
+        let is_tracked = self.node.is_tracked();
+        let small_size = page_size::<C>(level - 1);
+
+        let mut new_page = PageTableNode::<E, C>::alloc(level - 1, is_tracked);
+        match self.to_owned() {
+            Child::HugeFrame(seg, _, prop) => {
+                let mut rest = Some(seg);
+                for i in 0..nr_subpage_per_huge::<C>() {
+                    let mut small_seg = rest.take().unwrap();
+                    if small_seg.size() > small_size {
+                        let (head, tail) = small_seg.split(small_size);
+                        small_seg = head;
+                        rest = Some(tail);
+                    }
+                    let small_child = if level - 1 == 1 {
+                        Child::Frame(small_seg.next().unwrap(), prop)
+                    } else {
+                        Child::HugeFrame(small_seg, level - 1, prop)
+                    };
+                    let _ = new_page.entry(i).replace(small_child);
+                }
+            }
+            Child::Untracked(pa, _, prop) => {
+                for i in 0..nr_subpage_per_huge::<C>() {
+                    let small_pa = pa + i * small_size;
+                    let _ = new_page
+                        .entry(i)
+                        .replace(Child::Untracked(small_pa, level - 1, prop));
+                }
+            }
+            Child::PageTable(_) | Child::Frame(_, _) | Child::Swap(_) | Child::None => {
+                unreachable!()
+            }
         }
 
         let _ = self.replace(Child::PageTable(new_page.clone_raw()));
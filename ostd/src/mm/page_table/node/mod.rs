@@ -40,9 +40,9 @@ use super::{nr_subpage_per_huge, PageTableEntryTrait};
 use crate::{
     arch::mm::{PageTableEntry, PagingConsts},
     mm::{
-        frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame},
-        paddr_to_vaddr, FrameAllocOptions, Infallible, Paddr, PagingConstsTrait, PagingLevel,
-        VmReader,
+        frame::{inc_frame_ref_count, meta::AnyFrameMeta, Frame, Segment},
+        paddr_to_vaddr, page_size, FrameAllocOptions, Infallible, Paddr, PagingConstsTrait,
+        PagingLevel, SwapEntry, VmReader,
     },
 };
 
@@ -431,11 +431,20 @@ where
                     // SAFETY: The PTE points to a page table node. The ownership
                     // of the child is transferred to the child then dropped.
                     drop(unsafe { Frame::<Self>::from_raw(paddr) });
+                } else if is_tracked == MapTrackingStatus::Tracked && level > 1 {
+                    let range = paddr..paddr + page_size::<C>(level);
+                    // SAFETY: The PTE points to a tracked huge page. The ownership
+                    // of the child is transferred to the child then dropped.
+                    drop(unsafe { Segment::<dyn AnyFrameMeta>::from_raw(range) });
                 } else if is_tracked == MapTrackingStatus::Tracked {
                     // SAFETY: The PTE points to a tracked page. The ownership
                     // of the child is transferred to the child then dropped.
                     drop(unsafe { Frame::<dyn AnyFrameMeta>::from_raw(paddr) });
                 }
+            } else if let Some(val) = pte.swap_val() {
+                // SAFETY: The PTE records a swap entry. The ownership of the
+                // swap entry is transferred to the handle then dropped.
+                drop(unsafe { SwapEntry::from_raw(val) });
             }
         }
     }
@@ -7,7 +7,7 @@ use crate::{
     mm::{
         kspace::LINEAR_MAPPING_BASE_VADDR,
         page_prop::{CachePolicy, PageFlags},
-        FrameAllocOptions, MAX_USERSPACE_VADDR,
+        FrameAllocOptions, SwapEntry, MAX_USERSPACE_VADDR,
     },
     prelude::*,
 };
@@ -42,6 +42,90 @@ fn test_tracked_map_unmap() {
     assert!(pt.query(from.start + 10).is_none());
 }
 
+#[ktest]
+fn test_tracked_huge_map_split() {
+    const HUGE_PAGE_SIZE: usize = PAGE_SIZE * 512;
+
+    let pt = PageTable::<UserMode>::empty();
+    let from = HUGE_PAGE_SIZE..HUGE_PAGE_SIZE * 2;
+    let frames = FrameAllocOptions::new().alloc_segment(512).unwrap();
+    let start_paddr = frames.start_paddr();
+    let prop = PageProperty::new(PageFlags::RW, CachePolicy::Writeback);
+    unsafe { pt.cursor_mut(&from).unwrap().map_huge(frames.into(), prop) };
+    assert_eq!(pt.query(from.start + 10).unwrap().0, start_paddr + 10);
+
+    // The huge page is queried as base pages.
+    let mut cursor = pt.cursor(&from).unwrap();
+    cursor.jump(from.start + PAGE_SIZE * 3).unwrap();
+    let PageTableItem::Mapped { va, page, .. } = cursor.query().unwrap() else {
+        panic!("the huge page is not mapped");
+    };
+    assert_eq!(va, from.start + PAGE_SIZE * 3);
+    assert_eq!(page.start_paddr(), start_paddr + PAGE_SIZE * 3);
+    assert_eq!(cursor.count(), 512 - 3);
+
+    // Unmapping a part of the huge page splits it.
+    let unmap = from.start + PAGE_SIZE..from.start + PAGE_SIZE * 2;
+    assert!(matches!(
+        unsafe { pt.cursor_mut(&unmap).unwrap().take_next(unmap.len()) },
+        PageTableItem::Mapped { .. }
+    ));
+    assert!(pt.query(unmap.start + 10).is_none());
+    assert_eq!(pt.query(from.start + 10).unwrap().0, start_paddr + 10);
+    assert_eq!(
+        pt.query(unmap.end + 10).unwrap().0,
+        start_paddr + PAGE_SIZE * 2 + 10
+    );
+
+    // Unmapping a whole huge page takes it without splitting.
+    let whole = HUGE_PAGE_SIZE * 2..HUGE_PAGE_SIZE * 3;
+    let frames = FrameAllocOptions::new().alloc_segment(512).unwrap();
+    let start_paddr = frames.start_paddr();
+    unsafe { pt.cursor_mut(&whole).unwrap().map_huge(frames.into(), prop) };
+    let PageTableItem::MappedHuge { va, frames, .. } =
+        (unsafe { pt.cursor_mut(&whole).unwrap().take_next(whole.len()) })
+    else {
+        panic!("the huge page is not taken as a whole");
+    };
+    assert_eq!(va, whole.start);
+    assert_eq!(frames.start_paddr(), start_paddr);
+    assert_eq!(frames.size(), HUGE_PAGE_SIZE);
+    assert!(pt.query(whole.start + 10).is_none());
+}
+
+#[ktest]
+fn test_tracked_map_swap() {
+    let pt = PageTable::<UserMode>::empty();
+
+    let from = PAGE_SIZE..PAGE_SIZE * 2;
+    let page = FrameAllocOptions::new().alloc_frame().unwrap();
+    let start_paddr = page.start_paddr();
+    let prop = PageProperty::new(PageFlags::RW, CachePolicy::Writeback);
+    unsafe { pt.cursor_mut(&from).unwrap().map(page.into(), prop) };
+
+    // Recording a swap entry unmaps the page.
+    let old = unsafe { pt.cursor_mut(&from).unwrap().map_swap(SwapEntry::new(42)) };
+    assert_eq!(old.unwrap().start_paddr(), start_paddr);
+    assert!(pt.query(from.start + 10).is_none());
+    let PageTableItem::Swapped { va, entry } = pt.cursor(&from).unwrap().query().unwrap() else {
+        panic!("the swap entry is not recorded");
+    };
+    assert_eq!(va, from.start);
+    assert_eq!(entry.val(), 42);
+
+    // Unmapping takes the swap entry.
+    let PageTableItem::Swapped { entry, .. } =
+        (unsafe { pt.cursor_mut(&from).unwrap().take_next(from.len()) })
+    else {
+        panic!("the swap entry is not taken");
+    };
+    assert_eq!(entry.val(), 42);
+    assert!(matches!(
+        pt.cursor(&from).unwrap().query().unwrap(),
+        PageTableItem::NotMapped { .. }
+    ));
+}
+
 #[ktest]
 fn test_untracked_map_unmap() {
     let pt = PageTable::<KernelMode>::empty();
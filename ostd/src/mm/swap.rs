@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Swap entries in user page tables.
+
+use core::mem::ManuallyDrop;
+
+use spin::Once;
+
+/// A swap entry recorded in a non-present page table entry of a [`VmSpace`].
+///
+/// When the kernel swaps out a page, it can replace the mapping of the page
+/// with a swap entry, whose value is opaque to OSTD and locates the data that
+/// is swapped out. Accessing the page then triggers a page fault, and the
+/// kernel finds the swap entry by querying the VM space.
+///
+/// A `SwapEntry` is an owning handle. OSTD notifies the kernel via the
+/// operations registered with [`register_swap_entry_ops`] when a handle is
+/// duplicated or dropped, e.g., when the VM space is forked or unmapped. So
+/// the kernel can count the references to the swapped-out data.
+///
+/// [`VmSpace`]: crate::mm::VmSpace
+#[derive(Debug, PartialEq, Eq)]
+pub struct SwapEntry(usize);
+
+/// The operations on swap entries provided by the kernel.
+///
+/// The operations may be called with page table locks held and preemption
+/// disabled. So they must not sleep.
+#[derive(Debug, Clone, Copy)]
+pub struct SwapEntryOps {
+    /// Increases the reference count of the swap entry with the value.
+    pub dup: fn(usize),
+    /// Decreases the reference count of the swap entry with the value.
+    pub drop: fn(usize),
+}
+
+static SWAP_ENTRY_OPS: Once<SwapEntryOps> = Once::new();
+
+/// Registers the operations on swap entries.
+///
+/// The operations can only be registered once. Later registrations are
+/// ignored.
+pub fn register_swap_entry_ops(ops: SwapEntryOps) {
+    SWAP_ENTRY_OPS.call_once(|| ops);
+}
+
+impl SwapEntry {
+    /// The number of bits that the value of a swap entry can use.
+    pub const NR_BITS: u32 = 40;
+
+    /// Creates a swap entry handle with the value.
+    ///
+    /// The handle takes over a reference to the swap entry, which should be
+    /// counted by the caller.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the value does not fit in [`Self::NR_BITS`] bits.
+    pub fn new(val: usize) -> Self {
+        assert!(val < (1 << Self::NR_BITS));
+        Self(val)
+    }
+
+    /// Returns the value of the swap entry.
+    pub fn val(&self) -> usize {
+        self.0
+    }
+
+    /// Forgets the handle without dropping the reference.
+    ///
+    /// The value of the swap entry is returned in case the handle needs to be
+    /// restored using [`SwapEntry::from_raw`] later.
+    pub(in crate::mm) fn into_raw(self) -> usize {
+        let this = ManuallyDrop::new(self);
+        this.0
+    }
+
+    /// Restores a forgotten handle from the value.
+    ///
+    /// # Safety
+    ///
+    /// The caller should only restore a handle that was previously forgotten
+    /// using [`SwapEntry::into_raw`], and only once.
+    pub(in crate::mm) unsafe fn from_raw(val: usize) -> Self {
+        Self(val)
+    }
+}
+
+impl Clone for SwapEntry {
+    fn clone(&self) -> Self {
+        if let Some(ops) = SWAP_ENTRY_OPS.get() {
+            (ops.dup)(self.0);
+        }
+        Self(self.0)
+    }
+}
+
+impl Drop for SwapEntry {
+    fn drop(&mut self) {
+        if let Some(ops) = SWAP_ENTRY_OPS.get() {
+            (ops.drop)(self.0);
+        }
+    }
+}
@@ -9,7 +9,10 @@
 //! powerful concurrent accesses to the page table, and suffers from the same
 //! validity concerns as described in [`super::page_table::cursor`].
 
-use core::{ops::Range, sync::atomic::Ordering};
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use crate::{
     arch::mm::{
@@ -22,7 +25,8 @@ use crate::{
         kspace::KERNEL_PAGE_TABLE,
         page_table::{self, PageTable, PageTableItem, UserMode},
         tlb::{TlbFlushOp, TlbFlusher, FLUSH_ALL_RANGE_THRESHOLD},
-        PageProperty, UFrame, VmReader, VmWriter, MAX_USERSPACE_VADDR,
+        PageProperty, SwapEntry, UFrame, USegment, VmReader, VmWriter, HUGE_PAGE_SIZE,
+        MAX_USERSPACE_VADDR, PAGE_SIZE,
     },
     prelude::*,
     sync::{PreemptDisabled, RwLock, RwLockReadGuard},
@@ -53,6 +57,10 @@ pub struct VmSpace {
     /// Cursors hold read locks and activation require a write lock.
     activation_lock: RwLock<()>,
     cpus: AtomicCpuSet,
+    /// The total size of the frames mapped in the VM space.
+    rss: AtomicUsize,
+    /// The peak value of `rss` during the lifetime of the VM space.
+    rss_peak: AtomicUsize,
 }
 
 impl VmSpace {
@@ -63,6 +71,8 @@ impl VmSpace {
             page_fault_handler: None,
             activation_lock: RwLock::new(()),
             cpus: AtomicCpuSet::new(CpuSet::new_empty()),
+            rss: AtomicUsize::new(0),
+            rss_peak: AtomicUsize::new(0),
         }
     }
 
@@ -86,6 +96,7 @@ impl VmSpace {
             // SAFETY: We have ensured that the page table is not activated on
             // other CPUs and no cursors are alive.
             unsafe { self.pt.clear() };
+            self.rss.store(0, Ordering::Relaxed);
             if cpus_set_is_single_self {
                 tlb_flush_all_excluding_global();
             }
@@ -125,10 +136,28 @@ impl VmSpace {
                 pt_cursor,
                 activation_lock,
                 flusher: TlbFlusher::new(self.cpus.load(), disable_preempt()),
+                rss: &self.rss,
+                rss_peak: &self.rss_peak,
             }
         })?)
     }
 
+    /// Returns the resident set size, which is the total size of the frames
+    /// mapped in the VM space.
+    ///
+    /// A frame mapped into multiple VM spaces is counted in each of them.
+    pub fn rss(&self) -> usize {
+        self.rss.load(Ordering::Relaxed)
+    }
+
+    /// Returns the peak resident set size ("high water mark").
+    ///
+    /// The peak is kept when the VM space is cleared, so it covers the
+    /// mappings before the clearing as well.
+    pub fn rss_peak(&self) -> usize {
+        self.rss_peak.load(Ordering::Relaxed)
+    }
+
     /// Activates the page table on the current CPU.
     pub(crate) fn activate(self: &Arc<Self>) {
         let preempt_guard = disable_preempt();
@@ -250,7 +279,7 @@ impl Iterator for Cursor<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         let result = self.query();
         if result.is_ok() {
-            self.0.move_forward();
+            self.0.move_to_next_item();
         }
         result.ok()
     }
@@ -287,6 +316,8 @@ pub struct CursorMut<'a, 'b> {
     // We have a read lock so the CPU set in the flusher is always a superset
     // of actual activated CPUs.
     flusher: TlbFlusher<DisabledPreemptGuard>,
+    rss: &'a AtomicUsize,
+    rss_peak: &'a AtomicUsize,
 }
 
 impl CursorMut<'_, '_> {
@@ -332,9 +363,56 @@ impl CursorMut<'_, '_> {
             self.flusher
                 .issue_tlb_flush_with(TlbFlushOp::Address(start_va), old);
             self.flusher.dispatch_tlb_flush();
+        } else {
+            self.add_rss(PAGE_SIZE);
         }
     }
 
+    /// Map a huge page made of contiguous frames into the current slot.
+    ///
+    /// The size of the frames must be [`HUGE_PAGE_SIZE`], and both the current
+    /// virtual address and the physical address of the frames must be aligned
+    /// to it. The range must not be mapped. Querying or iterating over the
+    /// huge page yields the base pages in it. And the huge page is split into
+    /// smaller pages if only a part of it is unmapped, protected or remapped.
+    ///
+    /// This method will bring the cursor to the next slot after the modification.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the requirements above are not satisfied.
+    pub fn map_huge(&mut self, frames: USegment, prop: PageProperty) {
+        assert_eq!(frames.size(), HUGE_PAGE_SIZE);
+        // SAFETY: It is safe to map untyped memory into the userspace.
+        unsafe { self.pt_cursor.map_huge(frames.into(), prop) };
+        self.add_rss(HUGE_PAGE_SIZE);
+    }
+
+    /// Replace the mapping of the frame in the current slot with a swap entry.
+    ///
+    /// The swap entry is recorded even if the current slot is not mapped. If
+    /// the current slot is a part of a huge page, the huge page is split.
+    ///
+    /// It returns the previously mapped frame, if any. The TLB flush for the
+    /// slot is dispatched before this method returns.
+    ///
+    /// This method will bring the cursor to the next slot after the modification.
+    pub fn swap_out(&mut self, entry: SwapEntry) -> Option<UFrame> {
+        let start_va = self.virt_addr();
+        // SAFETY: It is safe to un-map memory in the userspace.
+        let old = unsafe { self.pt_cursor.map_swap(entry) }?;
+        self.rss.fetch_sub(PAGE_SIZE, Ordering::Relaxed);
+
+        self.flusher
+            .issue_tlb_flush_with(TlbFlushOp::Address(start_va), old.clone());
+        self.flusher.dispatch_tlb_flush();
+
+        let Ok(frame) = old.try_into() else {
+            panic!("found typed memory mapped into `VmSpace`");
+        };
+        Some(frame)
+    }
+
     /// Clear the mapping starting from the current slot.
     ///
     /// This method will bring the cursor forward by `len` bytes in the virtual
@@ -352,7 +430,7 @@ impl CursorMut<'_, '_> {
     ///
     /// This method will panic if `len` is not page-aligned.
     pub fn unmap(&mut self, len: usize) {
-        assert!(len % super::PAGE_SIZE == 0);
+        assert!(len % PAGE_SIZE == 0);
         let end_va = self.virt_addr() + len;
         let tlb_prefer_flush_all = len > FLUSH_ALL_RANGE_THRESHOLD;
 
@@ -361,6 +439,7 @@ impl CursorMut<'_, '_> {
             let result = unsafe { self.pt_cursor.take_next(end_va - self.virt_addr()) };
             match result {
                 PageTableItem::Mapped { va, page, .. } => {
+                    self.rss.fetch_sub(page.size(), Ordering::Relaxed);
                     if !self.flusher.need_remote_flush() && tlb_prefer_flush_all {
                         // Only on single-CPU cases we can drop the page immediately before flushing.
                         drop(page);
@@ -375,6 +454,20 @@ impl CursorMut<'_, '_> {
                 PageTableItem::MappedUntracked { .. } => {
                     panic!("found untracked memory mapped into `VmSpace`");
                 }
+                PageTableItem::MappedHuge { va, frames, .. } => {
+                    let size = frames.size();
+                    self.rss.fetch_sub(size, Ordering::Relaxed);
+                    if !self.flusher.need_remote_flush() && tlb_prefer_flush_all {
+                        drop(frames);
+                        continue;
+                    }
+                    self.flusher
+                        .issue_tlb_flush_with(TlbFlushOp::Range(va..va + size), frames);
+                }
+                PageTableItem::Swapped { entry, .. } => {
+                    // The swap entry is not cached by the TLB.
+                    drop(entry);
+                }
             }
         }
 
@@ -446,7 +539,13 @@ impl CursorMut<'_, '_> {
     ) {
         // SAFETY: Operations on user memory spaces are safe if it doesn't
         // involve dropping any pages.
-        unsafe { self.pt_cursor.copy_from(&mut src.pt_cursor, len, op) }
+        let copied_size = unsafe { self.pt_cursor.copy_from(&mut src.pt_cursor, len, op) };
+        self.add_rss(copied_size);
+    }
+
+    fn add_rss(&self, size: usize) {
+        let rss = self.rss.fetch_add(size, Ordering::Relaxed) + size;
+        self.rss_peak.fetch_max(rss, Ordering::Relaxed);
     }
 }
 
@@ -479,6 +578,13 @@ pub enum VmItem {
         /// The property of the slot.
         prop: PageProperty,
     },
+    /// The current slot is swapped out.
+    Swapped {
+        /// The virtual address of the slot.
+        va: Vaddr,
+        /// The swap entry recorded in the slot.
+        entry: SwapEntry,
+    },
 }
 
 impl TryFrom<PageTableItem> for VmItem {
@@ -497,6 +603,8 @@ impl TryFrom<PageTableItem> for VmItem {
             PageTableItem::MappedUntracked { .. } => {
                 Err("found untracked memory mapped into `VmSpace`")
             }
+            PageTableItem::MappedHuge { .. } => Err("tracked huge pages are queried as base pages"),
+            PageTableItem::Swapped { va, entry } => Ok(VmItem::Swapped { va, entry }),
         }
     }
 }
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+#define ROOT "/tmp/cgroup"
+#define GROUP ROOT "/test"
+#define PAGE_SIZE 4096
+
+static int write_file(const char *path, const char *data)
+{
+	int fd = open(path, O_WRONLY);
+	CHECK(fd >= 0);
+	int ret = write(fd, data, strlen(data));
+	int saved_errno = errno;
+	CHECK(close(fd) == 0);
+	errno = saved_errno;
+	return ret < 0 ? -1 : 0;
+}
+
+static void read_file(const char *path, char *buf, size_t len)
+{
+	int fd = open(path, O_RDONLY);
+	CHECK(fd >= 0);
+	ssize_t ret = read(fd, buf, len - 1);
+	CHECK(ret >= 0);
+	buf[ret] = '\0';
+	CHECK(close(fd) == 0);
+}
+
+static long read_number(const char *path)
+{
+	char buf[64];
+	read_file(path, buf, sizeof(buf));
+	return strtol(buf, NULL, 10);
+}
+
+#define CHECK_FILE(path, expected)                  \
+	do {                                        \
+		char buf[128];                      \
+		read_file(path, buf, sizeof(buf));  \
+		CHECK(strcmp(buf, expected) == 0);  \
+	} while (0)
+
+void test_hierarchy(void)
+{
+	CHECK_FILE(ROOT "/cgroup.controllers", "cpu memory pids\n");
+	CHECK_FILE(ROOT "/cgroup.subtree_control", "\n");
+	CHECK(access(ROOT "/pids.max", F_OK) == -1 && errno == ENOENT);
+
+	CHECK(mkdir(GROUP, 0755) == 0);
+	CHECK(mkdir(GROUP, 0755) == -1 && errno == EEXIST);
+	CHECK_FILE(GROUP "/cgroup.controllers", "\n");
+	CHECK_FILE(GROUP "/cgroup.procs", "");
+	CHECK(access(GROUP "/pids.max", F_OK) == -1 && errno == ENOENT);
+
+	CHECK(write_file(ROOT "/cgroup.subtree_control", "+foo") == -1 &&
+	      errno == ENOENT);
+	CHECK(write_file(ROOT "/cgroup.subtree_control",
+			 "+cpu +memory +pids") == 0);
+	CHECK_FILE(ROOT "/cgroup.subtree_control", "cpu memory pids\n");
+	CHECK_FILE(GROUP "/cgroup.controllers", "cpu memory pids\n");
+	CHECK_FILE(GROUP "/pids.max", "max\n");
+	CHECK_FILE(GROUP "/memory.max", "max\n");
+	CHECK_FILE(GROUP "/cpu.max", "max 100000\n");
+	CHECK_FILE(GROUP "/cpu.weight", "100\n");
+}
+
+void test_cpu(void)
+{
+	CHECK(write_file(GROUP "/cpu.max", "50000") == 0);
+	CHECK_FILE(GROUP "/cpu.max", "50000 100000\n");
+	CHECK(write_file(GROUP "/cpu.max", "20000 200000") == 0);
+	CHECK_FILE(GROUP "/cpu.max", "20000 200000\n");
+	CHECK(write_file(GROUP "/cpu.max", "500") == -1 && errno == EINVAL);
+	CHECK(write_file(GROUP "/cpu.max", "max") == 0);
+	CHECK_FILE(GROUP "/cpu.max", "max 200000\n");
+
+	CHECK(write_file(GROUP "/cpu.weight", "50") == 0);
+	CHECK_FILE(GROUP "/cpu.weight", "50\n");
+	CHECK(write_file(GROUP "/cpu.weight", "0") == -1 && errno == EINVAL);
+	CHECK(write_file(GROUP "/cpu.weight", "10001") == -1 &&
+	      errno == EINVAL);
+}
+
+#define MEMORY_MAX_PAGES 16
+#define OUTSIDE_PAGES 8192
+
+// Starts a process outside the cgroup that uses more memory than the
+// processes in the cgroup.
+static pid_t start_outside_process(void)
+{
+	int fds[2];
+	char c;
+
+	CHECK(pipe(fds) == 0);
+	pid_t pid = fork();
+	CHECK(pid >= 0);
+	if (pid == 0) {
+		CHECK(write_file(ROOT "/cgroup.procs", "0") == 0);
+		char *addr = mmap(NULL, OUTSIDE_PAGES * PAGE_SIZE,
+				  PROT_READ | PROT_WRITE,
+				  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+		CHECK(addr != MAP_FAILED);
+		for (int i = 0; i < OUTSIDE_PAGES; i++)
+			addr[i * PAGE_SIZE] = 1;
+		CHECK(write(fds[1], "", 1) == 1);
+		for (;;)
+			pause();
+	}
+	CHECK(close(fds[1]) == 0);
+	CHECK(read(fds[0], &c, 1) == 1);
+	CHECK(close(fds[0]) == 0);
+	return pid;
+}
+
+static void test_private_memory(void)
+{
+	char max[32];
+	int status;
+
+	pid_t outside_pid = start_outside_process();
+
+	snprintf(max, sizeof(max), "%ld",
+		 read_number(GROUP "/memory.current") +
+			 MEMORY_MAX_PAGES * PAGE_SIZE);
+	CHECK(write_file(GROUP "/memory.max", max) == 0);
+
+	pid_t pid = fork();
+	CHECK(pid >= 0);
+	if (pid == 0) {
+		char *addr = mmap(NULL, 4 * MEMORY_MAX_PAGES * PAGE_SIZE,
+				  PROT_READ | PROT_WRITE,
+				  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+		if (addr == MAP_FAILED)
+			_exit(EXIT_FAILURE);
+		for (int i = 0; i < 4 * MEMORY_MAX_PAGES; i++)
+			addr[i * PAGE_SIZE] = 1;
+		_exit(EXIT_SUCCESS);
+	}
+
+	// The victim is selected in the cgroup, even if the process outside
+	// the cgroup uses more memory.
+	CHECK(waitpid(pid, &status, 0) == pid && WIFSIGNALED(status) &&
+	      WTERMSIG(status) == SIGKILL);
+	CHECK(waitpid(outside_pid, &status, WNOHANG) == 0);
+	CHECK(kill(outside_pid, SIGKILL) == 0);
+	CHECK(waitpid(outside_pid, &status, 0) == outside_pid &&
+	      WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL);
+
+	CHECK(write_file(GROUP "/memory.max", "max") == 0);
+}
+
+static void child_main(int ready_fd)
+{
+	CHECK(write_file(GROUP "/cgroup.procs", "0") == 0);
+	CHECK_FILE("/proc/self/cgroup", "0::/test\n");
+	CHECK(read_number(GROUP "/pids.current") == 1);
+
+	// The number of threads is limited by `pids.max`.
+	CHECK(write_file(GROUP "/pids.max", "1") == 0);
+	CHECK(fork() == -1 && errno == EAGAIN);
+	CHECK(write_file(GROUP "/pids.max", "max") == 0);
+
+	CHECK(write_file(GROUP "/memory.max", "16K") == 0);
+	CHECK_FILE(GROUP "/memory.max", "16384\n");
+	CHECK(write_file(GROUP "/memory.max", "max") == 0);
+
+	// The pages of shared anonymous mappings are charged to the memory.
+	// The private pages that the process has copied since it joined the
+	// cgroup are charged as well.
+	long base = read_number(GROUP "/memory.current");
+	char *addr = mmap(NULL, 4 * PAGE_SIZE, PROT_READ | PROT_WRITE,
+			  MAP_SHARED | MAP_ANONYMOUS, -1, 0);
+	CHECK(addr != MAP_FAILED);
+	for (int i = 0; i < 4; i++)
+		addr[i * PAGE_SIZE] = 1;
+	CHECK(read_number(GROUP "/memory.current") == base + 4 * PAGE_SIZE);
+	CHECK(munmap(addr, 4 * PAGE_SIZE) == 0);
+	CHECK(read_number(GROUP "/memory.current") == base);
+
+	// The pages of private anonymous mappings are charged to the memory,
+	// so the process touching more pages than `memory.max` is killed.
+	test_private_memory();
+
+	CHECK(write(ready_fd, "", 1) == 1);
+	pause();
+}
+
+void test_procs(void)
+{
+	int fds[2];
+	CHECK(pipe(fds) == 0);
+
+	pid_t pid = fork();
+	CHECK(pid >= 0);
+	if (pid == 0) {
+		CHECK(close(fds[0]) == 0);
+		child_main(fds[1]);
+		exit(0);
+	}
+	CHECK(close(fds[1]) == 0);
+	char c;
+	CHECK(read(fds[0], &c, 1) == 1);
+
+	char expected[32];
+	snprintf(expected, sizeof(expected), "%d\n", pid);
+	CHECK_FILE(GROUP "/cgroup.procs", expected);
+	CHECK(rmdir(GROUP) == -1 && errno == EBUSY);
+
+	// No controllers can be enabled in a cgroup with processes.
+	CHECK(write_file(GROUP "/cgroup.subtree_control", "+pids") == -1 &&
+	      errno == EBUSY);
+
+	CHECK(kill(pid, SIGKILL) == 0);
+	CHECK(waitpid(pid, NULL, 0) == pid);
+	CHECK(read_number(GROUP "/pids.current") == 0);
+	CHECK_FILE(GROUP "/cgroup.procs", "");
+	CHECK(close(fds[0]) == 0);
+}
+
+void test_remove(void)
+{
+	CHECK(write_file(ROOT "/cgroup.subtree_control",
+			 "-cpu -memory -pids") == 0);
+	CHECK(access(GROUP "/pids.max", F_OK) == -1 && errno == ENOENT);
+	CHECK(rmdir(GROUP) == 0);
+	CHECK(access(GROUP, F_OK) == -1 && errno == ENOENT);
+}
+
+int main(void)
+{
+	CHECK(mkdir(ROOT, 0755) == 0 || errno == EEXIST);
+	CHECK(mount("none", ROOT, "cgroup2", 0, NULL) == 0);
+
+	test_hierarchy();
+	test_cpu();
+	test_procs();
+	test_remove();
+
+	CHECK(umount(ROOT) == 0);
+	printf("All tests passed.\n");
+	return 0;
+}
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+#include <sys/time.h>
+#include <sys/timex.h>
+#include <sys/wait.h>
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+#define STEP_SECS 1000
+#define MAX_FREQ (500L << 16)
+
+static long read_secs(clockid_t clockid)
+{
+	struct timespec ts;
+	CHECK(clock_gettime(clockid, &ts) == 0);
+	return ts.tv_sec;
+}
+
+static void test_step(void)
+{
+	long realtime = read_secs(CLOCK_REALTIME);
+	long monotonic = read_secs(CLOCK_MONOTONIC);
+
+	struct timespec ts = { .tv_sec = realtime + STEP_SECS, .tv_nsec = 0 };
+	CHECK(clock_settime(CLOCK_REALTIME, &ts) == 0);
+
+	// The real time is stepped, including the one read from the vDSO.
+	CHECK(read_secs(CLOCK_REALTIME) - realtime >= STEP_SECS);
+	CHECK(read_secs(CLOCK_REALTIME) - realtime <= STEP_SECS + 2);
+	CHECK(read_secs(CLOCK_REALTIME_COARSE) - realtime >= STEP_SECS - 1);
+	CHECK(time(NULL) - realtime >= STEP_SECS - 1);
+	struct timeval tv;
+	CHECK(gettimeofday(&tv, NULL) == 0);
+	CHECK(tv.tv_sec - realtime >= STEP_SECS);
+
+	// The monotonic time is not affected.
+	CHECK(read_secs(CLOCK_MONOTONIC) - monotonic <= 2);
+
+	// Step the real time back.
+	tv.tv_sec -= STEP_SECS;
+	CHECK(settimeofday(&tv, NULL) == 0);
+	CHECK(read_secs(CLOCK_REALTIME) - realtime <= 2);
+
+	// The time zone is ignored.
+	CHECK(settimeofday(NULL, NULL) == 0);
+}
+
+static void test_invalid_settime(void)
+{
+	struct timespec ts = { .tv_sec = read_secs(CLOCK_REALTIME) };
+
+	CHECK(clock_settime(CLOCK_MONOTONIC, &ts) == -1 && errno == EINVAL);
+	CHECK(clock_settime(CLOCK_BOOTTIME, &ts) == -1 && errno == EINVAL);
+
+	ts.tv_nsec = -1;
+	CHECK(clock_settime(CLOCK_REALTIME, &ts) == -1 && errno == EINVAL);
+}
+
+static void test_getres(void)
+{
+	struct timespec res;
+
+	CHECK(clock_getres(CLOCK_REALTIME, &res) == 0);
+	CHECK(res.tv_sec == 0 && res.tv_nsec == 1);
+	CHECK(clock_getres(CLOCK_MONOTONIC, &res) == 0);
+	CHECK(res.tv_sec == 0 && res.tv_nsec == 1);
+	CHECK(clock_getres(CLOCK_PROCESS_CPUTIME_ID, &res) == 0);
+	CHECK(res.tv_sec == 0 && res.tv_nsec == 1);
+
+	CHECK(clock_getres(CLOCK_MONOTONIC_COARSE, &res) == 0);
+	CHECK(res.tv_sec == 0 && res.tv_nsec > 1);
+
+	CHECK(clock_getres(CLOCK_REALTIME, NULL) == 0);
+	CHECK(clock_getres(100, &res) == -1 && errno == EINVAL);
+}
+
+static void test_adjtimex(void)
+{
+	struct timex tx;
+
+	memset(&tx, 0, sizeof(tx));
+	CHECK(adjtimex(&tx) >= 0);
+	CHECK(tx.freq == 0);
+	CHECK(tx.tick == 10000);
+
+	// The frequency is clamped.
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_FREQUENCY;
+	tx.freq = 2 * MAX_FREQ;
+	CHECK(adjtimex(&tx) >= 0);
+	CHECK(tx.freq == MAX_FREQ);
+
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_FREQUENCY;
+	tx.freq = 0;
+	CHECK(adjtimex(&tx) >= 0);
+	CHECK(tx.freq == 0);
+
+	// The offset is slewed instead of stepped.
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_OFFSET_SINGLESHOT;
+	tx.offset = 100000;
+	CHECK(adjtimex(&tx) >= 0);
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_OFFSET_SS_READ;
+	CHECK(adjtimex(&tx) >= 0);
+	CHECK(tx.offset > 0 && tx.offset <= 100000);
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_OFFSET_SINGLESHOT;
+	tx.offset = 0;
+	CHECK(adjtimex(&tx) >= 0);
+
+	// The offset is stepped with `ADJ_SETOFFSET`.
+	long realtime = read_secs(CLOCK_REALTIME);
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_SETOFFSET;
+	tx.time.tv_sec = STEP_SECS;
+	CHECK(adjtimex(&tx) >= 0);
+	CHECK(read_secs(CLOCK_REALTIME) - realtime >= STEP_SECS);
+	tx.time.tv_sec = -STEP_SECS;
+	CHECK(adjtimex(&tx) >= 0);
+	CHECK(read_secs(CLOCK_REALTIME) - realtime <= 2);
+
+	memset(&tx, 0, sizeof(tx));
+	tx.modes = ADJ_TICK;
+	tx.tick = 20000;
+	CHECK(adjtimex(&tx) == -1 && errno == EINVAL);
+
+	memset(&tx, 0, sizeof(tx));
+	CHECK(clock_adjtime(CLOCK_REALTIME, &tx) >= 0);
+	CHECK(clock_adjtime(CLOCK_MONOTONIC, &tx) == -1 &&
+	      errno == EOPNOTSUPP);
+}
+
+static void test_no_capability(void)
+{
+	pid_t pid = fork();
+	CHECK(pid >= 0);
+
+	if (pid == 0) {
+		// Dropping the root user drops `CAP_SYS_TIME`.
+		CHECK(setuid(65534) == 0);
+
+		struct timespec ts = { .tv_sec = read_secs(CLOCK_REALTIME) };
+		CHECK(clock_settime(CLOCK_REALTIME, &ts) == -1 &&
+		      errno == EPERM);
+		struct timeval tv = { .tv_sec = ts.tv_sec };
+		CHECK(settimeofday(&tv, NULL) == -1 && errno == EPERM);
+
+		struct timex tx;
+		memset(&tx, 0, sizeof(tx));
+		CHECK(adjtimex(&tx) >= 0);
+		tx.modes = ADJ_FREQUENCY;
+		CHECK(adjtimex(&tx) == -1 && errno == EPERM);
+
+		exit(0);
+	}
+
+	int status;
+	CHECK(waitpid(pid, &status, 0) == pid);
+	CHECK(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+
+int main(void)
+{
+	test_step();
+	test_invalid_settime();
+	test_getres();
+	test_adjtimex();
+	test_no_capability();
+
+	printf("All clock_settime tests passed.\n");
+	return 0;
+}
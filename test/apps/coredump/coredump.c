@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <elf.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <limits.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/prctl.h>
+#include <sys/resource.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+#define CORE_PATTERN_PATH "/proc/sys/kernel/core_pattern"
+#define MARKER "coredump-test-marker"
+#define NR_EXTRA_THREADS 2
+
+static char marker_buf[64];
+
+static void read_core_pattern(char *buf, size_t size)
+{
+	int fd = open(CORE_PATTERN_PATH, O_RDONLY);
+	ssize_t len;
+
+	CHECK(fd >= 0);
+	len = read(fd, buf, size - 1);
+	CHECK(len > 0);
+	buf[len] = '\0';
+	CHECK(close(fd) == 0);
+}
+
+static void write_core_pattern(const char *pattern)
+{
+	int fd = open(CORE_PATTERN_PATH, O_WRONLY);
+
+	CHECK(fd >= 0);
+	CHECK(write(fd, pattern, strlen(pattern)) == strlen(pattern));
+	CHECK(close(fd) == 0);
+}
+
+static void *spin(void *arg)
+{
+	(void)arg;
+	for (;;)
+		;
+	return NULL;
+}
+
+// Forks a child that sets up the core limit and the dumpable attribute,
+// spawns `nr_extra_threads` spinning threads, then aborts. Returns the wait
+// status of the child.
+static int fork_and_abort(pid_t *pid, rlim_t core_limit, int dumpable,
+			  int nr_extra_threads)
+{
+	int status;
+
+	*pid = fork();
+	CHECK(*pid >= 0);
+
+	if (*pid == 0) {
+		struct rlimit rlimit = { core_limit, core_limit };
+
+		strcpy(marker_buf, MARKER);
+		if (setrlimit(RLIMIT_CORE, &rlimit) != 0)
+			_exit(1);
+		if (prctl(PR_SET_DUMPABLE, dumpable) != 0 ||
+		    prctl(PR_GET_DUMPABLE) != dumpable)
+			_exit(1);
+		for (int i = 0; i < nr_extra_threads; i++) {
+			pthread_t thread;
+
+			if (pthread_create(&thread, NULL, spin, NULL) != 0)
+				_exit(1);
+		}
+		abort();
+	}
+
+	CHECK(waitpid(*pid, &status, 0) == *pid);
+	CHECK(WIFSIGNALED(status) && WTERMSIG(status) == SIGABRT);
+	return status;
+}
+
+static int core_exists(pid_t pid)
+{
+	char path[32];
+
+	snprintf(path, sizeof(path), "core.%d", pid);
+	return access(path, F_OK) == 0;
+}
+
+// Returns the number of `NT_PRSTATUS` notes, i.e., the number of threads.
+static int count_prstatus(const char *notes, size_t len)
+{
+	size_t offset = 0;
+	int count = 0;
+
+	while (offset + sizeof(Elf64_Nhdr) <= len) {
+		const Elf64_Nhdr *nhdr = (const Elf64_Nhdr *)(notes + offset);
+
+		if (nhdr->n_type == NT_PRSTATUS)
+			count++;
+		offset += sizeof(Elf64_Nhdr) + ((nhdr->n_namesz + 3) & ~3) +
+			  ((nhdr->n_descsz + 3) & ~3);
+	}
+
+	return count;
+}
+
+static void check_core_file(pid_t pid, int nr_threads)
+{
+	char path[32];
+	struct stat stat_buf;
+	Elf64_Ehdr ehdr;
+	Elf64_Phdr phdr;
+	char *data, *notes;
+	int fd, has_load = 0;
+
+	snprintf(path, sizeof(path), "core.%d", pid);
+	fd = open(path, O_RDONLY);
+	CHECK(fd >= 0);
+	CHECK(fstat(fd, &stat_buf) == 0);
+	CHECK((stat_buf.st_mode & 0777) == 0600);
+
+	CHECK(read(fd, &ehdr, sizeof(ehdr)) == sizeof(ehdr));
+	CHECK(memcmp(ehdr.e_ident, ELFMAG, SELFMAG) == 0);
+	CHECK(ehdr.e_ident[EI_CLASS] == ELFCLASS64);
+	CHECK(ehdr.e_type == ET_CORE);
+	CHECK(ehdr.e_phnum >= 2);
+
+	// The first segment holds the notes.
+	CHECK(pread(fd, &phdr, sizeof(phdr), ehdr.e_phoff) == sizeof(phdr));
+	CHECK(phdr.p_type == PT_NOTE && phdr.p_filesz > 0);
+
+	// Each thread has an `NT_PRSTATUS` note.
+	notes = malloc(phdr.p_filesz);
+	CHECK(notes != NULL);
+	CHECK(pread(fd, notes, phdr.p_filesz, phdr.p_offset) == phdr.p_filesz);
+	CHECK(count_prstatus(notes, phdr.p_filesz) == nr_threads);
+	free(notes);
+
+	for (int i = 1; i < ehdr.e_phnum; i++) {
+		CHECK(pread(fd, &phdr, sizeof(phdr),
+			    ehdr.e_phoff + i * sizeof(phdr)) == sizeof(phdr));
+		CHECK(phdr.p_type == PT_LOAD);
+		CHECK(phdr.p_filesz <= phdr.p_memsz);
+		has_load |= phdr.p_filesz > 0;
+	}
+	CHECK(has_load);
+
+	// The memory of the child should be found in the core file.
+	data = malloc(stat_buf.st_size);
+	CHECK(data != NULL);
+	CHECK(pread(fd, data, stat_buf.st_size, 0) == stat_buf.st_size);
+	CHECK(memmem(data, stat_buf.st_size, MARKER, strlen(MARKER)) != NULL);
+	free(data);
+
+	CHECK(close(fd) == 0);
+	CHECK(unlink(path) == 0);
+}
+
+static void test_dump(void)
+{
+	pid_t pid;
+	int status = fork_and_abort(&pid, RLIM_INFINITY, 1, 0);
+
+	CHECK(WCOREDUMP(status));
+	check_core_file(pid, 1);
+
+	printf("%s passed\n", __func__);
+}
+
+static void test_dump_threads(void)
+{
+	pid_t pid;
+	int status = fork_and_abort(&pid, RLIM_INFINITY, 1, NR_EXTRA_THREADS);
+
+	CHECK(WCOREDUMP(status));
+	check_core_file(pid, NR_EXTRA_THREADS + 1);
+
+	printf("%s passed\n", __func__);
+}
+
+static void test_zero_core_limit(void)
+{
+	pid_t pid;
+	int status = fork_and_abort(&pid, 0, 1, 0);
+
+	CHECK(!WCOREDUMP(status));
+	CHECK(!core_exists(pid));
+
+	printf("%s passed\n", __func__);
+}
+
+static void test_not_dumpable(void)
+{
+	pid_t pid;
+	int status = fork_and_abort(&pid, RLIM_INFINITY, 0, 0);
+
+	CHECK(!WCOREDUMP(status));
+	CHECK(!core_exists(pid));
+
+	printf("%s passed\n", __func__);
+}
+
+int main(void)
+{
+	char old_pattern[PATH_MAX], new_pattern[PATH_MAX];
+	char dir[] = "/tmp/coredump_XXXXXX";
+
+	CHECK(mkdtemp(dir) != NULL);
+	CHECK(chdir(dir) == 0);
+
+	read_core_pattern(old_pattern, sizeof(old_pattern));
+	write_core_pattern("core.%p\n");
+	read_core_pattern(new_pattern, sizeof(new_pattern));
+	CHECK(strcmp(new_pattern, "core.%p\n") == 0);
+
+	test_dump();
+	test_dump_threads();
+	test_zero_core_limit();
+	test_not_dumpable();
+
+	write_core_pattern(old_pattern);
+	CHECK(chdir("/") == 0);
+	CHECK(rmdir(dir) == 0);
+
+	printf("All tests passed\n");
+	return 0;
+}
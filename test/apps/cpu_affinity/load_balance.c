@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <pthread.h>
+#include <sched.h>
+#include <stdatomic.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <time.h>
+#include <unistd.h>
+
+#define MAX_WORKERS 64
+#define SPIN_MS 1000
+
+struct worker {
+	pthread_t thread;
+	// The CPU that the worker is pinned to, or -1 if it is not pinned.
+	int pinned_cpu;
+	// The CPUs that the worker has run on while spinning.
+	cpu_set_t seen_cpus;
+	// Whether the worker has run on a CPU other than the pinned one.
+	int is_misplaced;
+};
+
+static atomic_int nr_ready;
+static atomic_int start;
+static atomic_int stop;
+static cpu_set_t all_cpus;
+
+static void *spin(void *arg)
+{
+	struct worker *worker = arg;
+	cpu_set_t mask;
+
+	if (worker->pinned_cpu >= 0) {
+		CPU_ZERO(&mask);
+		CPU_SET(worker->pinned_cpu, &mask);
+		if (sched_setaffinity(0, sizeof(mask), &mask) < 0) {
+			perror("sched_setaffinity");
+			exit(EXIT_FAILURE);
+		}
+		// The thread is moved to the allowed CPU once it is woken up.
+		usleep(1000);
+	} else if (sched_setaffinity(0, sizeof(all_cpus), &all_cpus) < 0) {
+		perror("sched_setaffinity");
+		exit(EXIT_FAILURE);
+	}
+	// An unpinned worker never sleeps after this, so it can only be moved
+	// to other CPUs by the load balancing.
+
+	atomic_fetch_add(&nr_ready, 1);
+	while (!atomic_load(&start))
+		;
+
+	while (!atomic_load(&stop)) {
+		int cpu = sched_getcpu();
+		if (cpu < 0) {
+			perror("sched_getcpu");
+			exit(EXIT_FAILURE);
+		}
+		CPU_SET(cpu, &worker->seen_cpus);
+		if (worker->pinned_cpu >= 0 && cpu != worker->pinned_cpu)
+			worker->is_misplaced = 1;
+	}
+
+	return NULL;
+}
+
+static void pin_self(int cpu)
+{
+	cpu_set_t mask;
+
+	CPU_ZERO(&mask);
+	CPU_SET(cpu, &mask);
+	if (sched_setaffinity(0, sizeof(mask), &mask) < 0) {
+		perror("sched_setaffinity");
+		exit(EXIT_FAILURE);
+	}
+}
+
+// Runs `nr_workers` workers that spin for `SPIN_MS` milliseconds.
+static void run_workers(struct worker *workers, int nr_workers)
+{
+	struct timespec duration = {
+		.tv_sec = SPIN_MS / 1000,
+		.tv_nsec = (SPIN_MS % 1000) * 1000000L,
+	};
+
+	atomic_store(&nr_ready, 0);
+	atomic_store(&start, 0);
+	atomic_store(&stop, 0);
+
+	for (int i = 0; i < nr_workers; i++) {
+		CPU_ZERO(&workers[i].seen_cpus);
+		workers[i].is_misplaced = 0;
+		if (pthread_create(&workers[i].thread, NULL, spin,
+				   &workers[i]) != 0) {
+			perror("pthread_create");
+			exit(EXIT_FAILURE);
+		}
+	}
+
+	while (atomic_load(&nr_ready) < nr_workers)
+		sched_yield();
+	atomic_store(&start, 1);
+	nanosleep(&duration, NULL);
+	atomic_store(&stop, 1);
+
+	for (int i = 0; i < nr_workers; i++)
+		pthread_join(workers[i].thread, NULL);
+}
+
+// Checks that the threads pinned to different CPUs run on their own CPUs
+// simultaneously, and are never migrated by the load balancing.
+static void test_pinned_threads(int nr_cpus, const int *cpus)
+{
+	struct worker workers[MAX_WORKERS];
+
+	for (int i = 0; i < nr_cpus; i++)
+		workers[i].pinned_cpu = cpus[i];
+	run_workers(workers, nr_cpus);
+
+	for (int i = 0; i < nr_cpus; i++) {
+		if (workers[i].is_misplaced ||
+		    !CPU_ISSET(cpus[i], &workers[i].seen_cpus)) {
+			printf("Error: the thread pinned to CPU %d runs on other CPUs\n",
+			       cpus[i]);
+			exit(EXIT_FAILURE);
+		}
+	}
+	printf("Pinned threads run on their own CPUs\n");
+}
+
+// Checks that the threads spawned on one CPU are pulled by the other CPUs,
+// so that all the CPUs are used.
+static void test_work_stealing(int nr_cpus, const int *cpus)
+{
+	struct worker workers[MAX_WORKERS];
+	int nr_workers = nr_cpus * 2;
+	cpu_set_t seen_cpus;
+
+	if (nr_workers > MAX_WORKERS)
+		nr_workers = MAX_WORKERS;
+
+	// The new threads inherit the affinity, so they are all spawned on
+	// the first CPU.
+	pin_self(cpus[0]);
+	for (int i = 0; i < nr_workers; i++)
+		workers[i].pinned_cpu = -1;
+	run_workers(workers, nr_workers);
+	if (sched_setaffinity(0, sizeof(all_cpus), &all_cpus) < 0) {
+		perror("sched_setaffinity");
+		exit(EXIT_FAILURE);
+	}
+
+	CPU_ZERO(&seen_cpus);
+	for (int i = 0; i < nr_workers; i++)
+		CPU_OR(&seen_cpus, &seen_cpus, &workers[i].seen_cpus);
+	if (CPU_COUNT(&seen_cpus) != nr_cpus) {
+		printf("Error: the threads only run on %d of %d CPUs\n",
+		       CPU_COUNT(&seen_cpus), nr_cpus);
+		exit(EXIT_FAILURE);
+	}
+	printf("Threads are spread across all the CPUs\n");
+}
+
+int main(void)
+{
+	int cpus[MAX_WORKERS];
+	int nr_cpus = 0;
+
+	if (sched_getaffinity(0, sizeof(all_cpus), &all_cpus) < 0) {
+		perror("sched_getaffinity");
+		exit(EXIT_FAILURE);
+	}
+	for (int i = 0; i < CPU_SETSIZE && nr_cpus < MAX_WORKERS; i++) {
+		if (CPU_ISSET(i, &all_cpus))
+			cpus[nr_cpus++] = i;
+	}
+	if (nr_cpus < 2) {
+		printf("Skip the load balancing test on a single CPU\n");
+		return 0;
+	}
+
+	test_pinned_threads(nr_cpus, cpus);
+	test_work_stealing(nr_cpus, cpus);
+
+	return 0;
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+#include <sys/syscall.h>
+#include <sys/time.h>
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+#define TIMER_VALUE 42
+
+static volatile sig_atomic_t nr_signals;
+static volatile sig_atomic_t last_signo;
+static volatile sig_atomic_t last_code;
+static volatile sig_atomic_t last_value;
+static volatile sig_atomic_t last_overrun;
+
+static void handler(int signo, siginfo_t *info, void *ucontext)
+{
+	nr_signals++;
+	last_signo = signo;
+	last_code = info->si_code;
+	last_value = info->si_value.sival_int;
+	last_overrun = info->si_overrun;
+}
+
+static void set_handler(int signo)
+{
+	struct sigaction sa;
+	memset(&sa, 0, sizeof(sa));
+	sa.sa_flags = SA_SIGINFO | SA_RESTART;
+	sa.sa_sigaction = handler;
+	sigemptyset(&sa.sa_mask);
+	CHECK(sigaction(signo, &sa, NULL) == 0);
+
+	nr_signals = 0;
+	last_signo = 0;
+}
+
+static long elapsed_ms(clockid_t clockid, const struct timespec *start)
+{
+	struct timespec now;
+	CHECK(clock_gettime(clockid, &now) == 0);
+	return (now.tv_sec - start->tv_sec) * 1000 +
+	       (now.tv_nsec - start->tv_nsec) / 1000000;
+}
+
+// Spins in the user space until the CPU time is consumed.
+static void spin_cpu(clockid_t clockid, long ms)
+{
+	struct timespec start;
+	CHECK(clock_gettime(clockid, &start) == 0);
+	while (elapsed_ms(clockid, &start) < ms)
+		;
+}
+
+static void test_itimer(int which, int signo)
+{
+	set_handler(signo);
+
+	struct itimerval itv = {
+		.it_interval = { .tv_sec = 0, .tv_usec = 10000 },
+		.it_value = { .tv_sec = 0, .tv_usec = 10000 },
+	};
+	CHECK(setitimer(which, &itv, NULL) == 0);
+	spin_cpu(CLOCK_PROCESS_CPUTIME_ID, 200);
+
+	memset(&itv, 0, sizeof(itv));
+	CHECK(setitimer(which, &itv, NULL) == 0);
+
+	CHECK(nr_signals >= 5);
+	CHECK(last_signo == signo);
+}
+
+static timer_t create_timer(clockid_t clockid, struct sigevent *sev)
+{
+	timer_t timerid;
+	CHECK(timer_create(clockid, sev, &timerid) == 0);
+	return timerid;
+}
+
+static void arm_timer(timer_t timerid, long interval_ms)
+{
+	struct itimerspec its = {
+		.it_interval = { .tv_sec = 0,
+				 .tv_nsec = interval_ms * 1000000 },
+		.it_value = { .tv_sec = 0, .tv_nsec = interval_ms * 1000000 },
+	};
+	CHECK(timer_settime(timerid, 0, &its, NULL) == 0);
+}
+
+static void test_cpu_timer(clockid_t clockid)
+{
+	set_handler(SIGRTMIN);
+
+	struct sigevent sev;
+	memset(&sev, 0, sizeof(sev));
+	sev.sigev_notify = SIGEV_SIGNAL;
+	sev.sigev_signo = SIGRTMIN;
+	sev.sigev_value.sival_int = TIMER_VALUE;
+	timer_t timerid = create_timer(clockid, &sev);
+
+	arm_timer(timerid, 10);
+	spin_cpu(clockid, 200);
+	CHECK(timer_delete(timerid) == 0);
+
+	CHECK(nr_signals >= 5);
+	CHECK(last_signo == SIGRTMIN);
+	CHECK(last_code == SI_TIMER);
+	CHECK(last_value == TIMER_VALUE);
+}
+
+static void test_thread_id(void)
+{
+	set_handler(SIGUSR1);
+
+	struct sigevent sev;
+	memset(&sev, 0, sizeof(sev));
+	sev.sigev_notify = SIGEV_THREAD_ID;
+	sev.sigev_signo = SIGUSR1;
+	sev.sigev_value.sival_int = TIMER_VALUE;
+	sev._sigev_un._tid = syscall(SYS_gettid);
+	timer_t timerid = create_timer(CLOCK_MONOTONIC, &sev);
+
+	arm_timer(timerid, 10);
+	while (nr_signals == 0)
+		pause();
+	CHECK(timer_delete(timerid) == 0);
+
+	CHECK(last_signo == SIGUSR1);
+	CHECK(last_code == SI_TIMER);
+	CHECK(last_value == TIMER_VALUE);
+
+	sev._sigev_un._tid = 0x7fffffff;
+	CHECK(timer_create(CLOCK_MONOTONIC, &sev, &timerid) == -1 &&
+	      errno == EINVAL);
+}
+
+static void test_overrun(void)
+{
+	set_handler(SIGUSR2);
+
+	sigset_t mask;
+	sigemptyset(&mask);
+	sigaddset(&mask, SIGUSR2);
+	CHECK(sigprocmask(SIG_BLOCK, &mask, NULL) == 0);
+
+	struct sigevent sev;
+	memset(&sev, 0, sizeof(sev));
+	sev.sigev_notify = SIGEV_SIGNAL;
+	sev.sigev_signo = SIGUSR2;
+	timer_t timerid = create_timer(CLOCK_MONOTONIC, &sev);
+	CHECK(timer_getoverrun(timerid) == 0);
+
+	// The expirations while the signal is blocked are counted as overruns.
+	arm_timer(timerid, 5);
+	usleep(200 * 1000);
+	CHECK(sigprocmask(SIG_UNBLOCK, &mask, NULL) == 0);
+	CHECK(nr_signals == 1);
+	CHECK(last_code == SI_TIMER);
+	CHECK(last_overrun >= 10);
+	CHECK(timer_getoverrun(timerid) == last_overrun);
+
+	CHECK(timer_delete(timerid) == 0);
+	CHECK(timer_getoverrun(timerid) == -1 && errno == EINVAL);
+}
+
+int main(void)
+{
+	test_itimer(ITIMER_PROF, SIGPROF);
+	test_itimer(ITIMER_VIRTUAL, SIGVTALRM);
+	test_cpu_timer(CLOCK_PROCESS_CPUTIME_ID);
+	test_cpu_timer(CLOCK_THREAD_CPUTIME_ID);
+	test_thread_id();
+	test_overrun();
+
+	printf("All CPU timer tests passed.\n");
+	return 0;
+}
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 8
+// The time to wait for the pages to be merged, in seconds.
+#define MERGE_TIMEOUT 10
+
+static int read_merging_pages(void)
+{
+	FILE *file = fopen("/proc/self/ksm_merging_pages", "r");
+	if (file == NULL) {
+		perror("fopen failed");
+		exit(1);
+	}
+
+	int nr_pages;
+	if (fscanf(file, "%d", &nr_pages) != 1) {
+		printf("failed to read the number of merging pages\n");
+		exit(1);
+	}
+	fclose(file);
+	return nr_pages;
+}
+
+static void wait_for_merging_pages(int expected)
+{
+	for (int i = 0; i < MERGE_TIMEOUT * 10; i++) {
+		if (read_merging_pages() == expected) {
+			return;
+		}
+		usleep(100 * 1000);
+	}
+	printf("expected %d merging pages, but found %d\n", expected,
+	       read_merging_pages());
+	exit(1);
+}
+
+static void check_pages(char *addr, char value)
+{
+	for (int i = 0; i < NR_PAGES * PAGE_SIZE; i++) {
+		if (addr[i] != value) {
+			printf("the content at offset %d is corrupted\n", i);
+			exit(1);
+		}
+	}
+}
+
+int main(void)
+{
+	char *addr = mmap(NULL, NR_PAGES * PAGE_SIZE, PROT_READ | PROT_WRITE,
+			  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	memset(addr, 'a', NR_PAGES * PAGE_SIZE);
+
+	printf("Test merging identical pages\n");
+	if (madvise(addr, NR_PAGES * PAGE_SIZE, MADV_MERGEABLE) < 0) {
+		perror("madvise(MADV_MERGEABLE) failed");
+		exit(1);
+	}
+	wait_for_merging_pages(NR_PAGES);
+	check_pages(addr, 'a');
+
+	printf("Test writing to a merged page\n");
+	addr[0] = 'b';
+	if (read_merging_pages() != NR_PAGES - 1) {
+		printf("writing does not break the sharing\n");
+		exit(1);
+	}
+	if (addr[PAGE_SIZE] != 'a') {
+		printf("writing changes other merged pages\n");
+		exit(1);
+	}
+	addr[0] = 'a';
+
+	printf("Test unmerging pages\n");
+	if (madvise(addr, NR_PAGES * PAGE_SIZE, MADV_UNMERGEABLE) < 0) {
+		perror("madvise(MADV_UNMERGEABLE) failed");
+		exit(1);
+	}
+	if (read_merging_pages() != 0) {
+		printf("MADV_UNMERGEABLE does not unmerge the pages\n");
+		exit(1);
+	}
+	check_pages(addr, 'a');
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+
+	printf("All KSM tests passed\n");
+	return 0;
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <signal.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 4
+
+static char *map_anonymous(void)
+{
+	char *addr = mmap(NULL, NR_PAGES * PAGE_SIZE, PROT_READ | PROT_WRITE,
+			  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	memset(addr, 0x5a, NR_PAGES * PAGE_SIZE);
+	return addr;
+}
+
+// Forks a child that reads the first byte of `addr`, and returns the wait
+// status of the child. The child exits with the value it reads.
+static int fork_and_read(char *addr)
+{
+	pid_t pid = fork();
+	if (pid < 0) {
+		perror("fork");
+		exit(1);
+	} else if (pid == 0) {
+		_exit(*(volatile char *)addr);
+	}
+
+	int status;
+	if (waitpid(pid, &status, 0) < 0) {
+		perror("waitpid");
+		exit(1);
+	}
+	return status;
+}
+
+void test_dontneed(void)
+{
+	char *addr = map_anonymous();
+
+	if (madvise(addr + PAGE_SIZE, PAGE_SIZE, MADV_DONTNEED) < 0) {
+		perror("madvise(MADV_DONTNEED) failed");
+		exit(1);
+	}
+	if (addr[0] != 0x5a || addr[PAGE_SIZE] != 0 ||
+	    addr[2 * PAGE_SIZE] != 0x5a) {
+		printf("MADV_DONTNEED does not zap the page\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+void test_dontfork(void)
+{
+	char *addr = map_anonymous();
+
+	if (madvise(addr, PAGE_SIZE, MADV_DONTFORK) < 0) {
+		perror("madvise(MADV_DONTFORK) failed");
+		exit(1);
+	}
+	int status = fork_and_read(addr);
+	if (!WIFSIGNALED(status) || WTERMSIG(status) != SIGSEGV) {
+		printf("MADV_DONTFORK mapping is inherited by the child\n");
+		exit(1);
+	}
+	status = fork_and_read(addr + PAGE_SIZE);
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0x5a) {
+		printf("MADV_DONTFORK affects the pages outside the range\n");
+		exit(1);
+	}
+
+	if (madvise(addr, PAGE_SIZE, MADV_DOFORK) < 0) {
+		perror("madvise(MADV_DOFORK) failed");
+		exit(1);
+	}
+	status = fork_and_read(addr);
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0x5a) {
+		printf("MADV_DOFORK mapping is not inherited by the child\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+void test_wipeonfork(void)
+{
+	char *addr = map_anonymous();
+
+	if (madvise(addr, PAGE_SIZE, MADV_WIPEONFORK) < 0) {
+		perror("madvise(MADV_WIPEONFORK) failed");
+		exit(1);
+	}
+	int status = fork_and_read(addr);
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+		printf("MADV_WIPEONFORK mapping is not wiped in the child\n");
+		exit(1);
+	}
+	if (addr[0] != 0x5a) {
+		printf("MADV_WIPEONFORK mapping is wiped in the parent\n");
+		exit(1);
+	}
+
+	// Only private anonymous mappings can be wiped on fork.
+	char *shared = mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+			    MAP_SHARED | MAP_ANONYMOUS, -1, 0);
+	if (shared == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	if (madvise(shared, PAGE_SIZE, MADV_WIPEONFORK) == 0 ||
+	    errno != EINVAL) {
+		printf("MADV_WIPEONFORK is allowed on a shared mapping\n");
+		exit(1);
+	}
+
+	munmap(shared, PAGE_SIZE);
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+void test_unmapped(void)
+{
+	char *addr = map_anonymous();
+	munmap(addr + PAGE_SIZE, PAGE_SIZE);
+
+	if (madvise(addr, NR_PAGES * PAGE_SIZE, MADV_DONTNEED) == 0 ||
+	    errno != ENOMEM) {
+		printf("madvise on a range with holes does not fail\n");
+		exit(1);
+	}
+	// The mapped parts are still advised.
+	if (addr[0] != 0 || addr[2 * PAGE_SIZE] != 0) {
+		printf("madvise does not apply to the mapped parts\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+int main(void)
+{
+	printf("Test MADV_DONTNEED\n");
+	test_dontneed();
+
+	printf("Test MADV_DONTFORK and MADV_DOFORK\n");
+	test_dontfork();
+
+	printf("Test MADV_WIPEONFORK\n");
+	test_wipeonfork();
+
+	printf("Test madvise on unmapped range\n");
+	test_unmapped();
+
+	printf("All madvise tests passed\n");
+	return 0;
+}
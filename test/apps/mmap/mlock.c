@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 4
+
+static char *map_anonymous(void)
+{
+	char *addr = mmap(NULL, NR_PAGES * PAGE_SIZE, PROT_READ | PROT_WRITE,
+			  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	return addr;
+}
+
+// Returns the number of resident pages in the mapping.
+static int count_resident_pages(char *addr)
+{
+	unsigned char vec[NR_PAGES];
+	if (mincore(addr, NR_PAGES * PAGE_SIZE, vec) < 0) {
+		perror("mincore failed");
+		exit(1);
+	}
+
+	int count = 0;
+	for (int i = 0; i < NR_PAGES; i++) {
+		count += vec[i] & 1;
+	}
+	return count;
+}
+
+void test_mincore(void)
+{
+	char *addr = map_anonymous();
+	unsigned char vec[NR_PAGES];
+
+	if (count_resident_pages(addr) != 0) {
+		printf("untouched pages are resident\n");
+		exit(1);
+	}
+	addr[PAGE_SIZE] = 1;
+	if (mincore(addr, NR_PAGES * PAGE_SIZE, vec) < 0) {
+		perror("mincore failed");
+		exit(1);
+	}
+	if ((vec[0] & 1) || !(vec[1] & 1)) {
+		printf("mincore reports wrong residency\n");
+		exit(1);
+	}
+
+	if (mincore(addr + 1, PAGE_SIZE, vec) != -1 || errno != EINVAL) {
+		printf("mincore accepts an unaligned address\n");
+		exit(1);
+	}
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+	if (mincore(addr, PAGE_SIZE, vec) != -1 || errno != ENOMEM) {
+		printf("mincore accepts an unmapped range\n");
+		exit(1);
+	}
+}
+
+void test_mlock(void)
+{
+	char *addr = map_anonymous();
+
+	if (mlock(addr + 1, 2 * PAGE_SIZE) < 0) {
+		perror("mlock failed");
+		exit(1);
+	}
+	if (count_resident_pages(addr) != 3) {
+		printf("mlock does not populate the pages\n");
+		exit(1);
+	}
+	if (madvise(addr, PAGE_SIZE, MADV_DONTNEED) != -1 || errno != EINVAL) {
+		printf("MADV_DONTNEED discards locked pages\n");
+		exit(1);
+	}
+
+	if (munlock(addr, NR_PAGES * PAGE_SIZE) < 0) {
+		perror("munlock failed");
+		exit(1);
+	}
+	if (madvise(addr, PAGE_SIZE, MADV_DONTNEED) < 0) {
+		perror("madvise(MADV_DONTNEED) failed");
+		exit(1);
+	}
+
+	munmap(addr + PAGE_SIZE, PAGE_SIZE);
+	if (mlock(addr, NR_PAGES * PAGE_SIZE) != -1 || errno != ENOMEM) {
+		printf("mlock accepts a range with holes\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+void test_mlock_onfault(void)
+{
+	char *addr = map_anonymous();
+
+	if (mlock2(addr, NR_PAGES * PAGE_SIZE, 0x100) != -1 ||
+	    errno != EINVAL) {
+		printf("mlock2 accepts unknown flags\n");
+		exit(1);
+	}
+	if (mlock2(addr, NR_PAGES * PAGE_SIZE, MLOCK_ONFAULT) < 0) {
+		perror("mlock2 failed");
+		exit(1);
+	}
+	if (count_resident_pages(addr) != 0) {
+		printf("MLOCK_ONFAULT populates the pages\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+void test_mlockall(void)
+{
+	if (mlockall(MCL_ONFAULT) != -1 || errno != EINVAL) {
+		printf("mlockall accepts MCL_ONFAULT alone\n");
+		exit(1);
+	}
+	if (mlockall(MCL_CURRENT | MCL_FUTURE) < 0) {
+		perror("mlockall failed");
+		exit(1);
+	}
+
+	char *addr = map_anonymous();
+	if (count_resident_pages(addr) != NR_PAGES) {
+		printf("MCL_FUTURE does not populate new mappings\n");
+		exit(1);
+	}
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+
+	if (munlockall() < 0) {
+		perror("munlockall failed");
+		exit(1);
+	}
+	addr = map_anonymous();
+	if (count_resident_pages(addr) != 0) {
+		printf("munlockall does not cancel MCL_FUTURE\n");
+		exit(1);
+	}
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+}
+
+int main(void)
+{
+	printf("Test mincore\n");
+	test_mincore();
+
+	printf("Test mlock and munlock\n");
+	test_mlock();
+
+	printf("Test mlock2 with MLOCK_ONFAULT\n");
+	test_mlock_onfault();
+
+	printf("Test mlockall and munlockall\n");
+	test_mlockall();
+
+	printf("All mlock tests passed\n");
+	return 0;
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/wait.h>
+
+#define PAGE_SIZE 4096
+#define HUGE_PAGE_SIZE (2 * 1024 * 1024)
+
+// Maps an anonymous region that contains an aligned huge page range, and
+// advises it to be backed with huge pages. The aligned address is returned.
+static char *map_huge_range(void **base)
+{
+	size_t len = 2 * HUGE_PAGE_SIZE;
+	char *addr = mmap(NULL, len, PROT_READ | PROT_WRITE,
+			  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	if (madvise(addr, len, MADV_HUGEPAGE) < 0) {
+		perror("madvise(MADV_HUGEPAGE) failed");
+		exit(1);
+	}
+	*base = addr;
+
+	uintptr_t aligned = ((uintptr_t)addr + HUGE_PAGE_SIZE - 1) &
+			    ~(uintptr_t)(HUGE_PAGE_SIZE - 1);
+	return (char *)aligned;
+}
+
+static void check_content(char *addr, size_t len, char value)
+{
+	for (size_t i = 0; i < len; i += PAGE_SIZE) {
+		if (addr[i] != value) {
+			printf("unexpected value at %p: %d\n", addr + i,
+			       addr[i]);
+			exit(1);
+		}
+	}
+}
+
+void test_fault_and_split(void)
+{
+	void *base;
+	char *addr = map_huge_range(&base);
+
+	memset(addr, 1, HUGE_PAGE_SIZE);
+
+	// Unmapping a part of the huge page keeps the rest.
+	if (munmap(addr + PAGE_SIZE, PAGE_SIZE) < 0) {
+		perror("munmap failed");
+		exit(1);
+	}
+	check_content(addr, PAGE_SIZE, 1);
+	check_content(addr + 2 * PAGE_SIZE, HUGE_PAGE_SIZE - 2 * PAGE_SIZE, 1);
+
+	// Protecting a part of the huge page keeps the rest writable.
+	if (mprotect(addr + 2 * PAGE_SIZE, PAGE_SIZE, PROT_READ) < 0) {
+		perror("mprotect failed");
+		exit(1);
+	}
+	memset(addr + 3 * PAGE_SIZE, 2, HUGE_PAGE_SIZE - 3 * PAGE_SIZE);
+	check_content(addr + 2 * PAGE_SIZE, PAGE_SIZE, 1);
+	check_content(addr + 3 * PAGE_SIZE, HUGE_PAGE_SIZE - 3 * PAGE_SIZE, 2);
+
+	munmap(base, 2 * HUGE_PAGE_SIZE);
+}
+
+void test_fork(void)
+{
+	void *base;
+	char *addr = map_huge_range(&base);
+
+	memset(addr, 3, HUGE_PAGE_SIZE);
+
+	pid_t pid = fork();
+	if (pid < 0) {
+		perror("fork");
+		exit(1);
+	} else if (pid == 0) {
+		// The child writes to its copy of a part of the huge page.
+		memset(addr, 4, PAGE_SIZE);
+		check_content(addr, PAGE_SIZE, 4);
+		check_content(addr + PAGE_SIZE, HUGE_PAGE_SIZE - PAGE_SIZE, 3);
+		_exit(0);
+	}
+
+	int status;
+	if (waitpid(pid, &status, 0) < 0) {
+		perror("waitpid");
+		exit(1);
+	}
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+		printf("the child fails to access the huge page\n");
+		exit(1);
+	}
+	check_content(addr, HUGE_PAGE_SIZE, 3);
+
+	munmap(base, 2 * HUGE_PAGE_SIZE);
+}
+
+void test_nohugepage(void)
+{
+	void *base;
+	char *addr = map_huge_range(&base);
+
+	if (madvise(addr, HUGE_PAGE_SIZE, MADV_NOHUGEPAGE) < 0) {
+		perror("madvise(MADV_NOHUGEPAGE) failed");
+		exit(1);
+	}
+	memset(addr, 5, HUGE_PAGE_SIZE);
+	check_content(addr, HUGE_PAGE_SIZE, 5);
+
+	munmap(base, 2 * HUGE_PAGE_SIZE);
+}
+
+void test_mremap(void)
+{
+	void *base;
+	char *addr = map_huge_range(&base);
+
+	memset(addr, 6, HUGE_PAGE_SIZE);
+
+	// The destination is not aligned to the huge page size, so the huge
+	// page is split when being moved.
+	char *target = mmap(NULL, HUGE_PAGE_SIZE + 2 * PAGE_SIZE, PROT_NONE,
+			    MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (target == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	char *dest = target + PAGE_SIZE;
+	if (((uintptr_t)dest & (HUGE_PAGE_SIZE - 1)) == 0)
+		dest += PAGE_SIZE;
+	char *res = mremap(addr, HUGE_PAGE_SIZE, HUGE_PAGE_SIZE,
+			   MREMAP_MAYMOVE | MREMAP_FIXED, dest);
+	if (res != dest) {
+		perror("mremap failed");
+		exit(1);
+	}
+	check_content(dest, HUGE_PAGE_SIZE, 6);
+
+	// The moved pages can be unmapped one by one.
+	if (munmap(dest + PAGE_SIZE, PAGE_SIZE) < 0) {
+		perror("munmap failed");
+		exit(1);
+	}
+	check_content(dest, PAGE_SIZE, 6);
+	check_content(dest + 2 * PAGE_SIZE, HUGE_PAGE_SIZE - 2 * PAGE_SIZE, 6);
+
+	munmap(target, HUGE_PAGE_SIZE + 2 * PAGE_SIZE);
+	munmap(base, 2 * HUGE_PAGE_SIZE);
+}
+
+int main(void)
+{
+	printf("Test huge page faults and splits\n");
+	test_fault_and_split();
+
+	printf("Test huge pages across fork\n");
+	test_fork();
+
+	printf("Test MADV_NOHUGEPAGE\n");
+	test_nohugepage();
+
+	printf("Test mremap of huge pages to an unaligned address\n");
+	test_mremap();
+
+	printf("All huge page tests passed\n");
+	return 0;
+}
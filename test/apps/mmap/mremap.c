@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/mman.h>
+
+#define PAGE_SIZE 4096
+
+#ifndef MREMAP_DONTUNMAP
+#define MREMAP_DONTUNMAP 4
+#endif
+
+static char *map_anonymous(void *addr, size_t len, int flags)
+{
+	char *res = mmap(addr, len, PROT_READ | PROT_WRITE,
+			 MAP_PRIVATE | MAP_ANONYMOUS | flags, -1, 0);
+	if (res == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	return res;
+}
+
+static void check_content(char *addr, size_t len, char value)
+{
+	for (size_t i = 0; i < len; i += PAGE_SIZE) {
+		if (addr[i] != value) {
+			printf("unexpected value at %p: %d\n", addr + i,
+			       addr[i]);
+			exit(1);
+		}
+	}
+}
+
+void test_shrink_and_grow(void)
+{
+	// Reserve the space so that the mapping can grow in place.
+	char *addr = map_anonymous(NULL, 4 * PAGE_SIZE, 0);
+	munmap(addr + 2 * PAGE_SIZE, 2 * PAGE_SIZE);
+	memset(addr, 1, 2 * PAGE_SIZE);
+
+	char *res = mremap(addr, 2 * PAGE_SIZE, PAGE_SIZE, 0);
+	if (res != addr) {
+		perror("mremap shrink failed");
+		exit(1);
+	}
+
+	res = mremap(addr, PAGE_SIZE, 4 * PAGE_SIZE, 0);
+	if (res != addr) {
+		perror("mremap grow in place failed");
+		exit(1);
+	}
+	check_content(addr, PAGE_SIZE, 1);
+	check_content(addr + PAGE_SIZE, 3 * PAGE_SIZE, 0);
+
+	munmap(addr, 4 * PAGE_SIZE);
+}
+
+void test_maymove(void)
+{
+	// The last page blocks the growth in place.
+	char *addr = map_anonymous(NULL, 3 * PAGE_SIZE, 0);
+	memset(addr, 2, 2 * PAGE_SIZE);
+
+	if (mremap(addr, 2 * PAGE_SIZE, 4 * PAGE_SIZE, 0) != MAP_FAILED ||
+	    errno != ENOMEM) {
+		printf("mremap grows into an occupied range\n");
+		exit(1);
+	}
+
+	char *res = mremap(addr, 2 * PAGE_SIZE, 4 * PAGE_SIZE, MREMAP_MAYMOVE);
+	if (res == MAP_FAILED || res == addr) {
+		perror("mremap move failed");
+		exit(1);
+	}
+	check_content(res, 2 * PAGE_SIZE, 2);
+	check_content(res + 2 * PAGE_SIZE, 2 * PAGE_SIZE, 0);
+
+	munmap(res, 4 * PAGE_SIZE);
+	munmap(addr + 2 * PAGE_SIZE, PAGE_SIZE);
+}
+
+void test_fixed(void)
+{
+	char *addr = map_anonymous(NULL, 2 * PAGE_SIZE, 0);
+	char *target = map_anonymous(NULL, 2 * PAGE_SIZE, 0);
+	memset(addr, 3, 2 * PAGE_SIZE);
+	memset(target, 4, 2 * PAGE_SIZE);
+
+	char *res = mremap(addr, 2 * PAGE_SIZE, 2 * PAGE_SIZE,
+			   MREMAP_MAYMOVE | MREMAP_FIXED, target);
+	if (res != target) {
+		perror("mremap fixed failed");
+		exit(1);
+	}
+	check_content(target, 2 * PAGE_SIZE, 3);
+
+	munmap(target, 2 * PAGE_SIZE);
+}
+
+void test_fixed_shrink(void)
+{
+	char *addr = map_anonymous(NULL, 2 * PAGE_SIZE, 0);
+	memset(addr, 6, 2 * PAGE_SIZE);
+
+	// A failed move should not unmap the tail of the old mapping.
+	char *invalid_target = (char *)(0xffffffffffffUL & ~(PAGE_SIZE - 1UL));
+	if (mremap(addr, 2 * PAGE_SIZE, PAGE_SIZE,
+		   MREMAP_MAYMOVE | MREMAP_FIXED,
+		   invalid_target) != MAP_FAILED ||
+	    errno != EINVAL) {
+		printf("mremap moves to an invalid range\n");
+		exit(1);
+	}
+	check_content(addr, 2 * PAGE_SIZE, 6);
+
+	char *target = map_anonymous(NULL, PAGE_SIZE, 0);
+	char *res = mremap(addr, 2 * PAGE_SIZE, PAGE_SIZE,
+			   MREMAP_MAYMOVE | MREMAP_FIXED, target);
+	if (res != target) {
+		perror("mremap fixed shrink failed");
+		exit(1);
+	}
+	check_content(target, PAGE_SIZE, 6);
+
+	munmap(target, PAGE_SIZE);
+}
+
+void test_dontunmap(void)
+{
+	char *addr = map_anonymous(NULL, 2 * PAGE_SIZE, 0);
+	memset(addr, 5, 2 * PAGE_SIZE);
+
+	char *res = mremap(addr, 2 * PAGE_SIZE, 2 * PAGE_SIZE,
+			   MREMAP_MAYMOVE | MREMAP_DONTUNMAP, NULL);
+	if (res == MAP_FAILED) {
+		perror("mremap dontunmap failed");
+		exit(1);
+	}
+	check_content(res, 2 * PAGE_SIZE, 5);
+	// The old mapping is kept without the pages.
+	check_content(addr, 2 * PAGE_SIZE, 0);
+
+	munmap(addr, 2 * PAGE_SIZE);
+	munmap(res, 2 * PAGE_SIZE);
+}
+
+void test_move_and_grow_file(void)
+{
+	const char *path = "/tmp/mremap_test_file";
+	int fd = open(path, O_RDWR | O_CREAT | O_TRUNC, 0644);
+	if (fd < 0) {
+		perror("open failed");
+		exit(1);
+	}
+	char buf[PAGE_SIZE];
+	for (int i = 0; i < 4; i++) {
+		memset(buf, 'a' + i, PAGE_SIZE);
+		if (write(fd, buf, PAGE_SIZE) != PAGE_SIZE) {
+			perror("write failed");
+			exit(1);
+		}
+	}
+
+	char *addr = mmap(NULL, 4 * PAGE_SIZE, PROT_READ, MAP_SHARED, fd, 0);
+	if (addr == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+	// Shrink the mapping by unmapping its tail, and keep a page after it
+	// mapped so that it cannot grow in place.
+	munmap(addr + 2 * PAGE_SIZE, 2 * PAGE_SIZE);
+	char *blocker = map_anonymous(addr + 2 * PAGE_SIZE, PAGE_SIZE,
+				      MAP_FIXED);
+
+	// The moved mapping can access the file pages beyond the old size.
+	char *res = mremap(addr, 2 * PAGE_SIZE, 4 * PAGE_SIZE, MREMAP_MAYMOVE);
+	if (res == MAP_FAILED || res == addr) {
+		perror("mremap move and grow failed");
+		exit(1);
+	}
+	for (int i = 0; i < 4; i++) {
+		check_content(res + i * PAGE_SIZE, PAGE_SIZE, 'a' + i);
+	}
+
+	munmap(res, 4 * PAGE_SIZE);
+	munmap(blocker, PAGE_SIZE);
+	close(fd);
+	unlink(path);
+}
+
+int main(void)
+{
+	printf("Test mremap shrink and grow in place\n");
+	test_shrink_and_grow();
+
+	printf("Test mremap with MREMAP_MAYMOVE\n");
+	test_maymove();
+
+	printf("Test mremap with MREMAP_FIXED\n");
+	test_fixed();
+	test_fixed_shrink();
+
+	printf("Test mremap with MREMAP_DONTUNMAP\n");
+	test_dontunmap();
+
+	printf("Test mremap that moves and grows a file mapping\n");
+	test_move_and_grow_file();
+
+	printf("All mremap tests passed\n");
+	return 0;
+}
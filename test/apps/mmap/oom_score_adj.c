@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <unistd.h>
+#include <sys/wait.h>
+
+#define OOM_SCORE_ADJ "/proc/self/oom_score_adj"
+#define OOM_SCORE "/proc/self/oom_score"
+
+static long read_number(const char *path)
+{
+	char buf[32] = { 0 };
+	int fd = CHECK(open(path, O_RDONLY));
+	CHECK(read(fd, buf, sizeof(buf) - 1));
+	CHECK(close(fd));
+	return strtol(buf, NULL, 10);
+}
+
+static int write_string(const char *path, const char *str)
+{
+	int fd = CHECK(open(path, O_WRONLY | O_TRUNC));
+	int ret = write(fd, str, strlen(str));
+	int err = errno;
+	CHECK(close(fd));
+	errno = err;
+	return ret;
+}
+
+FN_TEST(default_value)
+{
+	TEST_RES(read_number(OOM_SCORE_ADJ), _ret == 0);
+	TEST_RES(read_number(OOM_SCORE), _ret > 0 && _ret <= 1333);
+}
+END_TEST()
+
+FN_TEST(write_value)
+{
+	TEST_RES(write_string(OOM_SCORE_ADJ, "500\n"), _ret == 4);
+	TEST_RES(read_number(OOM_SCORE_ADJ), _ret == 500);
+	TEST_RES(read_number(OOM_SCORE), _ret > 900);
+
+	TEST_ERRNO(write_string(OOM_SCORE_ADJ, "1001"), EINVAL);
+	TEST_ERRNO(write_string(OOM_SCORE_ADJ, "abc"), EINVAL);
+	TEST_RES(read_number(OOM_SCORE_ADJ), _ret == 500);
+
+	TEST_RES(write_string(OOM_SCORE_ADJ, "-1000"), _ret == 5);
+	TEST_RES(read_number(OOM_SCORE), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(inherit_value)
+{
+	int status;
+	pid_t pid;
+
+	TEST_RES(write_string(OOM_SCORE_ADJ, "-500"), _ret == 4);
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0)
+		exit(read_number(OOM_SCORE_ADJ) == -500 ? 0 : 1);
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_RES(write_string(OOM_SCORE_ADJ, "0"), _ret == 1);
+}
+END_TEST()
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/sysinfo.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 16384
+#define NR_DIRTY_PAGES 64
+#define CHUNK_SIZE (4UL << 20)
+#define MAX_WAITS 3000
+
+static void fail(const char *msg)
+{
+	perror(msg);
+	exit(1);
+}
+
+// Fills the page with a pattern that depends on its index and `seed`.
+static void fill_page(char *page, size_t idx, char seed)
+{
+	memset(page, seed + (char)idx, PAGE_SIZE);
+	memcpy(page, &idx, sizeof(idx));
+}
+
+static int check_page(const char *page, size_t idx, char seed)
+{
+	static char expected[PAGE_SIZE];
+
+	fill_page(expected, idx, seed);
+	return memcmp(page, expected, PAGE_SIZE) == 0;
+}
+
+// Returns the number of pages of the file that are in the page cache.
+static size_t count_cached_pages(char *addr)
+{
+	static unsigned char vec[NR_PAGES];
+
+	if (mincore(addr, NR_PAGES * PAGE_SIZE, vec) < 0)
+		fail("mincore failed");
+
+	size_t count = 0;
+	for (size_t i = 0; i < NR_PAGES; i++)
+		count += vec[i] & 1;
+	return count;
+}
+
+// Allocates anonymous memory until at least half of the cached pages are
+// reclaimed.
+//
+// The allocation pauses when the available memory is low, so that the
+// reclaimer can catch up instead of invoking the OOM killer.
+static void apply_memory_pressure(char *addr, size_t nr_cached)
+{
+	size_t nr_waits = 0;
+	struct sysinfo info;
+
+	while (count_cached_pages(addr) > nr_cached / 2) {
+		if (sysinfo(&info) < 0)
+			fail("sysinfo failed");
+		if (info.freeram * info.mem_unit <
+		    info.totalram * info.mem_unit / 48) {
+			if (++nr_waits > MAX_WAITS) {
+				printf("the page cache is not reclaimed\n");
+				exit(1);
+			}
+			usleep(10000);
+			continue;
+		}
+
+		char *chunk = mmap(NULL, CHUNK_SIZE, PROT_READ | PROT_WRITE,
+				   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+		if (chunk == MAP_FAILED)
+			fail("mmap anonymous memory failed");
+		memset(chunk, 1, CHUNK_SIZE);
+	}
+}
+
+int main(int argc, char *argv[])
+{
+	const char *dir = argc > 1 ? argv[1] : "/ext2";
+	static char page[PAGE_SIZE];
+	char path[256];
+	size_t nr_cached;
+	int fd, status;
+	pid_t pid;
+
+	snprintf(path, sizeof(path), "%s/test_page_cache_reclaim", dir);
+	fd = open(path, O_CREAT | O_TRUNC | O_RDWR, 0600);
+	if (fd < 0)
+		fail("open failed");
+
+	for (size_t i = 0; i < NR_PAGES; i++) {
+		fill_page(page, i, 'a');
+		if (write(fd, page, PAGE_SIZE) != PAGE_SIZE)
+			fail("write failed");
+	}
+	if (fsync(fd) < 0)
+		fail("fsync failed");
+
+	// The dirty pages must be written back before they are reclaimed.
+	for (size_t i = 0; i < NR_DIRTY_PAGES; i++) {
+		fill_page(page, i, 'A');
+		if (pwrite(fd, page, PAGE_SIZE, i * PAGE_SIZE) != PAGE_SIZE)
+			fail("pwrite failed");
+	}
+
+	char *addr = mmap(NULL, NR_PAGES * PAGE_SIZE, PROT_READ, MAP_SHARED,
+			  fd, 0);
+	if (addr == MAP_FAILED)
+		fail("mmap file failed");
+	nr_cached = count_cached_pages(addr);
+	if (nr_cached == 0) {
+		printf("the written pages are not cached\n");
+		exit(1);
+	}
+
+	// The anonymous memory is freed when the child exits.
+	pid = fork();
+	if (pid < 0)
+		fail("fork failed");
+	if (pid == 0) {
+		apply_memory_pressure(addr, nr_cached);
+		_exit(0);
+	}
+	if (waitpid(pid, &status, 0) != pid)
+		fail("waitpid failed");
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+		printf("the memory pressure is not relieved by reclaiming\n");
+		exit(1);
+	}
+
+	for (size_t i = 0; i < NR_PAGES; i++) {
+		char seed = i < NR_DIRTY_PAGES ? 'A' : 'a';
+
+		if (pread(fd, page, PAGE_SIZE, i * PAGE_SIZE) != PAGE_SIZE)
+			fail("pread failed");
+		if (!check_page(page, i, seed) ||
+		    !check_page(addr + i * PAGE_SIZE, i, seed)) {
+			printf("page %zu is corrupted after reclaiming\n", i);
+			exit(1);
+		}
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+	close(fd);
+	if (unlink(path) < 0)
+		fail("unlink failed");
+
+	printf("page cache reclaim test passed\n");
+	return 0;
+}
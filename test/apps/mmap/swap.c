@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/swap.h>
+#include <sys/sysinfo.h>
+#include <unistd.h>
+
+#define PAGE_SIZE 4096
+#define SWAP_PAGES 64
+
+static void fail(const char *msg)
+{
+	perror(msg);
+	exit(1);
+}
+
+// Creates a swap file in the format of `mkswap` if `with_header` is set.
+static void create_swap_file(const char *path, int with_header)
+{
+	static char page[PAGE_SIZE];
+
+	int fd = open(path, O_CREAT | O_TRUNC | O_WRONLY, 0600);
+	if (fd < 0)
+		fail("open swap file failed");
+
+	memset(page, 0, PAGE_SIZE);
+	for (int i = 0; i < SWAP_PAGES; i++) {
+		if (write(fd, page, PAGE_SIZE) != PAGE_SIZE)
+			fail("write swap file failed");
+	}
+
+	if (with_header) {
+		// The version, the last page, and the number of bad pages.
+		uint32_t info[3] = { 1, SWAP_PAGES - 1, 0 };
+		memcpy(page + 1024, info, sizeof(info));
+		memcpy(page + PAGE_SIZE - 10, "SWAPSPACE2", 10);
+		if (pwrite(fd, page, PAGE_SIZE, 0) != PAGE_SIZE)
+			fail("write swap header failed");
+	}
+
+	if (fsync(fd) < 0)
+		fail("fsync swap file failed");
+	close(fd);
+}
+
+static unsigned long total_swap(void)
+{
+	struct sysinfo info;
+	if (sysinfo(&info) < 0)
+		fail("sysinfo failed");
+	return info.totalswap;
+}
+
+static unsigned long free_swap(void)
+{
+	struct sysinfo info;
+	if (sysinfo(&info) < 0)
+		fail("sysinfo failed");
+	return info.freeswap;
+}
+
+// Swaps out anonymous pages with `MADV_PAGEOUT` and checks that their
+// content is kept after they are swapped in again.
+static void test_pageout(void)
+{
+	const size_t nr_pages = 8;
+	const size_t len = nr_pages * PAGE_SIZE;
+
+	char *addr = mmap(NULL, len, PROT_READ | PROT_WRITE,
+			  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED)
+		fail("mmap failed");
+	for (size_t i = 0; i < nr_pages; i++)
+		memset(addr + i * PAGE_SIZE, 'a' + i, PAGE_SIZE);
+
+	unsigned long free_before = free_swap();
+	if (madvise(addr, len, MADV_PAGEOUT) < 0)
+		fail("madvise(MADV_PAGEOUT) failed");
+	if (free_swap() >= free_before) {
+		printf("no pages are swapped out by MADV_PAGEOUT\n");
+		exit(1);
+	}
+
+	for (size_t i = 0; i < nr_pages; i++) {
+		for (size_t j = 0; j < PAGE_SIZE; j++) {
+			if (addr[i * PAGE_SIZE + j] != (char)('a' + i)) {
+				printf("page %zu is corrupted after swap-in\n",
+				       i);
+				exit(1);
+			}
+		}
+	}
+
+	if (madvise(addr, len, MADV_COLD) < 0)
+		fail("madvise(MADV_COLD) failed");
+
+	munmap(addr, len);
+}
+
+int main(int argc, char *argv[])
+{
+	const char *dir = argc > 1 ? argv[1] : "/ext2";
+	char swap_path[256], bad_path[256];
+	snprintf(swap_path, sizeof(swap_path), "%s/test_swapfile", dir);
+	snprintf(bad_path, sizeof(bad_path), "%s/test_bad_swapfile", dir);
+
+	create_swap_file(swap_path, 1);
+	create_swap_file(bad_path, 0);
+
+	if (swapon(bad_path, 0) == 0 || errno != EINVAL)
+		fail("swapon without the swap header should fail");
+
+	if (swapon(swap_path, SWAP_FLAG_PREFER | 5) < 0)
+		fail("swapon failed");
+	if (total_swap() == 0) {
+		printf("the swap space is not reported\n");
+		exit(1);
+	}
+	if (swapon(swap_path, 0) == 0 || errno != EBUSY)
+		fail("enabling the swap file twice should fail");
+
+	test_pageout();
+
+	if (swapoff(swap_path) < 0)
+		fail("swapoff failed");
+	if (total_swap() != 0) {
+		printf("the swap space is still reported\n");
+		exit(1);
+	}
+	if (swapoff(swap_path) == 0 || errno != EINVAL)
+		fail("disabling the swap file twice should fail");
+
+	unlink(swap_path);
+	unlink(bad_path);
+
+	printf("swap test passed\n");
+	return 0;
+}
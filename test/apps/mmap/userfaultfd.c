@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <linux/userfaultfd.h>
+#include <poll.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/ioctl.h>
+#include <sys/mman.h>
+#include <sys/syscall.h>
+
+#define PAGE_SIZE 4096
+#define NR_PAGES 4
+
+static int open_userfaultfd(void)
+{
+	int uffd = syscall(SYS_userfaultfd, O_CLOEXEC | O_NONBLOCK);
+	if (uffd < 0) {
+		perror("userfaultfd failed");
+		exit(1);
+	}
+
+	struct uffdio_api api = { .api = UFFD_API, .features = 0 };
+	if (ioctl(uffd, UFFDIO_API, &api) < 0) {
+		perror("UFFDIO_API failed");
+		exit(1);
+	}
+	if (!(api.ioctls & (1ULL << _UFFDIO_REGISTER))) {
+		printf("UFFDIO_API does not report UFFDIO_REGISTER\n");
+		exit(1);
+	}
+	return uffd;
+}
+
+static char *map_and_register(int uffd)
+{
+	char *addr = mmap(NULL, NR_PAGES * PAGE_SIZE, PROT_READ | PROT_WRITE,
+			  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	if (addr == MAP_FAILED) {
+		perror("mmap failed");
+		exit(1);
+	}
+
+	struct uffdio_register reg = {
+		.range = { .start = (unsigned long)addr,
+			   .len = NR_PAGES * PAGE_SIZE },
+		.mode = UFFDIO_REGISTER_MODE_MISSING,
+	};
+	if (ioctl(uffd, UFFDIO_REGISTER, &reg) < 0) {
+		perror("UFFDIO_REGISTER failed");
+		exit(1);
+	}
+	if (!(reg.ioctls & (1ULL << _UFFDIO_COPY))) {
+		printf("UFFDIO_REGISTER does not report UFFDIO_COPY\n");
+		exit(1);
+	}
+	return addr;
+}
+
+// Waits for the fault message, and checks that it is on the expected page.
+static void read_fault(int uffd, char *page, int is_write)
+{
+	struct pollfd pfd = { .fd = uffd, .events = POLLIN };
+	if (poll(&pfd, 1, -1) != 1 || !(pfd.revents & POLLIN)) {
+		printf("poll does not report the fault\n");
+		exit(1);
+	}
+
+	struct uffd_msg msg;
+	if (read(uffd, &msg, sizeof(msg)) != sizeof(msg)) {
+		perror("read failed");
+		exit(1);
+	}
+	if (msg.event != UFFD_EVENT_PAGEFAULT ||
+	    msg.arg.pagefault.address != (unsigned long)page ||
+	    !!(msg.arg.pagefault.flags & UFFD_PAGEFAULT_FLAG_WRITE) !=
+		    is_write) {
+		printf("the fault message is wrong\n");
+		exit(1);
+	}
+}
+
+static void *read_page(void *arg)
+{
+	return (void *)(long)*(volatile char *)arg;
+}
+
+static void *write_page(void *arg)
+{
+	*(volatile char *)arg = 'b';
+	return NULL;
+}
+
+void test_copy(void)
+{
+	int uffd = open_userfaultfd();
+	char *addr = map_and_register(uffd);
+	char *page = addr + PAGE_SIZE;
+
+	pthread_t thread;
+	pthread_create(&thread, NULL, read_page, page + 1);
+	read_fault(uffd, page, 0);
+
+	char *src = aligned_alloc(PAGE_SIZE, PAGE_SIZE);
+	memset(src, 'a', PAGE_SIZE);
+	struct uffdio_copy copy = {
+		.dst = (unsigned long)page,
+		.src = (unsigned long)src,
+		.len = PAGE_SIZE,
+		.mode = 0,
+	};
+	if (ioctl(uffd, UFFDIO_COPY, &copy) < 0 || copy.copy != PAGE_SIZE) {
+		perror("UFFDIO_COPY failed");
+		exit(1);
+	}
+
+	void *value;
+	pthread_join(thread, &value);
+	if ((long)value != 'a' || page[PAGE_SIZE - 1] != 'a') {
+		printf("the faulting thread reads a wrong value\n");
+		exit(1);
+	}
+
+	// The page is present, so it cannot be filled again.
+	if (ioctl(uffd, UFFDIO_COPY, &copy) != -1 || errno != EEXIST ||
+	    copy.copy != -EEXIST) {
+		printf("UFFDIO_COPY overwrites a present page\n");
+		exit(1);
+	}
+
+	free(src);
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+	close(uffd);
+}
+
+void test_zeropage(void)
+{
+	int uffd = open_userfaultfd();
+	char *addr = map_and_register(uffd);
+
+	pthread_t thread;
+	pthread_create(&thread, NULL, write_page, addr);
+	read_fault(uffd, addr, 1);
+
+	struct uffdio_zeropage zeropage = {
+		.range = { .start = (unsigned long)addr, .len = PAGE_SIZE },
+		.mode = 0,
+	};
+	if (ioctl(uffd, UFFDIO_ZEROPAGE, &zeropage) < 0 ||
+	    zeropage.zeropage != PAGE_SIZE) {
+		perror("UFFDIO_ZEROPAGE failed");
+		exit(1);
+	}
+
+	pthread_join(thread, NULL);
+	if (addr[0] != 'b' || addr[1] != 0) {
+		printf("the zero page is wrong\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+	close(uffd);
+}
+
+void test_unregister(void)
+{
+	int uffd = open_userfaultfd();
+	char *addr = map_and_register(uffd);
+
+	struct uffdio_range range = { .start = (unsigned long)addr,
+				      .len = NR_PAGES * PAGE_SIZE };
+	if (ioctl(uffd, UFFDIO_UNREGISTER, &range) < 0) {
+		perror("UFFDIO_UNREGISTER failed");
+		exit(1);
+	}
+	// The fault is handled by the kernel after unregistering.
+	addr[0] = 'c';
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+	close(uffd);
+}
+
+void test_invalid(void)
+{
+	if (syscall(SYS_userfaultfd, 0x100) != -1 || errno != EINVAL) {
+		printf("userfaultfd accepts unknown flags\n");
+		exit(1);
+	}
+
+	int uffd = syscall(SYS_userfaultfd, O_CLOEXEC | O_NONBLOCK);
+	struct uffdio_register reg = { .mode = UFFDIO_REGISTER_MODE_MISSING };
+	if (ioctl(uffd, UFFDIO_REGISTER, &reg) != -1 || errno != EINVAL) {
+		printf("UFFDIO_REGISTER succeeds before UFFDIO_API\n");
+		exit(1);
+	}
+	struct uffdio_api api = { .api = 0 };
+	if (ioctl(uffd, UFFDIO_API, &api) != -1 || errno != EINVAL) {
+		printf("UFFDIO_API accepts a wrong API version\n");
+		exit(1);
+	}
+	close(uffd);
+
+	uffd = open_userfaultfd();
+	char *addr = map_and_register(uffd);
+	reg.range.start = (unsigned long)addr + 1;
+	reg.range.len = PAGE_SIZE;
+	if (ioctl(uffd, UFFDIO_REGISTER, &reg) != -1 || errno != EINVAL) {
+		printf("UFFDIO_REGISTER accepts an unaligned range\n");
+		exit(1);
+	}
+
+	struct uffd_msg msg;
+	if (read(uffd, &msg, sizeof(msg)) != -1 || errno != EAGAIN) {
+		printf("read does not fail without faults\n");
+		exit(1);
+	}
+
+	munmap(addr, NR_PAGES * PAGE_SIZE);
+	close(uffd);
+}
+
+int main(void)
+{
+	printf("Test UFFDIO_COPY\n");
+	test_copy();
+
+	printf("Test UFFDIO_ZEROPAGE\n");
+	test_zeropage();
+
+	printf("Test UFFDIO_UNREGISTER\n");
+	test_unregister();
+
+	printf("Test invalid arguments\n");
+	test_invalid();
+
+	printf("All userfaultfd tests passed\n");
+	return 0;
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <linux/sched.h>
+#include <poll.h>
+#include <signal.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#ifndef CLONE_PIDFD
+#define CLONE_PIDFD 0x00001000
+#endif
+
+#ifndef P_PIDFD
+#define P_PIDFD 3
+#endif
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+static int sys_pidfd_open(pid_t pid, unsigned int flags)
+{
+	return syscall(SYS_pidfd_open, pid, flags);
+}
+
+static int sys_pidfd_send_signal(int pidfd, int sig, siginfo_t *info,
+				 unsigned int flags)
+{
+	return syscall(SYS_pidfd_send_signal, pidfd, sig, info, flags);
+}
+
+static int sys_pidfd_getfd(int pidfd, int target_fd, unsigned int flags)
+{
+	return syscall(SYS_pidfd_getfd, pidfd, target_fd, flags);
+}
+
+static int poll_pidfd(int pidfd, int timeout)
+{
+	struct pollfd pfd = { .fd = pidfd, .events = POLLIN };
+
+	CHECK(poll(&pfd, 1, timeout) >= 0);
+	return pfd.revents;
+}
+
+static pid_t fork_paused_child(void)
+{
+	pid_t pid = fork();
+	CHECK(pid >= 0);
+
+	if (pid == 0) {
+		for (;;)
+			pause();
+	}
+	return pid;
+}
+
+static void test_open(void)
+{
+	int pidfd;
+
+	errno = 0;
+	CHECK(sys_pidfd_open(0, 0) < 0 && errno == EINVAL);
+	errno = 0;
+	CHECK(sys_pidfd_open(getpid(), 1) < 0 && errno == EINVAL);
+
+	pidfd = sys_pidfd_open(getpid(), 0);
+	CHECK(pidfd >= 0);
+	CHECK(fcntl(pidfd, F_GETFD) == FD_CLOEXEC);
+	CHECK(poll_pidfd(pidfd, 0) == 0);
+
+	errno = 0;
+	CHECK(read(pidfd, &pidfd, sizeof(pidfd)) < 0 && errno == EINVAL);
+	CHECK(close(pidfd) == 0);
+
+	printf("%s passed\n", __func__);
+}
+
+static void test_send_signal_and_poll(void)
+{
+	pid_t pid = fork_paused_child();
+	siginfo_t info;
+	int pidfd, status;
+
+	pidfd = sys_pidfd_open(pid, 0);
+	CHECK(pidfd >= 0);
+
+	// Signal 0 only checks that the process exists.
+	CHECK(sys_pidfd_send_signal(pidfd, 0, NULL, 0) == 0);
+	errno = 0;
+	CHECK(sys_pidfd_send_signal(pidfd, SIGKILL, NULL, 0x100) < 0 &&
+	      errno == EINVAL);
+
+	// Impersonating the kernel is not allowed.
+	memset(&info, 0, sizeof(info));
+	info.si_signo = SIGKILL;
+	info.si_code = SI_USER;
+	errno = 0;
+	CHECK(sys_pidfd_send_signal(pidfd, SIGKILL, &info, 0) < 0 &&
+	      errno == EPERM);
+
+	CHECK(sys_pidfd_send_signal(pidfd, SIGKILL, NULL, 0) == 0);
+
+	// The pidfd becomes readable once the process exits.
+	CHECK(poll_pidfd(pidfd, -1) & POLLIN);
+
+	CHECK(waitpid(pid, &status, 0) == pid);
+	CHECK(WIFSIGNALED(status) && WTERMSIG(status) == SIGKILL);
+
+	// The process has been reaped.
+	CHECK(poll_pidfd(pidfd, 0) & POLLHUP);
+	errno = 0;
+	CHECK(sys_pidfd_send_signal(pidfd, 0, NULL, 0) < 0 && errno == ESRCH);
+	CHECK(close(pidfd) == 0);
+
+	printf("%s passed\n", __func__);
+}
+
+static void test_getfd(void)
+{
+	int fds[2], pidfd, fd;
+	char buf[4] = { 0 };
+
+	CHECK(pipe(fds) == 0);
+
+	pidfd = sys_pidfd_open(getpid(), 0);
+	CHECK(pidfd >= 0);
+
+	fd = sys_pidfd_getfd(pidfd, fds[1], 0);
+	CHECK(fd >= 0);
+	CHECK(fcntl(fd, F_GETFD) == FD_CLOEXEC);
+	CHECK(write(fd, "abc", 3) == 3);
+	CHECK(read(fds[0], buf, sizeof(buf)) == 3);
+	CHECK(strcmp(buf, "abc") == 0);
+
+	errno = 0;
+	CHECK(sys_pidfd_getfd(pidfd, 1000, 0) < 0 && errno == EBADF);
+	errno = 0;
+	CHECK(sys_pidfd_getfd(fds[0], fds[1], 0) < 0 && errno == EBADF);
+
+	CHECK(close(fd) == 0);
+	CHECK(close(pidfd) == 0);
+	CHECK(close(fds[0]) == 0);
+	CHECK(close(fds[1]) == 0);
+
+	printf("%s passed\n", __func__);
+}
+
+static void test_clone_pidfd_and_waitid(void)
+{
+	int pidfd = -1;
+	struct clone_args args = {
+		.flags = CLONE_PIDFD,
+		.pidfd = (uint64_t)(uintptr_t)&pidfd,
+		.exit_signal = SIGCHLD,
+	};
+	siginfo_t info;
+	pid_t pid;
+
+	pid = syscall(SYS_clone3, &args, sizeof(args));
+	CHECK(pid >= 0);
+	if (pid == 0)
+		_exit(42);
+
+	CHECK(pidfd >= 0);
+	CHECK(fcntl(pidfd, F_GETFD) == FD_CLOEXEC);
+
+	memset(&info, 0, sizeof(info));
+	CHECK(waitid(P_PIDFD, pidfd, &info, WEXITED) == 0);
+
+	// The PID may be reused after the process is reaped.
+	errno = 0;
+	CHECK(waitid(P_PIDFD, pidfd, &info, WEXITED) < 0 && errno == ECHILD);
+	CHECK(close(pidfd) == 0);
+
+	printf("%s passed\n", __func__);
+}
+
+int main(void)
+{
+	test_open();
+	test_send_signal_and_poll();
+	test_getfd();
+	test_clone_pidfd_and_waitid();
+
+	printf("All tests passed\n");
+	return 0;
+}
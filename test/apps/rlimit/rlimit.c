@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include "../network/test.h"
+
+#include <fcntl.h>
+#include <signal.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
+#include <sys/wait.h>
+
+#define PAGE_SIZE 4096
+#define FILE_PATH "/tmp/rlimit_test_file"
+
+static struct rlimit saved_rlimit;
+
+static int set_soft_limit(int resource, rlim_t value)
+{
+	struct rlimit rlimit;
+
+	if (getrlimit(resource, &saved_rlimit) < 0)
+		return -1;
+
+	rlimit.rlim_cur = value;
+	rlimit.rlim_max = saved_rlimit.rlim_max;
+	return setrlimit(resource, &rlimit);
+}
+
+static int restore_limit(int resource)
+{
+	return setrlimit(resource, &saved_rlimit);
+}
+
+FN_TEST(invalid_limit)
+{
+	struct rlimit rlimit;
+
+	TEST_SUCC(getrlimit(RLIMIT_NOFILE, &rlimit));
+
+	rlimit.rlim_cur = rlimit.rlim_max + 1;
+	TEST_ERRNO(setrlimit(RLIMIT_NOFILE, &rlimit), EINVAL);
+
+	TEST_ERRNO(prlimit(0x7fffffff, RLIMIT_NOFILE, NULL, &rlimit), ESRCH);
+}
+END_TEST()
+
+FN_TEST(nofile)
+{
+	int fds[8];
+	int i, nr_fds;
+
+	TEST_SUCC(set_soft_limit(RLIMIT_NOFILE, 8));
+
+	for (nr_fds = 0; nr_fds < 8; nr_fds++) {
+		fds[nr_fds] = open("/dev/null", O_RDONLY);
+		if (fds[nr_fds] < 0)
+			break;
+	}
+	TEST_ERRNO(open("/dev/null", O_RDONLY), EMFILE);
+	TEST_ERRNO(dup(0), EMFILE);
+	TEST_ERRNO(fcntl(0, F_DUPFD, 8), EINVAL);
+	TEST_ERRNO(dup2(0, 8), EBADF);
+
+	for (i = 0; i < nr_fds; i++)
+		TEST_SUCC(close(fds[i]));
+
+	TEST_SUCC(restore_limit(RLIMIT_NOFILE));
+}
+END_TEST()
+
+static volatile int nr_sigxfsz;
+
+static void handle_sigxfsz(int signum)
+{
+	(void)signum;
+	nr_sigxfsz++;
+}
+
+FN_SETUP(sigxfsz)
+{
+	CHECK_WITH(signal(SIGXFSZ, handle_sigxfsz) == SIG_ERR ? -1 : 0,
+		   _ret == 0);
+}
+END_SETUP()
+
+FN_TEST(fsize)
+{
+	static char buf[2 * PAGE_SIZE];
+	int fd;
+
+	fd = TEST_SUCC(open(FILE_PATH, O_CREAT | O_TRUNC | O_RDWR, 0600));
+	TEST_SUCC(set_soft_limit(RLIMIT_FSIZE, PAGE_SIZE));
+
+	// The write is truncated to the limit.
+	TEST_RES(write(fd, buf, sizeof(buf)), _ret == PAGE_SIZE);
+	TEST_RES(nr_sigxfsz, _ret == 0);
+
+	TEST_ERRNO(write(fd, buf, sizeof(buf)), EFBIG);
+	TEST_RES(nr_sigxfsz, _ret == 1);
+
+	TEST_ERRNO(ftruncate(fd, sizeof(buf)), EFBIG);
+	TEST_RES(nr_sigxfsz, _ret == 2);
+	TEST_SUCC(ftruncate(fd, PAGE_SIZE / 2));
+
+	TEST_SUCC(restore_limit(RLIMIT_FSIZE));
+	TEST_RES(write(fd, buf, sizeof(buf)), _ret == sizeof(buf));
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(FILE_PATH));
+}
+END_TEST()
+
+FN_TEST(address_space)
+{
+	void *addr;
+
+	TEST_SUCC(set_soft_limit(RLIMIT_AS, 256UL << 20));
+
+	TEST((long)mmap(NULL, 512UL << 20, PROT_READ | PROT_WRITE,
+		  MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+	     ENOMEM, (void *)_ret == MAP_FAILED);
+
+	addr = (void *)TEST_RES((long)mmap(NULL, PAGE_SIZE,
+					   PROT_READ | PROT_WRITE,
+					   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				_ret != (long)MAP_FAILED);
+	TEST((long)mremap(addr, PAGE_SIZE, 512UL << 20, MREMAP_MAYMOVE), ENOMEM,
+	     (void *)_ret == MAP_FAILED);
+	TEST_SUCC(munmap(addr, PAGE_SIZE));
+
+	// Overwriting existing mappings does not grow the address space.
+	addr = (void *)TEST_RES((long)mmap(NULL, 192UL << 20,
+					   PROT_READ | PROT_WRITE,
+					   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				_ret != (long)MAP_FAILED);
+	TEST_RES((long)mmap(addr, 192UL << 20, PROT_READ,
+			    MAP_PRIVATE | MAP_ANONYMOUS | MAP_FIXED, -1, 0),
+		 (void *)_ret == addr);
+	TEST_SUCC(munmap(addr, 192UL << 20));
+
+	TEST_SUCC(restore_limit(RLIMIT_AS));
+}
+END_TEST()
+
+FN_TEST(data)
+{
+	TEST_SUCC(set_soft_limit(RLIMIT_DATA, 1UL << 20));
+	TEST((long)sbrk(16UL << 20), ENOMEM, (void *)_ret == (void *)-1);
+	TEST_SUCC(restore_limit(RLIMIT_DATA));
+}
+END_TEST()
+
+static void handle_sigxcpu(int signum)
+{
+	(void)signum;
+	_exit(0);
+}
+
+FN_TEST(cpu)
+{
+	struct rlimit rlimit = { .rlim_cur = 1, .rlim_max = 2 };
+	int status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		signal(SIGXCPU, handle_sigxcpu);
+		if (setrlimit(RLIMIT_CPU, &rlimit) < 0)
+			_exit(1);
+		for (;;)
+			;
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(prlimit_permission)
+{
+	struct rlimit rlimit;
+	int status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// Without `CAP_SYS_RESOURCE`, only the processes with the same
+		// user and group IDs can be accessed.
+		if (setresgid(1000, 1000, 1000) < 0 ||
+		    setresuid(1000, 1000, 1000) < 0)
+			_exit(1);
+		if (prlimit(0, RLIMIT_NOFILE, NULL, &rlimit) < 0)
+			_exit(1);
+		if (prlimit(getppid(), RLIMIT_NOFILE, NULL, &rlimit) == 0 ||
+		    errno != EPERM)
+			_exit(1);
+		_exit(0);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
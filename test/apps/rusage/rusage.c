@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
+#include <sys/times.h>
+#include <sys/wait.h>
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+#define NR_PAGES 256
+
+static void touch_pages(void)
+{
+	long page_size = sysconf(_SC_PAGESIZE);
+	char *buf = mmap(NULL, NR_PAGES * page_size, PROT_READ | PROT_WRITE,
+			 MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+	CHECK(buf != MAP_FAILED);
+
+	for (int i = 0; i < NR_PAGES; i++)
+		buf[i * page_size] = 1;
+
+	CHECK(munmap(buf, NR_PAGES * page_size) == 0);
+}
+
+static void spin_for(long msecs)
+{
+	struct timespec start, now;
+	CHECK(clock_gettime(CLOCK_PROCESS_CPUTIME_ID, &start) == 0);
+	do {
+		CHECK(clock_gettime(CLOCK_PROCESS_CPUTIME_ID, &now) == 0);
+	} while ((now.tv_sec - start.tv_sec) * 1000 +
+			 (now.tv_nsec - start.tv_nsec) / 1000000 <
+		 msecs);
+}
+
+static void test_self(void)
+{
+	struct rusage before, after;
+
+	CHECK(getrusage(RUSAGE_SELF, &before) == 0);
+	touch_pages();
+	usleep(1000);
+	usleep(1000);
+	CHECK(getrusage(RUSAGE_SELF, &after) == 0);
+
+	CHECK(after.ru_minflt >= before.ru_minflt + NR_PAGES);
+	CHECK(after.ru_nvcsw >= before.ru_nvcsw + 2);
+	CHECK(after.ru_maxrss >= NR_PAGES * sysconf(_SC_PAGESIZE) / 1024);
+
+	CHECK(getrusage(RUSAGE_THREAD, &after) == 0);
+	CHECK(after.ru_minflt >= NR_PAGES);
+	CHECK(after.ru_nvcsw >= 2);
+
+	CHECK(getrusage(42, &after) == -1 && errno == EINVAL);
+}
+
+static void test_children(void)
+{
+	struct rusage children, child_usage;
+	struct tms tms;
+	int status;
+	pid_t pid;
+
+	CHECK(getrusage(RUSAGE_CHILDREN, &children) == 0);
+	CHECK(children.ru_minflt == 0);
+	CHECK(children.ru_utime.tv_sec == 0 && children.ru_utime.tv_usec == 0);
+
+	pid = fork();
+	CHECK(pid >= 0);
+	if (pid == 0) {
+		touch_pages();
+		spin_for(100);
+		_exit(0);
+	}
+
+	CHECK(wait4(pid, &status, 0, &child_usage) == pid);
+	CHECK(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	CHECK(child_usage.ru_minflt >= NR_PAGES);
+	CHECK(child_usage.ru_utime.tv_sec > 0 ||
+	      child_usage.ru_utime.tv_usec > 0);
+
+	CHECK(getrusage(RUSAGE_CHILDREN, &children) == 0);
+	CHECK(children.ru_minflt == child_usage.ru_minflt);
+	CHECK(children.ru_utime.tv_sec == child_usage.ru_utime.tv_sec &&
+	      children.ru_utime.tv_usec == child_usage.ru_utime.tv_usec);
+	CHECK(children.ru_maxrss >= NR_PAGES * sysconf(_SC_PAGESIZE) / 1024);
+
+	// `RUSAGE_BOTH` is only used inside the kernel.
+	CHECK(getrusage(-2, &children) == -1 && errno == EINVAL);
+
+	CHECK(times(&tms) != (clock_t)-1);
+	CHECK(tms.tms_cutime + tms.tms_cstime > 0);
+}
+
+static void test_procfs(void)
+{
+	char buf[4096];
+	unsigned long minflt, utime;
+	FILE *file;
+	size_t len;
+
+	file = fopen("/proc/self/status", "r");
+	CHECK(file != NULL);
+	len = fread(buf, 1, sizeof(buf) - 1, file);
+	buf[len] = '\0';
+	CHECK(fclose(file) == 0);
+	CHECK(strstr(buf, "VmHWM:") != NULL);
+	CHECK(strstr(buf, "voluntary_ctxt_switches:") != NULL);
+	CHECK(strstr(buf, "nonvoluntary_ctxt_switches:") != NULL);
+
+	// The process name does not contain spaces or parentheses.
+	file = fopen("/proc/self/stat", "r");
+	CHECK(file != NULL);
+	CHECK(fscanf(file,
+		     "%*d %*s %*c %*d %*d %*d %*d %*d %*u %lu %*u %*u %*u %lu",
+		     &minflt, &utime) == 2);
+	CHECK(fclose(file) == 0);
+	CHECK(minflt >= NR_PAGES);
+	(void)utime;
+}
+
+int main(void)
+{
+	test_self();
+	test_children();
+	test_procfs();
+
+	printf("All tests passed.\n");
+	return 0;
+}
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <sched.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/resource.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <linux/capability.h>
+
+// The `struct sched_attr` is not exported by the glibc headers.
+struct sched_attr {
+	uint32_t size;
+	uint32_t sched_policy;
+	uint64_t sched_flags;
+	int32_t sched_nice;
+	uint32_t sched_priority;
+	uint64_t sched_runtime;
+	uint64_t sched_deadline;
+	uint64_t sched_period;
+	uint32_t sched_util_min;
+	uint32_t sched_util_max;
+};
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+static void set_policy(int policy, int priority)
+{
+	struct sched_param param = { .sched_priority = priority };
+	CHECK(sched_setscheduler(0, policy, &param) == 0);
+	CHECK(sched_getscheduler(0) == policy);
+	CHECK(sched_getparam(0, &param) == 0);
+	CHECK(param.sched_priority == priority);
+}
+
+void test_priority_range(void)
+{
+	CHECK(sched_get_priority_min(SCHED_FIFO) == 1);
+	CHECK(sched_get_priority_max(SCHED_FIFO) == 99);
+	CHECK(sched_get_priority_min(SCHED_RR) == 1);
+	CHECK(sched_get_priority_max(SCHED_RR) == 99);
+	CHECK(sched_get_priority_min(SCHED_OTHER) == 0);
+	CHECK(sched_get_priority_max(SCHED_IDLE) == 0);
+	CHECK(sched_get_priority_max(42) == -1 && errno == EINVAL);
+}
+
+void test_set_policy(void)
+{
+	CHECK(sched_getscheduler(0) == SCHED_OTHER);
+
+	set_policy(SCHED_FIFO, 10);
+	struct timespec interval;
+	CHECK(sched_rr_get_interval(0, &interval) == 0);
+	CHECK(interval.tv_sec == 0 && interval.tv_nsec == 0);
+
+	set_policy(SCHED_RR, 20);
+	CHECK(sched_rr_get_interval(0, &interval) == 0);
+	CHECK(interval.tv_sec != 0 || interval.tv_nsec != 0);
+
+	struct sched_param param = { .sched_priority = 30 };
+	CHECK(sched_setparam(0, &param) == 0);
+	CHECK(sched_getscheduler(0) == SCHED_RR);
+	CHECK(sched_getparam(0, &param) == 0 && param.sched_priority == 30);
+
+	set_policy(SCHED_BATCH, 0);
+	set_policy(SCHED_IDLE, 0);
+	set_policy(SCHED_OTHER, 0);
+
+	// The policy is inherited by the child.
+	set_policy(SCHED_FIFO, 10);
+	pid_t pid = fork();
+	if (pid == 0) {
+		exit(sched_getscheduler(0) == SCHED_FIFO ? 0 : 1);
+	}
+	int status;
+	CHECK(waitpid(pid, &status, 0) == pid);
+	CHECK(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+	set_policy(SCHED_OTHER, 0);
+}
+
+void test_invalid(void)
+{
+	struct sched_param param = { .sched_priority = 0 };
+	CHECK(sched_setscheduler(0, SCHED_FIFO, &param) == -1 &&
+	      errno == EINVAL);
+	param.sched_priority = 100;
+	CHECK(sched_setscheduler(0, SCHED_RR, &param) == -1 &&
+	      errno == EINVAL);
+	param.sched_priority = 1;
+	CHECK(sched_setscheduler(0, SCHED_OTHER, &param) == -1 &&
+	      errno == EINVAL);
+	CHECK(sched_setscheduler(0, 42, &param) == -1 && errno == EINVAL);
+	CHECK(sched_setscheduler(-1, SCHED_OTHER, &param) == -1 &&
+	      errno == EINVAL);
+	CHECK(sched_getscheduler(0x7fffffff) == -1 && errno == ESRCH);
+}
+
+void test_sched_attr(void)
+{
+	struct sched_attr attr = {
+		.size = sizeof(attr),
+		.sched_policy = SCHED_RR,
+		.sched_priority = 5,
+	};
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == 0);
+	CHECK(sched_getscheduler(0) == SCHED_RR);
+
+	memset(&attr, 0xff, sizeof(attr));
+	CHECK(syscall(SYS_sched_getattr, 0, &attr, sizeof(attr), 0) == 0);
+	CHECK(attr.size == sizeof(attr));
+	CHECK(attr.sched_policy == SCHED_RR && attr.sched_priority == 5);
+
+	attr = (struct sched_attr){
+		.size = sizeof(attr),
+		.sched_policy = SCHED_OTHER,
+		.sched_nice = 5,
+	};
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == 0);
+	CHECK(syscall(SYS_sched_getattr, 0, &attr, sizeof(attr), 0) == 0);
+	CHECK(attr.sched_policy == SCHED_OTHER && attr.sched_nice == 5);
+
+	// The unknown trailing fields must be zero.
+	char buf[sizeof(attr) + 8] = { 0 };
+	memcpy(buf, &attr, sizeof(attr));
+	((struct sched_attr *)buf)->size = sizeof(buf);
+	buf[sizeof(attr)] = 1;
+	CHECK(syscall(SYS_sched_setattr, 0, buf, 0) == -1 && errno == E2BIG);
+	CHECK(((struct sched_attr *)buf)->size == sizeof(attr));
+
+	CHECK(syscall(SYS_sched_getattr, 0, &attr, 16, 0) == -1 &&
+	      errno == EINVAL);
+}
+
+#ifndef SCHED_DEADLINE
+#define SCHED_DEADLINE 6
+#endif
+
+void test_deadline(void)
+{
+	CHECK(sched_get_priority_max(SCHED_DEADLINE) == 0);
+	CHECK(sched_get_priority_min(SCHED_DEADLINE) == 0);
+
+	struct sched_attr attr = {
+		.size = sizeof(attr),
+		.sched_policy = SCHED_DEADLINE,
+		.sched_runtime = 10 * 1000 * 1000,
+		.sched_deadline = 30 * 1000 * 1000,
+		.sched_period = 100 * 1000 * 1000,
+	};
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == 0);
+	CHECK(sched_getscheduler(0) == SCHED_DEADLINE);
+
+	memset(&attr, 0, sizeof(attr));
+	CHECK(syscall(SYS_sched_getattr, 0, &attr, sizeof(attr), 0) == 0);
+	CHECK(attr.sched_policy == SCHED_DEADLINE);
+	CHECK(attr.sched_runtime == 10 * 1000 * 1000 &&
+	      attr.sched_deadline == 30 * 1000 * 1000 &&
+	      attr.sched_period == 100 * 1000 * 1000);
+
+	// The thread still runs under the bandwidth limit.
+	for (volatile int i = 0; i < 10 * 1000 * 1000; i++)
+		;
+
+	// A deadline thread cannot fork.
+	CHECK(fork() == -1 && errno == EAGAIN);
+
+	// The parameters must satisfy `runtime <= deadline <= period`.
+	attr.sched_runtime = 50 * 1000 * 1000;
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == -1 &&
+	      errno == EINVAL);
+	attr.sched_runtime = 0;
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == -1 &&
+	      errno == EINVAL);
+
+	// The bandwidth of a CPU cannot be exhausted.
+	attr.sched_runtime = attr.sched_deadline = attr.sched_period;
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == -1 &&
+	      errno == EBUSY);
+
+	// The deadline parameters cannot be set by `sched_setscheduler`.
+	struct sched_param param = { .sched_priority = 0 };
+	CHECK(sched_setscheduler(0, SCHED_DEADLINE, &param) == -1 &&
+	      errno == EINVAL);
+
+	set_policy(SCHED_OTHER, 0);
+}
+
+void test_permission(void)
+{
+	struct rlimit rlimit = { .rlim_cur = 10, .rlim_max = 10 };
+	CHECK(setrlimit(RLIMIT_RTPRIO, &rlimit) == 0);
+
+	// Drop all the capabilities.
+	struct __user_cap_header_struct header = {
+		.version = _LINUX_CAPABILITY_VERSION_3,
+		.pid = 0,
+	};
+	struct __user_cap_data_struct data[2];
+	memset(data, 0, sizeof(data));
+	CHECK(syscall(SYS_capset, &header, data) == 0);
+
+	set_policy(SCHED_FIFO, 10);
+	struct sched_param param = { .sched_priority = 11 };
+	CHECK(sched_setscheduler(0, SCHED_FIFO, &param) == -1 &&
+	      errno == EPERM);
+	set_policy(SCHED_FIFO, 5);
+	set_policy(SCHED_OTHER, 0);
+
+	rlimit.rlim_cur = 0;
+	CHECK(setrlimit(RLIMIT_RTPRIO, &rlimit) == 0);
+	param.sched_priority = 1;
+	CHECK(sched_setscheduler(0, SCHED_RR, &param) == -1 &&
+	      errno == EPERM);
+
+	struct sched_attr attr = {
+		.size = sizeof(attr),
+		.sched_policy = SCHED_DEADLINE,
+		.sched_runtime = 10 * 1000 * 1000,
+		.sched_deadline = 100 * 1000 * 1000,
+	};
+	CHECK(syscall(SYS_sched_setattr, 0, &attr, 0) == -1 &&
+	      errno == EPERM);
+}
+
+int main(void)
+{
+	printf("Test the priority range\n");
+	test_priority_range();
+
+	printf("Test setting the scheduling policies\n");
+	test_set_policy();
+
+	printf("Test invalid arguments\n");
+	test_invalid();
+
+	printf("Test sched_setattr and sched_getattr\n");
+	test_sched_attr();
+
+	printf("Test SCHED_DEADLINE\n");
+	test_deadline();
+
+	// The capabilities are dropped in a child process.
+	printf("Test the permission checks\n");
+	pid_t pid = fork();
+	if (pid == 0) {
+		test_permission();
+		exit(0);
+	}
+	int status;
+	CHECK(waitpid(pid, &status, 0) == pid);
+	CHECK(WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	printf("All scheduling policy tests passed\n");
+	return 0;
+}
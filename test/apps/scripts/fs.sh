@@ -53,6 +53,93 @@ test_fdatasync() {
     rm -f /exfat/test_fdatasync.txt
 }
 
+test_sysfs() {
+    cat /sys/devices/system/cpu/online
+    ls /sys/block /sys/class/block /sys/class/net /sys/class/input
+    cat /sys/block/vext2/size
+}
+
+test_page_cache_reclaim() {
+    mmap/page_cache_reclaim /ext2
+}
+
+test_swap() {
+    mmap/swap /ext2
+    grep -q "^SwapTotal:" /proc/meminfo
+}
+
+test_procfs() {
+    cat /proc/self/maps
+    grep -q "\[stack\]" /proc/self/maps
+    cat /proc/self/smaps > /dev/null
+    cat /proc/self/mountinfo
+    grep -q " /proc " /proc/mounts
+    grep -q "^cpu " /proc/stat
+    cat /proc/uptime
+    grep -q "^State:" /proc/self/status
+    cat /proc/self/stat
+}
+
+test_tmpfs() {
+    local tmpfs_dir="/tmp/tmpfs_test"
+
+    mkdir -p ${tmpfs_dir}
+    mount -t tmpfs -o size=1m,nr_inodes=16,mode=700 tmpfs ${tmpfs_dir}
+    grep -q " ${tmpfs_dir} tmpfs " /proc/mounts
+    df ${tmpfs_dir}
+
+    # Writing beyond the size limit should fail with ENOSPC
+    if dd if=/dev/zero of=${tmpfs_dir}/big_file bs=1K count=2048; then
+        echo "Error: The size limit of tmpfs is not enforced."
+        return 1
+    fi
+    rm -f ${tmpfs_dir}/big_file
+
+    # Remounting with a larger size should allow the write
+    mount -o remount,size=4m ${tmpfs_dir}
+    dd if=/dev/zero of=${tmpfs_dir}/big_file bs=1K count=2048
+    # The size cannot be shrunk below the usage
+    if mount -o remount,size=1m ${tmpfs_dir}; then
+        echo "Error: The tmpfs size is shrunk below its usage."
+        return 1
+    fi
+    rm -f ${tmpfs_dir}/big_file
+
+    # Remounting read-only should forbid creating files
+    mount -o remount,ro ${tmpfs_dir}
+    if touch ${tmpfs_dir}/new_file; then
+        echo "Error: A read-only mount is writable."
+        return 1
+    fi
+
+    umount ${tmpfs_dir}
+    rmdir ${tmpfs_dir}
+}
+
+test_mount_propagation() {
+    local shared_dir="/tmp/mount_shared"
+    local peer_dir="/tmp/mount_peer"
+
+    mkdir -p ${shared_dir} ${peer_dir}
+    mount -t tmpfs tmpfs ${shared_dir}
+    mount --make-shared ${shared_dir}
+    mount --bind ${shared_dir} ${peer_dir}
+    mkdir ${shared_dir}/ro
+
+    # The read-only mount is propagated to the peer with its flags
+    mount -t tmpfs -o ro tmpfs ${shared_dir}/ro
+    grep -q " ${peer_dir}/ro tmpfs ro" /proc/mounts
+    if touch ${peer_dir}/ro/new_file; then
+        echo "Error: The propagated read-only mount is writable."
+        return 1
+    fi
+
+    umount ${shared_dir}/ro
+    umount ${peer_dir}
+    umount ${shared_dir}
+    rmdir ${shared_dir} ${peer_dir}
+}
+
 echo "Start ext2 fs test......"
 test_ext2 "/ext2" "test_file.txt"
 echo "All ext2 fs test passed."
@@ -61,6 +148,30 @@ echo "Start fdatasync test......"
 test_fdatasync
 echo "All fdatasync test passed."
 
+echo "Start tmpfs test......"
+test_tmpfs
+echo "All tmpfs test passed."
+
+echo "Start mount propagation test......"
+test_mount_propagation
+echo "All mount propagation test passed."
+
+echo "Start page cache reclaim test......"
+test_page_cache_reclaim
+echo "All page cache reclaim test passed."
+
+echo "Start swap test......"
+test_swap
+echo "All swap test passed."
+
+echo "Start procfs test......"
+test_procfs
+echo "All procfs test passed."
+
+echo "Start sysfs test......"
+test_sysfs
+echo "All sysfs test passed."
+
 pipe/pipe_err
 pipe/short_rw
 epoll/epoll_err
@@ -10,10 +10,14 @@ cd ${SCRIPT_DIR}/..
 echo "Start process test......"
 # These test programs are sorted by name.
 tests="
+cgroup/cgroup
+clock_settime/clock_settime
 clone3/clone_exit_signal
 clone3/clone_no_exit_signal
 clone3/clone_process
+coredump/coredump
 cpu_affinity/cpu_affinity
+cpu_affinity/load_balance
 execve/execve
 exit/exit_code
 exit/exit_procfs
@@ -23,15 +27,28 @@ fork_c/fork
 getpid/getpid
 hello_pie/hello
 hello_world/hello_world
+itimer/cpu_timer
 itimer/setitimer
 itimer/timer_create
+mmap/ksm
+mmap/madvise
+mmap/mlock
 mmap/mmap_and_fork
+mmap/mmap_hugepage
 mmap/mmap_shared_filebacked
 mmap/mmap_readahead
+mmap/mremap
+mmap/oom_score_adj
+mmap/userfaultfd
+pidfd/pidfd
 pthread/pthread_test
 pty/open_pty
+rlimit/rlimit
+rusage/rusage
+sched/sched_policy
 shm/posix_shm
 signal_c/parent_death_signal
+signal_c/signal_fpu
 signal_c/signal_test
 "
 
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <signal.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+#ifndef SS_AUTODISARM
+#define SS_AUTODISARM (1U << 31)
+#endif
+
+#define CHECK(cond)                                                         \
+	do {                                                                \
+		if (!(cond)) {                                              \
+			printf("%s:%d: check `%s` failed: %s\n", __func__, \
+			       __LINE__, #cond, strerror(errno));           \
+			exit(1);                                            \
+		}                                                           \
+	} while (0)
+
+#define DEFAULT_MXCSR 0x1f80
+// Round toward zero, with all exceptions masked.
+#define TEST_MXCSR 0x7f80
+
+static volatile uint32_t handler_mxcsr;
+
+// Clobbers the vector registers and MXCSR, which must not leak back into the
+// interrupted code.
+static void clobber_fpu_handler(int signum)
+{
+	uint32_t mxcsr = 0;
+
+	asm volatile("stmxcsr %0" : "=m"(mxcsr));
+	handler_mxcsr = mxcsr;
+
+	mxcsr = DEFAULT_MXCSR | (1 << 13);
+	asm volatile("ldmxcsr %0" ::"m"(mxcsr));
+	if (__builtin_cpu_supports("avx")) {
+		asm volatile("vpcmpeqb %%ymm0, %%ymm0, %%ymm0\n\t"
+			     "vmovdqa %%ymm0, %%ymm8\n\t"
+			     "vmovdqa %%ymm0, %%ymm15\n\t" ::
+				     : "xmm0", "xmm8", "xmm15");
+	} else {
+		asm volatile("pcmpeqb %%xmm0, %%xmm0\n\t"
+			     "movdqa %%xmm0, %%xmm8\n\t"
+			     "movdqa %%xmm0, %%xmm15\n\t" ::
+				     : "xmm0", "xmm8", "xmm15");
+	}
+}
+
+// Loads a pattern into the vector registers and MXCSR, sends a signal to the
+// current process with a raw syscall, and saves the registers after the
+// handler returns.
+static void test_fpu_state_preserved(void)
+{
+	struct sigaction sa = { .sa_handler = clobber_fpu_handler };
+	uint8_t in[32], out_xmm8[32], out_xmm15[32];
+	uint32_t mxcsr_in = TEST_MXCSR, mxcsr_out = 0;
+	long ret;
+	int has_avx = __builtin_cpu_supports("avx");
+
+	for (int i = 0; i < 32; i++)
+		in[i] = i * 7 + 1;
+	memset(out_xmm8, 0, sizeof(out_xmm8));
+	memset(out_xmm15, 0, sizeof(out_xmm15));
+
+	sigemptyset(&sa.sa_mask);
+	CHECK(sigaction(SIGUSR1, &sa, NULL) == 0);
+
+	if (has_avx) {
+		asm volatile("vmovdqu %[in], %%ymm8\n\t"
+			     "vmovdqu %[in], %%ymm15\n\t"
+			     "ldmxcsr %[mxcsr_in]\n\t"
+			     "syscall\n\t"
+			     "stmxcsr %[mxcsr_out]\n\t"
+			     "vmovdqu %%ymm8, %[out8]\n\t"
+			     "vmovdqu %%ymm15, %[out15]\n\t"
+			     : "=a"(ret), [out8] "=m"(out_xmm8),
+			       [out15] "=m"(out_xmm15), [mxcsr_out] "=m"(mxcsr_out)
+			     : "a"(SYS_kill), "D"(getpid()), "S"(SIGUSR1),
+			       [in] "m"(in), [mxcsr_in] "m"(mxcsr_in)
+			     : "rcx", "r11", "xmm8", "xmm15", "memory");
+	} else {
+		asm volatile("movdqu %[in], %%xmm8\n\t"
+			     "movdqu %[in], %%xmm15\n\t"
+			     "ldmxcsr %[mxcsr_in]\n\t"
+			     "syscall\n\t"
+			     "stmxcsr %[mxcsr_out]\n\t"
+			     "movdqu %%xmm8, %[out8]\n\t"
+			     "movdqu %%xmm15, %[out15]\n\t"
+			     : "=a"(ret), [out8] "=m"(out_xmm8),
+			       [out15] "=m"(out_xmm15), [mxcsr_out] "=m"(mxcsr_out)
+			     : "a"(SYS_kill), "D"(getpid()), "S"(SIGUSR1),
+			       [in] "m"(in), [mxcsr_in] "m"(mxcsr_in)
+			     : "rcx", "r11", "xmm8", "xmm15", "memory");
+	}
+
+	// Restore the default MXCSR before the checks use floating point.
+	uint32_t mxcsr = DEFAULT_MXCSR;
+	asm volatile("ldmxcsr %0" ::"m"(mxcsr));
+
+	int len = has_avx ? 32 : 16;
+	CHECK(ret == 0);
+	CHECK(handler_mxcsr == DEFAULT_MXCSR);
+	CHECK(mxcsr_out == TEST_MXCSR);
+	CHECK(memcmp(in, out_xmm8, len) == 0);
+	CHECK(memcmp(in, out_xmm15, len) == 0);
+
+	sa.sa_handler = SIG_DFL;
+	CHECK(sigaction(SIGUSR1, &sa, NULL) == 0);
+}
+
+#define ALT_STACK_SIZE (4 * 4096)
+
+static char alt_stack[ALT_STACK_SIZE];
+static char new_alt_stack[ALT_STACK_SIZE];
+static volatile int handler_on_alt_stack;
+static stack_t handler_ss;
+static volatile int handler_set_ss_ret = -1;
+
+static void autodisarm_handler(int signum)
+{
+	char local;
+	stack_t new_ss = {
+		.ss_sp = new_alt_stack,
+		.ss_size = ALT_STACK_SIZE,
+		.ss_flags = 0,
+	};
+
+	handler_on_alt_stack = &local >= alt_stack &&
+			       &local < alt_stack + ALT_STACK_SIZE;
+	sigaltstack(NULL, &handler_ss);
+	// Installing another stack is allowed since the current one is disarmed.
+	handler_set_ss_ret = sigaltstack(&new_ss, NULL);
+}
+
+static void test_autodisarm(void)
+{
+	struct sigaction sa = {
+		.sa_handler = autodisarm_handler,
+		.sa_flags = SA_ONSTACK,
+	};
+	stack_t ss = {
+		.ss_sp = alt_stack,
+		.ss_size = ALT_STACK_SIZE,
+		.ss_flags = SS_AUTODISARM,
+	};
+
+	sigemptyset(&sa.sa_mask);
+	CHECK(sigaction(SIGUSR2, &sa, NULL) == 0);
+	CHECK(sigaltstack(&ss, NULL) == 0);
+
+	CHECK(raise(SIGUSR2) == 0);
+
+	CHECK(handler_on_alt_stack);
+	CHECK(handler_ss.ss_flags == SS_DISABLE);
+	CHECK(handler_ss.ss_sp == NULL);
+	CHECK(handler_ss.ss_size == 0);
+	CHECK(handler_set_ss_ret == 0);
+
+	// The stack is restored when the handler returns.
+	CHECK(sigaltstack(NULL, &ss) == 0);
+	CHECK(ss.ss_sp == alt_stack);
+	CHECK(ss.ss_size == ALT_STACK_SIZE);
+	CHECK(ss.ss_flags == (int)SS_AUTODISARM);
+
+	ss.ss_flags = SS_DISABLE;
+	CHECK(sigaltstack(&ss, NULL) == 0);
+	sa.sa_handler = SIG_DFL;
+	CHECK(sigaction(SIGUSR2, &sa, NULL) == 0);
+}
+
+int main(void)
+{
+	test_fpu_state_preserved();
+	test_autodisarm();
+
+	printf("All tests passed\n");
+	return 0;
+}